@@ -1,6 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use jsonrpc_core::serde::Deserialize;
+use num::rational::Ratio;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 
@@ -10,12 +11,24 @@ use std::path::PathBuf;
 pub struct APIConfig {
     /// when looking for next draw we want to look at max `draw_lookahead_period_count`
     pub draw_lookahead_period_count: u64,
+    /// number of past periods considered when diagnosing endorsement draws
+    pub endorsement_diagnostics_lookback_period_count: u64,
     /// bind for the private API
     pub bind_private: SocketAddr,
     /// bind for the public API
     pub bind_public: SocketAddr,
     /// max argument count
     pub max_arguments: u64,
+    /// max number of datastore keys returned per page
+    pub max_datastore_keys_per_page: u64,
+    /// max number of contracts returned per page
+    pub max_contracts_per_page: u64,
+    /// max number of deferred credits returned per page
+    pub max_deferred_credits_per_page: u64,
+    /// max number of address activities returned per page by `get_address_history`
+    pub max_address_activities_per_page: u64,
+    /// max number of stakers returned per page by `get_cycle_stats`
+    pub max_cycle_stakers_per_page: u64,
     /// openrpc specification path
     pub openrpc_spec_path: PathBuf,
     /// max datastore value length
@@ -30,4 +43,16 @@ pub struct APIConfig {
     pub max_function_name_length: u16,
     /// max parameter size
     pub max_parameter_size: u32,
+    /// max number of simultaneous subscriptions of a WebSocket connection
+    pub max_ws_subscriptions: usize,
+    /// the node runs in light mode: the endpoints reading the execution state or operations are unavailable
+    pub light_mode: bool,
+    /// block miss ratio above which the rolls of an address are deactivated
+    pub max_miss_ratio: Ratio<u64>,
+    /// chain id of the network, signed along the operations
+    pub chain_id: u64,
+    /// first period from which the operations are signed along the chain id
+    pub chain_id_activation_period: u64,
+    /// return addresses in the checksummed format instead of the base58 one in the version 2 responses
+    pub checksummed_addresses: bool,
 }
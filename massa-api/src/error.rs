@@ -3,9 +3,12 @@
 use displaydoc::Display;
 use massa_consensus_exports::error::ConsensusError;
 use massa_execution_exports::ExecutionError;
+use massa_factory_exports::FactoryError;
 use massa_hash::MassaHashError;
 use massa_models::error::ModelsError;
+use massa_models::operation::OperationId;
 use massa_network_exports::NetworkError;
+use massa_pool_exports::OperationRejection;
 use massa_protocol_exports::ProtocolError;
 use massa_time::TimeError;
 use massa_wallet::WalletError;
@@ -34,6 +37,8 @@ pub enum ApiError {
     TimeError(#[from] TimeError),
     /// Wallet error: {0}
     WalletError(#[from] WalletError),
+    /// Factory error: {0}
+    FactoryError(#[from] FactoryError),
     /// Not found
     NotFound,
     /// Inconsistency error: {0}
@@ -44,10 +49,14 @@ pub enum ApiError {
     MissingConfig(String),
     /// The wrong API (either Public or Private) was called
     WrongAPI,
+    /// Not available on a light node: it neither executes blocks nor stores operations
+    LightMode,
     /// Bad request: {0}
     BadRequest(String),
     /// Internal server error: {0}
     InternalServerError(String),
+    /// Operation {0} rejected by the pool: {1}
+    OperationRejected(OperationId, OperationRejection),
 }
 
 impl From<ApiError> for jsonrpc_core::Error {
@@ -71,11 +80,24 @@ impl From<ApiError> for jsonrpc_core::Error {
             ApiError::MissingCommandSender(_) => -32017,
             ApiError::MissingConfig(_) => -32018,
             ApiError::WrongAPI => -32019,
+            ApiError::LightMode => -32020,
+            ApiError::OperationRejected(..) => -32021,
+            ApiError::FactoryError(_) => -32022,
+        };
+        // machine-readable details for the errors that carry some
+        let data = match &err {
+            ApiError::OperationRejected(operation_id, reason) => {
+                Some(jsonrpc_core::serde_json::json!({
+                    "operation_id": operation_id,
+                    "reason": reason,
+                }))
+            }
+            _ => None,
         };
         jsonrpc_core::Error {
             code: jsonrpc_core::ErrorCode::ServerError(code),
             message: err.to_string(),
-            data: None,
+            data,
         }
     }
 }
@@ -5,15 +5,23 @@
 #![warn(unused_crate_dependencies)]
 use crate::error::ApiError::WrongAPI;
 use error::ApiError;
-use jsonrpc_core::{serde_json, BoxFuture, IoHandler, Value};
+use jsonrpc_core::{serde_json, BoxFuture, MetaIoHandler, Value};
 use jsonrpc_derive::rpc;
-use jsonrpc_http_server::{CloseHandle, ServerBuilder};
+use jsonrpc_http_server::{hyper, CloseHandle, ServerBuilder};
 use massa_consensus_exports::{ConsensusCommandSender, ConsensusConfig};
 use massa_execution_exports::ExecutionController;
+use massa_factory_exports::FactoryController;
+use massa_final_state::FinalState;
+use massa_ledger_exports::LedgerEntryProof;
 use massa_models::api::{
-    AddressInfo, BlockInfo, BlockSummary, DatastoreEntryInput, DatastoreEntryOutput,
-    EndorsementInfo, EventFilter, NodeStatus, OperationInfo, OperationInput,
-    ReadOnlyBytecodeExecution, ReadOnlyCall, TimeInterval,
+    AddressActivity, AddressDraws, AddressDrawsQuery, AddressHistoryQuery, AddressInfo,
+    AddressesPageInput, BlockInfo, BlockSummary, BlockTemplate, ContractsInput, ContractsOutput,
+    CycleStatsQuery, DatastoreEntryInput, DatastoreEntryOutput, DatastoreKeysInput,
+    DatastoreKeysOutput, DeferredCreditSchedule, DeferredCreditsInput, DeferredCreditsOutput,
+    EndorsementDiagnostic, EndorsementInfo, EventFilter, GraphDotQuery, LedgerProofInput,
+    NodeStatus, NodeSubsystem, OperationInfo, OperationInput, OperationPoolStatusInfo,
+    PeerScoreInfo, ReadOnlyBytecodeExecution, ReadOnlyCall, ReorgInfo, SlotExecutionTrace,
+    StakingKeyRotation, StakingKeyRotationInput, StakingReport, SubsystemAction, TimeInterval,
 };
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
@@ -22,10 +30,14 @@ use massa_models::node::NodeId;
 use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
+use massa_models::stats::{
+    BootstrapServerStats, CycleStats, DiskSpaceStatus, ExecutionPolicyStats, FeeRecommendation,
+    LedgerIntegrityReport, LedgerIntegrityStatus, NetworkActivityStats,
+};
 use massa_models::{
     address::Address,
     block::{Block, BlockId},
-    endorsement::EndorsementId,
+    endorsement::{EndorsementEquivocation, EndorsementId},
     slot::Slot,
     version::Version,
 };
@@ -34,22 +46,32 @@ use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::ProtocolCommandSender;
 use massa_storage::Storage;
+use massa_time::ClockCompensation;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 use tracing::{info, warn};
 
 mod config;
 mod error;
 mod private;
 mod public;
+mod request_tracing;
+mod subscriptions;
+pub mod v2;
+mod versioning;
 pub use config::APIConfig;
+use request_tracing::RequestTracer;
+pub use subscriptions::{SubscriptionEndpoints, Subscriptions};
+pub use versioning::API_V2_PATH;
+use versioning::{ApiVersion, VersionRouter};
 
 /// Public API component
+#[derive(Clone)]
 pub struct Public {
     /// link to the consensus component
     pub consensus_command_sender: ConsensusCommandSender,
@@ -73,10 +95,16 @@ pub struct Public {
     pub version: Version,
     /// link to the network component
     pub network_command_sender: NetworkCommandSender,
-    /// compensation milliseconds (used to sync time with bootstrap server)
-    pub compensation_millis: i64,
+    /// clock compensation (used to sync time with bootstrap server)
+    pub clock_compensation: ClockCompensation,
     /// our node id
     pub node_id: NodeId,
+    /// free disk space status, updated by the node's disk monitor
+    pub disk_status: Arc<RwLock<DiskSpaceStatus>>,
+    /// final ledger integrity status, updated by the node's ledger scrubber
+    pub ledger_integrity: Arc<RwLock<LedgerIntegrityStatus>>,
+    /// stats of the sessions served by the node's bootstrap server, none if it is disabled
+    pub bootstrap_server_stats: Option<Arc<RwLock<BootstrapServerStats>>>,
 }
 
 /// Private API content
@@ -87,16 +115,33 @@ pub struct Private {
     pub network_command_sender: NetworkCommandSender,
     /// link to the execution component
     pub execution_controller: Box<dyn ExecutionController>,
+    /// link to the factory component
+    pub factory_controller: Box<dyn FactoryController>,
+    /// link to the selector component
+    pub selector_controller: Box<dyn SelectorController>,
     /// consensus configuration (TODO: remove it, can be retrieved via an endpoint)
     pub consensus_config: ConsensusConfig,
     /// API settings
     pub api_settings: APIConfig,
-    /// stop channel
-    pub stop_node_channel: mpsc::Sender<()>,
+    /// stop channel, sending whether the stop is graceful
+    pub stop_node_channel: mpsc::Sender<bool>,
+    /// channel of the requests to act on individual subsystems of the node
+    pub subsystem_channel: mpsc::Sender<SubsystemCommand>,
     /// User wallet
     pub node_wallet: Arc<RwLock<Wallet>>,
+    /// final state, for ledger snapshots
+    pub final_state: Arc<RwLock<FinalState>>,
+    /// staking key rotations started since the node started, oldest first
+    pub staking_key_rotations: Arc<RwLock<Vec<StakingKeyRotation>>>,
 }
 
+/// Request to act on a subsystem of the node, along with the channel receiving its outcome
+pub type SubsystemCommand = (
+    NodeSubsystem,
+    SubsystemAction,
+    oneshot::Sender<Result<(), String>>,
+);
+
 /// The API wrapper
 pub struct API<T>(T);
 
@@ -106,15 +151,28 @@ pub trait RpcServer: Endpoints {
     fn serve(self, _: &SocketAddr) -> StopHandle;
 }
 
+/// Handler of the calls received by the HTTP servers,
+/// routed to the methods of the version of the API targeted by the path of their request
+type HttpIoHandler = MetaIoHandler<ApiVersion, (RequestTracer, VersionRouter)>;
+
+fn new_http_io_handler() -> HttpIoHandler {
+    MetaIoHandler::with_middleware((RequestTracer, VersionRouter::default()))
+}
+
 fn serve(api: impl Endpoints, url: &SocketAddr) -> StopHandle {
-    let mut io = IoHandler::new();
+    let mut io = new_http_io_handler();
     io.extend_with(api.to_delegate());
+    start_http_server(io, url)
+}
 
-    let server = ServerBuilder::new(io)
-        .event_loop_executor(tokio::runtime::Handle::current())
-        .max_request_body_size(50 * 1024 * 1024)
-        .start_http(url)
-        .expect("Unable to start RPC server");
+fn start_http_server(io: HttpIoHandler, url: &SocketAddr) -> StopHandle {
+    let server = ServerBuilder::with_meta_extractor(io, |request: &hyper::Request<hyper::Body>| {
+        ApiVersion::from_path(request.uri().path())
+    })
+    .event_loop_executor(tokio::runtime::Handle::current())
+    .max_request_body_size(50 * 1024 * 1024)
+    .start_http(url)
+    .expect("Unable to start RPC server");
 
     let close_handle = server.close_handle();
     let thread_builder = thread::Builder::new().name("rpc-server".into());
@@ -123,21 +181,32 @@ fn serve(api: impl Endpoints, url: &SocketAddr) -> StopHandle {
         .expect("failed to spawn thread : rpc-server");
 
     StopHandle {
-        close_handle,
+        close_handle: ServerCloseHandle::Http(close_handle),
         join_handle,
     }
 }
 
+/// Handle to close an API server
+enum ServerCloseHandle {
+    /// HTTP server of the public and private APIs
+    Http(CloseHandle),
+    /// WebSocket server of the subscriptions
+    Ws(jsonrpc_ws_server::CloseHandle),
+}
+
 /// Used to be able to stop the API
 pub struct StopHandle {
-    close_handle: CloseHandle,
+    close_handle: ServerCloseHandle,
     join_handle: JoinHandle<()>,
 }
 
 impl StopHandle {
     /// stop the API gracefully
     pub fn stop(self) {
-        self.close_handle.close();
+        match self.close_handle {
+            ServerCloseHandle::Http(close_handle) => close_handle.close(),
+            ServerCloseHandle::Ws(close_handle) => close_handle.close(),
+        }
         if let Err(err) = self.join_handle.join() {
             warn!("API thread panicked: {:?}", err);
         } else {
@@ -149,10 +218,15 @@ impl StopHandle {
 /// Exposed API endpoints
 #[rpc(server)]
 pub trait Endpoints {
-    /// Gracefully stop the node.
+    /// Stop the node.
     #[rpc(name = "stop_node")]
     fn stop_node(&self) -> BoxFuture<Result<(), ApiError>>;
 
+    /// Stop the node gracefully: stop accepting new blocks and operations, save the pool,
+    /// execute the pending final slots and flush the final ledger before exiting.
+    #[rpc(name = "stop_node_gracefully")]
+    fn stop_node_gracefully(&self) -> BoxFuture<Result<(), ApiError>>;
+
     /// Sign message with node's key.
     /// Returns the public key that signed the message and the signature.
     #[rpc(name = "node_sign_message")]
@@ -186,6 +260,58 @@ pub trait Endpoints {
     #[rpc(name = "get_staking_addresses")]
     fn get_staking_addresses(&self) -> BoxFuture<Result<PreHashSet<Address>, ApiError>>;
 
+    /// Sell rolls of a staking address of the node.
+    /// The address is then excluded from automatic roll compounding until the node restarts.
+    /// Returns the id of the sent roll sell operation.
+    #[rpc(name = "node_sell_staking_rolls")]
+    fn node_sell_staking_rolls(
+        &self,
+        _: Address,
+        _: u64,
+    ) -> BoxFuture<Result<OperationId, ApiError>>;
+
+    /// Stop, restart, pause or resume an individual subsystem of the node.
+    /// Returns once the action is done.
+    #[rpc(name = "node_manage_subsystem")]
+    fn node_manage_subsystem(
+        &self,
+        _: NodeSubsystem,
+        _: SubsystemAction,
+    ) -> BoxFuture<Result<(), ApiError>>;
+
+    /// Staking performance report of each staking address of the node:
+    /// produced and missed blocks, produced and expected endorsements and rewards per cycle,
+    /// along with the current miss ratio.
+    #[rpc(name = "get_staking_report")]
+    fn get_staking_report(&self) -> BoxFuture<Result<Vec<StakingReport>, ApiError>>;
+
+    /// Replace staking addresses of the node without restarting it:
+    /// the new secret keys are added to the staking keys right away,
+    /// and the old addresses are removed once the rolls of all the new addresses are active.
+    /// Returns the started rotation.
+    #[rpc(name = "node_rotate_staking_keys")]
+    fn node_rotate_staking_keys(
+        &self,
+        _: StakingKeyRotationInput,
+    ) -> BoxFuture<Result<StakingKeyRotation, ApiError>>;
+
+    /// Staking key rotations started since the node started, oldest first.
+    #[rpc(name = "get_staking_key_rotations")]
+    fn get_staking_key_rotations(&self) -> BoxFuture<Result<Vec<StakingKeyRotation>, ApiError>>;
+
+    /// Operations left out of the blocks produced by the node because of its local execution policy,
+    /// since the node started.
+    #[rpc(name = "get_execution_policy_stats")]
+    fn get_execution_policy_stats(&self) -> BoxFuture<Result<ExecutionPolicyStats, ApiError>>;
+
+    /// Debug tool: re-executes a slot that is not final yet in an isolated context
+    /// and returns the trace of the ABI calls and gas consumption of each of its execution steps.
+    #[rpc(name = "node_replay_slot_with_trace")]
+    fn node_replay_slot_with_trace(
+        &self,
+        _: Slot,
+    ) -> BoxFuture<Result<SlotExecutionTrace, ApiError>>;
+
     /// Bans given IP address(es).
     /// No confirmation to expect.
     #[rpc(name = "node_ban_by_ip")]
@@ -208,6 +334,24 @@ pub trait Endpoints {
     #[rpc(name = "node_remove_from_whitelist")]
     fn node_remove_from_whitelist(&self, _: Vec<IpAddr>) -> BoxFuture<Result<(), ApiError>>;
 
+    /// Returns the known peers along with their quality metrics and score, best scored first.
+    #[rpc(name = "node_get_peer_scores")]
+    fn node_get_peer_scores(&self) -> BoxFuture<Result<Vec<PeerScoreInfo>, ApiError>>;
+
+    /// Export a snapshot of the disk ledger to the given file path of the node host.
+    /// Returns the slot of the exported ledger.
+    #[rpc(name = "node_export_ledger_snapshot")]
+    fn node_export_ledger_snapshot(&self, _: String) -> BoxFuture<Result<Slot, ApiError>>;
+
+    /// Check the whole final ledger against its stored hash and Merkle tree, reporting the mismatched entries.
+    /// If the boolean is true and an inconsistency is found, the ledger hash and the Merkle tree are rebuilt from the entries.
+    /// The final state is locked during the check.
+    #[rpc(name = "node_verify_ledger_integrity")]
+    fn node_verify_ledger_integrity(
+        &self,
+        _: bool,
+    ) -> BoxFuture<Result<LedgerIntegrityReport, ApiError>>;
+
     /// Unban given IP address(es).
     /// No confirmation to expect.
     #[rpc(name = "node_unban_by_ip")]
@@ -226,10 +370,29 @@ pub trait Endpoints {
     #[rpc(name = "get_cliques")]
     fn get_cliques(&self) -> BoxFuture<Result<Vec<Clique>, ApiError>>;
 
+    /// Returns the latest blockclique re-organizations (depth, dropped and new blocks), oldest first.
+    #[rpc(name = "get_reorg_history")]
+    fn get_reorg_history(&self) -> BoxFuture<Result<Vec<ReorgInfo>, ApiError>>;
+
     /// Returns the active stakers and their active roll counts for the current cycle.
     #[rpc(name = "get_stakers")]
     fn get_stakers(&self) -> BoxFuture<Result<Vec<(Address, u64)>, ApiError>>;
 
+    /// Returns network-wide activity statistics (operations per second, fee percentiles, gas utilization, events)
+    /// over a given number of latest final slots.
+    #[rpc(name = "get_network_stats")]
+    fn get_network_stats(&self, _: u64) -> BoxFuture<Result<NetworkActivityStats, ApiError>>;
+
+    /// Returns the roll distribution and the block production statistics of the stakers for a given cycle
+    /// of the final proof-of-stake state, paginated by staker address.
+    #[rpc(name = "get_cycle_stats")]
+    fn get_cycle_stats(&self, _: CycleStatsQuery) -> BoxFuture<Result<CycleStats, ApiError>>;
+
+    /// Returns low, medium and high fee suggestions computed from the fill rate and the fees
+    /// of the latest final blocks, and from the number of operations waiting in the pool.
+    #[rpc(name = "get_fee_recommendation")]
+    fn get_fee_recommendation(&self) -> BoxFuture<Result<FeeRecommendation, ApiError>>;
+
     /// Returns operations information associated to a given list of operations' IDs.
     #[rpc(name = "get_operations")]
     fn get_operations(
@@ -253,12 +416,23 @@ pub trait Endpoints {
     #[rpc(name = "get_blockclique_block_by_slot")]
     fn get_blockclique_block_by_slot(&self, _: Slot) -> BoxFuture<Result<Option<Block>, ApiError>>;
 
+    /// Get the candidate block the node would produce at a slot (parents, endorsements, operations,
+    /// estimated gas and fees). The block is neither signed nor broadcast.
+    #[rpc(name = "get_block_template")]
+    fn get_block_template(&self, _: Slot) -> BoxFuture<Result<BlockTemplate, ApiError>>;
+
     /// Get the block graph within the specified time interval.
     /// Optional parameters: from `<time_start>` (included) and to `<time_end>` (excluded) millisecond timestamp
     #[rpc(name = "get_graph_interval")]
     fn get_graph_interval(&self, _: TimeInterval)
         -> BoxFuture<Result<Vec<BlockSummary>, ApiError>>;
 
+    /// Get a rendering of the block graph in the DOT language of graphviz, between optional slots
+    /// (start included, end excluded), with the clique membership, the finality
+    /// and the discard status of the blocks.
+    #[rpc(name = "get_graph_dot")]
+    fn get_graph_dot(&self, _: GraphDotQuery) -> BoxFuture<Result<String, ApiError>>;
+
     /// Get multiple datastore entries.
     #[rpc(name = "get_datastore_entries")]
     fn get_datastore_entries(
@@ -266,10 +440,97 @@ pub trait Endpoints {
         _: Vec<DatastoreEntryInput>,
     ) -> BoxFuture<Result<Vec<DatastoreEntryOutput>, ApiError>>;
 
+    /// Get a page of the datastore keys of an address, optionally filtered by prefix.
+    #[rpc(name = "get_datastore_keys")]
+    fn get_datastore_keys(
+        &self,
+        _: DatastoreKeysInput,
+    ) -> BoxFuture<Result<DatastoreKeysOutput, ApiError>>;
+
+    /// Get a page of the final addresses holding a non-empty bytecode.
+    #[rpc(name = "get_contracts")]
+    fn get_contracts(&self, _: ContractsInput) -> BoxFuture<Result<ContractsOutput, ApiError>>;
+
+    /// Get a page of the deferred credits of all addresses scheduled in a slot range.
+    #[rpc(name = "get_deferred_credits")]
+    fn get_deferred_credits(
+        &self,
+        _: DeferredCreditsInput,
+    ) -> BoxFuture<Result<DeferredCreditsOutput, ApiError>>;
+
+    /// Get the deferred credits of each given address, upcoming and already paid, sorted by slot,
+    /// with the roll sell operations they reimburse. Paid credits and sell operations are taken
+    /// from the remembered address activities: the oldest ones are missing.
+    #[rpc(name = "get_deferred_credit_schedule")]
+    fn get_deferred_credit_schedule(
+        &self,
+        _: Vec<Address>,
+    ) -> BoxFuture<Result<Vec<DeferredCreditSchedule>, ApiError>>;
+
+    /// Get a proof of inclusion of a final balance or datastore entry in the final ledger.
+    #[rpc(name = "get_ledger_entry_proof")]
+    fn get_ledger_entry_proof(
+        &self,
+        _: LedgerProofInput,
+    ) -> BoxFuture<Result<Option<LedgerEntryProof>, ApiError>>;
+
     /// Get addresses.
     #[rpc(name = "get_addresses")]
     fn get_addresses(&self, _: Vec<Address>) -> BoxFuture<Result<Vec<AddressInfo>, ApiError>>;
 
+    /// Get addresses with bounded datastore keys and cycle infos, and only the selected fields.
+    /// The datastore keys are paginated: query the next page with the returned `next_datastore_keys_last_key`.
+    #[rpc(name = "get_addresses_page")]
+    fn get_addresses_page(
+        &self,
+        _: AddressesPageInput,
+    ) -> BoxFuture<Result<Vec<AddressInfo>, ApiError>>;
+
+    /// Resolve names (`name.massa`) to addresses through the name registry contract
+    /// configured on the node. Unknown names resolve to `null`.
+    #[rpc(name = "resolve_names")]
+    fn resolve_names(&self, _: Vec<String>) -> BoxFuture<Result<Vec<Option<Address>>, ApiError>>;
+
+    /// For each given address, list the endorsement draws of the recent slots
+    /// and whether the corresponding endorsements were produced and included in blocks.
+    #[rpc(name = "get_endorsement_diagnostics")]
+    fn get_endorsement_diagnostics(
+        &self,
+        _: Vec<Address>,
+    ) -> BoxFuture<Result<Vec<EndorsementDiagnostic>, ApiError>>;
+
+    /// Get the block and endorsement draws of addresses over a slot range,
+    /// as far as the draws are already known.
+    #[rpc(name = "get_address_draws")]
+    fn get_address_draws(
+        &self,
+        _: AddressDrawsQuery,
+    ) -> BoxFuture<Result<Vec<AddressDraws>, ApiError>>;
+
+    /// Get the activity history of an address over a slot range, in chronological order:
+    /// produced blocks, executed operations it created, operations crediting it, roll sales and paid deferred credits.
+    #[rpc(name = "get_address_history")]
+    fn get_address_history(
+        &self,
+        _: AddressHistoryQuery,
+    ) -> BoxFuture<Result<Vec<AddressActivity>, ApiError>>;
+
+    /// Get the conflicting endorsements recently created by the given addresses.
+    /// All the retained records are returned if no address is given.
+    #[rpc(name = "get_endorsement_equivocations")]
+    fn get_endorsement_equivocations(
+        &self,
+        _: Vec<Address>,
+    ) -> BoxFuture<Result<Vec<EndorsementEquivocation>, ApiError>>;
+
+    /// Get the status of operations in the pool: pending, parked, or evicted with the reason of the eviction.
+    /// Evictions are only remembered for a bounded number of recent operations.
+    #[rpc(name = "get_operation_pool_status")]
+    fn get_operation_pool_status(
+        &self,
+        _: Vec<OperationId>,
+    ) -> BoxFuture<Result<Vec<OperationPoolStatusInfo>, ApiError>>;
+
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
     #[rpc(name = "send_operations")]
     fn send_operations(
@@ -299,6 +560,11 @@ fn wrong_api<T>() -> BoxFuture<Result<T, ApiError>> {
     Box::pin(closure())
 }
 
+fn light_mode_unavailable<T>() -> BoxFuture<Result<T, ApiError>> {
+    let closure = async move || Err(ApiError::LightMode);
+    Box::pin(closure())
+}
+
 fn _jsonrpc_assert(_method: &str, _request: Value, _response: Value) {
     // TODO: jsonrpc_client_transports::RawClient::call_method ... see #1182
 }
@@ -2,17 +2,27 @@
 
 use crate::config::APIConfig;
 use crate::error::ApiError;
-use crate::{Endpoints, Private, RpcServer, StopHandle, Value, API};
+use crate::{Endpoints, Private, RpcServer, StopHandle, SubsystemCommand, Value, API};
 
 use jsonrpc_core::BoxFuture;
-use jsonrpc_http_server::tokio::sync::mpsc;
+use jsonrpc_http_server::tokio::sync::{mpsc, oneshot};
 
 use massa_consensus_exports::{ConsensusCommandSender, ConsensusConfig};
-use massa_execution_exports::ExecutionController;
+use massa_execution_exports::{AddressInfoBounds, ExecutionController};
+use massa_factory_exports::FactoryController;
+use massa_final_state::FinalState;
+use massa_ledger_exports::{LedgerController, LedgerEntryProof};
 use massa_models::api::{
-    AddressInfo, BlockInfo, BlockSummary, DatastoreEntryInput, DatastoreEntryOutput,
-    EndorsementInfo, EventFilter, NodeStatus, OperationInfo, OperationInput,
-    ReadOnlyBytecodeExecution, ReadOnlyCall, TimeInterval,
+    AddressActivity, AddressActivityKind, AddressDraws, AddressDrawsQuery, AddressHistoryQuery,
+    AddressInfo, AddressesPageInput, BlockInfo, BlockSummary, BlockTemplate, ContractsInput,
+    ContractsOutput, CycleStatsQuery, DatastoreEntryInput, DatastoreEntryOutput,
+    DatastoreKeysInput, DatastoreKeysOutput, DeferredCreditSchedule, DeferredCreditsInput,
+    DeferredCreditsOutput, EndorsementDiagnostic, EndorsementInfo, EventFilter, GraphDotQuery,
+    LedgerProofInput, NodeStatus, NodeSubsystem, OperationInfo, OperationInput,
+    OperationPoolStatusInfo, PeerScoreInfo, ReadOnlyBytecodeExecution, ReadOnlyCall, ReorgInfo,
+    RewardRole, SlotExecutionTrace, StakingCycleReport, StakingKeyRotation,
+    StakingKeyRotationInput, StakingKeyRotationStatus, StakingReport, SubsystemAction,
+    TimeInterval,
 };
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
@@ -20,19 +30,29 @@ use massa_models::execution::ExecuteReadOnlyResponse;
 use massa_models::node::NodeId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
+use massa_models::stats::{
+    CycleStats, ExecutionPolicyStats, FeeRecommendation, LedgerIntegrityReport,
+    NetworkActivityStats,
+};
 use massa_models::{
     address::Address,
+    amount::Amount,
     block::{Block, BlockId},
-    endorsement::EndorsementId,
+    endorsement::{EndorsementEquivocation, EndorsementId},
     operation::OperationId,
     slot::Slot,
 };
 use massa_network_exports::NetworkCommandSender;
+use massa_pos_exports::SelectorController;
 use massa_signature::KeyPair;
+use massa_time::MassaTime;
 use massa_wallet::Wallet;
 
+use num::rational::Ratio;
 use parking_lot::RwLock;
+use std::collections::BTreeMap;
 use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
 
@@ -42,22 +62,32 @@ impl API<Private> {
         consensus_command_sender: ConsensusCommandSender,
         network_command_sender: NetworkCommandSender,
         execution_controller: Box<dyn ExecutionController>,
+        factory_controller: Box<dyn FactoryController>,
+        selector_controller: Box<dyn SelectorController>,
         api_settings: APIConfig,
         consensus_settings: ConsensusConfig,
         node_wallet: Arc<RwLock<Wallet>>,
-    ) -> (Self, mpsc::Receiver<()>) {
+        final_state: Arc<RwLock<FinalState>>,
+    ) -> (Self, mpsc::Receiver<bool>, mpsc::Receiver<SubsystemCommand>) {
         let (stop_node_channel, rx) = mpsc::channel(1);
+        let (subsystem_channel, subsystem_rx) = mpsc::channel(1);
         (
             API(Private {
                 consensus_command_sender,
                 network_command_sender,
                 execution_controller,
+                factory_controller,
+                selector_controller,
                 consensus_config: consensus_settings,
                 api_settings,
                 stop_node_channel,
+                subsystem_channel,
                 node_wallet,
+                final_state,
+                staking_key_rotations: Default::default(),
             }),
             rx,
+            subsystem_rx,
         )
     }
 }
@@ -73,7 +103,7 @@ impl Endpoints for API<Private> {
     fn stop_node(&self) -> BoxFuture<Result<(), ApiError>> {
         let stop = self.0.stop_node_channel.clone();
         let closure = async move || {
-            stop.send(()).await.map_err(|e| {
+            stop.send(false).await.map_err(|e| {
                 ApiError::SendChannelError(format!("error sending stop signal {}", e))
             })?;
             Ok(())
@@ -81,6 +111,17 @@ impl Endpoints for API<Private> {
         Box::pin(closure())
     }
 
+    fn stop_node_gracefully(&self) -> BoxFuture<Result<(), ApiError>> {
+        let stop = self.0.stop_node_channel.clone();
+        let closure = async move || {
+            stop.send(true).await.map_err(|e| {
+                ApiError::SendChannelError(format!("error sending graceful stop signal {}", e))
+            })?;
+            Ok(())
+        };
+        Box::pin(closure())
+    }
+
     fn node_sign_message(&self, message: Vec<u8>) -> BoxFuture<Result<PubkeySig, ApiError>> {
         let network_command_sender = self.0.network_command_sender.clone();
         let closure = async move || Ok(network_command_sender.node_sign_message(message).await?);
@@ -134,6 +175,285 @@ impl Endpoints for API<Private> {
         Box::pin(closure())
     }
 
+    fn node_sell_staking_rolls(
+        &self,
+        address: Address,
+        roll_count: u64,
+    ) -> BoxFuture<Result<OperationId, ApiError>> {
+        let factory_controller = self.0.factory_controller.clone();
+        let closure = async move || Ok(factory_controller.sell_rolls(address, roll_count)?);
+        Box::pin(closure())
+    }
+
+    fn node_manage_subsystem(
+        &self,
+        subsystem: NodeSubsystem,
+        action: SubsystemAction,
+    ) -> BoxFuture<Result<(), ApiError>> {
+        let subsystem_channel = self.0.subsystem_channel.clone();
+        let closure = async move || {
+            let (response_tx, response_rx) = oneshot::channel();
+            subsystem_channel
+                .send((subsystem, action, response_tx))
+                .await
+                .map_err(|e| {
+                    ApiError::SendChannelError(format!("error sending subsystem command {}", e))
+                })?;
+            response_rx
+                .await
+                .map_err(|e| {
+                    ApiError::ReceiveChannelError(format!(
+                        "error receiving subsystem command outcome {}",
+                        e
+                    ))
+                })?
+                .map_err(ApiError::BadRequest)
+        };
+        Box::pin(closure())
+    }
+
+    fn get_staking_report(&self) -> BoxFuture<Result<Vec<StakingReport>, ApiError>> {
+        let periods_per_cycle = self.0.consensus_config.periods_per_cycle;
+        let thread_count = self.0.consensus_config.thread_count;
+        let max_miss_ratio = self.0.api_settings.max_miss_ratio;
+
+        let mut addresses: Vec<Address> = self
+            .0
+            .node_wallet
+            .read()
+            .get_wallet_address_list()
+            .into_iter()
+            .collect();
+        addresses.sort_unstable();
+
+        // endorsements are only expected up to the latest executed slot
+        let draws_end = self
+            .0
+            .execution_controller
+            .get_stats()
+            .active_cursor
+            .get_next_slot(thread_count)
+            .unwrap_or_else(|_| Slot::max());
+        let get_expected_endorsements = |address: &Address, cycle: u64| -> Option<u64> {
+            let start = Slot::new_first_of_cycle(cycle, periods_per_cycle).ok()?;
+            // the selector forgets the draws of old cycles
+            self.0.selector_controller.get_selection(start).ok()?;
+            let end = Slot::new_first_of_cycle(cycle.saturating_add(1), periods_per_cycle)
+                .map_or(draws_end, |next_cycle_start| {
+                    next_cycle_start.min(draws_end)
+                });
+            self.0
+                .selector_controller
+                .get_address_draws(address, start, end)
+                .map(|draws| draws.endorsement_draws.len() as u64)
+                .ok()
+        };
+
+        let addresses_infos = self.0.execution_controller.get_addresses_infos(
+            &addresses,
+            &AddressInfoBounds {
+                max_datastore_keys: 0,
+                ..Default::default()
+            },
+        );
+        let res = addresses
+            .into_iter()
+            .zip(addresses_infos)
+            .map(|(address, info)| {
+                // rewards and included endorsements of each cycle, among the remembered activities
+                let mut cycle_rewards: BTreeMap<u64, (Amount, u64)> = BTreeMap::new();
+                let mut total_rewards = Amount::zero();
+                for activity in self
+                    .0
+                    .execution_controller
+                    .get_address_history(&address, None, None)
+                {
+                    if let AddressActivityKind::BlockReward { role, amount, .. } = activity.kind {
+                        let (rewards, endorsements) = cycle_rewards
+                            .entry(activity.slot.get_cycle(periods_per_cycle))
+                            .or_default();
+                        *rewards = rewards.saturating_add(amount);
+                        if role == RewardRole::EndorsementCreator {
+                            *endorsements += 1;
+                        }
+                        total_rewards = total_rewards.saturating_add(amount);
+                    }
+                }
+
+                let cycles = info
+                    .cycle_infos
+                    .iter()
+                    .map(|cycle_info| {
+                        let (rewards, produced_endorsements) = cycle_rewards
+                            .get(&cycle_info.cycle)
+                            .copied()
+                            .unwrap_or_default();
+                        StakingCycleReport {
+                            cycle: cycle_info.cycle,
+                            is_final: cycle_info.is_final,
+                            active_rolls: cycle_info.active_rolls,
+                            produced_blocks: cycle_info.ok_count,
+                            missed_blocks: cycle_info.nok_count,
+                            produced_endorsements,
+                            expected_endorsements: get_expected_endorsements(
+                                &address,
+                                cycle_info.cycle,
+                            ),
+                            rewards,
+                        }
+                    })
+                    .collect();
+
+                // miss ratio of the latest cycle the address was drawn in
+                let (ok_count, nok_count) = info
+                    .cycle_infos
+                    .iter()
+                    .rev()
+                    .map(|cycle_info| (cycle_info.ok_count, cycle_info.nok_count))
+                    .find(|(ok_count, nok_count)| ok_count.saturating_add(*nok_count) > 0)
+                    .unwrap_or_default();
+                let opportunities = ok_count.saturating_add(nok_count);
+                let (miss_ratio, above_max_miss_ratio) = if opportunities == 0 {
+                    (0.0, false)
+                } else {
+                    (
+                        nok_count as f64 / opportunities as f64,
+                        Ratio::new(nok_count, opportunities) > max_miss_ratio,
+                    )
+                };
+
+                StakingReport {
+                    address,
+                    cycles,
+                    total_rewards,
+                    miss_ratio,
+                    max_miss_ratio: *max_miss_ratio.numer() as f64 / *max_miss_ratio.denom() as f64,
+                    above_max_miss_ratio,
+                }
+            })
+            .collect();
+
+        let closure = async move || Ok(res);
+        Box::pin(closure())
+    }
+
+    fn node_rotate_staking_keys(
+        &self,
+        input: StakingKeyRotationInput,
+    ) -> BoxFuture<Result<StakingKeyRotation, ApiError>> {
+        let keypairs: Vec<KeyPair> = match input
+            .new_secret_keys
+            .iter()
+            .map(|x| KeyPair::from_str(x))
+            .collect()
+        {
+            Ok(keypairs) => keypairs,
+            Err(e) => {
+                let closure = async move || Err(ApiError::BadRequest(e.to_string()));
+                return Box::pin(closure());
+            }
+        };
+        let new_addresses: Vec<Address> = keypairs
+            .iter()
+            .map(|keypair| Address::from_public_key(&keypair.get_public_key()))
+            .collect();
+        let old_addresses = input.old_addresses;
+        if new_addresses.is_empty() || old_addresses.is_empty() {
+            let closure = async move || {
+                Err(ApiError::BadRequest(
+                    "a rotation needs new secret keys and old addresses".into(),
+                ))
+            };
+            return Box::pin(closure());
+        }
+        if new_addresses
+            .iter()
+            .any(|address| old_addresses.contains(address))
+        {
+            let closure = async move || {
+                Err(ApiError::BadRequest(
+                    "an address cannot be both added and retired".into(),
+                ))
+            };
+            return Box::pin(closure());
+        }
+
+        let periods_per_cycle = self.0.consensus_config.periods_per_cycle;
+        let check_interval = self.0.consensus_config.t0;
+        let execution_controller = self.0.execution_controller.clone();
+        let node_wallet = self.0.node_wallet.clone();
+        let rotations = self.0.staking_key_rotations.clone();
+        let closure = async move || {
+            let start_cycle = execution_controller
+                .get_stats()
+                .active_cursor
+                .get_cycle(periods_per_cycle);
+            let rotation = {
+                // both locks are held until the rotation is recorded,
+                // so that concurrent rotations cannot involve the same addresses
+                let mut w_wallet = node_wallet.write();
+                let mut w_rotations = rotations.write();
+                let staking_addresses = w_wallet.get_wallet_address_list();
+                if let Some(address) = old_addresses
+                    .iter()
+                    .find(|address| !staking_addresses.contains(address))
+                {
+                    return Err(ApiError::BadRequest(format!(
+                        "{} is not a staking address",
+                        address
+                    )));
+                }
+                if w_rotations.iter().any(|rotation| {
+                    rotation.status == StakingKeyRotationStatus::WaitingForActiveRolls
+                        && rotation
+                            .new_addresses
+                            .iter()
+                            .chain(rotation.old_addresses.iter())
+                            .any(|address| {
+                                new_addresses.contains(address) || old_addresses.contains(address)
+                            })
+                }) {
+                    return Err(ApiError::BadRequest(
+                        "some addresses are part of a rotation in progress".into(),
+                    ));
+                }
+                w_wallet.add_keypairs(keypairs)?;
+                let rotation = StakingKeyRotation {
+                    id: w_rotations.len() as u64,
+                    new_addresses,
+                    old_addresses,
+                    start_cycle,
+                    last_checked_cycle: start_cycle,
+                    status: StakingKeyRotationStatus::WaitingForActiveRolls,
+                };
+                w_rotations.push(rotation.clone());
+                rotation
+            };
+            tokio::spawn(drive_staking_key_rotation(
+                rotation.id as usize,
+                check_interval,
+                periods_per_cycle,
+                execution_controller,
+                node_wallet,
+                rotations,
+            ));
+            Ok(rotation)
+        };
+        Box::pin(closure())
+    }
+
+    fn get_staking_key_rotations(&self) -> BoxFuture<Result<Vec<StakingKeyRotation>, ApiError>> {
+        let rotations = self.0.staking_key_rotations.read().clone();
+        let closure = async move || Ok(rotations);
+        Box::pin(closure())
+    }
+
+    fn get_execution_policy_stats(&self) -> BoxFuture<Result<ExecutionPolicyStats, ApiError>> {
+        let stats = self.0.factory_controller.get_execution_policy_stats();
+        let closure = async move || Ok(stats);
+        Box::pin(closure())
+    }
+
     fn node_ban_by_ip(&self, ips: Vec<IpAddr>) -> BoxFuture<Result<(), ApiError>> {
         let network_command_sender = self.0.network_command_sender.clone();
         let closure = async move || Ok(network_command_sender.node_ban_by_ips(ips).await?);
@@ -166,10 +486,26 @@ impl Endpoints for API<Private> {
         crate::wrong_api::<Vec<Clique>>()
     }
 
+    fn get_reorg_history(&self) -> BoxFuture<Result<Vec<ReorgInfo>, ApiError>> {
+        crate::wrong_api::<Vec<ReorgInfo>>()
+    }
+
     fn get_stakers(&self) -> BoxFuture<Result<Vec<(Address, u64)>, ApiError>> {
         crate::wrong_api::<Vec<(Address, u64)>>()
     }
 
+    fn get_network_stats(&self, _: u64) -> BoxFuture<Result<NetworkActivityStats, ApiError>> {
+        crate::wrong_api::<NetworkActivityStats>()
+    }
+
+    fn get_cycle_stats(&self, _: CycleStatsQuery) -> BoxFuture<Result<CycleStats, ApiError>> {
+        crate::wrong_api::<CycleStats>()
+    }
+
+    fn get_fee_recommendation(&self) -> BoxFuture<Result<FeeRecommendation, ApiError>> {
+        crate::wrong_api::<FeeRecommendation>()
+    }
+
     fn get_operations(
         &self,
         _: Vec<OperationId>,
@@ -192,6 +528,10 @@ impl Endpoints for API<Private> {
         crate::wrong_api::<Option<Block>>()
     }
 
+    fn get_block_template(&self, _: Slot) -> BoxFuture<Result<BlockTemplate, ApiError>> {
+        crate::wrong_api::<BlockTemplate>()
+    }
+
     fn get_graph_interval(
         &self,
         _: TimeInterval,
@@ -199,6 +539,10 @@ impl Endpoints for API<Private> {
         crate::wrong_api::<Vec<BlockSummary>>()
     }
 
+    fn get_graph_dot(&self, _: GraphDotQuery) -> BoxFuture<Result<String, ApiError>> {
+        crate::wrong_api::<String>()
+    }
+
     fn get_datastore_entries(
         &self,
         _: Vec<DatastoreEntryInput>,
@@ -206,10 +550,88 @@ impl Endpoints for API<Private> {
         crate::wrong_api()
     }
 
+    fn get_datastore_keys(
+        &self,
+        _: DatastoreKeysInput,
+    ) -> BoxFuture<Result<DatastoreKeysOutput, ApiError>> {
+        crate::wrong_api()
+    }
+
+    fn get_contracts(&self, _: ContractsInput) -> BoxFuture<Result<ContractsOutput, ApiError>> {
+        crate::wrong_api()
+    }
+
+    fn get_deferred_credits(
+        &self,
+        _: DeferredCreditsInput,
+    ) -> BoxFuture<Result<DeferredCreditsOutput, ApiError>> {
+        crate::wrong_api::<DeferredCreditsOutput>()
+    }
+
+    fn get_deferred_credit_schedule(
+        &self,
+        _: Vec<Address>,
+    ) -> BoxFuture<Result<Vec<DeferredCreditSchedule>, ApiError>> {
+        crate::wrong_api::<Vec<DeferredCreditSchedule>>()
+    }
+
+    fn get_ledger_entry_proof(
+        &self,
+        _: LedgerProofInput,
+    ) -> BoxFuture<Result<Option<LedgerEntryProof>, ApiError>> {
+        crate::wrong_api()
+    }
+
     fn get_addresses(&self, _: Vec<Address>) -> BoxFuture<Result<Vec<AddressInfo>, ApiError>> {
         crate::wrong_api::<Vec<AddressInfo>>()
     }
 
+    fn get_addresses_page(
+        &self,
+        _: AddressesPageInput,
+    ) -> BoxFuture<Result<Vec<AddressInfo>, ApiError>> {
+        crate::wrong_api::<Vec<AddressInfo>>()
+    }
+
+    fn resolve_names(&self, _: Vec<String>) -> BoxFuture<Result<Vec<Option<Address>>, ApiError>> {
+        crate::wrong_api::<Vec<Option<Address>>>()
+    }
+
+    fn get_endorsement_diagnostics(
+        &self,
+        _: Vec<Address>,
+    ) -> BoxFuture<Result<Vec<EndorsementDiagnostic>, ApiError>> {
+        crate::wrong_api::<Vec<EndorsementDiagnostic>>()
+    }
+
+    fn get_address_draws(
+        &self,
+        _: AddressDrawsQuery,
+    ) -> BoxFuture<Result<Vec<AddressDraws>, ApiError>> {
+        crate::wrong_api::<Vec<AddressDraws>>()
+    }
+
+    fn get_address_history(
+        &self,
+        _: AddressHistoryQuery,
+    ) -> BoxFuture<Result<Vec<AddressActivity>, ApiError>> {
+        crate::wrong_api::<Vec<AddressActivity>>()
+    }
+
+    fn get_endorsement_equivocations(
+        &self,
+        _: Vec<Address>,
+    ) -> BoxFuture<Result<Vec<EndorsementEquivocation>, ApiError>> {
+        crate::wrong_api::<Vec<EndorsementEquivocation>>()
+    }
+
+    fn get_operation_pool_status(
+        &self,
+        _: Vec<OperationId>,
+    ) -> BoxFuture<Result<Vec<OperationPoolStatusInfo>, ApiError>> {
+        crate::wrong_api::<Vec<OperationPoolStatusInfo>>()
+    }
+
     fn send_operations(
         &self,
         _: Vec<OperationInput>,
@@ -236,7 +658,146 @@ impl Endpoints for API<Private> {
         Box::pin(closure())
     }
 
+    fn node_get_peer_scores(&self) -> BoxFuture<Result<Vec<PeerScoreInfo>, ApiError>> {
+        let network_command_sender = self.0.network_command_sender.clone();
+        let closure = async move || {
+            let mut peer_scores: Vec<PeerScoreInfo> = network_command_sender
+                .get_peers()
+                .await?
+                .peers
+                .into_values()
+                .map(|peer| {
+                    let info = peer.peer_info;
+                    PeerScoreInfo {
+                        ip: info.ip,
+                        peer_type: format!("{:?}", info.peer_type),
+                        banned: info.banned,
+                        connected: !peer.active_nodes.is_empty(),
+                        uptime: info.quality.uptime,
+                        successful_connections: info.quality.successful_connections,
+                        handshake_failures: info.quality.handshake_failures,
+                        bans: info.quality.bans,
+                        score: info.quality.score(),
+                    }
+                })
+                .collect();
+            peer_scores.sort_unstable_by(|a, b| b.score.total_cmp(&a.score));
+            Ok(peer_scores)
+        };
+        Box::pin(closure())
+    }
+
+    fn node_export_ledger_snapshot(&self, path: String) -> BoxFuture<Result<Slot, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
+        let final_state = self.0.final_state.clone();
+        let closure = async move || {
+            // the export reads the whole disk ledger, keep it out of the async runtime
+            tokio::task::spawn_blocking(move || {
+                // only the snapshot is taken under the lock, the file is written without it
+                let snapshot_writer = final_state.read().ledger.export_snapshot(Path::new(&path));
+                snapshot_writer
+                    .and_then(|write_snapshot| write_snapshot())
+                    .map_err(|e| ApiError::InternalServerError(e.to_string()))
+            })
+            .await
+            .map_err(|e| ApiError::InternalServerError(e.to_string()))?
+        };
+        Box::pin(closure())
+    }
+
+    fn node_verify_ledger_integrity(
+        &self,
+        repair: bool,
+    ) -> BoxFuture<Result<LedgerIntegrityReport, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
+        let final_state = self.0.final_state.clone();
+        let closure = async move || {
+            // the check reads the whole disk ledger, keep it out of the async runtime
+            tokio::task::spawn_blocking(move || {
+                final_state.write().ledger.verify_ledger_integrity(repair)
+            })
+            .await
+            .map_err(|e| ApiError::InternalServerError(e.to_string()))
+        };
+        Box::pin(closure())
+    }
+
+    fn node_replay_slot_with_trace(
+        &self,
+        slot: Slot,
+    ) -> BoxFuture<Result<SlotExecutionTrace, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
+        let execution_controller = self.0.execution_controller.clone();
+        let closure = async move || {
+            // the controller waits for the replay on the execution thread
+            tokio::task::spawn_blocking(move || {
+                Ok(execution_controller.replay_slot_with_trace(slot)?)
+            })
+            .await
+            .map_err(|e| ApiError::InternalServerError(e.to_string()))?
+        };
+        Box::pin(closure())
+    }
+
     fn get_openrpc_spec(&self) -> BoxFuture<Result<Value, ApiError>> {
         crate::wrong_api::<Value>()
     }
 }
+
+/// Retires the old addresses of a staking key rotation once the rolls of all its new addresses
+/// are active, that is once the selector draws them. Until then, the factory produces blocks
+/// and endorsements for both the old and the new addresses. Checked once per period.
+async fn drive_staking_key_rotation(
+    index: usize,
+    check_interval: MassaTime,
+    periods_per_cycle: u64,
+    execution_controller: Box<dyn ExecutionController>,
+    node_wallet: Arc<RwLock<Wallet>>,
+    rotations: Arc<RwLock<Vec<StakingKeyRotation>>>,
+) {
+    loop {
+        tokio::time::sleep(check_interval.to_duration()).await;
+        let cycle = execution_controller
+            .get_stats()
+            .active_cursor
+            .get_cycle(periods_per_cycle);
+        let active_rolls = execution_controller.get_cycle_active_rolls(cycle);
+
+        let mut w_wallet = node_wallet.write();
+        let mut w_rotations = rotations.write();
+        let rotation = &mut w_rotations[index];
+        rotation.last_checked_cycle = cycle;
+        let staking_addresses = w_wallet.get_wallet_address_list();
+        if let Some(address) = rotation
+            .new_addresses
+            .iter()
+            .find(|address| !staking_addresses.contains(address))
+        {
+            rotation.status = StakingKeyRotationStatus::Aborted(format!(
+                "the new address {} was removed from the staking addresses",
+                address
+            ));
+            return;
+        }
+        if rotation
+            .new_addresses
+            .iter()
+            .all(|address| active_rolls.get(address).copied().unwrap_or_default() > 0)
+        {
+            rotation.status = match w_wallet.remove_addresses(&rotation.old_addresses) {
+                Ok(()) => StakingKeyRotationStatus::Completed,
+                Err(err) => StakingKeyRotationStatus::Aborted(format!(
+                    "could not remove the old addresses: {}",
+                    err
+                )),
+            };
+            return;
+        }
+    }
+}
@@ -3,19 +3,27 @@
 
 use crate::config::APIConfig;
 use crate::error::ApiError;
+use crate::request_tracing::current_request_id;
+use crate::v2::EndpointsV2;
 use crate::{serde_json, Endpoints, Public, RpcServer, StopHandle, Value, API};
 use jsonrpc_core::BoxFuture;
 use massa_consensus_exports::{ConsensusCommandSender, ConsensusConfig};
 use massa_execution_exports::{
-    ExecutionController, ExecutionStackElement, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    AddressInfoBounds, ExecutionController, ExecutionStackElement, ReadOnlyExecutionRequest,
+    ReadOnlyExecutionTarget,
 };
-use massa_graph::DiscardReason;
+use massa_ledger_exports::LedgerEntryProof;
 use massa_models::api::{
-    BlockGraphStatus, DatastoreEntryInput, DatastoreEntryOutput, OperationInput,
-    ReadOnlyBytecodeExecution, ReadOnlyCall, SlotAmount,
+    BlockGraphStatus, BlockTemplate, ContractsInput, ContractsOutput, DatastoreEntryInput,
+    DatastoreEntryOutput, DatastoreKeysInput, DatastoreKeysOutput, DeferredCreditSchedule,
+    DeferredCreditScheduleEntry, DeferredCreditsInput, DeferredCreditsOutput,
+    EndorsementDiagnostic, IndexedSlot, LedgerProofInput, NodeSubsystem, OperationInput,
+    PeerScoreInfo, ReadOnlyBytecodeExecution, ReadOnlyCall, ReorgInfo, SlotAmount,
+    SlotExecutionTrace, StakingKeyRotation, StakingKeyRotationInput, StakingReport,
+    SubsystemAction,
 };
 use massa_models::execution::ReadOnlyResult;
-use massa_models::operation::OperationDeserializer;
+use massa_models::operation::{OperationDeserializer, OperationOrigin};
 use massa_models::wrapped::WrappedDeserializer;
 use massa_models::{
     block::Block, endorsement::WrappedEndorsement, error::ModelsError, operation::WrappedOperation,
@@ -29,21 +37,29 @@ use itertools::{izip, Itertools};
 use massa_models::datastore::DatastoreDeserializer;
 use massa_models::{
     address::Address,
+    amount::Amount,
     api::{
-        AddressInfo, BlockInfo, BlockInfoContent, BlockSummary, EndorsementInfo, EventFilter,
-        NodeStatus, OperationInfo, TimeInterval,
+        AddressActivity, AddressActivityKind, AddressDraws, AddressDrawsQuery, AddressHistoryQuery,
+        AddressInfo, AddressInfoFields, AddressesPageInput, BlockInfo, BlockInfoContent,
+        BlockSummary, CycleStatsQuery, EndorsementInfo, EventFilter, GraphDotQuery, NodeStatus,
+        OperationExecutionStatus, OperationInfo, OperationPoolStatusInfo, TimeInterval,
     },
     block::BlockId,
     clique::Clique,
     composite::PubkeySig,
     config::CompactConfig,
-    endorsement::EndorsementId,
+    endorsement::{EndorsementEquivocation, EndorsementId},
     execution::ExecuteReadOnlyResponse,
     node::NodeId,
     operation::OperationId,
     output_event::SCOutputEvent,
     prehash::{PreHashMap, PreHashSet},
     slot::Slot,
+    stats::{
+        BootstrapServerStats, CycleStats, DiskSpaceStatus, ExecutionPolicyStats, FeeRecommendation,
+        LedgerIntegrityReport, LedgerIntegrityStatus, NetworkActivityStats,
+    },
+    streaming_step::StreamingStep,
     timeslots::{get_latest_block_slot_at_timestamp, time_range_to_slot_range},
     version::Version,
 };
@@ -51,8 +67,12 @@ use massa_network_exports::{NetworkCommandSender, NetworkConfig};
 use massa_pool_exports::PoolController;
 use massa_signature::KeyPair;
 use massa_storage::Storage;
-use massa_time::MassaTime;
+use massa_time::ClockCompensation;
+use parking_lot::RwLock;
+use std::collections::{BTreeMap, HashMap};
 use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use tracing::debug;
 
 impl API<Public> {
     /// generate a new public API
@@ -67,9 +87,12 @@ impl API<Public> {
         network_settings: NetworkConfig,
         version: Version,
         network_command_sender: NetworkCommandSender,
-        compensation_millis: i64,
+        clock_compensation: ClockCompensation,
         node_id: NodeId,
         storage: Storage,
+        disk_status: Arc<RwLock<DiskSpaceStatus>>,
+        ledger_integrity: Arc<RwLock<LedgerIntegrityStatus>>,
+        bootstrap_server_stats: Option<Arc<RwLock<BootstrapServerStats>>>,
     ) -> Self {
         API(Public {
             consensus_command_sender,
@@ -80,18 +103,189 @@ impl API<Public> {
             version,
             network_command_sender,
             protocol_command_sender,
-            compensation_millis,
+            clock_compensation,
             node_id,
             execution_controller,
             selector_controller,
             storage,
+            disk_status,
+            ledger_integrity,
+            bootstrap_server_stats,
         })
     }
+
+    /// Gathers information about addresses, their datastore keys and cycle infos being limited by `bounds`
+    /// and the fields that are not selected in `fields` being left empty
+    fn get_addresses_infos(
+        &self,
+        addresses: Vec<Address>,
+        mut bounds: AddressInfoBounds,
+        fields: AddressInfoFields,
+    ) -> Vec<AddressInfo> {
+        // get info from storage about which blocks the addresses have created
+        let created_blocks: Vec<PreHashSet<BlockId>> = if fields.created_objects {
+            let lck = self.0.storage.read_blocks();
+            addresses
+                .iter()
+                .map(|address| {
+                    lck.get_blocks_created_by(address)
+                        .cloned()
+                        .unwrap_or_default()
+                })
+                .collect()
+        } else {
+            vec![Default::default(); addresses.len()]
+        };
+
+        // get info from storage about which operations the addresses have created
+        let created_operations: Vec<PreHashSet<OperationId>> = if fields.created_objects {
+            let lck = self.0.storage.read_operations();
+            addresses
+                .iter()
+                .map(|address| {
+                    lck.get_operations_created_by(address)
+                        .cloned()
+                        .unwrap_or_default()
+                })
+                .collect()
+        } else {
+            vec![Default::default(); addresses.len()]
+        };
+
+        // get info from storage about which endorsements the addresses have created
+        let created_endorsements: Vec<PreHashSet<EndorsementId>> = if fields.created_objects {
+            let lck = self.0.storage.read_endorsements();
+            addresses
+                .iter()
+                .map(|address| {
+                    lck.get_endorsements_created_by(address)
+                        .cloned()
+                        .unwrap_or_default()
+                })
+                .collect()
+        } else {
+            vec![Default::default(); addresses.len()]
+        };
+
+        // get execution info
+        if !fields.datastore_keys {
+            bounds.max_datastore_keys = 0;
+        }
+        if !fields.cycle_infos {
+            bounds.max_cycle_infos = 0;
+        }
+        let execution_infos = self
+            .0
+            .execution_controller
+            .get_addresses_infos(&addresses, &bounds);
+
+        // get future draws from selector
+        let selection_draws = if fields.draws {
+            let cur_slot = timeslots::get_current_latest_block_slot(
+                self.0.consensus_config.thread_count,
+                self.0.consensus_config.t0,
+                self.0.consensus_config.genesis_timestamp,
+                self.0.clock_compensation.get(),
+            )
+            .expect("could not get latest current slot")
+            .unwrap_or_else(|| Slot::new(0, 0));
+            let slot_end = Slot::new(
+                cur_slot
+                    .period
+                    .saturating_add(self.0.api_settings.draw_lookahead_period_count),
+                cur_slot.thread,
+            );
+            addresses
+                .iter()
+                .map(|addr| {
+                    self.0
+                        .selector_controller
+                        .get_address_selections(addr, cur_slot, slot_end)
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>()
+        } else {
+            addresses.iter().map(|_| Default::default()).collect()
+        };
+
+        // compile results
+        let mut res = Vec::with_capacity(addresses.len());
+        let iterator = izip!(
+            addresses.into_iter(),
+            created_blocks.into_iter(),
+            created_operations.into_iter(),
+            created_endorsements.into_iter(),
+            execution_infos.into_iter(),
+            selection_draws.into_iter(),
+        );
+        for (
+            address,
+            created_blocks,
+            created_operations,
+            created_endorsements,
+            execution_infos,
+            (next_block_draws, next_endorsement_draws),
+        ) in iterator
+        {
+            res.push(AddressInfo {
+                // general address info
+                address,
+                thread: address.get_thread(self.0.consensus_config.thread_count),
+
+                // final execution info
+                final_balance: execution_infos.final_balance,
+                final_roll_count: execution_infos.final_roll_count,
+                final_datastore_keys: execution_infos
+                    .final_datastore_keys
+                    .into_iter()
+                    .collect::<Vec<_>>(),
+
+                // candidate execution info
+                candidate_balance: execution_infos.candidate_balance,
+                candidate_roll_count: execution_infos.candidate_roll_count,
+                candidate_datastore_keys: execution_infos
+                    .candidate_datastore_keys
+                    .into_iter()
+                    .collect::<Vec<_>>(),
+                next_datastore_keys_last_key: match execution_infos.next_datastore_keys_cursor {
+                    StreamingStep::Ongoing(last_key) => Some(last_key),
+                    _ => None,
+                },
+
+                // deferred credits
+                deferred_credits: if fields.deferred_credits {
+                    execution_infos
+                        .future_deferred_credits
+                        .into_iter()
+                        .map(|(slot, amount)| SlotAmount { slot, amount })
+                        .collect::<Vec<_>>()
+                } else {
+                    Vec::new()
+                },
+
+                // selector info
+                next_block_draws,
+                next_endorsement_draws,
+
+                // created objects
+                created_blocks: created_blocks.into_iter().collect::<Vec<_>>(),
+                created_endorsements: created_endorsements.into_iter().collect::<Vec<_>>(),
+                created_operations: created_operations.into_iter().collect::<Vec<_>>(),
+
+                // cycle infos
+                cycle_infos: execution_infos.cycle_infos,
+            });
+        }
+        res
+    }
 }
 
 impl RpcServer for API<Public> {
     fn serve(self, url: &SocketAddr) -> StopHandle {
-        crate::serve(self, url)
+        let mut io = crate::new_http_io_handler();
+        io.extend_with(Endpoints::to_delegate(API(self.0.clone())));
+        io.extend_with(EndpointsV2::to_delegate(self));
+        crate::start_http_server(io, url)
     }
 }
 
@@ -101,6 +295,10 @@ impl Endpoints for API<Public> {
         crate::wrong_api::<()>()
     }
 
+    fn stop_node_gracefully(&self) -> BoxFuture<Result<(), ApiError>> {
+        crate::wrong_api::<()>()
+    }
+
     fn node_sign_message(&self, _: Vec<u8>) -> BoxFuture<Result<PubkeySig, ApiError>> {
         crate::wrong_api::<PubkeySig>()
     }
@@ -113,6 +311,9 @@ impl Endpoints for API<Public> {
         &self,
         reqs: Vec<ReadOnlyBytecodeExecution>,
     ) -> BoxFuture<Result<Vec<ExecuteReadOnlyResponse>, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
         if reqs.len() as u64 > self.0.api_settings.max_arguments {
             let closure = async move || Err(ApiError::BadRequest("too many arguments".into()));
             return Box::pin(closure());
@@ -125,6 +326,7 @@ impl Endpoints for API<Public> {
             simulated_gas_price,
             bytecode,
             operation_datastore,
+            deployment_simulation,
         } in reqs
         {
             let address = address.unwrap_or_else(|| {
@@ -161,12 +363,14 @@ impl Endpoints for API<Public> {
                 max_gas,
                 simulated_gas_price,
                 target: ReadOnlyExecutionTarget::BytecodeExecution(bytecode),
+                request_id: current_request_id(),
                 call_stack: vec![ExecutionStackElement {
                     address,
                     coins: Default::default(),
                     owned_addresses: vec![address],
                     operation_datastore: op_datastore,
                 }],
+                deployment_simulation,
             };
 
             // run
@@ -182,6 +386,12 @@ impl Endpoints for API<Public> {
                     |_| ReadOnlyResult::Ok,
                 ),
                 gas_cost: result.as_ref().map_or_else(|_| 0, |v| v.gas_cost),
+                created_addresses: result
+                    .as_ref()
+                    .map_or_else(|_| Default::default(), |v| v.created_addresses.clone()),
+                storage_cost: result
+                    .as_ref()
+                    .map_or_else(|_| Default::default(), |v| v.storage_cost),
                 output_events: result
                     .map_or_else(|_| Default::default(), |mut v| v.out.events.take()),
             };
@@ -198,6 +408,9 @@ impl Endpoints for API<Public> {
         &self,
         reqs: Vec<ReadOnlyCall>,
     ) -> BoxFuture<Result<Vec<ExecuteReadOnlyResponse>, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
         if reqs.len() as u64 > self.0.api_settings.max_arguments {
             let closure = async move || Err(ApiError::BadRequest("too many arguments".into()));
             return Box::pin(closure());
@@ -232,6 +445,7 @@ impl Endpoints for API<Public> {
                     target_addr: target_address,
                     parameter,
                 },
+                request_id: current_request_id(),
                 call_stack: vec![
                     ExecutionStackElement {
                         address: caller_address,
@@ -246,6 +460,7 @@ impl Endpoints for API<Public> {
                         operation_datastore: None, // should always be None
                     },
                 ],
+                deployment_simulation: None,
             };
 
             // run
@@ -261,6 +476,12 @@ impl Endpoints for API<Public> {
                     |_| ReadOnlyResult::Ok,
                 ),
                 gas_cost: result.as_ref().map_or_else(|_| 0, |v| v.gas_cost),
+                created_addresses: result
+                    .as_ref()
+                    .map_or_else(|_| Default::default(), |v| v.created_addresses.clone()),
+                storage_cost: result
+                    .as_ref()
+                    .map_or_else(|_| Default::default(), |v| v.storage_cost),
                 output_events: result
                     .map_or_else(|_| Default::default(), |mut v| v.out.events.take()),
             };
@@ -277,6 +498,41 @@ impl Endpoints for API<Public> {
         crate::wrong_api::<()>()
     }
 
+    fn node_sell_staking_rolls(
+        &self,
+        _: Address,
+        _: u64,
+    ) -> BoxFuture<Result<OperationId, ApiError>> {
+        crate::wrong_api::<OperationId>()
+    }
+
+    fn node_manage_subsystem(
+        &self,
+        _: NodeSubsystem,
+        _: SubsystemAction,
+    ) -> BoxFuture<Result<(), ApiError>> {
+        crate::wrong_api::<()>()
+    }
+
+    fn get_staking_report(&self) -> BoxFuture<Result<Vec<StakingReport>, ApiError>> {
+        crate::wrong_api::<Vec<StakingReport>>()
+    }
+
+    fn node_rotate_staking_keys(
+        &self,
+        _: StakingKeyRotationInput,
+    ) -> BoxFuture<Result<StakingKeyRotation, ApiError>> {
+        crate::wrong_api::<StakingKeyRotation>()
+    }
+
+    fn get_staking_key_rotations(&self) -> BoxFuture<Result<Vec<StakingKeyRotation>, ApiError>> {
+        crate::wrong_api::<Vec<StakingKeyRotation>>()
+    }
+
+    fn get_execution_policy_stats(&self) -> BoxFuture<Result<ExecutionPolicyStats, ApiError>> {
+        crate::wrong_api::<ExecutionPolicyStats>()
+    }
+
     fn get_staking_addresses(&self) -> BoxFuture<Result<PreHashSet<Address>, ApiError>> {
         crate::wrong_api::<PreHashSet<Address>>()
     }
@@ -301,15 +557,25 @@ impl Endpoints for API<Public> {
         let execution_controller = self.0.execution_controller.clone();
         let consensus_command_sender = self.0.consensus_command_sender.clone();
         let network_command_sender = self.0.network_command_sender.clone();
+        let protocol_command_sender = self.0.protocol_command_sender.clone();
         let network_config = self.0.network_settings.clone();
         let version = self.0.version;
         let consensus_settings = self.0.consensus_config.clone();
-        let compensation_millis = self.0.compensation_millis;
+        let clock_compensation = self.0.clock_compensation.clone();
         let pool_command_sender = self.0.pool_command_sender.clone();
         let node_id = self.0.node_id;
+        let disk_status = *self.0.disk_status.read();
+        let ledger_integrity = self.0.ledger_integrity.read().clone();
+        let chain_id = self.0.api_settings.chain_id;
+        let chain_id_activation_period = self.0.api_settings.chain_id_activation_period;
+        let bootstrap_server_stats = self
+            .0
+            .bootstrap_server_stats
+            .as_ref()
+            .map(|stats| stats.read().clone());
         let config = CompactConfig::default();
         let closure = async move || {
-            let now = MassaTime::now(compensation_millis)?;
+            let now = clock_compensation.now()?;
             let last_slot = get_latest_block_slot_at_timestamp(
                 consensus_settings.thread_count,
                 consensus_settings.t0,
@@ -319,9 +585,10 @@ impl Endpoints for API<Public> {
 
             let execution_stats = execution_controller.get_stats();
 
-            let (consensus_stats, network_stats, peers) = tokio::join!(
+            let (consensus_stats, network_stats, protocol_stats, peers) = tokio::join!(
                 consensus_command_sender.get_stats(),
                 network_command_sender.get_network_stats(),
+                protocol_command_sender.get_stats(),
                 network_command_sender.get_peers()
             );
 
@@ -329,11 +596,14 @@ impl Endpoints for API<Public> {
                 pool_command_sender.get_operation_count(),
                 pool_command_sender.get_endorsement_count(),
             );
+            let pool_operation_origins = pool_command_sender.get_operation_origin_counts();
 
             Ok(NodeStatus {
                 node_id,
                 node_ip: network_config.routable_ip,
                 version,
+                chain_id,
+                chain_id_activation_period,
                 current_time: now,
                 connected_nodes: peers?
                     .peers
@@ -351,7 +621,12 @@ impl Endpoints for API<Public> {
                 execution_stats,
                 consensus_stats: consensus_stats?,
                 network_stats: network_stats?,
+                protocol_stats: protocol_stats?,
                 pool_stats,
+                pool_operation_origins,
+                disk_status,
+                ledger_integrity,
+                bootstrap_server_stats,
                 config,
                 current_cycle: last_slot
                     .unwrap_or_else(|| Slot::new(0, 0))
@@ -367,17 +642,71 @@ impl Endpoints for API<Public> {
         Box::pin(closure())
     }
 
+    fn get_reorg_history(&self) -> BoxFuture<Result<Vec<ReorgInfo>, ApiError>> {
+        let consensus_command_sender = self.0.consensus_command_sender.clone();
+        let closure = async move || Ok(consensus_command_sender.get_reorg_history().await?);
+        Box::pin(closure())
+    }
+
+    fn get_network_stats(&self, window: u64) -> BoxFuture<Result<NetworkActivityStats, ApiError>> {
+        if window == 0 {
+            let closure = async move || {
+                Err(ApiError::BadRequest(
+                    "the window must hold at least one slot".into(),
+                ))
+            };
+            return Box::pin(closure());
+        }
+        let stats = self
+            .0
+            .execution_controller
+            .get_network_stats(window as usize);
+        let closure = async move || Ok(stats);
+        Box::pin(closure())
+    }
+
+    fn get_cycle_stats(&self, query: CycleStatsQuery) -> BoxFuture<Result<CycleStats, ApiError>> {
+        let max_page_size = self.0.api_settings.max_cycle_stakers_per_page;
+        let max_count = query.max_count.unwrap_or(max_page_size).min(max_page_size) as usize;
+        let stats =
+            self.0
+                .execution_controller
+                .get_cycle_stats(query.cycle, query.start_after, max_count);
+        let closure = async move || {
+            stats.ok_or_else(|| {
+                ApiError::BadRequest(format!(
+                    "cycle {} is not in the final cycle history",
+                    query.cycle
+                ))
+            })
+        };
+        Box::pin(closure())
+    }
+
+    fn get_fee_recommendation(&self) -> BoxFuture<Result<FeeRecommendation, ApiError>> {
+        let pool_operation_count = self.0.pool_command_sender.get_operation_count();
+        let recommendation = self
+            .0
+            .execution_controller
+            .get_fee_recommendation(pool_operation_count);
+        let closure = async move || Ok(recommendation);
+        Box::pin(closure())
+    }
+
     fn get_stakers(&self) -> BoxFuture<Result<Vec<(Address, u64)>, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
         let execution_controller = self.0.execution_controller.clone();
         let cfg = self.0.consensus_config.clone();
-        let compensation_millis = self.0.compensation_millis;
+        let clock_compensation = self.0.clock_compensation.clone();
 
         let closure = async move || {
             let curr_cycle = get_latest_block_slot_at_timestamp(
                 cfg.thread_count,
                 cfg.t0,
                 cfg.genesis_timestamp,
-                MassaTime::now(compensation_millis)?,
+                clock_compensation.now()?,
             )?
             .unwrap_or_else(|| Slot::new(0, 0))
             .get_cycle(cfg.periods_per_cycle);
@@ -397,6 +726,9 @@ impl Endpoints for API<Public> {
         &self,
         ops: Vec<OperationId>,
     ) -> BoxFuture<Result<Vec<OperationInfo>, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
         // get the operations and the list of blocks that contain them from storage
         let storage_info: Vec<(WrappedOperation, PreHashSet<BlockId>)> = {
             let read_blocks = self.0.storage.read_blocks();
@@ -422,6 +754,9 @@ impl Endpoints for API<Public> {
         // ask pool whether it carries the operations
         let in_pool = self.0.pool_command_sender.contains_operations(&ops);
 
+        // ask execution for the outcomes of their executions
+        let executions = self.0.execution_controller.get_op_execution_infos(&ops);
+
         let api_cfg = self.0.api_settings.clone();
         let consensus_command_sender = self.0.consensus_command_sender.clone();
         let closure = async move || {
@@ -459,14 +794,21 @@ impl Endpoints for API<Public> {
                 ops.into_iter(),
                 storage_info.into_iter(),
                 in_pool.into_iter(),
-                is_final.into_iter()
+                is_final.into_iter(),
+                executions.into_iter()
             );
-            for (id, (operation, in_blocks), in_pool, is_final) in zipped_iterator {
+            for (id, (operation, in_blocks), in_pool, is_final, execution) in zipped_iterator {
                 res.push(OperationInfo {
                     id,
                     operation,
                     in_pool,
                     is_final,
+                    execution_status: OperationExecutionStatus::new(
+                        !in_blocks.is_empty(),
+                        is_final,
+                        execution.as_ref(),
+                    ),
+                    execution,
                     in_blocks: in_blocks.into_iter().collect(),
                 });
             }
@@ -585,7 +927,16 @@ impl Endpoints for API<Public> {
             let is_in_blockclique = graph_status == BlockGraphStatus::ActiveInBlockclique;
             let is_candidate = graph_status == BlockGraphStatus::ActiveInBlockclique
                 || graph_status == BlockGraphStatus::ActiveInAlternativeCliques;
-            let is_discarded = graph_status == BlockGraphStatus::Discarded;
+            let discard_reason = match graph_status {
+                BlockGraphStatus::Discarded(reason) => Some(reason),
+                _ => None,
+            };
+            let confirmation_weight = consensus_command_sender
+                .get_confirmation_weights(&[id])
+                .await?
+                .into_iter()
+                .next()
+                .expect("expected get_confirmation_weights to return one element");
 
             Ok(BlockInfo {
                 id,
@@ -593,7 +944,9 @@ impl Endpoints for API<Public> {
                     is_final,
                     is_in_blockclique,
                     is_candidate,
-                    is_discarded,
+                    is_discarded: discard_reason.is_some(),
+                    discard_reason,
+                    confirmation_weight,
                     block,
                 }),
             })
@@ -624,6 +977,64 @@ impl Endpoints for API<Public> {
         Box::pin(closure())
     }
 
+    /// assembles a block template the same way the block factory does, without signing it
+    fn get_block_template(&self, slot: Slot) -> BoxFuture<Result<BlockTemplate, ApiError>> {
+        let consensus_command_sender = self.0.consensus_command_sender.clone();
+        let selector_controller = self.0.selector_controller.clone();
+        let pool_command_sender = self.0.pool_command_sender.clone();
+        let cfg = self.0.consensus_config.clone();
+        let closure = async move || {
+            if slot.thread >= cfg.thread_count || slot.period == 0 {
+                return Err(ApiError::BadRequest(format!("invalid slot {}", slot)));
+            }
+            let producer = selector_controller.get_producer(slot).map_err(|err| {
+                ApiError::BadRequest(format!("no producer drawn for slot {}: {}", slot, err))
+            })?;
+
+            // best parents, the endorsed block being the parent in the slot's thread
+            let parents: Vec<BlockId> = consensus_command_sender
+                .get_best_parents_async()
+                .await?
+                .into_iter()
+                .map(|(id, _period)| id)
+                .collect();
+            let same_thread_parent = parents.get(slot.thread as usize).ok_or_else(|| {
+                ApiError::InconsistencyError("missing best parent in slot thread".to_string())
+            })?;
+            let (endorsements, _endo_storage) =
+                pool_command_sender.get_block_endorsements(same_thread_parent, &slot);
+
+            // operations with their gas and fees
+            let (operations, op_storage) = pool_command_sender.get_block_operations(&slot);
+            let mut estimated_gas: u64 = 0;
+            let mut total_fees = Amount::default();
+            {
+                let ops = op_storage.read_operations();
+                for op_id in &operations {
+                    let op = ops.get(op_id).ok_or_else(|| {
+                        ApiError::InconsistencyError(format!(
+                            "operation {} selected by pool is missing from storage",
+                            op_id
+                        ))
+                    })?;
+                    estimated_gas = estimated_gas.saturating_add(op.get_gas_usage());
+                    total_fees = total_fees.saturating_add(op.content.fee);
+                }
+            }
+
+            Ok(BlockTemplate {
+                slot,
+                producer,
+                parents,
+                endorsements,
+                operations,
+                estimated_gas,
+                total_fees,
+            })
+        };
+        Box::pin(closure())
+    }
+
     /// gets an interval of the block graph from consensus, with time filtering
     /// time filtering is done consensus-side to prevent communication overhead
     fn get_graph_interval(
@@ -655,6 +1066,7 @@ impl Endpoints for API<Public> {
                     id,
                     is_final: exported_block.is_final,
                     is_stale: false,
+                    discard_reason: None,
                     is_in_blockclique: blockclique.block_ids.contains(&id),
                     slot: exported_block.header.content.slot,
                     creator: exported_block.header.creator_address,
@@ -662,11 +1074,12 @@ impl Endpoints for API<Public> {
                 });
             }
             for (id, (reason, (slot, creator, parents))) in graph.discarded_blocks.into_iter() {
-                if reason == DiscardReason::Stale {
+                if reason.is_stale() {
                     res.push(BlockSummary {
                         id,
                         is_final: false,
                         is_stale: true,
+                        discard_reason: Some(reason.kind()),
                         is_in_blockclique: false,
                         slot,
                         creator,
@@ -679,10 +1092,31 @@ impl Endpoints for API<Public> {
         Box::pin(closure())
     }
 
+    fn get_graph_dot(&self, query: GraphDotQuery) -> BoxFuture<Result<String, ApiError>> {
+        if matches!((query.start, query.end), (Some(start), Some(end)) if start >= end) {
+            let closure = async move || {
+                Err(ApiError::BadRequest(
+                    "start slot must be before end slot".into(),
+                ))
+            };
+            return Box::pin(closure());
+        }
+        let consensus_command_sender = self.0.consensus_command_sender.clone();
+        let closure = async move || {
+            Ok(consensus_command_sender
+                .get_graph_dot(query.start, query.end)
+                .await?)
+        };
+        Box::pin(closure())
+    }
+
     fn get_datastore_entries(
         &self,
         entries: Vec<DatastoreEntryInput>,
     ) -> BoxFuture<Result<Vec<DatastoreEntryOutput>, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
         let execution_controller = self.0.execution_controller.clone();
         let closure = async move || {
             Ok(execution_controller
@@ -702,156 +1136,526 @@ impl Endpoints for API<Public> {
         Box::pin(closure())
     }
 
+    fn get_datastore_keys(
+        &self,
+        input: DatastoreKeysInput,
+    ) -> BoxFuture<Result<DatastoreKeysOutput, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
+        if input.max_count > self.0.api_settings.max_datastore_keys_per_page {
+            let closure = async move || Err(ApiError::BadRequest("too many keys requested".into()));
+            return Box::pin(closure());
+        }
+        let execution_controller = self.0.execution_controller.clone();
+        let closure = async move || {
+            let cursor = match input.last_key {
+                Some(last_key) => StreamingStep::Ongoing(last_key),
+                None => StreamingStep::Started,
+            };
+            let (final_keys, candidate_keys, next_cursor) = execution_controller
+                .get_final_and_candidate_datastore_keys(
+                    &input.address,
+                    &input.prefix,
+                    cursor,
+                    input.max_count as usize,
+                );
+            Ok(DatastoreKeysOutput {
+                final_keys: final_keys.into_iter().collect(),
+                candidate_keys: candidate_keys.into_iter().collect(),
+                next_last_key: match next_cursor {
+                    StreamingStep::Ongoing(last_key) => Some(last_key),
+                    _ => None,
+                },
+            })
+        };
+        Box::pin(closure())
+    }
+
+    fn get_contracts(&self, input: ContractsInput) -> BoxFuture<Result<ContractsOutput, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
+        if input.max_count > self.0.api_settings.max_contracts_per_page {
+            let closure =
+                async move || Err(ApiError::BadRequest("too many contracts requested".into()));
+            return Box::pin(closure());
+        }
+        let execution_controller = self.0.execution_controller.clone();
+        let closure = async move || {
+            let cursor = match input.last_address {
+                Some(last_address) => StreamingStep::Ongoing(last_address),
+                None => StreamingStep::Started,
+            };
+            let (contracts, next_cursor) =
+                execution_controller.get_final_contracts(cursor, input.max_count as usize);
+            Ok(ContractsOutput {
+                contracts,
+                next_last_address: match next_cursor {
+                    StreamingStep::Ongoing(last_address) => Some(last_address),
+                    _ => None,
+                },
+            })
+        };
+        Box::pin(closure())
+    }
+
+    fn get_deferred_credits(
+        &self,
+        input: DeferredCreditsInput,
+    ) -> BoxFuture<Result<DeferredCreditsOutput, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
+        if input.max_count > self.0.api_settings.max_deferred_credits_per_page {
+            let closure = async move || {
+                Err(ApiError::BadRequest(
+                    "too many deferred credits requested".into(),
+                ))
+            };
+            return Box::pin(closure());
+        }
+        let execution_controller = self.0.execution_controller.clone();
+        let closure = async move || {
+            let cursor = match input.last {
+                Some(last) => StreamingStep::Ongoing(last),
+                None => StreamingStep::Started,
+            };
+            let (credits, next_cursor) = execution_controller.get_deferred_credits(
+                input.start.unwrap_or_else(Slot::min),
+                input.end.unwrap_or_else(Slot::max),
+                cursor,
+                input.max_count as usize,
+            );
+            Ok(DeferredCreditsOutput {
+                credits,
+                next_last: match next_cursor {
+                    StreamingStep::Ongoing(last) => Some(last),
+                    _ => None,
+                },
+            })
+        };
+        Box::pin(closure())
+    }
+
+    fn get_deferred_credit_schedule(
+        &self,
+        addresses: Vec<Address>,
+    ) -> BoxFuture<Result<Vec<DeferredCreditSchedule>, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
+        if addresses.len() as u64 > self.0.api_settings.max_arguments {
+            let closure = async move || Err(ApiError::BadRequest("too many arguments".into()));
+            return Box::pin(closure());
+        }
+
+        let addresses_infos = self.0.execution_controller.get_addresses_infos(
+            &addresses,
+            &AddressInfoBounds {
+                max_datastore_keys: 0,
+                max_cycle_infos: 0,
+                ..Default::default()
+            },
+        );
+        let res = addresses
+            .into_iter()
+            .zip(addresses_infos)
+            .map(|(address, info)| {
+                let mut credits: BTreeMap<Slot, DeferredCreditScheduleEntry> = info
+                    .future_deferred_credits
+                    .into_iter()
+                    .map(|(slot, amount)| {
+                        let credit = DeferredCreditScheduleEntry {
+                            slot,
+                            amount,
+                            is_credited: false,
+                            sell_operations: Vec::new(),
+                        };
+                        (slot, credit)
+                    })
+                    .collect();
+                let mut sales = Vec::new();
+                for activity in self
+                    .0
+                    .execution_controller
+                    .get_address_history(&address, None, None)
+                {
+                    match activity.kind {
+                        AddressActivityKind::DeferredCredit { amount } => {
+                            credits.insert(
+                                activity.slot,
+                                DeferredCreditScheduleEntry {
+                                    slot: activity.slot,
+                                    amount,
+                                    is_credited: true,
+                                    sell_operations: Vec::new(),
+                                },
+                            );
+                        }
+                        AddressActivityKind::RollSell {
+                            operation_id,
+                            credit_slot,
+                            ..
+                        } => sales.push((credit_slot, operation_id)),
+                        _ => {}
+                    }
+                }
+                // sales whose credit was cancelled by a slash or was paid too long ago are left out
+                for (credit_slot, operation_id) in sales {
+                    if let Some(credit) = credits.get_mut(&credit_slot) {
+                        credit.sell_operations.push(operation_id);
+                    }
+                }
+                DeferredCreditSchedule {
+                    address,
+                    credits: credits.into_values().collect(),
+                }
+            })
+            .collect();
+
+        let closure = async move || Ok(res);
+        Box::pin(closure())
+    }
+
+    fn get_ledger_entry_proof(
+        &self,
+        input: LedgerProofInput,
+    ) -> BoxFuture<Result<Option<LedgerEntryProof>, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
+        let execution_controller = self.0.execution_controller.clone();
+        let closure = async move || {
+            Ok(execution_controller
+                .get_final_ledger_proof(&input.address, input.datastore_key.as_deref()))
+        };
+        Box::pin(closure())
+    }
+
     fn get_addresses(
         &self,
         addresses: Vec<Address>,
     ) -> BoxFuture<Result<Vec<AddressInfo>, ApiError>> {
-        // get info from storage about which blocks the addresses have created
-        let created_blocks: Vec<PreHashSet<BlockId>> = {
-            let lck = self.0.storage.read_blocks();
-            addresses
-                .iter()
-                .map(|address| {
-                    lck.get_blocks_created_by(address)
-                        .cloned()
-                        .unwrap_or_default()
-                })
-                .collect()
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
+        let res = self.get_addresses_infos(
+            addresses,
+            AddressInfoBounds::default(),
+            AddressInfoFields::default(),
+        );
+        let closure = async move || Ok(res);
+        Box::pin(closure())
+    }
+
+    fn get_addresses_page(
+        &self,
+        input: AddressesPageInput,
+    ) -> BoxFuture<Result<Vec<AddressInfo>, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
+        if input.addresses.len() as u64 > self.0.api_settings.max_arguments {
+            let closure = async move || Err(ApiError::BadRequest("too many arguments".into()));
+            return Box::pin(closure());
+        }
+        if input.max_datastore_keys > self.0.api_settings.max_datastore_keys_per_page {
+            let closure = async move || Err(ApiError::BadRequest("too many keys requested".into()));
+            return Box::pin(closure());
+        }
+        let bounds = AddressInfoBounds {
+            datastore_keys_cursor: match input.datastore_keys_last_key {
+                Some(last_key) => StreamingStep::Ongoing(last_key),
+                None => StreamingStep::Started,
+            },
+            max_datastore_keys: input.max_datastore_keys as usize,
+            max_cycle_infos: input.max_cycle_infos as usize,
         };
+        let res = self.get_addresses_infos(input.addresses, bounds, input.fields);
+        let closure = async move || Ok(res);
+        Box::pin(closure())
+    }
 
-        // get info from storage about which operations the addresses have created
-        let created_operations: Vec<PreHashSet<OperationId>> = {
-            let lck = self.0.storage.read_operations();
-            addresses
-                .iter()
-                .map(|address| {
-                    lck.get_operations_created_by(address)
-                        .cloned()
-                        .unwrap_or_default()
-                })
-                .collect()
+    fn resolve_names(
+        &self,
+        names: Vec<String>,
+    ) -> BoxFuture<Result<Vec<Option<Address>>, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
+        if names.len() as u64 > self.0.api_settings.max_arguments {
+            let closure = async move || Err(ApiError::BadRequest("too many arguments".into()));
+            return Box::pin(closure());
+        }
+
+        let result: Result<Vec<Option<Address>>, ApiError> = names
+            .iter()
+            .map(|name| {
+                self.0
+                    .execution_controller
+                    .resolve_name(name)
+                    .map_err(ApiError::from)
+            })
+            .collect();
+        let closure = async move || result;
+        Box::pin(closure())
+    }
+
+    fn get_endorsement_diagnostics(
+        &self,
+        addresses: Vec<Address>,
+    ) -> BoxFuture<Result<Vec<EndorsementDiagnostic>, ApiError>> {
+        if addresses.len() as u64 > self.0.api_settings.max_arguments {
+            let closure = async move || Err(ApiError::BadRequest("too many arguments".into()));
+            return Box::pin(closure());
+        }
+
+        // look back from the latest slot
+        let cur_slot = match timeslots::get_current_latest_block_slot(
+            self.0.consensus_config.thread_count,
+            self.0.consensus_config.t0,
+            self.0.consensus_config.genesis_timestamp,
+            self.0.clock_compensation.get(),
+        ) {
+            Ok(Some(slot)) => slot,
+            Ok(None) => {
+                let closure = async move || Ok(Vec::new());
+                return Box::pin(closure());
+            }
+            Err(err) => {
+                let closure = async move || Err(ApiError::from(err));
+                return Box::pin(closure());
+            }
         };
+        let start_slot = Slot::new(
+            cur_slot.period.saturating_sub(
+                self.0
+                    .api_settings
+                    .endorsement_diagnostics_lookback_period_count,
+            ),
+            cur_slot.thread,
+        );
 
-        // get info from storage about which endorsements the addresses have created
-        let created_endorsements: Vec<PreHashSet<EndorsementId>> = {
+        // get endorsement draws from selector
+        let draws: Vec<(Address, Vec<IndexedSlot>)> = addresses
+            .into_iter()
+            .map(|address| {
+                let (_, endorsement_draws) = self
+                    .0
+                    .selector_controller
+                    .get_address_selections(&address, start_slot, cur_slot)
+                    .unwrap_or_default();
+                (address, endorsement_draws)
+            })
+            .collect();
+
+        // match draws with the endorsements created by the drawn addresses
+        let mut res: Vec<EndorsementDiagnostic> = {
             let lck = self.0.storage.read_endorsements();
-            addresses
-                .iter()
-                .map(|address| {
-                    lck.get_endorsements_created_by(address)
-                        .cloned()
-                        .unwrap_or_default()
+            draws
+                .into_iter()
+                .flat_map(|(address, endorsement_draws)| {
+                    let created: HashMap<(Slot, u32), EndorsementId> = lck
+                        .get_endorsements_created_by(&address)
+                        .map(|ids| {
+                            ids.iter()
+                                .filter_map(|id| {
+                                    lck.get(id)
+                                        .map(|e| ((e.content.slot, e.content.index), *id))
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    endorsement_draws
+                        .into_iter()
+                        .map(move |IndexedSlot { slot, index }| EndorsementDiagnostic {
+                            address,
+                            slot,
+                            index: index as u32,
+                            produced: created.get(&(slot, index as u32)).copied(),
+                            in_pool: false,
+                            included_in: Vec::new(),
+                        })
                 })
                 .collect()
         };
 
-        // get execution info
-        let execution_infos = self.0.execution_controller.get_addresses_infos(&addresses);
+        // check pool and block inclusion of the produced endorsements
+        let produced: Vec<EndorsementId> = res.iter().filter_map(|d| d.produced).collect();
+        let mut in_pool = self
+            .0
+            .pool_command_sender
+            .contains_endorsements(&produced)
+            .into_iter();
+        {
+            let lck = self.0.storage.read_blocks();
+            for diagnostic in res.iter_mut() {
+                if let Some(id) = diagnostic.produced {
+                    diagnostic.in_pool = in_pool.next().unwrap_or(false);
+                    diagnostic.included_in = lck
+                        .get_blocks_by_endorsement(&id)
+                        .map(|ids| ids.iter().copied().collect())
+                        .unwrap_or_default();
+                }
+            }
+        }
 
-        // get future draws from selector
-        let selection_draws = {
-            let cur_slot = timeslots::get_current_latest_block_slot(
+        let closure = async move || Ok(res);
+        Box::pin(closure())
+    }
+
+    fn get_address_draws(
+        &self,
+        query: AddressDrawsQuery,
+    ) -> BoxFuture<Result<Vec<AddressDraws>, ApiError>> {
+        if query.addresses.len() as u64 > self.0.api_settings.max_arguments {
+            let closure = async move || Err(ApiError::BadRequest("too many arguments".into()));
+            return Box::pin(closure());
+        }
+
+        // start from the current slot by default
+        let start = match query.start {
+            Some(start) => start,
+            None => match timeslots::get_current_latest_block_slot(
                 self.0.consensus_config.thread_count,
                 self.0.consensus_config.t0,
                 self.0.consensus_config.genesis_timestamp,
-                self.0.compensation_millis,
-            )
-            .expect("could not get latest current slot")
-            .unwrap_or_else(|| Slot::new(0, 0));
-            let slot_end = Slot::new(
-                cur_slot
-                    .period
-                    .saturating_add(self.0.api_settings.draw_lookahead_period_count),
-                cur_slot.thread,
-            );
-            addresses
-                .iter()
-                .map(|addr| {
-                    self.0
-                        .selector_controller
-                        .get_address_selections(addr, cur_slot, slot_end)
-                        .unwrap_or_default()
-                })
-                .collect::<Vec<_>>()
+                self.0.clock_compensation.get(),
+            ) {
+                Ok(cur_slot) => cur_slot.unwrap_or_else(|| Slot::new(0, 0)),
+                Err(err) => {
+                    let closure = async move || Err(ApiError::from(err));
+                    return Box::pin(closure());
+                }
+            },
         };
+        // the selector stops at the last slot with known draws
+        let end = query.end.unwrap_or_else(|| Slot::new(u64::MAX, 0));
+        if start >= end {
+            let closure = async move || {
+                Err(ApiError::BadRequest(
+                    "start slot must be before end slot".into(),
+                ))
+            };
+            return Box::pin(closure());
+        }
 
-        // compile results
-        let mut res = Vec::with_capacity(addresses.len());
-        let iterator = izip!(
-            addresses.into_iter(),
-            created_blocks.into_iter(),
-            created_operations.into_iter(),
-            created_endorsements.into_iter(),
-            execution_infos.into_iter(),
-            selection_draws.into_iter(),
-        );
-        for (
-            address,
-            created_blocks,
-            created_operations,
-            created_endorsements,
-            execution_infos,
-            (next_block_draws, next_endorsement_draws),
-        ) in iterator
-        {
-            res.push(AddressInfo {
-                // general address info
-                address,
-                thread: address.get_thread(self.0.consensus_config.thread_count),
+        let res: Result<Vec<AddressDraws>, ApiError> = query
+            .addresses
+            .iter()
+            .map(|address| {
+                self.0
+                    .selector_controller
+                    .get_address_draws(address, start, end)
+                    .map_err(|err| ApiError::BadRequest(err.to_string()))
+            })
+            .collect();
 
-                // final execution info
-                final_balance: execution_infos.final_balance,
-                final_roll_count: execution_infos.final_roll_count,
-                final_datastore_keys: execution_infos
-                    .final_datastore_keys
-                    .into_iter()
-                    .collect::<Vec<_>>(),
+        let closure = async move || res;
+        Box::pin(closure())
+    }
 
-                // candidate execution info
-                candidate_balance: execution_infos.candidate_balance,
-                candidate_roll_count: execution_infos.candidate_roll_count,
-                candidate_datastore_keys: execution_infos
-                    .candidate_datastore_keys
-                    .into_iter()
-                    .collect::<Vec<_>>(),
+    fn get_address_history(
+        &self,
+        query: AddressHistoryQuery,
+    ) -> BoxFuture<Result<Vec<AddressActivity>, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
+        if matches!((query.start, query.end), (Some(start), Some(end)) if start >= end) {
+            let closure = async move || {
+                Err(ApiError::BadRequest(
+                    "start slot must be before end slot".into(),
+                ))
+            };
+            return Box::pin(closure());
+        }
 
-                // deferred credits
-                deferred_credits: execution_infos
-                    .future_deferred_credits
-                    .into_iter()
-                    .map(|(slot, amount)| SlotAmount { slot, amount })
-                    .collect::<Vec<_>>(),
+        let max_page_size = self.0.api_settings.max_address_activities_per_page;
+        let max_count = query.max_count.unwrap_or(max_page_size).min(max_page_size) as usize;
+        let mut history =
+            self.0
+                .execution_controller
+                .get_address_history(&query.address, query.start, query.end);
+        // the page ends with all the activities of its last slot, so that the next one can start at the following slot
+        if history.len() > max_count {
+            let page_end = match max_count.checked_sub(1) {
+                Some(last_index) => {
+                    let last_slot = history[last_index].slot;
+                    history
+                        .iter()
+                        .position(|activity| activity.slot > last_slot)
+                        .unwrap_or(history.len())
+                }
+                None => 0,
+            };
+            history.truncate(page_end);
+        }
 
-                // selector info
-                next_block_draws,
-                next_endorsement_draws,
+        let closure = async move || Ok(history);
+        Box::pin(closure())
+    }
 
-                // created objects
-                created_blocks: created_blocks.into_iter().collect::<Vec<_>>(),
-                created_endorsements: created_endorsements.into_iter().collect::<Vec<_>>(),
-                created_operations: created_operations.into_iter().collect::<Vec<_>>(),
+    fn get_endorsement_equivocations(
+        &self,
+        addresses: Vec<Address>,
+    ) -> BoxFuture<Result<Vec<EndorsementEquivocation>, ApiError>> {
+        if addresses.len() as u64 > self.0.api_settings.max_arguments {
+            let closure = async move || Err(ApiError::BadRequest("too many arguments".into()));
+            return Box::pin(closure());
+        }
 
-                // cycle infos
-                cycle_infos: execution_infos.cycle_infos,
-            });
+        let mut res = self.0.pool_command_sender.get_endorsement_equivocations();
+        if !addresses.is_empty() {
+            res.retain(|record| addresses.contains(&record.creator));
         }
 
         let closure = async move || Ok(res);
         Box::pin(closure())
     }
 
+    fn get_operation_pool_status(
+        &self,
+        ops: Vec<OperationId>,
+    ) -> BoxFuture<Result<Vec<OperationPoolStatusInfo>, ApiError>> {
+        if ops.len() as u64 > self.0.api_settings.max_arguments {
+            let closure = async move || Err(ApiError::BadRequest("too many arguments".into()));
+            return Box::pin(closure());
+        }
+
+        let res = ops
+            .iter()
+            .copied()
+            .zip(self.0.pool_command_sender.get_operation_pool_status(&ops))
+            .map(|(id, status)| OperationPoolStatusInfo { id, status })
+            .collect();
+
+        let closure = async move || Ok(res);
+        Box::pin(closure())
+    }
+
     fn send_operations(
         &self,
         ops: Vec<OperationInput>,
     ) -> BoxFuture<Result<Vec<OperationId>, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
         let mut cmd_sender = self.0.pool_command_sender.clone();
         let mut protocol_sender = self.0.protocol_command_sender.clone();
         let api_cfg = self.0.api_settings.clone();
         let mut to_send = self.0.storage.clone_without_refs();
+        let disk_status = *self.0.disk_status.read();
         let closure = async move || {
             if ops.len() as u64 > api_cfg.max_arguments {
                 return Err(ApiError::BadRequest("too many arguments".into()));
             }
+            if disk_status == DiskSpaceStatus::Critical {
+                return Err(ApiError::InternalServerError(
+                    "free disk space is critically low, new operations are not accepted".into(),
+                ));
+            }
             let operation_deserializer = WrappedDeserializer::new(OperationDeserializer::new(
                 api_cfg.max_datastore_value_length,
                 api_cfg.max_function_name_length,
@@ -882,15 +1686,27 @@ impl Endpoints for API<Public> {
                 })
                 .map(|op| match op {
                     Ok(operation) => {
-                        operation.verify_signature()?;
+                        operation.verify_operation_signature(
+                            api_cfg.chain_id,
+                            api_cfg.chain_id_activation_period,
+                        )?;
                         Ok(operation)
                     }
                     Err(e) => Err(e),
                 })
                 .collect::<Result<Vec<WrappedOperation>, ApiError>>()?;
+            // do not propagate operations that the pool would not accept
+            if let Some((op, reason)) = verified_ops
+                .iter()
+                .zip(cmd_sender.check_operations(&verified_ops))
+                .find_map(|(op, rejection)| rejection.map(|reason| (op, reason)))
+            {
+                return Err(ApiError::OperationRejected(op.id, reason));
+            }
             to_send.store_operations(verified_ops.clone());
             let ids: Vec<OperationId> = verified_ops.iter().map(|op| op.id).collect();
-            cmd_sender.add_operations(to_send.clone());
+            debug!("sending operations {:?} to pool and protocol", ids);
+            cmd_sender.add_operations(to_send.clone(), OperationOrigin::Local);
             protocol_sender.propagate_operations(to_send).await?;
             Ok(ids)
         };
@@ -907,6 +1723,9 @@ impl Endpoints for API<Public> {
         &self,
         filter: EventFilter,
     ) -> BoxFuture<Result<Vec<SCOutputEvent>, ApiError>> {
+        if self.0.api_settings.light_mode {
+            return crate::light_mode_unavailable();
+        }
         let events = self
             .0
             .execution_controller
@@ -925,6 +1744,28 @@ impl Endpoints for API<Public> {
         crate::wrong_api::<()>()
     }
 
+    fn node_get_peer_scores(&self) -> BoxFuture<Result<Vec<PeerScoreInfo>, ApiError>> {
+        crate::wrong_api::<Vec<PeerScoreInfo>>()
+    }
+
+    fn node_export_ledger_snapshot(&self, _: String) -> BoxFuture<Result<Slot, ApiError>> {
+        crate::wrong_api::<Slot>()
+    }
+
+    fn node_verify_ledger_integrity(
+        &self,
+        _: bool,
+    ) -> BoxFuture<Result<LedgerIntegrityReport, ApiError>> {
+        crate::wrong_api::<LedgerIntegrityReport>()
+    }
+
+    fn node_replay_slot_with_trace(
+        &self,
+        _: Slot,
+    ) -> BoxFuture<Result<SlotExecutionTrace, ApiError>> {
+        crate::wrong_api::<SlotExecutionTrace>()
+    }
+
     fn get_openrpc_spec(&self) -> BoxFuture<Result<Value, ApiError>> {
         let openrpc_spec_path = self.0.api_settings.openrpc_spec_path.clone();
         let closure = async move || {
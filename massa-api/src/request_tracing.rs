@@ -0,0 +1,165 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Tracing of the API requests.
+//!
+//! Every call receives a unique request id. Its handling runs inside an `api_request` tracing span
+//! carrying that id and the JSON-RPC id of the call, so that the logs of the work it triggers can
+//! be correlated with it. The id is also returned in the responses: in the `data` field of errors,
+//! and in the `result` field of successes when it is an object.
+
+use jsonrpc_core::futures::future::Either;
+use jsonrpc_core::{serde_json, BoxFuture, Call, Id, Metadata, Middleware, Output, Value};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tracing::{debug, info_span, Instrument};
+
+/// id given to the next request
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(0);
+
+tokio::task_local! {
+    /// id of the request being handled
+    static REQUEST_ID: u64;
+}
+
+/// Returns the id of the API request being handled, if any
+pub(crate) fn current_request_id() -> Option<u64> {
+    REQUEST_ID.try_with(|id| *id).ok()
+}
+
+/// Middleware assigning an id to every API call and tracing its handling
+#[derive(Default)]
+pub(crate) struct RequestTracer;
+
+impl<M: Metadata> Middleware<M> for RequestTracer {
+    type Future = jsonrpc_core::middleware::NoopFuture;
+    type CallFuture = BoxFuture<Option<Output>>;
+
+    fn on_call<F, X>(&self, call: Call, meta: M, next: F) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, M) -> X + Send + Sync,
+        X: Future<Output = Option<Output>> + Send + 'static,
+    {
+        let request_id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+        let (method, call_id) = match &call {
+            Call::MethodCall(method_call) => (method_call.method.clone(), Some(&method_call.id)),
+            Call::Notification(notification) => (notification.method.clone(), None),
+            Call::Invalid { id } => ("invalid".to_string(), Some(id)),
+        };
+        let call_id = call_id.map(|id| match id {
+            Id::Null => "null".to_string(),
+            Id::Num(num) => num.to_string(),
+            Id::Str(string) => string.clone(),
+        });
+        let span = info_span!("api_request", request_id, method = %method, call_id = ?call_id);
+        let start = Instant::now();
+
+        // synchronous handlers do their work when called, asynchronous ones when polled
+        let call_future = span.in_scope(|| REQUEST_ID.sync_scope(request_id, || next(call, meta)));
+        let traced_future = async move {
+            let output = call_future.await;
+            debug!("API request handled in {} ms", start.elapsed().as_millis());
+            output.map(|output| with_request_id(output, request_id))
+        };
+        Either::Left(Box::pin(
+            REQUEST_ID.scope(request_id, traced_future.instrument(span)),
+        ))
+    }
+}
+
+/// Adds the request id to the output of a call: to the data of a failure, or to the result of a
+/// success when it is an object. Results of other types are left untouched not to break clients.
+fn with_request_id(output: Output, request_id: u64) -> Output {
+    match output {
+        Output::Success(mut success) => {
+            insert_request_id(&mut success.result, request_id);
+            Output::Success(success)
+        }
+        Output::Failure(mut failure) => {
+            match failure.error.data.as_mut() {
+                Some(data) => insert_request_id(data, request_id),
+                None => failure.error.data = Some(serde_json::json!({ "request_id": request_id })),
+            }
+            Output::Failure(failure)
+        }
+    }
+}
+
+/// Adds the request id to a JSON object that does not already have a `request_id` field
+fn insert_request_id(value: &mut Value, request_id: u64) {
+    if let Value::Object(object) = value {
+        object
+            .entry("request_id")
+            .or_insert_with(|| Value::from(request_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{current_request_id, RequestTracer};
+    use jsonrpc_core::{serde_json, Error, ErrorCode, MetaIoHandler, Value};
+
+    /// Calls `method` on a traced handler, returning the whole response
+    fn call(io: &MetaIoHandler<(), RequestTracer>, method: &str) -> Value {
+        let request = format!(
+            r#"{{"jsonrpc": "2.0", "method": "{}", "params": [], "id": 1}}"#,
+            method
+        );
+        serde_json::from_str(
+            &io.handle_request_sync(&request, ())
+                .expect("a method call has a response"),
+        )
+        .unwrap()
+    }
+
+    fn traced_handler() -> MetaIoHandler<(), RequestTracer> {
+        let mut io = MetaIoHandler::with_middleware(RequestTracer);
+        io.add_sync_method("get_request_id", |_| {
+            Ok(serde_json::json!({ "seen": current_request_id() }))
+        });
+        io.add_sync_method("get_list", |_| Ok(serde_json::json!([1, 2])));
+        io.add_sync_method("fail", |_| Err(Error::new(ErrorCode::InternalError)));
+        io.add_sync_method("fail_with_data", |_| {
+            Err(Error {
+                code: ErrorCode::InternalError,
+                message: "failed".into(),
+                data: Some(serde_json::json!({ "reason": "test" })),
+            })
+        });
+        io
+    }
+
+    #[test]
+    fn test_request_id_in_responses() {
+        let io = traced_handler();
+
+        // the handlers see the id returned in the response, and every call gets a new one
+        let first = call(&io, "get_request_id")["result"].clone();
+        let second = call(&io, "get_request_id")["result"].clone();
+        assert_eq!(first["seen"], first["request_id"]);
+        assert_eq!(second["seen"], second["request_id"]);
+        assert!(first["request_id"].as_u64().is_some());
+        assert_ne!(first["request_id"], second["request_id"]);
+
+        // results that are not objects are left untouched
+        assert_eq!(call(&io, "get_list")["result"], serde_json::json!([1, 2]));
+
+        // the id is added to the data of the errors, keeping the data they already have
+        assert!(call(&io, "fail")["error"]["data"]["request_id"]
+            .as_u64()
+            .is_some());
+        let data = call(&io, "fail_with_data")["error"]["data"].clone();
+        assert_eq!(data["reason"], Value::from("test"));
+        assert!(data["request_id"].as_u64().is_some());
+
+        // unknown methods are traced too
+        assert!(call(&io, "unknown")["error"]["data"]["request_id"]
+            .as_u64()
+            .is_some());
+    }
+
+    #[test]
+    fn test_no_request_id_outside_requests() {
+        assert_eq!(current_request_id(), None);
+    }
+}
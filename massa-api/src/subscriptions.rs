@@ -0,0 +1,579 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! WebSocket subscriptions to the updates of the node.
+//!
+//! Every topic is fed by a broadcast channel of the consensus or execution worker.
+//! Each subscription runs a task forwarding the matching updates to its client,
+//! until the client unsubscribes or disconnects.
+//! The number of active subscriptions of every connection is limited.
+//! Subscribers too slow to keep up with a topic miss the updates they lag behind.
+
+use crate::error::ApiError;
+use crate::request_tracing::RequestTracer;
+use crate::{APIConfig, ServerCloseHandle, StopHandle, API};
+use jsonrpc_core::{MetaIoHandler, Metadata};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{typed::Subscriber, PubSubHandler, PubSubMetadata, Session, SubscriptionId};
+use jsonrpc_ws_server::{RequestContext, ServerBuilder};
+use massa_consensus_exports::settings::ConsensusBroadcasts;
+use massa_execution_exports::ExecutionChannels;
+use massa_models::address::Address;
+use massa_models::api::{
+    BlockConfirmation, BlockSummary, EventFilter, OperationStatusNotification, ReorgInfo,
+};
+use massa_models::output_event::SCOutputEvent;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{hash_map, HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::thread;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// WebSocket subscriptions API content
+pub struct Subscriptions {
+    /// channels broadcasting block updates
+    pub consensus_broadcasts: ConsensusBroadcasts,
+    /// channels broadcasting execution outputs
+    pub execution_channels: ExecutionChannels,
+    /// API settings
+    pub api_settings: APIConfig,
+    /// active subscriptions of the clients
+    registry: SubscriptionRegistry,
+}
+
+/// Metadata of the calls received through a WebSocket connection
+#[derive(Clone)]
+pub struct SubscriptionSession {
+    /// id of the connection
+    id: u64,
+    /// publish-subscribe session of the connection
+    session: Arc<Session>,
+}
+
+impl Metadata for SubscriptionSession {}
+
+impl PubSubMetadata for SubscriptionSession {
+    fn session(&self) -> Option<Arc<Session>> {
+        Some(self.session.clone())
+    }
+}
+
+impl API<Subscriptions> {
+    /// generate a new subscriptions API
+    pub fn new(
+        consensus_broadcasts: ConsensusBroadcasts,
+        execution_channels: ExecutionChannels,
+        api_settings: APIConfig,
+    ) -> Self {
+        API(Subscriptions {
+            consensus_broadcasts,
+            execution_channels,
+            registry: SubscriptionRegistry::new(api_settings.max_ws_subscriptions),
+            api_settings,
+        })
+    }
+
+    /// Start the WebSocket server
+    pub fn serve_ws(self, url: &SocketAddr) -> StopHandle {
+        let mut io = PubSubHandler::new(
+            MetaIoHandler::<SubscriptionSession, RequestTracer>::with_middleware(RequestTracer),
+        );
+        io.extend_with(self.to_delegate());
+
+        let server = ServerBuilder::with_meta_extractor(io, |context: &RequestContext| {
+            SubscriptionSession {
+                id: context.session_id,
+                session: Arc::new(Session::new(context.sender())),
+            }
+        })
+        .event_loop_executor(tokio::runtime::Handle::current())
+        .start(url)
+        .expect("Unable to start WebSocket server");
+
+        let close_handle = server.close_handle();
+        let thread_builder = thread::Builder::new().name("ws-server".into());
+        let join_handle = thread_builder
+            .spawn(|| {
+                if let Err(err) = server.wait() {
+                    warn!("WebSocket server error: {}", err);
+                }
+            })
+            .expect("failed to spawn thread : ws-server");
+
+        StopHandle {
+            close_handle: ServerCloseHandle::Ws(close_handle),
+            join_handle,
+        }
+    }
+}
+
+/// Forwarding tasks of the active subscriptions
+#[derive(Default)]
+struct ActiveSubscriptions {
+    /// connection and forwarding task of each subscription
+    tasks: HashMap<SubscriptionId, (u64, JoinHandle<()>)>,
+    /// connections whose subscriptions are cancelled when they close
+    watched_sessions: HashSet<u64>,
+}
+
+impl ActiveSubscriptions {
+    /// Number of active subscriptions of a connection
+    fn session_count(&self, session_id: u64) -> usize {
+        self.tasks
+            .values()
+            .filter(|(task_session, _)| *task_session == session_id)
+            .count()
+    }
+
+    /// Cancels the subscriptions of a closed connection
+    fn close_session(&mut self, session_id: u64) {
+        self.watched_sessions.remove(&session_id);
+        self.tasks.retain(|_, (task_session, task)| {
+            if *task_session == session_id {
+                task.abort();
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+/// Active subscriptions of the clients, limited per connection
+struct SubscriptionRegistry {
+    /// max number of active subscriptions of a connection
+    max_per_session: usize,
+    /// runtime running the subscription tasks
+    runtime: tokio::runtime::Handle,
+    /// forwarding tasks of the active subscriptions
+    active: Arc<Mutex<ActiveSubscriptions>>,
+}
+
+impl SubscriptionRegistry {
+    fn new(max_per_session: usize) -> Self {
+        SubscriptionRegistry {
+            max_per_session,
+            runtime: tokio::runtime::Handle::current(),
+            active: Default::default(),
+        }
+    }
+
+    /// Starts forwarding the updates of a topic passing the filter to a new subscriber
+    fn subscribe<T, F>(
+        &self,
+        session: &SubscriptionSession,
+        subscriber: Subscriber<T>,
+        mut receiver: broadcast::Receiver<T>,
+        filter: F,
+    ) where
+        T: Serialize + Clone + Send + 'static,
+        F: Fn(&T) -> bool + Send + 'static,
+    {
+        // the lock is held until the task is registered, so that it cannot unregister itself before
+        let mut active = self.active.lock();
+        if active.session_count(session.id) >= self.max_per_session {
+            let _ = subscriber
+                .reject(ApiError::BadRequest("too many active subscriptions".to_string()).into());
+            return;
+        }
+
+        // random ids, so that clients cannot guess the subscriptions of others
+        let id = loop {
+            let id = SubscriptionId::Number(rand::random());
+            if !active.tasks.contains_key(&id) {
+                break id;
+            }
+        };
+        let sink = match subscriber.assign_id(id.clone()) {
+            Ok(sink) => sink,
+            // the client disconnected
+            Err(()) => return,
+        };
+
+        // cancel the subscriptions of the connection as soon as it closes
+        if active.watched_sessions.insert(session.id) {
+            let session_active = self.active.clone();
+            let session_id = session.id;
+            session
+                .session
+                .on_drop(move || session_active.lock().close_session(session_id));
+        }
+
+        let task_active = self.active.clone();
+        let task_id = id.clone();
+        let task = self.runtime.spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(update) => {
+                        if filter(&update) && sink.notify(Ok(update)).is_err() {
+                            // the client disconnected
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        debug!("subscription {:?} skipped {} updates", task_id, skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            task_active.lock().tasks.remove(&task_id);
+        });
+        active.tasks.insert(id, (session.id, task));
+    }
+
+    /// Stops forwarding updates to a subscriber of the connection, if known
+    fn unsubscribe(
+        &self,
+        session: Option<&SubscriptionSession>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        match self.active.lock().tasks.entry(id) {
+            hash_map::Entry::Occupied(entry)
+                if session.map_or(true, |session| session.id == entry.get().0) =>
+            {
+                entry.remove().1.abort();
+                Ok(true)
+            }
+            _ => Err(ApiError::NotFound.into()),
+        }
+    }
+}
+
+/// Topics of the WebSocket subscriptions
+#[rpc(server)]
+pub trait SubscriptionEndpoints {
+    /// Session metadata
+    type Metadata;
+
+    /// Subscribe to the blocks entering the blockclique.
+    #[pubsub(subscription = "new_blocks", subscribe, name = "subscribe_new_blocks")]
+    fn subscribe_new_blocks(&self, _: Self::Metadata, _: Subscriber<BlockSummary>);
+
+    /// Cancel a subscription to the blocks entering the blockclique.
+    #[pubsub(
+        subscription = "new_blocks",
+        unsubscribe,
+        name = "unsubscribe_new_blocks"
+    )]
+    fn unsubscribe_new_blocks(
+        &self,
+        _: Option<Self::Metadata>,
+        _: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool>;
+
+    /// Subscribe to the blocks becoming final.
+    #[pubsub(
+        subscription = "final_blocks",
+        subscribe,
+        name = "subscribe_final_blocks"
+    )]
+    fn subscribe_final_blocks(&self, _: Self::Metadata, _: Subscriber<BlockSummary>);
+
+    /// Cancel a subscription to the blocks becoming final.
+    #[pubsub(
+        subscription = "final_blocks",
+        unsubscribe,
+        name = "unsubscribe_final_blocks"
+    )]
+    fn unsubscribe_final_blocks(
+        &self,
+        _: Option<Self::Metadata>,
+        _: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool>;
+
+    /// Subscribe to the blockclique re-organizations.
+    #[pubsub(subscription = "reorgs", subscribe, name = "subscribe_reorgs")]
+    fn subscribe_reorgs(&self, _: Self::Metadata, _: Subscriber<ReorgInfo>);
+
+    /// Cancel a subscription to the blockclique re-organizations.
+    #[pubsub(subscription = "reorgs", unsubscribe, name = "unsubscribe_reorgs")]
+    fn unsubscribe_reorgs(
+        &self,
+        _: Option<Self::Metadata>,
+        _: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool>;
+
+    /// Subscribe to the blocks whose confirmation weight crosses one of the configured thresholds.
+    #[pubsub(
+        subscription = "block_confirmations",
+        subscribe,
+        name = "subscribe_block_confirmations"
+    )]
+    fn subscribe_block_confirmations(&self, _: Self::Metadata, _: Subscriber<BlockConfirmation>);
+
+    /// Cancel a subscription to the block confirmations.
+    #[pubsub(
+        subscription = "block_confirmations",
+        unsubscribe,
+        name = "unsubscribe_block_confirmations"
+    )]
+    fn unsubscribe_block_confirmations(
+        &self,
+        _: Option<Self::Metadata>,
+        _: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool>;
+
+    /// Subscribe to the execution status changes of the operations involving an address.
+    /// A status is notified when the operation is executed in a candidate block, and when it becomes final.
+    #[pubsub(
+        subscription = "operation_status",
+        subscribe,
+        name = "subscribe_operation_status"
+    )]
+    fn subscribe_operation_status(
+        &self,
+        _: Self::Metadata,
+        _: Subscriber<OperationStatusNotification>,
+        _: Address,
+    );
+
+    /// Cancel a subscription to operation status changes.
+    #[pubsub(
+        subscription = "operation_status",
+        unsubscribe,
+        name = "unsubscribe_operation_status"
+    )]
+    fn unsubscribe_operation_status(
+        &self,
+        _: Option<Self::Metadata>,
+        _: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool>;
+
+    /// Subscribe to the smart contract events passing a filter.
+    /// An event is notified when emitted in a candidate slot, and when it becomes final.
+    #[pubsub(subscription = "sc_events", subscribe, name = "subscribe_sc_events")]
+    fn subscribe_sc_events(&self, _: Self::Metadata, _: Subscriber<SCOutputEvent>, _: EventFilter);
+
+    /// Cancel a subscription to smart contract events.
+    #[pubsub(
+        subscription = "sc_events",
+        unsubscribe,
+        name = "unsubscribe_sc_events"
+    )]
+    fn unsubscribe_sc_events(
+        &self,
+        _: Option<Self::Metadata>,
+        _: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool>;
+}
+
+impl SubscriptionEndpoints for API<Subscriptions> {
+    type Metadata = SubscriptionSession;
+
+    fn subscribe_new_blocks(&self, session: Self::Metadata, subscriber: Subscriber<BlockSummary>) {
+        let receiver = self
+            .0
+            .consensus_broadcasts
+            .blockclique_block_sender
+            .subscribe();
+        self.0
+            .registry
+            .subscribe(&session, subscriber, receiver, |_| true);
+    }
+
+    fn unsubscribe_new_blocks(
+        &self,
+        session: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        self.0.registry.unsubscribe(session.as_ref(), id)
+    }
+
+    fn subscribe_final_blocks(
+        &self,
+        session: Self::Metadata,
+        subscriber: Subscriber<BlockSummary>,
+    ) {
+        let receiver = self.0.consensus_broadcasts.final_block_sender.subscribe();
+        self.0
+            .registry
+            .subscribe(&session, subscriber, receiver, |_| true);
+    }
+
+    fn unsubscribe_final_blocks(
+        &self,
+        session: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        self.0.registry.unsubscribe(session.as_ref(), id)
+    }
+
+    fn subscribe_reorgs(&self, session: Self::Metadata, subscriber: Subscriber<ReorgInfo>) {
+        let receiver = self.0.consensus_broadcasts.reorg_sender.subscribe();
+        self.0
+            .registry
+            .subscribe(&session, subscriber, receiver, |_| true);
+    }
+
+    fn unsubscribe_reorgs(
+        &self,
+        session: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        self.0.registry.unsubscribe(session.as_ref(), id)
+    }
+
+    fn subscribe_block_confirmations(
+        &self,
+        session: Self::Metadata,
+        subscriber: Subscriber<BlockConfirmation>,
+    ) {
+        let receiver = self.0.consensus_broadcasts.confirmation_sender.subscribe();
+        self.0
+            .registry
+            .subscribe(&session, subscriber, receiver, |_| true);
+    }
+
+    fn unsubscribe_block_confirmations(
+        &self,
+        session: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        self.0.registry.unsubscribe(session.as_ref(), id)
+    }
+
+    fn subscribe_operation_status(
+        &self,
+        session: Self::Metadata,
+        subscriber: Subscriber<OperationStatusNotification>,
+        address: Address,
+    ) {
+        let receiver = self
+            .0
+            .execution_channels
+            .operation_status_sender
+            .subscribe();
+        self.0
+            .registry
+            .subscribe(&session, subscriber, receiver, move |status| {
+                status.involved_addresses.contains(&address)
+            });
+    }
+
+    fn unsubscribe_operation_status(
+        &self,
+        session: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        self.0.registry.unsubscribe(session.as_ref(), id)
+    }
+
+    fn subscribe_sc_events(
+        &self,
+        session: Self::Metadata,
+        subscriber: Subscriber<SCOutputEvent>,
+        filter: EventFilter,
+    ) {
+        let receiver = self.0.execution_channels.sc_event_sender.subscribe();
+        self.0
+            .registry
+            .subscribe(&session, subscriber, receiver, move |event| {
+                filter.matches(event)
+            });
+    }
+
+    fn unsubscribe_sc_events(
+        &self,
+        session: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        self.0.registry.unsubscribe(session.as_ref(), id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SubscriptionRegistry, SubscriptionSession};
+    use jsonrpc_core::futures::channel::mpsc;
+    use jsonrpc_pubsub::{typed::Subscriber, Session, SubscriptionId};
+    use std::sync::Arc;
+    use tokio::sync::broadcast;
+
+    fn new_session(id: u64) -> SubscriptionSession {
+        let (sender, _receiver) = mpsc::unbounded();
+        SubscriptionSession {
+            id,
+            session: Arc::new(Session::new(sender)),
+        }
+    }
+
+    /// Subscribes a connection to a topic, returning the id of the subscription if accepted
+    async fn subscribe(
+        registry: &SubscriptionRegistry,
+        session: &SubscriptionSession,
+        sender: &broadcast::Sender<u64>,
+    ) -> Option<SubscriptionId> {
+        let (subscriber, id_receiver, _notifications) = Subscriber::<u64>::new_test("notify");
+        registry.subscribe(session, subscriber, sender.subscribe(), |_| true);
+        id_receiver.await.unwrap().ok()
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_limited_per_session() {
+        let registry = SubscriptionRegistry::new(2);
+        let (sender, _) = broadcast::channel(16);
+        let first_session = new_session(1);
+        let second_session = new_session(2);
+
+        assert!(subscribe(&registry, &first_session, &sender)
+            .await
+            .is_some());
+        let id = subscribe(&registry, &first_session, &sender)
+            .await
+            .expect("the limit is not reached");
+        assert!(subscribe(&registry, &first_session, &sender)
+            .await
+            .is_none());
+
+        // the other connections are not affected by the limit of the first one
+        assert!(subscribe(&registry, &second_session, &sender)
+            .await
+            .is_some());
+
+        // a connection cannot cancel the subscriptions of another one
+        assert!(registry
+            .unsubscribe(Some(&second_session), id.clone())
+            .is_err());
+
+        // unsubscribing frees a slot
+        assert_eq!(
+            registry.unsubscribe(Some(&first_session), id.clone()),
+            Ok(true)
+        );
+        assert!(registry.unsubscribe(Some(&first_session), id).is_err());
+        assert!(subscribe(&registry, &first_session, &sender)
+            .await
+            .is_some());
+        assert_eq!(registry.active.lock().session_count(1), 2);
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_cancelled_on_disconnect() {
+        let registry = SubscriptionRegistry::new(2);
+        let (sender, _) = broadcast::channel(16);
+        let first_session = new_session(1);
+        let second_session = new_session(2);
+        subscribe(&registry, &first_session, &sender).await.unwrap();
+        subscribe(&registry, &first_session, &sender).await.unwrap();
+        subscribe(&registry, &second_session, &sender)
+            .await
+            .unwrap();
+        assert_eq!(sender.receiver_count(), 3);
+
+        // closing the first connection cancels its subscriptions only
+        drop(first_session);
+        {
+            let active = registry.active.lock();
+            assert_eq!(active.session_count(1), 0);
+            assert_eq!(active.session_count(2), 1);
+            assert!(!active.watched_sessions.contains(&1));
+        }
+
+        // the aborted tasks release their receivers
+        tokio::task::yield_now().await;
+        assert_eq!(sender.receiver_count(), 1);
+    }
+}
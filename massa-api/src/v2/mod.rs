@@ -0,0 +1,199 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Version 2 of the public API, served on the `/api/v2` path of the public API server.
+//!
+//! Its methods are implemented on top of the ones of the version 1,
+//! their parameters and results being converted from and to the types of `types`.
+
+pub mod types;
+
+use crate::error::ApiError;
+use crate::{Endpoints, Public, API};
+use jsonrpc_core::BoxFuture;
+use jsonrpc_derive::rpc;
+use massa_models::{address::Address, block::BlockId, operation::OperationId};
+use std::str::FromStr;
+use types::{
+    AddressFormat, AddressInfo, Block, DatastoreEntry, DatastoreEntryInput, Event, EventFilter,
+    NodeStatus, Operation, OperationInput,
+};
+
+/// Endpoints of the version 2 of the public API
+#[rpc(server)]
+pub trait EndpointsV2 {
+    /// Summary of the current state of the node.
+    #[rpc(name = "v2.get_status")]
+    fn get_status(&self) -> BoxFuture<Result<NodeStatus, ApiError>>;
+
+    /// Get the final and candidate state of addresses.
+    #[rpc(name = "v2.get_addresses")]
+    fn get_addresses(&self, _: Vec<String>) -> BoxFuture<Result<Vec<AddressInfo>, ApiError>>;
+
+    /// Get blocks from their ids, `null` for the unknown ones.
+    #[rpc(name = "v2.get_blocks")]
+    fn get_blocks(&self, _: Vec<String>) -> BoxFuture<Result<Vec<Option<Block>>, ApiError>>;
+
+    /// Get operations from their ids, along with their progress. Unknown operations are left out.
+    #[rpc(name = "v2.get_operations")]
+    fn get_operations(&self, _: Vec<String>) -> BoxFuture<Result<Vec<Operation>, ApiError>>;
+
+    /// Send operations to the pool. Returns the ids of the operations.
+    #[rpc(name = "v2.send_operations")]
+    fn send_operations(&self, _: Vec<OperationInput>) -> BoxFuture<Result<Vec<String>, ApiError>>;
+
+    /// Get the final and candidate values of datastore entries.
+    #[rpc(name = "v2.get_datastore_entries")]
+    fn get_datastore_entries(
+        &self,
+        _: Vec<DatastoreEntryInput>,
+    ) -> BoxFuture<Result<Vec<DatastoreEntry>, ApiError>>;
+
+    /// Get the smart contract events matching a filter.
+    #[rpc(name = "v2.get_events")]
+    fn get_events(&self, _: EventFilter) -> BoxFuture<Result<Vec<Event>, ApiError>>;
+}
+
+/// Returns a future failing with `err`
+fn failure<T: Send + 'static>(err: ApiError) -> BoxFuture<Result<T, ApiError>> {
+    let closure = async move || Err(err);
+    Box::pin(closure())
+}
+
+impl API<Public> {
+    /// Checks the number of arguments of a call and parses them, `what` naming them in the errors
+    fn parse_arguments<T: FromStr>(
+        &self,
+        values: &[String],
+        what: &str,
+    ) -> Result<Vec<T>, ApiError> {
+        if values.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()));
+        }
+        types::parse_params(values, what)
+    }
+
+    /// Format of the addresses of the responses
+    fn address_format(&self) -> AddressFormat {
+        AddressFormat::new(self.0.api_settings.checksummed_addresses)
+    }
+
+    /// Checks the number of arguments of a call and converts them to the types of the version 1
+    fn convert_arguments<T, U>(&self, values: Vec<T>) -> Result<Vec<U>, ApiError>
+    where
+        U: TryFrom<T, Error = ApiError>,
+    {
+        if values.len() as u64 > self.0.api_settings.max_arguments {
+            return Err(ApiError::BadRequest("too many arguments".into()));
+        }
+        values.into_iter().map(U::try_from).collect()
+    }
+}
+
+#[doc(hidden)]
+impl EndpointsV2 for API<Public> {
+    fn get_status(&self) -> BoxFuture<Result<NodeStatus, ApiError>> {
+        let status = Endpoints::get_status(self);
+        let closure = async move || Ok(status.await?.into());
+        Box::pin(closure())
+    }
+
+    fn get_addresses(
+        &self,
+        addresses: Vec<String>,
+    ) -> BoxFuture<Result<Vec<AddressInfo>, ApiError>> {
+        let addresses: Vec<Address> = match self.parse_arguments(&addresses, "address") {
+            Ok(addresses) => addresses,
+            Err(err) => return failure(err),
+        };
+        let infos = Endpoints::get_addresses(self, addresses);
+        let format = self.address_format();
+        let closure = async move || {
+            Ok(infos
+                .await?
+                .into_iter()
+                .map(|info| AddressInfo::from_info(info, format))
+                .collect())
+        };
+        Box::pin(closure())
+    }
+
+    fn get_blocks(&self, ids: Vec<String>) -> BoxFuture<Result<Vec<Option<Block>>, ApiError>> {
+        let ids: Vec<BlockId> = match self.parse_arguments(&ids, "block id") {
+            Ok(ids) => ids,
+            Err(err) => return failure(err),
+        };
+        let infos: Vec<_> = ids
+            .into_iter()
+            .map(|id| Endpoints::get_block(self, id))
+            .collect();
+        let format = self.address_format();
+        let closure = async move || {
+            let mut blocks = Vec::with_capacity(infos.len());
+            for info in infos {
+                blocks.push(Block::from_info(info.await?, format));
+            }
+            Ok(blocks)
+        };
+        Box::pin(closure())
+    }
+
+    fn get_operations(&self, ids: Vec<String>) -> BoxFuture<Result<Vec<Operation>, ApiError>> {
+        let ids: Vec<OperationId> = match self.parse_arguments(&ids, "operation id") {
+            Ok(ids) => ids,
+            Err(err) => return failure(err),
+        };
+        let infos = Endpoints::get_operations(self, ids);
+        let format = self.address_format();
+        let closure = async move || {
+            Ok(infos
+                .await?
+                .into_iter()
+                .map(|info| Operation::from_info(info, format))
+                .collect())
+        };
+        Box::pin(closure())
+    }
+
+    fn send_operations(
+        &self,
+        ops: Vec<OperationInput>,
+    ) -> BoxFuture<Result<Vec<String>, ApiError>> {
+        let ops = match self.convert_arguments(ops) {
+            Ok(ops) => ops,
+            Err(err) => return failure(err),
+        };
+        let ids = Endpoints::send_operations(self, ops);
+        let closure = async move || Ok(ids.await?.iter().map(|id| id.to_string()).collect());
+        Box::pin(closure())
+    }
+
+    fn get_datastore_entries(
+        &self,
+        entries: Vec<DatastoreEntryInput>,
+    ) -> BoxFuture<Result<Vec<DatastoreEntry>, ApiError>> {
+        let entries = match self.convert_arguments(entries) {
+            Ok(entries) => entries,
+            Err(err) => return failure(err),
+        };
+        let outputs = Endpoints::get_datastore_entries(self, entries);
+        let closure = async move || Ok(outputs.await?.into_iter().map(Into::into).collect());
+        Box::pin(closure())
+    }
+
+    fn get_events(&self, filter: EventFilter) -> BoxFuture<Result<Vec<Event>, ApiError>> {
+        let filter = match filter.try_into() {
+            Ok(filter) => filter,
+            Err(err) => return failure(err),
+        };
+        let events = Endpoints::get_filtered_sc_output_event(self, filter);
+        let format = self.address_format();
+        let closure = async move || {
+            Ok(events
+                .await?
+                .into_iter()
+                .map(|event| Event::from_event(event, format))
+                .collect())
+        };
+        Box::pin(closure())
+    }
+}
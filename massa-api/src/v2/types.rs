@@ -0,0 +1,1048 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Request and response types of the version 2 of the public API.
+//!
+//! They only hold plain values: identifiers, addresses, keys and signatures are strings,
+//! amounts are decimal strings and times are milliseconds since 1970-01-01.
+//! They are converted from and to the internal types of the node here,
+//! so that changes to the internal types do not change what external clients send and receive.
+
+use crate::error::ApiError;
+use massa_models as models;
+use massa_models::address::Address;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Textual format of the addresses of the responses, selected by the `checksummed_addresses` setting.
+/// Both formats are accepted as parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressFormat {
+    /// `A` + base58check encoding, as displayed by the node
+    Base58,
+    /// `A0` + hexadecimal with case and character checksums, see `Address::to_checksummed_string`
+    Checksummed,
+}
+
+impl AddressFormat {
+    /// Format selected by the `checksummed_addresses` setting
+    pub fn new(checksummed: bool) -> Self {
+        if checksummed {
+            AddressFormat::Checksummed
+        } else {
+            AddressFormat::Base58
+        }
+    }
+
+    /// Formats an address in this format
+    pub fn format(self, address: &Address) -> String {
+        match self {
+            AddressFormat::Base58 => address.to_string(),
+            AddressFormat::Checksummed => address.to_checksummed_string(),
+        }
+    }
+}
+
+/// Parses a string parameter, `what` naming the parameter in the error
+pub(crate) fn parse_param<T: FromStr>(value: &str, what: &str) -> Result<T, ApiError> {
+    T::from_str(value).map_err(|_| ApiError::BadRequest(format!("invalid {}: {}", what, value)))
+}
+
+/// Parses a list of string parameters, `what` naming the parameters in the error
+pub(crate) fn parse_params<T: FromStr>(values: &[String], what: &str) -> Result<Vec<T>, ApiError> {
+    values
+        .iter()
+        .map(|value| parse_param(value, what))
+        .collect()
+}
+
+/// Slot: period and thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Slot {
+    /// period of the slot
+    pub period: u64,
+    /// thread of the slot
+    pub thread: u8,
+}
+
+impl From<models::slot::Slot> for Slot {
+    fn from(slot: models::slot::Slot) -> Self {
+        Slot {
+            period: slot.period,
+            thread: slot.thread,
+        }
+    }
+}
+
+impl From<Slot> for models::slot::Slot {
+    fn from(slot: Slot) -> Self {
+        models::slot::Slot::new(slot.period, slot.thread)
+    }
+}
+
+/// Peer connected to the node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectedNode {
+    /// id of the peer
+    pub node_id: String,
+    /// IP address of the peer
+    pub ip: String,
+    /// true if the connection is outgoing, false if it is incoming
+    pub is_outgoing: bool,
+}
+
+/// Parameters of the network the node belongs to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// time of the first slot, in milliseconds since 1970-01-01
+    pub genesis_timestamp: u64,
+    /// time at which the network stops, if it is a test network
+    pub end_timestamp: Option<u64>,
+    /// number of threads
+    pub thread_count: u8,
+    /// time between two periods of a thread, in milliseconds
+    pub t0: u64,
+    /// number of periods an operation is valid for
+    pub operation_validity_periods: u64,
+    /// number of periods of a cycle
+    pub periods_per_cycle: u64,
+    /// reward for producing a block, decimal amount
+    pub block_reward: String,
+    /// price of a roll, decimal amount
+    pub roll_price: String,
+    /// max size of a block, in bytes
+    pub max_block_size: u32,
+}
+
+impl From<models::config::CompactConfig> for NetworkConfig {
+    fn from(config: models::config::CompactConfig) -> Self {
+        NetworkConfig {
+            genesis_timestamp: config.genesis_timestamp.to_millis(),
+            end_timestamp: config.end_timestamp.map(|time| time.to_millis()),
+            thread_count: config.thread_count,
+            t0: config.t0.to_millis(),
+            operation_validity_periods: config.operation_validity_periods,
+            periods_per_cycle: config.periods_per_cycle,
+            block_reward: config.block_reward.to_string(),
+            roll_price: config.roll_price.to_string(),
+            max_block_size: config.max_block_size,
+        }
+    }
+}
+
+/// Status of the node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeStatus {
+    /// id of the node
+    pub node_id: String,
+    /// routable IP address of the node, if any
+    pub node_ip: Option<String>,
+    /// version of the node
+    pub version: String,
+    /// chain id of the network the node belongs to
+    pub chain_id: u64,
+    /// first period from which operations are signed along the chain id
+    pub chain_id_activation_period: u64,
+    /// current time of the node, in milliseconds since 1970-01-01
+    pub current_time: u64,
+    /// current cycle
+    pub current_cycle: u64,
+    /// latest slot, none before the genesis
+    pub last_slot: Option<Slot>,
+    /// next slot
+    pub next_slot: Slot,
+    /// peers connected to the node
+    pub connected_nodes: Vec<ConnectedNode>,
+    /// number of final blocks during the stats time span
+    pub final_block_count: u64,
+    /// number of stale blocks in memory
+    pub stale_block_count: u64,
+    /// number of cliques
+    pub clique_count: u64,
+    /// number of operations in the pool
+    pub pool_operation_count: u64,
+    /// number of endorsements in the pool
+    pub pool_endorsement_count: u64,
+    /// number of operations of the pool submitted to the node or created by it, parked ones included
+    pub pool_local_operation_count: u64,
+    /// number of operations of the pool received from the network, parked ones included
+    pub pool_network_operation_count: u64,
+    /// parameters of the network
+    pub config: NetworkConfig,
+}
+
+impl From<models::api::NodeStatus> for NodeStatus {
+    fn from(status: models::api::NodeStatus) -> Self {
+        let mut connected_nodes: Vec<ConnectedNode> = status
+            .connected_nodes
+            .into_iter()
+            .map(|(node_id, (ip, is_outgoing))| ConnectedNode {
+                node_id: node_id.to_string(),
+                ip: ip.to_string(),
+                is_outgoing,
+            })
+            .collect();
+        connected_nodes.sort_unstable_by(|a, b| a.node_id.cmp(&b.node_id));
+        NodeStatus {
+            node_id: status.node_id.to_string(),
+            node_ip: status.node_ip.map(|ip| ip.to_string()),
+            version: status.version.to_string(),
+            chain_id: status.chain_id,
+            chain_id_activation_period: status.chain_id_activation_period,
+            current_time: status.current_time.to_millis(),
+            current_cycle: status.current_cycle,
+            last_slot: status.last_slot.map(Slot::from),
+            next_slot: status.next_slot.into(),
+            connected_nodes,
+            final_block_count: status.consensus_stats.final_block_count,
+            stale_block_count: status.consensus_stats.stale_block_count,
+            clique_count: status.consensus_stats.clique_count,
+            pool_operation_count: status.pool_stats.0 as u64,
+            pool_endorsement_count: status.pool_stats.1 as u64,
+            pool_local_operation_count: status.pool_operation_origins.local as u64,
+            pool_network_operation_count: status.pool_operation_origins.network as u64,
+            config: status.config.into(),
+        }
+    }
+}
+
+/// Amount credited at a slot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlotAmount {
+    /// slot of the credit
+    pub slot: Slot,
+    /// decimal amount
+    pub amount: String,
+}
+
+/// Endorsement draw
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndorsementDraw {
+    /// slot of the endorsed block
+    pub slot: Slot,
+    /// index of the endorsement in the block
+    pub index: u64,
+}
+
+/// Production statistics of an address during a cycle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressCycleInfo {
+    /// cycle number
+    pub cycle: u64,
+    /// true if the cycle is final
+    pub is_final: bool,
+    /// number of blocks produced by the address
+    pub ok_count: u64,
+    /// number of blocks missed by the address
+    pub nok_count: u64,
+    /// number of active rolls of the address, if still known
+    pub active_rolls: Option<u64>,
+}
+
+/// State of an address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressInfo {
+    /// the address
+    pub address: String,
+    /// thread of the address
+    pub thread: u8,
+    /// final balance, decimal amount
+    pub final_balance: String,
+    /// candidate balance, decimal amount
+    pub candidate_balance: String,
+    /// final roll count
+    pub final_roll_count: u64,
+    /// candidate roll count
+    pub candidate_roll_count: u64,
+    /// final datastore keys
+    pub final_datastore_keys: Vec<Vec<u8>>,
+    /// candidate datastore keys
+    pub candidate_datastore_keys: Vec<Vec<u8>>,
+    /// upcoming deferred credits
+    pub deferred_credits: Vec<SlotAmount>,
+    /// upcoming block draws
+    pub next_block_draws: Vec<Slot>,
+    /// upcoming endorsement draws
+    pub next_endorsement_draws: Vec<EndorsementDraw>,
+    /// ids of the blocks created by the address
+    pub created_blocks: Vec<String>,
+    /// ids of the operations created by the address
+    pub created_operations: Vec<String>,
+    /// ids of the endorsements created by the address
+    pub created_endorsements: Vec<String>,
+    /// production statistics per cycle
+    pub cycle_infos: Vec<AddressCycleInfo>,
+}
+
+impl AddressInfo {
+    /// Builds the state of an address from the information about it
+    pub(crate) fn from_info(info: models::api::AddressInfo, format: AddressFormat) -> Self {
+        AddressInfo {
+            address: format.format(&info.address),
+            thread: info.thread,
+            final_balance: info.final_balance.to_string(),
+            candidate_balance: info.candidate_balance.to_string(),
+            final_roll_count: info.final_roll_count,
+            candidate_roll_count: info.candidate_roll_count,
+            final_datastore_keys: info.final_datastore_keys,
+            candidate_datastore_keys: info.candidate_datastore_keys,
+            deferred_credits: info
+                .deferred_credits
+                .into_iter()
+                .map(|credit| SlotAmount {
+                    slot: credit.slot.into(),
+                    amount: credit.amount.to_string(),
+                })
+                .collect(),
+            next_block_draws: info.next_block_draws.into_iter().map(Slot::from).collect(),
+            next_endorsement_draws: info
+                .next_endorsement_draws
+                .into_iter()
+                .map(|draw| EndorsementDraw {
+                    slot: draw.slot.into(),
+                    index: draw.index as u64,
+                })
+                .collect(),
+            created_blocks: info
+                .created_blocks
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+            created_operations: info
+                .created_operations
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+            created_endorsements: info
+                .created_endorsements
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+            cycle_infos: info
+                .cycle_infos
+                .into_iter()
+                .map(|cycle_info| AddressCycleInfo {
+                    cycle: cycle_info.cycle,
+                    is_final: cycle_info.is_final,
+                    ok_count: cycle_info.ok_count,
+                    nok_count: cycle_info.nok_count,
+                    active_rolls: cycle_info.active_rolls,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Reason why a block was discarded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockDiscardReason {
+    /// not newer than the latest final block of its thread
+    Late,
+    /// incompatible with a final block, or depending on a stale block
+    Stale,
+    /// final and pruned from the graph
+    Final,
+    /// its creator was not drawn to produce it
+    WrongCreator,
+    /// one of its endorsements was not created by the address drawn for it
+    WrongEndorser,
+    /// its parents are invalid
+    ParentMismatch,
+    /// it depends on an invalid block
+    InvalidDependency,
+    /// its content is invalid
+    InvalidContent,
+}
+
+impl From<models::api::BlockDiscardKind> for BlockDiscardReason {
+    fn from(kind: models::api::BlockDiscardKind) -> Self {
+        use models::api::BlockDiscardKind;
+        match kind {
+            BlockDiscardKind::Late => BlockDiscardReason::Late,
+            BlockDiscardKind::Stale => BlockDiscardReason::Stale,
+            BlockDiscardKind::Final => BlockDiscardReason::Final,
+            BlockDiscardKind::WrongCreator => BlockDiscardReason::WrongCreator,
+            BlockDiscardKind::WrongEndorser => BlockDiscardReason::WrongEndorser,
+            BlockDiscardKind::ParentMismatch => BlockDiscardReason::ParentMismatch,
+            BlockDiscardKind::InvalidDependency => BlockDiscardReason::InvalidDependency,
+            BlockDiscardKind::InvalidContent => BlockDiscardReason::InvalidContent,
+        }
+    }
+}
+
+/// Status of a block in the graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockStatus {
+    /// final
+    Final,
+    /// in the blockclique, not final yet
+    InBlockclique,
+    /// in a clique other than the blockclique
+    Candidate,
+    /// discarded
+    Discarded,
+}
+
+/// Endorsement included in a block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Endorsement {
+    /// id of the endorsement
+    pub id: String,
+    /// address of the endorser
+    pub creator_address: String,
+    /// slot of the endorsed block
+    pub slot: Slot,
+    /// index of the endorsement in the block
+    pub index: u32,
+    /// id of the endorsed block
+    pub endorsed_block: String,
+}
+
+/// Block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Block {
+    /// id of the block
+    pub id: String,
+    /// status of the block
+    pub status: BlockStatus,
+    /// reason why the block was discarded, if it was
+    pub discard_reason: Option<BlockDiscardReason>,
+    /// sum of the fitness of the block and of its descendants, if the block is active
+    pub confirmation_weight: Option<u64>,
+    /// address of the creator of the block
+    pub creator_address: String,
+    /// public key of the creator of the block
+    pub creator_public_key: String,
+    /// signature of the header of the block
+    pub signature: String,
+    /// slot of the block
+    pub slot: Slot,
+    /// ids of the parents of the block, one per thread
+    pub parents: Vec<String>,
+    /// endorsements of the block
+    pub endorsements: Vec<Endorsement>,
+    /// ids of the operations of the block, in block order
+    pub operations: Vec<String>,
+}
+
+impl Block {
+    /// Builds a block from the information about it, `None` if its content is unknown
+    pub(crate) fn from_info(info: models::api::BlockInfo, format: AddressFormat) -> Option<Self> {
+        let content = info.content?;
+        let status = if content.is_final {
+            BlockStatus::Final
+        } else if content.is_in_blockclique {
+            BlockStatus::InBlockclique
+        } else if content.is_candidate {
+            BlockStatus::Candidate
+        } else {
+            BlockStatus::Discarded
+        };
+        let header = &content.block.header;
+        Some(Block {
+            id: info.id.to_string(),
+            status,
+            discard_reason: content.discard_reason.map(BlockDiscardReason::from),
+            confirmation_weight: content.confirmation_weight,
+            creator_address: format.format(&header.creator_address),
+            creator_public_key: header.creator_public_key.to_string(),
+            signature: header.signature.to_string(),
+            slot: header.content.slot.into(),
+            parents: header
+                .content
+                .parents
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+            endorsements: header
+                .content
+                .endorsements
+                .iter()
+                .map(|endorsement| Endorsement {
+                    id: endorsement.id.to_string(),
+                    creator_address: format.format(&endorsement.creator_address),
+                    slot: endorsement.content.slot.into(),
+                    index: endorsement.content.index,
+                    endorsed_block: endorsement.content.endorsed_block.to_string(),
+                })
+                .collect(),
+            operations: content
+                .block
+                .operations
+                .iter()
+                .map(|id| id.to_string())
+                .collect(),
+        })
+    }
+}
+
+/// Content specific to the kind of an operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OperationKind {
+    /// transfer of coins
+    Transaction {
+        /// address receiving the coins
+        recipient_address: String,
+        /// decimal amount transferred
+        amount: String,
+    },
+    /// purchase of rolls
+    RollBuy {
+        /// number of rolls bought
+        roll_count: u64,
+    },
+    /// sale of rolls
+    RollSell {
+        /// number of rolls sold
+        roll_count: u64,
+    },
+    /// execution of a smart contract bytecode
+    ExecuteSc {
+        /// bytecode to execute
+        bytecode: Vec<u8>,
+        /// max gas of the execution
+        max_gas: u64,
+        /// price of a unit of gas, decimal amount
+        gas_price: String,
+        /// datastore of the operation, as key-value pairs
+        datastore: Vec<(Vec<u8>, Vec<u8>)>,
+    },
+    /// call of a function of a smart contract
+    CallSc {
+        /// address of the called smart contract
+        target_address: String,
+        /// name of the called function
+        target_function: String,
+        /// parameter passed to the function
+        parameter: String,
+        /// max gas of the call
+        max_gas: u64,
+        /// coins transferred to the smart contract, decimal amount
+        coins: String,
+        /// price of a unit of gas, decimal amount
+        gas_price: String,
+    },
+}
+
+impl OperationKind {
+    /// Builds the content specific to the kind of an operation from its type
+    pub(crate) fn from_type(op: &models::operation::OperationType, format: AddressFormat) -> Self {
+        use models::operation::OperationType;
+        match op {
+            OperationType::Transaction {
+                recipient_address,
+                amount,
+            } => OperationKind::Transaction {
+                recipient_address: format.format(recipient_address),
+                amount: amount.to_string(),
+            },
+            OperationType::RollBuy { roll_count } => OperationKind::RollBuy {
+                roll_count: *roll_count,
+            },
+            OperationType::RollSell { roll_count } => OperationKind::RollSell {
+                roll_count: *roll_count,
+            },
+            OperationType::ExecuteSC {
+                data,
+                max_gas,
+                gas_price,
+                datastore,
+            } => OperationKind::ExecuteSc {
+                bytecode: data.clone(),
+                max_gas: *max_gas,
+                gas_price: gas_price.to_string(),
+                datastore: datastore
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect(),
+            },
+            OperationType::CallSC {
+                target_addr,
+                target_func,
+                param,
+                max_gas,
+                coins,
+                gas_price,
+            } => OperationKind::CallSc {
+                target_address: format.format(target_addr),
+                target_function: target_func.clone(),
+                parameter: param.clone(),
+                max_gas: *max_gas,
+                coins: coins.to_string(),
+                gas_price: gas_price.to_string(),
+            },
+        }
+    }
+}
+
+/// Progress of an operation, from the pool to its final execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationStatus {
+    /// waiting in the pool
+    Pending,
+    /// included in a block, not executed yet
+    Included,
+    /// executed successfully in a candidate block
+    ExecutedSuccess,
+    /// executed in a candidate block, the execution failed
+    ExecutedFailure,
+    /// the execution is final
+    Final,
+}
+
+impl From<models::api::OperationExecutionStatus> for OperationStatus {
+    fn from(status: models::api::OperationExecutionStatus) -> Self {
+        use models::api::OperationExecutionStatus;
+        match status {
+            OperationExecutionStatus::Pending => OperationStatus::Pending,
+            OperationExecutionStatus::Included => OperationStatus::Included,
+            OperationExecutionStatus::ExecutedSuccess => OperationStatus::ExecutedSuccess,
+            OperationExecutionStatus::ExecutedFailure => OperationStatus::ExecutedFailure,
+            OperationExecutionStatus::Final => OperationStatus::Final,
+        }
+    }
+}
+
+/// Outcome of the execution of an operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationExecution {
+    /// id of the block the operation was executed in
+    pub block_id: String,
+    /// slot of that block
+    pub slot: Slot,
+    /// true if the execution is final
+    pub is_final: bool,
+    /// gas used by the execution
+    pub gas_used: u64,
+    /// error of the execution, none if it succeeded
+    pub error: Option<String>,
+}
+
+/// Operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operation {
+    /// id of the operation
+    pub id: String,
+    /// progress of the operation
+    pub status: OperationStatus,
+    /// true if the operation is in the pool
+    pub in_pool: bool,
+    /// ids of the blocks including the operation, in different cliques
+    pub in_blocks: Vec<String>,
+    /// outcome of the execution of the operation, if it is remembered
+    pub execution: Option<OperationExecution>,
+    /// address of the creator of the operation
+    pub creator_address: String,
+    /// public key of the creator of the operation
+    pub creator_public_key: String,
+    /// signature of the operation
+    pub signature: String,
+    /// fee of the operation, decimal amount
+    pub fee: String,
+    /// last period at which the operation can be included in a block
+    pub expire_period: u64,
+    /// content specific to the kind of the operation
+    #[serde(flatten)]
+    pub kind: OperationKind,
+}
+
+impl Operation {
+    /// Builds an operation from the information about it
+    pub(crate) fn from_info(info: models::api::OperationInfo, format: AddressFormat) -> Self {
+        Operation {
+            id: info.id.to_string(),
+            status: info.execution_status.into(),
+            in_pool: info.in_pool,
+            in_blocks: info.in_blocks.iter().map(|id| id.to_string()).collect(),
+            execution: info.execution.map(|execution| OperationExecution {
+                block_id: execution.block_id.to_string(),
+                slot: execution.slot.into(),
+                is_final: execution.is_final,
+                gas_used: execution.gas_used,
+                error: execution.execution_error,
+            }),
+            creator_address: format.format(&info.operation.creator_address),
+            creator_public_key: info.operation.creator_public_key.to_string(),
+            signature: info.operation.signature.to_string(),
+            fee: info.operation.content.fee.to_string(),
+            expire_period: info.operation.content.expire_period,
+            kind: OperationKind::from_type(&info.operation.content.op, format),
+        }
+    }
+}
+
+/// Signed operation to send to the network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationInput {
+    /// public key of the creator of the operation
+    pub creator_public_key: String,
+    /// signature of the serialized content
+    pub signature: String,
+    /// serialized content of the operation
+    pub serialized_content: Vec<u8>,
+}
+
+impl TryFrom<OperationInput> for models::api::OperationInput {
+    type Error = ApiError;
+
+    fn try_from(input: OperationInput) -> Result<Self, ApiError> {
+        Ok(models::api::OperationInput {
+            creator_public_key: parse_param(&input.creator_public_key, "public key")?,
+            signature: parse_param(&input.signature, "signature")?,
+            serialized_content: input.serialized_content,
+        })
+    }
+}
+
+/// Datastore entry to read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatastoreEntryInput {
+    /// address owning the entry
+    pub address: String,
+    /// key of the entry
+    pub key: Vec<u8>,
+}
+
+impl TryFrom<DatastoreEntryInput> for models::api::DatastoreEntryInput {
+    type Error = ApiError;
+
+    fn try_from(input: DatastoreEntryInput) -> Result<Self, ApiError> {
+        Ok(models::api::DatastoreEntryInput {
+            address: parse_param(&input.address, "address")?,
+            key: input.key,
+        })
+    }
+}
+
+/// Final and candidate values of a datastore entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatastoreEntry {
+    /// final value, none if the entry does not exist
+    pub final_value: Option<Vec<u8>>,
+    /// candidate value, none if the entry does not exist
+    pub candidate_value: Option<Vec<u8>>,
+}
+
+impl From<models::api::DatastoreEntryOutput> for DatastoreEntry {
+    fn from(output: models::api::DatastoreEntryOutput) -> Self {
+        DatastoreEntry {
+            final_value: output.final_value,
+            candidate_value: output.candidate_value,
+        }
+    }
+}
+
+/// Filter of the smart contract events, every field being optional
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventFilter {
+    /// first slot of the events (included)
+    pub start: Option<Slot>,
+    /// last slot of the events (included)
+    pub end: Option<Slot>,
+    /// address of the contract that emitted the events
+    pub emitter_address: Option<String>,
+    /// address of the original caller
+    pub original_caller_address: Option<String>,
+    /// id of the operation that triggered the events
+    pub original_operation_id: Option<String>,
+    /// true for final events only, false for candidate events only, none for both
+    pub is_final: Option<bool>,
+}
+
+impl TryFrom<EventFilter> for models::api::EventFilter {
+    type Error = ApiError;
+
+    fn try_from(filter: EventFilter) -> Result<Self, ApiError> {
+        Ok(models::api::EventFilter {
+            start: filter.start.map(Into::into),
+            end: filter.end.map(Into::into),
+            emitter_address: filter
+                .emitter_address
+                .map(|address| parse_param(&address, "address"))
+                .transpose()?,
+            original_caller_address: filter
+                .original_caller_address
+                .map(|address| parse_param(&address, "address"))
+                .transpose()?,
+            original_operation_id: filter
+                .original_operation_id
+                .map(|id| parse_param(&id, "operation id"))
+                .transpose()?,
+            is_final: filter.is_final,
+        })
+    }
+}
+
+/// Event emitted by a smart contract
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    /// slot of the execution that emitted the event
+    pub slot: Slot,
+    /// id of the block executed at that slot, if any
+    pub block_id: Option<String>,
+    /// index of the event in the slot
+    pub index_in_slot: u64,
+    /// true if the event was emitted by a read-only execution
+    pub read_only: bool,
+    /// true if the event is final
+    pub is_final: bool,
+    /// call stack of the emission, most recent call last
+    pub call_stack: Vec<String>,
+    /// id of the operation that triggered the event, if any
+    pub origin_operation_id: Option<String>,
+    /// data of the event
+    pub data: String,
+}
+
+impl Event {
+    /// Builds an event from a smart contract output event
+    pub(crate) fn from_event(
+        event: models::output_event::SCOutputEvent,
+        format: AddressFormat,
+    ) -> Self {
+        let context = event.context;
+        Event {
+            slot: context.slot.into(),
+            block_id: context.block.map(|id| id.to_string()),
+            index_in_slot: context.index_in_slot,
+            read_only: context.read_only,
+            is_final: context.is_final,
+            call_stack: context
+                .call_stack
+                .iter()
+                .map(|address| format.format(address))
+                .collect(),
+            origin_operation_id: context.origin_operation_id.map(|id| id.to_string()),
+            data: event.data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpc_core::serde_json::{self, json};
+    use massa_hash::Hash;
+    use massa_models::{
+        amount::Amount,
+        block::{BlockHeader, BlockHeaderSerializer, BlockId},
+        operation::{OperationId, OperationType},
+        wrapped::{Id, WrappedContent},
+    };
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_slot_conversion() {
+        let slot = models::slot::Slot::new(12, 3);
+        let api_slot = Slot::from(slot);
+        assert_eq!(
+            serde_json::to_value(api_slot).unwrap(),
+            json!({"period": 12, "thread": 3})
+        );
+        assert_eq!(models::slot::Slot::from(api_slot), slot);
+    }
+
+    #[test]
+    fn test_status_conversions() {
+        use models::api::{BlockDiscardKind, OperationExecutionStatus};
+        assert_eq!(
+            serde_json::to_value(BlockDiscardReason::from(BlockDiscardKind::WrongCreator)).unwrap(),
+            json!("wrong_creator")
+        );
+        assert_eq!(
+            BlockDiscardReason::from(BlockDiscardKind::InvalidDependency),
+            BlockDiscardReason::InvalidDependency
+        );
+        assert_eq!(
+            OperationStatus::from(OperationExecutionStatus::ExecutedFailure),
+            OperationStatus::ExecutedFailure
+        );
+        assert_eq!(
+            serde_json::to_value(OperationStatus::from(OperationExecutionStatus::Final)).unwrap(),
+            json!("final")
+        );
+    }
+
+    #[test]
+    fn test_block_conversion() {
+        let keypair = KeyPair::generate();
+        let parents = vec![
+            BlockId(Hash::compute_from(b"parent 0")),
+            BlockId(Hash::compute_from(b"parent 1")),
+        ];
+        let header = BlockHeader::new_wrapped(
+            BlockHeader {
+                slot: models::slot::Slot::new(4, 1),
+                parents: parents.clone(),
+                operation_merkle_root: Hash::compute_from(&Vec::new()),
+                endorsements: Vec::new(),
+            },
+            BlockHeaderSerializer::new(),
+            &keypair,
+        )
+        .unwrap();
+        let id = BlockId(Hash::compute_from(b"block"));
+        let info = |is_final, discard_reason| models::api::BlockInfo {
+            id,
+            content: Some(models::api::BlockInfoContent {
+                is_final,
+                is_in_blockclique: false,
+                is_candidate: false,
+                is_discarded: discard_reason.is_some(),
+                discard_reason,
+                confirmation_weight: Some(7).filter(|_| is_final),
+                block: models::block::Block {
+                    header: header.clone(),
+                    operations: Vec::new(),
+                },
+            }),
+        };
+
+        let block = Block::from_info(info(true, None), AddressFormat::Base58).unwrap();
+        assert_eq!(block.id, id.to_string());
+        assert_eq!(block.status, BlockStatus::Final);
+        assert_eq!(block.discard_reason, None);
+        assert_eq!(block.confirmation_weight, Some(7));
+        assert_eq!(block.creator_address, header.creator_address.to_string());
+        assert_eq!(
+            block.creator_public_key,
+            keypair.get_public_key().to_string()
+        );
+        assert_eq!(
+            block.slot,
+            Slot {
+                period: 4,
+                thread: 1
+            }
+        );
+        assert_eq!(
+            block.parents,
+            parents.iter().map(|id| id.to_string()).collect::<Vec<_>>()
+        );
+        assert!(block.endorsements.is_empty() && block.operations.is_empty());
+
+        let block = Block::from_info(
+            info(false, Some(models::api::BlockDiscardKind::Stale)),
+            AddressFormat::Base58,
+        )
+        .unwrap();
+        assert_eq!(block.status, BlockStatus::Discarded);
+        assert_eq!(block.discard_reason, Some(BlockDiscardReason::Stale));
+        assert_eq!(block.confirmation_weight, None);
+
+        // blocks of unknown content are left out
+        assert!(Block::from_info(
+            models::api::BlockInfo { id, content: None },
+            AddressFormat::Base58
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_operation_kind_conversion() {
+        let recipient = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let transaction = OperationType::Transaction {
+            recipient_address: recipient,
+            amount: Amount::from_str("1.5").unwrap(),
+        };
+        let kind = OperationKind::from_type(&transaction, AddressFormat::Base58);
+        assert_eq!(
+            serde_json::to_value(kind).unwrap(),
+            json!({
+                "kind": "transaction",
+                "recipient_address": recipient.to_string(),
+                "amount": "1.5"
+            })
+        );
+        let kind = OperationKind::from_type(&transaction, AddressFormat::Checksummed);
+        assert_eq!(
+            serde_json::to_value(kind).unwrap(),
+            json!({
+                "kind": "transaction",
+                "recipient_address": recipient.to_checksummed_string(),
+                "amount": "1.5"
+            })
+        );
+        let kind = OperationKind::from_type(
+            &OperationType::RollSell { roll_count: 3 },
+            AddressFormat::Checksummed,
+        );
+        assert_eq!(
+            serde_json::to_value(kind).unwrap(),
+            json!({"kind": "roll_sell", "roll_count": 3})
+        );
+    }
+
+    #[test]
+    fn test_input_conversions() {
+        let keypair = KeyPair::generate();
+        let signature = keypair.sign(&Hash::compute_from(b"content")).unwrap();
+        let input = OperationInput {
+            creator_public_key: keypair.get_public_key().to_string(),
+            signature: signature.to_string(),
+            serialized_content: b"content".to_vec(),
+        };
+        let converted = models::api::OperationInput::try_from(input.clone()).unwrap();
+        assert_eq!(converted.creator_public_key, keypair.get_public_key());
+        assert_eq!(converted.signature, signature);
+        assert_eq!(converted.serialized_content, b"content".to_vec());
+        assert!(matches!(
+            models::api::OperationInput::try_from(OperationInput {
+                creator_public_key: "not a key".to_string(),
+                ..input
+            }),
+            Err(ApiError::BadRequest(_))
+        ));
+
+        let address = Address::from_public_key(&keypair.get_public_key());
+        let entry = models::api::DatastoreEntryInput::try_from(DatastoreEntryInput {
+            address: address.to_string(),
+            key: b"key".to_vec(),
+        })
+        .unwrap();
+        assert_eq!(entry.address, address);
+        assert_eq!(entry.key, b"key".to_vec());
+        assert!(matches!(
+            models::api::DatastoreEntryInput::try_from(DatastoreEntryInput {
+                address: "not an address".to_string(),
+                key: Vec::new(),
+            }),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_event_filter_conversion() {
+        // every field is optional
+        let filter: EventFilter = serde_json::from_value(json!({
+            "start": {"period": 1, "thread": 0},
+            "is_final": true
+        }))
+        .unwrap();
+        let converted = models::api::EventFilter::try_from(filter).unwrap();
+        assert_eq!(converted.start, Some(models::slot::Slot::new(1, 0)));
+        assert_eq!(converted.end, None);
+        assert_eq!(converted.emitter_address, None);
+        assert_eq!(converted.is_final, Some(true));
+
+        let operation_id = OperationId::new(Hash::compute_from(b"operation"));
+        let converted = models::api::EventFilter::try_from(EventFilter {
+            original_operation_id: Some(operation_id.to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(converted.original_operation_id, Some(operation_id));
+
+        assert!(matches!(
+            models::api::EventFilter::try_from(EventFilter {
+                emitter_address: Some("not an address".to_string()),
+                ..Default::default()
+            }),
+            Err(ApiError::BadRequest(_))
+        ));
+    }
+}
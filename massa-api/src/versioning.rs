@@ -0,0 +1,202 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Versions of the API served over HTTP.
+//!
+//! The version targeted by a call is given by the path of its HTTP request:
+//! `/api/v2` for the version 2, any other path for the version 1.
+//! The methods of the version 2 are registered with the `v2.` prefix,
+//! which is added to the calls made on the version 2 path and refused on the other paths.
+//! The methods of the version 1 that have a version 2 equivalent are deprecated:
+//! the first call to each of them is logged with its replacement.
+
+use jsonrpc_core::futures::future::Either;
+use jsonrpc_core::{BoxFuture, Call, Metadata, Middleware, Output};
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::future::Future;
+use tracing::warn;
+
+/// Path of the HTTP requests targeting the version 2 of the API
+pub const API_V2_PATH: &str = "/api/v2";
+
+/// Prefix of the names under which the methods of the version 2 are registered
+pub(crate) const V2_METHOD_PREFIX: &str = "v2.";
+
+/// Methods of the version 1 that are deprecated, along with their version 2 replacements
+const DEPRECATED_V1_METHODS: &[(&str, &str)] = &[
+    ("get_status", "get_status"),
+    ("get_addresses", "get_addresses"),
+    ("get_block", "get_blocks"),
+    ("get_operations", "get_operations"),
+    ("send_operations", "send_operations"),
+    ("get_datastore_entries", "get_datastore_entries"),
+    ("get_filtered_sc_output_event", "get_events"),
+];
+
+/// Version of the API targeted by a call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum ApiVersion {
+    /// historical API, using the internal types of the node
+    #[default]
+    V1,
+    /// API using its own request and response types, see `crate::v2::types`
+    V2,
+}
+
+impl Metadata for ApiVersion {}
+
+impl ApiVersion {
+    /// Gets the version targeted by an HTTP request from its path
+    pub fn from_path(path: &str) -> Self {
+        if path.trim_end_matches('/') == API_V2_PATH {
+            ApiVersion::V2
+        } else {
+            ApiVersion::V1
+        }
+    }
+}
+
+/// Middleware routing the calls to the methods of the version of the API they target
+#[derive(Default)]
+pub(crate) struct VersionRouter {
+    /// deprecated methods whose use was already logged
+    logged_deprecations: Mutex<HashSet<&'static str>>,
+}
+
+impl VersionRouter {
+    /// Logs the first call to a deprecated method of the version 1
+    fn log_deprecation(&self, method: &str) {
+        if let Some((deprecated, replacement)) = DEPRECATED_V1_METHODS
+            .iter()
+            .find(|(deprecated, _)| *deprecated == method)
+        {
+            if self.logged_deprecations.lock().insert(*deprecated) {
+                warn!(
+                    "deprecated API method {} called, use {} on {} instead",
+                    deprecated, replacement, API_V2_PATH
+                );
+            }
+        }
+    }
+}
+
+impl Middleware<ApiVersion> for VersionRouter {
+    type Future = jsonrpc_core::middleware::NoopFuture;
+    type CallFuture = BoxFuture<Option<Output>>;
+
+    fn on_call<F, X>(
+        &self,
+        mut call: Call,
+        version: ApiVersion,
+        next: F,
+    ) -> Either<Self::CallFuture, X>
+    where
+        F: Fn(Call, ApiVersion) -> X + Send + Sync,
+        X: Future<Output = Option<Output>> + Send + 'static,
+    {
+        let method = match &mut call {
+            Call::MethodCall(method_call) => &mut method_call.method,
+            Call::Notification(notification) => &mut notification.method,
+            Call::Invalid { .. } => return Either::Right(next(call, version)),
+        };
+        match version {
+            ApiVersion::V2 => method.insert_str(0, V2_METHOD_PREFIX),
+            ApiVersion::V1 => {
+                // the methods of the version 2 are only served on its own path
+                if method.starts_with(V2_METHOD_PREFIX) {
+                    let output = method_not_found(call);
+                    return Either::Left(Box::pin(std::future::ready(output)));
+                }
+                self.log_deprecation(method);
+            }
+        }
+        Either::Right(next(call, version))
+    }
+}
+
+/// Output of a call to an unknown method, none for a notification
+fn method_not_found(call: Call) -> Option<Output> {
+    match call {
+        Call::MethodCall(method_call) => Some(Output::from(
+            Err(jsonrpc_core::Error::method_not_found()),
+            method_call.id,
+            method_call.jsonrpc,
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApiVersion, VersionRouter};
+    use jsonrpc_core::{serde_json, ErrorCode, MetaIoHandler, Value};
+
+    #[test]
+    fn test_api_version_from_path() {
+        assert_eq!(ApiVersion::from_path("/api/v2"), ApiVersion::V2);
+        assert_eq!(ApiVersion::from_path("/api/v2/"), ApiVersion::V2);
+        assert_eq!(ApiVersion::from_path("/"), ApiVersion::V1);
+        assert_eq!(ApiVersion::from_path(""), ApiVersion::V1);
+        assert_eq!(ApiVersion::from_path("/api"), ApiVersion::V1);
+        assert_eq!(ApiVersion::from_path("/api/v2/get_status"), ApiVersion::V1);
+        assert_eq!(ApiVersion::from_path("/API/V2"), ApiVersion::V1);
+    }
+
+    /// Calls `method` on a handler serving `get_status` in both versions, returning the result
+    /// or the error code of the call
+    fn call(method: &str, version: ApiVersion) -> Result<Value, i64> {
+        let mut io = MetaIoHandler::with_middleware(VersionRouter::default());
+        io.add_sync_method("get_status", |_| Ok(Value::from("v1")));
+        io.add_sync_method("v2.get_status", |_| Ok(Value::from("v2")));
+        io.add_sync_method("v2.get_events", |_| Ok(Value::from("v2 events")));
+        let request = format!(
+            r#"{{"jsonrpc": "2.0", "method": "{}", "params": [], "id": 1}}"#,
+            method
+        );
+        let response: Value = serde_json::from_str(
+            &io.handle_request_sync(&request, version)
+                .expect("a method call has a response"),
+        )
+        .unwrap();
+        match response.get("error") {
+            Some(error) => Err(error["code"].as_i64().unwrap()),
+            None => Ok(response["result"].clone()),
+        }
+    }
+
+    #[test]
+    fn test_version_router() {
+        let not_found = ErrorCode::MethodNotFound.code();
+
+        // the calls made on the version 2 path are prefixed
+        assert_eq!(call("get_status", ApiVersion::V2), Ok(Value::from("v2")));
+        assert_eq!(
+            call("get_events", ApiVersion::V2),
+            Ok(Value::from("v2 events"))
+        );
+        assert_eq!(call("v2.get_status", ApiVersion::V2), Err(not_found));
+
+        // the version 2 methods are refused on the other paths
+        assert_eq!(call("get_status", ApiVersion::V1), Ok(Value::from("v1")));
+        assert_eq!(call("v2.get_status", ApiVersion::V1), Err(not_found));
+        assert_eq!(call("get_events", ApiVersion::V1), Err(not_found));
+
+        // refused notifications have no response
+        let mut io = MetaIoHandler::with_middleware(VersionRouter::default());
+        io.add_sync_method("v2.get_status", |_| Ok(Value::from("v2")));
+        let notification = r#"{"jsonrpc": "2.0", "method": "v2.get_status", "params": []}"#;
+        assert_eq!(io.handle_request_sync(notification, ApiVersion::V1), None);
+    }
+
+    #[test]
+    fn test_deprecations_logged_once() {
+        let router = VersionRouter::default();
+        router.log_deprecation("get_block");
+        router.log_deprecation("get_block");
+        router.log_deprecation("get_status");
+        router.log_deprecation("get_stakers");
+        let logged = router.logged_deprecations.lock();
+        assert_eq!(logged.len(), 2);
+        assert!(logged.contains("get_block") && logged.contains("get_status"));
+    }
+}
@@ -9,7 +9,7 @@ use crate::{
     AsyncMessageDeserializer, AsyncMessageIdDeserializer, AsyncMessageIdSerializer,
     AsyncMessageSerializer,
 };
-use massa_models::{slot::Slot, streaming_step::StreamingStep};
+use massa_models::{address::Address, slot::Slot, streaming_step::StreamingStep};
 use massa_serialization::{
     Deserializer, SerializeError, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
@@ -22,6 +22,29 @@ use nom::{
 use std::collections::BTreeMap;
 use std::ops::Bound::{Excluded, Included, Unbounded};
 
+/// Pending messages of the pool sent to the same destination address
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DestinationUsage {
+    /// number of messages
+    pub message_count: u64,
+    /// sum of the `max_gas` of the messages
+    pub total_gas: u64,
+}
+
+impl DestinationUsage {
+    /// Accounts for one more message
+    pub fn add(&mut self, msg: &AsyncMessage) {
+        self.message_count = self.message_count.saturating_add(1);
+        self.total_gas = self.total_gas.saturating_add(msg.max_gas);
+    }
+
+    /// Stops accounting for a message
+    fn remove(&mut self, msg: &AsyncMessage) {
+        self.message_count = self.message_count.saturating_sub(1);
+        self.total_gas = self.total_gas.saturating_sub(msg.max_gas);
+    }
+}
+
 /// Represents a pool of sorted messages in a deterministic way.
 /// The final asynchronous pool is attached to the output of the latest final slot within the context of massa-final-state.
 /// Nodes must bootstrap the final message pool when they join the network.
@@ -32,6 +55,10 @@ pub struct AsyncPool {
 
     /// Messages sorted by decreasing ID (decreasing priority)
     pub(crate) messages: BTreeMap<AsyncMessageId, AsyncMessage>,
+
+    /// Usage of the pool by each destination address.
+    /// Derived from `messages`: it is neither serialized nor bootstrapped, but rebuilt along with them.
+    destination_usage: BTreeMap<Address, DestinationUsage>,
 }
 
 impl AsyncPool {
@@ -40,6 +67,48 @@ impl AsyncPool {
         AsyncPool {
             config,
             messages: Default::default(),
+            destination_usage: Default::default(),
+        }
+    }
+
+    /// Gets the number of messages and the total gas of the messages of the pool sent to `destination`
+    pub fn get_destination_usage(&self, destination: &Address) -> DestinationUsage {
+        self.destination_usage
+            .get(destination)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Accounts for a message added to `messages`
+    fn track_message(&mut self, msg: &AsyncMessage) {
+        self.destination_usage
+            .entry(msg.destination)
+            .or_default()
+            .add(msg);
+    }
+
+    /// Stops accounting for a message removed from `messages`
+    fn untrack_message(&mut self, msg: &AsyncMessage) {
+        if let Some(usage) = self.destination_usage.get_mut(&msg.destination) {
+            usage.remove(msg);
+            if usage.message_count == 0 {
+                self.destination_usage.remove(&msg.destination);
+            }
+        }
+    }
+
+    /// Removes a message, if present
+    fn remove_message(&mut self, message_id: &AsyncMessageId) -> Option<AsyncMessage> {
+        let msg = self.messages.remove(message_id)?;
+        self.untrack_message(&msg);
+        Some(msg)
+    }
+
+    /// Inserts a message, replacing the one with the same ID if any
+    fn insert_message(&mut self, msg_id: AsyncMessageId, msg: AsyncMessage) {
+        self.track_message(&msg);
+        if let Some(previous) = self.messages.insert(msg_id, msg) {
+            self.untrack_message(&previous);
         }
     }
 
@@ -53,12 +122,12 @@ impl AsyncPool {
             match change {
                 // add a new message to the pool
                 Change::Add(msg_id, msg) => {
-                    self.messages.insert(*msg_id, msg.clone());
+                    self.insert_message(*msg_id, msg.clone());
                 }
 
                 // delete a message from the pool
                 Change::Delete(msg_id) => {
-                    self.messages.remove(msg_id);
+                    self.remove_message(msg_id);
                 }
             }
         }
@@ -87,11 +156,16 @@ impl AsyncPool {
         let mut eliminated: Vec<_> = self
             .messages
             .drain_filter(|_k, v| *slot >= v.validity_end)
-            .chain(new_messages.drain_filter(|(_k, v)| *slot >= v.validity_end))
             .collect();
+        for (_id, msg) in eliminated.iter() {
+            self.untrack_message(msg);
+        }
+        eliminated.extend(new_messages.drain_filter(|(_k, v)| *slot >= v.validity_end));
 
         // Insert new messages into the pool
-        self.messages.extend(new_messages.clone());
+        for (msg_id, msg) in new_messages.iter() {
+            self.insert_message(*msg_id, msg.clone());
+        }
 
         // Truncate message pool to its max size, removing non-prioritary items
         let excess_count = self
@@ -100,7 +174,9 @@ impl AsyncPool {
             .saturating_sub(self.config.max_length as usize);
         eliminated.reserve_exact(excess_count);
         for _ in 0..excess_count {
-            eliminated.push(self.messages.pop_last().unwrap()); // will not panic (checked at excess_count computation)
+            let (msg_id, msg) = self.messages.pop_last().unwrap(); // will not panic (checked at excess_count computation)
+            self.untrack_message(&msg);
+            eliminated.push((msg_id, msg));
         }
         eliminated
     }
@@ -122,7 +198,8 @@ impl AsyncPool {
     ) -> Vec<(AsyncMessageId, AsyncMessage)> {
         // gather all selected items and remove them from self.messages
         // iterate in decreasing priority order
-        self.messages
+        let batch: Vec<_> = self
+            .messages
             .drain_filter(|_, msg| {
                 // check available gas and validity period
                 if available_gas >= msg.max_gas
@@ -135,7 +212,11 @@ impl AsyncPool {
                     false
                 }
             })
-            .collect()
+            .collect();
+        for (_id, msg) in batch.iter() {
+            self.untrack_message(msg);
+        }
+        batch
     }
 
     /// Get a part of the async pool.
@@ -187,7 +268,9 @@ impl AsyncPool {
         &mut self,
         part: BTreeMap<AsyncMessageId, AsyncMessage>,
     ) -> StreamingStep<AsyncMessageId> {
-        self.messages.extend(part);
+        for (msg_id, msg) in part {
+            self.insert_message(msg_id, msg);
+        }
         if let Some(message_id) = self.messages.last_key_value().map(|(&id, _)| id) {
             StreamingStep::Ongoing(message_id)
         } else {
@@ -333,3 +416,62 @@ fn test_take_batch() {
     pool.take_batch_to_execute(Slot::new(2, 0), 19);
     assert_eq!(pool.messages.len(), 6);
 }
+
+#[test]
+fn test_destination_usage() {
+    use massa_hash::Hash;
+    use massa_models::{address::Address, amount::Amount, slot::Slot};
+
+    let config = AsyncPoolConfig {
+        thread_count: 2,
+        max_length: 2,
+        max_async_message_data: 1_000_000,
+        bootstrap_part_size: 100,
+    };
+    let mut pool = AsyncPool::new(config);
+    let destination = Address(Hash::compute_from(b"abc"));
+    let mut new_messages: Vec<_> = (0..3)
+        .map(|i| {
+            let message = AsyncMessage {
+                emission_slot: Slot::new(0, 0),
+                emission_index: i,
+                sender: Address(Hash::compute_from(b"def")),
+                destination,
+                handler: "function".to_string(),
+                validity_start: Slot::new(1, 0),
+                validity_end: Slot::new(3, 0),
+                max_gas: 10 * (i + 1),
+                gas_price: Amount::zero(),
+                coins: Amount::zero(),
+                data: Vec::new(),
+            };
+            (message.compute_id(), message)
+        })
+        .collect();
+    // the excess message is eliminated and no longer counted
+    let eliminated = pool.settle_slot(&Slot::new(0, 0), &mut new_messages);
+    assert_eq!(eliminated.len(), 1);
+    let usage = pool.get_destination_usage(&destination);
+    assert_eq!(usage.message_count, 2);
+    assert_eq!(
+        usage.total_gas,
+        pool.messages.values().map(|msg| msg.max_gas).sum::<u64>()
+    );
+
+    let batch = pool.take_batch_to_execute(Slot::new(1, 0), u64::MAX);
+    assert_eq!(batch.len(), 2);
+    assert_eq!(
+        pool.get_destination_usage(&destination),
+        DestinationUsage::default()
+    );
+
+    // the usage is rebuilt from the bootstrapped messages
+    let mut bootstrapped = AsyncPool::new(pool.config.clone());
+    bootstrapped.set_pool_part(batch.into_iter().collect());
+    assert_eq!(
+        bootstrapped
+            .get_destination_usage(&destination)
+            .message_count,
+        2
+    );
+}
@@ -15,7 +15,7 @@ pub fn create_async_pool(
     messages: BTreeMap<(Reverse<Amount>, Slot, u64), AsyncMessage>,
 ) -> AsyncPool {
     let mut async_pool = AsyncPool::new(config);
-    async_pool.messages = messages;
+    async_pool.set_pool_part(messages);
     async_pool
 }
 
@@ -1,8 +1,14 @@
 use std::{net::SocketAddr, sync::Arc, time::Duration};
 
+use futures::future::join_all;
 use massa_final_state::FinalState;
+use massa_hash::Hash;
+use massa_ledger_exports::LedgerChanges;
 use massa_logging::massa_trace;
-use massa_models::{streaming_step::StreamingStep, version::Version};
+use massa_models::{
+    slot::Slot, streaming_step::StreamingStep, timeslots::get_latest_block_slot_at_timestamp,
+    version::Version,
+};
 use massa_signature::PublicKey;
 use massa_time::MassaTime;
 use parking_lot::RwLock;
@@ -16,6 +22,7 @@ use tracing::{debug, info, warn};
 use crate::{
     client_binder::BootstrapClientBinder,
     error::BootstrapError,
+    establisher::types::Duplex,
     messages::{BootstrapClientMessage, BootstrapServerMessage},
     BootstrapConfig, Establisher, GlobalBootstrapState,
 };
@@ -23,13 +30,16 @@ use crate::{
 /// This function will send the starting point to receive a stream of the ledger and will receive and process each part until receive a `BootstrapServerMessage::FinalStateFinished` message from the server.
 /// `next_bootstrap_message` passed as parameter must be `BootstrapClientMessage::AskFinalStatePart` enum variant.
 /// `next_bootstrap_message` will be updated after receiving each part so that in case of connection lost we can restart from the last message we processed.
-async fn stream_final_state(
+/// The streamed parts are only kept in memory: an interrupted session resumes on the next server of the same run, not after a node restart.
+pub(crate) async fn stream_final_state(
     cfg: &BootstrapConfig,
     client: &mut BootstrapClientBinder,
     next_bootstrap_message: &mut BootstrapClientMessage,
     global_bootstrap_state: &mut GlobalBootstrapState,
 ) -> Result<(), BootstrapError> {
     if let BootstrapClientMessage::AskFinalStatePart { .. } = &next_bootstrap_message {
+        // when resyncing, only the final state changes are received
+        let resync = is_resync_message(next_bootstrap_message);
         match tokio::time::timeout(
             cfg.write_timeout.into(),
             client.send(next_bootstrap_message),
@@ -60,25 +70,53 @@ async fn stream_final_state(
                 BootstrapServerMessage::FinalStatePart {
                     slot,
                     ledger_part,
+                    ledger_part_hash,
                     async_pool_part,
                     pos_cycle_part,
                     pos_credits_part,
                     exec_ops_part,
                     final_state_changes,
                 } => {
+                    // the cursors are left on the last verified part, from which the next server resumes
+                    if Hash::compute_from(&ledger_part) != ledger_part_hash {
+                        return Err(BootstrapError::GeneralError(
+                            "received a ledger part not matching its checksum".to_string(),
+                        ));
+                    }
                     let mut write_final_state = global_bootstrap_state.final_state.write();
-                    let last_ledger_step = write_final_state.ledger.set_ledger_part(ledger_part)?;
-                    let last_pool_step =
-                        write_final_state.async_pool.set_pool_part(async_pool_part);
-                    let last_cycle_step = write_final_state
-                        .pos_state
-                        .set_cycle_history_part(pos_cycle_part);
-                    let last_credits_step = write_final_state
-                        .pos_state
-                        .set_deferred_credits_part(pos_credits_part);
-                    let last_ops_step = write_final_state
-                        .executed_ops
-                        .set_executed_ops_part(exec_ops_part);
+                    if resync {
+                        *next_bootstrap_message = BootstrapClientMessage::AskFinalStatePart {
+                            last_slot: Some(slot),
+                            last_ledger_step: StreamingStep::Finished,
+                            last_pool_step: StreamingStep::Finished,
+                            last_cycle_step: StreamingStep::Finished,
+                            last_credits_step: StreamingStep::Finished,
+                            last_ops_step: StreamingStep::Finished,
+                        };
+                    } else {
+                        let last_ledger_step =
+                            write_final_state.ledger.set_ledger_part(ledger_part)?;
+                        let last_pool_step =
+                            write_final_state.async_pool.set_pool_part(async_pool_part);
+                        let last_cycle_step = write_final_state
+                            .pos_state
+                            .set_cycle_history_part(pos_cycle_part);
+                        let last_credits_step = write_final_state
+                            .pos_state
+                            .set_deferred_credits_part(pos_credits_part);
+                        let last_ops_step = write_final_state
+                            .executed_ops
+                            .set_executed_ops_part(exec_ops_part);
+                        // Set new message in case of disconnection
+                        *next_bootstrap_message = BootstrapClientMessage::AskFinalStatePart {
+                            last_slot: Some(slot),
+                            last_ledger_step,
+                            last_pool_step,
+                            last_cycle_step,
+                            last_credits_step,
+                            last_ops_step,
+                        };
+                    }
                     for (changes_slot, changes) in final_state_changes.iter() {
                         write_final_state
                             .ledger
@@ -100,15 +138,6 @@ async fn stream_final_state(
                         }
                     }
                     write_final_state.slot = slot;
-                    // Set new message in case of disconnection
-                    *next_bootstrap_message = BootstrapClientMessage::AskFinalStatePart {
-                        last_slot: Some(slot),
-                        last_ledger_step,
-                        last_pool_step,
-                        last_cycle_step,
-                        last_credits_step,
-                        last_ops_step,
-                    };
                     debug!(
                         "client final state bootstrap cursors: {:?}",
                         next_bootstrap_message
@@ -118,12 +147,35 @@ async fn stream_final_state(
                         final_state_changes.len()
                     );
                 }
-                BootstrapServerMessage::FinalStateFinished => {
+                BootstrapServerMessage::FinalStateFinished {
+                    ledger_hash,
+                    ledger_root,
+                } => {
+                    {
+                        let mut write_final_state = global_bootstrap_state.final_state.write();
+                        // attach the ledger to the final slot, as it is on the server, so that both hashes match
+                        let final_slot = write_final_state.slot;
+                        write_final_state
+                            .ledger
+                            .apply_changes(LedgerChanges::default(), final_slot);
+                        let local_ledger_hash = write_final_state.ledger.get_ledger_hash();
+                        let local_ledger_root = write_final_state.ledger.get_ledger_root();
+                        if local_ledger_hash != ledger_hash || local_ledger_root != ledger_root {
+                            // the streamed state is inconsistent and cannot be patched: it is discarded by `get_state`
+                            return Err(BootstrapError::LedgerHashMismatch(format!(
+                                "local ledger hash {} and root {}, server ledger hash {} and root {}",
+                                local_ledger_hash, local_ledger_root, ledger_hash, ledger_root
+                            )));
+                        }
+                    }
                     info!("State bootstrap complete");
                     // Set next bootstrap message
                     *next_bootstrap_message = BootstrapClientMessage::AskBootstrapPeers;
                     return Ok(());
                 }
+                BootstrapServerMessage::SlotTooOld if resync => {
+                    return Err(BootstrapError::ResyncSlotTooOld);
+                }
                 BootstrapServerMessage::SlotTooOld => {
                     info!("Slot is too old retry bootstrap from scratch");
                     *next_bootstrap_message = BootstrapClientMessage::AskFinalStatePart {
@@ -151,17 +203,97 @@ async fn stream_final_state(
     }
 }
 
-/// Gets the state from a bootstrap server (internal private function)
+/// Returns true if the message only asks for the final state changes since the client's final slot
+fn is_resync_message(message: &BootstrapClientMessage) -> bool {
+    matches!(
+        message,
+        BootstrapClientMessage::AskFinalStatePart {
+            last_slot: Some(_),
+            last_ledger_step: StreamingStep::Finished,
+            last_pool_step: StreamingStep::Finished,
+            last_cycle_step: StreamingStep::Finished,
+            last_credits_step: StreamingStep::Finished,
+            last_ops_step: StreamingStep::Finished,
+        }
+    )
+}
+
+/// Returns the number of slots elapsed between a local final state attached to `last_slot`
+/// and `current_slot`, if they are few enough to resync the final state from the history of
+/// final state changes kept by the bootstrap servers.
+///
+/// The bound is `max_resync_slots`, capped by the history length of the servers.
+pub(crate) fn get_resync_slot_count(
+    cfg: &BootstrapConfig,
+    last_slot: Slot,
+    current_slot: Slot,
+) -> Option<u64> {
+    let max_resync_slots = cfg.max_resync_slots.min(cfg.max_changes_slot_count);
+    current_slot
+        .slots_since(&last_slot, cfg.thread_count)
+        .ok()
+        .filter(|offline_slots| *offline_slots < max_resync_slots)
+}
+
+/// Builds the first final state request sent to the bootstrap servers.
+///
+/// A node whose local final state is recent enough, see `get_resync_slot_count`,
+/// only asks for the final state changes that happened since its final slot.
+/// Other nodes stream the whole final state.
+fn get_initial_bootstrap_message(
+    cfg: &BootstrapConfig,
+    final_state: &FinalState,
+    genesis_timestamp: MassaTime,
+) -> Result<BootstrapClientMessage, BootstrapError> {
+    let genesis_slot = Slot::new(0, cfg.thread_count.saturating_sub(1));
+    let last_slot = final_state.slot;
+    if last_slot > genesis_slot {
+        let current_slot = get_latest_block_slot_at_timestamp(
+            cfg.thread_count,
+            cfg.t0,
+            genesis_timestamp,
+            MassaTime::now(0)?,
+        )?
+        .unwrap_or(genesis_slot);
+        if let Some(offline_slots) = get_resync_slot_count(cfg, last_slot, current_slot) {
+            info!(
+                "local final state is {} slot(s) old: resyncing it from slot {}",
+                offline_slots, last_slot
+            );
+            return Ok(BootstrapClientMessage::AskFinalStatePart {
+                last_slot: Some(last_slot),
+                last_ledger_step: StreamingStep::Finished,
+                last_pool_step: StreamingStep::Finished,
+                last_cycle_step: StreamingStep::Finished,
+                last_credits_step: StreamingStep::Finished,
+                last_ops_step: StreamingStep::Finished,
+            });
+        }
+        if cfg.max_resync_slots > 0 {
+            warn!(
+                "local final state at slot {} is too old to be resynced at slot {}, bootstrapping the whole final state",
+                last_slot, current_slot
+            );
+        }
+    }
+    Ok(BootstrapClientMessage::AskFinalStatePart {
+        last_slot: None,
+        last_ledger_step: StreamingStep::Started,
+        last_pool_step: StreamingStep::Started,
+        last_cycle_step: StreamingStep::Started,
+        last_credits_step: StreamingStep::Started,
+        last_ops_step: StreamingStep::Started,
+    })
+}
+
+/// Checks the versions and the clocks of the node and of a bootstrap server.
+/// Returns the clock compensation to apply, in milliseconds.
 /// needs to be CANCELLABLE
-async fn bootstrap_from_server(
+async fn handshake_with_server(
     cfg: &BootstrapConfig,
     client: &mut BootstrapClientBinder,
-    next_bootstrap_message: &mut BootstrapClientMessage,
-    global_bootstrap_state: &mut GlobalBootstrapState,
     our_version: Version,
-) -> Result<(), BootstrapError> {
-    massa_trace!("bootstrap.lib.bootstrap_from_server", {});
-
+) -> Result<i64, BootstrapError> {
     // read error (if sent by the server)
     // client.next() is not cancel-safe but we drop the whole client object if cancelled => it's OK
     match tokio::time::timeout(cfg.read_error_timeout.into(), client.next()).await {
@@ -261,16 +393,198 @@ async fn bootstrap_from_server(
     } else {
         0
     };
+    Ok(compensation_millis)
+}
+
+/// Cross-verifies the streamed final state against other bootstrap servers.
+///
+/// Only the connections are opened concurrently. The servers are then verified one after the other:
+/// each streams the final state changes since our final slot, along with the hash of its ledger
+/// which must match ours once they are applied.
+/// Unreachable and failing servers are skipped. A server whose ledger does not match ours is skipped too,
+/// but counts against the quorum: the verification fails with `BootstrapError::LedgerHashMismatch`
+/// unless more servers matched our ledger than mismatched it.
+///
+/// Returns the connection to the last server the final state was verified against, if any:
+/// the final state is now attached to its final slot so the bootstrap has to be completed with it.
+/// needs to be CANCELLABLE
+pub(crate) async fn cross_verify_final_state(
+    cfg: &BootstrapConfig,
+    establisher: &mut Establisher,
+    sources: &[(SocketAddr, PublicKey)],
+    global_bootstrap_state: &mut GlobalBootstrapState,
+    our_version: Version,
+) -> Result<Option<BootstrapClientBinder>, BootstrapError> {
+    let mut connectors = Vec::with_capacity(sources.len());
+    for _ in sources {
+        connectors.push(establisher.get_connector(cfg.connect_timeout).await?);
+    }
+    let connections = join_all(sources.iter().zip(connectors).map(
+        |((addr, pub_key), mut connector)| async move {
+            let connection = async {
+                let socket = connector.connect(*addr).await?;
+                let mut client = new_client_binder(cfg, socket, pub_key);
+                handshake_with_server(cfg, &mut client, our_version).await?;
+                Ok::<_, BootstrapError>(client)
+            };
+            (*addr, connection.await)
+        },
+    ))
+    .await;
+
+    let mut verified_source: Option<(SocketAddr, BootstrapClientBinder)> = None;
+    let mut verified_count = 0usize;
+    let mut mismatched_count = 0usize;
+    for (addr, connection) in connections {
+        let mut client = match connection {
+            Ok(client) => client,
+            Err(err) => {
+                warn!(
+                    "Could not connect to bootstrap verification source {}: {}",
+                    addr, err
+                );
+                continue;
+            }
+        };
+        let mut resync_message = BootstrapClientMessage::AskFinalStatePart {
+            last_slot: Some(global_bootstrap_state.final_state.read().slot),
+            last_ledger_step: StreamingStep::Finished,
+            last_pool_step: StreamingStep::Finished,
+            last_cycle_step: StreamingStep::Finished,
+            last_credits_step: StreamingStep::Finished,
+            last_ops_step: StreamingStep::Finished,
+        };
+        match stream_final_state(
+            cfg,
+            &mut client,
+            &mut resync_message,
+            global_bootstrap_state,
+        )
+        .await
+        {
+            Ok(()) => {
+                debug!("final state verified against bootstrap source {}", addr);
+                verified_count += 1;
+                if let Some((_, mut previous_client)) = verified_source.replace((addr, client)) {
+                    let _ = tokio::time::timeout(
+                        cfg.write_error_timeout.into(),
+                        previous_client.send(&BootstrapClientMessage::BootstrapSuccess),
+                    )
+                    .await;
+                }
+            }
+            Err(err) => {
+                if let BootstrapError::LedgerHashMismatch(_) = err {
+                    // its changes are applied: if they were wrong, the next sources mismatch as well
+                    warn!(
+                        "Skipping bootstrap verification source {} not matching the final state: {}",
+                        addr, err
+                    );
+                    mismatched_count += 1;
+                } else {
+                    warn!(
+                        "Could not verify the final state against bootstrap source {}: {}",
+                        addr, err
+                    );
+                }
+                let _ = tokio::time::timeout(
+                    cfg.write_error_timeout.into(),
+                    client.send(&BootstrapClientMessage::BootstrapError {
+                        error: err.to_string(),
+                    }),
+                )
+                .await;
+            }
+        }
+    }
+    if mismatched_count > 0 && verified_count <= mismatched_count {
+        return Err(BootstrapError::LedgerHashMismatch(format!(
+            "{} of {} bootstrap verification sources do not match the final state, {} match it",
+            mismatched_count,
+            sources.len(),
+            verified_count
+        )));
+    }
+    if verified_count == 0 {
+        warn!(
+            "The bootstrapped final state could not be verified against any other bootstrap source"
+        );
+    } else {
+        info!(
+            "Final state verified against {} of {} other bootstrap sources, {} mismatched",
+            verified_count,
+            sources.len(),
+            mismatched_count
+        );
+    }
+    Ok(verified_source.map(|(_, client)| client))
+}
 
-    global_bootstrap_state.compensation_millis = compensation_millis;
+/// Gets the state from a bootstrap server (internal private function)
+/// needs to be CANCELLABLE
+async fn bootstrap_from_server(
+    cfg: &BootstrapConfig,
+    client: &mut BootstrapClientBinder,
+    establisher: &mut Establisher,
+    verification_sources: &[(SocketAddr, PublicKey)],
+    next_bootstrap_message: &mut BootstrapClientMessage,
+    global_bootstrap_state: &mut GlobalBootstrapState,
+    our_version: Version,
+) -> Result<(), BootstrapError> {
+    massa_trace!("bootstrap.lib.bootstrap_from_server", {});
+
+    global_bootstrap_state.compensation_millis =
+        handshake_with_server(cfg, client, our_version).await?;
 
     let write_timeout: std::time::Duration = cfg.write_timeout.into();
     // Loop to ask data to the server depending on the last message we sent
     loop {
         match next_bootstrap_message {
             BootstrapClientMessage::AskFinalStatePart { .. } => {
-                stream_final_state(cfg, client, next_bootstrap_message, global_bootstrap_state)
-                    .await?;
+                match stream_final_state(
+                    cfg,
+                    client,
+                    next_bootstrap_message,
+                    global_bootstrap_state,
+                )
+                .await
+                {
+                    Err(BootstrapError::ResyncSlotTooOld) => {
+                        // the server history does not reach back to our final slot:
+                        // bootstrap the whole final state from scratch on the same connection
+                        warn!("the bootstrap server cannot resync the local final state, bootstrapping the whole final state");
+                        global_bootstrap_state.final_state.write().reset();
+                        *next_bootstrap_message = BootstrapClientMessage::AskFinalStatePart {
+                            last_slot: None,
+                            last_ledger_step: StreamingStep::Started,
+                            last_pool_step: StreamingStep::Started,
+                            last_cycle_step: StreamingStep::Started,
+                            last_credits_step: StreamingStep::Started,
+                            last_ops_step: StreamingStep::Started,
+                        };
+                        continue;
+                    }
+                    result => result?,
+                }
+                if !verification_sources.is_empty() {
+                    if let Some(verified_client) = cross_verify_final_state(
+                        cfg,
+                        establisher,
+                        verification_sources,
+                        global_bootstrap_state,
+                        our_version,
+                    )
+                    .await?
+                    {
+                        // the final state now follows the verification source: end the session with the first server
+                        let _ = tokio::time::timeout(
+                            cfg.write_error_timeout.into(),
+                            client.send(&BootstrapClientMessage::BootstrapSuccess),
+                        )
+                        .await;
+                        *client = verified_client;
+                    }
+                }
             }
             BootstrapClientMessage::AskBootstrapPeers => {
                 let peers = match send_client_message(
@@ -370,7 +684,15 @@ async fn connect_to_server(
         .get_connector(bootstrap_config.connect_timeout)
         .await?; // cancellable
     let socket = connector.connect(*addr).await?; // cancellable
-    Ok(BootstrapClientBinder::new(
+    Ok(new_client_binder(bootstrap_config, socket, pub_key))
+}
+
+fn new_client_binder(
+    bootstrap_config: &BootstrapConfig,
+    socket: Duplex,
+    pub_key: &PublicKey,
+) -> BootstrapClientBinder {
+    BootstrapClientBinder::new(
         socket,
         *pub_key,
         bootstrap_config.max_bytes_read_write,
@@ -401,7 +723,7 @@ async fn connect_to_server(
         bootstrap_config.max_credits_length,
         bootstrap_config.max_executed_ops_length,
         bootstrap_config.max_ops_changes_length,
-    ))
+    )
 }
 
 /// Gets the state from a bootstrap server
@@ -443,15 +765,13 @@ pub async fn get_state(
     }
     let mut shuffled_list = bootstrap_config.bootstrap_list.clone();
     shuffled_list.shuffle(&mut StdRng::from_entropy());
-    let mut next_bootstrap_message: BootstrapClientMessage =
-        BootstrapClientMessage::AskFinalStatePart {
-            last_slot: None,
-            last_ledger_step: StreamingStep::Started,
-            last_pool_step: StreamingStep::Started,
-            last_cycle_step: StreamingStep::Started,
-            last_credits_step: StreamingStep::Started,
-            last_ops_step: StreamingStep::Started,
-        };
+    let mut next_bootstrap_message =
+        get_initial_bootstrap_message(bootstrap_config, &final_state.read(), genesis_timestamp)?;
+    if !is_resync_message(&next_bootstrap_message) {
+        // the whole final state is streamed: it must not be mixed with the local one,
+        // such as a ledger imported at startup
+        final_state.write().reset();
+    }
     let mut global_bootstrap_state = GlobalBootstrapState::new(final_state.clone());
     loop {
         for (addr, pub_key) in shuffled_list.iter() {
@@ -461,12 +781,31 @@ pub async fn get_state(
                 }
             }
             info!("Start bootstrapping from {}", addr);
+            let verification_sources: Vec<(SocketAddr, PublicKey)> = shuffled_list
+                .iter()
+                .filter(|(source_addr, _)| source_addr != addr)
+                .take(bootstrap_config.bootstrap_verification_sources)
+                .cloned()
+                .collect();
             match connect_to_server(&mut establisher, bootstrap_config, addr, pub_key).await {
                 Ok(mut client) => {
-                    match bootstrap_from_server(bootstrap_config, &mut client, &mut next_bootstrap_message, &mut global_bootstrap_state,version)
+                    match bootstrap_from_server(bootstrap_config, &mut client, &mut establisher, &verification_sources, &mut next_bootstrap_message, &mut global_bootstrap_state,version)
                     .await  // cancellable
                     {
                         Err(BootstrapError::ReceivedError(error)) => warn!("Error received from bootstrap server: {}", error),
+                        Err(e @ BootstrapError::LedgerHashMismatch(_)) => {
+                            warn!("Discarding the bootstrapped final state: {}", e);
+                            let _ = tokio::time::timeout(bootstrap_config.write_error_timeout.into(), client.send(&BootstrapClientMessage::BootstrapError { error: e.to_string() })).await;
+                            final_state.write().reset();
+                            next_bootstrap_message = BootstrapClientMessage::AskFinalStatePart {
+                                last_slot: None,
+                                last_ledger_step: StreamingStep::Started,
+                                last_pool_step: StreamingStep::Started,
+                                last_cycle_step: StreamingStep::Started,
+                                last_credits_step: StreamingStep::Started,
+                                last_ops_step: StreamingStep::Started,
+                            };
+                        }
                         Err(e) => {
                             warn!("Error while bootstrapping: {}", e);
                             // We allow unused result because we don't care if an error is thrown when sending the error message to the server we will close the socket anyway.
@@ -50,4 +50,8 @@ pub enum BootstrapError {
     IncompatibleVersionError(String),
     /// Received error: {0}
     ReceivedError(String),
+    /// the final state history of the server does not reach back to the slot to resync from
+    ResyncSlotTooOld,
+    /// bootstrapped ledger does not match the server ledger: {0}
+    LedgerHashMismatch(String),
 }
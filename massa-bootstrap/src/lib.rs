@@ -24,6 +24,7 @@ mod client;
 mod client_binder;
 mod error;
 mod establisher;
+mod limiter;
 mod messages;
 mod server;
 mod server_binder;
@@ -0,0 +1,166 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Limits on the bootstrap sessions served to the IP subnets.
+//!
+//! Each subnet owns a token bucket: starting a session consumes a token, and tokens are refilled
+//! at a constant rate up to the capacity of the bucket. Short bursts of bootstrap attempts are
+//! allowed while their average rate stays bounded. The number of sessions running at the same
+//! time for a subnet is capped as well.
+
+use std::{
+    collections::{hash_map, HashMap},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+use crate::{tools::normalize_ip, BootstrapConfig};
+
+/// Token bucket of a subnet
+struct TokenBucket {
+    /// available tokens, a session can start if there is at least one
+    tokens: f64,
+    /// last time the tokens were refilled
+    last_refill: Instant,
+}
+
+/// Session and rate limits of the bootstrap server, per IP subnet
+pub(crate) struct SubnetLimiter {
+    /// length of the prefix identifying the subnet of an IPv4 address
+    ipv4_prefix_length: u8,
+    /// length of the prefix identifying the subnet of an IPv6 address
+    ipv6_prefix_length: u8,
+    /// max number of tokens of a bucket, rate limiting is disabled if 0
+    bucket_capacity: f64,
+    /// time needed to refill one token
+    refill_interval: Duration,
+    /// max number of sessions running at the same time for a subnet
+    max_sessions_per_subnet: usize,
+    /// max number of subnets whose bucket is kept
+    max_tracked_subnets: usize,
+    /// token buckets of the subnets
+    buckets: HashMap<IpAddr, TokenBucket>,
+    /// number of running sessions per subnet
+    active_sessions: HashMap<IpAddr, usize>,
+    /// total number of running sessions
+    session_count: usize,
+}
+
+impl SubnetLimiter {
+    /// Creates a limiter from the bootstrap configuration
+    pub fn new(config: &BootstrapConfig) -> Self {
+        SubnetLimiter {
+            ipv4_prefix_length: config.ipv4_subnet_prefix_length.min(32),
+            ipv6_prefix_length: config.ipv6_subnet_prefix_length.min(128),
+            bucket_capacity: config.subnet_bucket_capacity as f64,
+            refill_interval: config.subnet_bucket_refill_interval.to_duration(),
+            max_sessions_per_subnet: config.max_simultaneous_bootstraps_per_subnet as usize,
+            max_tracked_subnets: config.ip_list_max_size,
+            buckets: HashMap::new(),
+            active_sessions: HashMap::new(),
+            session_count: 0,
+        }
+    }
+
+    /// Returns the subnet of an IP address, IPv4-mapped IPv6 addresses being handled as IPv4
+    pub fn subnet(&self, ip: IpAddr) -> IpAddr {
+        match normalize_ip(ip) {
+            IpAddr::V4(ip) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - self.ipv4_prefix_length as u32)
+                    .unwrap_or(0);
+                IpAddr::V4(Ipv4Addr::from(u32::from(ip) & mask))
+            }
+            IpAddr::V6(ip) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - self.ipv6_prefix_length as u32)
+                    .unwrap_or(0);
+                IpAddr::V6(Ipv6Addr::from(u128::from(ip) & mask))
+            }
+        }
+    }
+
+    /// Total number of sessions running for all the subnets
+    pub fn session_count(&self) -> usize {
+        self.session_count
+    }
+
+    /// Returns true if the subnet can run one more session
+    pub fn has_session_slot(&self, subnet: &IpAddr) -> bool {
+        self.active_sessions
+            .get(subnet)
+            .copied()
+            .unwrap_or_default()
+            < self.max_sessions_per_subnet
+    }
+
+    /// Consumes a token of the bucket of the subnet.
+    ///
+    /// # Returns
+    /// `Err` with the time to wait for a token to be available if the bucket is empty
+    pub fn try_consume_token(&mut self, subnet: IpAddr, now: Instant) -> Result<(), Duration> {
+        if self.bucket_capacity == 0.0 {
+            return Ok(());
+        }
+        if self.buckets.len() >= self.max_tracked_subnets && !self.buckets.contains_key(&subnet) {
+            self.prune_buckets(now);
+        }
+        let bucket = self.buckets.entry(subnet).or_insert(TokenBucket {
+            tokens: self.bucket_capacity,
+            last_refill: now,
+        });
+        Self::refill(bucket, self.bucket_capacity, self.refill_interval, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(self.refill_interval.mul_f64(1.0 - bucket.tokens))
+        }
+    }
+
+    /// Records the start of a session for the subnet
+    pub fn start_session(&mut self, subnet: IpAddr) {
+        *self.active_sessions.entry(subnet).or_default() += 1;
+        self.session_count += 1;
+    }
+
+    /// Records the end of a session of the subnet
+    pub fn end_session(&mut self, subnet: IpAddr) {
+        if let hash_map::Entry::Occupied(mut occ) = self.active_sessions.entry(subnet) {
+            *occ.get_mut() -= 1;
+            if *occ.get() == 0 {
+                occ.remove();
+            }
+            self.session_count -= 1;
+        }
+    }
+
+    /// Refills the tokens of a bucket according to the time elapsed since its last refill
+    fn refill(bucket: &mut TokenBucket, capacity: f64, refill_interval: Duration, now: Instant) {
+        let elapsed = now.saturating_duration_since(bucket.last_refill);
+        bucket.tokens = if refill_interval.is_zero() {
+            capacity
+        } else {
+            (bucket.tokens + elapsed.as_secs_f64() / refill_interval.as_secs_f64()).min(capacity)
+        };
+        bucket.last_refill = now;
+    }
+
+    /// Forgets the buckets that are full again, as they would be recreated identical.
+    /// If too many subnets are still tracked, all the buckets are cleared.
+    fn prune_buckets(&mut self, now: Instant) {
+        let (capacity, refill_interval) = (self.bucket_capacity, self.refill_interval);
+        self.buckets.retain(|_subnet, bucket| {
+            Self::refill(bucket, capacity, refill_interval, now);
+            bucket.tokens < capacity
+        });
+        if self.buckets.len() >= self.max_tracked_subnets {
+            // too many subnets are spamming us: clear the buckets
+            warn!(
+                "high bootstrap load: at least {} different subnets are rate limited",
+                self.buckets.len()
+            );
+            self.buckets.clear();
+        }
+    }
+}
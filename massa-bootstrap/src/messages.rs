@@ -9,6 +9,7 @@ use massa_final_state::{StateChanges, StateChangesDeserializer, StateChangesSeri
 use massa_graph::{
     BootstrapableGraph, BootstrapableGraphDeserializer, BootstrapableGraphSerializer,
 };
+use massa_hash::{Hash, HashDeserializer, HashSerializer};
 use massa_ledger_exports::{KeyDeserializer, KeySerializer};
 use massa_models::operation::OperationId;
 use massa_models::prehash::PreHashSet;
@@ -68,6 +69,8 @@ pub enum BootstrapServerMessage {
         slot: Slot,
         /// Part of the execution ledger sent in a serialized way
         ledger_part: Vec<u8>,
+        /// Checksum of `ledger_part`, verified by the client before applying it
+        ledger_part_hash: Hash,
         /// Part of the async pool
         async_pool_part: BTreeMap<AsyncMessageId, AsyncMessage>,
         /// Part of the Proof of Stake `cycle_history`
@@ -80,7 +83,12 @@ pub enum BootstrapServerMessage {
         final_state_changes: Vec<(Slot, StateChanges)>,
     },
     /// Message sent when there is no state part left
-    FinalStateFinished,
+    FinalStateFinished {
+        /// Hash of the server ledger at the last streamed slot, to be matched by the client ledger
+        ledger_hash: Hash,
+        /// Root of the Merkle tree of the server ledger at the last streamed slot, to be matched by the client ledger
+        ledger_root: Hash,
+    },
     /// Slot sent to get state changes is too old
     SlotTooOld,
     /// Bootstrap error
@@ -112,6 +120,7 @@ pub struct BootstrapServerMessageSerializer {
     state_changes_serializer: StateChangesSerializer,
     bootstrapable_graph_serializer: BootstrapableGraphSerializer,
     vec_u8_serializer: VecU8Serializer,
+    hash_serializer: HashSerializer,
     slot_serializer: SlotSerializer,
     async_pool_serializer: AsyncPoolSerializer,
     opt_pos_cycle_serializer: OptionSerializer<CycleInfo, CycleInfoSerializer>,
@@ -137,6 +146,7 @@ impl BootstrapServerMessageSerializer {
             state_changes_serializer: StateChangesSerializer::new(),
             bootstrapable_graph_serializer: BootstrapableGraphSerializer::new(),
             vec_u8_serializer: VecU8Serializer::new(),
+            hash_serializer: HashSerializer::new(),
             slot_serializer: SlotSerializer::new(),
             async_pool_serializer: AsyncPoolSerializer::new(),
             opt_pos_cycle_serializer: OptionSerializer::new(CycleInfoSerializer::new()),
@@ -192,6 +202,7 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
             BootstrapServerMessage::FinalStatePart {
                 slot,
                 ledger_part,
+                ledger_part_hash,
                 async_pool_part,
                 pos_cycle_part,
                 pos_credits_part,
@@ -205,6 +216,7 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
                 self.slot_serializer.serialize(slot, buffer)?;
                 // ledger
                 self.vec_u8_serializer.serialize(ledger_part, buffer)?;
+                self.hash_serializer.serialize(ledger_part_hash, buffer)?;
                 // async pool
                 self.async_pool_serializer
                     .serialize(async_pool_part, buffer)?;
@@ -226,9 +238,14 @@ impl Serializer<BootstrapServerMessage> for BootstrapServerMessageSerializer {
                         .serialize(state_changes, buffer)?;
                 }
             }
-            BootstrapServerMessage::FinalStateFinished => {
+            BootstrapServerMessage::FinalStateFinished {
+                ledger_hash,
+                ledger_root,
+            } => {
                 self.u32_serializer
                     .serialize(&u32::from(MessageServerTypeId::FinalStateFinished), buffer)?;
+                self.hash_serializer.serialize(ledger_hash, buffer)?;
+                self.hash_serializer.serialize(ledger_root, buffer)?;
             }
             BootstrapServerMessage::SlotTooOld => {
                 self.u32_serializer
@@ -260,6 +277,7 @@ pub struct BootstrapServerMessageDeserializer {
     state_changes_deserializer: StateChangesDeserializer,
     bootstrapable_graph_deserializer: BootstrapableGraphDeserializer,
     ledger_bytes_deserializer: VecU8Deserializer,
+    hash_deserializer: HashDeserializer,
     length_bootstrap_error: U64VarIntDeserializer,
     slot_deserializer: SlotDeserializer,
     async_pool_deserializer: AsyncPoolDeserializer,
@@ -339,6 +357,7 @@ impl BootstrapServerMessageDeserializer {
                 Included(0),
                 Included(max_bootstrap_final_state_parts_size),
             ),
+            hash_deserializer: HashDeserializer::new(),
             length_bootstrap_error: U64VarIntDeserializer::new(
                 Included(0),
                 Included(max_bootstrap_error_length),
@@ -451,6 +470,9 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
                     context("Failed ledger_data deserialization", |input| {
                         self.ledger_bytes_deserializer.deserialize(input)
                     }),
+                    context("Failed ledger_part_hash deserialization", |input| {
+                        self.hash_deserializer.deserialize(input)
+                    }),
                     context("Failed async_pool_part deserialization", |input| {
                         self.async_pool_deserializer.deserialize(input)
                     }),
@@ -480,6 +502,7 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
                     |(
                         slot,
                         ledger_part,
+                        ledger_part_hash,
                         async_pool_part,
                         pos_cycle_part,
                         pos_credits_part,
@@ -489,6 +512,7 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
                         BootstrapServerMessage::FinalStatePart {
                             slot,
                             ledger_part,
+                            ledger_part_hash,
                             async_pool_part,
                             pos_cycle_part,
                             pos_credits_part,
@@ -498,9 +522,21 @@ impl Deserializer<BootstrapServerMessage> for BootstrapServerMessageDeserializer
                     },
                 )
                 .parse(input),
-                MessageServerTypeId::FinalStateFinished => {
-                    Ok((input, BootstrapServerMessage::FinalStateFinished))
-                }
+                MessageServerTypeId::FinalStateFinished => tuple((
+                    context("Failed ledger_hash deserialization", |input| {
+                        self.hash_deserializer.deserialize(input)
+                    }),
+                    context("Failed ledger_root deserialization", |input| {
+                        self.hash_deserializer.deserialize(input)
+                    }),
+                ))
+                .map(
+                    |(ledger_hash, ledger_root)| BootstrapServerMessage::FinalStateFinished {
+                        ledger_hash,
+                        ledger_root,
+                    },
+                )
+                .parse(input),
                 MessageServerTypeId::SlotTooOld => Ok((input, BootstrapServerMessage::SlotTooOld)),
                 MessageServerTypeId::BootstrapError => context(
                     "Failed BootstrapError deserialization",
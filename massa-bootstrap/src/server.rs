@@ -3,11 +3,14 @@ use futures::StreamExt;
 use massa_async_pool::AsyncMessageId;
 use massa_consensus_exports::ConsensusCommandSender;
 use massa_final_state::FinalState;
+use massa_hash::Hash;
 use massa_logging::massa_trace;
-use massa_models::{slot::Slot, streaming_step::StreamingStep, version::Version};
+use massa_models::{
+    slot::Slot, stats::BootstrapServerStats, streaming_step::StreamingStep, version::Version,
+};
 use massa_network_exports::NetworkCommandSender;
 use massa_signature::KeyPair;
-use massa_time::MassaTime;
+use massa_time::ClockCompensation;
 use parking_lot::RwLock;
 use std::{
     collections::{hash_map, HashMap, HashSet},
@@ -20,9 +23,11 @@ use tracing::{debug, info, warn};
 
 use crate::{
     error::BootstrapError,
+    limiter::SubnetLimiter,
     messages::{BootstrapClientMessage, BootstrapServerMessage},
     server_binder::BootstrapServerBinder,
     tools::normalize_ip,
+    types::Duplex,
     BootstrapConfig, Establisher,
 };
 
@@ -30,9 +35,15 @@ use crate::{
 pub struct BootstrapManager {
     join_handle: JoinHandle<Result<(), BootstrapError>>,
     manager_tx: mpsc::Sender<()>,
+    stats: Arc<RwLock<BootstrapServerStats>>,
 }
 
 impl BootstrapManager {
+    /// get a handle on the stats of the sessions served by the bootstrap server
+    pub fn get_stats(&self) -> Arc<RwLock<BootstrapServerStats>> {
+        self.stats.clone()
+    }
+
     /// stop the bootstrap server
     pub async fn stop(self) -> Result<(), BootstrapError> {
         massa_trace!("bootstrap.lib.stop", {});
@@ -56,7 +67,7 @@ pub async fn start_bootstrap_server(
     bootstrap_config: BootstrapConfig,
     establisher: Establisher,
     keypair: KeyPair,
-    compensation_millis: i64,
+    clock_compensation: ClockCompensation,
     version: Version,
 ) -> Result<Option<BootstrapManager>, BootstrapError> {
     massa_trace!("bootstrap.lib.start_bootstrap_server", {});
@@ -99,6 +110,26 @@ pub async fn start_bootstrap_server(
             None
         };
 
+        let allowlist = if let Ok(allowlist) =
+            std::fs::read_to_string(&bootstrap_config.bootstrap_allowlist_file)
+        {
+            Some(
+                serde_json::from_str::<HashSet<IpAddr>>(allowlist.as_str())
+                    .map_err(|_| {
+                        BootstrapError::GeneralError(String::from(
+                            "Failed to parse bootstrap allowlist",
+                        ))
+                    })?
+                    .into_iter()
+                    .map(normalize_ip)
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        let stats = Arc::new(RwLock::new(BootstrapServerStats::default()));
+        let server_stats = stats.clone();
         let join_handle = tokio::spawn(async move {
             BootstrapServer {
                 consensus_command_sender,
@@ -108,10 +139,13 @@ pub async fn start_bootstrap_server(
                 manager_rx,
                 bind,
                 keypair,
-                compensation_millis,
+                clock_compensation,
                 version,
                 whitelist,
                 blacklist,
+                allowlist,
+                subnet_limiter: SubnetLimiter::new(&bootstrap_config),
+                stats: server_stats,
                 ip_hist_map: HashMap::with_capacity(bootstrap_config.ip_list_max_size),
                 bootstrap_config,
             }
@@ -121,6 +155,7 @@ pub async fn start_bootstrap_server(
         Ok(Some(BootstrapManager {
             join_handle,
             manager_tx,
+            stats,
         }))
     } else {
         Ok(None)
@@ -136,11 +171,14 @@ struct BootstrapServer {
     bind: SocketAddr,
     keypair: KeyPair,
     bootstrap_config: BootstrapConfig,
-    compensation_millis: i64,
+    clock_compensation: ClockCompensation,
     version: Version,
     blacklist: Option<HashSet<IpAddr>>,
     whitelist: Option<HashSet<IpAddr>>,
+    allowlist: Option<HashSet<IpAddr>>,
     ip_hist_map: HashMap<IpAddr, Instant>,
+    subnet_limiter: SubnetLimiter,
+    stats: Arc<RwLock<BootstrapServerStats>>,
 }
 
 impl BootstrapServer {
@@ -157,6 +195,13 @@ impl BootstrapServer {
         // )> = None;
         // let cache_timer = sleep(cache_timeout);
         let per_ip_min_interval = self.bootstrap_config.per_ip_min_interval.to_duration();
+        let max_simultaneous_bootstraps: usize = self
+            .bootstrap_config
+            .max_simultaneous_bootstraps
+            .try_into()
+            .map_err(|_| {
+                BootstrapError::GeneralError("Fail to convert u32 to usize".to_string())
+            })?;
         // tokio::pin!(cache_timer);
         /*
             select! without the "biased" modifier will randomly select the 1st branch to check,
@@ -183,7 +228,19 @@ impl BootstrapServer {
                 // }
 
                 // bootstrap session finished
-                Some(_) = bootstrap_sessions.next() => {
+                Some((subnet, success)) = bootstrap_sessions.next() => {
+                    if let Some(subnet) = subnet {
+                        self.subnet_limiter.end_session(subnet);
+                    }
+                    {
+                        let mut stats = self.stats.write();
+                        stats.active_sessions = bootstrap_sessions.len();
+                        if success {
+                            stats.served_sessions += 1;
+                        } else {
+                            stats.failed_sessions += 1;
+                        }
+                    }
                     massa_trace!("bootstrap.session.finished", {"active_count": bootstrap_sessions.len()});
                 }
 
@@ -194,9 +251,19 @@ impl BootstrapServer {
                     } else {
                         continue;
                     };
-                    if bootstrap_sessions.len() < self.bootstrap_config.max_simultaneous_bootstraps.try_into().map_err(|_| BootstrapError::GeneralError("Fail to convert u32 to usize".to_string()))? {
 
-                        massa_trace!("bootstrap.lib.run.select.accept", {"remote_addr": remote_addr});
+                    // allowlisted IPs bypass the session and rate limits
+                    let allowlisted = self.allowlist.as_ref().map_or(false, |allowlist| allowlist.contains(&normalize_ip(remote_addr.ip())));
+                    let subnet = self.subnet_limiter.subnet(remote_addr.ip());
+                    if !allowlisted && (self.subnet_limiter.session_count() >= max_simultaneous_bootstraps || !self.subnet_limiter.has_session_slot(&subnet)) {
+                        self.stats.write().refused_no_slot += 1;
+                        self.send_refusal(dplx, "Bootstrap failed because the bootstrap server currently has no slots available.".to_string()).await;
+                        debug!("did not bootstrap {}: no available slots", remote_addr);
+                        continue;
+                    }
+
+                    massa_trace!("bootstrap.lib.run.select.accept", {"remote_addr": remote_addr});
+                    if !allowlisted {
                         let now = Instant::now();
 
                         // clear IP history if necessary
@@ -213,15 +280,9 @@ impl BootstrapServer {
                         match self.ip_hist_map.entry(remote_addr.ip()) {
                             hash_map::Entry::Occupied(mut occ) => {
                                 if now.duration_since(*occ.get()) <= per_ip_min_interval {
-                                    let mut server = BootstrapServerBinder::new(dplx, self.keypair.clone(), self.bootstrap_config.max_bytes_read_write, self.bootstrap_config.max_bootstrap_message_size, self.bootstrap_config.thread_count, self.bootstrap_config.max_datastore_key_length, self.bootstrap_config.randomness_size_bytes);
-                                    let _ = match tokio::time::timeout(self.bootstrap_config.write_error_timeout.into(), server.send(BootstrapServerMessage::BootstrapError {
-                                        error:
-                                        format!("Your last bootstrap on this server was {:#?} ago and you have to wait {:#?} before retrying.", occ.get().elapsed(), per_ip_min_interval.saturating_sub(occ.get().elapsed()))
-                                    })).await {
-                                        Err(_) => Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "bootstrap error too early retry bootstrap send timed out").into()),
-                                        Ok(Err(e)) => Err(e),
-                                        Ok(Ok(_)) => Ok(()),
-                                    };
+                                    let error = format!("Your last bootstrap on this server was {:#?} ago and you have to wait {:#?} before retrying.", occ.get().elapsed(), per_ip_min_interval.saturating_sub(occ.get().elapsed()));
+                                    self.stats.write().refused_rate_limited += 1;
+                                    self.send_refusal(dplx, error).await;
                                     // in list, non-expired => refuse
                                     massa_trace!("bootstrap.lib.run.select.accept.refuse_limit", {"remote_addr": remote_addr});
                                     continue;
@@ -235,57 +296,69 @@ impl BootstrapServer {
                             }
                         }
 
-                        // load cache if absent
-                        // if bootstrap_data.is_none() {
-                        //     massa_trace!("bootstrap.lib.run.select.accept.cache_load.start", {});
-
-                        //     // Note that all requests are done simultaneously except for the consensus graph that is done after the others.
-                        //     // This is done to ensure that the execution bootstrap state is older than the consensus state.
-                        //     // If the consensus state snapshot is older than the execution state snapshot,
-                        //     //   the execution final ledger will be in the future after bootstrap, which causes an inconsistency.
-                        //     bootstrap_data = Some((data_graph, data_peers, self.final_state.clone()));
-                        //     cache_timer.set(sleep(cache_timeout));
-                        // }
-                        massa_trace!("bootstrap.lib.run.select.accept.cache_available", {});
-
-                        // launch bootstrap
-
-                        let compensation_millis = self.compensation_millis;
-                        let version = self.version;
-                        let data_execution = self.final_state.clone();
-                        let consensus_command_sender = self.consensus_command_sender.clone();
-                        let network_command_sender = self.network_command_sender.clone();
-                        let keypair = self.keypair.clone();
-                        let config = self.bootstrap_config.clone();
-
-                        bootstrap_sessions.push(async move {
-                            let mut server = BootstrapServerBinder::new(dplx, keypair, config.max_bytes_read_write, config.max_bootstrap_message_size, config.thread_count, config.max_datastore_key_length, config.randomness_size_bytes);
-                            match manage_bootstrap(&config, &mut server, data_execution, compensation_millis, version, consensus_command_sender, network_command_sender).await {
-                                Ok(_) => {
-                                    info!("bootstrapped peer {}", remote_addr)
-                                },
-                                Err(BootstrapError::ReceivedError(error)) => debug!("bootstrap serving error received from peer {}: {}", remote_addr, error),
-                                Err(err) => {
-                                    debug!("bootstrap serving error for peer {}: {}", remote_addr, err);
-                                    // We allow unused result because we don't care if an error is thrown when sending the error message to the server we will close the socket anyway.
-                                    let _ = tokio::time::timeout(config.write_error_timeout.into(), server.send(BootstrapServerMessage::BootstrapError { error: err.to_string() })).await;
-                                },
-                            }
+                        // check the rate limit of the subnet
+                        if let Err(wait) = self.subnet_limiter.try_consume_token(subnet, now) {
+                            let error = format!("Too many bootstrap attempts were made from your subnet recently, you have to wait {:#?} before retrying.", wait);
+                            self.stats.write().refused_rate_limited += 1;
+                            self.send_refusal(dplx, error).await;
+                            massa_trace!("bootstrap.lib.run.select.accept.refuse_subnet_limit", {"remote_addr": remote_addr});
+                            continue;
+                        }
+                        self.subnet_limiter.start_session(subnet);
+                    }
 
-                        });
-                        massa_trace!("bootstrap.session.started", {"active_count": bootstrap_sessions.len()});
-                    } else {
-                        let config = self.bootstrap_config.clone();
-                        let mut server = BootstrapServerBinder::new(dplx, self.keypair.clone(), config.max_bytes_read_write, config.max_bootstrap_message_size, config.thread_count, config.max_datastore_key_length, config.randomness_size_bytes);
-                        let _ = match tokio::time::timeout(config.clone().write_error_timeout.into(), server.send(BootstrapServerMessage::BootstrapError {
-                            error: "Bootstrap failed because the bootstrap server currently has no slots available.".to_string()
-                        })).await {
-                            Err(_) => Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "bootstrap error no available slots send timed out").into()),
-                            Ok(Err(e)) => Err(e),
-                            Ok(Ok(_)) => Ok(()),
+                    // load cache if absent
+                    // if bootstrap_data.is_none() {
+                    //     massa_trace!("bootstrap.lib.run.select.accept.cache_load.start", {});
+
+                    //     // Note that all requests are done simultaneously except for the consensus graph that is done after the others.
+                    //     // This is done to ensure that the execution bootstrap state is older than the consensus state.
+                    //     // If the consensus state snapshot is older than the execution state snapshot,
+                    //     //   the execution final ledger will be in the future after bootstrap, which causes an inconsistency.
+                    //     bootstrap_data = Some((data_graph, data_peers, self.final_state.clone()));
+                    //     cache_timer.set(sleep(cache_timeout));
+                    // }
+                    massa_trace!("bootstrap.lib.run.select.accept.cache_available", {});
+
+                    // launch bootstrap
+
+                    let clock_compensation = self.clock_compensation.clone();
+                    let version = self.version;
+                    let data_execution = self.final_state.clone();
+                    let consensus_command_sender = self.consensus_command_sender.clone();
+                    let network_command_sender = self.network_command_sender.clone();
+                    let keypair = self.keypair.clone();
+                    let config = self.bootstrap_config.clone();
+                    let session_subnet = if allowlisted { None } else { Some(subnet) };
+
+                    bootstrap_sessions.push(async move {
+                        let mut server = BootstrapServerBinder::new(dplx, keypair, config.max_bytes_read_write, config.max_bootstrap_message_size, config.thread_count, config.max_datastore_key_length, config.randomness_size_bytes);
+                        let success = match manage_bootstrap(&config, &mut server, data_execution, clock_compensation, version, consensus_command_sender, network_command_sender).await {
+                            Ok(_) => {
+                                info!("bootstrapped peer {}", remote_addr);
+                                true
+                            },
+                            Err(BootstrapError::ReceivedError(error)) => {
+                                debug!("bootstrap serving error received from peer {}: {}", remote_addr, error);
+                                false
+                            },
+                            Err(err) => {
+                                debug!("bootstrap serving error for peer {}: {}", remote_addr, err);
+                                // We allow unused result because we don't care if an error is thrown when sending the error message to the server we will close the socket anyway.
+                                let _ = tokio::time::timeout(config.write_error_timeout.into(), server.send(BootstrapServerMessage::BootstrapError { error: err.to_string() })).await;
+                                false
+                            },
                         };
-                        debug!("did not bootstrap {}: no available slots", remote_addr);
+                        (session_subnet, success)
+                    });
+                    {
+                        let mut stats = self.stats.write();
+                        stats.active_sessions = bootstrap_sessions.len();
+                        if allowlisted {
+                            stats.allowlisted_sessions += 1;
+                        }
                     }
+                    massa_trace!("bootstrap.session.started", {"active_count": bootstrap_sessions.len()});
                 }
             }
         }
@@ -294,6 +367,26 @@ impl BootstrapServer {
 
         Ok(())
     }
+
+    /// Sends an error to a client whose bootstrap attempt is refused, before closing the connection
+    async fn send_refusal(&self, dplx: Duplex, error: String) {
+        let config = &self.bootstrap_config;
+        let mut server = BootstrapServerBinder::new(
+            dplx,
+            self.keypair.clone(),
+            config.max_bytes_read_write,
+            config.max_bootstrap_message_size,
+            config.thread_count,
+            config.max_datastore_key_length,
+            config.randomness_size_bytes,
+        );
+        // We don't care if the error message can not be sent as the socket is closed anyway
+        let _ = tokio::time::timeout(
+            config.write_error_timeout.into(),
+            server.send(BootstrapServerMessage::BootstrapError { error }),
+        )
+        .await;
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -316,7 +409,10 @@ pub async fn send_final_state_stream(
         }
 
         let current_slot;
+        let mut slot_too_old = false;
         let ledger_part;
+        let ledger_hash;
+        let ledger_root;
         let async_pool_part;
         let pos_cycle_part;
         let pos_credits_part;
@@ -330,6 +426,8 @@ pub async fn send_final_state_stream(
                 .ledger
                 .get_ledger_part(last_ledger_step.clone())?;
             ledger_part = data;
+            ledger_hash = final_state_read.ledger.get_ledger_hash();
+            ledger_root = final_state_read.ledger.get_ledger_root();
 
             let (pool_data, new_pool_step) =
                 final_state_read.async_pool.get_pool_part(last_pool_step);
@@ -356,14 +454,20 @@ pub async fn send_final_state_stream(
                         "Bootstrap cursor set to future slot".to_string(),
                     ));
                 }
-                final_state_changes = final_state_read.get_state_changes_part(
-                    slot,
-                    new_ledger_step.clone(),
-                    new_pool_step,
-                    new_cycle_step,
-                    new_credits_step,
-                    new_ops_step,
-                )?;
+                // the changes following `slot` must all be in the history
+                slot_too_old = final_state_read.is_slot_too_old(slot);
+                final_state_changes = if slot_too_old {
+                    Vec::new()
+                } else {
+                    final_state_read.get_state_changes_part(
+                        slot,
+                        new_ledger_step.clone(),
+                        new_pool_step,
+                        new_cycle_step,
+                        new_credits_step,
+                        new_ops_step,
+                    )?
+                };
             } else {
                 final_state_changes = Vec::new();
             }
@@ -378,6 +482,24 @@ pub async fn send_final_state_stream(
             current_slot = final_state_read.slot;
         }
 
+        if slot_too_old {
+            match tokio::time::timeout(
+                write_timeout,
+                server.send(BootstrapServerMessage::SlotTooOld),
+            )
+            .await
+            {
+                Err(_) => Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "bootstrap slot too old send timed out",
+                )
+                .into()),
+                Ok(Err(e)) => Err(e),
+                Ok(Ok(_)) => Ok(()),
+            }?;
+            break;
+        }
+
         if !last_ledger_step.finished()
             || !last_pool_step.finished()
             || !last_cycle_step.finished()
@@ -389,6 +511,7 @@ pub async fn send_final_state_stream(
                 write_timeout,
                 server.send(BootstrapServerMessage::FinalStatePart {
                     slot: current_slot,
+                    ledger_part_hash: Hash::compute_from(&ledger_part),
                     ledger_part,
                     async_pool_part,
                     pos_cycle_part,
@@ -411,7 +534,10 @@ pub async fn send_final_state_stream(
             // There is no ledger data nor async pool data.
             match tokio::time::timeout(
                 write_timeout,
-                server.send(BootstrapServerMessage::FinalStateFinished),
+                server.send(BootstrapServerMessage::FinalStateFinished {
+                    ledger_hash,
+                    ledger_root,
+                }),
             )
             .await
             {
@@ -434,7 +560,7 @@ async fn manage_bootstrap(
     bootstrap_config: &BootstrapConfig,
     server: &mut BootstrapServerBinder,
     final_state: Arc<RwLock<FinalState>>,
-    compensation_millis: i64,
+    clock_compensation: ClockCompensation,
     version: Version,
     consensus_command_sender: ConsensusCommandSender,
     network_command_sender: NetworkCommandSender,
@@ -471,7 +597,7 @@ async fn manage_bootstrap(
     let write_timeout: std::time::Duration = bootstrap_config.write_timeout.into();
 
     // Sync clocks.
-    let server_time = MassaTime::now(compensation_millis)?;
+    let server_time = clock_compensation.now()?;
 
     match tokio::time::timeout(
         write_timeout,
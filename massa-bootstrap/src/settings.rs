@@ -14,6 +14,8 @@ pub struct BootstrapConfig {
     pub bootstrap_whitelist_file: std::path::PathBuf,
     /// Path to the bootstrap blacklist file. This whitelist define IPs that will not be able to bootstrap on your node. This list is optional.
     pub bootstrap_blacklist_file: std::path::PathBuf,
+    /// Path to the bootstrap allowlist file. IPs of this list bypass the session and rate limits of the server. This list is optional.
+    pub bootstrap_allowlist_file: std::path::PathBuf,
     /// Port to listen if we choose to allow other nodes to use us as bootstrap node.
     pub bind: Option<SocketAddr>,
     /// connection timeout
@@ -40,6 +42,16 @@ pub struct BootstrapConfig {
     pub per_ip_min_interval: MassaTime,
     /// Max size of the IP list
     pub ip_list_max_size: usize,
+    /// Max simultaneous bootstraps for the IPs of a given subnet
+    pub max_simultaneous_bootstraps_per_subnet: u32,
+    /// Max number of bootstrap attempts a subnet can make in a burst, rate limiting is disabled if 0
+    pub subnet_bucket_capacity: u32,
+    /// Time after which a subnet can make one more bootstrap attempt
+    pub subnet_bucket_refill_interval: MassaTime,
+    /// Length of the prefix identifying the subnet of an IPv4 address
+    pub ipv4_subnet_prefix_length: u8,
+    /// Length of the prefix identifying the subnet of an IPv6 address
+    pub ipv6_subnet_prefix_length: u8,
     /// Read-Write limitation for a connection in bytes per seconds
     pub max_bytes_read_write: f64,
     /// max bootstrap message size in bytes
@@ -48,6 +60,15 @@ pub struct BootstrapConfig {
     pub thread_count: u8,
     /// period per cycle
     pub periods_per_cycle: u64,
+    /// time between the periods in the same thread
+    pub t0: MassaTime,
+    /// max number of slots a node can have been offline for to only download the final state changes
+    /// since its last final slot instead of the whole final state, capped by `max_changes_slot_count`.
+    /// Resync is disabled if 0
+    pub max_resync_slots: u64,
+    /// number of other bootstrap servers the final state streamed from a server is cross-verified against,
+    /// disabled if 0
+    pub bootstrap_verification_sources: usize,
     /// max datastore key length
     pub max_datastore_key_length: u8,
     /// randomness size bytes
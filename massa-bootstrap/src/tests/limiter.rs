@@ -0,0 +1,79 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::{limiter::SubnetLimiter, tests::tools::get_bootstrap_config, BootstrapConfig};
+use massa_signature::KeyPair;
+use std::{
+    net::IpAddr,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+fn get_limiter_config() -> BootstrapConfig {
+    let mut config = get_bootstrap_config(KeyPair::generate().get_public_key());
+    config.max_simultaneous_bootstraps_per_subnet = 1;
+    config.subnet_bucket_capacity = 2;
+    config.subnet_bucket_refill_interval = 10000.into();
+    config
+}
+
+fn ip(s: &str) -> IpAddr {
+    IpAddr::from_str(s).unwrap()
+}
+
+#[test]
+fn test_subnet_of_ip() {
+    let limiter = SubnetLimiter::new(&get_limiter_config());
+    assert_eq!(limiter.subnet(ip("192.168.1.7")), ip("192.168.1.0"));
+    assert_eq!(limiter.subnet(ip("192.168.1.200")), ip("192.168.1.0"));
+    // IPv4-mapped addresses share the subnet of the IPv4 address
+    assert_eq!(limiter.subnet(ip("::ffff:192.168.1.7")), ip("192.168.1.0"));
+    assert_ne!(limiter.subnet(ip("192.168.2.7")), ip("192.168.1.0"));
+    assert_eq!(limiter.subnet(ip("2001:db8:1:2::7")), ip("2001:db8:1::"));
+}
+
+#[test]
+fn test_subnet_token_bucket() {
+    let mut limiter = SubnetLimiter::new(&get_limiter_config());
+    let subnet = limiter.subnet(ip("10.0.0.1"));
+    let other_subnet = limiter.subnet(ip("10.0.1.1"));
+    let start = Instant::now();
+
+    // a burst of attempts empties the bucket
+    assert!(limiter.try_consume_token(subnet, start).is_ok());
+    assert!(limiter.try_consume_token(subnet, start).is_ok());
+    assert_eq!(
+        limiter.try_consume_token(subnet, start),
+        Err(Duration::from_secs(10))
+    );
+    // other subnets have their own bucket
+    assert!(limiter.try_consume_token(other_subnet, start).is_ok());
+
+    // tokens are refilled over time
+    assert_eq!(
+        limiter.try_consume_token(subnet, start + Duration::from_secs(5)),
+        Err(Duration::from_secs(5))
+    );
+    assert!(limiter
+        .try_consume_token(subnet, start + Duration::from_secs(10))
+        .is_ok());
+    assert!(limiter
+        .try_consume_token(subnet, start + Duration::from_secs(10))
+        .is_err());
+}
+
+#[test]
+fn test_subnet_session_slots() {
+    let mut limiter = SubnetLimiter::new(&get_limiter_config());
+    let subnet = limiter.subnet(ip("10.0.0.1"));
+    let other_subnet = limiter.subnet(ip("10.0.1.1"));
+
+    limiter.start_session(subnet);
+    assert!(!limiter.has_session_slot(&limiter.subnet(ip("10.0.0.2"))));
+    assert!(limiter.has_session_slot(&other_subnet));
+    limiter.start_session(other_subnet);
+    assert_eq!(limiter.session_count(), 2);
+
+    limiter.end_session(subnet);
+    assert!(limiter.has_session_slot(&subnet));
+    assert_eq!(limiter.session_count(), 1);
+}
@@ -1,6 +1,9 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 mod binders;
+mod limiter;
 pub mod mock_establisher;
+mod resync;
 mod scenarios;
+mod tampering;
 pub mod tools;
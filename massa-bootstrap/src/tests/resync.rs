@@ -0,0 +1,46 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::tools::get_bootstrap_config;
+use crate::client::get_resync_slot_count;
+use massa_models::slot::Slot;
+use massa_signature::KeyPair;
+
+#[test]
+fn test_resync_slot_count_bounds() {
+    let mut config = get_bootstrap_config(KeyPair::generate().get_public_key());
+    let thread_count = config.thread_count as u64;
+    let last_slot = Slot::new(10, 0);
+    config.max_resync_slots = 2 * thread_count;
+    config.max_changes_slot_count = 1000;
+
+    // offline for one period
+    assert_eq!(
+        get_resync_slot_count(&config, last_slot, Slot::new(11, 0)),
+        Some(thread_count)
+    );
+    // the bound is exclusive
+    assert_eq!(
+        get_resync_slot_count(&config, last_slot, Slot::new(12, 0)),
+        None
+    );
+    // the local slot is ahead of the current one
+    assert_eq!(
+        get_resync_slot_count(&config, last_slot, Slot::new(9, 0)),
+        None
+    );
+
+    // the resync window never exceeds the history kept by the servers
+    config.max_changes_slot_count = thread_count;
+    assert_eq!(
+        get_resync_slot_count(&config, last_slot, Slot::new(11, 0)),
+        None
+    );
+    assert_eq!(
+        get_resync_slot_count(&config, last_slot, Slot::new(10, 1)),
+        Some(1)
+    );
+
+    // resync disabled
+    config.max_resync_slots = 0;
+    assert_eq!(get_resync_slot_count(&config, last_slot, last_slot), None);
+}
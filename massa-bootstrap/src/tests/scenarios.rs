@@ -36,7 +36,7 @@ use massa_pos_exports::{
 };
 use massa_pos_worker::start_selector_worker;
 use massa_signature::KeyPair;
-use massa_time::MassaTime;
+use massa_time::{ClockCompensation, MassaTime};
 use parking_lot::RwLock;
 use serial_test::serial;
 use std::{path::PathBuf, str::FromStr, sync::Arc, time::Duration};
@@ -88,6 +88,8 @@ async fn test_bootstrap_server() {
         executed_ops_config: ExecutedOpsConfig {
             thread_count,
             bootstrap_part_size: 10,
+            disk_path: None,
+            bloom_filter_size: 1000,
         },
         final_history_length: 100,
         initial_seed_string: "".into(),
@@ -145,7 +147,7 @@ async fn test_bootstrap_server() {
         bootstrap_config.clone(),
         bootstrap_establisher,
         keypair.clone(),
-        0,
+        ClockCompensation::default(),
         Version::from_str("TEST.1.10").unwrap(),
     )
     .await
@@ -0,0 +1,298 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::mock_establisher::{self, MockEstablisherInterface};
+use super::tools::get_bootstrap_config;
+use crate::client::{cross_verify_final_state, stream_final_state};
+use crate::client_binder::BootstrapClientBinder;
+use crate::error::BootstrapError;
+use crate::messages::{BootstrapClientMessage, BootstrapServerMessage};
+use crate::server_binder::BootstrapServerBinder;
+use crate::GlobalBootstrapState;
+use massa_async_pool::AsyncPoolConfig;
+use massa_executed_ops::ExecutedOpsConfig;
+use massa_final_state::{FinalState, FinalStateConfig};
+use massa_hash::Hash;
+use massa_ledger_exports::{LedgerChanges, LedgerConfig};
+use massa_models::{
+    config::{
+        BOOTSTRAP_RANDOMNESS_SIZE_BYTES, MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH,
+        MAX_BOOTSTRAP_MESSAGE_SIZE, MAX_DATASTORE_KEY_LENGTH, PERIODS_PER_CYCLE, POS_SAVED_CYCLES,
+        THREAD_COUNT,
+    },
+    slot::Slot,
+    streaming_step::StreamingStep,
+    version::Version,
+};
+use massa_pos_exports::{test_exports::MockSelectorController, PoSConfig, PoSFinalState};
+use massa_signature::{KeyPair, PublicKey};
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+use serial_test::serial;
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+};
+use tempfile::TempDir;
+use tokio::io::duplex;
+
+/// Creates an empty final state for the client, kept on disk in `temp_dir`
+fn get_client_final_state(temp_dir: &TempDir) -> Arc<RwLock<FinalState>> {
+    let config = FinalStateConfig {
+        ledger_config: LedgerConfig {
+            thread_count: THREAD_COUNT,
+            initial_ledger_path: "".into(),
+            disk_ledger_path: temp_dir.path().to_path_buf(),
+            max_key_length: MAX_DATASTORE_KEY_LENGTH,
+            max_ledger_part_size: 100_000,
+        },
+        async_pool_config: AsyncPoolConfig {
+            thread_count: THREAD_COUNT,
+            max_length: MAX_ASYNC_POOL_LENGTH,
+            max_async_message_data: MAX_ASYNC_MESSAGE_DATA,
+            bootstrap_part_size: 100,
+        },
+        pos_config: PoSConfig {
+            periods_per_cycle: PERIODS_PER_CYCLE,
+            thread_count: THREAD_COUNT,
+            cycle_history_length: POS_SAVED_CYCLES,
+            credits_bootstrap_part_size: 100,
+        },
+        executed_ops_config: ExecutedOpsConfig {
+            thread_count: THREAD_COUNT,
+            bootstrap_part_size: 10,
+            disk_path: None,
+            bloom_filter_size: 1000,
+        },
+        final_history_length: 100,
+        initial_seed_string: "".into(),
+        initial_rolls_path: "".into(),
+        thread_count: THREAD_COUNT,
+        periods_per_cycle: PERIODS_PER_CYCLE,
+    };
+    let rolls_path = PathBuf::from_str("../massa-node/base_config/initial_rolls.json").unwrap();
+    let (selector_controller, _selector_receiver) = MockSelectorController::new_with_receiver();
+    let pos_state = PoSFinalState::new(
+        config.pos_config.clone(),
+        "",
+        &rolls_path,
+        selector_controller,
+    )
+    .unwrap();
+    Arc::new(RwLock::new(FinalState::create_final_state(
+        pos_state, config,
+    )))
+}
+
+/// Connects a client to a server that answers the first final state request with `answer`
+async fn get_client_of_server(answer: BootstrapServerMessage) -> BootstrapClientBinder {
+    let keypair = KeyPair::generate();
+    let config = get_bootstrap_config(keypair.get_public_key());
+    let version = Version::from_str("TEST.1.10").unwrap();
+    let (client_duplex, server_duplex) = duplex(1_000_000);
+    let mut server = BootstrapServerBinder::new(
+        server_duplex,
+        keypair,
+        f64::INFINITY,
+        MAX_BOOTSTRAP_MESSAGE_SIZE,
+        THREAD_COUNT,
+        MAX_DATASTORE_KEY_LENGTH,
+        BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
+    );
+    tokio::spawn(async move {
+        server.handshake(version).await.unwrap();
+        match server.next().await.unwrap() {
+            BootstrapClientMessage::AskFinalStatePart { .. } => {}
+            _ => panic!("Bad message receive: Expected a final state part request"),
+        }
+        server.send(answer).await.unwrap();
+    });
+    let mut client = BootstrapClientBinder::test_default(client_duplex, config.bootstrap_list[0].1);
+    client.handshake(version).await.unwrap();
+    client
+}
+
+/// A ledger part not matching its checksum is rejected, and the next request resumes from the last verified part
+#[tokio::test]
+#[serial]
+async fn test_tampered_ledger_part_is_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let final_state = get_client_final_state(&temp_dir);
+    let mut global_bootstrap_state = GlobalBootstrapState::new(final_state.clone());
+    let config = get_bootstrap_config(KeyPair::generate().get_public_key());
+    let mut client = get_client_of_server(BootstrapServerMessage::FinalStatePart {
+        slot: Slot::new(1, 0),
+        ledger_part: vec![1, 2, 3],
+        ledger_part_hash: Hash::compute_from(&[1, 2, 4]),
+        async_pool_part: BTreeMap::new(),
+        pos_cycle_part: None,
+        pos_credits_part: Default::default(),
+        exec_ops_part: BTreeMap::new(),
+        final_state_changes: Vec::new(),
+    })
+    .await;
+
+    let mut next_bootstrap_message = BootstrapClientMessage::AskFinalStatePart {
+        last_slot: None,
+        last_ledger_step: StreamingStep::Started,
+        last_pool_step: StreamingStep::Started,
+        last_cycle_step: StreamingStep::Started,
+        last_credits_step: StreamingStep::Started,
+        last_ops_step: StreamingStep::Started,
+    };
+    let result = stream_final_state(
+        &config,
+        &mut client,
+        &mut next_bootstrap_message,
+        &mut global_bootstrap_state,
+    )
+    .await;
+    match result {
+        Err(BootstrapError::GeneralError(error)) => assert!(error.contains("checksum")),
+        _ => panic!("a tampered ledger part should have been rejected"),
+    }
+
+    // nothing was applied from the tampered part
+    assert!(matches!(
+        next_bootstrap_message,
+        BootstrapClientMessage::AskFinalStatePart {
+            last_slot: None,
+            last_ledger_step: StreamingStep::Started,
+            ..
+        }
+    ));
+    assert_eq!(final_state.read().slot, Slot::new(0, 0));
+    assert!(final_state.read().ledger.get_every_address().is_empty());
+}
+
+/// Accepts the verification source connections of `establisher_interface`, each source answering
+/// the final state request with the message given for its address in `answers`
+fn serve_verification_sources(
+    mut establisher_interface: MockEstablisherInterface,
+    mut answers: HashMap<SocketAddr, (KeyPair, BootstrapServerMessage)>,
+) {
+    let version = Version::from_str("TEST.1.10").unwrap();
+    tokio::spawn(async move {
+        while !answers.is_empty() {
+            let (server_duplex, addr, accept_tx) = establisher_interface
+                .wait_connection_attempt_from_controller()
+                .await
+                .unwrap();
+            let (keypair, answer) = answers.remove(&addr).expect("unknown verification source");
+            accept_tx.send(true).unwrap();
+            let mut server = BootstrapServerBinder::new(
+                server_duplex,
+                keypair,
+                f64::INFINITY,
+                MAX_BOOTSTRAP_MESSAGE_SIZE,
+                THREAD_COUNT,
+                MAX_DATASTORE_KEY_LENGTH,
+                BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
+            );
+            tokio::spawn(async move {
+                server.handshake(version).await.unwrap();
+                server
+                    .send(BootstrapServerMessage::BootstrapTime {
+                        server_time: MassaTime::now(0).unwrap(),
+                        version,
+                    })
+                    .await
+                    .unwrap();
+                match server.next().await.unwrap() {
+                    BootstrapClientMessage::AskFinalStatePart { .. } => {}
+                    _ => panic!("Bad message receive: Expected a final state part request"),
+                }
+                server.send(answer).await.unwrap();
+                // the client ends the session with a success or an error message
+                let _ = server.next().await;
+            });
+        }
+    });
+}
+
+/// A verification source whose ledger hash does not match the bootstrapped ledger is skipped,
+/// and the verification fails if the mismatching sources are not outnumbered by the matching ones
+#[tokio::test]
+#[serial]
+async fn test_mismatched_verification_source_is_skipped() {
+    let temp_dir = TempDir::new().unwrap();
+    let final_state = get_client_final_state(&temp_dir);
+    let mut global_bootstrap_state = GlobalBootstrapState::new(final_state.clone());
+    let config = get_bootstrap_config(KeyPair::generate().get_public_key());
+    let version = Version::from_str("TEST.1.10").unwrap();
+    let (ledger_hash, ledger_root) = {
+        let mut write_final_state = final_state.write();
+        let final_slot = write_final_state.slot;
+        write_final_state
+            .ledger
+            .apply_changes(LedgerChanges::default(), final_slot);
+        (
+            write_final_state.ledger.get_ledger_hash(),
+            write_final_state.ledger.get_ledger_root(),
+        )
+    };
+    let mismatching_answer = BootstrapServerMessage::FinalStateFinished {
+        ledger_hash: Hash::compute_from(b"tampered ledger"),
+        ledger_root,
+    };
+    let matching_answer = BootstrapServerMessage::FinalStateFinished {
+        ledger_hash,
+        ledger_root,
+    };
+    let sources: Vec<(SocketAddr, KeyPair)> = (0..3)
+        .map(|port| {
+            (
+                SocketAddr::new(IpAddr::V4(Ipv4Addr::new(169, 202, 0, 1)), 31240 + port),
+                KeyPair::generate(),
+            )
+        })
+        .collect();
+    let source_keys: Vec<(SocketAddr, PublicKey)> = sources
+        .iter()
+        .map(|(addr, keypair)| (*addr, keypair.get_public_key()))
+        .collect();
+
+    // a single mismatching source: the quorum is not reached
+    let (mut establisher, establisher_interface) = mock_establisher::new();
+    serve_verification_sources(
+        establisher_interface,
+        HashMap::from([(
+            sources[0].0,
+            (sources[0].1.clone(), mismatching_answer.clone()),
+        )]),
+    );
+    let result = cross_verify_final_state(
+        &config,
+        &mut establisher,
+        &source_keys[..1],
+        &mut global_bootstrap_state,
+        version,
+    )
+    .await;
+    assert!(matches!(result, Err(BootstrapError::LedgerHashMismatch(_))));
+
+    // the mismatching source is skipped and outnumbered by the matching ones
+    let (mut establisher, establisher_interface) = mock_establisher::new();
+    serve_verification_sources(
+        establisher_interface,
+        HashMap::from([
+            (sources[0].0, (sources[0].1.clone(), mismatching_answer)),
+            (
+                sources[1].0,
+                (sources[1].1.clone(), matching_answer.clone()),
+            ),
+            (sources[2].0, (sources[2].1.clone(), matching_answer)),
+        ]),
+    );
+    let result = cross_verify_final_state(
+        &config,
+        &mut establisher,
+        &source_keys,
+        &mut global_bootstrap_state,
+        version,
+    )
+    .await;
+    assert!(matches!(result, Ok(Some(_))));
+}
@@ -24,7 +24,7 @@ use massa_models::config::{
     MAX_EXECUTED_OPS_LENGTH, MAX_FUNCTION_NAME_LENGTH, MAX_LEDGER_CHANGES_COUNT,
     MAX_OPERATIONS_PER_BLOCK, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
     MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE,
-    MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, PERIODS_PER_CYCLE, THREAD_COUNT,
+    MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, PERIODS_PER_CYCLE, T0, THREAD_COUNT,
 };
 use massa_models::{
     address::Address,
@@ -290,10 +290,18 @@ pub fn get_bootstrap_config(bootstrap_public_key: PublicKey) -> BootstrapConfig
         bootstrap_blacklist_file: std::path::PathBuf::from(
             "../massa-node/base_config/bootstrap_blacklist.json",
         ),
+        bootstrap_allowlist_file: std::path::PathBuf::from(
+            "../massa-node/base_config/bootstrap_allowlist.json",
+        ),
         enable_clock_synchronization: true,
         cache_duration: 10000.into(),
         max_simultaneous_bootstraps: 2,
         ip_list_max_size: 10,
+        max_simultaneous_bootstraps_per_subnet: 2,
+        subnet_bucket_capacity: 0,
+        subnet_bucket_refill_interval: 10000.into(),
+        ipv4_subnet_prefix_length: 24,
+        ipv6_subnet_prefix_length: 48,
         per_ip_min_interval: 10000.into(),
         max_bytes_read_write: std::f64::INFINITY,
         max_bootstrap_message_size: MAX_BOOTSTRAP_MESSAGE_SIZE,
@@ -301,6 +309,9 @@ pub fn get_bootstrap_config(bootstrap_public_key: PublicKey) -> BootstrapConfig
         randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
         thread_count: THREAD_COUNT,
         periods_per_cycle: PERIODS_PER_CYCLE,
+        t0: T0,
+        max_resync_slots: 0,
+        bootstrap_verification_sources: 0,
         endorsement_count: ENDORSEMENT_COUNT,
         max_advertise_length: MAX_ADVERTISE_LENGTH,
         max_bootstrap_blocks_length: MAX_BOOTSTRAP_BLOCKS,
@@ -0,0 +1,116 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Deployment of smart contracts too large to fit in a single operation.
+//!
+//! The bytecode is split into chunks carried by the datastores of several `ExecuteSC` operations,
+//! all executing the same small loader contract provided by the user.
+//! The loader is expected to implement the following protocol, driven by the operation datastore:
+//! * chunk operations: for each chunk index `i`, the entry `CHUNK_KEY ++ i` holds the chunk
+//!   and `CHUNK_HASH_KEY ++ i` its hash. The loader checks every chunk against its hash
+//!   and stores it in the datastore of the sender address under `h ++ i`,
+//!   `h` being the hash of the whole bytecode held by `BYTECODE_HASH_KEY`.
+//! * the assembly operation, sent once all chunk operations are final: `ASSEMBLE_KEY` holds the number of chunks.
+//!   The loader concatenates the stored chunks, checks the result against `BYTECODE_HASH_KEY`,
+//!   deploys it and deletes the stored chunks.
+//!
+//! Chunk indexes and counts are encoded as big-endian `u32`, hashes as their 32 bytes.
+
+use anyhow::{bail, Result};
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use massa_models::config::{
+    MAX_OPERATION_DATASTORE_ENTRY_COUNT, MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+};
+use massa_models::datastore::Datastore;
+
+/// Operation datastore key prefix of a bytecode chunk
+pub(crate) const CHUNK_KEY: &[u8] = b"massa_deploy_chunk";
+
+/// Operation datastore key prefix of the hash of a bytecode chunk
+pub(crate) const CHUNK_HASH_KEY: &[u8] = b"massa_deploy_chunk_hash";
+
+/// Operation datastore key of the hash of the whole bytecode
+pub(crate) const BYTECODE_HASH_KEY: &[u8] = b"massa_deploy_bytecode_hash";
+
+/// Operation datastore key of the chunk count, asking the loader to assemble and deploy the bytecode
+pub(crate) const ASSEMBLE_KEY: &[u8] = b"massa_deploy_assemble";
+
+/// Bytecode split into the datastores of the operations deploying it
+pub(crate) struct ChunkedDeployment {
+    /// hash of the whole bytecode
+    pub bytecode_hash: Hash,
+    /// datastores of the chunk operations, that can be executed in any order
+    pub chunk_datastores: Vec<Datastore>,
+    /// datastore of the assembly operation, to execute once all the chunks are stored
+    pub assembly_datastore: Datastore,
+}
+
+/// Builds the key of an indexed datastore entry
+fn indexed_key(prefix: &[u8], index: u32) -> Vec<u8> {
+    let mut key = prefix.to_vec();
+    key.extend(index.to_be_bytes());
+    key
+}
+
+/// Splits a bytecode into the datastores of the operations deploying it through a loader.
+///
+/// # Arguments
+/// * `bytecode`: the bytecode to deploy
+/// * `max_datastore_size`: maximum size in bytes of the datastore of a single operation
+pub(crate) fn split_bytecode(
+    bytecode: &[u8],
+    max_datastore_size: usize,
+) -> Result<ChunkedDeployment> {
+    let chunk_size = MAX_OPERATION_DATASTORE_VALUE_LENGTH as usize;
+    let bytecode_hash = Hash::compute_from(bytecode);
+
+    // every chunk takes two entries (chunk and hash) whose keys end with its 4 bytes index,
+    // and one entry is taken by the bytecode hash
+    let bytecode_hash_entry_size = BYTECODE_HASH_KEY.len() + HASH_SIZE_BYTES;
+    let chunk_entries_size =
+        CHUNK_KEY.len() + chunk_size + CHUNK_HASH_KEY.len() + HASH_SIZE_BYTES + 8;
+    let chunks_per_operation = ((MAX_OPERATION_DATASTORE_ENTRY_COUNT as usize).saturating_sub(1)
+        / 2)
+    .min(max_datastore_size.saturating_sub(bytecode_hash_entry_size) / chunk_entries_size);
+    if chunks_per_operation == 0 {
+        bail!(
+            "operations can not hold a {} bytes chunk: the loader is too big",
+            chunk_size
+        );
+    }
+
+    let chunk_datastores = bytecode
+        .chunks(chunk_size)
+        .enumerate()
+        .collect::<Vec<_>>()
+        .chunks(chunks_per_operation)
+        .map(|operation_chunks| {
+            let mut datastore = Datastore::new();
+            datastore.insert(
+                BYTECODE_HASH_KEY.to_vec(),
+                bytecode_hash.to_bytes().to_vec(),
+            );
+            for (index, chunk) in operation_chunks {
+                datastore.insert(indexed_key(CHUNK_KEY, *index as u32), chunk.to_vec());
+                datastore.insert(
+                    indexed_key(CHUNK_HASH_KEY, *index as u32),
+                    Hash::compute_from(chunk).to_bytes().to_vec(),
+                );
+            }
+            datastore
+        })
+        .collect();
+
+    let chunk_count = ((bytecode.len() + chunk_size - 1) / chunk_size) as u32;
+    let mut assembly_datastore = Datastore::new();
+    assembly_datastore.insert(
+        BYTECODE_HASH_KEY.to_vec(),
+        bytecode_hash.to_bytes().to_vec(),
+    );
+    assembly_datastore.insert(ASSEMBLE_KEY.to_vec(), chunk_count.to_be_bytes().to_vec());
+
+    Ok(ChunkedDeployment {
+        bytecode_hash,
+        chunk_datastores,
+        assembly_datastore,
+    })
+}
@@ -1,10 +1,19 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::chunked_deploy::split_bytecode;
+use crate::history_export::{write_activities, ExportFormat, ExportedActivity};
+use crate::i18n::{self, tr};
+use crate::profile::{self, NodeEndpoints};
 use crate::repl::Output;
+use crate::settings::SETTINGS;
 use anyhow::{anyhow, bail, Result};
 use console::style;
+use massa_hash::Hash;
 use massa_models::api::{
-    AddressInfo, CompactAddressInfo, DatastoreEntryInput, EventFilter, OperationInput,
+    AddressActivity, AddressDrawsQuery, AddressHistoryQuery, AddressInfo, CompactAddressInfo,
+    ContractsInput, DatastoreEntryInput, DatastoreKeysInput, EventFilter, GraphDotQuery,
+    LedgerProofInput, NodeSubsystem, OperationExecutionInfo, OperationExecutionStatus,
+    OperationInput, StakingKeyRotationInput, SubsystemAction,
 };
 use massa_models::api::{ReadOnlyBytecodeExecution, ReadOnlyCall};
 use massa_models::node::NodeId;
@@ -19,7 +28,7 @@ use massa_models::{
     slot::Slot,
 };
 use massa_sdk::Client;
-use massa_signature::KeyPair;
+use massa_signature::{KeyPair, PublicKey, Signature};
 use massa_time::MassaTime;
 use massa_wallet::Wallet;
 use serde::Serialize;
@@ -29,9 +38,25 @@ use std::fmt::{Debug, Display};
 use std::net::IpAddr;
 use std::path::PathBuf;
 use std::process;
+use std::time::Duration;
 use strum::{EnumMessage, EnumProperty, IntoEnumIterator};
 use strum_macros::{Display, EnumIter, EnumMessage, EnumProperty, EnumString};
 
+/// Number of datastore keys requested per page by `get_datastore_keys`
+const DATASTORE_KEYS_PAGE_SIZE: u64 = 100;
+
+/// Number of contracts requested per page by `get_contracts`
+const CONTRACTS_PAGE_SIZE: u64 = 100;
+
+/// Number of activities requested per page by `get_address_history` and `export_history`
+const ADDRESS_HISTORY_PAGE_SIZE: u64 = 1000;
+
+/// Time between two polls of the status of an operation by `watch_operation`
+const WATCH_OPERATION_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Time after which `watch_operation` gives up if the operation is still not final
+const WATCH_OPERATION_DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+
 /// All the client commands
 /// the order they are defined is the order they are displayed in so be careful
 /// Maybe it would be worth renaming some of them for consistency
@@ -72,12 +97,30 @@ pub enum Command {
     )]
     node_ban_by_id,
 
-    #[strum(ascii_case_insensitive, message = "stops the node")]
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "[--graceful]"),
+        message = "stops the node, with --graceful: stops accepting blocks and operations, saves the pool, executes the pending final slots and flushes the ledger first"
+    )]
     node_stop,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Subsystem Action"),
+        message = "act on an individual subsystem of the node: Production (Stop, Restart, Pause or Resume) or PublicApi (Stop or Restart)"
+    )]
+    node_manage_subsystem,
+
     #[strum(ascii_case_insensitive, message = "show staking addresses")]
     node_get_staking_addresses,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "[Address1 Address2 ...]"),
+        message = "show the recent endorsement draws of the given addresses (default: staking addresses) and whether they were produced and included"
+    )]
+    node_get_endorsement_diagnostics,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address1 Address2 ..."),
@@ -85,6 +128,38 @@ pub enum Command {
     )]
     node_remove_staking_addresses,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address RollCount"),
+        message = "sell rolls of a staking address of the node, which then stops compounding rolls until the node restarts"
+    )]
+    node_sell_staking_rolls,
+
+    #[strum(
+        ascii_case_insensitive,
+        message = "show the staking performance of each staking address of the node: produced and missed blocks, endorsements and rewards per cycle, miss ratio"
+    )]
+    node_get_staking_report,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "NewSecretKey1 NewSecretKey2 ... OldAddress1 OldAddress2 ..."),
+        message = "replace staking addresses without restarting the node: the new keys stake right away and the old addresses are removed once the rolls of the new ones are active"
+    )]
+    node_rotate_staking_keys,
+
+    #[strum(
+        ascii_case_insensitive,
+        message = "show the staking key rotations started since the node started"
+    )]
+    node_get_staking_key_rotations,
+
+    #[strum(
+        ascii_case_insensitive,
+        message = "show the operations left out of the blocks produced by the node because of its execution policy"
+    )]
+    node_get_execution_policy_stats,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "SecretKey1 SecretKey2 ..."),
@@ -113,12 +188,58 @@ pub enum Command {
     )]
     node_remove_from_whitelist,
 
+    #[strum(
+        ascii_case_insensitive,
+        message = "show the known peers with their quality score, best scored first"
+    )]
+    node_get_peer_scores,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "FilePath"),
+        message = "export a snapshot of the disk ledger to a file of the node host"
+    )]
+    node_export_ledger_snapshot,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "[--repair]"),
+        message = "check the whole final ledger against its hash and Merkle tree, with --repair: rebuild them from the ledger entries if they do not match"
+    )]
+    node_verify_ledger_integrity,
+
     #[strum(
         ascii_case_insensitive,
         message = "show the status of the node (reachable? number of peers connected, consensus, version, config parameter summary...)"
     )]
     get_status,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "SlotCount"),
+        message = "show network-wide activity statistics over the given number of latest final slots (operations per second, fees, gas utilization, events)"
+    )]
+    get_network_stats,
+
+    #[strum(
+        ascii_case_insensitive,
+        message = "show low, medium and high fee suggestions based on the latest final blocks and the number of operations in the pool"
+    )]
+    get_fee_recommendation,
+
+    #[strum(
+        ascii_case_insensitive,
+        message = "show the latest blockclique re-organizations (depth, dropped and new blocks)"
+    )]
+    get_reorg_history,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "[--from-slot Slot] [--to-slot Slot] [--output File]"),
+        message = "render the block graph in the DOT format of graphviz, printed or written to a file: final blocks in green, blockclique in blue, stale blocks in red, clique indexes in the labels (slots formatted as period,thread, --to-slot excluded)"
+    )]
+    get_graph_dot,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address1 Address2 ..."),
@@ -126,6 +247,34 @@ pub enum Command {
     )]
     get_addresses,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address1 Address2 ..."),
+        message = "get the known upcoming block and endorsement draws of a list of addresses"
+    )]
+    get_address_draws,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address StartSlot EndSlot"),
+        message = "get the recent activity of an address in chronological order: produced blocks, created operations, received credits (slots formatted as period,thread, optional)"
+    )]
+    get_address_history,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address OutputFile [--from-slot Slot] [--to-slot Slot] [--format csv|json]"),
+        message = "export the recent activity of an address to a file for accounting, with the timestamps of the slots: received transfers, rewards, deferred credits, produced blocks and created operations (slots formatted as period,thread, --to-slot excluded, CSV by default)"
+    )]
+    export_history,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address1 Address2 ..."),
+        message = "get the deferred credits of a list of addresses, upcoming and recently paid, with the roll sells they reimburse"
+    )]
+    get_deferred_credit_schedule,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address Key"),
@@ -133,6 +282,27 @@ pub enum Command {
     )]
     get_datastore_entry,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address [Prefix] [LastKey]"),
+        message = "get a page of the datastore keys of an address starting with a prefix, after a given key (prefix and key must be UTF-8)"
+    )]
+    get_datastore_keys,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "[LastAddress]"),
+        message = "get a page of the final addresses holding a bytecode, after a given address"
+    )]
+    get_contracts,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address [DatastoreKey]"),
+        message = "get a proof of inclusion of the final balance of an address, or of one of its final datastore entries (key must be UTF-8)"
+    )]
+    get_ledger_proof,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "BlockId"),
@@ -154,6 +324,20 @@ pub enum Command {
     )]
     get_operations,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "OperationId1 OperationId2 ..."),
+        message = "show the status of a list of operations in the pool, with the reason of their eviction if they were evicted"
+    )]
+    get_operation_pool_status,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "OperationId TimeoutSeconds"),
+        message = "follow the status of an operation until it is final, or until the timeout expires (300 seconds by default)"
+    )]
+    watch_operation,
+
     #[strum(
         ascii_case_insensitive,
         props(
@@ -196,6 +380,27 @@ pub enum Command {
     )]
     wallet_sign,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address Path"),
+        message = "sign the given file with given address (address must be in the wallet), producing a detached signature"
+    )]
+    wallet_sign_file,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Profile [Password]"),
+        message = "switch to the wallet of a profile, and to its node if it has its own"
+    )]
+    wallet_switch,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "PublicKey Path Signature"),
+        message = "verify a detached signature of the given file"
+    )]
+    verify_file_signature,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address RollCount Fee"),
@@ -224,6 +429,13 @@ pub enum Command {
     )]
     send_smart_contract,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "SenderAddress PathToLoader PathToBytecode MaxGas GasPrice Fee",),
+        message = "deploy a bytecode too big for a single operation in chunks, through a loader contract"
+    )]
+    send_smart_contract_chunked,
+
     #[strum(
         ascii_case_insensitive,
         props(
@@ -259,21 +471,23 @@ pub enum Command {
 
 /// Display the help of all commands
 pub(crate) fn help() {
-    println!("HELP of Massa client (list of available commands):");
+    println!("{}", tr!("help.header"));
+    println!("{}", style(tr!("help.amount_units")).color256(8).italic()); // grey
+    println!("{}", style(tr!("help.address_names")).color256(8).italic()); // grey
     Command::iter().map(|c| c.help()).collect()
 }
 
 /// bail a shinny RPC error
 macro_rules! rpc_error {
     ($e:expr) => {
-        bail!("check if your node is running: {}", $e)
+        bail!(tr!("error.rpc", $e))
     };
 }
 
 /// print a yellow warning
 macro_rules! client_warning {
     ($e:expr) => {
-        println!("{}: {}", style("WARNING").yellow(), $e)
+        println!("{}: {}", style(tr!("warning.label")).yellow(), $e)
     };
 }
 
@@ -329,7 +543,7 @@ impl ExtendedWallet {
 impl Display for ExtendedWallet {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.0.is_empty() {
-            client_warning!("your wallet does not contain any key, use 'wallet_generate_secret_key' to generate a new key and add it to your wallet");
+            client_warning!(tr!("warning.empty_wallet"));
         }
 
         for entry in self.0.values() {
@@ -339,6 +553,72 @@ impl Display for ExtendedWallet {
     }
 }
 
+/// Status of a watched operation, observed at a given time
+#[derive(Debug, Serialize)]
+pub struct OperationStatusChange {
+    /// time at which the status was observed
+    pub time: MassaTime,
+    /// progress of the operation
+    pub status: OperationExecutionStatus,
+    /// blocks including the operation
+    pub in_blocks: Vec<BlockId>,
+    /// outcome of the execution of the operation, if executed
+    pub execution: Option<OperationExecutionInfo>,
+}
+
+impl OperationStatusChange {
+    /// true if both observations report the same status
+    fn same_status(&self, other: &OperationStatusChange) -> bool {
+        self.status == other.status
+            && self.in_blocks == other.in_blocks
+            && self.execution == other.execution
+    }
+}
+
+impl Display for OperationStatusChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.time.to_utc_string(), self.status)?;
+        if !self.in_blocks.is_empty() {
+            write!(
+                f,
+                ", in block(s) {}",
+                self.in_blocks
+                    .iter()
+                    .map(|block_id| block_id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+        if let Some(execution) = &self.execution {
+            write!(f, "\n{}", execution.to_string().trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Status changes of an operation followed by `watch_operation`
+#[derive(Debug, Serialize)]
+pub struct WatchedOperation {
+    /// id of the operation
+    pub id: OperationId,
+    /// successive statuses of the operation, oldest first
+    pub changes: Vec<OperationStatusChange>,
+    /// true if the operation became final before the timeout
+    pub is_final: bool,
+}
+
+impl Display for WatchedOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_final {
+            write!(f, "{}", tr!("info.operation_final", self.id))
+        } else if self.changes.is_empty() {
+            write!(f, "{}", tr!("warning.operation_not_found", self.id))
+        } else {
+            write!(f, "{}", tr!("warning.operation_not_final", self.id))
+        }
+    }
+}
+
 impl Command {
     /// Display the help of the command
     /// with fancy colors and so on
@@ -346,31 +626,46 @@ impl Command {
         println!(
             "- {} {}: {}{}",
             style(self.to_string()).green(),
-            if self.get_str("args").is_some() {
-                style(self.get_str("args").unwrap_or("")).yellow()
-            } else {
-                style("no args").color256(8).italic() // grey
+            match self.args_hint() {
+                Some(args) => style(args.to_string()).yellow(),
+                None => style(tr!("help.no_args")).color256(8).italic(), // grey
             },
             if self.get_str("todo").is_some() {
-                style(self.get_str("todo").unwrap_or("[not yet implemented] ")).red()
+                style(
+                    self.get_str("todo")
+                        .unwrap_or("[not yet implemented] ")
+                        .to_string(),
+                )
+                .red()
             } else {
-                style("")
+                style(String::new())
             },
-            self.get_message().unwrap()
+            self.description()
         )
     }
 
+    /// Arguments expected by the command, as displayed in the help
+    pub(crate) fn args_hint(&self) -> Option<&'static str> {
+        self.get_str("args")
+    }
+
+    /// Description of the command in the selected language
+    pub(crate) fn description(&self) -> String {
+        i18n::get_opt(&format!("help.{}", self))
+            .unwrap_or_else(|| self.get_message().unwrap_or_default().to_string())
+    }
+
     /// run a given command
     ///
     /// # parameters
-    /// - client: the RPC client
-    /// - wallet: an access to the wallet
+    /// - client: the RPC client, replaced when switching profiles
+    /// - wallet: an access to the wallet, replaced when switching profiles
     /// - parameters: the parsed parameters
     /// - json: true if --json was passed as an option
     ///     it means that we don't want to print anything we just want the json output
     pub(crate) async fn run(
         &self,
-        client: &Client,
+        client: &mut Client,
         wallet: &mut Wallet,
         parameters: &[String],
         json: bool,
@@ -384,9 +679,7 @@ impl Command {
                         if let Ok(c) = parameters[0].parse::<Command>() {
                             c.help();
                         } else {
-                            println!(
-                                "Command not found!\ntype \"help\" to get the list of commands"
-                            );
+                            println!("{}", tr!("help.command_not_found"));
                             help();
                         }
                     } else {
@@ -401,7 +694,7 @@ impl Command {
                 match client.private.node_unban_by_ip(ips).await {
                     Ok(()) => {
                         if !json {
-                            println!("Request of unbanning successfully sent!")
+                            println!("{}", tr!("success.unban_sent"))
                         }
                     }
                     Err(e) => rpc_error!(e),
@@ -414,7 +707,7 @@ impl Command {
                 match client.private.node_unban_by_id(ids).await {
                     Ok(()) => {
                         if !json {
-                            println!("Request of unbanning successfully sent!")
+                            println!("{}", tr!("success.unban_sent"))
                         }
                     }
                     Err(e) => rpc_error!(e),
@@ -427,7 +720,7 @@ impl Command {
                 match client.private.node_ban_by_ip(ips).await {
                     Ok(()) => {
                         if !json {
-                            println!("Request of banning successfully sent!")
+                            println!("{}", tr!("success.ban_sent"))
                         }
                     }
                     Err(e) => rpc_error!(e),
@@ -440,7 +733,7 @@ impl Command {
                 match client.private.node_ban_by_id(ids).await {
                     Ok(()) => {
                         if !json {
-                            println!("Request of banning successfully sent!")
+                            println!("{}", tr!("success.ban_sent"))
                         }
                     }
                     Err(e) => rpc_error!(e),
@@ -449,10 +742,42 @@ impl Command {
             }
 
             Command::node_stop => {
-                match client.private.stop_node().await {
+                let graceful = match parameters {
+                    [] => false,
+                    [flag] if flag == "--graceful" => true,
+                    [_] => bail!(tr!("error.invalid_parameter")),
+                    _ => bail!(tr!("error.wrong_parameter_count")),
+                };
+                let res = if graceful {
+                    client.private.stop_node_gracefully().await
+                } else {
+                    client.private.stop_node().await
+                };
+                match res {
+                    Ok(()) => {
+                        if !json {
+                            println!("{}", tr!("success.stop_sent"))
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                };
+                Ok(Box::new(()))
+            }
+
+            Command::node_manage_subsystem => {
+                if parameters.len() != 2 {
+                    bail!(tr!("error.wrong_parameter_count"));
+                }
+                let subsystem = parameters[0].parse::<NodeSubsystem>()?;
+                let action = parameters[1].parse::<SubsystemAction>()?;
+                match client
+                    .private
+                    .node_manage_subsystem(subsystem, action)
+                    .await
+                {
                     Ok(()) => {
                         if !json {
-                            println!("Request of stopping the Node successfully sent")
+                            println!("{}", tr!("success.subsystem_managed", subsystem, action))
                         }
                     }
                     Err(e) => rpc_error!(e),
@@ -467,12 +792,27 @@ impl Command {
                 }
             }
 
+            Command::node_get_endorsement_diagnostics => {
+                let addresses = if parameters.is_empty() {
+                    match client.private.get_staking_addresses().await {
+                        Ok(staking_addresses) => staking_addresses.into_iter().collect(),
+                        Err(e) => rpc_error!(e),
+                    }
+                } else {
+                    resolve_addresses(client, parameters).await?
+                };
+                match client.public.get_endorsement_diagnostics(addresses).await {
+                    Ok(diagnostics) => Ok(Box::new(diagnostics)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::node_remove_staking_addresses => {
-                let addresses = parse_vec::<Address>(parameters)?;
+                let addresses = resolve_addresses(client, parameters).await?;
                 match client.private.remove_staking_addresses(addresses).await {
                     Ok(()) => {
                         if !json {
-                            println!("Addresses successfully removed!")
+                            println!("{}", tr!("success.staking_addresses_removed"))
                         }
                     }
                     Err(e) => rpc_error!(e),
@@ -480,6 +820,60 @@ impl Command {
                 Ok(Box::new(()))
             }
 
+            Command::node_sell_staking_rolls => {
+                if parameters.len() != 2 {
+                    bail!(tr!("error.wrong_parameter_count"));
+                }
+                let address = resolve_address(client, &parameters[0]).await?;
+                let roll_count = parameters[1].parse::<u64>()?;
+                match client
+                    .private
+                    .node_sell_staking_rolls(address, roll_count)
+                    .await
+                {
+                    Ok(operation_id) => Ok(Box::new(vec![operation_id])),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::node_get_staking_report => match client.private.get_staking_report().await {
+                Ok(reports) => Ok(Box::new(reports)),
+                Err(e) => rpc_error!(e),
+            },
+
+            Command::node_rotate_staking_keys => {
+                // secret keys and addresses have distinct prefixes
+                let (new_secret_keys, old_addresses): (Vec<String>, Vec<String>) = parameters
+                    .iter()
+                    .cloned()
+                    .partition(|parameter| parameter.parse::<KeyPair>().is_ok());
+                if new_secret_keys.is_empty() || old_addresses.is_empty() {
+                    bail!(tr!("error.wrong_parameter_count"));
+                }
+                let input = StakingKeyRotationInput {
+                    new_secret_keys,
+                    old_addresses: resolve_addresses(client, &old_addresses).await?,
+                };
+                match client.private.node_rotate_staking_keys(input).await {
+                    Ok(rotation) => Ok(Box::new(rotation)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::node_get_staking_key_rotations => {
+                match client.private.get_staking_key_rotations().await {
+                    Ok(rotations) => Ok(Box::new(rotations)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::node_get_execution_policy_stats => {
+                match client.private.get_execution_policy_stats().await {
+                    Ok(stats) => Ok(Box::new(stats)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::node_add_staking_secret_keys => {
                 match client
                     .private
@@ -488,7 +882,7 @@ impl Command {
                 {
                     Ok(()) => {
                         if !json {
-                            println!("Keys successfully added!")
+                            println!("{}", tr!("success.staking_keys_added"))
                         }
                     }
                     Err(e) => rpc_error!(e),
@@ -498,10 +892,10 @@ impl Command {
 
             Command::node_testnet_rewards_program_ownership_proof => {
                 if parameters.len() != 2 {
-                    bail!("wrong number of parameters");
+                    bail!(tr!("error.wrong_parameter_count"));
                 }
                 // parse
-                let addr = parameters[0].parse::<Address>()?;
+                let addr = resolve_address(client, &parameters[0]).await?;
                 let msg = parameters[1].as_bytes().to_vec();
                 // get address signature
                 if let Some(addr_sig) = wallet.sign_message(&addr, msg.clone()) {
@@ -510,7 +904,7 @@ impl Command {
                         // print concatenation
                         Ok(node_sig) => {
                             if !json {
-                                println!("Enter the following in discord:");
+                                println!("{}", tr!("info.ownership_proof"));
                             }
                             Ok(Box::new(format!(
                                 "{}/{}/{}/{}",
@@ -523,7 +917,7 @@ impl Command {
                         Err(e) => rpc_error!(e),
                     }
                 } else {
-                    bail!("address not found")
+                    bail!(tr!("error.address_not_found"))
                 }
             }
 
@@ -532,19 +926,156 @@ impl Command {
                 Err(e) => rpc_error!(e),
             },
 
+            Command::get_network_stats => {
+                if parameters.len() != 1 {
+                    bail!(tr!("error.wrong_parameter_count"));
+                }
+                let window = parameters[0].parse::<u64>()?;
+                match client.public.get_network_stats(window).await {
+                    Ok(stats) => Ok(Box::new(stats)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::get_fee_recommendation => match client.public.get_fee_recommendation().await {
+                Ok(recommendation) => Ok(Box::new(recommendation)),
+                Err(e) => rpc_error!(e),
+            },
+
+            Command::get_reorg_history => match client.public.get_reorg_history().await {
+                Ok(history) => Ok(Box::new(history)),
+                Err(e) => rpc_error!(e),
+            },
+
+            Command::get_graph_dot => {
+                let mut query = GraphDotQuery {
+                    start: None,
+                    end: None,
+                };
+                let mut output = None;
+                let mut options = parameters.iter();
+                while let Some(option) = options.next() {
+                    let value = options
+                        .next()
+                        .ok_or_else(|| anyhow!(tr!("error.wrong_parameter_count")))?;
+                    match option.as_str() {
+                        "--from-slot" => query.start = Some(value.parse::<Slot>()?),
+                        "--to-slot" => query.end = Some(value.parse::<Slot>()?),
+                        "--output" => output = Some(PathBuf::from(value)),
+                        _ => bail!(tr!("error.invalid_parameter")),
+                    }
+                }
+                let dot = match client.public.get_graph_dot(query).await {
+                    Ok(dot) => dot,
+                    Err(e) => rpc_error!(e),
+                };
+                match output {
+                    Some(output) => {
+                        std::fs::write(&output, dot)?;
+                        if !json {
+                            println!("{}", tr!("success.graph_exported", output.display()));
+                        }
+                        Ok(Box::new(()))
+                    }
+                    None => Ok(Box::new(dot)),
+                }
+            }
+
             Command::get_addresses => {
-                let addresses = parse_vec::<Address>(parameters)?;
+                let addresses = resolve_addresses(client, parameters).await?;
                 match client.public.get_addresses(addresses).await {
                     Ok(addresses_info) => Ok(Box::new(addresses_info)),
                     Err(e) => rpc_error!(e),
                 }
             }
 
+            Command::get_address_draws => {
+                let query = AddressDrawsQuery {
+                    addresses: resolve_addresses(client, parameters).await?,
+                    start: None,
+                    end: None,
+                };
+                match client.public.get_address_draws(query).await {
+                    Ok(draws) => Ok(Box::new(draws)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::get_address_history => {
+                if parameters.is_empty() || parameters.len() > 3 {
+                    bail!(tr!("error.wrong_parameter_count"));
+                }
+                let address = resolve_address(client, &parameters[0]).await?;
+                let start = parameters.get(1).map(|s| s.parse::<Slot>()).transpose()?;
+                let end = parameters.get(2).map(|s| s.parse::<Slot>()).transpose()?;
+                let thread_count = match client.public.get_status().await {
+                    Ok(status) => status.config.thread_count,
+                    Err(e) => rpc_error!(e),
+                };
+                let history =
+                    get_full_address_history(client, address, start, end, thread_count).await?;
+                Ok(Box::new(history))
+            }
+
+            Command::export_history => {
+                let (address, output, options) = match parameters {
+                    [address, output, options @ ..] => (address, PathBuf::from(output), options),
+                    _ => bail!(tr!("error.wrong_parameter_count")),
+                };
+                let (mut start, mut end, mut format) = (None, None, ExportFormat::Csv);
+                let mut options = options.iter();
+                while let Some(option) = options.next() {
+                    let value = options
+                        .next()
+                        .ok_or_else(|| anyhow!(tr!("error.wrong_parameter_count")))?;
+                    match option.as_str() {
+                        "--from-slot" => start = Some(value.parse::<Slot>()?),
+                        "--to-slot" => end = Some(value.parse::<Slot>()?),
+                        "--format" => format = value.parse::<ExportFormat>()?,
+                        _ => bail!(tr!("error.invalid_parameter")),
+                    }
+                }
+                let address = resolve_address(client, address).await?;
+                let config = match client.public.get_status().await {
+                    Ok(status) => status.config,
+                    Err(e) => rpc_error!(e),
+                };
+                let history =
+                    get_full_address_history(client, address, start, end, config.thread_count)
+                        .await?;
+                let activities = history
+                    .iter()
+                    .map(|activity| ExportedActivity::new(activity, &config))
+                    .collect::<Result<Vec<_>>>()?;
+                let mut file = std::io::BufWriter::new(std::fs::File::create(&output)?);
+                write_activities(&mut file, &activities, format)?;
+                std::io::Write::flush(&mut file)?;
+                if !json {
+                    println!(
+                        "{}",
+                        tr!(
+                            "success.history_exported",
+                            activities.len(),
+                            output.display()
+                        )
+                    );
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::get_deferred_credit_schedule => {
+                let addresses = resolve_addresses(client, parameters).await?;
+                match client.public.get_deferred_credit_schedule(addresses).await {
+                    Ok(schedules) => Ok(Box::new(schedules)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::get_datastore_entry => {
                 if parameters.len() != 2 {
-                    bail!("invalid number of parameters");
+                    bail!(tr!("error.wrong_parameter_count"));
                 }
-                let address = parameters[0].parse::<Address>()?;
+                let address = resolve_address(client, &parameters[0]).await?;
                 let key = parameters[1].as_bytes().to_vec();
                 match client
                     .public
@@ -556,9 +1087,59 @@ impl Command {
                 }
             }
 
+            Command::get_datastore_keys => {
+                if parameters.is_empty() || parameters.len() > 3 {
+                    bail!(tr!("error.wrong_parameter_count"));
+                }
+                let input = DatastoreKeysInput {
+                    address: resolve_address(client, &parameters[0]).await?,
+                    prefix: parameters
+                        .get(1)
+                        .map(|prefix| prefix.as_bytes().to_vec())
+                        .unwrap_or_default(),
+                    last_key: parameters.get(2).map(|key| key.as_bytes().to_vec()),
+                    max_count: DATASTORE_KEYS_PAGE_SIZE,
+                };
+                match client.public.get_datastore_keys(input).await {
+                    Ok(result) => Ok(Box::new(result)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::get_contracts => {
+                if parameters.len() > 1 {
+                    bail!(tr!("error.wrong_parameter_count"));
+                }
+                let input = ContractsInput {
+                    last_address: match parameters.first() {
+                        Some(address) => Some(resolve_address(client, address).await?),
+                        None => None,
+                    },
+                    max_count: CONTRACTS_PAGE_SIZE,
+                };
+                match client.public.get_contracts(input).await {
+                    Ok(result) => Ok(Box::new(result)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::get_ledger_proof => {
+                if parameters.is_empty() || parameters.len() > 2 {
+                    bail!(tr!("error.wrong_parameter_count"));
+                }
+                let input = LedgerProofInput {
+                    address: resolve_address(client, &parameters[0]).await?,
+                    datastore_key: parameters.get(1).map(|key| key.as_bytes().to_vec()),
+                };
+                match client.public.get_ledger_entry_proof(input).await {
+                    Ok(result) => Ok(Box::new(result)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::get_block => {
                 if parameters.len() != 1 {
-                    bail!("wrong param numbers")
+                    bail!(tr!("error.wrong_parameter_count"))
                 }
                 let block_id = parameters[0].parse::<BlockId>()?;
                 match client.public.get_block(block_id).await {
@@ -583,6 +1164,28 @@ impl Command {
                 }
             }
 
+            Command::get_operation_pool_status => {
+                let operations = parse_vec::<OperationId>(parameters)?;
+                match client.public.get_operation_pool_status(operations).await {
+                    Ok(statuses) => Ok(Box::new(statuses)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::watch_operation => {
+                if parameters.is_empty() || parameters.len() > 2 {
+                    bail!(tr!("error.wrong_parameter_count"));
+                }
+                let operation_id = parameters[0].parse::<OperationId>()?;
+                let timeout = match parameters.get(1) {
+                    Some(seconds) => Duration::from_secs(seconds.parse::<u64>()?),
+                    None => WATCH_OPERATION_DEFAULT_TIMEOUT,
+                };
+                Ok(Box::new(
+                    watch_operation(client, operation_id, timeout, json).await?,
+                ))
+            }
+
             Command::get_filtered_sc_output_event => {
                 let p_list: [&str; 6] = [
                     "start",
@@ -598,7 +1201,7 @@ impl Command {
                     if s.len() == 2 && p_list.contains(&s[0]) {
                         p.insert(s[0], s[1]);
                     } else {
-                        bail!("invalid parameter");
+                        bail!(tr!("error.invalid_parameter"));
                     }
                 }
                 let filter = EventFilter {
@@ -617,7 +1220,7 @@ impl Command {
 
             Command::wallet_info => {
                 if !json {
-                    client_warning!("do not share your key");
+                    client_warning!(tr!("warning.do_not_share_key"));
                 }
                 match client
                     .public
@@ -637,8 +1240,8 @@ impl Command {
                 if json {
                     Ok(Box::new(ad.to_string()))
                 } else {
-                    println!("Generated {} address and added it to the wallet", ad);
-                    println!("Type `node_add_staking_secret_keys <your secret key>` to start staking with this key.\n");
+                    println!("{}", tr!("info.generated_address", ad));
+                    println!("{}", tr!("info.start_staking_with_key"));
                     Ok(Box::new(()))
                 }
             }
@@ -650,22 +1253,22 @@ impl Command {
                     return Ok(Box::new(addresses));
                 } else {
                     for address in addresses {
-                        println!("Derived and added address {} to the wallet.", address);
+                        println!("{}", tr!("info.derived_address", address));
                     }
-                    println!("Type `node_add_staking_secret_keys <your secret key>` to start staking with the corresponding key.\n");
+                    println!("{}", tr!("info.start_staking_with_keys"));
                 }
                 Ok(Box::new(()))
             }
 
             Command::wallet_remove_addresses => {
                 let mut res = "".to_string();
-                let addresses = parse_vec::<Address>(parameters)?;
+                let addresses = resolve_addresses(client, parameters).await?;
                 match wallet.remove_addresses(&addresses) {
                     Ok(_) => {
-                        let _ = writeln!(res, "Addresses removed from the wallet");
+                        let _ = writeln!(res, "{}", tr!("info.wallet_addresses_removed"));
                     }
                     Err(_) => {
-                        let _ = writeln!(res, "Wallet error while removing addresses");
+                        let _ = writeln!(res, "{}", tr!("error.wallet_remove_addresses"));
                     }
                 }
                 if !json {
@@ -676,15 +1279,15 @@ impl Command {
 
             Command::buy_rolls => {
                 if parameters.len() != 3 {
-                    bail!("wrong number of parameters");
+                    bail!(tr!("error.wrong_parameter_count"));
                 }
-                let addr = parameters[0].parse::<Address>()?;
+                let addr = resolve_address(client, &parameters[0]).await?;
                 let roll_count = parameters[1].parse::<u64>()?;
-                let fee = parameters[2].parse::<Amount>()?;
+                let fee = parse_amount(&parameters[2])?;
 
                 if !json {
                     let roll_price = match client.public.get_status().await {
-                        Err(e) => bail!("RpcError: {}", e),
+                        Err(e) => bail!(tr!("error.rpc_short", e)),
                         Ok(status) => status.config.roll_price,
                     };
                     match roll_price
@@ -698,22 +1301,22 @@ impl Command {
                                 match addresses_info.get(0) {
                                     Some(info) => {
                                         if info.candidate_balance < total {
-                                            client_warning!("this operation may be rejected due to insufficient balance");
+                                            client_warning!(tr!("warning.insufficient_balance"));
                                         }
                                     }
                                     None => {
-                                        client_warning!(format!("address {} not found", addr))
+                                        client_warning!(tr!("warning.address_not_found", addr))
                                     }
                                 }
                             }
                         }
                         None => {
-                            client_warning!("the total amount hit the limit overflow, operation will certainly be rejected");
+                            client_warning!(tr!("warning.amount_overflow"));
                         }
                     }
                     if let Ok(staked_keys) = client.private.get_staking_addresses().await {
                         if !staked_keys.contains(&addr) {
-                            client_warning!("You are buying rolls with an address not registered for staking. Don't forget to run 'node_add_staking_secret_keys <your_secret_key'");
+                            client_warning!(tr!("warning.address_not_staking"));
                         }
                     }
                 }
@@ -730,11 +1333,11 @@ impl Command {
 
             Command::sell_rolls => {
                 if parameters.len() != 3 {
-                    bail!("wrong number of parameters");
+                    bail!(tr!("error.wrong_parameter_count"));
                 }
-                let addr = parameters[0].parse::<Address>()?;
+                let addr = resolve_address(client, &parameters[0]).await?;
                 let roll_count = parameters[1].parse::<u64>()?;
-                let fee = parameters[2].parse::<Amount>()?;
+                let fee = parse_amount(&parameters[2])?;
 
                 if !json {
                     if let Ok(addresses_info) = client.public.get_addresses(vec![addr]).await {
@@ -743,10 +1346,10 @@ impl Command {
                                 if info.candidate_balance < fee
                                     || roll_count > info.candidate_roll_count
                                 {
-                                    client_warning!("this operation may be rejected due to insufficient balance or roll count");
+                                    client_warning!(tr!("warning.insufficient_balance_or_rolls"));
                                 }
                             }
-                            None => client_warning!(format!("address {} not found", addr)),
+                            None => client_warning!(tr!("warning.address_not_found", addr)),
                         }
                     }
                 }
@@ -764,23 +1367,23 @@ impl Command {
 
             Command::send_transaction => {
                 if parameters.len() != 4 {
-                    bail!("wrong number of parameters");
+                    bail!(tr!("error.wrong_parameter_count"));
                 }
-                let addr = parameters[0].parse::<Address>()?;
-                let recipient_address = parameters[1].parse::<Address>()?;
-                let amount = parameters[2].parse::<Amount>()?;
-                let fee = parameters[3].parse::<Amount>()?;
+                let addr = resolve_address(client, &parameters[0]).await?;
+                let recipient_address = resolve_address(client, &parameters[1]).await?;
+                let amount = parse_amount(&parameters[2])?;
+                let fee = parse_amount(&parameters[3])?;
 
                 if !json {
                     if let Ok(addresses_info) = client.public.get_addresses(vec![addr]).await {
                         match addresses_info.get(0) {
                             Some(info) => {
                                 if info.candidate_balance < fee {
-                                    client_warning!("this operation may be rejected due to insufficient balance");
+                                    client_warning!(tr!("warning.insufficient_balance"));
                                 }
                             }
                             None => {
-                                client_warning!(format!("address {} not found", addr))
+                                client_warning!(tr!("warning.address_not_found", addr))
                             }
                         }
                     }
@@ -802,7 +1405,7 @@ impl Command {
             Command::when_episode_ends => {
                 let end = match client.public.get_status().await {
                     Ok(node_status) => node_status.config.end_timestamp,
-                    Err(e) => bail!("RpcError: {}", e),
+                    Err(e) => bail!(tr!("error.rpc_short", e)),
                 };
                 let mut res = "".to_string();
                 if let Some(e) = end {
@@ -810,9 +1413,13 @@ impl Command {
                         .saturating_sub(MassaTime::now(0)?)
                         .days_hours_mins_secs()?; // compensation milliseconds is zero
 
-                    let _ = write!(res, "{} days, {} hours, {} minutes, {} seconds remaining until the end of the current episode", days, hours, mins, secs);
+                    let _ = write!(
+                        res,
+                        "{}",
+                        tr!("info.episode_remaining", days, hours, mins, secs)
+                    );
                 } else {
-                    let _ = write!(res, "There is no end !");
+                    let _ = write!(res, "{}", tr!("info.episode_no_end"));
                 }
                 if !json {
                     println!("{}", res);
@@ -820,7 +1427,7 @@ impl Command {
                 Ok(Box::new(()))
             }
             Command::when_moon => {
-                let res = "At night 🌔.";
+                let res = tr!("info.moon");
                 if !json {
                     println!("{}", res);
                 }
@@ -828,13 +1435,13 @@ impl Command {
             }
             Command::send_smart_contract => {
                 if parameters.len() != 5 {
-                    bail!("wrong number of parameters");
+                    bail!(tr!("error.wrong_parameter_count"));
                 }
-                let addr = parameters[0].parse::<Address>()?;
+                let addr = resolve_address(client, &parameters[0]).await?;
                 let path = parameters[1].parse::<PathBuf>()?;
                 let max_gas = parameters[2].parse::<u64>()?;
-                let gas_price = parameters[3].parse::<Amount>()?;
-                let fee = parameters[4].parse::<Amount>()?;
+                let gas_price = parse_amount(&parameters[3])?;
+                let fee = parse_amount(&parameters[4])?;
 
                 if !json {
                     match gas_price
@@ -848,17 +1455,17 @@ impl Command {
                                 match addresses_info.get(0) {
                                     Some(info) => {
                                         if info.candidate_balance < total {
-                                            client_warning!("this operation may be rejected due to insufficient balance");
+                                            client_warning!(tr!("warning.insufficient_balance"));
                                         }
                                     }
                                     None => {
-                                        client_warning!(format!("address {} not found", addr));
+                                        client_warning!(tr!("warning.address_not_found", addr));
                                     }
                                 }
                             }
                         }
                         None => {
-                            client_warning!("the total amount hit the limit overflow, operation will certainly be rejected");
+                            client_warning!(tr!("warning.amount_overflow"));
                         }
                     }
                 };
@@ -866,10 +1473,10 @@ impl Command {
                 if !json {
                     let max_block_size = match client.public.get_status().await {
                         Ok(node_status) => node_status.config.max_block_size,
-                        Err(e) => bail!("RpcError: {}", e),
+                        Err(e) => bail!(tr!("error.rpc_short", e)),
                     };
                     if data.len() > max_block_size as usize / 2 {
-                        client_warning!("bytecode size exceeded half of the maximum size of a block, operation will certainly be rejected");
+                        client_warning!(tr!("warning.bytecode_too_big"));
                     }
                 }
                 let datastore = BTreeMap::new();
@@ -889,18 +1496,121 @@ impl Command {
                 )
                 .await
             }
+            Command::send_smart_contract_chunked => {
+                if parameters.len() != 6 {
+                    bail!(tr!("error.wrong_parameter_count"));
+                }
+                let addr = resolve_address(client, &parameters[0]).await?;
+                let loader_path = parameters[1].parse::<PathBuf>()?;
+                let path = parameters[2].parse::<PathBuf>()?;
+                let max_gas = parameters[3].parse::<u64>()?;
+                let gas_price = parse_amount(&parameters[4])?;
+                let fee = parse_amount(&parameters[5])?;
+
+                let loader = get_file_as_byte_vec(&loader_path).await?;
+                let bytecode = get_file_as_byte_vec(&path).await?;
+                let max_block_size = match client.public.get_status().await {
+                    Ok(node_status) => node_status.config.max_block_size,
+                    Err(e) => rpc_error!(e),
+                };
+                // same bound as the warning of `send_smart_contract`, the loader taking its share
+                let max_datastore_size = (max_block_size as usize / 2).saturating_sub(loader.len());
+                let deployment = split_bytecode(&bytecode, max_datastore_size)?;
+                let operation_count = deployment.chunk_datastores.len() + 1;
+                if !json {
+                    println!(
+                        "{}",
+                        tr!(
+                            "info.chunked_deployment",
+                            bytecode.len(),
+                            deployment.bytecode_hash,
+                            operation_count
+                        )
+                    );
+                }
+
+                // send all the chunks, they can be executed in any order
+                let mut operation_ids = Vec::with_capacity(operation_count);
+                for (index, datastore) in deployment.chunk_datastores.into_iter().enumerate() {
+                    let ids = send_operation_ids(
+                        client,
+                        wallet,
+                        OperationType::ExecuteSC {
+                            data: loader.clone(),
+                            max_gas,
+                            gas_price,
+                            datastore,
+                        },
+                        fee,
+                        addr,
+                    )
+                    .await?;
+                    if !json {
+                        for id in &ids {
+                            println!(
+                                "{}",
+                                tr!("info.chunk_sent", index + 1, operation_count - 1, id)
+                            );
+                        }
+                    }
+                    operation_ids.extend(ids);
+                }
+
+                // the loader can only assemble the bytecode once all the chunks are stored
+                for operation_id in &operation_ids {
+                    let watched = watch_operation(
+                        client,
+                        *operation_id,
+                        WATCH_OPERATION_DEFAULT_TIMEOUT,
+                        json,
+                    )
+                    .await?;
+                    if !watched.is_final {
+                        bail!(tr!("error.chunk_not_final", operation_id));
+                    }
+                    if let Some(error) = watched
+                        .changes
+                        .last()
+                        .and_then(|change| change.execution.as_ref())
+                        .and_then(|execution| execution.execution_error.as_ref())
+                    {
+                        bail!(tr!("error.chunk_failed", operation_id, error));
+                    }
+                }
+
+                let ids = send_operation_ids(
+                    client,
+                    wallet,
+                    OperationType::ExecuteSC {
+                        data: loader,
+                        max_gas,
+                        gas_price,
+                        datastore: deployment.assembly_datastore,
+                    },
+                    fee,
+                    addr,
+                )
+                .await?;
+                if !json {
+                    for id in &ids {
+                        println!("{}", tr!("info.assembly_sent", id));
+                    }
+                }
+                operation_ids.extend(ids);
+                Ok(Box::new(operation_ids))
+            }
             Command::call_smart_contract => {
                 if parameters.len() != 8 {
-                    bail!("wrong number of parameters");
+                    bail!(tr!("error.wrong_parameter_count"));
                 }
-                let addr = parameters[0].parse::<Address>()?;
-                let target_addr = parameters[1].parse::<Address>()?;
+                let addr = resolve_address(client, &parameters[0]).await?;
+                let target_addr = resolve_address(client, &parameters[1]).await?;
                 let target_func = parameters[2].clone();
                 let param = parameters[3].clone();
                 let max_gas = parameters[4].parse::<u64>()?;
-                let gas_price = parameters[5].parse::<Amount>()?;
-                let coins = parameters[6].parse::<Amount>()?;
-                let fee = parameters[7].parse::<Amount>()?;
+                let gas_price = parse_amount(&parameters[5])?;
+                let coins = parse_amount(&parameters[6])?;
+                let fee = parse_amount(&parameters[7])?;
                 if !json {
                     match gas_price
                         .checked_mul_u64(max_gas)
@@ -913,12 +1623,12 @@ impl Command {
                                 match addresses_info.get(0) {
                                     Some(info) => {
                                         if info.candidate_balance < total {
-                                            client_warning!("this operation may be rejected due to insufficient balance");
+                                            client_warning!(tr!("warning.insufficient_balance"));
                                         }
                                     }
                                     None => {
-                                        client_warning!(format!(
-                                            "address {} not found",
+                                        client_warning!(tr!(
+                                            "warning.address_not_found",
                                             target_addr
                                         ));
                                     }
@@ -926,7 +1636,7 @@ impl Command {
                             }
                         }
                         None => {
-                            client_warning!("the total amount hit the limit overflow, operation will certainly be rejected");
+                            client_warning!(tr!("warning.amount_overflow"));
                         }
                     }
                 };
@@ -949,26 +1659,71 @@ impl Command {
             }
             Command::wallet_sign => {
                 if parameters.len() != 2 {
-                    bail!("wrong number of parameters");
+                    bail!(tr!("error.wrong_parameter_count"));
                 }
-                let addr = parameters[0].parse::<Address>()?;
+                let addr = resolve_address(client, &parameters[0]).await?;
                 let msg = parameters[1].clone();
                 if let Some(signed) = wallet.sign_message(&addr, msg.into_bytes()) {
                     Ok(Box::new(signed))
                 } else {
-                    bail!("Missing public key")
+                    bail!(tr!("error.missing_public_key"))
+                }
+            }
+            Command::wallet_sign_file => {
+                if parameters.len() != 2 {
+                    bail!(tr!("error.wrong_parameter_count"));
+                }
+                let addr = resolve_address(client, &parameters[0]).await?;
+                let path = parameters[1].parse::<PathBuf>()?;
+                let hash = get_file_hash(path).await?;
+                if let Some(signed) = wallet.sign_hash(&addr, &hash) {
+                    Ok(Box::new(signed))
+                } else {
+                    bail!(tr!("error.missing_public_key"))
                 }
             }
+            Command::wallet_switch => {
+                if parameters.is_empty() || parameters.len() > 2 {
+                    bail!(tr!("error.wrong_parameter_count"));
+                }
+                let (wallet_path, node) = profile::get_profile(&parameters[0])?;
+                *wallet = profile::load_wallet(wallet_path, parameters.get(1).cloned())?;
+                let node = NodeEndpoints::new(node, None, None, None);
+                *client = node.connect().await?;
+                if !json {
+                    println!(
+                        "{}",
+                        tr!("success.profile_switched", parameters[0], node.label)
+                    );
+                }
+                Ok(Box::new(()))
+            }
+            Command::verify_file_signature => {
+                if parameters.len() != 3 {
+                    bail!(tr!("error.wrong_parameter_count"));
+                }
+                let public_key = parameters[0].parse::<PublicKey>()?;
+                let path = parameters[1].parse::<PathBuf>()?;
+                let signature = parameters[2].parse::<Signature>()?;
+                let hash = get_file_hash(path).await?;
+                if public_key.verify_signature(&hash, &signature).is_err() {
+                    bail!(tr!("error.invalid_file_signature"))
+                }
+                if !json {
+                    println!("{}", tr!("success.file_signature_verified"));
+                }
+                Ok(Box::new(()))
+            }
             Command::read_only_smart_contract => {
                 if parameters.len() != 3 && parameters.len() != 4 {
-                    bail!("wrong number of parameters");
+                    bail!(tr!("error.wrong_parameter_count"));
                 }
 
                 let path = parameters[0].parse::<PathBuf>()?;
                 let max_gas = parameters[1].parse::<u64>()?;
-                let simulated_gas_price = parameters[2].parse::<Amount>()?;
+                let simulated_gas_price = parse_amount(&parameters[2])?;
                 let address = if let Some(adr) = parameters.get(3) {
-                    Some(adr.parse::<Address>()?)
+                    Some(resolve_address(client, adr).await?)
                 } else {
                     None
                 };
@@ -981,6 +1736,7 @@ impl Command {
                         bytecode,
                         address,
                         operation_datastore: None, // TODO - #3072
+                        deployment_simulation: None,
                     })
                     .await
                 {
@@ -990,16 +1746,16 @@ impl Command {
             }
             Command::read_only_call => {
                 if parameters.len() != 5 && parameters.len() != 6 {
-                    bail!("wrong number of parameters");
+                    bail!(tr!("error.wrong_parameter_count"));
                 }
 
-                let target_address = parameters[0].parse::<Address>()?;
+                let target_address = resolve_address(client, &parameters[0]).await?;
                 let target_function = parameters[1].parse::<String>()?;
                 let parameter = parameters[2].parse::<String>()?;
                 let max_gas = parameters[3].parse::<u64>()?;
-                let simulated_gas_price = parameters[4].parse::<Amount>()?;
+                let simulated_gas_price = parse_amount(&parameters[4])?;
                 let caller_address = if let Some(addr) = parameters.get(5) {
-                    Some(addr.parse::<Address>()?)
+                    Some(resolve_address(client, addr).await?)
                 } else {
                     None
                 };
@@ -1024,7 +1780,7 @@ impl Command {
                 match client.private.node_whitelist(ips).await {
                     Ok(()) => {
                         if !json {
-                            println!("Request of whitelisting successfully sent!")
+                            println!("{}", tr!("success.whitelist_sent"))
                         }
                     }
                     Err(e) => rpc_error!(e),
@@ -1036,13 +1792,50 @@ impl Command {
                 match client.private.node_remove_from_whitelist(ips).await {
                     Ok(()) => {
                         if !json {
-                            println!("Request of removing from whitelist successfully sent!")
+                            println!("{}", tr!("success.remove_from_whitelist_sent"))
                         }
                     }
                     Err(e) => rpc_error!(e),
                 }
                 Ok(Box::new(()))
             }
+
+            Command::node_get_peer_scores => match client.private.node_get_peer_scores().await {
+                Ok(peer_scores) => Ok(Box::new(peer_scores)),
+                Err(e) => rpc_error!(e),
+            },
+
+            Command::node_export_ledger_snapshot => {
+                if parameters.len() != 1 {
+                    bail!(tr!("error.wrong_parameter_count"))
+                }
+                match client
+                    .private
+                    .node_export_ledger_snapshot(parameters[0].clone())
+                    .await
+                {
+                    Ok(slot) => {
+                        if !json {
+                            println!("{}", tr!("success.ledger_snapshot_exported", slot))
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::node_verify_ledger_integrity => {
+                let repair = match parameters {
+                    [] => false,
+                    [flag] if flag == "--repair" => true,
+                    [_] => bail!(tr!("error.invalid_parameter")),
+                    _ => bail!(tr!("error.wrong_parameter_count")),
+                };
+                match client.private.node_verify_ledger_integrity(repair).await {
+                    Ok(report) => Ok(Box::new(report)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
         }
     }
 }
@@ -1056,11 +1849,33 @@ async fn send_operation(
     addr: Address,
     json: bool,
 ) -> Result<Box<dyn Output>> {
-    let cfg = match client.public.get_status().await {
+    let operation_ids = send_operation_ids(client, wallet, op, fee, addr).await?;
+    if !json {
+        println!("{}", tr!("info.sent_operation_ids"));
+    }
+    Ok(Box::new(operation_ids))
+}
+
+/// wraps and sends an operation with proper validity period, returning the ids of the sent operations
+async fn send_operation_ids(
+    client: &Client,
+    wallet: &Wallet,
+    op: OperationType,
+    fee: Amount,
+    addr: Address,
+) -> Result<Vec<OperationId>> {
+    let node_status = match client.public.get_status().await {
         Ok(node_status) => node_status,
         Err(e) => rpc_error!(e),
+    };
+    if let Some(expected_chain_id) = chain_id_mismatch(node_status.chain_id) {
+        bail!(tr!(
+            "error.chain_id_mismatch",
+            node_status.chain_id,
+            expected_chain_id
+        ));
     }
-    .config;
+    let cfg = node_status.config;
 
     let slot = get_current_latest_block_slot(cfg.thread_count, cfg.t0, cfg.genesis_timestamp, 0)? // clock compensation is zero
         .unwrap_or_else(|| Slot::new(0, 0));
@@ -1076,6 +1891,8 @@ async fn send_operation(
             op,
         },
         addr,
+        node_status.chain_id,
+        node_status.chain_id_activation_period,
     )?;
 
     match client
@@ -1087,37 +1904,167 @@ async fn send_operation(
         }])
         .await
     {
-        Ok(operation_ids) => {
-            if !json {
-                println!("Sent operation IDs:");
-            }
-            Ok(Box::new(operation_ids))
-        }
+        Ok(operation_ids) => Ok(operation_ids),
         Err(e) => rpc_error!(e),
     }
 }
 
+/// Returns the chain id expected by the client configuration if the node belongs to another chain
+pub(crate) fn chain_id_mismatch(node_chain_id: u64) -> Option<u64> {
+    SETTINGS
+        .chain_id
+        .filter(|expected_chain_id| *expected_chain_id != node_chain_id)
+}
+
 /// TODO: ugly utilities functions
 /// takes a slice of string and makes it into a `Vec<T>`
 pub fn parse_vec<T: std::str::FromStr>(args: &[String]) -> anyhow::Result<Vec<T>, T::Err> {
     args.iter().map(|x| x.parse::<T>()).collect()
 }
 
+/// Suffix of the names resolved through the name registry of the node
+const NAME_SUFFIX: &str = ".massa";
+
+/// Parses an address parameter, either an address or a `name.massa` name
+/// resolved by the node through its name registry contract
+async fn resolve_address(client: &Client, parameter: &str) -> Result<Address> {
+    if !parameter.ends_with(NAME_SUFFIX) {
+        return Ok(parameter.parse::<Address>()?);
+    }
+    match client
+        .public
+        .resolve_names(vec![parameter.to_string()])
+        .await
+    {
+        Ok(addresses) => match addresses.into_iter().next().flatten() {
+            Some(address) => Ok(address),
+            None => bail!(tr!("error.unknown_name", parameter)),
+        },
+        Err(e) => bail!(tr!("error.rpc_short", e)),
+    }
+}
+
+/// Parses a list of address parameters, see `resolve_address`
+async fn resolve_addresses(client: &Client, parameters: &[String]) -> Result<Vec<Address>> {
+    let mut addresses = Vec::with_capacity(parameters.len());
+    for parameter in parameters {
+        addresses.push(resolve_address(client, parameter).await?);
+    }
+    Ok(addresses)
+}
+
+/// Parses an amount parameter, written in MAS or nMAS
+fn parse_amount(parameter: &str) -> Result<Amount> {
+    Ok(Amount::from_str_with_unit(parameter)?)
+}
+
+/// Gets the activities of an address over a slot range, requesting them page by page.
+/// Each page ends with all the activities of its last slot, so the next page starts at the following slot.
+async fn get_full_address_history(
+    client: &Client,
+    address: Address,
+    mut start: Option<Slot>,
+    end: Option<Slot>,
+    thread_count: u8,
+) -> Result<Vec<AddressActivity>> {
+    let mut history = Vec::new();
+    loop {
+        let query = AddressHistoryQuery {
+            address,
+            start,
+            end,
+            max_count: Some(ADDRESS_HISTORY_PAGE_SIZE),
+        };
+        let page = match client.public.get_address_history(query).await {
+            Ok(page) => page,
+            Err(e) => rpc_error!(e),
+        };
+        let next_start = match page.last() {
+            Some(activity) => activity.slot.get_next_slot(thread_count)?,
+            None => break,
+        };
+        history.extend(page);
+        if end.map_or(false, |end| next_start >= end) {
+            break;
+        }
+        start = Some(next_start);
+    }
+    Ok(history)
+}
+
+/// Polls the status of an operation until it is final or the timeout expires.
+/// Status changes are printed as they are observed, unless the output is JSON.
+async fn watch_operation(
+    client: &Client,
+    operation_id: OperationId,
+    timeout: Duration,
+    json: bool,
+) -> Result<WatchedOperation> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut watched = WatchedOperation {
+        id: operation_id,
+        changes: Vec::new(),
+        is_final: false,
+    };
+    loop {
+        let operation_info = match client.public.get_operations(vec![operation_id]).await {
+            Ok(mut operations_info) => operations_info.pop(),
+            Err(e) => rpc_error!(e),
+        };
+
+        // the operation may not have reached the node yet
+        if let Some(operation_info) = operation_info {
+            let mut in_blocks = operation_info.in_blocks;
+            in_blocks.sort_unstable();
+            let change = OperationStatusChange {
+                time: MassaTime::now(0)?, // clock compensation is zero
+                status: operation_info.execution_status,
+                in_blocks,
+                execution: operation_info.execution,
+            };
+            if watched
+                .changes
+                .last()
+                .map_or(true, |last| !last.same_status(&change))
+            {
+                if !json {
+                    println!("{}", change);
+                }
+                watched.is_final = change.status == OperationExecutionStatus::Final;
+                watched.changes.push(change);
+                if watched.is_final {
+                    break;
+                }
+            }
+        }
+
+        if tokio::time::Instant::now() + WATCH_OPERATION_POLL_INTERVAL > deadline {
+            break;
+        }
+        tokio::time::sleep(WATCH_OPERATION_POLL_INTERVAL).await;
+    }
+    Ok(watched)
+}
+
 /// reads a file
 async fn get_file_as_byte_vec(filename: &std::path::Path) -> Result<Vec<u8>> {
     Ok(tokio::fs::read(filename).await?)
 }
 
+/// hashes a file, streaming it so that large files are not loaded in memory
+async fn get_file_hash(filename: PathBuf) -> Result<Hash> {
+    tokio::task::spawn_blocking(move || {
+        let mut file = std::io::BufReader::new(std::fs::File::open(filename)?);
+        Ok(Hash::compute_from_reader(&mut file)?)
+    })
+    .await?
+}
+
 // chains get_key_value with its parsing and displays a warning on parsing error
 pub fn parse_key_value<T: std::str::FromStr>(p: &HashMap<&str, &str>, key: &str) -> Option<T> {
     p.get_key_value(key).and_then(|x| {
         x.1.parse::<T>()
-            .map_err(|_| {
-                client_warning!(format!(
-                    "'{}' parameter was ignored because of wrong corresponding value",
-                    key
-                ))
-            })
+            .map_err(|_| client_warning!(tr!("warning.ignored_parameter", key)))
             .ok()
     })
 }
@@ -0,0 +1,205 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Export of the activity history of an address for accounting purposes.
+//!
+//! Each activity becomes a record stamped with the time of its slot: the credited amount,
+//! the operation and the block involved are split into their own fields.
+//! Records are written as CSV, with a header line, or as a JSON array.
+
+use anyhow::{bail, Result};
+use massa_models::amount::Amount;
+use massa_models::api::{AddressActivity, AddressActivityKind};
+use massa_models::block::BlockId;
+use massa_models::config::CompactConfig;
+use massa_models::operation::OperationId;
+use massa_models::slot::Slot;
+use massa_time::MassaTime;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::io::Write;
+use std::str::FromStr;
+
+/// Header line of the CSV exports
+const CSV_HEADER: &str =
+    "period,thread,timestamp,date,final,kind,credited_amount,operation_id,block_id,description";
+
+/// Format of an exported history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExportFormat {
+    /// comma separated values, one activity per line
+    Csv,
+    /// JSON array of activities
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            _ => bail!("unknown export format {}, expected csv or json", s),
+        }
+    }
+}
+
+/// Activity of an address, stamped with the time of its slot
+#[derive(Debug, Serialize)]
+pub(crate) struct ExportedActivity {
+    /// slot at which the activity was executed
+    pub slot: Slot,
+    /// timestamp of the slot, in milliseconds since 1970-01-01
+    pub timestamp: MassaTime,
+    /// UTC date of the slot
+    pub date: String,
+    /// true if the execution of the slot is final
+    pub is_final: bool,
+    /// kind of activity
+    pub kind: &'static str,
+    /// amount credited to the address by the activity, if any
+    pub credited_amount: Option<Amount>,
+    /// operation involved in the activity, if any
+    pub operation_id: Option<OperationId>,
+    /// block involved in the activity, if any
+    pub block_id: Option<BlockId>,
+    /// description of the activity
+    pub description: String,
+}
+
+impl ExportedActivity {
+    /// Builds the record of an activity, `config` being the configuration of the network it happened on
+    pub fn new(activity: &AddressActivity, config: &CompactConfig) -> Result<Self> {
+        let timestamp = config.get_slot_timestamp(activity.slot)?;
+        // sold rolls are only credited later, by the matching deferred credit
+        let (kind, credited_amount, operation_id, block_id) = match &activity.kind {
+            AddressActivityKind::BlockProduced { block_id } => {
+                ("block_produced", None, None, Some(*block_id))
+            }
+            AddressActivityKind::OperationCreated {
+                operation_id,
+                block_id,
+            } => (
+                "operation_created",
+                None,
+                Some(*operation_id),
+                Some(*block_id),
+            ),
+            AddressActivityKind::OperationCredit {
+                operation_id,
+                amount,
+            } => ("operation_credit", Some(*amount), Some(*operation_id), None),
+            AddressActivityKind::RollSell { operation_id, .. } => {
+                ("roll_sell", None, Some(*operation_id), None)
+            }
+            AddressActivityKind::DeferredCredit { amount } => {
+                ("deferred_credit", Some(*amount), None, None)
+            }
+            AddressActivityKind::BlockReward {
+                block_id, amount, ..
+            } => ("block_reward", Some(*amount), None, Some(*block_id)),
+        };
+        Ok(ExportedActivity {
+            slot: activity.slot,
+            timestamp,
+            date: timestamp.to_utc_string(),
+            is_final: activity.is_final,
+            kind,
+            credited_amount,
+            operation_id,
+            block_id,
+            description: activity.kind.to_string(),
+        })
+    }
+}
+
+/// Writes exported activities in the given format
+pub(crate) fn write_activities<W: Write>(
+    writer: &mut W,
+    activities: &[ExportedActivity],
+    format: ExportFormat,
+) -> Result<()> {
+    match format {
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut *writer, activities)?;
+            writeln!(writer)?;
+        }
+        ExportFormat::Csv => {
+            writeln!(writer, "{}", CSV_HEADER)?;
+            for activity in activities {
+                writeln!(
+                    writer,
+                    "{},{},{},{},{},{},{},{},{},{}",
+                    activity.slot.period,
+                    activity.slot.thread,
+                    activity.timestamp.to_millis(),
+                    activity.date,
+                    activity.is_final,
+                    activity.kind,
+                    display_or_empty(activity.credited_amount),
+                    display_or_empty(activity.operation_id),
+                    display_or_empty(activity.block_id),
+                    csv_field(&activity.description)
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Displays an optional value, as an empty string if it is missing
+fn display_or_empty<T: std::fmt::Display>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Quotes a CSV field if it contains a separator, a quote or a line break
+fn csv_field(field: &str) -> Cow<str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::address::Address;
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("credited 1 MAS"), "credited 1 MAS");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let config = CompactConfig::default();
+        let activity = AddressActivity {
+            address: Address::from_public_key(&KeyPair::generate().get_public_key()),
+            slot: Slot::new(2, 1),
+            is_final: true,
+            kind: AddressActivityKind::DeferredCredit {
+                amount: Amount::from_str("1.5").unwrap(),
+            },
+        };
+        let record = ExportedActivity::new(&activity, &config).unwrap();
+        assert_eq!(
+            record.timestamp,
+            config.get_slot_timestamp(Slot::new(2, 1)).unwrap()
+        );
+
+        let mut output = Vec::new();
+        write_activities(&mut output, &[record], ExportFormat::Csv).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some(CSV_HEADER));
+        let fields: Vec<&str> = lines.next().unwrap().split(',').collect();
+        assert_eq!(fields.len(), 10);
+        assert_eq!(&fields[..2], &["2", "1"]);
+        assert_eq!(&fields[4..9], &["true", "deferred_credit", "1.5", "", ""]);
+        assert_eq!(lines.next(), None);
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Localization of the client messages.
+//!
+//! Client-facing strings are identified by keys and looked up in the catalog of the selected language.
+//! A key missing from that catalog falls back to the English catalog, and then to the key itself.
+//! Catalogs are JSON objects mapping keys to messages, where each `{}` is replaced by an argument in order.
+//! Adding a language only requires a new catalog file and its entry in `CATALOGS`.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::RwLock;
+
+/// Language of the messages used when the selected one is missing a key
+pub(crate) const DEFAULT_LANGUAGE: &str = "en";
+
+/// Built-in catalogs, by language code
+const CATALOGS: [(&str, &str); 2] = [
+    ("en", include_str!("en.json")),
+    ("fr", include_str!("fr.json")),
+];
+
+type Catalog = HashMap<String, String>;
+
+lazy_static::lazy_static! {
+    static ref FALLBACK: Catalog = load_catalog(DEFAULT_LANGUAGE).expect("missing default catalog");
+    static ref SELECTED: RwLock<Option<Catalog>> = RwLock::new(None);
+}
+
+/// Parses the built-in catalog of a language, if there is one
+fn load_catalog(language: &str) -> Option<Catalog> {
+    CATALOGS
+        .iter()
+        .find(|(code, _)| code.eq_ignore_ascii_case(language))
+        .map(|(code, content)| {
+            serde_json::from_str(content)
+                .unwrap_or_else(|err| panic!("invalid catalog for language {}: {}", code, err))
+        })
+}
+
+/// Selects the language of the messages.
+/// Returns false if there is no catalog for that language, in which case English is used.
+pub(crate) fn set_language(language: &str) -> bool {
+    let catalog = load_catalog(language);
+    let found = catalog.is_some();
+    *SELECTED.write().expect("i18n lock poisoned") = catalog;
+    found
+}
+
+/// Codes of the languages that have a catalog
+pub(crate) fn available_languages() -> Vec<&'static str> {
+    CATALOGS.iter().map(|(code, _)| *code).collect()
+}
+
+/// Gets the message of a key in the selected language, or in English if it is missing
+pub(crate) fn get_opt(key: &str) -> Option<String> {
+    SELECTED
+        .read()
+        .expect("i18n lock poisoned")
+        .as_ref()
+        .and_then(|catalog| catalog.get(key))
+        .or_else(|| FALLBACK.get(key))
+        .cloned()
+}
+
+/// Gets the message of a key, falling back to the key itself if no catalog has it
+pub(crate) fn get(key: &str) -> String {
+    get_opt(key).unwrap_or_else(|| key.to_string())
+}
+
+/// Gets the message of a key and replaces each `{}` in it by the next argument
+pub(crate) fn format(key: &str, args: &[&dyn Display]) -> String {
+    let message = get(key);
+    let mut parts = message.split("{}");
+    let mut res = parts.next().unwrap_or_default().to_string();
+    let mut args = args.iter();
+    for part in parts {
+        match args.next() {
+            Some(arg) => res.push_str(&arg.to_string()),
+            None => res.push_str("{}"),
+        }
+        res.push_str(part);
+    }
+    res
+}
+
+/// Localized message of a key, with optional arguments replacing the `{}` of the message in order
+macro_rules! tr {
+    ($key:expr) => {
+        $crate::i18n::get($key)
+    };
+    ($key:expr, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::format($key, &[$(&$arg),+])
+    };
+}
+pub(crate) use tr;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmds::Command;
+    use strum::{EnumMessage, IntoEnumIterator};
+
+    #[test]
+    fn test_catalogs_have_the_same_keys() {
+        for (code, _) in CATALOGS {
+            let catalog = load_catalog(code).unwrap();
+            for key in catalog.keys() {
+                assert!(
+                    FALLBACK.contains_key(key),
+                    "{} key {} is unknown",
+                    code,
+                    key
+                );
+            }
+            for key in FALLBACK.keys() {
+                assert!(catalog.contains_key(key), "{} key {} is missing", code, key);
+            }
+        }
+    }
+
+    #[test]
+    fn test_help_texts_match_commands() {
+        for cmd in Command::iter() {
+            assert_eq!(
+                FALLBACK.get(&format!("help.{}", cmd)).map(String::as_str),
+                cmd.get_message(),
+                "English help text of {} does not match its description",
+                cmd
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_placeholders() {
+        assert_eq!(format("unknown key {}", &[&1]), "unknown key 1");
+        assert_eq!(
+            format("warning.address_not_found", &[&"A12"]),
+            "address A12 not found"
+        );
+    }
+}
@@ -3,21 +3,24 @@
 #![feature(str_split_whitespace_as_str)]
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
+use crate::i18n::tr;
 use crate::settings::SETTINGS;
 use anyhow::Result;
 use atty::Stream;
 use cmds::Command;
 use console::style;
-use dialoguer::Password;
-use massa_sdk::Client;
-use massa_wallet::Wallet;
+use profile::NodeEndpoints;
 use serde::Serialize;
 use std::net::IpAddr;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use structopt::StructOpt;
 
+mod chunked_deploy;
 mod cmds;
+mod history_export;
+mod i18n;
+mod profile;
 mod repl;
 mod settings;
 mod utils;
@@ -50,12 +53,21 @@ struct Args {
         default_value = "wallet.dat"
     )]
     wallet: PathBuf,
+    /// Name of the wallet profile to use, selecting its wallet file (overrides --wallet) and node
+    #[structopt(long = "profile")]
+    profile: Option<String>,
     /// Enable a mode where input/output are serialized as JSON
     #[structopt(short = "j", long = "json")]
     json: bool,
     #[structopt(short = "p", long = "pwd")]
     /// Wallet password
     password: Option<String>,
+    /// Language of the client messages (overrides the configured one)
+    #[structopt(long = "lang")]
+    lang: Option<String>,
+    /// Path of a file of commands to execute one per line (non-interactive mode)
+    #[structopt(long = "script", parse(from_os_str))]
+    script: Option<PathBuf>,
 }
 
 #[derive(Serialize)]
@@ -63,23 +75,6 @@ struct JsonError {
     error: String,
 }
 
-/// Ask for the wallet password
-/// If the wallet does not exist, it will require password confirmation
-fn ask_password(wallet_path: &Path) -> String {
-    if wallet_path.is_file() {
-        Password::new()
-            .with_prompt("Enter wallet password")
-            .interact()
-            .expect("IO error: Password reading failed, walled couldn't be unlocked")
-    } else {
-        Password::new()
-            .with_prompt("Enter new password for wallet")
-            .with_confirmation("Confirm password", "Passwords mismatching")
-            .interact()
-            .expect("IO error: Password reading failed, wallet couldn't be created")
-    }
-}
-
 #[paw::main]
 fn main(args: Args) -> anyhow::Result<()> {
     let tokio_rt = tokio::runtime::Builder::new_multi_thread()
@@ -98,18 +93,20 @@ fn main(args: Args) -> anyhow::Result<()> {
 async fn run(args: Args) -> Result<()> {
     // TODO: move settings loading in another crate ... see #1277
     let settings = SETTINGS.clone();
-    let address = match args.ip {
-        Some(ip) => ip,
-        None => settings.default_node.ip,
-    };
-    let public_port = match args.public_port {
-        Some(public_port) => public_port,
-        None => settings.default_node.public_port,
-    };
-    let private_port = match args.private_port {
-        Some(private_port) => private_port,
-        None => settings.default_node.private_port,
-    };
+
+    // select the language of the messages
+    let language = args.lang.as_deref().unwrap_or(&settings.language);
+    if !i18n::set_language(language) {
+        println!(
+            "{}: {}",
+            style(tr!("warning.label")).yellow(),
+            tr!(
+                "warning.unknown_language",
+                language,
+                i18n::available_languages().join(", ")
+            )
+        );
+    }
 
     // Setup panic handlers,
     // and when a panic occurs,
@@ -121,18 +118,30 @@ async fn run(args: Args) -> Result<()> {
         std::process::exit(1);
     }));
 
-    // ...
-    let password = args.password.unwrap_or_else(|| ask_password(&args.wallet));
-    let mut wallet = Wallet::new(args.wallet, password)?;
-    let client = Client::new(address, public_port, private_port).await;
-    if atty::is(Stream::Stdout) && args.command == Command::help && !args.json {
+    // select the wallet file and the node of the profile, if any
+    let (wallet_path, node) = match &args.profile {
+        Some(profile) => profile::get_profile(profile)?,
+        None => (args.wallet, &SETTINGS.default_node),
+    };
+    let node = NodeEndpoints::new(node, args.ip, args.public_port, args.private_port);
+    let mut wallet = profile::load_wallet(wallet_path, args.password)?;
+    let mut client = node.connect().await?;
+    if let Some(script_path) = &args.script {
+        // Script mode
+        let script = std::fs::read_to_string(script_path).map_err(|err| {
+            anyhow::anyhow!("could not read script {}: {}", script_path.display(), err)
+        })?;
+        if !repl::run_script(&mut client, &mut wallet, &script, args.json).await {
+            std::process::exit(1);
+        }
+    } else if atty::is(Stream::Stdout) && args.command == Command::help && !args.json {
         // Interactive mode
-        repl::run(&client, &mut wallet).await;
+        repl::run(&mut client, &mut wallet, node.label).await;
     } else {
         // Non-Interactive mode
         match args
             .command
-            .run(&client, &mut wallet, &args.parameters, args.json)
+            .run(&mut client, &mut wallet, &args.parameters, args.json)
             .await
         {
             Ok(output) => {
@@ -152,7 +161,7 @@ async fn run(args: Args) -> Result<()> {
                     .expect("fail to serialize to JSON error");
                     println!("{}", error);
                 } else {
-                    println!("{}", style(format!("Error: {}", e)).red());
+                    println!("{}", style(format!("{}: {}", tr!("error.label"), e)).red());
                 }
             }
         }
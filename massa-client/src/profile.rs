@@ -0,0 +1,174 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Wallet profiles.
+//!
+//! A profile is a named wallet file, configured in the `profiles` section of the client settings
+//! along an optional node that replaces the default one while the profile is in use.
+//! A profile is selected at startup with `--profile`, or later with the `wallet_switch` command.
+//! The public calls fail over to the fallback nodes of the node in use when it does not answer.
+
+use crate::i18n::tr;
+use crate::settings::{DefaultNode, FallbackNode, SETTINGS};
+use anyhow::{anyhow, Result};
+use dialoguer::Password;
+use massa_sdk::{Client, ClientConfig, RetryPolicy};
+use massa_wallet::Wallet;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+/// Endpoints of the node the client talks to
+#[derive(Debug, Clone)]
+pub(crate) struct NodeEndpoints {
+    pub ip: IpAddr,
+    pub public_port: u16,
+    pub private_port: u16,
+    /// name of the node displayed in the prompt
+    pub label: String,
+    /// nodes whose public API is called when this one does not answer
+    pub fallbacks: Vec<FallbackNode>,
+}
+
+impl NodeEndpoints {
+    /// Endpoints of a configured node, overridden by the ones given on the command line.
+    /// The alias and the fallbacks of the node are only used if its endpoints are not overridden.
+    pub(crate) fn new(
+        node: &DefaultNode,
+        ip: Option<IpAddr>,
+        public_port: Option<u16>,
+        private_port: Option<u16>,
+    ) -> Self {
+        let endpoints = NodeEndpoints {
+            ip: ip.unwrap_or(node.ip),
+            public_port: public_port.unwrap_or(node.public_port),
+            private_port: private_port.unwrap_or(node.private_port),
+            label: String::new(),
+            fallbacks: Vec::new(),
+        };
+        if ip.is_some() || public_port.is_some() {
+            return NodeEndpoints {
+                label: format!("{}:{}", endpoints.ip, endpoints.public_port),
+                ..endpoints
+            };
+        }
+        NodeEndpoints {
+            label: node
+                .alias
+                .clone()
+                .unwrap_or_else(|| format!("{}:{}", endpoints.ip, endpoints.public_port)),
+            fallbacks: node.fallbacks.clone(),
+            ..endpoints
+        }
+    }
+
+    /// Creates a client connected to the node, retrying and failing over as configured
+    pub(crate) async fn connect(&self) -> Result<Client> {
+        let public_urls: Vec<String> = std::iter::once((self.ip, self.public_port))
+            .chain(
+                self.fallbacks
+                    .iter()
+                    .map(|node| (node.ip, node.public_port)),
+            )
+            .map(|(ip, port)| format!("http://{}", SocketAddr::new(ip, port)))
+            .collect();
+        let private_url = format!("http://{}", SocketAddr::new(self.ip, self.private_port));
+        let config = ClientConfig {
+            retry: RetryPolicy {
+                max_retries: SETTINGS.retry_count,
+                initial_backoff: SETTINGS.retry_backoff.to_duration(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        Client::from_endpoints(&public_urls, &private_url, config)
+            .await
+            .map_err(|err| anyhow!(tr!("error.connection_failed", self.label, err)))
+    }
+}
+
+/// Wallet file and node of a configured profile
+pub(crate) fn get_profile(name: &str) -> Result<(PathBuf, &'static DefaultNode)> {
+    let profile = SETTINGS.profiles.get(name).ok_or_else(|| {
+        anyhow!(tr!(
+            "error.unknown_profile",
+            name,
+            SETTINGS
+                .profiles
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    })?;
+    Ok((
+        profile.wallet.clone(),
+        profile.node.as_ref().unwrap_or(&SETTINGS.default_node),
+    ))
+}
+
+/// Name of the profile using a wallet file, or the name of the file if no profile uses it
+pub(crate) fn profile_name(wallet_path: &Path) -> String {
+    SETTINGS
+        .profiles
+        .iter()
+        .find(|(_, profile)| profile.wallet == wallet_path)
+        .map(|(name, _)| name.clone())
+        .unwrap_or_else(|| {
+            wallet_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })
+}
+
+/// Ask for the wallet password
+/// If the wallet does not exist, it will require password confirmation
+fn ask_password(wallet_path: &Path) -> String {
+    if wallet_path.is_file() {
+        Password::new()
+            .with_prompt(tr!("prompt.wallet_password"))
+            .interact()
+            .expect("IO error: Password reading failed, walled couldn't be unlocked")
+    } else {
+        Password::new()
+            .with_prompt(tr!("prompt.new_wallet_password"))
+            .with_confirmation(
+                tr!("prompt.confirm_password"),
+                tr!("prompt.password_mismatch"),
+            )
+            .interact()
+            .expect("IO error: Password reading failed, wallet couldn't be created")
+    }
+}
+
+/// Opens a wallet file, created if it does not exist,
+/// asking for its password if it is not given
+pub(crate) fn load_wallet(wallet_path: PathBuf, password: Option<String>) -> Result<Wallet> {
+    let password = password.unwrap_or_else(|| ask_password(&wallet_path));
+    Ok(Wallet::new(wallet_path, password)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_endpoints_label() {
+        let node = DefaultNode {
+            alias: Some("my node".to_string()),
+            ip: "127.0.0.1".parse().unwrap(),
+            private_port: 33034,
+            public_port: 33035,
+            fallbacks: vec![FallbackNode {
+                ip: "127.0.0.2".parse().unwrap(),
+                public_port: 33035,
+            }],
+        };
+        let endpoints = NodeEndpoints::new(&node, None, None, Some(1234));
+        assert_eq!(endpoints.label, "my node");
+        assert_eq!(endpoints.private_port, 1234);
+        assert_eq!(endpoints.fallbacks.len(), 1);
+        let endpoints = NodeEndpoints::new(&node, None, Some(1235), None);
+        assert_eq!(endpoints.label, "127.0.0.1:1235");
+        assert!(endpoints.fallbacks.is_empty());
+    }
+}
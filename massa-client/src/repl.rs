@@ -1,19 +1,28 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::cmds::{Command, ExtendedWallet};
+use crate::cmds::{chain_id_mismatch, Command, ExtendedWallet, WatchedOperation};
+use crate::i18n::tr;
+use crate::profile::{self, NodeEndpoints};
 use crate::settings::SETTINGS;
 use crate::utils::longest_common_prefix;
 use console::style;
 use dialoguer::{theme::ColorfulTheme, Completion, History, Input};
 use erased_serde::{Serialize, Serializer};
 use glob::glob;
+use massa_ledger_exports::LedgerEntryProof;
 use massa_models::api::{
-    AddressInfo, BlockInfo, DatastoreEntryOutput, EndorsementInfo, NodeStatus, OperationInfo,
+    AddressActivity, AddressDraws, AddressInfo, BlockInfo, ContractsOutput, DatastoreEntryOutput,
+    DatastoreKeysOutput, DeferredCreditSchedule, EndorsementDiagnostic, EndorsementInfo,
+    NodeStatus, OperationInfo, OperationPoolStatusInfo, PeerScoreInfo, ReorgInfo,
+    StakingKeyRotation, StakingReport,
 };
 use massa_models::composite::PubkeySig;
 use massa_models::execution::ExecuteReadOnlyResponse;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashSet;
+use massa_models::stats::{
+    ExecutionPolicyStats, FeeRecommendation, LedgerIntegrityReport, NetworkActivityStats,
+};
 use massa_models::{address::Address, operation::OperationId};
 use massa_sdk::Client;
 use massa_wallet::Wallet;
@@ -94,37 +103,196 @@ fn group_parameters(parameters: Vec<String>) -> Vec<String> {
     new_parameters
 }
 
-pub(crate) async fn run(client: &Client, wallet: &mut Wallet) {
+/// Splits an input line into a command and its parameters.
+/// Returns `None` for blank lines and `#` comments.
+fn parse_input(input: &str) -> Option<(Result<Command, ParseError>, Vec<String>)> {
+    let input = input.trim();
+    if input.is_empty() || input.starts_with('#') {
+        return None;
+    }
+    let mut input = group_parameters(input.split_whitespace().map(|x| x.to_string()).collect());
+    if input.is_empty() {
+        return None;
+    }
+    let parameters = input.split_off(1);
+    Some((input[0].parse(), parameters))
+}
+
+/// Builds the prompt showing the network of the node, the node and the wallet profile in use.
+/// A chain id differing from the one expected by the client configuration is flagged.
+fn build_prompt(node_status: Option<&NodeStatus>, node_label: &str, profile: &str) -> String {
+    let network = match node_status {
+        Some(status) => match chain_id_mismatch(status.chain_id) {
+            Some(expected_chain_id) => style(format!(
+                "{} #{} ≠ #{}",
+                status.version, status.chain_id, expected_chain_id
+            ))
+            .red()
+            .to_string(),
+            None => format!("{} #{}", status.version, status.chain_id),
+        },
+        None => style(tr!("repl.node_unreachable")).yellow().to_string(),
+    };
+    format!(
+        "{} [{} | {} | {}]",
+        tr!("repl.prompt"),
+        network,
+        node_label,
+        profile
+    )
+}
+
+pub(crate) async fn run(client: &mut Client, wallet: &mut Wallet, mut node_label: String) {
     massa_fancy_ascii_art_logo!();
-    println!("Use 'exit' to quit the prompt");
-    println!("Use the Up/Down arrows to scroll through history");
-    println!("Use the Right arrow or Tab to complete your command");
-    println!("Use the Enter key to execute your command");
+    println!("{}", tr!("repl.usage_exit"));
+    println!("{}", tr!("repl.usage_history"));
+    println!("{}", tr!("repl.usage_completion"));
+    println!("{}", tr!("repl.usage_execute"));
     crate::cmds::help();
     let mut history = CommandHistory::default();
     let completion = CommandCompletion::default();
+    let mut warned_chain_id = None;
     loop {
+        // refresh the network context of the prompt, warning once per mismatching chain
+        let node_status = client.public.get_status().await.ok();
+        if let Some(status) = &node_status {
+            if let Some(expected_chain_id) = chain_id_mismatch(status.chain_id) {
+                if warned_chain_id != Some(status.chain_id) {
+                    println!(
+                        "{}: {}",
+                        style(tr!("warning.label")).yellow(),
+                        tr!(
+                            "warning.chain_id_mismatch",
+                            status.chain_id,
+                            status.version,
+                            expected_chain_id
+                        )
+                    );
+                    warned_chain_id = Some(status.chain_id);
+                }
+            }
+        }
         if let Ok(input) = Input::<String>::with_theme(&ColorfulTheme::default())
-            .with_prompt("command")
+            .with_prompt(build_prompt(
+                node_status.as_ref(),
+                &node_label,
+                &profile::profile_name(&wallet.wallet_path),
+            ))
             .history_with(&mut history)
             .completion_with(&completion)
             .interact_text()
         {
             // User input parsing
-            let input: Vec<String> =
-                group_parameters(input.split_whitespace().map(|x| x.to_string()).collect());
-            let cmd: Result<Command, ParseError> = input[0].parse();
-            let parameters = input[1..].to_vec();
+            let (cmd, parameters) = match parse_input(&input) {
+                Some(parsed) => parsed,
+                None => continue,
+            };
             // Print result of evaluated command
             match cmd {
                 Ok(command) => match command.run(client, wallet, &parameters, false).await {
-                    Ok(output) => output.pretty_print(),
-                    Err(e) => println!("{}", style(format!("Error: {}", e)).red()),
+                    Ok(output) => {
+                        // the node of the prompt follows the profile
+                        if command == Command::wallet_switch {
+                            if let Ok((_, node)) = profile::get_profile(&parameters[0]) {
+                                node_label = NodeEndpoints::new(node, None, None, None).label;
+                            }
+                        }
+                        output.pretty_print()
+                    }
+                    Err(e) => println!("{}", style(format!("{}: {}", tr!("error.label"), e)).red()),
                 },
-                Err(_) => println!("Command not found!\ntype \"help\" to get the list of commands"),
+                Err(_) => println!("{}", tr!("help.command_not_found")),
+            }
+        }
+    }
+}
+
+/// Outcome of a command of a script, as aggregated in the JSON output of `--script`
+#[derive(serde::Serialize)]
+struct ScriptCommandOutput {
+    /// line of the command in the script, starting at 1
+    line: usize,
+    /// command as written in the script
+    command: String,
+    /// output of the command, if it succeeded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<serde_json::Value>,
+    /// error of the command, if it failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Runs the commands of a script, one per line, stopping at the first failing command.
+/// Blank lines and lines starting with `#` are skipped.
+/// In JSON mode, the outcomes of the commands are printed at the end as a single JSON array.
+///
+/// # Returns
+/// false if a command failed
+pub(crate) async fn run_script(
+    client: &mut Client,
+    wallet: &mut Wallet,
+    script: &str,
+    json: bool,
+) -> bool {
+    let mut outputs = Vec::new();
+    let mut success = true;
+    for (index, line) in script.lines().enumerate() {
+        let (cmd, parameters) = match parse_input(line) {
+            Some(parsed) => parsed,
+            None => continue,
+        };
+        if !json {
+            println!("{}", style(format!("> {}", line.trim())).dim());
+        }
+        let result = match cmd {
+            Ok(command) => command.run(client, wallet, &parameters, json).await,
+            Err(_) => Err(anyhow::anyhow!(tr!(
+                "error.unknown_command",
+                line.split_whitespace().next().unwrap_or_default()
+            ))),
+        };
+        if json {
+            let mut outcome = ScriptCommandOutput {
+                line: index + 1,
+                command: line.trim().to_string(),
+                output: None,
+                error: None,
+            };
+            match result.and_then(|output| output.to_json_value()) {
+                Ok(value) => outcome.output = Some(value),
+                Err(e) => outcome.error = Some(format!("{:?}", e)),
+            }
+            success = outcome.error.is_none();
+            outputs.push(outcome);
+        } else {
+            match result {
+                Ok(output) => output.pretty_print(),
+                Err(e) => {
+                    println!(
+                        "{}",
+                        style(format!(
+                            "{} ({}): {}",
+                            tr!("error.label"),
+                            tr!("repl.script_line", index + 1),
+                            e
+                        ))
+                        .red()
+                    );
+                    success = false;
+                }
             }
         }
+        if !success {
+            break;
+        }
+    }
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string(&outputs).expect("fail to serialize to JSON script output")
+        );
     }
+    success
 }
 
 struct CommandHistory {
@@ -133,11 +301,24 @@ struct CommandHistory {
 }
 
 impl CommandHistory {
+    /// Loads the commands of the previous sessions, most recent first.
+    /// The history file is truncated to the configured maximal history length.
     fn get_saved_history() -> Result<VecDeque<String>, Error> {
         if let Ok(file) = File::open(&SETTINGS.history_file_path) {
             let lines = RevLines::new(BufReader::new(file))?;
-            Ok(lines.collect())
+            let mut history: VecDeque<String> = lines.take(SETTINGS.history + 1).collect();
+            if history.len() > SETTINGS.history {
+                history.truncate(SETTINGS.history);
+                let mut file = File::create(&SETTINGS.history_file_path)?;
+                for command in history.iter().rev() {
+                    writeln!(file, "{}", command)?;
+                }
+            }
+            Ok(history)
         } else {
+            if let Some(parent) = SETTINGS.history_file_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
             File::create(&SETTINGS.history_file_path)?;
             Ok(VecDeque::new())
         }
@@ -185,6 +366,19 @@ impl<T: ToString> History<T> for CommandHistory {
     }
 }
 
+/// Prints the arguments expected by a command, as a completion hint
+fn print_args_hint(command: &Command) {
+    println!();
+    println!(
+        "{} {}",
+        style(command.to_string()).green(),
+        match command.args_hint() {
+            Some(args) => style(args.to_string()).yellow(),
+            None => style(tr!("help.no_args")).color256(8).italic(), // grey
+        }
+    );
+}
+
 struct CommandCompletion {
     options: Vec<String>,
 }
@@ -213,6 +407,9 @@ impl Completion for CommandCompletion {
         let input = input.to_string();
         if input.contains(' ') {
             let mut args: Vec<&str> = input.split(' ').collect();
+            if let Ok(command) = args[0].parse::<Command>() {
+                print_args_hint(&command);
+            }
             let mut default_path = "./";
             let path_to_complete = args.last_mut().unwrap_or(&mut default_path);
             let expanded_path = expand_path(path_to_complete);
@@ -240,7 +437,12 @@ impl Completion for CommandCompletion {
                 .filter(|s| s.len() >= input.len() && input == s[..input.len()])
                 .map(|s| &s[..])
                 .collect();
-            if !suggestions.is_empty() {
+            if let [suggestion] = suggestions.as_slice() {
+                if let Ok(command) = suggestion.parse::<Command>() {
+                    print_args_hint(&command);
+                }
+                Some(String::from(*suggestion))
+            } else if !suggestions.is_empty() {
                 println!();
                 for suggestion in &suggestions {
                     println!("{}", style(suggestion).dim());
@@ -264,6 +466,16 @@ impl dyn Output {
         self.erased_serialize(&mut format)?;
         Ok(())
     }
+
+    pub(crate) fn to_json_value(&self) -> anyhow::Result<serde_json::Value> {
+        let mut bytes = Vec::new();
+        {
+            let json = &mut serde_json::Serializer::new(&mut bytes);
+            let mut format: Box<dyn Serializer> = Box::new(<dyn Serializer>::erase(json));
+            self.erased_serialize(&mut format)?;
+        }
+        Ok(serde_json::from_slice(&bytes)?)
+    }
 }
 
 impl Output for Wallet {
@@ -300,6 +512,24 @@ impl Output for NodeStatus {
     }
 }
 
+impl Output for NetworkActivityStats {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for FeeRecommendation {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for LedgerIntegrityReport {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
 impl Output for BlockInfo {
     fn pretty_print(&self) {
         println!("{}", self);
@@ -332,6 +562,27 @@ impl Output for Vec<DatastoreEntryOutput> {
     }
 }
 
+impl Output for DatastoreKeysOutput {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for ContractsOutput {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl Output for Option<LedgerEntryProof> {
+    fn pretty_print(&self) {
+        match self {
+            Some(proof) => println!("{}", proof),
+            None => println!("{}", tr!("info.no_ledger_entry")),
+        }
+    }
+}
+
 impl Output for Vec<EndorsementInfo> {
     fn pretty_print(&self) {
         for endorsement_info in self {
@@ -340,6 +591,88 @@ impl Output for Vec<EndorsementInfo> {
     }
 }
 
+impl Output for Vec<AddressDraws> {
+    fn pretty_print(&self) {
+        for draws in self {
+            println!("{}", draws);
+        }
+    }
+}
+
+impl Output for Vec<AddressActivity> {
+    fn pretty_print(&self) {
+        for activity in self {
+            print!("{}", activity);
+        }
+    }
+}
+
+impl Output for Vec<DeferredCreditSchedule> {
+    fn pretty_print(&self) {
+        for schedule in self {
+            println!("{}", schedule);
+        }
+    }
+}
+
+impl Output for Vec<EndorsementDiagnostic> {
+    fn pretty_print(&self) {
+        for diagnostic in self {
+            print!("{}", diagnostic);
+        }
+    }
+}
+
+impl Output for Vec<ReorgInfo> {
+    fn pretty_print(&self) {
+        if self.is_empty() {
+            println!("{}", tr!("info.no_reorg"));
+        }
+        for reorg in self {
+            println!("{}", reorg);
+        }
+    }
+}
+
+impl Output for Vec<StakingReport> {
+    fn pretty_print(&self) {
+        for report in self {
+            println!("{}", report);
+        }
+    }
+}
+
+impl Output for StakingKeyRotation {
+    fn pretty_print(&self) {
+        print!("{}", self);
+    }
+}
+
+impl Output for Vec<StakingKeyRotation> {
+    fn pretty_print(&self) {
+        if self.is_empty() {
+            println!("{}", tr!("info.no_staking_key_rotation"));
+        }
+        for rotation in self {
+            print!("{}", rotation);
+        }
+    }
+}
+
+impl Output for ExecutionPolicyStats {
+    fn pretty_print(&self) {
+        print!("{}", self);
+    }
+}
+
+impl Output for Vec<PeerScoreInfo> {
+    fn pretty_print(&self) {
+        for peer in self {
+            print!("{}", peer);
+        }
+    }
+}
+
 impl Output for Vec<OperationInfo> {
     fn pretty_print(&self) {
         for operation_info in self {
@@ -348,6 +681,20 @@ impl Output for Vec<OperationInfo> {
     }
 }
 
+impl Output for Vec<OperationPoolStatusInfo> {
+    fn pretty_print(&self) {
+        for status in self {
+            print!("{}", status);
+        }
+    }
+}
+
+impl Output for WatchedOperation {
+    fn pretty_print(&self) {
+        println!("{}", self);
+    }
+}
+
 impl Output for Vec<OperationId> {
     fn pretty_print(&self) {
         for operation_id in self {
@@ -383,3 +730,21 @@ impl Output for ExecuteReadOnlyResponse {
         println!("{}", self);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input() {
+        assert!(parse_input("   ").is_none());
+        assert!(parse_input("# comment").is_none());
+        let (cmd, parameters) = parse_input("  wallet_info  ").unwrap();
+        assert_eq!(cmd.unwrap(), Command::wallet_info);
+        assert!(parameters.is_empty());
+        let (cmd, parameters) = parse_input("help \"wallet info\" x").unwrap();
+        assert_eq!(cmd.unwrap(), Command::help);
+        assert_eq!(parameters, vec!["wallet info".to_string(), "x".to_string()]);
+        assert!(parse_input("not_a_command").unwrap().0.is_err());
+    }
+}
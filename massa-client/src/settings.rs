@@ -4,7 +4,7 @@
 use massa_models::config::build_massa_settings;
 use massa_time::MassaTime;
 use serde::Deserialize;
-use std::{net::IpAddr, path::PathBuf};
+use std::{collections::BTreeMap, net::IpAddr, path::PathBuf};
 
 lazy_static::lazy_static! {
     pub static ref SETTINGS: Settings = build_massa_settings("massa-client", "MASSA_CLIENT");
@@ -16,13 +16,44 @@ pub struct Settings {
     pub history: usize,
     pub history_file_path: PathBuf,
     pub timeout: MassaTime,
+    /// retries of a call to a node that does not answer, before failing over to the next one
+    pub retry_count: u32,
+    /// delay before the first retry of a call, doubled before each of the next ones
+    pub retry_backoff: MassaTime,
+    pub language: String,
+    /// chain id of the network the client and its wallet are meant for, if any
+    pub chain_id: Option<u64>,
+    /// wallet profiles, by name
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// Wallet profile, selected with `--profile` or `wallet_switch`
+#[derive(Debug, Deserialize, Clone)]
+pub struct Profile {
+    /// wallet file of the profile
+    pub wallet: PathBuf,
+    /// node used with the profile instead of the default one
+    pub node: Option<DefaultNode>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct DefaultNode {
+    /// name of the node displayed in the prompt
+    pub alias: Option<String>,
     pub ip: IpAddr,
     pub private_port: u16,
     pub public_port: u16,
+    /// nodes whose public API is called in turn when this one does not answer
+    #[serde(default)]
+    pub fallbacks: Vec<FallbackNode>,
+}
+
+/// Node whose public API replaces the one of an unreachable node
+#[derive(Debug, Deserialize, Clone)]
+pub struct FallbackNode {
+    pub ip: IpAddr,
+    pub public_port: u16,
 }
 
 #[cfg(test)]
@@ -2,7 +2,7 @@
 
 //! Contains definitions of commands used by the controller
 use massa_graph::{BlockGraphExport, BootstrapableGraph};
-use massa_models::api::BlockGraphStatus;
+use massa_models::api::{BlockGraphStatus, ReorgInfo};
 use massa_models::{block::BlockId, slot::Slot};
 use massa_models::{clique::Clique, stats::ConsensusStats};
 use massa_storage::Storage;
@@ -20,6 +20,15 @@ pub enum ConsensusCommand {
         /// response channel
         response_tx: oneshot::Sender<BlockGraphExport>,
     },
+    /// Returns through a channel a rendering of the block graph in the DOT language of graphviz
+    GetGraphDot {
+        /// optional start slot
+        slot_start: Option<Slot>,
+        /// optional end slot
+        slot_end: Option<Slot>,
+        /// response channel
+        response_tx: oneshot::Sender<String>,
+    },
     /// Returns through a channel the graph statuses of a batch of blocks
     GetBlockStatuses {
         /// wanted block IDs
@@ -27,6 +36,13 @@ pub enum ConsensusCommand {
         /// response channel
         response_tx: oneshot::Sender<Vec<BlockGraphStatus>>,
     },
+    /// Returns through a channel the confirmation weights of a batch of blocks (None if not active)
+    GetConfirmationWeights {
+        /// wanted block IDs
+        ids: Vec<BlockId>,
+        /// response channel
+        response_tx: oneshot::Sender<Vec<Option<u64>>>,
+    },
     /// Returns the bootstrap state
     GetBootstrapState(mpsc::Sender<Box<BootstrapableGraph>>),
     /// get current stats on consensus
@@ -63,6 +79,8 @@ pub enum ConsensusCommand {
     },
     /// Get cliques
     GetCliques(oneshot::Sender<Vec<Clique>>),
+    /// Get the latest blockclique re-organizations
+    GetReorgHistory(oneshot::Sender<Vec<ReorgInfo>>),
 }
 
 /// Events that are emitted by consensus.
@@ -1,7 +1,7 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_graph::{BlockGraphExport, BootstrapableGraph};
-use massa_models::api::BlockGraphStatus;
+use massa_models::api::{BlockGraphStatus, ReorgInfo};
 use massa_models::{block::BlockId, slot::Slot};
 use massa_models::{clique::Clique, stats::ConsensusStats};
 use massa_protocol_exports::ProtocolEventReceiver;
@@ -56,6 +56,37 @@ impl ConsensusCommandSender {
         })
     }
 
+    /// Gets a rendering of the block graph in the DOT language of graphviz,
+    /// with the clique membership, the finality and the discard status of the blocks.
+    ///
+    /// # Arguments
+    /// * `slot_start`: optional slot start for slot-based filtering (included).
+    /// * `slot_end`: optional slot end for slot-based filtering (excluded).
+    pub async fn get_graph_dot(
+        &self,
+        slot_start: Option<Slot>,
+        slot_end: Option<Slot>,
+    ) -> Result<String> {
+        let (response_tx, response_rx) = oneshot::channel::<String>();
+        self.0
+            .send(ConsensusCommand::GetGraphDot {
+                slot_start,
+                slot_end,
+                response_tx,
+            })
+            .await
+            .map_err(|_| {
+                ConsensusError::SendChannelError(
+                    "send error consensus command get_graph_dot".to_string(),
+                )
+            })?;
+        response_rx.await.map_err(|_| {
+            ConsensusError::ReceiveChannelError(
+                "consensus command get_graph_dot response read error".to_string(),
+            )
+        })
+    }
+
     /// Gets all cliques.
     ///
     pub async fn get_cliques(&self) -> Result<Vec<Clique>, ConsensusError> {
@@ -102,6 +133,33 @@ impl ConsensusCommandSender {
         })
     }
 
+    /// Gets the confirmation weights of a batch of blocks, None for the blocks that are not active.
+    ///
+    /// # Arguments
+    /// * ids: array of block IDs
+    pub async fn get_confirmation_weights(
+        &self,
+        ids: &[BlockId],
+    ) -> Result<Vec<Option<u64>>, ConsensusError> {
+        let (response_tx, response_rx) = oneshot::channel::<Vec<Option<u64>>>();
+        self.0
+            .send(ConsensusCommand::GetConfirmationWeights {
+                ids: ids.to_vec(),
+                response_tx,
+            })
+            .await
+            .map_err(|_| {
+                ConsensusError::SendChannelError(
+                    "send error consensus command get_confirmation_weights".to_string(),
+                )
+            })?;
+        response_rx.await.map_err(|_| {
+            ConsensusError::ReceiveChannelError(
+                "consensus command get_confirmation_weights response read error".to_string(),
+            )
+        })
+    }
+
     /// get bootstrap snapshot
     pub async fn get_bootstrap_state(&self) -> Result<BootstrapableGraph, ConsensusError> {
         let (response_tx, mut response_rx) = mpsc::channel::<Box<BootstrapableGraph>>(10);
@@ -137,6 +195,24 @@ impl ConsensusCommandSender {
         })
     }
 
+    /// get best parents, from an async context
+    pub async fn get_best_parents_async(&self) -> Result<Vec<(BlockId, u64)>, ConsensusError> {
+        let (response_tx, response_rx) = oneshot::channel::<Vec<(BlockId, u64)>>();
+        self.0
+            .send(ConsensusCommand::GetBestParents { response_tx })
+            .await
+            .map_err(|_| {
+                ConsensusError::SendChannelError(
+                    "send error consensus command get_best_parents".into(),
+                )
+            })?;
+        response_rx.await.map_err(|_| {
+            ConsensusError::ReceiveChannelError(
+                "consensus command get_best_parents response read error".to_string(),
+            )
+        })
+    }
+
     /// get block id of a slot in a blockclique
     pub async fn get_blockclique_block_at_slot(
         &self,
@@ -196,6 +272,24 @@ impl ConsensusCommandSender {
         })
     }
 
+    /// Gets the latest blockclique re-organizations, oldest first.
+    pub async fn get_reorg_history(&self) -> Result<Vec<ReorgInfo>, ConsensusError> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.0
+            .send(ConsensusCommand::GetReorgHistory(response_tx))
+            .await
+            .map_err(|_| {
+                ConsensusError::SendChannelError(
+                    "send error consensus command get_reorg_history".to_string(),
+                )
+            })?;
+        response_rx.await.map_err(|_| {
+            ConsensusError::ReceiveChannelError(
+                "consensus command get_reorg_history response read error".to_string(),
+            )
+        })
+    }
+
     ///send block
     pub fn send_block(
         &self,
@@ -1,6 +1,10 @@
+use massa_models::api::ReorgInfo;
+
 /// Events that are emitted by consensus.
 #[derive(Debug, Clone)]
 pub enum ConsensusEvent {
     /// probable desynchronization detected, need re-synchronization
     NeedSync,
+    /// previously notified blockclique blocks were dropped from the blockclique
+    Reorg(ReorgInfo),
 }
@@ -46,12 +46,17 @@
 //! `massa-models` crate sources.
 use massa_execution_exports::ExecutionController;
 use massa_graph::settings::GraphConfig;
+use massa_models::api::{BlockConfirmation, BlockSummary, ReorgInfo};
+use massa_models::stats::DiskSpaceStatus;
 use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
 use massa_protocol_exports::{ProtocolCommandSender, ProtocolEventReceiver};
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
-use tokio::sync::mpsc;
+use parking_lot::RwLock;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
 
 use crate::{
     commands::{ConsensusCommand, ConsensusManagementCommand},
@@ -97,10 +102,16 @@ pub struct ConsensusConfig {
     pub block_db_prune_interval: MassaTime,
     /// max number of items returned while querying
     pub max_item_return_count: usize,
+    /// max number of blockclique re-organizations kept in history
+    pub max_reorg_history: usize,
     /// Max gas per block for the execution configuration
     pub max_gas_per_block: u64,
     /// channel size
     pub channel_size: usize,
+    /// directory of the disk store of the active blocks, replayed at startup. None to disable it
+    pub graph_store_path: Option<PathBuf>,
+    /// confirmation weights at which a block is notified as more likely to become final
+    pub confirmation_weight_thresholds: Vec<u64>,
 }
 
 impl From<&ConsensusConfig> for GraphConfig {
@@ -118,6 +129,7 @@ impl From<&ConsensusConfig> for GraphConfig {
             force_keep_final_periods: cfg.force_keep_final_periods,
             endorsement_count: cfg.endorsement_count,
             max_item_return_count: cfg.max_item_return_count,
+            confirmation_weight_thresholds: cfg.confirmation_weight_thresholds.clone(),
         }
     }
 }
@@ -137,6 +149,10 @@ pub struct ConsensusWorkerChannels {
     pub pool_command_sender: Box<dyn PoolController>,
     /// Selector controller
     pub selector_controller: Box<dyn SelectorController>,
+    /// Channels broadcasting block updates.
+    pub broadcasts: ConsensusBroadcasts,
+    /// Free disk space status, the block graph store is not synced while it is critical.
+    pub disk_status: Arc<RwLock<DiskSpaceStatus>>,
     /// Channel receiving consensus commands.
     pub controller_command_rx: mpsc::Receiver<ConsensusCommand>,
     /// Channel sending out consensus events.
@@ -158,6 +174,23 @@ pub struct ConsensusChannels {
     pub pool_command_sender: Box<dyn PoolController>,
     /// selector controller
     pub selector_controller: Box<dyn SelectorController>,
+    /// channels broadcasting block updates
+    pub broadcasts: ConsensusBroadcasts,
+    /// free disk space status of the node's storage paths
+    pub disk_status: Arc<RwLock<DiskSpaceStatus>>,
+}
+
+/// Channels broadcasting block updates to their subscribers
+#[derive(Clone)]
+pub struct ConsensusBroadcasts {
+    /// broadcasts the blocks entering the blockclique
+    pub blockclique_block_sender: broadcast::Sender<BlockSummary>,
+    /// broadcasts the blocks becoming final
+    pub final_block_sender: broadcast::Sender<BlockSummary>,
+    /// broadcasts the blockclique re-organizations
+    pub reorg_sender: broadcast::Sender<ReorgInfo>,
+    /// broadcasts the blocks crossing a confirmation weight threshold
+    pub confirmation_sender: broadcast::Sender<BlockConfirmation>,
 }
 
 #[cfg(feature = "testing")]
@@ -211,8 +244,11 @@ impl Default for ConsensusConfig {
             endorsement_count: ENDORSEMENT_COUNT,
             block_db_prune_interval: MassaTime::from_millis(1000),
             max_item_return_count: 100,
+            max_reorg_history: 100,
             max_gas_per_block: MAX_GAS_PER_BLOCK,
             channel_size: CHANNEL_SIZE,
+            graph_store_path: None,
+            confirmation_weight_thresholds: Vec::new(),
         }
     }
 }
@@ -1,17 +1,32 @@
 use std::collections::HashMap;
 
+use crate::settings::ConsensusBroadcasts;
 use massa_cipher::encrypt;
 use massa_models::{
     address::Address,
+    config::BROADCAST_CHANNEL_SIZE,
     ledger_models::LedgerData,
     rolls::{RollCounts, RollUpdate, RollUpdates},
 };
 use massa_signature::KeyPair;
 use tempfile::NamedTempFile;
+use tokio::sync::broadcast;
 
 /// Password used for encryption in tests
 pub const TEST_PASSWORD: &str = "PASSWORD";
 
+impl Default for ConsensusBroadcasts {
+    /// broadcast channels without subscribers used for testing
+    fn default() -> Self {
+        Self {
+            blockclique_block_sender: broadcast::channel(BROADCAST_CHANNEL_SIZE).0,
+            final_block_sender: broadcast::channel(BROADCAST_CHANNEL_SIZE).0,
+            reorg_sender: broadcast::channel(BROADCAST_CHANNEL_SIZE).0,
+            confirmation_sender: broadcast::channel(BROADCAST_CHANNEL_SIZE).0,
+        }
+    }
+}
+
 /// generate a named temporary JSON ledger file
 pub fn generate_ledger_file(ledger_vec: &HashMap<Address, LedgerData>) -> NamedTempFile {
     use std::io::prelude::*;
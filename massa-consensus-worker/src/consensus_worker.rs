@@ -5,14 +5,21 @@ use massa_consensus_exports::{
     settings::ConsensusWorkerChannels,
     ConsensusConfig,
 };
-use massa_graph::{BlockGraph, BlockGraphExport};
+use massa_graph::{BlockGraph, BlockGraphExport, BlockGraphStore};
 use massa_models::timeslots::{get_block_slot_timestamp, get_latest_block_slot_at_timestamp};
+use massa_models::{
+    active_block::ActiveBlock,
+    api::{BlockGraphStatus, BlockSummary, ReorgInfo},
+};
 use massa_models::{address::Address, block::BlockId, slot::Slot};
 use massa_models::{block::WrappedHeader, prehash::PreHashMap};
-use massa_models::{prehash::PreHashSet, stats::ConsensusStats};
+use massa_models::{
+    prehash::PreHashSet,
+    stats::{ConsensusStats, DiskSpaceStatus, FinalityLatencyStats},
+};
 use massa_protocol_exports::{ProtocolEvent, ProtocolEventReceiver};
 use massa_storage::Storage;
-use massa_time::MassaTime;
+use massa_time::{ClockCompensation, MassaTime};
 use std::{
     cmp::max,
     collections::{HashMap, VecDeque},
@@ -35,6 +42,8 @@ pub struct ConsensusWorker {
     channels: ConsensusWorkerChannels,
     /// Database containing all information about blocks, the `BlockGraph` and cliques.
     block_db: BlockGraph,
+    /// Disk store of the active blocks, if enabled
+    graph_store: Option<BlockGraphStore>,
     /// Previous slot.
     previous_slot: Option<Slot>,
     /// Next slot
@@ -44,13 +53,15 @@ pub struct ConsensusWorker {
     /// latest final periods
     latest_final_periods: Vec<u64>,
     /// clock compensation
-    clock_compensation: i64,
+    clock_compensation: ClockCompensation,
     /// Final block stats `(time, creator, is_from_protocol)`
     final_block_stats: VecDeque<(MassaTime, Address, bool)>,
     /// Blocks that come from protocol used for stats and ids are removed when inserted in `final_block_stats`
     protocol_blocks: VecDeque<(MassaTime, BlockId)>,
     /// Stale block timestamp
     stale_block_stats: VecDeque<MassaTime>,
+    /// Finality latency stats `(finality time, thread, time elapsed since the block slot)`
+    finality_latency_stats: VecDeque<(MassaTime, u8, MassaTime)>,
     /// the time span considered for stats
     stats_history_timespan: MassaTime,
     /// the time span considered for desynchronization detection
@@ -60,6 +71,8 @@ pub struct ConsensusWorker {
     launch_time: MassaTime,
     /// previous blockclique notified to Execution
     prev_blockclique: PreHashMap<BlockId, Slot>,
+    /// latest blockclique re-organizations, oldest first
+    reorg_history: VecDeque<ReorgInfo>,
 }
 
 impl ConsensusWorker {
@@ -70,16 +83,20 @@ impl ConsensusWorker {
     /// * `cfg`: consensus configuration.
     /// * `protocol_command_sender`: associated protocol controller
     /// * `block_db`: Database containing all information about blocks, the blockgraph and cliques.
+    /// * `graph_store`: disk store of the active blocks, whose blocks are replayed into `block_db`
+    /// * `storage`: shared storage receiving the replayed blocks
     /// * `controller_command_rx`: Channel receiving consensus commands.
     /// * `controller_event_tx`: Channel sending out consensus events.
     /// * `controller_manager_rx`: Channel receiving consensus management commands.
     pub(crate) async fn new(
         cfg: ConsensusConfig,
         channels: ConsensusWorkerChannels,
-        block_db: BlockGraph,
-        clock_compensation: i64,
+        mut block_db: BlockGraph,
+        mut graph_store: Option<BlockGraphStore>,
+        storage: Storage,
+        clock_compensation: ClockCompensation,
     ) -> Result<ConsensusWorker> {
-        let now = MassaTime::now(clock_compensation)?;
+        let now = clock_compensation.now()?;
         let previous_slot = get_latest_block_slot_at_timestamp(
             cfg.thread_count,
             cfg.t0,
@@ -89,6 +106,29 @@ impl ConsensusWorker {
         let next_slot = previous_slot.map_or(Ok(Slot::new(0u64, 0u8)), |s| {
             s.get_next_slot(cfg.thread_count)
         })?;
+
+        // replay the blocks stored by a previous run that are more recent than the final ones
+        if let Some(store) = graph_store.as_mut() {
+            let mut replayed_count = 0usize;
+            for (block_id, export_block) in store.load() {
+                let slot = export_block.block.content.header.content.slot;
+                let (_, latest_final_period) =
+                    block_db.get_latest_final_blocks_periods()[slot.thread as usize];
+                if slot.period <= latest_final_period {
+                    continue;
+                }
+                let mut block_storage = storage.clone_without_refs();
+                block_storage.store_operations(export_block.operations);
+                block_storage.store_block(export_block.block);
+                block_db.incoming_block(block_id, slot, previous_slot, block_storage)?;
+                replayed_count += 1;
+            }
+            info!(
+                "Replayed {} blocks from the block graph store",
+                replayed_count
+            );
+        }
+
         let latest_final_periods: Vec<u64> = block_db
             .get_latest_final_blocks_periods()
             .iter()
@@ -153,20 +193,23 @@ impl ConsensusWorker {
 
         Ok(ConsensusWorker {
             block_db,
+            graph_store,
             previous_slot,
             next_slot,
             wishlist: Default::default(),
             latest_final_periods,
-            clock_compensation,
+            clock_compensation: clock_compensation.clone(),
             channels,
             final_block_stats: Default::default(),
             protocol_blocks: Default::default(),
             stale_block_stats: VecDeque::new(),
+            finality_latency_stats: VecDeque::new(),
             stats_desync_detection_timespan,
             stats_history_timespan: max(stats_desync_detection_timespan, cfg.stats_timespan),
             cfg,
-            launch_time: MassaTime::now(clock_compensation)?,
+            launch_time: clock_compensation.now()?,
             prev_blockclique,
+            reorg_history: Default::default(),
         })
     }
 
@@ -178,6 +221,11 @@ impl ConsensusWorker {
             .pool_command_sender
             .notify_final_cs_periods(&self.latest_final_periods);
 
+        // process the changes brought by the replayed blocks
+        if self.graph_store.is_some() {
+            self.block_db_changed().await?;
+        }
+
         // set slot timer
         let slot_deadline = get_block_slot_timestamp(
             self.cfg.thread_count,
@@ -185,7 +233,7 @@ impl ConsensusWorker {
             self.cfg.genesis_timestamp,
             self.next_slot,
         )?
-        .estimate_instant(self.clock_compensation)?;
+        .estimate_instant(self.clock_compensation.get())?;
         let next_slot_timer = sleep_until(tokio::time::Instant::from(slot_deadline));
 
         tokio::pin!(next_slot_timer);
@@ -225,7 +273,7 @@ impl ConsensusWorker {
                 _ = &mut next_slot_timer => {
                     massa_trace!("consensus.consensus_worker.run_loop.select.slot_tick", {});
                     if let Some(end) = self.cfg.end_timestamp {
-                        if MassaTime::now(self.clock_compensation)? > end {
+                        if self.clock_compensation.now()? > end {
                             info!("This episode has come to an end, please get the latest testnet node version to continue");
                             break;
                         }
@@ -239,6 +287,16 @@ impl ConsensusWorker {
                     // prune block db
                     let _discarded_final_blocks = self.block_db.prune()?;
 
+                    // write the changes of the graph to disk, unless free disk space is
+                    // critically low: they are caught up on the first sync once it is back
+                    if let Some(store) = self.graph_store.as_mut() {
+                        if *self.channels.disk_status.read() == DiskSpaceStatus::Critical {
+                            debug!("free disk space is critically low: graph store sync paused");
+                        } else {
+                            store.sync(&self.block_db)?;
+                        }
+                    }
+
                     // reset timer
                     prune_timer.set(sleep( self.cfg.block_db_prune_interval.to_duration()))
                 }
@@ -254,6 +312,9 @@ impl ConsensusWorker {
             }
         }
         // after this curly brace you can find the end of the loop
+        if let Some(store) = self.graph_store.as_mut() {
+            store.sync(&self.block_db)?;
+        }
         Ok(self.channels.protocol_event_receiver)
     }
 
@@ -262,7 +323,7 @@ impl ConsensusWorker {
     /// detects desynchronization
     /// produce quite more logs than actual stuff
     async fn slot_tick(&mut self, next_slot_timer: &mut std::pin::Pin<&mut Sleep>) -> Result<()> {
-        let now = MassaTime::now(self.clock_compensation)?;
+        let now = self.clock_compensation.now()?;
         let observed_slot = get_latest_block_slot_at_timestamp(
             self.cfg.thread_count,
             self.cfg.t0,
@@ -278,7 +339,7 @@ impl ConsensusWorker {
                 self.cfg.genesis_timestamp,
                 self.next_slot,
             )?
-            .estimate_instant(self.clock_compensation)?;
+            .estimate_instant(self.clock_compensation.get())?;
             next_slot_timer.set(sleep_until(tokio::time::Instant::from(sleep_deadline)));
             return Ok(());
         }
@@ -335,7 +396,7 @@ impl ConsensusWorker {
             self.cfg.genesis_timestamp,
             self.next_slot,
         )?
-        .estimate_instant(self.clock_compensation)?;
+        .estimate_instant(self.clock_compensation.get())?;
         next_slot_timer.set(sleep_until(tokio::time::Instant::from(sleep_deadline)));
 
         // prune stats
@@ -373,6 +434,21 @@ impl ConsensusWorker {
                 }
                 Ok(())
             }
+            ConsensusCommand::GetGraphDot {
+                slot_start,
+                slot_end,
+                response_tx,
+            } => {
+                massa_trace!(
+                    "consensus.consensus_worker.process_consensus_command.get_graph_dot",
+                    {}
+                );
+                let graph = BlockGraphExport::extract_from(&self.block_db, slot_start, slot_end)?;
+                if response_tx.send(graph.to_dot()).is_err() {
+                    warn!("consensus: could not send GetGraphDot answer");
+                }
+                Ok(())
+            }
             // gets the graph status of a batch of blocks
             ConsensusCommand::GetBlockStatuses { ids, response_tx } => {
                 massa_trace!(
@@ -388,6 +464,21 @@ impl ConsensusWorker {
                 }
                 Ok(())
             }
+            // gets the confirmation weight of a batch of blocks
+            ConsensusCommand::GetConfirmationWeights { ids, response_tx } => {
+                massa_trace!(
+                    "consensus.consensus_worker.process_consensus_command.get_confirmation_weights",
+                    {}
+                );
+                let res: Vec<_> = ids
+                    .iter()
+                    .map(|id| self.block_db.get_confirmation_weight(id))
+                    .collect();
+                if response_tx.send(res).is_err() {
+                    warn!("consensus: could not send get_confirmation_weights answer");
+                }
+                Ok(())
+            }
             ConsensusCommand::GetCliques(response_tx) => {
                 massa_trace!(
                     "consensus.consensus_worker.process_consensus_command.get_cliques",
@@ -398,6 +489,19 @@ impl ConsensusWorker {
                 }
                 Ok(())
             }
+            ConsensusCommand::GetReorgHistory(response_tx) => {
+                massa_trace!(
+                    "consensus.consensus_worker.process_consensus_command.get_reorg_history",
+                    {}
+                );
+                if response_tx
+                    .send(self.reorg_history.iter().cloned().collect())
+                    .is_err()
+                {
+                    warn!("consensus: could not send get_reorg_history response");
+                }
+                Ok(())
+            }
             ConsensusCommand::GetBootstrapState(response_tx) => {
                 massa_trace!(
                     "consensus.consensus_worker.process_consensus_command.get_bootstrap_state",
@@ -465,7 +569,7 @@ impl ConsensusWorker {
     /// retrieve stats
     /// Used in response to a API request
     fn get_stats(&mut self) -> Result<ConsensusStats> {
-        let timespan_end = max(self.launch_time, MassaTime::now(self.clock_compensation)?);
+        let timespan_end = max(self.launch_time, self.clock_compensation.now()?);
         let timespan_start = max(
             timespan_end.saturating_sub(self.cfg.stats_timespan),
             self.launch_time,
@@ -481,10 +585,22 @@ impl ConsensusWorker {
             .filter(|t| **t >= timespan_start && **t < timespan_end)
             .count() as u64;
         let clique_count = self.block_db.get_clique_count() as u64;
+        let mut thread_latencies = vec![Vec::new(); self.cfg.thread_count as usize];
+        for (t, thread, latency) in self.finality_latency_stats.iter() {
+            if *t >= timespan_start && *t < timespan_end {
+                thread_latencies[*thread as usize].push(*latency);
+            }
+        }
+        let finality_latencies = thread_latencies
+            .into_iter()
+            .enumerate()
+            .map(|(thread, latencies)| FinalityLatencyStats::from_samples(thread as u8, latencies))
+            .collect();
         Ok(ConsensusStats {
             final_block_count,
             stale_block_count,
             clique_count,
+            finality_latencies,
             start_timespan: timespan_start,
             end_timespan: timespan_end,
         })
@@ -507,7 +623,7 @@ impl ConsensusWorker {
                 );
                 self.block_db
                     .incoming_block(block_id, slot, self.previous_slot, storage)?;
-                let now = MassaTime::now(self.clock_compensation)?;
+                let now = self.clock_compensation.now()?;
                 self.protocol_blocks.push_back((now, block_id));
                 self.block_db_changed().await?;
             }
@@ -531,8 +647,10 @@ impl ConsensusWorker {
 
     /// prune statistics according to the stats span
     fn prune_stats(&mut self) -> Result<()> {
-        let start_time =
-            MassaTime::now(self.clock_compensation)?.saturating_sub(self.stats_history_timespan);
+        let start_time = self
+            .clock_compensation
+            .now()?
+            .saturating_sub(self.stats_history_timespan);
         while let Some((t, _, _)) = self.final_block_stats.front() {
             if t < &start_time {
                 self.final_block_stats.pop_front();
@@ -547,6 +665,13 @@ impl ConsensusWorker {
                 break;
             }
         }
+        while let Some((t, _, _)) = self.finality_latency_stats.front() {
+            if t < &start_time {
+                self.finality_latency_stats.pop_front();
+            } else {
+                break;
+            }
+        }
         while let Some((t, _)) = self.protocol_blocks.front() {
             if t < &start_time {
                 self.protocol_blocks.pop_front();
@@ -558,7 +683,8 @@ impl ConsensusWorker {
     }
 
     /// Notify execution about blockclique changes and finalized blocks.
-    fn notify_execution(&mut self, finalized_blocks: HashMap<Slot, BlockId>) {
+    /// Returns the re-organization that happened if previously notified blocks left the blockclique.
+    fn notify_execution(&mut self, finalized_blocks: HashMap<Slot, BlockId>) -> Option<ReorgInfo> {
         // List new block storage instances that Execution doesn't know about.
         // That's blocks that have not been sent to execution before, ie. in the previous blockclique).
         let mut new_blocks_storage: PreHashMap<BlockId, Storage> = finalized_blocks
@@ -578,6 +704,7 @@ impl ConsensusWorker {
 
         // Get new blockclique block list with slots.
         let mut blockclique_changed = false;
+        let mut entered_blocks: Vec<(BlockId, Slot)> = Vec::new();
         let new_blockclique: PreHashMap<BlockId, Slot> = self
             .block_db
             .get_blockclique()
@@ -597,6 +724,13 @@ impl ConsensusWorker {
                         .get_active_block(b_id)
                         .expect("blockclique block not found in active blocks");
                     new_blocks_storage.insert(*b_id, storage.clone());
+                    entered_blocks.push((*b_id, a_block.slot));
+                    // notify the subscribers (fails only if there is none)
+                    let _ = self
+                        .channels
+                        .broadcasts
+                        .blockclique_block_sender
+                        .send(get_block_summary(a_block, true));
                     (*b_id, a_block.slot)
                 }
             })
@@ -607,13 +741,14 @@ impl ConsensusWorker {
             // In that case, we mark the blockclique as having changed.
             blockclique_changed = true;
         }
+        let reorg = self.record_reorg(entered_blocks);
         // Overwrite previous blockclique.
         // Should still be done even if unchanged because elements were removed from it above.
         self.prev_blockclique = new_blockclique.clone();
 
         if finalized_blocks.is_empty() && !blockclique_changed {
             // There are no changes (neither block finalizations not blockclique changes) to send to execution.
-            return;
+            return reorg;
         }
 
         // Notify execution of block finalizations and blockclique changes
@@ -628,6 +763,71 @@ impl ConsensusWorker {
                 },
                 new_blocks_storage,
             );
+        reorg
+    }
+
+    /// Records a re-organization if blocks of the previous blockclique were dropped from it.
+    /// Must be called once the blocks of the new blockclique were removed from `prev_blockclique`.
+    ///
+    /// # Arguments
+    /// * `new_blocks`: blocks that entered the blockclique
+    fn record_reorg(&mut self, mut new_blocks: Vec<(BlockId, Slot)>) -> Option<ReorgInfo> {
+        // Final blocks leave the blockclique only when pruned: they are not re-organized.
+        let mut dropped_blocks: Vec<(BlockId, Slot)> = self
+            .prev_blockclique
+            .iter()
+            .filter(|(b_id, _slot)| {
+                !matches!(
+                    self.block_db.get_block_status(b_id),
+                    BlockGraphStatus::Final | BlockGraphStatus::NotFound
+                )
+            })
+            .map(|(b_id, slot)| (*b_id, *slot))
+            .collect();
+        if dropped_blocks.is_empty() {
+            return None;
+        }
+        dropped_blocks.sort_unstable_by_key(|(_b_id, slot)| *slot);
+        new_blocks.sort_unstable_by_key(|(_b_id, slot)| *slot);
+
+        // the depth is the largest number of blocks dropped in a single thread
+        let mut dropped_per_thread = vec![0u64; self.cfg.thread_count as usize];
+        for (_b_id, slot) in &dropped_blocks {
+            dropped_per_thread[slot.thread as usize] += 1;
+        }
+        let reorg = ReorgInfo {
+            time: self
+                .clock_compensation
+                .now()
+                .expect("could not get current time"),
+            depth: dropped_per_thread.into_iter().max().unwrap_or_default(),
+            dropped_blocks,
+            new_blocks,
+        };
+        warn!(
+            "blockclique re-organization of depth {}: blocks {:?} were replaced by blocks {:?}",
+            reorg.depth,
+            reorg
+                .dropped_blocks
+                .iter()
+                .map(|(b_id, _slot)| b_id)
+                .collect::<Vec<_>>(),
+            reorg
+                .new_blocks
+                .iter()
+                .map(|(b_id, _slot)| b_id)
+                .collect::<Vec<_>>()
+        );
+
+        // keep a rolling history
+        self.reorg_history.push_back(reorg.clone());
+        while self.reorg_history.len() > self.cfg.max_reorg_history {
+            self.reorg_history.pop_front();
+        }
+
+        // notify the subscribers (fails only if there is none)
+        let _ = self.channels.broadcasts.reorg_sender.send(reorg.clone());
+        Some(reorg)
     }
 
     /// call me if the block database changed
@@ -670,7 +870,7 @@ impl ConsensusWorker {
         }
 
         // manage finalized blocks
-        let timestamp = MassaTime::now(self.clock_compensation)?;
+        let timestamp = self.clock_compensation.now()?;
         let finalized_blocks = self.block_db.get_new_final_blocks();
         let mut final_block_slots = HashMap::with_capacity(finalized_blocks.len());
         for b_id in finalized_blocks {
@@ -678,6 +878,14 @@ impl ConsensusWorker {
                 // add to final blocks to notify execution
                 final_block_slots.insert(a_block.slot, b_id);
 
+                // notify the subscribers (fails only if there is none)
+                let is_in_blockclique = self.block_db.get_blockclique().contains(&b_id);
+                let _ = self
+                    .channels
+                    .broadcasts
+                    .final_block_sender
+                    .send(get_block_summary(a_block, is_in_blockclique));
+
                 // add to stats
                 let block_is_from_protocol = self
                     .protocol_blocks
@@ -688,11 +896,38 @@ impl ConsensusWorker {
                     a_block.creator_address,
                     block_is_from_protocol,
                 ));
+                let slot_timestamp = get_block_slot_timestamp(
+                    self.cfg.thread_count,
+                    self.cfg.t0,
+                    self.cfg.genesis_timestamp,
+                    a_block.slot,
+                )?;
+                self.finality_latency_stats.push_back((
+                    timestamp,
+                    a_block.slot.thread,
+                    timestamp.saturating_sub(slot_timestamp),
+                ));
             }
         }
 
+        // notify the subscribers of the blocks crossing a confirmation weight threshold
+        for confirmation in self.block_db.get_new_confirmations() {
+            let _ = self
+                .channels
+                .broadcasts
+                .confirmation_sender
+                .send(confirmation);
+        }
+
         // notify execution
-        self.notify_execution(final_block_slots);
+        #[cfg_attr(feature = "sandbox", allow(unused_variables))]
+        let reorg = self.notify_execution(final_block_slots);
+        #[cfg(not(feature = "sandbox"))]
+        if let Some(reorg) = reorg {
+            let _ = self
+                .send_consensus_event(ConsensusEvent::Reorg(reorg))
+                .await;
+        }
 
         // notify protocol of block wishlist
         let new_wishlist = self.block_db.get_block_wishlist()?;
@@ -745,7 +980,7 @@ impl ConsensusWorker {
 
         // add stale blocks to stats
         let new_stale_block_ids_creators_slots = self.block_db.get_new_stale_blocks();
-        let timestamp = MassaTime::now(self.clock_compensation)?;
+        let timestamp = self.clock_compensation.now()?;
         for (_b_id, (_b_creator, _b_slot)) in new_stale_block_ids_creators_slots.into_iter() {
             self.stale_block_stats.push_back(timestamp);
 
@@ -786,3 +1021,17 @@ impl ConsensusWorker {
         Err(ConsensusError::ChannelError("failed to send event".into()))
     }
 }
+
+/// Summarizes an active block for the subscribers of block updates
+fn get_block_summary(a_block: &ActiveBlock, is_in_blockclique: bool) -> BlockSummary {
+    BlockSummary {
+        id: a_block.block_id,
+        is_final: a_block.is_final,
+        is_stale: false,
+        discard_reason: None,
+        is_in_blockclique,
+        slot: a_block.slot,
+        creator: a_block.creator_address,
+        parents: a_block.parents.iter().map(|(id, _period)| *id).collect(),
+    }
+}
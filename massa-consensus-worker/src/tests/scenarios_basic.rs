@@ -1,10 +1,21 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use super::tools;
+use super::tools::get_dummy_block_id;
 use crate::tests::block_factory::BlockFactory;
 use massa_consensus_exports::ConsensusConfig;
+use massa_graph::{
+    create_genesis_block, export_active_block::ExportActiveBlock, settings::GraphConfig,
+    BlockGraph, BlockGraphExport, BlockGraphStore, BootstrapableGraph,
+};
 use massa_hash::Hash;
-use massa_models::{block::BlockId, slot::Slot};
+use massa_models::{
+    address::Address,
+    api::{BlockDiscardKind, BlockGraphStatus},
+    block::{BlockId, WrappedBlock},
+    slot::Slot,
+};
+use massa_pos_exports::test_exports::MockSelectorController;
 use massa_signature::KeyPair;
 use massa_storage::Storage;
 use serial_test::serial;
@@ -55,7 +66,14 @@ async fn test_old_stale_not_propagated_and_discarded() {
                 .await
                 .expect("could not get block graph status");
             assert_eq!(status.discarded_blocks.len(), 1);
-            assert!(status.discarded_blocks.get(&block_3.id).is_some());
+            // its parent in its own thread is not from an earlier slot
+            assert_eq!(
+                status
+                    .discarded_blocks
+                    .get(&block_3.id)
+                    .map(|(reason, _)| reason.kind()),
+                Some(BlockDiscardKind::ParentMismatch)
+            );
             (
                 block_factory.take_protocol_controller(),
                 consensus_command_sender,
@@ -259,3 +277,202 @@ async fn test_double_staking_does_not_propagate() {
     )
     .await;
 }
+
+/// Graph bootstrapped with the genesis blocks and two final blocks:
+/// the first one at slot (1, 0) on top of the genesis blocks,
+/// the second one at slot (1, 1) on top of the first one and of the genesis blocks of the other
+/// threads.
+/// Returns the graph along with the ids of the genesis blocks and of the two blocks.
+async fn get_bootstrapped_graph(cfg: &GraphConfig) -> (BlockGraph, Vec<BlockId>, BlockId, BlockId) {
+    let keypair = KeyPair::generate();
+    let export_block = |block: WrappedBlock, parents: Vec<(BlockId, u64)>| ExportActiveBlock {
+        block,
+        operations: Vec::new(),
+        parents,
+        is_final: true,
+    };
+    let mut final_blocks = Vec::new();
+    let mut genesis_ids = Vec::new();
+    for thread in 0..cfg.thread_count {
+        let (id, block) = create_genesis_block(cfg, thread).expect("could not create genesis");
+        genesis_ids.push(id);
+        final_blocks.push(export_block(block, Vec::new()));
+    }
+    let block_1 = tools::create_block_with_merkle_root(
+        &ConsensusConfig::default(),
+        Hash::compute_from(&Vec::new()),
+        Slot::new(1, 0),
+        genesis_ids.clone(),
+        &keypair,
+    );
+    let block_1_id = block_1.id;
+    final_blocks.push(export_block(
+        block_1,
+        genesis_ids.iter().map(|id| (*id, 0)).collect(),
+    ));
+    let mut block_2_parents: Vec<(BlockId, u64)> = genesis_ids.iter().map(|id| (*id, 0)).collect();
+    block_2_parents[0] = (block_1_id, 1);
+    let block_2 = tools::create_block_with_merkle_root(
+        &ConsensusConfig::default(),
+        Hash::compute_from(&Vec::new()),
+        Slot::new(1, 1),
+        block_2_parents.iter().map(|(id, _)| *id).collect(),
+        &keypair,
+    );
+    let block_2_id = block_2.id;
+    final_blocks.push(export_block(block_2, block_2_parents));
+
+    let (selector_controller, _selector_receiver) = MockSelectorController::new_with_receiver();
+    let graph = BlockGraph::new(
+        cfg.clone(),
+        Some(BootstrapableGraph { final_blocks }),
+        Storage::create_root(),
+        selector_controller,
+    )
+    .await
+    .expect("could not bootstrap the block graph");
+    (graph, genesis_ids, block_1_id, block_2_id)
+}
+
+#[tokio::test]
+#[serial]
+async fn test_graph_store_replayed_after_restart() {
+    let cfg = GraphConfig::from(&ConsensusConfig::default());
+    let (graph, genesis_ids, block_1_id, block_2_id) = get_bootstrapped_graph(&cfg).await;
+    let store_dir = tempfile::tempdir().expect("could not create the graph store directory");
+
+    // the blocks of the graph other than the genesis ones are written to the store
+    let mut store = BlockGraphStore::new(store_dir.path(), &cfg);
+    assert!(store.load().is_empty());
+    store.sync(&graph).expect("could not sync the graph store");
+    drop(store);
+
+    // a restarted store reads them back, parents first
+    let mut store = BlockGraphStore::new(store_dir.path(), &cfg);
+    let stored: Vec<(BlockId, bool)> = store
+        .load()
+        .into_iter()
+        .map(|(id, block)| (id, block.is_final))
+        .collect();
+    assert_eq!(stored, vec![(block_1_id, true), (block_2_id, true)]);
+    assert!(!stored.iter().any(|(id, _)| genesis_ids.contains(id)));
+
+    // the blocks leaving the graph are removed from the store
+    let (selector_controller, _selector_receiver) = MockSelectorController::new_with_receiver();
+    let empty_graph = BlockGraph::new(
+        cfg.clone(),
+        None,
+        Storage::create_root(),
+        selector_controller,
+    )
+    .await
+    .expect("could not create the block graph");
+    store
+        .sync(&empty_graph)
+        .expect("could not sync the graph store");
+    drop(store);
+    let mut store = BlockGraphStore::new(store_dir.path(), &cfg);
+    assert!(store.load().is_empty());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_confirmation_weight_accumulates_descendants() {
+    let cfg = GraphConfig::from(&ConsensusConfig::default());
+    let (graph, genesis_ids, block_1_id, block_2_id) = get_bootstrapped_graph(&cfg).await;
+
+    // without endorsements, each block has a fitness of 1
+    let weight = |id: &BlockId| graph.get_confirmation_weight(id);
+    assert_eq!(weight(&genesis_ids[0]), Some(3));
+    assert_eq!(weight(&genesis_ids[1]), Some(3));
+    assert_eq!(weight(&block_1_id), Some(2));
+    assert_eq!(weight(&block_2_id), Some(1));
+    assert_eq!(weight(&get_dummy_block_id("unknown")), None);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_graph_dot() {
+    let cfg = GraphConfig::from(&ConsensusConfig::default());
+    let (graph, genesis_ids, block_1_id, block_2_id) = get_bootstrapped_graph(&cfg).await;
+
+    let dot = BlockGraphExport::extract_from(&graph, None, None)
+        .expect("could not export the graph")
+        .to_dot();
+    assert!(dot.starts_with("digraph block_graph {"));
+    // the blocks are linked to their parents and drawn as final
+    for parent in &genesis_ids {
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\";", parent, block_1_id)));
+    }
+    assert!(dot.contains(&format!("\"{}\" -> \"{}\";", block_1_id, block_2_id)));
+    assert!(dot.contains(&format!("\"{}\" -> \"{}\";", genesis_ids[1], block_2_id)));
+    assert!(!dot.contains(&format!("\"{}\" -> \"{}\";", genesis_ids[0], block_2_id)));
+    for id in genesis_ids.iter().chain([&block_1_id, &block_2_id]) {
+        assert!(dot.contains(&format!("\"{}\" [label=", id)));
+    }
+    assert_eq!(
+        dot.matches("fillcolor=palegreen").count(),
+        genesis_ids.len() + 2
+    );
+
+    // the blocks are left out of a range starting after them, along with their edges
+    let dot = BlockGraphExport::extract_from(&graph, Some(Slot::new(1, 1)), None)
+        .expect("could not export the graph")
+        .to_dot();
+    assert!(dot.contains(&format!("\"{}\" [label=", block_2_id)));
+    assert!(!dot.contains(&format!("\"{}\"", block_1_id)));
+    assert!(!dot.contains(&format!("\"{}\"", genesis_ids[0])));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_discard_reasons() {
+    let cfg = GraphConfig::from(&ConsensusConfig::default());
+    let (mut graph, genesis_ids, block_1_id, block_2_id) = get_bootstrapped_graph(&cfg).await;
+    let keypair = KeyPair::generate();
+
+    // a block not newer than the latest final block of its thread is late, and counted as stale
+    let late_block = tools::create_block_with_merkle_root(
+        &ConsensusConfig::default(),
+        Hash::compute_from(&Vec::new()),
+        Slot::new(1, 0),
+        genesis_ids.clone(),
+        &keypair,
+    );
+    graph
+        .incoming_header(late_block.id, late_block.content.header.clone(), None)
+        .expect("could not process the late header");
+    assert_eq!(
+        graph.get_block_status(&late_block.id),
+        BlockGraphStatus::Discarded(BlockDiscardKind::Late)
+    );
+
+    // a block found invalid by protocol has an invalid content, and is not stale
+    let mut parents = genesis_ids.clone();
+    parents[0] = block_1_id;
+    parents[1] = block_2_id;
+    let invalid_block = tools::create_block_with_merkle_root(
+        &ConsensusConfig::default(),
+        Hash::compute_from(&Vec::new()),
+        Slot::new(2, 0),
+        parents,
+        &keypair,
+    );
+    graph
+        .invalid_block(&invalid_block.id, invalid_block.content.header.clone())
+        .expect("could not mark the block as invalid");
+    assert_eq!(
+        graph.get_block_status(&invalid_block.id),
+        BlockGraphStatus::Discarded(BlockDiscardKind::InvalidContent)
+    );
+
+    let new_stale_blocks = graph.get_new_stale_blocks();
+    assert_eq!(new_stale_blocks.len(), 1);
+    assert_eq!(
+        new_stale_blocks.get(&late_block.id),
+        Some(&(
+            Address::from_public_key(&keypair.get_public_key()),
+            Slot::new(1, 0)
+        ))
+    );
+}
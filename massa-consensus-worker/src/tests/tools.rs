@@ -5,7 +5,8 @@ use crate::start_consensus_controller;
 use massa_cipher::decrypt;
 use massa_consensus_exports::error::ConsensusResult;
 use massa_consensus_exports::{
-    settings::ConsensusChannels, ConsensusCommandSender, ConsensusConfig, ConsensusEventReceiver,
+    settings::{ConsensusBroadcasts, ConsensusChannels},
+    ConsensusCommandSender, ConsensusConfig, ConsensusEventReceiver,
 };
 use massa_execution_exports::test_exports::MockExecutionController;
 use massa_graph::{export_active_block::ExportActiveBlock, BlockGraphExport, BootstrapableGraph};
@@ -733,10 +734,12 @@ pub async fn _consensus_pool_test<F, V>(
                 protocol_event_receiver,
                 pool_command_sender: pool_controller.clone(),
                 selector_controller,
+                broadcasts: ConsensusBroadcasts::default(),
+                disk_status: Default::default(),
             },
             boot_graph,
             storage.clone(),
-            0,
+            Default::default(),
         )
         .await
         .expect("could not start consensus controller");
@@ -823,10 +826,12 @@ pub async fn consensus_pool_test_with_storage<F, V>(
                 protocol_event_receiver,
                 pool_command_sender: pool_controller.clone(),
                 selector_controller: selector_controller,
+                broadcasts: ConsensusBroadcasts::default(),
+                disk_status: Default::default(),
             },
             boot_graph,
             storage.clone(),
-            0,
+            Default::default(),
         )
         .await
         .expect("could not start consensus controller");
@@ -917,10 +922,12 @@ where
                 protocol_event_receiver,
                 pool_command_sender: pool_controller,
                 selector_controller: selector_controller.clone(),
+                broadcasts: ConsensusBroadcasts::default(),
+                disk_status: Default::default(),
             },
             None,
             storage.clone(),
-            0,
+            Default::default(),
         )
         .await
         .expect("could not start consensus controller");
@@ -1009,10 +1016,12 @@ where
                 protocol_event_receiver,
                 pool_command_sender: pool_controller,
                 selector_controller: selector_controller.clone(),
+                broadcasts: ConsensusBroadcasts::default(),
+                disk_status: Default::default(),
             },
             None,
             storage.clone(),
-            0,
+            Default::default(),
         )
         .await
         .expect("could not start consensus controller");
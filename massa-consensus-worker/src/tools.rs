@@ -7,8 +7,9 @@ use massa_consensus_exports::{
     settings::{ConsensusChannels, ConsensusWorkerChannels},
     ConsensusCommandSender, ConsensusEventReceiver, ConsensusManager,
 };
-use massa_graph::{settings::GraphConfig, BlockGraph, BootstrapableGraph};
+use massa_graph::{settings::GraphConfig, BlockGraph, BlockGraphStore, BootstrapableGraph};
 use massa_storage::Storage;
+use massa_time::ClockCompensation;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info};
 
@@ -24,7 +25,7 @@ pub async fn start_consensus_controller(
     channels: ConsensusChannels,
     boot_graph: Option<BootstrapableGraph>,
     storage: Storage,
-    clock_compensation: i64,
+    clock_compensation: ClockCompensation,
 ) -> Result<(
     ConsensusCommandSender,
     ConsensusEventReceiver,
@@ -62,6 +63,10 @@ pub async fn start_consensus_controller(
         channels.selector_controller.clone(),
     )
     .await?;
+    let graph_store = cfg
+        .graph_store_path
+        .as_ref()
+        .map(|path| BlockGraphStore::new(path, &GraphConfig::from(&cfg)));
     let (command_tx, command_rx) = mpsc::channel::<ConsensusCommand>(cfg.channel_size);
     let (event_tx, event_rx) = mpsc::channel::<ConsensusEvent>(cfg.channel_size);
     let (manager_tx, manager_rx) = mpsc::channel::<ConsensusManagementCommand>(1);
@@ -75,11 +80,15 @@ pub async fn start_consensus_controller(
                 execution_controller: channels.execution_controller,
                 pool_command_sender: channels.pool_command_sender,
                 selector_controller: channels.selector_controller,
+                broadcasts: channels.broadcasts,
+                disk_status: channels.disk_status,
                 controller_command_rx: command_rx,
                 controller_event_tx: event_tx,
                 controller_manager_rx: manager_rx,
             },
             block_db,
+            graph_store,
+            storage,
             clock_compensation,
         )
         .await?
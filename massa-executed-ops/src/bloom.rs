@@ -0,0 +1,67 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This file defines a counting bloom filter of operation ids,
+//! used to avoid disk reads when checking operations that were not executed.
+
+use massa_models::operation::OperationId;
+
+/// Number of counters associated to each operation id
+const BLOOM_HASH_COUNT: usize = 4;
+
+/// Counting bloom filter of operation ids.
+///
+/// Operation ids being hashes, their bytes are used as is to pick their counters.
+/// Counters allow removing pruned ids. A saturated counter is never decremented anymore,
+/// so that the filter never gives false negatives.
+#[derive(Debug, Clone)]
+pub(crate) struct OpIdBloomFilter {
+    counters: Vec<u8>,
+}
+
+impl OpIdBloomFilter {
+    /// Creates an empty filter with `size` counters
+    pub fn new(size: usize) -> Self {
+        OpIdBloomFilter {
+            counters: vec![0; size.max(1)],
+        }
+    }
+
+    /// Indexes of the counters of an operation id
+    fn indexes(&self, op_id: &OperationId) -> [usize; BLOOM_HASH_COUNT] {
+        let bytes = op_id.to_bytes();
+        let mut indexes = [0; BLOOM_HASH_COUNT];
+        for (index, chunk) in indexes.iter_mut().zip(bytes.chunks_exact(8)) {
+            let value = u64::from_le_bytes(chunk.try_into().expect("chunk is 8 bytes long"));
+            *index = (value % self.counters.len() as u64) as usize;
+        }
+        indexes
+    }
+
+    /// Removes every operation id
+    pub fn clear(&mut self) {
+        self.counters.iter_mut().for_each(|counter| *counter = 0);
+    }
+
+    /// Adds an operation id
+    pub fn insert(&mut self, op_id: &OperationId) {
+        for index in self.indexes(op_id) {
+            self.counters[index] = self.counters[index].saturating_add(1);
+        }
+    }
+
+    /// Removes an operation id that was previously added
+    pub fn remove(&mut self, op_id: &OperationId) {
+        for index in self.indexes(op_id) {
+            if self.counters[index] != u8::MAX {
+                self.counters[index] = self.counters[index].saturating_sub(1);
+            }
+        }
+    }
+
+    /// Returns false if the operation id was certainly not added
+    pub fn may_contain(&self, op_id: &OperationId) -> bool {
+        self.indexes(op_id)
+            .iter()
+            .all(|index| self.counters[*index] > 0)
+    }
+}
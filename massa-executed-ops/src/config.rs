@@ -1,9 +1,15 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use std::path::PathBuf;
+
 #[derive(Debug, Clone)]
 pub struct ExecutedOpsConfig {
     /// Number of threads
     pub thread_count: u8,
     /// Maximum size of a bootstrap part
     pub bootstrap_part_size: u64,
+    /// Path of the disk store of the executed operations, `None` to keep them in memory
+    pub disk_path: Option<PathBuf>,
+    /// Number of counters of the bloom filter of the disk store
+    pub bloom_filter_size: usize,
 }
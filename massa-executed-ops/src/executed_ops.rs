@@ -3,7 +3,7 @@
 //! This file defines a structure to list and prune previously executed operations.
 //! Used to detect operation reuse.
 
-use crate::{ops_changes::ExecutedOpsChanges, ExecutedOpsConfig};
+use crate::{ops_changes::ExecutedOpsChanges, store::ExecutedOpsStore, ExecutedOpsConfig};
 use massa_hash::{Hash, HASH_SIZE_BYTES};
 use massa_models::{
     operation::{OperationId, OperationIdDeserializer},
@@ -28,15 +28,16 @@ use std::{
 
 const EXECUTED_OPS_INITIAL_BYTES: &[u8; 32] = &[0; HASH_SIZE_BYTES];
 
-/// A structure to list and prune previously executed operations
-#[derive(Debug, Clone)]
+/// A structure to list and prune previously executed operations.
+///
+/// The operations are kept in memory, or on disk if `ExecutedOpsConfig::disk_path` is set.
+/// On disk, a bloom filter answers most lookups of unknown operations without disk reads.
+#[derive(Debug)]
 pub struct ExecutedOps {
     /// Executed operations configuration
     config: ExecutedOpsConfig,
-    /// Executed operations btreemap with slot as index for better pruning complexity
-    pub sorted_ops: BTreeMap<Slot, PreHashSet<OperationId>>,
-    /// Executed operations only for better insertion complexity
-    pub ops: PreHashSet<OperationId>,
+    /// Executed operations along their expiration slot
+    store: ExecutedOpsStore,
     /// Accumulated hash of the executed operations
     pub hash: Hash,
 }
@@ -45,69 +46,54 @@ impl ExecutedOps {
     /// Creates a new `ExecutedOps`
     pub fn new(config: ExecutedOpsConfig) -> Self {
         Self {
+            store: ExecutedOpsStore::new(&config),
             config,
-            sorted_ops: BTreeMap::new(),
-            ops: PreHashSet::default(),
             hash: Hash::from_bytes(EXECUTED_OPS_INITIAL_BYTES),
         }
     }
 
     /// Returns the number of executed operations
     pub fn len(&self) -> usize {
-        self.ops.len()
+        self.store.len()
     }
 
     /// Check executed ops emptiness
     pub fn is_empty(&self) -> bool {
-        self.ops.is_empty()
+        self.store.len() == 0
     }
 
-    /// Internal function used to insert the values of an operation id iter and update the object hash
-    fn extend_and_compute_hash<'a, I>(&mut self, values: I)
+    /// Internal function used to insert operations with their expiration slot and update the hash
+    fn extend_and_compute_hash<I>(&mut self, values: I)
     where
-        I: Iterator<Item = &'a OperationId>,
+        I: Iterator<Item = (OperationId, Slot)>,
     {
-        for op_id in values {
-            if self.ops.insert(*op_id) {
-                self.hash ^= *op_id.get_hash();
-            }
+        for op_id in self.store.insert(values) {
+            self.hash ^= *op_id.get_hash();
         }
     }
 
     /// Apply speculative operations changes to the final executed operations state
     pub fn apply_changes(&mut self, changes: ExecutedOpsChanges, slot: Slot) {
-        self.extend_and_compute_hash(changes.keys());
-        for (op_id, slot) in changes {
-            self.sorted_ops
-                .entry(slot)
-                .and_modify(|ids| {
-                    ids.insert(op_id);
-                })
-                .or_insert_with(|| {
-                    let mut new = PreHashSet::default();
-                    new.insert(op_id);
-                    new
-                });
-        }
+        self.extend_and_compute_hash(changes.into_iter());
         self.prune(slot);
     }
 
+    /// Removes every executed operation, before bootstrapping them again from scratch
+    pub fn reset(&mut self) {
+        self.store.clear();
+        self.hash = Hash::from_bytes(EXECUTED_OPS_INITIAL_BYTES);
+    }
+
     /// Check if an operation was executed
     pub fn contains(&self, op_id: &OperationId) -> bool {
-        self.ops.contains(op_id)
+        self.store.contains(op_id)
     }
 
     /// Prune all operations that expire strictly before `slot`
     fn prune(&mut self, slot: Slot) {
-        let kept = self.sorted_ops.split_off(&slot);
-        let removed = std::mem::take(&mut self.sorted_ops);
-        for (_, ids) in removed {
-            for op_id in ids {
-                self.ops.remove(&op_id);
-                self.hash ^= *op_id.get_hash();
-            }
+        for op_id in self.store.prune(slot) {
+            self.hash ^= *op_id.get_hash();
         }
-        self.sorted_ops = kept;
     }
 
     /// Get a part of the executed operations.
@@ -119,22 +105,15 @@ impl ExecutedOps {
         &self,
         cursor: StreamingStep<Slot>,
     ) -> (BTreeMap<Slot, PreHashSet<OperationId>>, StreamingStep<Slot>) {
-        let mut ops_part = BTreeMap::new();
         let left_bound = match cursor {
             StreamingStep::Started => Unbounded,
             StreamingStep::Ongoing(slot) => Excluded(slot),
-            StreamingStep::Finished => return (ops_part, cursor),
+            StreamingStep::Finished => return (BTreeMap::new(), cursor),
         };
-        let mut ops_part_last_slot: Option<Slot> = None;
-        for (slot, ids) in self.sorted_ops.range((left_bound, Unbounded)) {
-            if ops_part.len() < self.config.bootstrap_part_size as usize {
-                ops_part.insert(*slot, ids.clone());
-                ops_part_last_slot = Some(*slot);
-            } else {
-                break;
-            }
-        }
-        if let Some(last_slot) = ops_part_last_slot {
+        let ops_part = self
+            .store
+            .get_part(left_bound, self.config.bootstrap_part_size as usize);
+        if let Some(last_slot) = ops_part.last_key_value().map(|(slot, _)| *slot) {
             (ops_part, StreamingStep::Ongoing(last_slot))
         } else {
             (ops_part, StreamingStep::Finished)
@@ -151,10 +130,12 @@ impl ExecutedOps {
         &mut self,
         part: BTreeMap<Slot, PreHashSet<OperationId>>,
     ) -> StreamingStep<Slot> {
-        self.sorted_ops.extend(part.clone());
-        self.extend_and_compute_hash(part.iter().flat_map(|(_, ids)| ids));
-        if let Some(slot) = self.sorted_ops.last_key_value().map(|(slot, _)| slot) {
-            StreamingStep::Ongoing(*slot)
+        self.extend_and_compute_hash(
+            part.into_iter()
+                .flat_map(|(slot, ids)| ids.into_iter().map(move |op_id| (op_id, slot))),
+        );
+        if let Some(slot) = self.store.last_slot() {
+            StreamingStep::Ongoing(slot)
         } else {
             StreamingStep::Finished
         }
@@ -170,6 +151,8 @@ fn test_executed_ops_xor_computing() {
     let config = ExecutedOpsConfig {
         thread_count: 2,
         bootstrap_part_size: 10,
+        disk_path: None,
+        bloom_filter_size: 1000,
     };
 
     // initialize the executed ops and executed ops changes
@@ -220,6 +203,57 @@ fn test_executed_ops_xor_computing() {
     );
 }
 
+#[test]
+fn test_executed_ops_disk_store() {
+    use massa_models::prehash::PreHashMap;
+
+    // initialize an executed ops in memory and another one on disk
+    let disk_dir = tempfile::TempDir::new().expect("cannot create temp directory");
+    let memory_config = ExecutedOpsConfig {
+        thread_count: 2,
+        bootstrap_part_size: 3,
+        disk_path: None,
+        bloom_filter_size: 1000,
+    };
+    let disk_config = ExecutedOpsConfig {
+        disk_path: Some(disk_dir.path().to_path_buf()),
+        ..memory_config.clone()
+    };
+    let mut memory_ops = ExecutedOps::new(memory_config);
+    let mut disk_ops = ExecutedOps::new(disk_config);
+    let mut changes = PreHashMap::default();
+    for i in 0u8..20 {
+        let expiration_slot = Slot::new(i as u64 / 2, i % 2);
+        changes.insert(OperationId::new(Hash::compute_from(&[i])), expiration_slot);
+    }
+    memory_ops.apply_changes(changes.clone(), Slot::new(0, 0));
+    disk_ops.apply_changes(changes, Slot::new(0, 0));
+    assert_eq!(disk_ops.len(), 20);
+    assert_eq!(disk_ops.hash, memory_ops.hash);
+    assert!(disk_ops.contains(&OperationId::new(Hash::compute_from(&[3]))));
+    assert!(!disk_ops.contains(&OperationId::new(Hash::compute_from(&[20]))));
+
+    // both stores are streamed the same way
+    let mut memory_cursor = StreamingStep::Started;
+    let mut disk_cursor = StreamingStep::Started;
+    while !memory_cursor.finished() {
+        let (memory_part, new_memory_cursor) = memory_ops.get_executed_ops_part(memory_cursor);
+        let (disk_part, new_disk_cursor) = disk_ops.get_executed_ops_part(disk_cursor);
+        assert_eq!(memory_part, disk_part);
+        assert_eq!(new_memory_cursor, new_disk_cursor);
+        memory_cursor = new_memory_cursor;
+        disk_cursor = new_disk_cursor;
+    }
+
+    // prune the operations expiring before period 5
+    memory_ops.apply_changes(PreHashMap::default(), Slot::new(5, 0));
+    disk_ops.apply_changes(PreHashMap::default(), Slot::new(5, 0));
+    assert_eq!(disk_ops.len(), 10);
+    assert_eq!(disk_ops.hash, memory_ops.hash);
+    assert!(!disk_ops.contains(&OperationId::new(Hash::compute_from(&[3]))));
+    assert!(disk_ops.contains(&OperationId::new(Hash::compute_from(&[10]))));
+}
+
 /// `ExecutedOps` Serializer
 pub struct ExecutedOpsSerializer {
     slot_serializer: SlotSerializer,
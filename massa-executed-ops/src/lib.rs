@@ -1,8 +1,10 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+mod bloom;
 mod config;
 mod executed_ops;
 mod ops_changes;
+mod store;
 
 pub use config::*;
 pub use executed_ops::*;
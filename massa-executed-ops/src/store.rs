@@ -0,0 +1,322 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This file defines where the executed operations are stored:
+//! in memory, or on disk with a bloom filter in memory for fast negative checks.
+
+use crate::bloom::OpIdBloomFilter;
+use crate::ExecutedOpsConfig;
+use massa_models::{
+    operation::{OperationId, OPERATION_ID_SIZE_BYTES},
+    prehash::PreHashSet,
+    slot::{Slot, SLOT_KEY_SIZE},
+};
+use rocksdb::{ColumnFamilyDescriptor, Direction, IteratorMode, Options, WriteBatch, DB};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
+use std::path::Path;
+
+const OPS_CF: &str = "ops";
+const EXPIRATIONS_CF: &str = "expirations";
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const CF_ERROR: &str = "critical: rocksdb column family operation failed";
+
+/// Storage of the executed operations along their expiration slot
+#[derive(Debug)]
+pub(crate) enum ExecutedOpsStore {
+    /// operations kept in memory
+    Memory {
+        /// operations sorted by expiration slot for better pruning complexity
+        sorted_ops: BTreeMap<Slot, PreHashSet<OperationId>>,
+        /// operations only for better lookup complexity
+        ops: PreHashSet<OperationId>,
+    },
+    /// operations kept on disk
+    Disk(ExecutedOpsDB),
+}
+
+/// Disk storage of the executed operations.
+///
+/// The `ops` column family associates each operation to its expiration slot,
+/// and the `expirations` column family indexes them by expiration slot, in order, for pruning.
+pub(crate) struct ExecutedOpsDB {
+    db: DB,
+    /// filter of the stored operations, to skip the disk reads of most unknown operations
+    bloom_filter: OpIdBloomFilter,
+    /// number of stored operations
+    len: usize,
+}
+
+impl Debug for ExecutedOpsDB {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#?}", self.db)
+    }
+}
+
+/// Key of an operation in the `expirations` column family
+fn expiration_key(slot: &Slot, op_id: &OperationId) -> Vec<u8> {
+    [&slot.to_bytes_key()[..], op_id.to_bytes()].concat()
+}
+
+/// Reads a key of the `expirations` column family
+fn parse_expiration_key(key: &[u8]) -> (Slot, OperationId) {
+    let slot = Slot::from_bytes_key(
+        key[..SLOT_KEY_SIZE]
+            .try_into()
+            .expect("critical: invalid executed ops expiration key"),
+    );
+    let op_id = OperationId::from_bytes(
+        key[SLOT_KEY_SIZE..SLOT_KEY_SIZE + OPERATION_ID_SIZE_BYTES]
+            .try_into()
+            .expect("critical: invalid executed ops expiration key"),
+    );
+    (slot, op_id)
+}
+
+impl ExecutedOpsDB {
+    /// Creates an empty disk storage, replacing the one that may remain at `path`:
+    /// the executed operations are bootstrapped along the rest of the final state.
+    fn new(path: &Path, bloom_filter_size: usize) -> Self {
+        let _ = DB::destroy(&Options::default(), path);
+        let mut db_opts = Options::default();
+        db_opts.create_if_missing(true);
+        db_opts.create_missing_column_families(true);
+        let db = DB::open_cf_descriptors(
+            &db_opts,
+            path,
+            vec![
+                ColumnFamilyDescriptor::new(OPS_CF, Options::default()),
+                ColumnFamilyDescriptor::new(EXPIRATIONS_CF, Options::default()),
+            ],
+        )
+        .expect(OPEN_ERROR);
+        ExecutedOpsDB {
+            db,
+            bloom_filter: OpIdBloomFilter::new(bloom_filter_size),
+            len: 0,
+        }
+    }
+
+    fn contains(&self, op_id: &OperationId) -> bool {
+        if !self.bloom_filter.may_contain(op_id) {
+            return false;
+        }
+        let handle = self.db.cf_handle(OPS_CF).expect(CF_ERROR);
+        self.db
+            .get_pinned_cf(handle, op_id.to_bytes())
+            .expect(CRUD_ERROR)
+            .is_some()
+    }
+
+    fn insert(&mut self, ops: impl Iterator<Item = (OperationId, Slot)>) -> Vec<OperationId> {
+        let ops_handle = self.db.cf_handle(OPS_CF).expect(CF_ERROR);
+        let expirations_handle = self.db.cf_handle(EXPIRATIONS_CF).expect(CF_ERROR);
+        let mut batch = WriteBatch::default();
+        let mut inserted = Vec::new();
+        let mut inserted_set = PreHashSet::default();
+        for (op_id, slot) in ops {
+            if self.contains(&op_id) || !inserted_set.insert(op_id) {
+                continue;
+            }
+            batch.put_cf(ops_handle, op_id.to_bytes(), slot.to_bytes_key());
+            batch.put_cf(expirations_handle, expiration_key(&slot, &op_id), b"");
+            inserted.push(op_id);
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+        for op_id in inserted.iter() {
+            self.bloom_filter.insert(op_id);
+        }
+        self.len += inserted.len();
+        inserted
+    }
+
+    fn prune(&mut self, slot: Slot) -> Vec<OperationId> {
+        let ops_handle = self.db.cf_handle(OPS_CF).expect(CF_ERROR);
+        let expirations_handle = self.db.cf_handle(EXPIRATIONS_CF).expect(CF_ERROR);
+        let mut batch = WriteBatch::default();
+        let mut removed = Vec::new();
+        for (key, _) in self
+            .db
+            .iterator_cf(expirations_handle, IteratorMode::Start)
+            .flatten()
+        {
+            let (expiration_slot, op_id) = parse_expiration_key(&key);
+            if expiration_slot >= slot {
+                break;
+            }
+            batch.delete_cf(ops_handle, op_id.to_bytes());
+            batch.delete_cf(expirations_handle, key);
+            removed.push(op_id);
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+        for op_id in removed.iter() {
+            self.bloom_filter.remove(op_id);
+        }
+        self.len -= removed.len();
+        removed
+    }
+
+    fn clear(&mut self) {
+        let mut batch = WriteBatch::default();
+        for cf in [OPS_CF, EXPIRATIONS_CF] {
+            let handle = self.db.cf_handle(cf).expect(CF_ERROR);
+            for (key, _) in self.db.iterator_cf(handle, IteratorMode::Start).flatten() {
+                batch.delete_cf(handle, key);
+            }
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+        self.bloom_filter.clear();
+        self.len = 0;
+    }
+
+    fn get_part(
+        &self,
+        left_bound: Bound<Slot>,
+        max_slots: usize,
+    ) -> BTreeMap<Slot, PreHashSet<OperationId>> {
+        let handle = self.db.cf_handle(EXPIRATIONS_CF).expect(CF_ERROR);
+        let start_key = match left_bound {
+            Included(slot) | Excluded(slot) => slot.to_bytes_key().to_vec(),
+            Unbounded => Vec::new(),
+        };
+        let mut part: BTreeMap<Slot, PreHashSet<OperationId>> = BTreeMap::new();
+        for (key, _) in self
+            .db
+            .iterator_cf(
+                handle,
+                IteratorMode::From(&start_key[..], Direction::Forward),
+            )
+            .flatten()
+        {
+            let (slot, op_id) = parse_expiration_key(&key);
+            if left_bound == Excluded(slot) {
+                continue;
+            }
+            if !part.contains_key(&slot) && part.len() >= max_slots {
+                break;
+            }
+            part.entry(slot).or_default().insert(op_id);
+        }
+        part
+    }
+
+    fn last_slot(&self) -> Option<Slot> {
+        let handle = self.db.cf_handle(EXPIRATIONS_CF).expect(CF_ERROR);
+        self.db
+            .iterator_cf(handle, IteratorMode::End)
+            .flatten()
+            .next()
+            .map(|(key, _)| parse_expiration_key(&key).0)
+    }
+}
+
+impl ExecutedOpsStore {
+    /// Creates an empty storage, on disk if a path is configured
+    pub fn new(config: &ExecutedOpsConfig) -> Self {
+        match &config.disk_path {
+            Some(path) => {
+                ExecutedOpsStore::Disk(ExecutedOpsDB::new(path, config.bloom_filter_size))
+            }
+            None => ExecutedOpsStore::Memory {
+                sorted_ops: BTreeMap::new(),
+                ops: PreHashSet::default(),
+            },
+        }
+    }
+
+    /// Number of stored operations
+    pub fn len(&self) -> usize {
+        match self {
+            ExecutedOpsStore::Memory { ops, .. } => ops.len(),
+            ExecutedOpsStore::Disk(db) => db.len,
+        }
+    }
+
+    /// Checks if an operation is stored
+    pub fn contains(&self, op_id: &OperationId) -> bool {
+        match self {
+            ExecutedOpsStore::Memory { ops, .. } => ops.contains(op_id),
+            ExecutedOpsStore::Disk(db) => db.contains(op_id),
+        }
+    }
+
+    /// Stores operations along their expiration slot, skipping the already stored ones
+    ///
+    /// # Returns
+    /// The newly stored operations
+    pub fn insert(
+        &mut self,
+        new_ops: impl Iterator<Item = (OperationId, Slot)>,
+    ) -> Vec<OperationId> {
+        match self {
+            ExecutedOpsStore::Memory { sorted_ops, ops } => {
+                let mut inserted = Vec::new();
+                for (op_id, slot) in new_ops {
+                    if ops.insert(op_id) {
+                        sorted_ops.entry(slot).or_default().insert(op_id);
+                        inserted.push(op_id);
+                    }
+                }
+                inserted
+            }
+            ExecutedOpsStore::Disk(db) => db.insert(new_ops),
+        }
+    }
+
+    /// Removes the operations expiring strictly before `slot`
+    ///
+    /// # Returns
+    /// The removed operations
+    pub fn prune(&mut self, slot: Slot) -> Vec<OperationId> {
+        match self {
+            ExecutedOpsStore::Memory { sorted_ops, ops } => {
+                let kept = sorted_ops.split_off(&slot);
+                let removed = std::mem::replace(sorted_ops, kept);
+                removed
+                    .into_values()
+                    .flatten()
+                    .filter(|op_id| ops.remove(op_id))
+                    .collect()
+            }
+            ExecutedOpsStore::Disk(db) => db.prune(slot),
+        }
+    }
+
+    /// Removes every operation
+    pub fn clear(&mut self) {
+        match self {
+            ExecutedOpsStore::Memory { sorted_ops, ops } => {
+                sorted_ops.clear();
+                ops.clear();
+            }
+            ExecutedOpsStore::Disk(db) => db.clear(),
+        }
+    }
+
+    /// Gets the operations of at most `max_slots` expiration slots after `left_bound`
+    pub fn get_part(
+        &self,
+        left_bound: Bound<Slot>,
+        max_slots: usize,
+    ) -> BTreeMap<Slot, PreHashSet<OperationId>> {
+        match self {
+            ExecutedOpsStore::Memory { sorted_ops, .. } => sorted_ops
+                .range((left_bound, Unbounded))
+                .take(max_slots)
+                .map(|(slot, ids)| (*slot, ids.clone()))
+                .collect(),
+            ExecutedOpsStore::Disk(db) => db.get_part(left_bound, max_slots),
+        }
+    }
+
+    /// Latest expiration slot of the stored operations
+    pub fn last_slot(&self) -> Option<Slot> {
+        match self {
+            ExecutedOpsStore::Memory { sorted_ops, .. } => {
+                sorted_ops.last_key_value().map(|(slot, _)| *slot)
+            }
+            ExecutedOpsStore::Disk(db) => db.last_slot(),
+        }
+    }
+}
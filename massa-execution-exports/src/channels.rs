@@ -0,0 +1,16 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This file defines the channels broadcasting the outputs of the execution worker
+
+use massa_models::{api::OperationStatusNotification, output_event::SCOutputEvent};
+use tokio::sync::broadcast;
+
+/// Channels broadcasting the outputs of the execution worker to their subscribers.
+/// Candidate outputs are broadcast when executed, and again when they become final.
+#[derive(Clone)]
+pub struct ExecutionChannels {
+    /// broadcasts the execution status of the executed operations
+    pub operation_status_sender: broadcast::Sender<OperationStatusNotification>,
+    /// broadcasts the events emitted by smart contracts
+    pub sc_event_sender: broadcast::Sender<SCOutputEvent>,
+}
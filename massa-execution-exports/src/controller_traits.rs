@@ -4,19 +4,27 @@
 
 use crate::types::ReadOnlyExecutionRequest;
 use crate::ExecutionError;
-use crate::{ExecutionAddressInfo, ReadOnlyExecutionOutput};
+use crate::{
+    AddressInfoBounds, BlockSimulationOutput, ExecutionAddressInfo, ReadOnlyExecutionOutput,
+};
+use massa_ledger_exports::LedgerEntryProof;
 use massa_models::address::Address;
 use massa_models::amount::Amount;
-use massa_models::api::EventFilter;
+use massa_models::api::{
+    AddressActivity, ContractInfo, DeferredCreditInfo, EventFilter, OperationExecutionInfo,
+    SlotExecutionTrace,
+};
 use massa_models::block::BlockId;
-use massa_models::operation::OperationId;
+use massa_models::operation::{OperationId, WrappedOperation};
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::PreHashMap;
 use massa_models::prehash::PreHashSet;
 use massa_models::slot::Slot;
-use massa_models::stats::ExecutionStats;
+use massa_models::stats::{CycleStats, ExecutionStats, FeeRecommendation, NetworkActivityStats};
+use massa_models::streaming_step::StreamingStep;
 use massa_storage::Storage;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 
 /// interface that communicates with the execution worker thread
@@ -61,6 +69,92 @@ pub trait ExecutionController: Send + Sync {
         input: Vec<(Address, Vec<u8>)>,
     ) -> Vec<(Option<Vec<u8>>, Option<Vec<u8>>)>;
 
+    /// Get a page of the final and candidate datastore keys of an address starting with a given prefix
+    ///
+    /// # Arguments
+    /// * `cursor`: `Started` to get the first page, `Ongoing(last_key)` to get the keys following `last_key`
+    /// * `max_count`: maximum number of final keys to return
+    ///
+    /// # Return value
+    /// * `(final_keys, candidate_keys, next_cursor)`, `next_cursor` being `Finished` if there are no more keys
+    #[allow(clippy::type_complexity)]
+    fn get_final_and_candidate_datastore_keys(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: StreamingStep<Vec<u8>>,
+        max_count: usize,
+    ) -> (BTreeSet<Vec<u8>>, BTreeSet<Vec<u8>>, StreamingStep<Vec<u8>>);
+
+    /// Get a page of the final addresses holding a non-empty bytecode
+    ///
+    /// # Arguments
+    /// * `cursor`: `Started` to get the first page, `Ongoing(last_address)` to get the contracts following `last_address`
+    /// * `max_count`: maximum number of contracts to return
+    ///
+    /// # Return value
+    /// * `(contracts, next_cursor)`, `next_cursor` being `Finished` if there are no more contracts
+    fn get_final_contracts(
+        &self,
+        cursor: StreamingStep<Address>,
+        max_count: usize,
+    ) -> (Vec<ContractInfo>, StreamingStep<Address>);
+
+    /// Get a page of the deferred credits of all addresses scheduled in a slot range,
+    /// looking at the candidate executions and the final PoS state
+    ///
+    /// # Arguments
+    /// * `start`: first slot of the credits (included)
+    /// * `end`: slot bounding the credits (excluded)
+    /// * `cursor`: `Started` to get the first page, `Ongoing((slot, address))` to get the credits following that one
+    /// * `max_count`: maximum number of credits to return
+    ///
+    /// # Return value
+    /// * `(credits, next_cursor)`, credits being sorted by slot then address and `next_cursor` being `Finished` if there are no more credits
+    fn get_deferred_credits(
+        &self,
+        start: Slot,
+        end: Slot,
+        cursor: StreamingStep<(Slot, Address)>,
+        max_count: usize,
+    ) -> (Vec<DeferredCreditInfo>, StreamingStep<(Slot, Address)>);
+
+    /// Get a proof of inclusion of a final ledger entry, against the current root of the final ledger
+    ///
+    /// # Arguments
+    /// * `address`: address of the entry
+    /// * `datastore_key`: key of the datastore entry to prove, `None` to prove the balance
+    ///
+    /// # Return value
+    /// * the proof, `None` if the entry does not exist
+    fn get_final_ledger_proof(
+        &self,
+        address: &Address,
+        datastore_key: Option<&[u8]>,
+    ) -> Option<LedgerEntryProof>;
+
+    /// Get the outcomes of the execution of operations, candidate executions first
+    ///
+    /// # Return value
+    /// * for each operation, the outcome of its execution, `None` if it is unknown
+    fn get_op_execution_infos(&self, ops: &[OperationId]) -> Vec<Option<OperationExecutionInfo>>;
+
+    /// Get the activities of an address in a slot range, in chronological order
+    ///
+    /// # Arguments
+    /// * `address`: address to get the history of
+    /// * `start`: optional first slot of the range (included)
+    /// * `end`: optional slot bounding the range (excluded)
+    ///
+    /// # Return value
+    /// * the activities, missing the final ones that are too old to be remembered
+    fn get_address_history(
+        &self,
+        address: &Address,
+        start: Option<Slot>,
+        end: Option<Slot>,
+    ) -> Vec<AddressActivity>;
+
     /// Returns for a given cycle the stakers taken into account
     /// by the selector. That correspond to the `roll_counts` in `cycle - 3`.
     ///
@@ -80,6 +174,36 @@ pub trait ExecutionController: Send + Sync {
         req: ReadOnlyExecutionRequest,
     ) -> Result<ReadOnlyExecutionOutput, ExecutionError>;
 
+    /// Simulate the operations of a candidate block in an isolated speculative context,
+    /// on top of the latest candidate state, without affecting it.
+    /// Asynchronous messages are not executed.
+    ///
+    /// # arguments
+    /// * `slot`: slot of the candidate block
+    /// * `operations`: operations of the candidate block, in block order
+    ///
+    /// # returns
+    /// The outcome of each operation with the total gas and fees of the block,
+    /// or an error if the simulation could not be run.
+    fn simulate_block(
+        &self,
+        slot: Slot,
+        operations: Vec<WrappedOperation>,
+    ) -> Result<BlockSimulationOutput, ExecutionError>;
+
+    /// Debug tool: re-execute a slot of the active history in an isolated context,
+    /// recording every ABI call made by the executed bytecode and the gas used by each operation.
+    /// Neither the candidate nor the final state are affected.
+    /// Final slots can not be replayed as the state preceding them is not kept.
+    ///
+    /// # arguments
+    /// * `slot`: executed candidate slot to replay
+    ///
+    /// # returns
+    /// The trace of each asynchronous message and operation executed at the slot,
+    /// or an error if the slot is not in the active history.
+    fn replay_slot_with_trace(&self, slot: Slot) -> Result<SlotExecutionTrace, ExecutionError>;
+
     /// List which operations inside the provided list were not executed
     fn unexecuted_ops_among(
         &self,
@@ -87,12 +211,80 @@ pub trait ExecutionController: Send + Sync {
         thread: u8,
     ) -> PreHashSet<OperationId>;
 
-    /// Gets information about a batch of addresses
-    fn get_addresses_infos(&self, addresses: &[Address]) -> Vec<ExecutionAddressInfo>;
+    /// Resolves a name such as `name.massa` into an address,
+    /// through a read-only call to the `resolve` function of the configured name registry contract
+    ///
+    /// # returns
+    /// The address emitted by the registry as the data of its last event,
+    /// `None` if the name is not registered,
+    /// or an error if no registry is configured or if the call failed.
+    fn resolve_name(&self, name: &str) -> Result<Option<Address>, ExecutionError>;
+
+    /// Gets information about a batch of addresses,
+    /// their datastore keys and cycle infos being limited by `bounds`
+    fn get_addresses_infos(
+        &self,
+        addresses: &[Address],
+        bounds: &AddressInfoBounds,
+    ) -> Vec<ExecutionAddressInfo>;
 
     /// Get execution statistics
     fn get_stats(&self) -> ExecutionStats;
 
+    /// Get network-wide activity statistics (operations per second, fees, gas, events)
+    /// over the `window` latest final slots, capped to `max_network_stats_slots`
+    fn get_network_stats(&self, window: usize) -> NetworkActivityStats;
+
+    /// Get low, medium and high fee suggestions from the inclusion of operations
+    /// in the blocks of the `fee_recommendation_slots` latest final slots
+    ///
+    /// # Arguments
+    /// * `pool_operation_count`: number of operations waiting in the pool, used to measure its pressure
+    fn get_fee_recommendation(&self, pool_operation_count: usize) -> FeeRecommendation;
+
+    /// Get the proof-of-stake statistics of a cycle from the final state: roll distribution,
+    /// block production and stakers exceeding the max miss ratio.
+    /// The stakers are sorted by address, the returned page starting after `start_after` and holding at most `max_count` of them.
+    ///
+    /// # Returns
+    /// `None` if the cycle is not in the final cycle history
+    fn get_cycle_stats(
+        &self,
+        cycle: u64,
+        start_after: Option<Address>,
+        max_count: usize,
+    ) -> Option<CycleStats>;
+
+    /// Sandbox tool: moves the time seen by the execution forward by `slot_count` slots,
+    /// so that the candidate slots until then are executed right away.
+    /// Time-dependent logic (deferred credits, asynchronous messages) can be tested without waiting for the slots to happen.
+    #[cfg(feature = "sandbox")]
+    fn sandbox_fast_forward(&self, slot_count: u64);
+
+    /// Sandbox tool: executes a block as a candidate at an arbitrary slot, in place of the content of that slot.
+    /// The time seen by the execution is moved forward to reach the slot if needed,
+    /// and the candidate slots following it are executed again.
+    ///
+    /// # arguments
+    /// * `block_id`: block to execute
+    /// * `slot`: slot at which the block is executed, which must be after the latest final slot
+    /// * `block_storage`: storage instance owning refs to the block and its operations
+    ///
+    /// # returns
+    /// An error if the slot is final or if the block is absent from `block_storage`
+    #[cfg(feature = "sandbox")]
+    fn sandbox_execute_block_at_slot(
+        &self,
+        block_id: BlockId,
+        slot: Slot,
+        block_storage: Storage,
+    ) -> Result<(), ExecutionError>;
+
+    /// Sandbox tool: drops the candidate executions, the injected blocks and the time shifts,
+    /// so that the speculative state matches the final state again
+    #[cfg(feature = "sandbox")]
+    fn sandbox_reset_to_final(&self);
+
     /// Returns a boxed clone of self.
     /// Useful to allow cloning `Box<dyn ExecutionController>`.
     fn clone_box(&self) -> Box<dyn ExecutionController>;
@@ -113,4 +305,8 @@ pub trait ExecutionManager {
     /// because it is not allowed to move out of Box<dyn ExecutionManager>
     /// This will improve if the `unsized_fn_params` feature stabilizes enough to be safely usable.
     fn stop(&mut self);
+
+    /// Stop the execution thread once the final slots already notified are executed,
+    /// and after writing the final ledger to disk
+    fn stop_gracefully(&mut self);
 }
@@ -41,4 +41,19 @@ pub enum ExecutionError {
 
     /// Include operation error: {0}
     IncludeOperationError(String),
+
+    /// Call stack overflow: {0}
+    CallStackOverflow(String),
+
+    /// Gas cost table error: {0}
+    GasCostsError(String),
+
+    /// Name resolution error: {0}
+    NameResolutionError(String),
+
+    /// Event limit exceeded: {0}
+    EventLimitExceeded(String),
+
+    /// Async message inbox quota exceeded: {0}
+    AsyncInboxQuotaExceeded(String),
 }
@@ -0,0 +1,111 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This file defines the gas cost table used to reprice ABI calls and storage without code changes.
+//!
+//! The costs of the table are charged on top of the metering of massa-sc-runtime.
+//! Every node of the network has to use the same table: the hash of the table is attached
+//! to every execution output and reported in the node status so that mismatches can be spotted.
+//! The hash is only a diagnostic: it is not part of the blocks nor of the final state,
+//! so nodes never compare it and a node using another table is not rejected,
+//! it just computes other gas costs and may diverge from the network.
+
+use crate::ExecutionError;
+use massa_hash::Hash;
+use massa_models::amount::Amount;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Names of the ABIs that can be priced in the table, as passed to `abi_call!` by the execution worker
+pub const ABI_NAMES: &[&str] = &[
+    "print",
+    "init_call",
+    "finish_call",
+    "get_balance",
+    "get_balance_for",
+    "create_module",
+    "raw_get_data_for",
+    "raw_set_data_for",
+    "raw_append_data_for",
+    "raw_delete_data_for",
+    "has_data_for",
+    "raw_get_data",
+    "raw_set_data",
+    "raw_append_data",
+    "raw_delete_data",
+    "has_data",
+    "get_op_keys",
+    "has_op_key",
+    "get_op_data",
+    "hash",
+    "address_from_public_key",
+    "signature_verify",
+    "transfer_coins",
+    "transfer_coins_for",
+    "get_owned_addresses",
+    "get_call_stack",
+    "get_call_coins",
+    "generate_event",
+    "get_time",
+    "unsafe_random",
+    "unsafe_random_f64",
+    "send_message",
+    "get_current_period",
+    "get_current_thread",
+    "raw_set_bytecode",
+    "raw_set_bytecode_for",
+];
+
+/// Gas cost table, loaded from a JSON file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasCosts {
+    /// extra gas charged for every call of an ABI, by ABI name.
+    /// ABIs that are not listed only cost what massa-sc-runtime charges for them
+    #[serde(default)]
+    pub abi_costs: BTreeMap<String, u64>,
+    /// coins locked per byte of ledger storage, overriding the built-in cost if set
+    #[serde(default)]
+    pub ledger_cost_per_byte: Option<Amount>,
+}
+
+impl GasCosts {
+    /// Loads a gas cost table from a JSON file.
+    /// Fails if the table prices an ABI that is not in `ABI_NAMES`, as its cost would never be charged.
+    pub fn load(path: &Path) -> Result<Self, ExecutionError> {
+        let table = std::fs::read_to_string(path).map_err(|err| {
+            ExecutionError::GasCostsError(format!("error opening {}: {}", path.display(), err))
+        })?;
+        let gas_costs: GasCosts = serde_json::from_str(&table).map_err(|err| {
+            ExecutionError::GasCostsError(format!(
+                "error deserializing {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+        if let Some(name) = gas_costs
+            .abi_costs
+            .keys()
+            .find(|name| !ABI_NAMES.contains(&name.as_str()))
+        {
+            return Err(ExecutionError::GasCostsError(format!(
+                "unknown ABI {} in {}",
+                name,
+                path.display()
+            )));
+        }
+        Ok(gas_costs)
+    }
+
+    /// Extra gas charged for a call of an ABI
+    pub fn get_abi_cost(&self, abi_name: &str) -> u64 {
+        self.abi_costs.get(abi_name).copied().unwrap_or_default()
+    }
+
+    /// Hash of the table, computed on its canonical JSON form
+    /// (the ABI costs are sorted by name)
+    pub fn compute_hash(&self) -> Hash {
+        Hash::compute_from(
+            &serde_json::to_vec(self).expect("critical: gas cost table serialization failed"),
+        )
+    }
+}
@@ -22,6 +22,9 @@
 //!
 //! # Architecture
 //!
+//! ## `channels.rs`
+//! Defines the channels broadcasting the outputs of the execution worker.
+//!
 //! ## `config.rs`
 //! Contains configuration parameters for the execution system.
 //!
@@ -34,6 +37,9 @@
 //! ## `event_store.rs`
 //! Defines an indexed, finite-size storage system for execution events.
 //!
+//! ## `gas_costs.rs`
+//! Defines the gas cost table loaded from a file to reprice ABI calls and storage.
+//!
 //! ## `types.rs`
 //! Defines useful shared structures.
 //!
@@ -44,19 +50,24 @@
 
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
+mod channels;
 mod controller_traits;
 mod error;
 mod event_store;
+mod gas_costs;
 mod settings;
 mod types;
 
+pub use channels::ExecutionChannels;
 pub use controller_traits::{ExecutionController, ExecutionManager};
 pub use error::ExecutionError;
 pub use event_store::EventStore;
+pub use gas_costs::{GasCosts, ABI_NAMES};
 pub use settings::{ExecutionConfig, StorageCostsConstants};
 pub use types::{
+    AddressInfoBounds, BlockSimulationOutput, BlockSimulationRequest, EventUsage,
     ExecutionAddressInfo, ExecutionOutput, ExecutionStackElement, ReadOnlyCallRequest,
-    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget, SimulatedOperation,
 };
 
 #[cfg(feature = "testing")]
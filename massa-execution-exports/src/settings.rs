@@ -2,8 +2,10 @@
 
 //! This module provides the structures used to provide configuration parameters to the Execution system
 
-use massa_models::amount::Amount;
-use massa_time::MassaTime;
+use crate::GasCosts;
+use massa_hash::Hash;
+use massa_models::{address::Address, amount::Amount};
+use massa_time::{ClockCompensation, MassaTime};
 use num::rational::Ratio;
 
 /// Storage cost constants
@@ -22,20 +24,41 @@ pub struct StorageCostsConstants {
 pub struct ExecutionConfig {
     /// read-only execution request queue length
     pub readonly_queue_length: usize,
+    /// max number of read-only execution outputs cached until the next active slot execution, 0 to disable
+    pub readonly_cache_size: usize,
     /// maximum number of SC output events kept in cache
     pub max_final_events: usize,
+    /// maximum number of final operation execution outcomes kept in cache
+    pub max_final_op_executions: usize,
+    /// maximum number of final address activities kept in cache
+    pub max_final_address_activities: usize,
+    /// maximum number of latest final slots network activity statistics can be computed over
+    pub max_network_stats_slots: usize,
+    /// number of latest final slots analyzed to suggest fees, capped to `max_network_stats_slots`
+    pub fee_recommendation_slots: usize,
     /// maximum available gas for asynchronous messages execution
     pub max_async_gas: u64,
+    /// max number of pending asynchronous messages sent to the same destination address
+    pub max_async_messages_per_destination: u64,
+    /// max total gas of the pending asynchronous messages sent to the same destination address
+    pub max_async_gas_per_destination: u64,
     /// maximum gas per block
     pub max_gas_per_block: u64,
     /// number of threads
     pub thread_count: u8,
+    /// chain id of the network, signed along the operations
+    pub chain_id: u64,
+    /// first period from which the operations are signed along the chain id, see `compute_operation_signed_hash`
+    pub chain_id_activation_period: u64,
     /// price of a roll inside the network
     pub roll_price: Amount,
     /// extra lag to add on the execution cursor to improve performance
     pub cursor_delay: MassaTime,
-    /// time compensation in milliseconds
-    pub clock_compensation: i64,
+    /// max number of active history slots (starting from the oldest one) taken into account
+    /// when serving candidate values to API reads, unbounded if `None`
+    pub max_candidate_read_depth: Option<usize>,
+    /// time compensation, shared with the time health checks that may adjust it
+    pub clock_compensation: ClockCompensation,
     /// genesis timestamp
     pub genesis_timestamp: MassaTime,
     /// period duration
@@ -50,14 +73,42 @@ pub struct ExecutionConfig {
     pub periods_per_cycle: u64,
     /// duration of the statistics time window
     pub stats_time_window_duration: MassaTime,
+    /// log every saturation of the coin arithmetic of the credit paths
+    pub log_saturations: bool,
     /// Max miss ratio for auto roll sell
     pub max_miss_ratio: Ratio<u64>,
     /// Max size of a datastore key
     pub max_datastore_key_length: u8,
     /// Max bytecode size
     pub max_bytecode_size: u64,
+    /// Max number of elements in the call stack
+    pub max_call_stack_depth: usize,
+    /// Max number of events emitted by a smart contract call
+    pub max_event_count_per_call: u64,
+    /// Max total size of the events emitted by a smart contract call, in bytes
+    pub max_event_size_per_call: u64,
+    /// Max number of events emitted by the smart contracts executed at a slot
+    pub max_event_count_per_slot: u64,
+    /// Max total size of the events emitted by the smart contracts executed at a slot, in bytes
+    pub max_event_size_per_slot: u64,
     /// Max datastore value size
     pub max_datastore_value_size: u64,
     /// Storage cost constants
     pub storage_costs_constants: StorageCostsConstants,
+    /// gas cost table charged on top of the metering of the runtime
+    pub gas_costs: GasCosts,
+    /// hash of the gas cost table, computed once when the table is loaded
+    pub gas_costs_hash: Hash,
+    /// rent charged at the end of each cycle per byte of bytecode and datastore, disabled if `None`
+    pub storage_rent_per_byte_per_cycle: Option<Amount>,
+    /// light mode: blocks are not executed and read-only requests are rejected
+    pub light_mode: bool,
+    /// wall-clock budget of a slot execution, after which a warning is logged without changing its outcome.
+    /// 0 to disable
+    pub max_slot_execution_time: MassaTime,
+    /// address of the name registry contract resolving names such as `name.massa`,
+    /// name resolution is disabled if `None`
+    pub name_registry_address: Option<Address>,
+    /// max gas of the read-only calls resolving names through the registry
+    pub name_registry_max_gas: u64,
 }
@@ -2,9 +2,10 @@
 
 //! This file defines testing tools related to the configuration
 
-use crate::{ExecutionConfig, StorageCostsConstants};
+use crate::{ExecutionChannels, ExecutionConfig, GasCosts, StorageCostsConstants};
 use massa_models::config::*;
 use massa_time::MassaTime;
+use tokio::sync::broadcast;
 
 impl Default for ExecutionConfig {
     /// default configuration used for testing
@@ -21,11 +22,21 @@ impl Default for ExecutionConfig {
 
         Self {
             readonly_queue_length: 100,
+            readonly_cache_size: 0,
             max_final_events: 1000,
+            max_final_op_executions: 1000,
+            max_final_address_activities: 1000,
+            max_network_stats_slots: 1000,
+            fee_recommendation_slots: 320,
             max_async_gas: MAX_ASYNC_GAS,
+            max_async_messages_per_destination: MAX_ASYNC_MESSAGES_PER_DESTINATION,
+            max_async_gas_per_destination: MAX_ASYNC_GAS_PER_DESTINATION,
             thread_count: THREAD_COUNT,
+            chain_id: TESTNET_CHAIN_ID,
+            chain_id_activation_period: 0,
             roll_price: ROLL_PRICE,
             cursor_delay: MassaTime::from_millis(0),
+            max_candidate_read_depth: None,
             block_reward: BLOCK_REWARD,
             endorsement_count: ENDORSEMENT_COUNT as u64,
             max_gas_per_block: MAX_GAS_PER_BLOCK,
@@ -37,11 +48,34 @@ impl Default for ExecutionConfig {
                 .expect("Impossible to reset the timestamp in test"),
             t0: 64.into(),
             stats_time_window_duration: MassaTime::from_millis(30000),
+            log_saturations: true,
             max_miss_ratio: *POS_MISS_RATE_DEACTIVATION_THRESHOLD,
             max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
             max_bytecode_size: MAX_BYTECODE_LENGTH,
+            max_call_stack_depth: MAX_CALL_STACK_DEPTH,
+            max_event_count_per_call: MAX_EVENT_COUNT_PER_CALL,
+            max_event_size_per_call: MAX_EVENT_SIZE_PER_CALL,
+            max_event_count_per_slot: MAX_EVENT_COUNT_PER_SLOT,
+            max_event_size_per_slot: MAX_EVENT_SIZE_PER_SLOT,
             max_datastore_value_size: MAX_DATASTORE_VALUE_LENGTH,
             storage_costs_constants,
+            gas_costs: Default::default(),
+            gas_costs_hash: GasCosts::default().compute_hash(),
+            storage_rent_per_byte_per_cycle: STORAGE_RENT_PER_BYTE_PER_CYCLE,
+            light_mode: false,
+            max_slot_execution_time: MassaTime::from_millis(0),
+            name_registry_address: None,
+            name_registry_max_gas: 10_000_000,
+        }
+    }
+}
+
+impl Default for ExecutionChannels {
+    /// channels without subscribers used for testing
+    fn default() -> Self {
+        Self {
+            operation_status_sender: broadcast::channel(BROADCAST_CHANNEL_SIZE).0,
+            sc_event_sender: broadcast::channel(BROADCAST_CHANNEL_SIZE).0,
         }
     }
 }
@@ -3,26 +3,31 @@
 //! This file defines utilities to mock the crate for testing purposes
 
 use crate::{
-    ExecutionAddressInfo, ExecutionController, ExecutionError, ReadOnlyExecutionOutput,
-    ReadOnlyExecutionRequest,
+    AddressInfoBounds, BlockSimulationOutput, ExecutionAddressInfo, ExecutionController,
+    ExecutionError, GasCosts, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    SimulatedOperation,
 };
-use massa_ledger_exports::LedgerEntry;
+use massa_ledger_exports::{LedgerEntry, LedgerEntryProof};
 use massa_models::{
     address::Address,
     amount::Amount,
-    api::EventFilter,
+    api::{
+        AddressActivity, ContractInfo, DeferredCreditInfo, EventFilter, OperationExecutionInfo,
+        SlotExecutionTrace,
+    },
     block::BlockId,
-    operation::OperationId,
+    operation::{OperationId, WrappedOperation},
     output_event::SCOutputEvent,
     prehash::{PreHashMap, PreHashSet},
     slot::Slot,
-    stats::ExecutionStats,
+    stats::{CycleStats, ExecutionStats, FeeRecommendation, NetworkActivityStats},
+    streaming_step::StreamingStep,
 };
 use massa_storage::Storage;
 use massa_time::MassaTime;
 use parking_lot::Mutex;
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     sync::{
         mpsc::{self, Receiver},
         Arc,
@@ -121,9 +126,53 @@ impl ExecutionController for MockExecutionController {
             final_block_count: 0,
             final_executed_operations_count: 0,
             active_cursor: Slot::new(0, 0),
+            miss_ratio_warnings: Vec::new(),
+            gas_costs_hash: GasCosts::default().compute_hash(),
+            readonly_cache_hits: 0,
+            readonly_cache_misses: 0,
+        }
+    }
+
+    fn get_network_stats(&self, _window: usize) -> NetworkActivityStats {
+        NetworkActivityStats {
+            window_start: None,
+            window_end: None,
+            slot_count: 0,
+            block_count: 0,
+            operation_count: 0,
+            operations_per_second: 0.0,
+            average_fee: Amount::zero(),
+            fee_p50: Amount::zero(),
+            fee_p90: Amount::zero(),
+            fee_p99: Amount::zero(),
+            average_gas_per_block: 0,
+            gas_utilization: 0.0,
+            event_count: 0,
+        }
+    }
+
+    fn get_fee_recommendation(&self, pool_operation_count: usize) -> FeeRecommendation {
+        FeeRecommendation {
+            block_count: 0,
+            block_fill_rate: 0.0,
+            min_fee_per_gas: Amount::zero(),
+            pool_operation_count: pool_operation_count as u64,
+            pool_pressure: pool_operation_count as f64,
+            low: Amount::zero(),
+            medium: Amount::zero(),
+            high: Amount::zero(),
         }
     }
 
+    fn get_cycle_stats(
+        &self,
+        _cycle: u64,
+        _start_after: Option<Address>,
+        _max_count: usize,
+    ) -> Option<CycleStats> {
+        None
+    }
+
     fn update_blockclique_status(
         &self,
         finalized_blocks: HashMap<Slot, BlockId>,
@@ -177,7 +226,60 @@ impl ExecutionController for MockExecutionController {
         Vec::default()
     }
 
-    fn get_addresses_infos(&self, _addresses: &[Address]) -> Vec<ExecutionAddressInfo> {
+    fn get_final_and_candidate_datastore_keys(
+        &self,
+        _addr: &Address,
+        _prefix: &[u8],
+        _cursor: StreamingStep<Vec<u8>>,
+        _max_count: usize,
+    ) -> (BTreeSet<Vec<u8>>, BTreeSet<Vec<u8>>, StreamingStep<Vec<u8>>) {
+        (BTreeSet::new(), BTreeSet::new(), StreamingStep::Finished)
+    }
+
+    fn get_final_contracts(
+        &self,
+        _cursor: StreamingStep<Address>,
+        _max_count: usize,
+    ) -> (Vec<ContractInfo>, StreamingStep<Address>) {
+        (Vec::new(), StreamingStep::Finished)
+    }
+
+    fn get_deferred_credits(
+        &self,
+        _start: Slot,
+        _end: Slot,
+        _cursor: StreamingStep<(Slot, Address)>,
+        _max_count: usize,
+    ) -> (Vec<DeferredCreditInfo>, StreamingStep<(Slot, Address)>) {
+        (Vec::new(), StreamingStep::Finished)
+    }
+
+    fn get_final_ledger_proof(
+        &self,
+        _address: &Address,
+        _datastore_key: Option<&[u8]>,
+    ) -> Option<LedgerEntryProof> {
+        None
+    }
+
+    fn get_op_execution_infos(&self, ops: &[OperationId]) -> Vec<Option<OperationExecutionInfo>> {
+        vec![None; ops.len()]
+    }
+
+    fn get_address_history(
+        &self,
+        _address: &Address,
+        _start: Option<Slot>,
+        _end: Option<Slot>,
+    ) -> Vec<AddressActivity> {
+        Vec::new()
+    }
+
+    fn get_addresses_infos(
+        &self,
+        _addresses: &[Address],
+        _bounds: &AddressInfoBounds,
+    ) -> Vec<ExecutionAddressInfo> {
         Vec::default()
     }
 
@@ -197,6 +299,42 @@ impl ExecutionController for MockExecutionController {
         response_rx.recv().unwrap()
     }
 
+    /// Every operation succeeds, consuming its max gas and paying its fee
+    fn simulate_block(
+        &self,
+        _slot: Slot,
+        operations: Vec<WrappedOperation>,
+    ) -> Result<BlockSimulationOutput, ExecutionError> {
+        Ok(BlockSimulationOutput {
+            total_gas: operations.iter().map(|op| op.get_gas_usage()).sum(),
+            total_fees: operations.iter().fold(Amount::default(), |fees, op| {
+                fees.saturating_add(op.get_total_fee())
+            }),
+            operations: operations
+                .iter()
+                .map(|op| SimulatedOperation {
+                    operation_id: op.id,
+                    success: true,
+                    gas_used: op.get_gas_usage(),
+                    error: None,
+                })
+                .collect(),
+        })
+    }
+
+    /// Every slot is replayed as a miss without asynchronous messages
+    fn replay_slot_with_trace(&self, slot: Slot) -> Result<SlotExecutionTrace, ExecutionError> {
+        Ok(SlotExecutionTrace {
+            slot,
+            block_id: None,
+            steps: Vec::new(),
+        })
+    }
+
+    fn resolve_name(&self, _name: &str) -> Result<Option<Address>, ExecutionError> {
+        Ok(None)
+    }
+
     fn unexecuted_ops_among(
         &self,
         ops: &PreHashSet<OperationId>,
@@ -219,6 +357,22 @@ impl ExecutionController for MockExecutionController {
             .unwrap()
     }
 
+    #[cfg(feature = "sandbox")]
+    fn sandbox_fast_forward(&self, _slot_count: u64) {}
+
+    #[cfg(feature = "sandbox")]
+    fn sandbox_execute_block_at_slot(
+        &self,
+        _block_id: BlockId,
+        _slot: Slot,
+        _block_storage: Storage,
+    ) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+
+    #[cfg(feature = "sandbox")]
+    fn sandbox_reset_to_final(&self) {}
+
     fn clone_box(&self) -> Box<dyn ExecutionController> {
         Box::new(self.clone())
     }
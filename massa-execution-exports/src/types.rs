@@ -4,10 +4,18 @@
 
 use crate::event_store::EventStore;
 use massa_final_state::StateChanges;
+use massa_hash::Hash;
+use massa_models::api::OperationStatusNotification;
 use massa_models::datastore::Datastore;
+use massa_models::execution::DeploymentSimulation;
 use massa_models::{
-    address::Address, address::ExecutionAddressCycleInfo, amount::Amount, block::BlockId,
+    address::Address,
+    address::ExecutionAddressCycleInfo,
+    amount::Amount,
+    block::BlockId,
+    operation::{OperationId, WrappedOperation},
     slot::Slot,
+    streaming_step::StreamingStep,
 };
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -28,6 +36,8 @@ pub struct ExecutionAddressInfo {
     pub candidate_roll_count: u64,
     /// candidate datastore keys of the address
     pub candidate_datastore_keys: BTreeSet<Vec<u8>>,
+    /// cursor to query the next datastore keys with, `Finished` if there are no more keys
+    pub next_datastore_keys_cursor: StreamingStep<Vec<u8>>,
 
     /// future deferred credits
     pub future_deferred_credits: BTreeMap<Slot, Amount>,
@@ -36,6 +46,28 @@ pub struct ExecutionAddressInfo {
     pub cycle_infos: Vec<ExecutionAddressCycleInfo>,
 }
 
+/// Bounds of the information gathered about each address by `ExecutionController::get_addresses_infos`
+#[derive(Clone, Debug)]
+pub struct AddressInfoBounds {
+    /// `Started` to get the first datastore keys, `Ongoing(last_key)` to get the ones following `last_key`
+    pub datastore_keys_cursor: StreamingStep<Vec<u8>>,
+    /// maximum number of final datastore keys, the candidate keys being limited to the same key range
+    pub max_datastore_keys: usize,
+    /// maximum number of cycle infos, the latest cycles being kept
+    pub max_cycle_infos: usize,
+}
+
+impl Default for AddressInfoBounds {
+    /// no bound: all the datastore keys and cycle infos
+    fn default() -> Self {
+        AddressInfoBounds {
+            datastore_keys_cursor: StreamingStep::Started,
+            max_datastore_keys: usize::MAX,
+            max_cycle_infos: usize::MAX,
+        }
+    }
+}
+
 /// structure describing the output of a single execution
 #[derive(Debug, Clone)]
 pub struct ExecutionOutput {
@@ -47,6 +79,12 @@ pub struct ExecutionOutput {
     pub state_changes: StateChanges,
     /// events emitted by the execution step
     pub events: EventStore,
+    /// execution status of the operations of the executed block
+    pub operation_statuses: Vec<OperationStatusNotification>,
+    /// activities of the addresses involved in the execution step, in execution order
+    pub address_activities: Vec<AddressActivity>,
+    /// hash of the gas cost table the execution step was metered with
+    pub gas_costs_hash: Hash,
 }
 
 /// structure describing the output of a read only execution
@@ -56,6 +94,21 @@ pub struct ReadOnlyExecutionOutput {
     pub out: ExecutionOutput,
     /// Gas cost for this execution
     pub gas_cost: u64,
+    /// smart contract addresses created by the execution, in creation order
+    pub created_addresses: Vec<Address>,
+    /// storage costs of the created addresses, as paid by their creator
+    pub storage_cost: Amount,
+    /// events emitted by the smart contracts of the execution, counted against the event limits
+    pub event_usage: EventUsage,
+}
+
+/// Events emitted by smart contracts, counted against the event limits of the execution config
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventUsage {
+    /// number of emitted events
+    pub count: u64,
+    /// total size of the data of the emitted events, in bytes
+    pub size: u64,
 }
 
 /// structure describing different types of read-only execution request
@@ -69,6 +122,44 @@ pub struct ReadOnlyExecutionRequest {
     pub call_stack: Vec<ExecutionStackElement>,
     /// Target of the request
     pub target: ReadOnlyExecutionTarget,
+    /// id of the API request that triggered the execution, for tracing
+    pub request_id: Option<u64>,
+    /// simulation of a deployment, giving the created smart contracts the addresses of an active execution
+    pub deployment_simulation: Option<DeploymentSimulation>,
+}
+
+/// structure describing the simulation of the operations of a candidate block
+#[derive(Debug, Clone)]
+pub struct BlockSimulationRequest {
+    /// slot of the candidate block
+    pub slot: Slot,
+    /// operations of the candidate block, in block order
+    pub operations: Vec<WrappedOperation>,
+}
+
+/// outcome of the simulated execution of an operation of a candidate block
+#[derive(Debug, Clone)]
+pub struct SimulatedOperation {
+    /// operation ID
+    pub operation_id: OperationId,
+    /// true if the operation could be included and its execution succeeded
+    pub success: bool,
+    /// gas consumed by the operation: its whole max gas if its execution failed,
+    /// zero if it could not be included
+    pub gas_used: u64,
+    /// reason why the operation could not be included or why its execution failed
+    pub error: Option<String>,
+}
+
+/// structure describing the output of the simulation of a candidate block
+#[derive(Debug, Clone)]
+pub struct BlockSimulationOutput {
+    /// outcome of each operation, in block order
+    pub operations: Vec<SimulatedOperation>,
+    /// total block gas consumed by the included operations
+    pub total_gas: u64,
+    /// total fees paid by the included operations
+    pub total_fees: Amount,
 }
 
 /// structure describing different possible targets of a read-only execution request
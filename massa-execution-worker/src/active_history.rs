@@ -42,6 +42,14 @@ impl ActiveHistory {
         }
     }
 
+    /// Iterate over the `max_depth` oldest elements of history, or over the whole history if `max_depth` is `None`.
+    pub fn outputs_up_to(
+        &self,
+        max_depth: Option<usize>,
+    ) -> impl DoubleEndedIterator<Item = &ExecutionOutput> {
+        self.0.iter().take(max_depth.unwrap_or(usize::MAX))
+    }
+
     /// Lazily query (from end to beginning) the active list of executed ops to check if an op was executed.
     ///
     /// Returns a `HistorySearchResult`.
@@ -62,7 +70,16 @@ impl ActiveHistory {
     ///
     /// Returns a `HistorySearchResult`.
     pub fn fetch_balance(&self, addr: &Address) -> HistorySearchResult<Amount> {
-        for output in self.0.iter().rev() {
+        self.fetch_balance_up_to(addr, None)
+    }
+
+    /// Same as `fetch_balance` but only considers the `max_depth` oldest elements of history.
+    pub fn fetch_balance_up_to(
+        &self,
+        addr: &Address,
+        max_depth: Option<usize>,
+    ) -> HistorySearchResult<Amount> {
+        for output in self.outputs_up_to(max_depth).rev() {
             match output.state_changes.ledger_changes.0.get(addr) {
                 Some(SetUpdateOrDelete::Set(v)) => return HistorySearchResult::Present(v.balance),
                 Some(SetUpdateOrDelete::Update(LedgerEntryUpdate {
@@ -96,6 +113,26 @@ impl ActiveHistory {
         HistorySearchResult::NoInfo
     }
 
+    /// Lazily query (from end to beginning) the size of the active bytecode of an address, without copying it.
+    ///
+    /// Returns a `HistorySearchResult`.
+    pub fn fetch_bytecode_size(&self, addr: &Address) -> HistorySearchResult<u64> {
+        for output in self.0.iter().rev() {
+            match output.state_changes.ledger_changes.0.get(addr) {
+                Some(SetUpdateOrDelete::Set(v)) => {
+                    return HistorySearchResult::Present(v.bytecode.len() as u64)
+                }
+                Some(SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                    bytecode: SetOrKeep::Set(v),
+                    ..
+                })) => return HistorySearchResult::Present(v.len() as u64),
+                Some(SetUpdateOrDelete::Delete) => return HistorySearchResult::Absent,
+                _ => (),
+            }
+        }
+        HistorySearchResult::NoInfo
+    }
+
     /// Lazily query (from end to beginning) the active datastore entry of an address after a given index.
     ///
     /// Returns a `HistorySearchResult`.
@@ -104,7 +141,17 @@ impl ActiveHistory {
         addr: &Address,
         key: &[u8],
     ) -> HistorySearchResult<Vec<u8>> {
-        for output in self.0.iter().rev() {
+        self.fetch_active_history_data_entry_up_to(addr, key, None)
+    }
+
+    /// Same as `fetch_active_history_data_entry` but only considers the `max_depth` oldest elements of history.
+    pub fn fetch_active_history_data_entry_up_to(
+        &self,
+        addr: &Address,
+        key: &[u8],
+        max_depth: Option<usize>,
+    ) -> HistorySearchResult<Vec<u8>> {
+        for output in self.outputs_up_to(max_depth).rev() {
             match output.state_changes.ledger_changes.0.get(addr) {
                 Some(SetUpdateOrDelete::Set(LedgerEntry { datastore, .. })) => {
                     match datastore.get(key) {
@@ -133,7 +180,12 @@ impl ActiveHistory {
     /// # Arguments
     /// * `addr`: address to fetch the rolls from
     pub fn fetch_roll_count(&self, addr: &Address) -> Option<u64> {
-        self.0.iter().rev().find_map(|output| {
+        self.fetch_roll_count_up_to(addr, None)
+    }
+
+    /// Same as `fetch_roll_count` but only considers the `max_depth` oldest elements of history.
+    pub fn fetch_roll_count_up_to(&self, addr: &Address, max_depth: Option<usize>) -> Option<u64> {
+        self.outputs_up_to(max_depth).rev().find_map(|output| {
             output
                 .state_changes
                 .pos_changes
@@ -7,6 +7,7 @@
 //! More generally, the context acts only on its own state
 //! and does not write anything persistent to the consensus state.
 
+use crate::saturation;
 use crate::speculative_async_pool::SpeculativeAsyncPool;
 use crate::speculative_executed_ops::SpeculativeExecutedOps;
 use crate::speculative_ledger::SpeculativeLedger;
@@ -14,7 +15,8 @@ use crate::{active_history::ActiveHistory, speculative_roll_state::SpeculativeRo
 use massa_async_pool::{AsyncMessage, AsyncMessageId};
 use massa_executed_ops::ExecutedOpsChanges;
 use massa_execution_exports::{
-    EventStore, ExecutionConfig, ExecutionError, ExecutionOutput, ExecutionStackElement,
+    EventStore, EventUsage, ExecutionConfig, ExecutionError, ExecutionOutput,
+    ExecutionStackElement,
 };
 use massa_final_state::{FinalState, StateChanges};
 use massa_ledger_exports::LedgerChanges;
@@ -22,7 +24,12 @@ use massa_models::address::ExecutionAddressCycleInfo;
 use massa_models::{
     address::Address,
     amount::Amount,
+    api::{
+        AbiCallTrace, AddressActivity, AddressActivityKind, ExecutionTraceOrigin,
+        ExecutionTraceStep,
+    },
     block::BlockId,
+    execution::DeploymentSimulation,
     operation::OperationId,
     output_event::{EventExecutionContext, SCOutputEvent},
     slot::Slot,
@@ -33,6 +40,7 @@ use rand::SeedableRng;
 use rand_xoshiro::Xoshiro256PlusPlus;
 use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::Instant;
 use tracing::debug;
 
 /// A snapshot taken from an `ExecutionContext` and that represents its current state.
@@ -62,10 +70,26 @@ pub(crate) struct ExecutionContextSnapshot {
     /// generated events during this execution, with multiple indexes
     pub events: EventStore,
 
+    /// events emitted by the smart contracts of the current call
+    pub call_event_usage: EventUsage,
+
+    /// events emitted by the smart contracts of the slot
+    pub slot_event_usage: EventUsage,
+
     /// Unsafe random state
     pub unsafe_rng: Xoshiro256PlusPlus,
 }
 
+/// ABI calls and execution steps recorded while a slot is replayed in trace mode
+#[derive(Default)]
+pub(crate) struct SlotTraceRecorder {
+    /// ABI calls of the step being executed
+    pub abi_calls: Vec<AbiCallTrace>,
+
+    /// finished steps, in execution order
+    pub steps: Vec<ExecutionTraceStep>,
+}
+
 /// An execution context that needs to be initialized before executing bytecode,
 /// passed to the VM to interact with during bytecode execution (through ABIs),
 /// and read after execution to gather results.
@@ -91,6 +115,9 @@ pub(crate) struct ExecutionContext {
     /// max gas for this execution
     pub max_gas: u64,
 
+    /// extra gas charged so far for the ABI calls of this execution, following the gas cost table
+    pub abi_gas_used: u64,
+
     /// gas price of the execution
     pub gas_price: Amount,
 
@@ -115,9 +142,18 @@ pub(crate) struct ExecutionContext {
     /// True if it's a read-only context
     pub read_only: bool,
 
+    /// deployment simulated by a read-only context, whose created addresses are the ones of an active execution
+    pub deployment_simulation: Option<DeploymentSimulation>,
+
     /// generated events during this execution, with multiple indexes
     pub events: EventStore,
 
+    /// events emitted by the smart contracts of the current call, counted against the per-call limits
+    pub call_event_usage: EventUsage,
+
+    /// events emitted by the smart contracts of the slot, counted against the per-slot limits
+    pub slot_event_usage: EventUsage,
+
     /// Unsafe random state (can be predicted and manipulated)
     pub unsafe_rng: Xoshiro256PlusPlus,
 
@@ -126,6 +162,19 @@ pub(crate) struct ExecutionContext {
 
     /// operation id that originally caused this execution (if any)
     pub origin_operation_id: Option<OperationId>,
+
+    /// trace of the ABI calls and execution steps, only recorded when a slot is replayed in trace mode
+    pub trace: Option<SlotTraceRecorder>,
+
+    /// instant after which the slot execution is reported as too slow, set for active slots with a time budget
+    pub execution_deadline: Option<Instant>,
+
+    /// true once an ABI call was made after the time budget of the slot execution
+    pub execution_timed_out: bool,
+
+    /// true once the time or the unsafe random generator was read,
+    /// in which case the output of a read-only execution is not cached
+    pub time_or_randomness_read: bool,
 }
 
 impl ExecutionContext {
@@ -156,6 +205,8 @@ impl ExecutionContext {
             speculative_async_pool: SpeculativeAsyncPool::new(
                 final_state.clone(),
                 active_history.clone(),
+                config.max_async_messages_per_destination,
+                config.max_async_gas_per_destination,
             ),
             speculative_roll_state: SpeculativeRollState::new(
                 final_state.clone(),
@@ -163,6 +214,7 @@ impl ExecutionContext {
             ),
             speculative_executed_ops: SpeculativeExecutedOps::new(final_state, active_history),
             max_gas: Default::default(),
+            abi_gas_used: Default::default(),
             gas_price: Default::default(),
             slot: Slot::new(0, 0),
             created_addr_index: Default::default(),
@@ -171,10 +223,17 @@ impl ExecutionContext {
             opt_block_id: Default::default(),
             stack: Default::default(),
             read_only: Default::default(),
+            deployment_simulation: None,
             events: Default::default(),
+            call_event_usage: Default::default(),
+            slot_event_usage: Default::default(),
             unsafe_rng: Xoshiro256PlusPlus::from_seed([0u8; 32]),
             creator_address: Default::default(),
             origin_operation_id: Default::default(),
+            trace: Default::default(),
+            execution_deadline: None,
+            execution_timed_out: false,
+            time_or_randomness_read: false,
             config,
         }
     }
@@ -191,6 +250,8 @@ impl ExecutionContext {
             created_event_index: self.created_event_index,
             stack: self.stack.clone(),
             events: self.events.clone(),
+            call_event_usage: self.call_event_usage,
+            slot_event_usage: self.slot_event_usage,
             unsafe_rng: self.unsafe_rng.clone(),
         }
     }
@@ -227,31 +288,41 @@ impl ExecutionContext {
         self.created_event_index = snapshot.created_event_index;
         self.stack = snapshot.stack;
         self.events = snapshot.events;
+        self.call_event_usage = snapshot.call_event_usage;
+        self.slot_event_usage = snapshot.slot_event_usage;
         self.unsafe_rng = snapshot.unsafe_rng;
 
         // If there was an error, emit the corresponding event now.
-        // Note that the context event counter is properly handled by event_emit (see doc).
+        // Note that the context event counter is properly handled by node_event_emit (see doc).
         if let Some(event) = err_event {
-            self.event_emit(event);
+            self.node_event_emit(event);
         }
     }
 
+    /// Resets the events counted against the per-call limits, to be done before starting a new execution in the context
+    pub fn reset_call_event_usage(&mut self) {
+        self.call_event_usage = EventUsage::default();
+    }
+
     /// Create a new `ExecutionContext` for read-only execution
     /// This should be used before performing a read-only execution.
     ///
     /// # arguments
     /// * `slot`: slot at which the execution will happen
     /// * `req`: parameters of the read only execution
+    /// * `deployment_simulation`: optional deployment simulated by the execution
     /// * `final_state`: thread-safe access to the final state. Note that this will be used only for reading, never for writing
     ///
     /// # returns
     /// A `ExecutionContext` instance ready for a read-only execution
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn readonly(
         config: ExecutionConfig,
         slot: Slot,
         max_gas: u64,
         gas_price: Amount,
         call_stack: Vec<ExecutionStackElement>,
+        deployment_simulation: Option<DeploymentSimulation>,
         final_state: Arc<RwLock<FinalState>>,
         active_history: Arc<RwLock<ActiveHistory>>,
     ) -> Self {
@@ -277,6 +348,9 @@ impl ExecutionContext {
             slot,
             stack: call_stack,
             read_only: true,
+            deployment_simulation,
+            created_addr_index: deployment_simulation
+                .map_or(0, |simulation| simulation.created_addr_index),
             unsafe_rng,
             ..ExecutionContext::new(config, final_state, active_history)
         }
@@ -334,15 +408,90 @@ impl ExecutionContext {
         let seed = massa_hash::Hash::compute_from(&seed).into_bytes();
         let unsafe_rng = Xoshiro256PlusPlus::from_seed(seed);
 
+        // the time budget of the slot starts now
+        let execution_deadline = (config.max_slot_execution_time.to_millis() > 0)
+            .then(|| Instant::now() + config.max_slot_execution_time.to_duration());
+
         // return active slot execution context
         ExecutionContext {
             slot,
+            execution_deadline,
             opt_block_id,
             unsafe_rng,
             ..ExecutionContext::new(config, final_state, active_history)
         }
     }
 
+    /// Closes the current step of a slot replayed in trace mode, attaching to it the ABI calls made since the previous step.
+    /// Does nothing outside of trace mode.
+    ///
+    /// # Arguments
+    /// * `origin`: message or operation executed during the step
+    /// * `gas`: gas used by the operation and block gas left after it, `None` for asynchronous messages
+    /// * `error`: error that made the step fail, if any
+    pub fn trace_step(
+        &mut self,
+        origin: ExecutionTraceOrigin,
+        gas: Option<(u64, u64)>,
+        error: Option<String>,
+    ) {
+        if let Some(trace) = self.trace.as_mut() {
+            let abi_calls = std::mem::take(&mut trace.abi_calls);
+            trace.steps.push(ExecutionTraceStep {
+                origin,
+                abi_calls,
+                gas_used: gas.map(|(gas_used, _)| gas_used),
+                remaining_block_gas: gas.map(|(_, remaining_block_gas)| remaining_block_gas),
+                error,
+            });
+        }
+    }
+
+    /// Watchdog of the slot execution, called at every ABI call:
+    /// records that the slot execution exceeded its time budget, to report it once the slot is executed.
+    /// The execution itself goes on unchanged, as its outcome must not depend on the speed of the node.
+    pub fn check_execution_deadline(&mut self) {
+        if matches!(self.execution_deadline, Some(deadline) if Instant::now() >= deadline) {
+            self.execution_timed_out = true;
+            // no need to check again
+            self.execution_deadline = None;
+        }
+    }
+
+    /// Charges the extra gas of an ABI call, following the gas cost table.
+    /// Fails if the extra gas charged for the ABI calls of the execution exceeds its max gas.
+    pub fn charge_abi_gas(&mut self, abi_name: &str) -> Result<(), ExecutionError> {
+        let cost = self.config.gas_costs.get_abi_cost(abi_name);
+        self.abi_gas_used = self.abi_gas_used.saturating_add(cost);
+        if self.abi_gas_used > self.max_gas {
+            return Err(ExecutionError::NotEnoughGas(format!(
+                "not enough gas to call ABI {}",
+                abi_name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Gets the total gas used by the execution: the gas metered by the runtime
+    /// plus the extra gas charged for the ABI calls.
+    /// Fails if that total exceeds the max gas of the execution.
+    ///
+    /// # Arguments
+    /// * `remaining_gas`: gas left by the runtime at the end of the execution
+    pub fn get_total_gas_used(&self, remaining_gas: u64) -> Result<u64, ExecutionError> {
+        let total_gas_used = self
+            .max_gas
+            .saturating_sub(remaining_gas)
+            .saturating_add(self.abi_gas_used);
+        if total_gas_used > self.max_gas {
+            return Err(ExecutionError::NotEnoughGas(format!(
+                "execution used {} gas ({} of which charged for ABI calls) but its max gas is {}",
+                total_gas_used, self.abi_gas_used, self.max_gas
+            )));
+        }
+        Ok(total_gas_used)
+    }
+
     /// Gets the address at the top of the call stack, if any
     pub fn get_current_address(&self) -> Result<Address, ExecutionError> {
         match self.stack.last() {
@@ -397,20 +546,7 @@ impl ExecutionContext {
         //  https://github.com/massalabs/massa/issues/2331
 
         // deterministically generate a new unique smart contract address
-
-        // create a seed from the current slot
-        let mut data: Vec<u8> = self.slot.to_bytes_key().to_vec();
-        // add the index of the created address within this context to the seed
-        data.append(&mut self.created_addr_index.to_be_bytes().to_vec());
-        // add a flag on whether we are in read-only mode or not to the seed
-        // this prevents read-only contexts from shadowing existing addresses
-        if self.read_only {
-            data.push(0u8);
-        } else {
-            data.push(1u8);
-        }
-        // hash the seed to get a unique address
-        let address = Address(massa_hash::Hash::compute_from(&data));
+        let address = self.get_sc_address_at_index(self.created_addr_index);
 
         // add this address with its bytecode to the speculative ledger
         self.speculative_ledger.create_new_sc_address(
@@ -440,6 +576,46 @@ impl ExecutionContext {
         Ok(address)
     }
 
+    /// Deterministically computes the address of the smart contract created with index `created_addr_index` in this context
+    fn get_sc_address_at_index(&self, created_addr_index: u64) -> Address {
+        // a simulated deployment creates the addresses of an active execution at the simulated slot
+        let (slot, read_only) = match &self.deployment_simulation {
+            Some(simulation) => (simulation.slot, false),
+            None => (self.slot, self.read_only),
+        };
+
+        // create a seed from the slot
+        let mut data: Vec<u8> = slot.to_bytes_key().to_vec();
+        // add the index of the created address within this context to the seed
+        data.append(&mut created_addr_index.to_be_bytes().to_vec());
+        // add a flag on whether we are in read-only mode or not to the seed
+        // this prevents read-only contexts from shadowing existing addresses
+        if read_only {
+            data.push(0u8);
+        } else {
+            data.push(1u8);
+        }
+        // hash the seed to get a unique address
+        Address(massa_hash::Hash::compute_from(&data))
+    }
+
+    /// Gets the addresses created so far in this context, in creation order.
+    /// For an active slot, they include the addresses created by the previous operations of the slot.
+    pub fn get_created_addresses(&self) -> Vec<Address> {
+        let first_index = self
+            .deployment_simulation
+            .map_or(0, |simulation| simulation.created_addr_index);
+        (first_index..self.created_addr_index)
+            .map(|index| self.get_sc_address_at_index(index))
+            .collect()
+    }
+
+    /// Gets the storage costs paid for the ledger entry of an address as it currently is:
+    /// the entry itself, its bytecode and its datastore entries
+    pub fn get_storage_cost(&self, address: &Address) -> Result<Amount, ExecutionError> {
+        self.speculative_ledger.get_storage_cost(address)
+    }
+
     /// gets the bytecode of an address if it exists in the speculative ledger, or returns None
     pub fn get_bytecode(&self, address: &Address) -> Option<Vec<u8>> {
         self.speculative_ledger.get_bytecode(address)
@@ -588,8 +764,11 @@ impl ExecutionContext {
     ///
     /// # Arguments
     /// * `msg`: asynchronous message to add
-    pub fn push_new_message(&mut self, msg: AsyncMessage) {
-        self.speculative_async_pool.push_new_message(msg);
+    ///
+    /// # Returns
+    /// An `AsyncInboxQuotaExceeded` error if the inbox of the destination of the message is full
+    pub fn push_new_message(&mut self, msg: AsyncMessage) -> Result<(), ExecutionError> {
+        self.speculative_async_pool.push_new_message(msg)
     }
 
     /// Cancels an asynchronous message, reimbursing `msg.coins` to the sender
@@ -657,16 +836,67 @@ impl ExecutionContext {
     /// # Arguments
     /// * `slot`: associated slot of the deferred credits to be executed
     /// * `credits`: deferred to be executed
-    pub fn execute_deferred_credits(&mut self, slot: &Slot) {
+    ///
+    /// # Returns
+    /// The deferred credits that were paid
+    pub fn execute_deferred_credits(&mut self, slot: &Slot) -> Vec<(Address, Amount)> {
         let credits = self.speculative_roll_state.get_deferred_credits(slot);
+        let mut paid_credits = Vec::with_capacity(credits.len());
         for (addr, amount) in credits {
-            if let Err(e) = self.transfer_coins(None, Some(addr), amount, false) {
-                debug!(
+            match self.transfer_coins(None, Some(addr), amount, false) {
+                Ok(_) => paid_credits.push((addr, amount)),
+                Err(e) => debug!(
                     "could not credit {} deferred coins to {} at slot {}: {}",
                     amount, addr, slot, e
-                );
+                ),
             }
         }
+        paid_credits
+    }
+
+    /// Charges the storage rent of every ledger entry holding a bytecode or datastore entries.
+    ///
+    /// The rent of an entry is proportional to its storage size and is burnt from its balance.
+    /// Entries unable to pay the full rent lose their whole balance and have their bytecode and datastore reclaimed.
+    ///
+    /// # Arguments
+    /// * `rent_per_byte`: rent charged for each byte of storage
+    fn charge_storage_rent(&mut self, rent_per_byte: Amount) {
+        let slot = self.slot;
+        for (addr, size) in self.speculative_ledger.get_storage_sizes() {
+            let rent = saturation::saturating_mul_u64(
+                rent_per_byte,
+                size,
+                self.config.log_saturations,
+                "storage rent",
+            );
+            if rent.is_zero() {
+                continue;
+            }
+            let balance = self.get_balance(&addr).unwrap_or_default();
+            if balance >= rent {
+                if let Err(e) = self.transfer_coins(Some(addr), None, rent, false) {
+                    debug!(
+                        "could not charge {} storage rent to {} at slot {}: {}",
+                        rent, addr, slot, e
+                    );
+                }
+                continue;
+            }
+            if !balance.is_zero() {
+                if let Err(e) = self.transfer_coins(Some(addr), None, balance, false) {
+                    debug!(
+                        "could not charge {} storage rent to {} at slot {}: {}",
+                        balance, addr, slot, e
+                    );
+                }
+            }
+            self.speculative_ledger.reclaim_storage(&addr);
+            debug!(
+                "storage of {} ({} bytes) reclaimed at slot {}: unpaid rent of {}",
+                addr, size, slot, rent
+            );
+        }
     }
 
     /// Finishes a slot and generates the execution output.
@@ -686,7 +916,16 @@ impl ExecutionContext {
         }
 
         // execute the deferred credits coming from roll sells
-        self.execute_deferred_credits(&slot);
+        let address_activities = self
+            .execute_deferred_credits(&slot)
+            .into_iter()
+            .map(|(address, amount)| AddressActivity {
+                address,
+                slot,
+                is_final: false,
+                kind: AddressActivityKind::DeferredCredit { amount },
+            })
+            .collect();
 
         // if the current slot is last in cycle check the production stats and act accordingly
         if self
@@ -700,6 +939,11 @@ impl ExecutionContext {
                 self.config.roll_price,
                 self.config.max_miss_ratio,
             );
+
+            // charge the storage rent of the ledger entries
+            if let Some(rent_per_byte) = self.config.storage_rent_per_byte_per_cycle {
+                self.charge_storage_rent(rent_per_byte);
+            }
         }
 
         // generate the execution output
@@ -714,6 +958,9 @@ impl ExecutionContext {
             block_id: std::mem::take(&mut self.opt_block_id),
             state_changes,
             events: std::mem::take(&mut self.events),
+            operation_statuses: Vec::new(),
+            address_activities,
+            gas_costs_hash: self.config.gas_costs_hash,
         }
     }
 
@@ -770,9 +1017,56 @@ impl ExecutionContext {
         SCOutputEvent { context, data }
     }
 
-    /// Emits a previously created event.
+    /// Emits a previously created event generated by a smart contract,
+    /// after checking it against the event count and size limits of the call and of the slot.
     /// Overrides the event's index with the current event counter value, and increments the event counter.
-    pub fn event_emit(&mut self, mut event: SCOutputEvent) {
+    ///
+    /// # Returns
+    /// An `EventLimitExceeded` error if the event would exceed one of the limits, in which case it is not emitted
+    pub fn event_emit(&mut self, event: SCOutputEvent) -> Result<(), ExecutionError> {
+        let size = event.data.len() as u64;
+        let call_usage = EventUsage {
+            count: self.call_event_usage.count.saturating_add(1),
+            size: self.call_event_usage.size.saturating_add(size),
+        };
+        let slot_usage = EventUsage {
+            count: self.slot_event_usage.count.saturating_add(1),
+            size: self.slot_event_usage.size.saturating_add(size),
+        };
+        if call_usage.count > self.config.max_event_count_per_call {
+            return Err(ExecutionError::EventLimitExceeded(format!(
+                "a call cannot emit more than {} events",
+                self.config.max_event_count_per_call
+            )));
+        }
+        if call_usage.size > self.config.max_event_size_per_call {
+            return Err(ExecutionError::EventLimitExceeded(format!(
+                "the events of a call cannot exceed {} bytes",
+                self.config.max_event_size_per_call
+            )));
+        }
+        if slot_usage.count > self.config.max_event_count_per_slot {
+            return Err(ExecutionError::EventLimitExceeded(format!(
+                "the smart contracts of a slot cannot emit more than {} events",
+                self.config.max_event_count_per_slot
+            )));
+        }
+        if slot_usage.size > self.config.max_event_size_per_slot {
+            return Err(ExecutionError::EventLimitExceeded(format!(
+                "the events of the smart contracts of a slot cannot exceed {} bytes",
+                self.config.max_event_size_per_slot
+            )));
+        }
+        self.call_event_usage = call_usage;
+        self.slot_event_usage = slot_usage;
+        self.node_event_emit(event);
+        Ok(())
+    }
+
+    /// Emits a previously created event generated by the node itself, such as an execution error,
+    /// which is not subject to the event limits.
+    /// Overrides the event's index with the current event counter value, and increments the event counter.
+    pub fn node_event_emit(&mut self, mut event: SCOutputEvent) {
         // Set the event index
         event.context.index_in_slot = self.created_event_index;
 
@@ -809,6 +1103,16 @@ impl ExecutionContext {
             .get_address_cycle_infos(address, periods_per_cycle, self.slot)
     }
 
+    /// Get the deferred credits of all addresses scheduled in a slot range
+    pub fn get_deferred_credits_range(
+        &self,
+        start: Slot,
+        end: Slot,
+    ) -> BTreeMap<(Slot, Address), Amount> {
+        self.speculative_roll_state
+            .get_deferred_credits_range(start, end)
+    }
+
     /// Get future deferred credits of an address
     pub fn get_address_future_deferred_credits(
         &self,
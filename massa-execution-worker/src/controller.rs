@@ -6,19 +6,29 @@
 use crate::execution::ExecutionState;
 use crate::request_queue::{RequestQueue, RequestWithResponseSender};
 use massa_execution_exports::{
-    ExecutionAddressInfo, ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager,
+    AddressInfoBounds, BlockSimulationOutput, BlockSimulationRequest, ExecutionAddressInfo,
+    ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager,
     ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
 };
-use massa_models::api::EventFilter;
+use massa_ledger_exports::LedgerEntryProof;
+use massa_models::api::{
+    AddressActivity, ContractInfo, DeferredCreditInfo, EventFilter, OperationExecutionInfo,
+    SlotExecutionTrace,
+};
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::{PreHashMap, PreHashSet};
-use massa_models::stats::ExecutionStats;
-use massa_models::{address::Address, amount::Amount, operation::OperationId};
-use massa_models::{block::BlockId, slot::Slot};
+use massa_models::stats::{CycleStats, ExecutionStats, FeeRecommendation, NetworkActivityStats};
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    operation::{OperationId, WrappedOperation},
+};
+use massa_models::{block::BlockId, slot::Slot, streaming_step::StreamingStep};
 use massa_storage::Storage;
 use parking_lot::{Condvar, Mutex, RwLock};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt::Display;
+use std::str::FromStr;
 use std::sync::Arc;
 use tracing::info;
 
@@ -26,6 +36,9 @@ use tracing::info;
 pub(crate) struct ExecutionInputData {
     /// set stop to true to stop the thread
     pub stop: bool,
+    /// set drain to true along with stop to execute the pending final slots
+    /// and flush the final state before stopping the thread
+    pub drain: bool,
     /// list of newly finalized blocks
     pub finalized_blocks: HashMap<Slot, BlockId>,
     /// new blockclique (if there is a new one)
@@ -34,14 +47,41 @@ pub(crate) struct ExecutionInputData {
     pub block_storage: PreHashMap<BlockId, Storage>,
     /// queue for read-only execution requests and response MPSCs to send back their outputs
     pub readonly_requests: RequestQueue<ReadOnlyExecutionRequest, ReadOnlyExecutionOutput>,
+    /// queue for candidate block simulation requests and response MPSCs to send back their outputs
+    pub block_simulation_requests: RequestQueue<BlockSimulationRequest, BlockSimulationOutput>,
+    /// queue for trace mode slot replay requests and response MPSCs to send back their traces
+    pub slot_replay_requests: RequestQueue<Slot, SlotExecutionTrace>,
+    /// time-travel commands of the sandbox mode, applied in order
+    #[cfg(feature = "sandbox")]
+    pub sandbox_commands: Vec<SandboxCommand>,
+}
+
+/// Time-travel command of the sandbox mode.
+/// See the `sandbox_*` methods of `ExecutionController` for details.
+#[cfg(feature = "sandbox")]
+pub(crate) enum SandboxCommand {
+    /// move the time seen by the execution forward by a number of slots
+    FastForward(u64),
+    /// execute a block as a candidate at an arbitrary slot
+    ExecuteBlockAtSlot {
+        /// block to execute
+        block_id: BlockId,
+        /// slot at which the block is executed
+        slot: Slot,
+        /// storage instance owning refs to the block and its operations
+        block_storage: Storage,
+    },
+    /// drop the candidate executions
+    ResetToFinal,
 }
 
 impl Display for ExecutionInputData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "stop={:?}, finalized={:?}, blockclique={:?}, readonly={:?}",
+            "stop={:?}, drain={:?}, finalized={:?}, blockclique={:?}, readonly={:?}, block_simulations={:?}, slot_replays={:?}",
             self.stop,
+            self.drain,
             self.finalized_blocks
                 .iter()
                 .map(|(slot, id)| (*slot, *id))
@@ -50,7 +90,9 @@ impl Display for ExecutionInputData {
                 .iter()
                 .map(|(slot, id)| (*slot, *id))
                 .collect::<BTreeMap<Slot, BlockId>>()),
-            self.readonly_requests
+            self.readonly_requests,
+            self.block_simulation_requests,
+            self.slot_replay_requests
         )
     }
 }
@@ -60,10 +102,15 @@ impl ExecutionInputData {
     pub fn new(config: ExecutionConfig) -> Self {
         ExecutionInputData {
             stop: Default::default(),
+            drain: Default::default(),
             finalized_blocks: Default::default(),
             new_blockclique: Default::default(),
             block_storage: Default::default(),
             readonly_requests: RequestQueue::new(config.max_final_events),
+            block_simulation_requests: RequestQueue::new(config.readonly_queue_length),
+            slot_replay_requests: RequestQueue::new(config.readonly_queue_length),
+            #[cfg(feature = "sandbox")]
+            sandbox_commands: Default::default(),
         }
     }
 
@@ -71,8 +118,11 @@ impl ExecutionInputData {
     /// and resets self.
     pub fn take(&mut self) -> Self {
         let max_final_events = self.readonly_requests.capacity();
+        let max_block_simulations = self.block_simulation_requests.capacity();
+        let max_slot_replays = self.slot_replay_requests.capacity();
         ExecutionInputData {
             stop: std::mem::take(&mut self.stop),
+            drain: std::mem::take(&mut self.drain),
             finalized_blocks: std::mem::take(&mut self.finalized_blocks),
             new_blockclique: std::mem::take(&mut self.new_blockclique),
             block_storage: std::mem::take(&mut self.block_storage),
@@ -80,6 +130,16 @@ impl ExecutionInputData {
                 &mut self.readonly_requests,
                 RequestQueue::new(max_final_events),
             ),
+            block_simulation_requests: std::mem::replace(
+                &mut self.block_simulation_requests,
+                RequestQueue::new(max_block_simulations),
+            ),
+            slot_replay_requests: std::mem::replace(
+                &mut self.slot_replay_requests,
+                RequestQueue::new(max_slot_replays),
+            ),
+            #[cfg(feature = "sandbox")]
+            sandbox_commands: std::mem::take(&mut self.sandbox_commands),
         }
     }
 }
@@ -215,6 +275,103 @@ impl ExecutionController for ExecutionControllerImpl {
         }
     }
 
+    /// Resolves a name through a read-only call to the name registry contract
+    fn resolve_name(&self, name: &str) -> Result<Option<Address>, ExecutionError> {
+        let req = self.execution_state.read().name_resolution_request(name)?;
+        let output = self.execute_readonly_request(req).map_err(|err| {
+            ExecutionError::NameResolutionError(format!("could not resolve {}: {}", name, err))
+        })?;
+        // the registry emits the resolved address as the data of its last event
+        Ok(output
+            .out
+            .events
+            .iter()
+            .last()
+            .and_then(|event| Address::from_str(event.data.trim().trim_matches('"')).ok()))
+    }
+
+    /// Simulates the operations of a candidate block on the execution thread,
+    /// without affecting the candidate state
+    fn simulate_block(
+        &self,
+        slot: Slot,
+        operations: Vec<WrappedOperation>,
+    ) -> Result<BlockSimulationOutput, ExecutionError> {
+        let resp_rx = {
+            let mut input_data = self.input_data.1.lock();
+
+            // if the simulation queue is already full, return an error
+            if input_data.block_simulation_requests.is_full() {
+                return Err(ExecutionError::ChannelError(
+                    "too many queued block simulation requests".into(),
+                ));
+            }
+
+            // prepare the channel to send back the result of the simulation
+            let (resp_tx, resp_rx) =
+                std::sync::mpsc::channel::<Result<BlockSimulationOutput, ExecutionError>>();
+
+            // append the request to the queue of input simulation requests
+            input_data
+                .block_simulation_requests
+                .push(RequestWithResponseSender::new(
+                    BlockSimulationRequest { slot, operations },
+                    resp_tx,
+                ));
+
+            // wake up the execution main loop
+            self.input_data.0.notify_one();
+
+            resp_rx
+        };
+
+        // Wait for the result of the simulation
+        match resp_rx.recv() {
+            Ok(result) => result,
+            Err(err) => Err(ExecutionError::ChannelError(format!(
+                "block simulation response channel readout failed: {}",
+                err
+            ))),
+        }
+    }
+
+    /// Replays a slot of the active history in trace mode on the execution thread
+    fn replay_slot_with_trace(&self, slot: Slot) -> Result<SlotExecutionTrace, ExecutionError> {
+        let resp_rx = {
+            let mut input_data = self.input_data.1.lock();
+
+            // if the replay queue is already full, return an error
+            if input_data.slot_replay_requests.is_full() {
+                return Err(ExecutionError::ChannelError(
+                    "too many queued slot replay requests".into(),
+                ));
+            }
+
+            // prepare the channel to send back the trace of the replay
+            let (resp_tx, resp_rx) =
+                std::sync::mpsc::channel::<Result<SlotExecutionTrace, ExecutionError>>();
+
+            // append the request to the queue of input replay requests
+            input_data
+                .slot_replay_requests
+                .push(RequestWithResponseSender::new(slot, resp_tx));
+
+            // wake up the execution main loop
+            self.input_data.0.notify_one();
+
+            resp_rx
+        };
+
+        // Wait for the trace of the replay
+        match resp_rx.recv() {
+            Ok(result) => result,
+            Err(err) => Err(ExecutionError::ChannelError(format!(
+                "slot replay response channel readout failed: {}",
+                err
+            ))),
+        }
+    }
+
     /// List which operations inside the provided list were not executed
     fn unexecuted_ops_among(
         &self,
@@ -226,13 +383,101 @@ impl ExecutionController for ExecutionControllerImpl {
             .unexecuted_ops_among(ops, thread)
     }
 
-    /// Gets information about a batch of addresses
-    fn get_addresses_infos(&self, addresses: &[Address]) -> Vec<ExecutionAddressInfo> {
+    /// Get a page of the final and candidate datastore keys of an address starting with a given prefix
+    fn get_final_and_candidate_datastore_keys(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: StreamingStep<Vec<u8>>,
+        max_count: usize,
+    ) -> (BTreeSet<Vec<u8>>, BTreeSet<Vec<u8>>, StreamingStep<Vec<u8>>) {
+        self.execution_state
+            .read()
+            .get_final_and_candidate_datastore_keys(addr, prefix, cursor, max_count)
+    }
+
+    /// Get a page of the final addresses holding a non-empty bytecode
+    fn get_final_contracts(
+        &self,
+        cursor: StreamingStep<Address>,
+        max_count: usize,
+    ) -> (Vec<ContractInfo>, StreamingStep<Address>) {
+        self.execution_state
+            .read()
+            .get_final_contracts(cursor, max_count)
+    }
+
+    /// Get a page of the deferred credits of all addresses scheduled in a slot range
+    fn get_deferred_credits(
+        &self,
+        start: Slot,
+        end: Slot,
+        cursor: StreamingStep<(Slot, Address)>,
+        max_count: usize,
+    ) -> (Vec<DeferredCreditInfo>, StreamingStep<(Slot, Address)>) {
+        self.execution_state
+            .read()
+            .get_deferred_credits_page(start, end, cursor, max_count)
+    }
+
+    /// Get a proof of inclusion of a final balance or datastore entry
+    fn get_final_ledger_proof(
+        &self,
+        address: &Address,
+        datastore_key: Option<&[u8]>,
+    ) -> Option<LedgerEntryProof> {
+        self.execution_state
+            .read()
+            .get_final_ledger_proof(address, datastore_key)
+    }
+
+    /// Get the outcomes of the execution of operations, candidate executions first
+    fn get_op_execution_infos(&self, ops: &[OperationId]) -> Vec<Option<OperationExecutionInfo>> {
+        let exec_state = self.execution_state.read();
+        ops.iter()
+            .map(|op_id| exec_state.get_op_execution_info(op_id))
+            .collect()
+    }
+
+    /// Get the activities of an address in a slot range, in chronological order
+    fn get_address_history(
+        &self,
+        address: &Address,
+        start: Option<Slot>,
+        end: Option<Slot>,
+    ) -> Vec<AddressActivity> {
+        self.execution_state
+            .read()
+            .get_address_history(address, start, end)
+    }
+
+    /// Gets information about a batch of addresses,
+    /// their datastore keys and cycle infos being limited by `bounds`
+    fn get_addresses_infos(
+        &self,
+        addresses: &[Address],
+        bounds: &AddressInfoBounds,
+    ) -> Vec<ExecutionAddressInfo> {
         let mut res = Vec::with_capacity(addresses.len());
         let exec_state = self.execution_state.read();
         for addr in addresses {
-            let (final_datastore_keys, candidate_datastore_keys) =
-                exec_state.get_final_and_candidate_datastore_keys(addr);
+            let (final_datastore_keys, candidate_datastore_keys, next_datastore_keys_cursor) =
+                if bounds.max_datastore_keys == 0 {
+                    (BTreeSet::new(), BTreeSet::new(), StreamingStep::Finished)
+                } else {
+                    exec_state.get_final_and_candidate_datastore_keys(
+                        addr,
+                        &[],
+                        bounds.datastore_keys_cursor.clone(),
+                        bounds.max_datastore_keys,
+                    )
+                };
+            let mut cycle_infos = if bounds.max_cycle_infos == 0 {
+                Vec::new()
+            } else {
+                exec_state.get_address_cycle_infos(addr)
+            };
+            cycle_infos.drain(..cycle_infos.len().saturating_sub(bounds.max_cycle_infos));
             let (final_balance, candidate_balance) =
                 exec_state.get_final_and_candidate_balance(addr);
             let (final_roll_count, candidate_roll_count) =
@@ -240,12 +485,13 @@ impl ExecutionController for ExecutionControllerImpl {
             res.push(ExecutionAddressInfo {
                 final_datastore_keys,
                 candidate_datastore_keys,
+                next_datastore_keys_cursor,
                 final_balance: final_balance.unwrap_or_default(),
                 candidate_balance: candidate_balance.unwrap_or_default(),
                 final_roll_count,
                 candidate_roll_count,
                 future_deferred_credits: exec_state.get_address_future_deferred_credits(addr),
-                cycle_infos: exec_state.get_address_cycle_infos(addr),
+                cycle_infos,
             });
         }
         res
@@ -256,6 +502,83 @@ impl ExecutionController for ExecutionControllerImpl {
         self.execution_state.read().get_stats()
     }
 
+    /// Get network-wide activity statistics over the latest final slots
+    fn get_network_stats(&self, window: usize) -> NetworkActivityStats {
+        self.execution_state.read().get_network_stats(window)
+    }
+
+    /// Get the proof-of-stake statistics of a cycle from the final state, with a page of its stakers
+    fn get_cycle_stats(
+        &self,
+        cycle: u64,
+        start_after: Option<Address>,
+        max_count: usize,
+    ) -> Option<CycleStats> {
+        self.execution_state
+            .read()
+            .get_cycle_stats(cycle, start_after, max_count)
+    }
+
+    /// Get fee suggestions from the inclusion of operations in the latest final blocks
+    fn get_fee_recommendation(&self, pool_operation_count: usize) -> FeeRecommendation {
+        self.execution_state
+            .read()
+            .get_fee_recommendation(pool_operation_count)
+    }
+
+    /// Moves the time seen by the execution thread forward by `slot_count` slots
+    #[cfg(feature = "sandbox")]
+    fn sandbox_fast_forward(&self, slot_count: u64) {
+        let mut input_data = self.input_data.1.lock();
+        input_data
+            .sandbox_commands
+            .push(SandboxCommand::FastForward(slot_count));
+        self.input_data.0.notify_one();
+    }
+
+    /// Executes a block at an arbitrary candidate slot on the execution thread
+    #[cfg(feature = "sandbox")]
+    fn sandbox_execute_block_at_slot(
+        &self,
+        block_id: BlockId,
+        slot: Slot,
+        block_storage: Storage,
+    ) -> Result<(), ExecutionError> {
+        let final_cursor = self.execution_state.read().final_cursor;
+        if slot <= final_cursor {
+            return Err(ExecutionError::RuntimeError(format!(
+                "cannot execute a block at slot {} which is not after the final slot {}",
+                slot, final_cursor
+            )));
+        }
+        if !block_storage.get_block_refs().contains(&block_id) {
+            return Err(ExecutionError::RuntimeError(format!(
+                "block {} is absent from the provided storage",
+                block_id
+            )));
+        }
+        let mut input_data = self.input_data.1.lock();
+        input_data
+            .sandbox_commands
+            .push(SandboxCommand::ExecuteBlockAtSlot {
+                block_id,
+                slot,
+                block_storage,
+            });
+        self.input_data.0.notify_one();
+        Ok(())
+    }
+
+    /// Drops the candidate executions on the execution thread
+    #[cfg(feature = "sandbox")]
+    fn sandbox_reset_to_final(&self) {
+        let mut input_data = self.input_data.1.lock();
+        input_data
+            .sandbox_commands
+            .push(SandboxCommand::ResetToFinal);
+        self.input_data.0.notify_one();
+    }
+
     /// Returns a boxed clone of self.
     /// Allows cloning `Box<dyn ExecutionController>`,
     /// see `massa-execution-exports/controller_traits.rs`
@@ -274,20 +597,35 @@ pub struct ExecutionManagerImpl {
     pub(crate) thread_handle: Option<std::thread::JoinHandle<()>>,
 }
 
-impl ExecutionManager for ExecutionManagerImpl {
-    /// stops the worker
-    fn stop(&mut self) {
-        info!("stopping Execution controller...");
-        // notify the worker thread to stop
+impl ExecutionManagerImpl {
+    /// notifies the worker thread to stop, draining the pending final slots if `drain` is true,
+    /// and waits for it to finish
+    fn stop_worker(&mut self, drain: bool) {
         {
             let mut input_wlock = self.input_data.1.lock();
             input_wlock.stop = true;
+            input_wlock.drain = drain;
             self.input_data.0.notify_one();
         }
         // join the execution thread
         if let Some(join_handle) = self.thread_handle.take() {
             join_handle.join().expect("VM controller thread panicked");
         }
+    }
+}
+
+impl ExecutionManager for ExecutionManagerImpl {
+    /// stops the worker
+    fn stop(&mut self) {
+        info!("stopping Execution controller...");
+        self.stop_worker(false);
+        info!("execution controller stopped");
+    }
+
+    /// stops the worker after executing the pending final slots and flushing the final state
+    fn stop_gracefully(&mut self) {
+        info!("stopping Execution controller after draining the final slots...");
+        self.stop_worker(true);
         info!("execution controller stopped");
     }
 }
@@ -11,30 +11,43 @@
 use crate::active_history::{ActiveHistory, HistorySearchResult};
 use crate::context::ExecutionContext;
 use crate::interface_impl::InterfaceImpl;
-use crate::stats::ExecutionStatsCounter;
+use crate::readonly_cache::{hash_readonly_request, ReadOnlyCache};
+use crate::saturation;
+use crate::speculative_roll_state::get_roll_sale_credit_slot;
+use crate::stats::{ExecutionStatsCounter, FinalSlotSummary, NetworkActivityCounter};
 use massa_async_pool::AsyncMessage;
 use massa_execution_exports::{
-    EventStore, ExecutionConfig, ExecutionError, ExecutionOutput, ExecutionStackElement,
-    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    BlockSimulationOutput, BlockSimulationRequest, EventStore, ExecutionChannels, ExecutionConfig,
+    ExecutionError, ExecutionOutput, ExecutionStackElement, ReadOnlyExecutionOutput,
+    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget, SimulatedOperation,
 };
 use massa_final_state::FinalState;
-use massa_ledger_exports::{SetOrDelete, SetUpdateOrDelete};
+use massa_ledger_exports::{LedgerEntryProof, LedgerError, SetOrDelete, SetUpdateOrDelete};
 use massa_models::address::ExecutionAddressCycleInfo;
-use massa_models::api::EventFilter;
+use massa_models::api::{
+    AddressActivity, AddressActivityKind, ContractInfo, DeferredCreditInfo, EventFilter,
+    ExecutionTraceOrigin, OperationExecutionInfo, OperationStatusNotification, RewardRole,
+    SlotExecutionTrace,
+};
 use massa_models::output_event::SCOutputEvent;
-use massa_models::prehash::PreHashSet;
-use massa_models::stats::ExecutionStats;
+use massa_models::prehash::{PreHashMap, PreHashSet};
+use massa_models::stats::{
+    CycleStats, ExecutionStats, FeeRecommendation, MissRatioProjection, NetworkActivityStats,
+    StakerCycleStats,
+};
 use massa_models::{
     address::Address,
     block::BlockId,
     operation::{OperationId, OperationType, WrappedOperation},
 };
-use massa_models::{amount::Amount, slot::Slot};
+use massa_models::{amount::Amount, slot::Slot, streaming_step::StreamingStep};
 use massa_pos_exports::SelectorController;
 use massa_sc_runtime::Interface;
 use massa_storage::Storage;
+use num::rational::Ratio;
 use parking_lot::{Mutex, RwLock};
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::ops::Bound::{Excluded, Unbounded};
 use std::sync::Arc;
 use tracing::{debug, info, warn};
 
@@ -56,12 +69,20 @@ pub(crate) struct ExecutionState {
     // its output is popped from the front of active_history and applied to the final state.
     // It has atomic R/W access.
     active_history: Arc<RwLock<ActiveHistory>>,
+    // blocks executed at the slots of the active history (None on misses), kept to replay those slots
+    active_slot_inputs: BTreeMap<Slot, Option<(BlockId, Storage)>>,
     // a cursor pointing to the highest executed slot
     pub active_cursor: Slot,
     // a cursor pointing to the highest executed final slot
     pub final_cursor: Slot,
     // store containing execution events that became final
     final_events: EventStore,
+    // outcomes of the final operation executions, oldest first in `final_op_executions_order`
+    final_op_executions: PreHashMap<OperationId, OperationExecutionInfo>,
+    final_op_executions_order: VecDeque<OperationId>,
+    // final activities of each address, oldest first, `final_address_activities_order` listing their addresses by age
+    final_address_activities: PreHashMap<Address, VecDeque<AddressActivity>>,
+    final_address_activities_order: VecDeque<Address>,
     // final state with atomic R/W access
     final_state: Arc<RwLock<FinalState>>,
     // execution context (see documentation in context.rs)
@@ -70,6 +91,14 @@ pub(crate) struct ExecutionState {
     execution_interface: Box<dyn Interface>,
     // execution statistics
     stats_counter: ExecutionStatsCounter,
+    // network activity over the latest final slots
+    network_activity: NetworkActivityCounter,
+    // block producers on track to exceed the max miss ratio of the current cycle
+    miss_ratio_warnings: Vec<MissRatioProjection>,
+    // outputs of the read-only executions done at the current active cursor
+    readonly_cache: ReadOnlyCache,
+    // channels broadcasting the execution outputs
+    channels: ExecutionChannels,
 }
 
 impl ExecutionState {
@@ -78,10 +107,15 @@ impl ExecutionState {
     /// # Arguments
     /// * `config`: execution configuration
     /// * `final_state`: atomic access to the final state
+    /// * `channels`: channels broadcasting the execution outputs
     ///
     /// # returns
     /// A new `ExecutionState`
-    pub fn new(config: ExecutionConfig, final_state: Arc<RwLock<FinalState>>) -> ExecutionState {
+    pub fn new(
+        config: ExecutionConfig,
+        final_state: Arc<RwLock<FinalState>>,
+        channels: ExecutionChannels,
+    ) -> ExecutionState {
         // Get the slot at the output of which the final state is attached.
         // This should be among the latest final slots.
         let last_final_slot = final_state.read().slot;
@@ -109,22 +143,65 @@ impl ExecutionState {
             execution_interface,
             // empty execution output history: it is not recovered through bootstrap
             active_history,
+            active_slot_inputs: Default::default(),
             // empty final event store: it is not recovered through bootstrap
             final_events: Default::default(),
+            // no final operation execution outcome: they are not recovered through bootstrap
+            final_op_executions: Default::default(),
+            final_op_executions_order: Default::default(),
+            // no final address activity: they are not recovered through bootstrap
+            final_address_activities: Default::default(),
+            final_address_activities_order: Default::default(),
             // no active slots executed yet: set active_cursor to the last final block
             active_cursor: last_final_slot,
             final_cursor: last_final_slot,
             stats_counter: ExecutionStatsCounter::new(
                 config.stats_time_window_duration,
-                config.clock_compensation,
+                config.clock_compensation.clone(),
+            ),
+            network_activity: NetworkActivityCounter::new(
+                config.max_network_stats_slots,
+                config.t0,
+                config.thread_count,
+                config.max_gas_per_block,
             ),
+            miss_ratio_warnings: Vec::new(),
+            readonly_cache: ReadOnlyCache::new(config.readonly_cache_size),
+            channels,
             config,
         }
     }
 
     /// Get execution statistics
     pub fn get_stats(&self) -> ExecutionStats {
-        self.stats_counter.get_stats(self.active_cursor)
+        self.stats_counter.get_stats(
+            self.active_cursor,
+            self.miss_ratio_warnings.clone(),
+            self.config.gas_costs_hash,
+            self.readonly_cache.get_hits_and_misses(),
+        )
+    }
+
+    /// Get the network activity statistics over the latest final slots
+    ///
+    /// # Arguments
+    /// * `window`: number of latest final slots to compute the statistics over, capped to `max_network_stats_slots`
+    pub fn get_network_stats(&self, window: usize) -> NetworkActivityStats {
+        self.network_activity
+            .get_stats(window.min(self.network_activity.max_slots()))
+    }
+
+    /// Get fee suggestions from the inclusion of operations in the latest final blocks
+    ///
+    /// # Arguments
+    /// * `pool_operation_count`: number of operations waiting in the pool
+    pub fn get_fee_recommendation(&self, pool_operation_count: usize) -> FeeRecommendation {
+        self.network_activity.get_fee_recommendation(
+            self.config
+                .fee_recommendation_slots
+                .min(self.network_activity.max_slots()),
+            pool_operation_count,
+        )
     }
 
     /// Applies the output of an execution to the final execution state.
@@ -144,6 +221,26 @@ impl ExecutionState {
                 exec_out.state_changes.executed_ops_changes.len(),
             );
         }
+        self.network_activity.register_final_slot(FinalSlotSummary {
+            slot: exec_out.slot,
+            has_block: exec_out.block_id.is_some(),
+            operation_fees: exec_out
+                .operation_statuses
+                .iter()
+                .map(|status| status.fee)
+                .collect(),
+            gas_used: exec_out
+                .operation_statuses
+                .iter()
+                .map(|status| status.execution.gas_used)
+                .sum(),
+            min_fee_per_gas: exec_out
+                .operation_statuses
+                .iter()
+                .filter_map(|status| status.fee.checked_div_u64(status.execution.gas_used))
+                .min(),
+            event_count: exec_out.events.iter().count() as u64,
+        });
 
         // apply state changes to the final ledger
         self.final_state
@@ -159,10 +256,48 @@ impl ExecutionState {
             self.active_cursor = self.final_cursor;
         }
 
-        // append generated events to the final event store
+        // notify the subscribers that the execution output is final
         exec_out.events.finalize();
+        for status in exec_out.operation_statuses.iter_mut() {
+            status.execution.is_final = true;
+        }
+        self.broadcast_execution_output(&exec_out.operation_statuses, &exec_out.events);
+
+        // append generated events to the final event store
         self.final_events.extend(exec_out.events);
         self.final_events.prune(self.config.max_final_events);
+
+        // remember the outcomes of the final operation executions, forgetting the oldest ones
+        for status in exec_out.operation_statuses {
+            self.final_op_executions_order.push_back(status.id);
+            self.final_op_executions.insert(status.id, status.execution);
+        }
+        while self.final_op_executions_order.len() > self.config.max_final_op_executions {
+            if let Some(op_id) = self.final_op_executions_order.pop_front() {
+                self.final_op_executions.remove(&op_id);
+            }
+        }
+
+        // index the final address activities, forgetting the oldest ones
+        for mut activity in exec_out.address_activities {
+            activity.is_final = true;
+            self.final_address_activities_order
+                .push_back(activity.address);
+            self.final_address_activities
+                .entry(activity.address)
+                .or_default()
+                .push_back(activity);
+        }
+        while self.final_address_activities_order.len() > self.config.max_final_address_activities {
+            if let Some(address) = self.final_address_activities_order.pop_front() {
+                if let Some(activities) = self.final_address_activities.get_mut(&address) {
+                    activities.pop_front();
+                    if activities.is_empty() {
+                        self.final_address_activities.remove(&address);
+                    }
+                }
+            }
+        }
     }
 
     /// Applies an execution output to the active (non-final) state
@@ -181,10 +316,32 @@ impl ExecutionState {
         // update active cursor to reflect the new latest active slot
         self.active_cursor = exec_out.slot;
 
+        // notify the subscribers of the candidate execution output
+        self.broadcast_execution_output(&exec_out.operation_statuses, &exec_out.events);
+
         // add the execution output at the end of the output history
         self.active_history.write().0.push_back(exec_out);
     }
 
+    /// Broadcasts the operation statuses and the events of an execution output to their subscribers
+    fn broadcast_execution_output(
+        &self,
+        operation_statuses: &[OperationStatusNotification],
+        events: &EventStore,
+    ) {
+        // sending only fails if there is no subscriber
+        if self.channels.operation_status_sender.receiver_count() > 0 {
+            for status in operation_statuses {
+                let _ = self.channels.operation_status_sender.send(status.clone());
+            }
+        }
+        if self.channels.sc_event_sender.receiver_count() > 0 {
+            for event in events.iter() {
+                let _ = self.channels.sc_event_sender.send(event.clone());
+            }
+        }
+    }
+
     /// Execute an operation in the context of a block.
     /// Assumes the execution context was initialized at the beginning of the slot.
     ///
@@ -193,13 +350,17 @@ impl ExecutionState {
     /// * `block_slot`: slot of the block in which the op is included
     /// * `remaining_block_gas`: mutable reference towards the remaining gas in the block
     /// * `block_credits`: mutable reference towards the total block reward/fee credits
+    ///
+    /// # Returns
+    /// The gas consumed by the execution and its error if it failed,
+    /// or an error if the operation could not be included, in which case it was not executed
     pub fn execute_operation(
         &self,
         operation: &WrappedOperation,
         block_slot: Slot,
         remaining_block_gas: &mut u64,
         block_credits: &mut Amount,
-    ) -> Result<(), ExecutionError> {
+    ) -> Result<(u64, Option<String>), ExecutionError> {
         // check validity period
         if !(operation
             .get_validity_range(self.config.operation_validity_period)
@@ -234,7 +395,9 @@ impl ExecutionState {
 
         // compute fee from (op.max_gas * op.gas_price + op.fee)
         let op_fees = operation.get_total_fee();
-        let new_block_credits = block_credits.saturating_add(op_fees);
+        let new_block_credits = block_credits.strict_add(op_fees).map_err(|err| {
+            ExecutionError::IncludeOperationError(format!("block credits overflow: {}", err))
+        })?;
 
         let context_snapshot;
         {
@@ -273,6 +436,8 @@ impl ExecutionState {
 
             // set the context max gas to match the one defined in the operation
             context.max_gas = operation.get_gas_usage();
+            context.abi_gas_used = 0;
+            context.reset_call_event_usage();
 
             // set the creator address
             context.creator_address = Some(operation.creator_address);
@@ -297,37 +462,34 @@ impl ExecutionState {
             OperationType::CallSC { .. } => {
                 self.execute_callsc_op(&operation.content.op, sender_addr)
             }
-            OperationType::RollBuy { .. } => {
-                self.execute_roll_buy_op(&operation.content.op, sender_addr)
-            }
-            OperationType::RollSell { .. } => {
-                self.execute_roll_sell_op(&operation.content.op, sender_addr)
-            }
-            OperationType::Transaction { .. } => {
-                self.execute_transaction_op(&operation.content.op, sender_addr)
-            }
+            OperationType::RollBuy { .. } => self
+                .execute_roll_buy_op(&operation.content.op, sender_addr)
+                .map(|_| 0),
+            OperationType::RollSell { .. } => self
+                .execute_roll_sell_op(&operation.content.op, sender_addr)
+                .map(|_| 0),
+            OperationType::Transaction { .. } => self
+                .execute_transaction_op(&operation.content.op, sender_addr)
+                .map(|_| 0),
         };
 
-        {
-            // lock execution context
-            let mut context = context_guard!(self);
+        // check execution results
+        match execution_result {
+            Ok(gas_used) => Ok((gas_used, None)),
+            Err(err) => {
+                // an error occurred: emit error event and reset context to snapshot
+                let err = ExecutionError::RuntimeError(format!(
+                    "runtime error when executing operation {}: {}",
+                    operation_id, &err
+                ));
+                debug!("{}", &err);
+                let execution_error = err.to_string();
+                context_guard!(self).reset_to_snapshot(context_snapshot, Some(err));
 
-            // check execution results
-            match execution_result {
-                Ok(_) => {}
-                Err(err) => {
-                    // an error occurred: emit error event and reset context to snapshot
-                    let err = ExecutionError::RuntimeError(format!(
-                        "runtime error when executing operation {}: {}",
-                        operation_id, &err
-                    ));
-                    debug!("{}", &err);
-                    context.reset_to_snapshot(context_snapshot, Some(err));
-                }
+                // the gas of a failed execution is entirely consumed
+                Ok((op_gas, Some(execution_error)))
             }
         }
-
-        Ok(())
     }
 
     /// Execute an operation of type `RollSell`
@@ -475,11 +637,14 @@ impl ExecutionState {
     /// # Arguments
     /// * `operation`: the `WrappedOperation` to process, must be an `ExecuteSC`
     /// * `sender_addr`: address of the sender
+    ///
+    /// # Returns
+    /// The gas consumed by the execution
     pub fn execute_executesc_op(
         &self,
         operation: &OperationType,
         sender_addr: Address,
-    ) -> Result<(), ExecutionError> {
+    ) -> Result<u64, ExecutionError> {
         // process ExecuteSC operations only
         let (bytecode, max_gas, datastore) = match &operation {
             OperationType::ExecuteSC {
@@ -509,17 +674,15 @@ impl ExecutionState {
 
         // run the VM on the bytecode contained in the operation
         match massa_sc_runtime::run_main(bytecode, *max_gas, &*self.execution_interface) {
-            Ok(_reamining_gas) => {}
+            Ok(remaining_gas) => context_guard!(self).get_total_gas_used(remaining_gas),
             Err(err) => {
                 // there was an error during bytecode execution
-                return Err(ExecutionError::RuntimeError(format!(
+                Err(ExecutionError::RuntimeError(format!(
                     "bytecode execution error: {}",
                     err
-                )));
+                )))
             }
         }
-
-        Ok(())
     }
 
     /// Execute an operation of type `CallSC`
@@ -530,11 +693,14 @@ impl ExecutionState {
     /// * `block_creator_addr`: address of the block creator
     /// * `operation_id`: ID of the operation
     /// * `sender_addr`: address of the sender
+    ///
+    /// # Returns
+    /// The gas consumed by the execution
     pub fn execute_callsc_op(
         &self,
         operation: &OperationType,
         sender_addr: Address,
-    ) -> Result<(), ExecutionError> {
+    ) -> Result<u64, ExecutionError> {
         // process CallSC operations only
         let (max_gas, target_addr, target_func, param, coins) = match &operation {
             OperationType::CallSC {
@@ -589,7 +755,7 @@ impl ExecutionState {
 
             // quit if there is no function to be called
             if target_func.is_empty() {
-                return Ok(());
+                return Ok(0);
             }
 
             // Load bytecode. Assume empty bytecode if not found.
@@ -604,17 +770,15 @@ impl ExecutionState {
             param,
             &*self.execution_interface,
         ) {
-            Ok(_reamining_gas) => {}
+            Ok(remaining_gas) => context_guard!(self).get_total_gas_used(remaining_gas),
             Err(err) => {
                 // there was an error during bytecode execution
-                return Err(ExecutionError::RuntimeError(format!(
+                Err(ExecutionError::RuntimeError(format!(
                     "bytecode execution error: {}",
                     err
-                )));
+                )))
             }
         }
-
-        Ok(())
     }
 
     /// Tries to execute an asynchronous message
@@ -634,6 +798,8 @@ impl ExecutionState {
             let mut context = context_guard!(self);
             context_snapshot = context.get_snapshot();
             context.max_gas = message.max_gas;
+            context.abi_gas_used = 0;
+            context.reset_call_event_usage();
             context.gas_price = message.gas_price;
             context.creator_address = None;
             context.stack = vec![
@@ -686,14 +852,20 @@ impl ExecutionState {
             (bytecode, data)
         };
 
-        // run the target function
+        // run the target function, the ABI calls being charged on top of the gas metered by the runtime
         if let Err(err) = massa_sc_runtime::run_function(
             &bytecode,
             message.max_gas,
             &message.handler,
             data,
             &*self.execution_interface,
-        ) {
+        )
+        .map_err(|err| err.to_string())
+        .and_then(|remaining_gas| {
+            context_guard!(self)
+                .get_total_gas_used(remaining_gas)
+                .map_err(|err| err.to_string())
+        }) {
             // execution failed: reset context to snapshot and reimburse sender
             let err = ExecutionError::RuntimeError(format!(
                 "async message runtime execution error: {}",
@@ -723,6 +895,29 @@ impl ExecutionState {
         slot: &Slot,
         exec_target: Option<&(BlockId, Storage)>,
         selector: Box<dyn SelectorController>,
+    ) -> ExecutionOutput {
+        self.execute_slot_on(
+            slot,
+            exec_target,
+            selector,
+            self.active_history.clone(),
+            false,
+        )
+    }
+
+    /// Executes a full slot on top of a given active history, without causing any changes to the state.
+    /// See `execute_slot`.
+    ///
+    /// # Arguments
+    /// * `active_history`: history of the slots executed before this one
+    /// * `trace`: true to record the ABI calls and the steps of the execution in the context
+    fn execute_slot_on(
+        &self,
+        slot: &Slot,
+        exec_target: Option<&(BlockId, Storage)>,
+        selector: Box<dyn SelectorController>,
+        active_history: Arc<RwLock<ActiveHistory>>,
+        trace: bool,
     ) -> ExecutionOutput {
         // Create a new execution context for the whole active slot
         let mut execution_context = ExecutionContext::active_slot(
@@ -730,21 +925,43 @@ impl ExecutionState {
             *slot,
             exec_target.as_ref().map(|(b_id, _)| *b_id),
             self.final_state.clone(),
-            self.active_history.clone(),
+            active_history,
         );
+        if trace {
+            execution_context.trace = Some(Default::default());
+        }
 
         // Get asynchronous messages to execute
         let messages = execution_context.take_async_batch(self.config.max_async_gas);
 
+        // Execution status of the operations of the executed block
+        let mut operation_statuses = Vec::new();
+
+        // Activities of the addresses involved in the executed block
+        let mut address_activities = Vec::new();
+
         // Apply the created execution context for slot execution
         *context_guard!(self) = execution_context;
 
         // Try executing asynchronous messages.
         // Effects are cancelled on failure and the sender is reimbursed.
         for (opt_bytecode, message) in messages {
-            if let Err(err) = self.execute_async_message(message, opt_bytecode) {
+            let origin = trace.then(|| ExecutionTraceOrigin::AsyncMessage {
+                sender: message.sender,
+                destination: message.destination,
+                handler: message.handler.clone(),
+            });
+            let result = self.execute_async_message(message, opt_bytecode);
+            if let Err(err) = &result {
                 debug!("failed executing async message: {}", err);
             }
+            if let Some(origin) = origin {
+                context_guard!(self).trace_step(
+                    origin,
+                    None,
+                    result.err().map(|err| err.to_string()),
+                );
+            }
         }
 
         // Check if there is a block at this slot
@@ -755,6 +972,14 @@ impl ExecutionState {
                 .get(block_id)
                 .expect("Missing block in storage.")
                 .clone();
+            address_activities.push(AddressActivity {
+                address: stored_block.creator_address,
+                slot: *slot,
+                is_final: false,
+                kind: AddressActivityKind::BlockProduced {
+                    block_id: *block_id,
+                },
+            });
 
             // gather all operations
             let operations = {
@@ -805,16 +1030,99 @@ impl ExecutionState {
             // Try executing the operations of this block in the order in which they appear in the block.
             // Errors are logged but do not interrupt the execution of the slot.
             for operation in operations.into_iter() {
-                if let Err(err) = self.execute_operation(
+                match self.execute_operation(
                     &operation,
                     stored_block.content.header.content.slot,
                     &mut remaining_block_gas,
                     &mut block_credits,
                 ) {
-                    debug!(
-                        "failed executing operation {} in block {}: {}",
-                        operation.id, block_id, err
-                    );
+                    Ok((gas_used, execution_error)) => {
+                        if trace {
+                            context_guard!(self).trace_step(
+                                ExecutionTraceOrigin::Operation(operation.id),
+                                Some((gas_used, remaining_block_gas)),
+                                execution_error.clone(),
+                            );
+                        }
+                        address_activities.push(AddressActivity {
+                            address: operation.creator_address,
+                            slot: *slot,
+                            is_final: false,
+                            kind: AddressActivityKind::OperationCreated {
+                                operation_id: operation.id,
+                                block_id: *block_id,
+                            },
+                        });
+                        let credit = match &operation.content.op {
+                            OperationType::Transaction {
+                                recipient_address,
+                                amount,
+                            } => Some((*recipient_address, *amount)),
+                            OperationType::CallSC {
+                                target_addr, coins, ..
+                            } if !coins.is_zero() => Some((*target_addr, *coins)),
+                            _ => None,
+                        };
+                        if let (Some((address, amount)), None) = (credit, &execution_error) {
+                            address_activities.push(AddressActivity {
+                                address,
+                                slot: *slot,
+                                is_final: false,
+                                kind: AddressActivityKind::OperationCredit {
+                                    operation_id: operation.id,
+                                    amount,
+                                },
+                            });
+                        }
+                        if let (OperationType::RollSell { roll_count }, None) =
+                            (&operation.content.op, &execution_error)
+                        {
+                            // the sale succeeded so the value of the rolls does not overflow
+                            address_activities.push(AddressActivity {
+                                address: operation.creator_address,
+                                slot: *slot,
+                                is_final: false,
+                                kind: AddressActivityKind::RollSell {
+                                    operation_id: operation.id,
+                                    roll_count: *roll_count,
+                                    credit_slot: get_roll_sale_credit_slot(
+                                        slot.get_cycle(self.config.periods_per_cycle),
+                                        self.config.periods_per_cycle,
+                                        self.config.thread_count,
+                                    ),
+                                    amount: self.config.roll_price.saturating_mul_u64(*roll_count),
+                                },
+                            });
+                        }
+                        operation_statuses.push(OperationStatusNotification {
+                            id: operation.id,
+                            involved_addresses: operation
+                                .get_ledger_involved_addresses()
+                                .into_iter()
+                                .collect(),
+                            fee: operation.content.fee,
+                            execution: OperationExecutionInfo {
+                                block_id: *block_id,
+                                slot: *slot,
+                                is_final: false,
+                                gas_used,
+                                execution_error,
+                            },
+                        });
+                    }
+                    Err(err) => {
+                        debug!(
+                            "failed executing operation {} in block {}: {}",
+                            operation.id, block_id, err
+                        );
+                        if trace {
+                            context_guard!(self).trace_step(
+                                ExecutionTraceOrigin::Operation(operation.id),
+                                Some((0, remaining_block_gas)),
+                                Some(err.to_string()),
+                            );
+                        }
+                    }
                 }
             }
 
@@ -844,7 +1152,22 @@ impl ExecutionState {
                     false,
                 ) {
                     Ok(_) => {
-                        remaining_credit = remaining_credit.saturating_sub(block_credit_part);
+                        remaining_credit = saturation::saturating_sub(
+                            remaining_credit,
+                            block_credit_part,
+                            self.config.log_saturations,
+                            "remaining block credit",
+                        );
+                        address_activities.push(AddressActivity {
+                            address: *endorsement_creator,
+                            slot: *slot,
+                            is_final: false,
+                            kind: AddressActivityKind::BlockReward {
+                                block_id: *block_id,
+                                role: RewardRole::EndorsementCreator,
+                                amount: block_credit_part,
+                            },
+                        });
                     }
                     Err(err) => {
                         debug!(
@@ -862,7 +1185,22 @@ impl ExecutionState {
                     false,
                 ) {
                     Ok(_) => {
-                        remaining_credit = remaining_credit.saturating_sub(block_credit_part);
+                        remaining_credit = saturation::saturating_sub(
+                            remaining_credit,
+                            block_credit_part,
+                            self.config.log_saturations,
+                            "remaining block credit",
+                        );
+                        address_activities.push(AddressActivity {
+                            address: endorsement_target_creator,
+                            slot: *slot,
+                            is_final: false,
+                            kind: AddressActivityKind::BlockReward {
+                                block_id: *block_id,
+                                role: RewardRole::EndorsedBlockCreator,
+                                amount: block_credit_part,
+                            },
+                        });
                     }
                     Err(err) => {
                         debug!(
@@ -874,13 +1212,21 @@ impl ExecutionState {
             }
 
             // Credit block creator with remaining_credit
-            if let Err(err) =
-                context.transfer_coins(None, Some(block_creator_addr), remaining_credit, false)
-            {
-                debug!(
+            match context.transfer_coins(None, Some(block_creator_addr), remaining_credit, false) {
+                Ok(_) => address_activities.push(AddressActivity {
+                    address: block_creator_addr,
+                    slot: *slot,
+                    is_final: false,
+                    kind: AddressActivityKind::BlockReward {
+                        block_id: *block_id,
+                        role: RewardRole::BlockCreator,
+                        amount: remaining_credit,
+                    },
+                }),
+                Err(err) => debug!(
                     "failed to credit {} coins to block creator {} on block execution: {}",
                     remaining_credit, block_creator_addr, err
-                )
+                ),
             }
         } else {
             // the slot is a miss, check who was supposed to be the creator and update production stats
@@ -890,8 +1236,23 @@ impl ExecutionState {
             context_guard!(self).update_production_stats(&producer_addr, *slot, None);
         }
 
+        // Report a slot execution that exceeded its time budget.
+        // It is only logged: the execution results must not depend on the speed of the node
+        if context_guard!(self).execution_timed_out {
+            warn!(
+                "execution of slot {} exceeded its time budget of {} ms",
+                slot,
+                self.config.max_slot_execution_time.to_millis()
+            );
+        }
+
         // Finish slot and return the execution output
-        context_guard!(self).settle_slot()
+        let mut exec_out = context_guard!(self).settle_slot();
+        exec_out.operation_statuses = operation_statuses;
+        // the deferred credits are paid when settling the slot, after the block
+        address_activities.append(&mut exec_out.address_activities);
+        exec_out.address_activities = address_activities;
+        exec_out
     }
 
     /// Execute a candidate slot
@@ -923,19 +1284,32 @@ impl ExecutionState {
             self.active_history
                 .write()
                 .truncate_from(slot, self.config.thread_count);
+            self.active_slot_inputs
+                .retain(|active_slot, _| active_slot < slot);
             self.active_cursor = slot
                 .get_prev_slot(self.config.thread_count)
                 .expect("overflow when iterating on slots");
+            self.readonly_cache.invalidate();
         }
 
         let exec_out = self.execute_slot(slot, exec_target, selector);
         debug!("execute_candidate_slot: execution finished");
+        self.active_slot_inputs.insert(*slot, exec_target.cloned());
 
         // apply execution output to active state
         self.apply_active_execution_output(exec_out);
         debug!("execute_candidate_slot: execution state applied");
     }
 
+    /// Sandbox mode: drops the whole active history so that the candidate state matches the final state
+    #[cfg(feature = "sandbox")]
+    pub fn reset_active_history(&mut self) {
+        self.active_history.write().0.clear();
+        self.active_slot_inputs.clear();
+        self.active_cursor = self.final_cursor;
+        self.readonly_cache.invalidate();
+    }
+
     /// Execute an SCE-final slot
     pub fn execute_final_slot(
         &mut self,
@@ -957,6 +1331,10 @@ impl ExecutionState {
             return;
         }
 
+        // the final slot leaves the active history: it can not be replayed anymore
+        self.active_slot_inputs
+            .retain(|active_slot, _| active_slot > slot);
+
         // check if the final slot execution result is already cached at the front of the speculative execution history
         let first_exec_output = self.active_history.write().0.pop_front();
         if let Some(exec_out) = first_exec_output {
@@ -965,6 +1343,7 @@ impl ExecutionState {
 
                 // apply the cached output and return
                 self.apply_final_execution_output(exec_out);
+                self.update_miss_ratio_warnings(slot, selector.as_ref());
 
                 debug!("execute_final_slot: found in cache, applied cache");
                 return;
@@ -985,16 +1364,248 @@ impl ExecutionState {
 
         // truncate the whole execution queue
         self.active_history.write().0.clear();
+        self.active_slot_inputs.clear();
         self.active_cursor = self.final_cursor;
+        self.readonly_cache.invalidate();
 
         // execute slot
         debug!("execute_final_slot: execution started");
-        let exec_out = self.execute_slot(slot, exec_target, selector);
+        let exec_out = self.execute_slot(slot, exec_target, selector.clone());
         debug!("execute_final_slot: execution finished");
 
         // apply execution output to final state
         self.apply_final_execution_output(exec_out);
         debug!("execute_final_slot: execution result applied");
+
+        self.update_miss_ratio_warnings(slot, selector.as_ref());
+    }
+
+    /// Projects the end-of-cycle miss ratio of the block producers of the cycle of a newly final slot,
+    /// and warns about the ones on track to exceed the max miss ratio and lose their rolls.
+    /// Projections are refreshed once per period, when its last thread becomes final.
+    fn update_miss_ratio_warnings(&mut self, slot: &Slot, selector: &dyn SelectorController) {
+        if slot.thread + 1 != self.config.thread_count {
+            return;
+        }
+        // the stats of the cycle are settled at its last slot
+        if slot.is_last_of_cycle(self.config.periods_per_cycle, self.config.thread_count) {
+            self.miss_ratio_warnings.clear();
+            return;
+        }
+        let cycle = slot.get_cycle(self.config.periods_per_cycle);
+        let production_stats = match self
+            .final_state
+            .read()
+            .pos_state
+            .get_all_production_stats(cycle)
+        {
+            Some(stats) => stats.clone(),
+            None => {
+                self.miss_ratio_warnings.clear();
+                return;
+            }
+        };
+
+        // count the draws left in the cycle
+        let last_slot = Slot::new_last_of_cycle(
+            cycle,
+            self.config.periods_per_cycle,
+            self.config.thread_count,
+        )
+        .expect("unexpected cycle overflow in update_miss_ratio_warnings");
+        let mut remaining_draws: PreHashMap<Address, u64> = PreHashMap::default();
+        let mut cur_slot = *slot;
+        while cur_slot < last_slot {
+            cur_slot = cur_slot
+                .get_next_slot(self.config.thread_count)
+                .expect("unexpected slot overflow in update_miss_ratio_warnings");
+            match selector.get_producer(cur_slot) {
+                Ok(producer) => *remaining_draws.entry(producer).or_default() += 1,
+                Err(err) => {
+                    debug!(
+                        "could not project the miss ratios of cycle {}: {}",
+                        cycle, err
+                    );
+                    return;
+                }
+            }
+        }
+
+        let max_miss_ratio = self.config.max_miss_ratio;
+        let mut warnings = Vec::new();
+        for (address, stats) in production_stats {
+            if stats.is_satisfying(&max_miss_ratio) {
+                continue;
+            }
+            let remaining = remaining_draws.get(&address).copied().unwrap_or_default();
+            let opportunities = stats
+                .block_success_count
+                .saturating_add(stats.block_failure_count);
+            let end_of_cycle_opportunities = opportunities.saturating_add(remaining);
+            // max number of misses allowed at the end of the cycle
+            let allowed_failures =
+                (max_miss_ratio * Ratio::from_integer(end_of_cycle_opportunities)).to_integer();
+            let required_successes = stats
+                .block_failure_count
+                .saturating_add(remaining)
+                .saturating_sub(allowed_failures);
+            warnings.push(MissRatioProjection {
+                address,
+                cycle,
+                block_success_count: stats.block_success_count,
+                block_failure_count: stats.block_failure_count,
+                remaining_draws: remaining,
+                // keeping the current rate over the remaining draws leaves the ratio unchanged
+                projected_miss_ratio: stats.block_failure_count as f64 / opportunities as f64,
+                required_successes: (required_successes <= remaining).then_some(required_successes),
+            });
+        }
+        warnings.sort_unstable_by_key(|projection| projection.address);
+
+        // only log the addresses that just got on track to lose their rolls
+        for projection in &warnings {
+            if self
+                .miss_ratio_warnings
+                .iter()
+                .any(|prev| prev.address == projection.address && prev.cycle == projection.cycle)
+            {
+                continue;
+            }
+            warn!(
+                "block producer on track to exceed the max miss ratio and lose its rolls: {}",
+                projection
+            );
+        }
+        self.miss_ratio_warnings = warnings;
+    }
+
+    /// Simulates the operations of a candidate block.
+    /// They are executed in a fresh speculative context on top of the latest candidate state,
+    /// as they would be in a block at the requested slot, but the context is then discarded:
+    /// neither the active history nor the final state are affected.
+    /// Asynchronous messages are not executed.
+    /// Operations whose signature does not hold for the chain id of the network are not executed either,
+    /// as the other nodes would refuse the block.
+    ///
+    /// # Arguments
+    /// * `req`: the slot and operations of the candidate block
+    ///
+    /// # Returns
+    /// The outcome of each operation, with the total gas and fees of the block
+    pub(crate) fn simulate_block(&self, req: BlockSimulationRequest) -> BlockSimulationOutput {
+        // create an isolated execution context for the candidate block
+        *context_guard!(self) = ExecutionContext::active_slot(
+            self.config.clone(),
+            req.slot,
+            None,
+            self.final_state.clone(),
+            self.active_history.clone(),
+        );
+
+        // execute the operations in block order, as execute_slot does
+        let mut remaining_block_gas = self.config.max_gas_per_block;
+        let mut block_fees = Amount::zero();
+        let operations = req
+            .operations
+            .iter()
+            .map(|operation| {
+                if let Err(err) = operation.verify_operation_signature(
+                    self.config.chain_id,
+                    self.config.chain_id_activation_period,
+                ) {
+                    return SimulatedOperation {
+                        operation_id: operation.id,
+                        success: false,
+                        gas_used: 0,
+                        error: Some(format!("invalid signature: {}", err)),
+                    };
+                }
+                match self.execute_operation(
+                    operation,
+                    req.slot,
+                    &mut remaining_block_gas,
+                    &mut block_fees,
+                ) {
+                    Ok((gas_used, execution_error)) => SimulatedOperation {
+                        operation_id: operation.id,
+                        success: execution_error.is_none(),
+                        gas_used,
+                        error: execution_error,
+                    },
+                    Err(err) => SimulatedOperation {
+                        operation_id: operation.id,
+                        success: false,
+                        gas_used: 0,
+                        error: Some(err.to_string()),
+                    },
+                }
+            })
+            .collect();
+
+        BlockSimulationOutput {
+            operations,
+            total_gas: self
+                .config
+                .max_gas_per_block
+                .saturating_sub(remaining_block_gas),
+            total_fees: block_fees,
+        }
+    }
+
+    /// Re-executes a slot of the active history in trace mode, in an isolated context:
+    /// the slot is executed again on top of the final state and of the active history preceding it,
+    /// then the context is discarded, neither the active history nor the final state being affected.
+    /// Final slots can not be replayed as the final state does not keep the state preceding them.
+    ///
+    /// # Arguments
+    /// * `slot`: slot to replay
+    /// * `selector`: access to the block producer draws, needed to replay misses
+    ///
+    /// # Returns
+    /// The trace of the ABI calls and gas consumption of each execution step of the slot,
+    /// or an error if the slot is not in the active history
+    pub(crate) fn replay_slot_with_trace(
+        &self,
+        slot: Slot,
+        selector: Box<dyn SelectorController>,
+    ) -> Result<SlotExecutionTrace, ExecutionError> {
+        let exec_target = match self.active_slot_inputs.get(&slot) {
+            Some(exec_target) => exec_target,
+            None if slot <= self.final_cursor => {
+                return Err(ExecutionError::RuntimeError(format!(
+                    "slot {} is final and can not be replayed anymore",
+                    slot
+                )))
+            }
+            None => {
+                return Err(ExecutionError::RuntimeError(format!(
+                    "slot {} has not been executed yet",
+                    slot
+                )))
+            }
+        };
+
+        // history of the slots executed before the replayed one
+        let mut active_history = ActiveHistory(self.active_history.read().0.clone());
+        active_history.truncate_from(&slot, self.config.thread_count);
+
+        self.execute_slot_on(
+            &slot,
+            exec_target.as_ref(),
+            selector,
+            Arc::new(RwLock::new(active_history)),
+            true,
+        );
+        let steps = context_guard!(self)
+            .trace
+            .take()
+            .map(|trace| trace.steps)
+            .unwrap_or_default();
+        Ok(SlotExecutionTrace {
+            slot,
+            block_id: exec_target.as_ref().map(|(block_id, _)| *block_id),
+            steps,
+        })
     }
 
     /// Runs a read-only execution request.
@@ -1014,6 +1625,12 @@ impl ExecutionState {
         // TODO ensure that speculative things are reset after every execution ends (incl. on error and readonly)
         // otherwise, on prod stats accumulation etc... from the API we might be counting the remainder of this speculative execution
 
+        // serve the output of an identical request executed since the latest active slot execution
+        let request_hash = hash_readonly_request(&req);
+        if let Some(output) = self.readonly_cache.get(self.active_cursor, &request_hash) {
+            return Ok(output);
+        }
+
         // set the execution slot to be the one after the latest executed active slot
         let slot = self
             .active_cursor
@@ -1027,6 +1644,7 @@ impl ExecutionState {
             req.max_gas,
             req.simulated_gas_price,
             req.call_stack,
+            req.deployment_simulation,
             self.final_state.clone(),
             self.active_history.clone(),
         );
@@ -1067,11 +1685,30 @@ impl ExecutionState {
         };
 
         // return the execution output
-        let execution_output = context_guard!(self).settle_slot();
-        Ok(ReadOnlyExecutionOutput {
-            out: execution_output,
-            gas_cost: req.max_gas.saturating_sub(remaining_gas),
-        })
+        let mut context = context_guard!(self);
+        let gas_cost = context.get_total_gas_used(remaining_gas)?;
+        let created_addresses = context.get_created_addresses();
+        let mut storage_cost = Amount::zero();
+        for address in &created_addresses {
+            storage_cost = storage_cost
+                .checked_add(context.get_storage_cost(address)?)
+                .ok_or_else(|| {
+                    ExecutionError::RuntimeError("overflow when summing storage costs".to_string())
+                })?;
+        }
+        let output = ReadOnlyExecutionOutput {
+            out: context.settle_slot(),
+            gas_cost,
+            created_addresses,
+            storage_cost,
+            event_usage: context.slot_event_usage,
+        };
+        // outputs depending on the time or on random draws are not served again
+        if !context.time_or_randomness_read {
+            self.readonly_cache
+                .insert(self.active_cursor, request_hash, output.clone());
+        }
+        Ok(output)
     }
 
     /// Gets a balance both at the latest final and candidate executed slots
@@ -1080,7 +1717,10 @@ impl ExecutionState {
         address: &Address,
     ) -> (Option<Amount>, Option<Amount>) {
         let final_balance = self.final_state.read().ledger.get_balance(address);
-        let search_result = self.active_history.read().fetch_balance(address);
+        let search_result = self
+            .active_history
+            .read()
+            .fetch_balance_up_to(address, self.config.max_candidate_read_depth);
         (
             final_balance,
             match search_result {
@@ -1097,7 +1737,7 @@ impl ExecutionState {
         let active_rolls = self
             .active_history
             .read()
-            .fetch_roll_count(address)
+            .fetch_roll_count_up_to(address, self.config.max_candidate_read_depth)
             .unwrap_or(final_rolls);
         (final_rolls, active_rolls)
     }
@@ -1112,7 +1752,11 @@ impl ExecutionState {
         let search_result = self
             .active_history
             .read()
-            .fetch_active_history_data_entry(address, key);
+            .fetch_active_history_data_entry_up_to(
+                address,
+                key,
+                self.config.max_candidate_read_depth,
+            );
         (
             final_entry.clone(),
             match search_result {
@@ -1123,29 +1767,152 @@ impl ExecutionState {
         )
     }
 
-    /// Get every final and active datastore key of the given address
+    /// Get a page of the final addresses holding a non-empty bytecode.
+    ///
+    /// # Returns
+    /// The contracts of the page sorted by address and the cursor of the next one, `Finished` if there are no more contracts
+    pub fn get_final_contracts(
+        &self,
+        cursor: StreamingStep<Address>,
+        max_count: usize,
+    ) -> (Vec<ContractInfo>, StreamingStep<Address>) {
+        self.final_state
+            .read()
+            .ledger
+            .get_contracts_page(cursor, max_count)
+    }
+
+    /// Gets the outcome of the execution of an operation, looking at the candidate executions first.
+    ///
+    /// # Returns
+    /// The outcome of the execution, `None` if the operation was not executed,
+    /// or if its final execution is too old to be remembered
+    pub fn get_op_execution_info(&self, op_id: &OperationId) -> Option<OperationExecutionInfo> {
+        self.active_history
+            .read()
+            .0
+            .iter()
+            .rev()
+            .flat_map(|output| output.operation_statuses.iter())
+            .find(|status| &status.id == op_id)
+            .map(|status| status.execution.clone())
+            .or_else(|| self.final_op_executions.get(op_id).cloned())
+    }
+
+    /// Get the activities of an address, in chronological order, final activities first
+    ///
+    /// # Arguments
+    /// * `address`: address to get the history of
+    /// * `start`: optional first slot of the activities (included)
+    /// * `end`: optional slot bounding the activities (excluded)
+    ///
+    /// # Returns
+    /// The activities, the final ones that are too old to be remembered being missing
+    pub fn get_address_history(
+        &self,
+        address: &Address,
+        start: Option<Slot>,
+        end: Option<Slot>,
+    ) -> Vec<AddressActivity> {
+        let in_range = |activity: &&AddressActivity| {
+            start.map_or(true, |start| activity.slot >= start)
+                && end.map_or(true, |end| activity.slot < end)
+        };
+        let mut history: Vec<AddressActivity> = self
+            .final_address_activities
+            .get(address)
+            .into_iter()
+            .flatten()
+            .filter(in_range)
+            .cloned()
+            .collect();
+        history.extend(
+            self.active_history
+                .read()
+                .0
+                .iter()
+                .flat_map(|output| output.address_activities.iter())
+                .filter(|activity| &activity.address == address)
+                .filter(in_range)
+                .cloned(),
+        );
+        history
+    }
+
+    /// Get a proof of inclusion of the final balance of an address, or of one of its final datastore entries.
+    ///
+    /// # Returns
+    /// The proof against the current final ledger root, `None` if the entry does not exist
+    pub fn get_final_ledger_proof(
+        &self,
+        address: &Address,
+        datastore_key: Option<&[u8]>,
+    ) -> Option<LedgerEntryProof> {
+        self.final_state
+            .read()
+            .ledger
+            .get_entry_proof(address, datastore_key)
+    }
+
+    /// Get a page of the final and active datastore keys of the given address starting with `prefix`.
+    ///
+    /// The page covers the keys following the cursor, up to the last of the `max_count` final keys returned.
+    /// The candidate keys are restricted to that same key range, and the returned cursor points to the next page.
     pub fn get_final_and_candidate_datastore_keys(
         &self,
         addr: &Address,
-    ) -> (BTreeSet<Vec<u8>>, BTreeSet<Vec<u8>>) {
-        // here, get the final keys from the final ledger, and make a copy of it for the candidate list
-        let final_keys = self.final_state.read().ledger.get_datastore_keys(addr);
+        prefix: &[u8],
+        cursor: StreamingStep<Vec<u8>>,
+        max_count: usize,
+    ) -> (BTreeSet<Vec<u8>>, BTreeSet<Vec<u8>>, StreamingStep<Vec<u8>>) {
+        // here, get the final keys of the page from the final ledger, and make a copy of it for the candidate list
+        let last_key = match &cursor {
+            StreamingStep::Ongoing(last_key) => Some(last_key.clone()),
+            _ => None,
+        };
+        let (final_keys, next_cursor) = self
+            .final_state
+            .read()
+            .ledger
+            .get_datastore_keys_page(addr, prefix, cursor, max_count);
         let mut candidate_keys = final_keys.clone();
 
+        // the candidate keys are limited to the key range covered by the final page
+        let in_page = |key: &Vec<u8>| {
+            key.starts_with(prefix)
+                && last_key.as_ref().map_or(true, |last_key| key > last_key)
+                && match &next_cursor {
+                    StreamingStep::Ongoing(page_end) => key <= page_end,
+                    _ => true,
+                }
+        };
+
         // here, traverse the history from oldest to newest, applying additions and deletions
-        for output in &self.active_history.read().0 {
+        for output in self
+            .active_history
+            .read()
+            .outputs_up_to(self.config.max_candidate_read_depth)
+        {
             match output.state_changes.ledger_changes.get(addr) {
                 // address absent from the changes
                 None => (),
 
                 // address ledger entry being reset to an absolute new list of keys
                 Some(SetUpdateOrDelete::Set(new_ledger_entry)) => {
-                    candidate_keys = new_ledger_entry.datastore.keys().cloned().collect();
+                    candidate_keys = new_ledger_entry
+                        .datastore
+                        .keys()
+                        .filter(|key| in_page(key))
+                        .cloned()
+                        .collect();
                 }
 
                 // address ledger entry being updated
                 Some(SetUpdateOrDelete::Update(entry_updates)) => {
                     for (ds_key, ds_update) in &entry_updates.datastore {
+                        if !in_page(ds_key) {
+                            continue;
+                        }
                         match ds_update {
                             SetOrDelete::Set(_) => candidate_keys.insert(ds_key.clone()),
                             SetOrDelete::Delete => candidate_keys.remove(ds_key),
@@ -1160,7 +1927,43 @@ impl ExecutionState {
             }
         }
 
-        (final_keys, candidate_keys)
+        (final_keys, candidate_keys, next_cursor)
+    }
+
+    /// Builds the read-only request resolving `name`
+    /// through the `resolve` function of the name registry contract
+    ///
+    /// # Returns
+    /// The request, or an error if no name registry is configured
+    pub fn name_resolution_request(
+        &self,
+        name: &str,
+    ) -> Result<ReadOnlyExecutionRequest, ExecutionError> {
+        let registry = self.config.name_registry_address.ok_or_else(|| {
+            ExecutionError::NameResolutionError("no name registry is configured".into())
+        })?;
+        Ok(ReadOnlyExecutionRequest {
+            max_gas: self.config.name_registry_max_gas,
+            simulated_gas_price: Amount::zero(),
+            call_stack: vec![ExecutionStackElement {
+                address: registry,
+                coins: Amount::zero(),
+                owned_addresses: vec![registry],
+                operation_datastore: None,
+            }],
+            target: ReadOnlyExecutionTarget::FunctionCall {
+                target_addr: registry,
+                target_func: "resolve".to_string(),
+                parameter: name.to_string(),
+            },
+            request_id: None,
+            deployment_simulation: None,
+        })
+    }
+
+    /// Writes the pending changes of the final ledger to disk, used before stopping
+    pub fn flush_final_ledger(&self) -> Result<(), LedgerError> {
+        self.final_state.read().ledger.flush()
     }
 
     /// Returns for a given cycle the stakers taken into account
@@ -1183,6 +1986,73 @@ impl ExecutionState {
         }
     }
 
+    /// Gets the proof-of-stake statistics of a cycle from the final state.
+    /// The stakers are the addresses owning rolls at the end of the cycle or having production stats during it,
+    /// sorted by address: the returned page starts after `start_after` and holds at most `max_count` of them.
+    ///
+    /// # Returns
+    /// `None` if the cycle is not in the final cycle history
+    pub fn get_cycle_stats(
+        &self,
+        cycle: u64,
+        start_after: Option<Address>,
+        max_count: usize,
+    ) -> Option<CycleStats> {
+        let final_state = self.final_state.read();
+        let cycle_index = final_state.pos_state.get_cycle_index(cycle)?;
+        let cycle_info = &final_state.pos_state.cycle_history[cycle_index];
+        let stakers: BTreeSet<Address> = cycle_info
+            .roll_counts
+            .keys()
+            .chain(cycle_info.production_stats.keys())
+            .copied()
+            .collect();
+        let mut stats = CycleStats {
+            cycle,
+            complete: cycle_info.complete,
+            staker_count: stakers.len() as u64,
+            roll_count: 0,
+            ok_count: 0,
+            nok_count: 0,
+            miss_ratio_exceeded_count: 0,
+            stakers: Vec::new(),
+        };
+        for address in stakers {
+            let roll_count = cycle_info
+                .roll_counts
+                .get(&address)
+                .copied()
+                .unwrap_or_default();
+            let production = cycle_info
+                .production_stats
+                .get(&address)
+                .copied()
+                .unwrap_or_default();
+            let miss_ratio_exceeded = !production.is_satisfying(&self.config.max_miss_ratio);
+            stats.roll_count = stats.roll_count.saturating_add(roll_count);
+            stats.ok_count = stats
+                .ok_count
+                .saturating_add(production.block_success_count);
+            stats.nok_count = stats
+                .nok_count
+                .saturating_add(production.block_failure_count);
+            if miss_ratio_exceeded {
+                stats.miss_ratio_exceeded_count += 1;
+            }
+            if stats.stakers.len() < max_count
+                && start_after.map_or(true, |start_after| address > start_after)
+            {
+                stats.stakers.push(StakerCycleStats {
+                    address,
+                    roll_count,
+                    ok_count: production.block_success_count,
+                    nok_count: production.block_failure_count,
+                    miss_ratio_exceeded,
+                });
+            }
+        }
+        Some(stats)
+    }
     /// Gets execution events optionally filtered by:
     /// * start slot
     /// * end slot
@@ -1200,8 +2070,7 @@ impl ExecutionState {
             Some(false) => self
                 .active_history
                 .read()
-                .0
-                .iter()
+                .outputs_up_to(self.config.max_candidate_read_depth)
                 .flat_map(|item| item.events.get_filtered_sc_output_events(&filter))
                 .collect(),
             None => self
@@ -1211,8 +2080,7 @@ impl ExecutionState {
                 .chain(
                     self.active_history
                         .read()
-                        .0
-                        .iter()
+                        .outputs_up_to(self.config.max_candidate_read_depth)
                         .flat_map(|item| item.events.get_filtered_sc_output_events(&filter)),
                 )
                 .collect(),
@@ -1264,6 +2132,47 @@ impl ExecutionState {
         context_guard!(self).get_address_cycle_infos(address, self.config.periods_per_cycle)
     }
 
+    /// Get a page of the deferred credits of all addresses scheduled in a slot range,
+    /// sorted by slot then address
+    ///
+    /// # Arguments
+    /// * `start`: first slot of the range (included)
+    /// * `end`: slot bounding the range (excluded)
+    /// * `cursor`: `Started` to get the first page, `Ongoing((slot, address))` to get the credits following that one
+    /// * `max_count`: maximum number of credits to return
+    pub fn get_deferred_credits_page(
+        &self,
+        start: Slot,
+        end: Slot,
+        cursor: StreamingStep<(Slot, Address)>,
+        max_count: usize,
+    ) -> (Vec<DeferredCreditInfo>, StreamingStep<(Slot, Address)>) {
+        let credits = context_guard!(self).get_deferred_credits_range(start, end);
+        let mut remaining = match cursor {
+            StreamingStep::Started => credits.range(..),
+            StreamingStep::Ongoing(last) => credits.range((Excluded(last), Unbounded)),
+            StreamingStep::Finished => return (Vec::new(), StreamingStep::Finished),
+        }
+        .peekable();
+        let mut page = Vec::with_capacity(max_count.min(credits.len()));
+        while page.len() < max_count {
+            match remaining.next() {
+                Some((&(slot, address), &amount)) => page.push(DeferredCreditInfo {
+                    slot,
+                    address,
+                    amount,
+                }),
+                None => break,
+            }
+        }
+        // more credits remain: the next page starts after the last returned credit
+        let next_cursor = match (remaining.peek(), page.last()) {
+            (Some(_), Some(last)) => StreamingStep::Ongoing((last.slot, last.address)),
+            _ => StreamingStep::Finished,
+        };
+        (page, next_cursor)
+    }
+
     /// Get future deferred credits of an address
     pub fn get_address_future_deferred_credits(&self, address: &Address) -> BTreeMap<Slot, Amount> {
         context_guard!(self).get_address_future_deferred_credits(address, self.config.thread_count)
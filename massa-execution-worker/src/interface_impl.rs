@@ -8,10 +8,11 @@
 use crate::context::ExecutionContext;
 use anyhow::{anyhow, bail, Result};
 use massa_async_pool::AsyncMessage;
-use massa_execution_exports::ExecutionConfig;
 use massa_execution_exports::ExecutionStackElement;
+use massa_execution_exports::{ExecutionConfig, ExecutionError, ABI_NAMES};
 use massa_models::{
-    address::Address, amount::Amount, slot::Slot, timeslots::get_block_slot_timestamp,
+    address::Address, amount::Amount, api::AbiCallTrace, slot::Slot,
+    timeslots::get_block_slot_timestamp,
 };
 use massa_sc_runtime::{Interface, InterfaceClone};
 use parking_lot::Mutex;
@@ -20,6 +21,14 @@ use std::str::FromStr;
 use std::sync::Arc;
 use tracing::debug;
 
+/// Parses a public key given either in its Massa text form (`P` prefix and version)
+/// or as the legacy `bs58` check encoding of its bytes
+fn parse_public_key(public_key: &str) -> Result<massa_signature::PublicKey> {
+    massa_signature::PublicKey::from_str(public_key)
+        .or_else(|_| massa_signature::PublicKey::from_bs58_check(public_key))
+        .map_err(|err| anyhow!("invalid public key {}: {}", public_key, err))
+}
+
 /// helper for locking the context mutex
 macro_rules! context_guard {
     ($self:ident) => {
@@ -27,6 +36,34 @@ macro_rules! context_guard {
     };
 }
 
+/// called at the start of every ABI: records the call in the context trace when a slot is replayed in trace mode,
+/// the arguments being only formatted in that case, watches the execution time budget of the slot,
+/// then charges the extra gas of the ABI from the gas cost table.
+/// The name has to be listed in `ABI_NAMES` for the gas cost table to price it.
+macro_rules! abi_call {
+    ($self:ident, $name:literal $(, $arg:expr)*) => {{
+        debug_assert!(ABI_NAMES.contains(&$name), "ABI {} missing from ABI_NAMES", $name);
+        let mut context = context_guard!($self);
+        if let Some(trace) = context.trace.as_mut() {
+            trace.abi_calls.push(AbiCallTrace {
+                name: $name.to_string(),
+                arguments: vec![$(format!("{:?}", $arg)),*],
+            });
+        }
+        context.check_execution_deadline();
+        context.charge_abi_gas($name)?;
+    }};
+}
+
+/// byte array argument of a traced ABI call, summarized by its length
+struct TracedBytes(usize);
+
+impl std::fmt::Debug for TracedBytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{} bytes>", self.0)
+    }
+}
+
 /// an implementation of the Interface trait (see massa-sc-runtime crate)
 #[derive(Clone)]
 pub(crate) struct InterfaceImpl {
@@ -61,6 +98,7 @@ impl InterfaceClone for InterfaceImpl {
 impl Interface for InterfaceImpl {
     /// prints a message in the node logs at log level 3 (debug)
     fn print(&self, message: &str) -> Result<()> {
+        abi_call!(self, "print", message);
         debug!("SC print: {}", message);
         Ok(())
     }
@@ -77,6 +115,7 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// The target bytecode or an error
     fn init_call(&self, address: &str, raw_coins: u64) -> Result<Vec<u8>> {
+        abi_call!(self, "init_call", address, raw_coins);
         // get target address
         let to_address = massa_models::address::Address::from_str(address)?;
 
@@ -95,6 +134,22 @@ impl Interface for InterfaceImpl {
             _ => bail!("failed to read call stack current address"),
         };
 
+        // check that the call stack can grow
+        if context.stack.len() >= self.config.max_call_stack_depth {
+            let call_chain = context
+                .stack
+                .iter()
+                .map(|elem| elem.address.to_string())
+                .chain(std::iter::once(to_address.to_string()))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(ExecutionError::CallStackOverflow(format!(
+                "maximum depth of {} reached by call chain {}",
+                self.config.max_call_stack_depth, call_chain
+            ))
+            .into());
+        }
+
         // transfer coins from caller to target address
         let coins = massa_models::amount::Amount::from_raw(raw_coins);
         if let Err(err) = context.transfer_coins(Some(from_address), Some(to_address), coins, true)
@@ -123,6 +178,7 @@ impl Interface for InterfaceImpl {
     /// Called to finish the call process after a bytecode calls a function from another one.
     /// This function just pops away the top element of the call stack.
     fn finish_call(&self) -> Result<()> {
+        abi_call!(self, "finish_call");
         let mut context = context_guard!(self);
 
         if context.stack.pop().is_none() {
@@ -138,6 +194,7 @@ impl Interface for InterfaceImpl {
     /// The raw representation (no decimal factor) of the balance of the address,
     /// or zero if the address is not found in the ledger.
     fn get_balance(&self) -> Result<u64> {
+        abi_call!(self, "get_balance");
         let context = context_guard!(self);
         let address = context.get_current_address()?;
         Ok(context.get_balance(&address).unwrap_or_default().to_raw())
@@ -152,6 +209,7 @@ impl Interface for InterfaceImpl {
     /// The raw representation (no decimal factor) of the balance of the address,
     /// or zero if the address is not found in the ledger.
     fn get_balance_for(&self, address: &str) -> Result<u64> {
+        abi_call!(self, "get_balance_for", address);
         let address = massa_models::address::Address::from_str(address)?;
         Ok(context_guard!(self)
             .get_balance(&address)
@@ -168,6 +226,7 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// The string representation of the newly created address
     fn create_module(&self, bytecode: &[u8]) -> Result<String> {
+        abi_call!(self, "create_module", TracedBytes(bytecode.len()));
         match context_guard!(self).create_new_sc_address(bytecode.to_vec()) {
             Ok(addr) => Ok(addr.to_string()),
             Err(err) => bail!("couldn't create new SC address: {}", err),
@@ -183,6 +242,7 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// The datastore value matching the provided key, if found, otherwise an error.
     fn raw_get_data_for(&self, address: &str, key: &str) -> Result<Vec<u8>> {
+        abi_call!(self, "raw_get_data_for", address, key);
         let addr = &massa_models::address::Address::from_str(address)?;
         let context = context_guard!(self);
         match context.get_data_entry(addr, key.as_bytes()) {
@@ -200,6 +260,13 @@ impl Interface for InterfaceImpl {
     /// * key: string key of the datastore entry to set
     /// * value: new value to set
     fn raw_set_data_for(&self, address: &str, key: &str, value: &[u8]) -> Result<()> {
+        abi_call!(
+            self,
+            "raw_set_data_for",
+            address,
+            key,
+            TracedBytes(value.len())
+        );
         let addr = massa_models::address::Address::from_str(address)?;
         let mut context = context_guard!(self);
         context.set_data_entry(&addr, key.as_bytes().to_vec(), value.to_vec())?;
@@ -214,6 +281,13 @@ impl Interface for InterfaceImpl {
     /// * key: string key of the datastore entry
     /// * value: value to append
     fn raw_append_data_for(&self, address: &str, key: &str, value: &[u8]) -> Result<()> {
+        abi_call!(
+            self,
+            "raw_append_data_for",
+            address,
+            key,
+            TracedBytes(value.len())
+        );
         let addr = massa_models::address::Address::from_str(address)?;
         context_guard!(self).append_data_entry(&addr, key.as_bytes().to_vec(), value.to_vec())?;
         Ok(())
@@ -226,6 +300,7 @@ impl Interface for InterfaceImpl {
     /// * address: string representation of the address
     /// * key: string key of the datastore entry to delete
     fn raw_delete_data_for(&self, address: &str, key: &str) -> Result<()> {
+        abi_call!(self, "raw_delete_data_for", address, key);
         let addr = &massa_models::address::Address::from_str(address)?;
         context_guard!(self).delete_data_entry(addr, key.as_bytes())?;
         Ok(())
@@ -240,6 +315,7 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// true if the address exists and has the entry matching the provided key in its datastore, otherwise false
     fn has_data_for(&self, address: &str, key: &str) -> Result<bool> {
+        abi_call!(self, "has_data_for", address, key);
         let addr = massa_models::address::Address::from_str(address)?;
         let context = context_guard!(self);
         Ok(context.has_data_entry(&addr, key.as_bytes()))
@@ -253,6 +329,7 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// The datastore value matching the provided key, if found, otherwise an error.
     fn raw_get_data(&self, key: &str) -> Result<Vec<u8>> {
+        abi_call!(self, "raw_get_data", key);
         let context = context_guard!(self);
         let addr = context.get_current_address()?;
         match context.get_data_entry(&addr, key.as_bytes()) {
@@ -270,6 +347,7 @@ impl Interface for InterfaceImpl {
     /// * key: string key of the datastore entry to set
     /// * value: new value to set
     fn raw_set_data(&self, key: &str, value: &[u8]) -> Result<()> {
+        abi_call!(self, "raw_set_data", key, TracedBytes(value.len()));
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
         context.set_data_entry(&addr, key.as_bytes().to_vec(), value.to_vec())?;
@@ -284,6 +362,7 @@ impl Interface for InterfaceImpl {
     /// * key: string key of the datastore entry
     /// * value: value to append
     fn raw_append_data(&self, key: &str, value: &[u8]) -> Result<()> {
+        abi_call!(self, "raw_append_data", key, TracedBytes(value.len()));
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
         context.append_data_entry(&addr, key.as_bytes().to_vec(), value.to_vec())?;
@@ -296,6 +375,7 @@ impl Interface for InterfaceImpl {
     /// # Arguments
     /// * key: string key of the datastore entry to delete
     fn raw_delete_data(&self, key: &str) -> Result<()> {
+        abi_call!(self, "raw_delete_data", key);
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
         context.delete_data_entry(&addr, key.as_bytes())?;
@@ -310,6 +390,7 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// true if the address exists and has the entry matching the provided key in its datastore, otherwise false
     fn has_data(&self, key: &str) -> Result<bool> {
+        abi_call!(self, "has_data", key);
         let context = context_guard!(self);
         let addr = context.get_current_address()?;
         Ok(context.has_data_entry(&addr, key.as_bytes()))
@@ -321,6 +402,7 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// A list of keys (keys are byte arrays)
     fn get_op_keys(&self) -> Result<Vec<Vec<u8>>> {
+        abi_call!(self, "get_op_keys");
         let context = context_guard!(self);
         let stack = context.stack.last().ok_or_else(|| anyhow!("No stack"))?;
         let datastore = stack
@@ -341,6 +423,7 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// true if the entry is matching the provided key in its operation datastore, otherwise false
     fn has_op_key(&self, key: &[u8]) -> Result<bool> {
+        abi_call!(self, "has_op_key", TracedBytes(key.len()));
         debug!("[abi has_op_key] checking key {:?}", key);
         let context = context_guard!(self);
         let stack = context.stack.last().ok_or_else(|| anyhow!("No stack"))?;
@@ -362,6 +445,7 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// The operation datastore value matching the provided key, if found, otherwise an error.
     fn get_op_data(&self, key: &[u8]) -> Result<Vec<u8>> {
+        abi_call!(self, "get_op_data", TracedBytes(key.len()));
         debug!("[abi get_op_data] data for {:?}", key);
         let context = context_guard!(self);
         let stack = context.stack.last().ok_or_else(|| anyhow!("No stack"))?;
@@ -385,18 +469,21 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// The string representation of the resulting hash
     fn hash(&self, data: &[u8]) -> Result<String> {
+        abi_call!(self, "hash", TracedBytes(data.len()));
         Ok(massa_hash::Hash::compute_from(data).to_bs58_check())
     }
 
     /// Converts a public key to an address
     ///
     /// # Arguments
-    /// * `public_key`: string representation of the public key
+    /// * `public_key`: string representation of the public key,
+    ///   in its Massa text form or as the legacy `bs58` check encoding of its bytes
     ///
     /// # Returns
     /// The string representation of the resulting address
     fn address_from_public_key(&self, public_key: &str) -> Result<String> {
-        let public_key = massa_signature::PublicKey::from_bs58_check(public_key)?;
+        abi_call!(self, "address_from_public_key", public_key);
+        let public_key = parse_public_key(public_key)?;
         let addr = massa_models::address::Address::from_public_key(&public_key);
         Ok(addr.to_string())
     }
@@ -406,16 +493,24 @@ impl Interface for InterfaceImpl {
     /// # Arguments
     /// * data: the data bytes that were signed
     /// * signature: string representation of the signature
-    /// * public key: string representation of the public key to check against
+    /// * public key: string representation of the public key to check against,
+    ///   in its Massa text form or as the legacy `bs58` check encoding of its bytes
     ///
     /// # Returns
     /// true if the signature verification succeeded, false otherwise
     fn signature_verify(&self, data: &[u8], signature: &str, public_key: &str) -> Result<bool> {
+        abi_call!(
+            self,
+            "signature_verify",
+            TracedBytes(data.len()),
+            signature,
+            public_key
+        );
         let signature = match massa_signature::Signature::from_bs58_check(signature) {
             Ok(sig) => sig,
             Err(_) => return Ok(false),
         };
-        let public_key = match massa_signature::PublicKey::from_bs58_check(public_key) {
+        let public_key = match parse_public_key(public_key) {
             Ok(pubk) => pubk,
             Err(_) => return Ok(false),
         };
@@ -429,6 +524,7 @@ impl Interface for InterfaceImpl {
     /// * `to_address`: string representation of the address to which the coins are sent
     /// * `raw_amount`: raw representation (no decimal factor) of the amount of coins to transfer
     fn transfer_coins(&self, to_address: &str, raw_amount: u64) -> Result<()> {
+        abi_call!(self, "transfer_coins", to_address, raw_amount);
         let to_address = massa_models::address::Address::from_str(to_address)?;
         let amount = massa_models::amount::Amount::from_raw(raw_amount);
         let mut context = context_guard!(self);
@@ -449,6 +545,13 @@ impl Interface for InterfaceImpl {
         to_address: &str,
         raw_amount: u64,
     ) -> Result<()> {
+        abi_call!(
+            self,
+            "transfer_coins_for",
+            from_address,
+            to_address,
+            raw_amount
+        );
         let from_address = massa_models::address::Address::from_str(from_address)?;
         let to_address = massa_models::address::Address::from_str(to_address)?;
         let amount = massa_models::amount::Amount::from_raw(raw_amount);
@@ -466,6 +569,7 @@ impl Interface for InterfaceImpl {
     /// A vector with the string representation of each owned address.
     /// Note that the ordering of this vector is deterministic and conserved.
     fn get_owned_addresses(&self) -> Result<Vec<String>> {
+        abi_call!(self, "get_owned_addresses");
         Ok(context_guard!(self)
             .get_current_owned_addresses()?
             .into_iter()
@@ -478,6 +582,7 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// A vector with the string representation of each call stack address.
     fn get_call_stack(&self) -> Result<Vec<String>> {
+        abi_call!(self, "get_call_stack");
         Ok(context_guard!(self)
             .get_call_stack()
             .into_iter()
@@ -491,6 +596,7 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// The raw representation (no decimal factor) of the amount of coins
     fn get_call_coins(&self) -> Result<u64> {
+        abi_call!(self, "get_call_coins");
         Ok(context_guard!(self).get_current_call_coins()?.to_raw())
     }
 
@@ -499,16 +605,21 @@ impl Interface for InterfaceImpl {
     /// # Arguments:
     /// data: the string data that is the payload of the event
     fn generate_event(&self, data: String) -> Result<()> {
+        abi_call!(self, "generate_event", data);
         let mut context = context_guard!(self);
         let event = context.event_create(data);
-        context.event_emit(event);
-        Ok(())
+        Ok(context.event_emit(event)?)
     }
 
     /// Returns the current time (millisecond UNIX timestamp)
     /// Note that in order to ensure determinism, this is actually the time of the context slot.
     fn get_time(&self) -> Result<u64> {
-        let slot = context_guard!(self).slot;
+        abi_call!(self, "get_time");
+        let slot = {
+            let mut context = context_guard!(self);
+            context.time_or_randomness_read = true;
+            context.slot
+        };
         let ts = get_block_slot_timestamp(
             self.config.thread_count,
             self.config.t0,
@@ -524,8 +635,11 @@ impl Interface for InterfaceImpl {
     /// This random number generator is unsafe:
     /// it can be both predicted and manipulated before the execution
     fn unsafe_random(&self) -> Result<i64> {
+        abi_call!(self, "unsafe_random");
         let distr = rand::distributions::Uniform::new_inclusive(i64::MIN, i64::MAX);
-        Ok(context_guard!(self).unsafe_rng.sample(distr))
+        let mut context = context_guard!(self);
+        context.time_or_randomness_read = true;
+        Ok(context.unsafe_rng.sample(distr))
     }
 
     /// Returns a pseudo-random deterministic `f64` number
@@ -534,8 +648,11 @@ impl Interface for InterfaceImpl {
     /// This random number generator is unsafe:
     /// it can be both predicted and manipulated before the execution
     fn unsafe_random_f64(&self) -> Result<f64> {
+        abi_call!(self, "unsafe_random_f64");
         let distr = rand::distributions::Uniform::new(0f64, 1f64);
-        Ok(context_guard!(self).unsafe_rng.sample(distr))
+        let mut context = context_guard!(self);
+        context.time_or_randomness_read = true;
+        Ok(context.unsafe_rng.sample(distr))
     }
 
     /// Adds an asynchronous message to the context speculative asynchronous pool
@@ -560,6 +677,18 @@ impl Interface for InterfaceImpl {
         raw_coins: u64,
         data: &[u8],
     ) -> Result<()> {
+        abi_call!(
+            self,
+            "send_message",
+            target_address,
+            target_handler,
+            validity_start,
+            validity_end,
+            max_gas,
+            gas_price,
+            raw_coins,
+            TracedBytes(data.len()),
+        );
         if validity_start.1 >= self.config.thread_count {
             bail!("validity start thread exceeds the configuration thread count")
         }
@@ -584,25 +713,28 @@ impl Interface for InterfaceImpl {
             gas_price: Amount::from_raw(gas_price),
             coins,
             data: data.to_vec(),
-        });
+        })?;
         execution_context.created_message_index += 1;
         Ok(())
     }
 
     /// Returns the period of the current execution slot
     fn get_current_period(&self) -> Result<u64> {
+        abi_call!(self, "get_current_period");
         let slot = context_guard!(self).slot;
         Ok(slot.period)
     }
 
     /// Returns the thread of the current execution slot
     fn get_current_thread(&self) -> Result<u8> {
+        abi_call!(self, "get_current_thread");
         let slot = context_guard!(self).slot;
         Ok(slot.thread)
     }
 
     /// Sets the bytecode of the current address
     fn raw_set_bytecode(&self, bytecode: &[u8]) -> Result<()> {
+        abi_call!(self, "raw_set_bytecode", TracedBytes(bytecode.len()));
         let mut execution_context = context_guard!(self);
         let address = execution_context.get_current_address()?;
         match execution_context.set_bytecode(&address, bytecode.to_vec()) {
@@ -614,6 +746,12 @@ impl Interface for InterfaceImpl {
     /// Sets the bytecode of an arbitrary address.
     /// Fails if the address does not exist of if the context doesn't have write access rights on it.
     fn raw_set_bytecode_for(&self, address: &str, bytecode: &[u8]) -> Result<()> {
+        abi_call!(
+            self,
+            "raw_set_bytecode_for",
+            address,
+            TracedBytes(bytecode.len())
+        );
         let address = massa_models::address::Address::from_str(address)?;
         let mut execution_context = context_guard!(self);
         match execution_context.set_bytecode(&address, bytecode.to_vec()) {
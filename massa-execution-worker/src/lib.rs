@@ -73,6 +73,9 @@
 //! This module contains the implementation of a generic finite-size execution request queue.
 //! It handles requests that come with an MPSC to send back the result of their execution once it's done.
 //!
+//! ## `readonly_cache.rs`
+//! Caches the outputs of the read-only executions done since the latest active slot execution.
+//!
 //! ## `stats.rs`
 //! Defines a structure that gathers execution statistics.
 
@@ -87,7 +90,9 @@ mod context;
 mod controller;
 mod execution;
 mod interface_impl;
+mod readonly_cache;
 mod request_queue;
+mod saturation;
 mod slot_sequencer;
 mod speculative_async_pool;
 mod speculative_executed_ops;
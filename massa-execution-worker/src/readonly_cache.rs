@@ -0,0 +1,286 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Cache of the outputs of read-only executions.
+//! Public nodes serve the same read-only calls over and over again:
+//! as long as no active slot is executed, their outputs can be reused.
+//! The outputs of the executions reading the time or the unsafe random generator are not cached.
+
+use massa_execution_exports::{
+    ExecutionStackElement, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    ReadOnlyExecutionTarget,
+};
+use massa_hash::Hash;
+use massa_models::slot::Slot;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+
+/// Outputs of the successful read-only executions done since the latest active slot execution
+pub struct ReadOnlyCache {
+    /// maximal number of cached outputs, caching is disabled if 0
+    max_entries: usize,
+    /// cached outputs, with hit and miss counters
+    inner: Mutex<ReadOnlyCacheInner>,
+}
+
+/// Mutable part of the `ReadOnlyCache`
+#[derive(Default)]
+struct ReadOnlyCacheInner {
+    /// active cursor at which the cached outputs were computed
+    active_cursor: Option<Slot>,
+    /// cached outputs by request hash
+    outputs: HashMap<Hash, ReadOnlyExecutionOutput>,
+    /// request hashes of the cached outputs, oldest first
+    order: VecDeque<Hash>,
+    /// number of requests served from the cache since the start of the node
+    hits: u64,
+    /// number of requests that had to be executed since the start of the node
+    misses: u64,
+}
+
+impl ReadOnlyCache {
+    /// create a new `ReadOnlyCache`
+    ///
+    /// # Arguments
+    /// * `max_entries`: maximal number of cached outputs, caching is disabled if 0
+    pub fn new(max_entries: usize) -> Self {
+        ReadOnlyCache {
+            max_entries,
+            inner: Default::default(),
+        }
+    }
+
+    /// Gets the output of a request executed at the same active cursor, if any,
+    /// counting a hit or a miss
+    pub fn get(&self, active_cursor: Slot, request_hash: &Hash) -> Option<ReadOnlyExecutionOutput> {
+        if self.max_entries == 0 {
+            return None;
+        }
+        let mut inner = self.inner.lock();
+        let output = if inner.active_cursor == Some(active_cursor) {
+            inner.outputs.get(request_hash).cloned()
+        } else {
+            None
+        };
+        if output.is_some() {
+            inner.hits += 1;
+        } else {
+            inner.misses += 1;
+        }
+        output
+    }
+
+    /// Caches the output of a request executed at `active_cursor`,
+    /// dropping the outputs computed at another active cursor and the oldest ones in excess
+    pub fn insert(&self, active_cursor: Slot, request_hash: Hash, output: ReadOnlyExecutionOutput) {
+        if self.max_entries == 0 {
+            return;
+        }
+        let mut inner = self.inner.lock();
+        if inner.active_cursor != Some(active_cursor) {
+            inner.outputs.clear();
+            inner.order.clear();
+            inner.active_cursor = Some(active_cursor);
+        }
+        if inner.outputs.insert(request_hash, output).is_none() {
+            inner.order.push_back(request_hash);
+        }
+        while inner.order.len() > self.max_entries {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.outputs.remove(&oldest);
+            }
+        }
+    }
+
+    /// Drops all the cached outputs, to be done when the active history is truncated
+    /// because slots can then be executed again at the same active cursor with a different outcome
+    pub fn invalidate(&mut self) {
+        let inner = self.inner.get_mut();
+        inner.outputs.clear();
+        inner.order.clear();
+        inner.active_cursor = None;
+    }
+
+    /// Gets the number of cache hits and misses since the start of the node
+    pub fn get_hits_and_misses(&self) -> (u64, u64) {
+        let inner = self.inner.lock();
+        (inner.hits, inner.misses)
+    }
+}
+
+/// Computes the hash identifying a read-only request in the cache:
+/// every field but the API request id, which does not change the output
+pub fn hash_readonly_request(req: &ReadOnlyExecutionRequest) -> Hash {
+    let mut bytes = Vec::new();
+    bytes.extend(req.max_gas.to_be_bytes());
+    bytes.extend(req.simulated_gas_price.to_raw().to_be_bytes());
+    bytes.extend((req.call_stack.len() as u64).to_be_bytes());
+    for element in &req.call_stack {
+        append_stack_element_bytes(&mut bytes, element);
+    }
+    match &req.target {
+        ReadOnlyExecutionTarget::BytecodeExecution(bytecode) => {
+            bytes.push(0u8);
+            append_sized_bytes(&mut bytes, bytecode);
+        }
+        ReadOnlyExecutionTarget::FunctionCall {
+            target_addr,
+            target_func,
+            parameter,
+        } => {
+            bytes.push(1u8);
+            bytes.extend(target_addr.to_bytes());
+            append_sized_bytes(&mut bytes, target_func.as_bytes());
+            append_sized_bytes(&mut bytes, parameter.as_bytes());
+        }
+    }
+    match &req.deployment_simulation {
+        Some(simulation) => {
+            bytes.push(1u8);
+            bytes.extend(simulation.slot.to_bytes_key());
+            bytes.extend(simulation.created_addr_index.to_be_bytes());
+        }
+        None => bytes.push(0u8),
+    }
+    Hash::compute_from(&bytes)
+}
+
+/// Appends the bytes of a call stack element to `bytes`
+fn append_stack_element_bytes(bytes: &mut Vec<u8>, element: &ExecutionStackElement) {
+    bytes.extend(element.address.to_bytes());
+    bytes.extend(element.coins.to_raw().to_be_bytes());
+    bytes.extend((element.owned_addresses.len() as u64).to_be_bytes());
+    for address in &element.owned_addresses {
+        bytes.extend(address.to_bytes());
+    }
+    match &element.operation_datastore {
+        Some(datastore) => {
+            bytes.push(1u8);
+            bytes.extend((datastore.len() as u64).to_be_bytes());
+            for (key, value) in datastore {
+                append_sized_bytes(bytes, key);
+                append_sized_bytes(bytes, value);
+            }
+        }
+        None => bytes.push(0u8),
+    }
+}
+
+/// Appends `data` to `bytes`, prefixed by its length so that consecutive fields cannot be confused
+fn append_sized_bytes(bytes: &mut Vec<u8>, data: &[u8]) {
+    bytes.extend((data.len() as u64).to_be_bytes());
+    bytes.extend(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash_readonly_request, ReadOnlyCache};
+    use massa_execution_exports::{
+        ExecutionOutput, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    };
+    use massa_hash::Hash;
+    use massa_models::{address::Address, amount::Amount, slot::Slot};
+
+    /// Output of a read-only execution, told apart by its gas cost
+    fn output(gas_cost: u64) -> ReadOnlyExecutionOutput {
+        ReadOnlyExecutionOutput {
+            out: ExecutionOutput {
+                slot: Slot::new(1, 0),
+                block_id: None,
+                state_changes: Default::default(),
+                events: Default::default(),
+                operation_statuses: Vec::new(),
+                address_activities: Vec::new(),
+                gas_costs_hash: Hash::compute_from(b"gas costs"),
+            },
+            gas_cost,
+            created_addresses: Vec::new(),
+            storage_cost: Amount::zero(),
+            event_usage: Default::default(),
+        }
+    }
+
+    fn cached_gas(cache: &ReadOnlyCache, cursor: Slot, request_hash: &Hash) -> Option<u64> {
+        cache
+            .get(cursor, request_hash)
+            .map(|output| output.gas_cost)
+    }
+
+    #[test]
+    fn test_readonly_cache_hits_and_misses() {
+        let cache = ReadOnlyCache::new(10);
+        let cursor = Slot::new(1, 0);
+        let request = Hash::compute_from(b"request");
+
+        assert_eq!(cached_gas(&cache, cursor, &request), None);
+        cache.insert(cursor, request, output(1));
+        assert_eq!(cached_gas(&cache, cursor, &request), Some(1));
+        assert_eq!(
+            cached_gas(&cache, cursor, &Hash::compute_from(b"other")),
+            None
+        );
+        assert_eq!(cache.get_hits_and_misses(), (1, 2));
+
+        // outputs computed at another active cursor are not served, and dropped by an insert
+        let next_cursor = Slot::new(1, 1);
+        assert_eq!(cached_gas(&cache, next_cursor, &request), None);
+        cache.insert(next_cursor, Hash::compute_from(b"other"), output(2));
+        assert_eq!(cached_gas(&cache, next_cursor, &request), None);
+        assert_eq!(cached_gas(&cache, cursor, &request), None);
+        assert_eq!(cache.get_hits_and_misses(), (1, 5));
+    }
+
+    #[test]
+    fn test_readonly_cache_eviction_and_invalidation() {
+        let mut cache = ReadOnlyCache::new(2);
+        let cursor = Slot::new(1, 0);
+        let requests: Vec<Hash> = (0u8..3).map(|i| Hash::compute_from(&[i])).collect();
+
+        // the oldest outputs are evicted first
+        for (gas, request) in requests.iter().enumerate() {
+            cache.insert(cursor, *request, output(gas as u64));
+        }
+        assert_eq!(cached_gas(&cache, cursor, &requests[0]), None);
+        assert_eq!(cached_gas(&cache, cursor, &requests[1]), Some(1));
+        assert_eq!(cached_gas(&cache, cursor, &requests[2]), Some(2));
+
+        // nothing is served after an invalidation, even at the same active cursor
+        cache.invalidate();
+        assert_eq!(cached_gas(&cache, cursor, &requests[1]), None);
+        assert_eq!(cached_gas(&cache, cursor, &requests[2]), None);
+    }
+
+    #[test]
+    fn test_readonly_cache_disabled() {
+        let cache = ReadOnlyCache::new(0);
+        let cursor = Slot::new(1, 0);
+        let request = Hash::compute_from(b"request");
+        cache.insert(cursor, request, output(1));
+        assert_eq!(cached_gas(&cache, cursor, &request), None);
+        assert_eq!(cache.get_hits_and_misses(), (0, 0));
+    }
+
+    #[test]
+    fn test_hash_readonly_request() {
+        let request = |parameter: &str, request_id: Option<u64>| ReadOnlyExecutionRequest {
+            max_gas: 1_000_000,
+            simulated_gas_price: Amount::zero(),
+            call_stack: Vec::new(),
+            target: ReadOnlyExecutionTarget::FunctionCall {
+                target_addr: Address(Hash::compute_from(b"target")),
+                target_func: "main".to_string(),
+                parameter: parameter.to_string(),
+            },
+            request_id,
+            deployment_simulation: None,
+        };
+        // the API request id does not change the output, the parameter does
+        assert_eq!(
+            hash_readonly_request(&request("a", Some(1))),
+            hash_readonly_request(&request("a", Some(2)))
+        );
+        assert_ne!(
+            hash_readonly_request(&request("a", None)),
+            hash_readonly_request(&request("b", None))
+        );
+    }
+}
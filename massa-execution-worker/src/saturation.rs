@@ -0,0 +1,43 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Saturation policy of the coin arithmetic of the execution credit paths.
+//!
+//! Credit computations either propagate the typed errors of the strict `Amount` operations,
+//! or saturate explicitly through the functions of this module so that every saturation can be logged.
+
+use massa_models::amount::Amount;
+use tracing::warn;
+
+/// Subtracts `amount` from `value`, saturating at zero on underflow.
+///
+/// # Arguments
+/// * `log`: whether to log the saturation
+/// * `context`: description of the computation, used in the log
+pub(crate) fn saturating_sub(value: Amount, amount: Amount, log: bool, context: &str) -> Amount {
+    value.strict_sub(amount).unwrap_or_else(|err| {
+        if log {
+            warn!(
+                "{} saturated computing {} - {}: {}",
+                context, value, amount, err
+            );
+        }
+        Amount::MIN
+    })
+}
+
+/// Multiplies `value` by `factor`, saturating at `Amount::MAX` on overflow.
+///
+/// # Arguments
+/// * `log`: whether to log the saturation
+/// * `context`: description of the computation, used in the log
+pub(crate) fn saturating_mul_u64(value: Amount, factor: u64, log: bool, context: &str) -> Amount {
+    value.strict_mul_u64(factor).unwrap_or_else(|err| {
+        if log {
+            warn!(
+                "{} saturated computing {} * {}: {}",
+                context, value, factor, err
+            );
+        }
+        Amount::MAX
+    })
+}
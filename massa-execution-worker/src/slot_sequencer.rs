@@ -2,6 +2,8 @@
 
 //! This module allows Execution to manage slot sequencing.
 
+#[cfg(feature = "sandbox")]
+use std::collections::BTreeMap;
 use std::collections::{HashMap, VecDeque};
 
 use massa_execution_exports::ExecutionConfig;
@@ -42,6 +44,7 @@ impl SlotInfo {
 /// `SlotSequencer::run_task_with` allows running the next slot in the queue, if any.
 /// Note that SCE-final slots are executed in priority over candidate slots.
 /// `SlotSequencer::get_next_slot_deadline` allows getting the time at which the next slot will happen (this is useful to sequence slots as they happen even if there is no block there).
+/// In sandbox mode, the `SlotSequencer::sandbox_*` methods allow moving through time to test time-dependent logic without waiting for the slots to happen.
 pub struct SlotSequencer {
     /// Config
     config: ExecutionConfig,
@@ -60,6 +63,13 @@ pub struct SlotSequencer {
 
     /// candidate slot execution cursor
     latest_executed_candidate_slot: Slot,
+
+    /// shift applied to the current time, only moved forward in sandbox mode
+    time_offset: MassaTime,
+
+    /// blocks executed at candidate slots in place of the content of the sequence, in sandbox mode
+    #[cfg(feature = "sandbox")]
+    sandbox_blocks: BTreeMap<Slot, (BlockId, Storage)>,
 }
 
 impl SlotSequencer {
@@ -76,6 +86,9 @@ impl SlotSequencer {
             latest_sce_final_slot: final_cursor,
             latest_executed_final_slot: final_cursor,
             latest_executed_candidate_slot: final_cursor,
+            time_offset: MassaTime::from_millis(0),
+            #[cfg(feature = "sandbox")]
+            sandbox_blocks: Default::default(),
             config,
         }
     }
@@ -181,15 +194,22 @@ impl SlotSequencer {
         self.cleanup_sequence();
     }
 
+    /// Internal function getting the current time, shifted by the time offset of the sandbox mode
+    fn now(&self) -> MassaTime {
+        self.config
+            .clock_compensation
+            .now()
+            .expect("could not get current time")
+            .saturating_add(self.time_offset)
+    }
+
     /// Internal function allowing to get the latest slot we should execute at the current time.
     /// This is useful to fill the sequencer with slots as they happen, even if there are no blocks there.
     ///
     /// Note that this time cursor is shifted by `self.config.cursor_delay`
     /// to avoid computing speculative slots that are too recent, and therefore subject to frequent re-writes.
     fn get_time_cursor(&self) -> Slot {
-        let shifted_now = MassaTime::now(self.config.clock_compensation)
-            .expect("could not get current time")
-            .saturating_sub(self.config.cursor_delay);
+        let shifted_now = self.now().saturating_sub(self.config.cursor_delay);
         get_latest_block_slot_at_timestamp(
             self.config.thread_count,
             self.config.t0,
@@ -628,6 +648,67 @@ impl SlotSequencer {
         }
     }
 
+    /// If the next SCE-final slot is ready for execution, this method will mark it as executed
+    /// and call the provided callback function on it for execution.
+    /// Candidate slots are ignored: this is used to drain the final slots before stopping.
+    ///
+    /// # Arguments
+    /// * `callback`: callback function that executes the slot
+    ///   * Callback arguments:
+    ///     * a reference to the slot
+    ///     * a reference to the block at that slot and its storage, if any (otherwise None)
+    ///   * Callback return value: an arbitrary `T`
+    ///
+    /// # Returns
+    /// An option that is `None` if there was no SCE-final slot to be executed,
+    /// or `Some(T)` where `T` is the value returned by the `callback` function otherwise.
+    pub fn run_final_task_with<F, T>(&mut self, callback: F) -> Option<T>
+    where
+        F: Fn(&Slot, Option<&(BlockId, Storage)>) -> T,
+    {
+        // Get the slot just after the latest executed SCE-final slot.
+        let slot = self
+            .latest_executed_final_slot
+            .get_next_slot(self.config.thread_count)
+            .expect("overflow in slot iteration");
+
+        // Check whether that slot is in the sequence and marked as SCE-final.
+        let res = match self.get_slot(&slot) {
+            Some(SlotInfo {
+                sce_final: true,
+                content,
+                ..
+            }) => {
+                // There is an SCE-final slot ready for execution.
+                // Call the callback function to execute the slot.
+                callback(&slot, content.as_ref())
+            }
+            _ => return None,
+        };
+
+        // Update the SCE-final execution cursor.
+        self.latest_executed_final_slot = slot;
+
+        // A block injected at that slot in sandbox mode made the candidate executions diverge from the final ones:
+        // they are dropped by the execution of the final slot, so they need to be executed again.
+        #[cfg(feature = "sandbox")]
+        if self.sandbox_blocks.remove(&slot).is_some() {
+            self.latest_executed_candidate_slot = slot;
+        }
+
+        // If the speculative execution cursor is late on the SCE-final one, make it catch up.
+        self.latest_executed_candidate_slot = std::cmp::max(
+            self.latest_executed_candidate_slot,
+            self.latest_executed_final_slot,
+        );
+
+        // Clean the sequence from the executed CSS-final slot if it is not useful anymore.
+        self.cleanup_sequence();
+
+        // Return `Some(result of the callback)`.
+        Some(res)
+    }
+
     /// If a slot is ready for execution, this method will mark it as executed and call the provided callback function on it for execution.
     /// SCE-final slots are executed in priority over candidate slots.
     ///
@@ -652,39 +733,8 @@ impl SlotSequencer {
         }
 
         // High priority: execute the next SCE-final that is available for execution, if any.
-        {
-            // Get the slot just after the latest executed SCE-final slot.
-            let slot = self
-                .latest_executed_final_slot
-                .get_next_slot(self.config.thread_count)
-                .expect("overflow in slot iteration");
-            // Check whether that slot is in the sequence and marked as SCE-final.
-            if let Some(SlotInfo {
-                sce_final, content, ..
-            }) = self.get_slot(&slot)
-            {
-                if *sce_final {
-                    // There is an SCE-final slot ready for execution.
-
-                    // Call the callback function to execute the slot.
-                    let res = Some(callback(true, &slot, content.as_ref()));
-
-                    // Update the SCE-final execution cursor.
-                    self.latest_executed_final_slot = slot;
-
-                    // If the speculative execution cursor is late on the SCE-final one, make it catch up.
-                    self.latest_executed_candidate_slot = std::cmp::max(
-                        self.latest_executed_candidate_slot,
-                        self.latest_executed_final_slot,
-                    );
-
-                    // Clean the sequence from the executed CSS-final slot if it is not useful anymore.
-                    self.cleanup_sequence();
-
-                    // Return `Some(result of the callback)`.
-                    return res;
-                }
-            }
+        if let Some(res) = self.run_final_task_with(|slot, content| callback(true, slot, content)) {
+            return Some(res);
         }
 
         // Here we know that there are no SCE-final slots to execute.
@@ -704,6 +754,10 @@ impl SlotSequencer {
                 // Consider it a miss if it is absent from the sequence.
                 let content = self.get_slot(&slot).and_then(|nfo| nfo.content.as_ref());
 
+                // In sandbox mode, a block injected at that slot replaces its content.
+                #[cfg(feature = "sandbox")]
+                let content = self.sandbox_blocks.get(&slot).or(content);
+
                 // Call the `callback` function to execute the slot.
                 let res = Some(callback(false, &slot, content));
 
@@ -726,7 +780,10 @@ impl SlotSequencer {
         // This means that we are still waiting for `Self::update` to be called for the first time.
         // To avoid CPU-intensive loops upstream, just register a wake-up after a single slot delay (t0/T).
         if self.sequence.is_empty() {
-            return MassaTime::now(self.config.clock_compensation)
+            return self
+                .config
+                .clock_compensation
+                .now()
                 .expect("could not get current time")
                 .saturating_add(
                     self.config
@@ -742,7 +799,7 @@ impl SlotSequencer {
             .get_next_slot(self.config.thread_count)
             .expect("slot overflow in slot deadline computation");
 
-        // Return the timestamp of that slot, shifted by the cursor delay.
+        // Return the timestamp of that slot, shifted by the cursor delay and back to the actual time.
         get_block_slot_timestamp(
             self.config.thread_count,
             self.config.t0,
@@ -751,5 +808,70 @@ impl SlotSequencer {
         )
         .expect("could not compute slot timestamp")
         .saturating_add(self.config.cursor_delay)
+        .saturating_sub(self.time_offset)
+    }
+
+    /// Sandbox mode: moves the time cursor forward by `slot_count` slots,
+    /// so that the candidate slots until then are executed right away.
+    #[cfg(feature = "sandbox")]
+    pub fn sandbox_fast_forward(&mut self, slot_count: u64) {
+        let slot_duration = self
+            .config
+            .t0
+            .checked_div_u64(self.config.thread_count as u64)
+            .expect("could not compute slot duration");
+        self.time_offset = self
+            .time_offset
+            .saturating_add(slot_duration.saturating_mul(slot_count));
+    }
+
+    /// Sandbox mode: executes a block as a candidate at `slot`, in place of the content of the sequence at that slot.
+    /// The candidate slots from `slot` are executed again,
+    /// and the time cursor is moved forward to reach `slot` if it is in the future.
+    ///
+    /// # Returns
+    /// `false` if the block could not be injected because `slot` was already executed as final
+    #[cfg(feature = "sandbox")]
+    pub fn sandbox_inject_block(
+        &mut self,
+        slot: Slot,
+        block_id: BlockId,
+        block_storage: Storage,
+    ) -> bool {
+        if slot <= self.latest_executed_final_slot {
+            return false;
+        }
+
+        // Move the time cursor forward until it reaches the slot.
+        let slot_time = get_block_slot_timestamp(
+            self.config.thread_count,
+            self.config.t0,
+            self.config.genesis_timestamp,
+            slot,
+        )
+        .expect("could not compute slot timestamp")
+        .saturating_add(self.config.cursor_delay);
+        self.time_offset = self
+            .time_offset
+            .saturating_add(slot_time.saturating_sub(self.now()));
+
+        // Roll back the candidate execution cursor if the slot was already executed.
+        if self.latest_executed_candidate_slot >= slot {
+            self.latest_executed_candidate_slot = slot
+                .get_prev_slot(self.config.thread_count)
+                .expect("could not rollback speculative execution cursor");
+        }
+
+        self.sandbox_blocks.insert(slot, (block_id, block_storage));
+        true
+    }
+
+    /// Sandbox mode: drops the injected blocks and the time shift,
+    /// and rolls the candidate execution cursor back to the final one so that the candidate slots are executed again.
+    #[cfg(feature = "sandbox")]
+    pub fn sandbox_reset_to_final(&mut self) {
+        self.sandbox_blocks.clear();
+        self.time_offset = MassaTime::from_millis(0);
+        self.latest_executed_candidate_slot = self.latest_executed_final_slot;
     }
 }
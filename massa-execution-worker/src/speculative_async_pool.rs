@@ -5,6 +5,7 @@
 
 use crate::active_history::ActiveHistory;
 use massa_async_pool::{AsyncMessage, AsyncMessageId, AsyncPool, AsyncPoolChanges};
+use massa_execution_exports::ExecutionError;
 use massa_final_state::FinalState;
 use massa_models::slot::Slot;
 use parking_lot::RwLock;
@@ -23,15 +24,25 @@ pub(crate) struct SpeculativeAsyncPool {
 
     /// List of changes (additions/deletions) to the pool after settling emitted messages
     settled_changes: AsyncPoolChanges,
+
+    /// max number of pending messages sent to the same destination address
+    max_messages_per_destination: u64,
+
+    /// max total gas of the pending messages sent to the same destination address
+    max_gas_per_destination: u64,
 }
 
 impl SpeculativeAsyncPool {
     /// Creates a new `SpeculativeAsyncPool`
     ///
     /// # Arguments
+    /// * `max_messages_per_destination`: max number of pending messages sent to the same destination address
+    /// * `max_gas_per_destination`: max total gas of the pending messages sent to the same destination address
     pub fn new(
         final_state: Arc<RwLock<FinalState>>,
         active_history: Arc<RwLock<ActiveHistory>>,
+        max_messages_per_destination: u64,
+        max_gas_per_destination: u64,
     ) -> Self {
         // deduce speculative async pool from history
         let mut async_pool = final_state.read().async_pool.clone();
@@ -43,6 +54,8 @@ impl SpeculativeAsyncPool {
             async_pool,
             emitted: Default::default(),
             settled_changes: Default::default(),
+            max_messages_per_destination,
+            max_gas_per_destination,
         }
     }
 
@@ -64,8 +77,32 @@ impl SpeculativeAsyncPool {
     }
 
     /// Add a new message to the list of changes of this `SpeculativeAsyncPool`
-    pub fn push_new_message(&mut self, msg: AsyncMessage) {
+    ///
+    /// # Returns
+    /// An `AsyncInboxQuotaExceeded` error if the pending messages sent to the destination of `msg`
+    /// would exceed the per-destination limits, in which case the message is not added
+    pub fn push_new_message(&mut self, msg: AsyncMessage) -> Result<(), ExecutionError> {
+        let mut usage = self.async_pool.get_destination_usage(&msg.destination);
+        for (_id, emitted) in self.emitted.iter() {
+            if emitted.destination == msg.destination {
+                usage.add(emitted);
+            }
+        }
+        usage.add(&msg);
+        if usage.message_count > self.max_messages_per_destination {
+            return Err(ExecutionError::AsyncInboxQuotaExceeded(format!(
+                "more than {} pending messages would be sent to {}",
+                self.max_messages_per_destination, msg.destination
+            )));
+        }
+        if usage.total_gas > self.max_gas_per_destination {
+            return Err(ExecutionError::AsyncInboxQuotaExceeded(format!(
+                "the pending messages sent to {} would use more than {} gas",
+                msg.destination, self.max_gas_per_destination
+            )));
+        }
         self.emitted.push((msg.compute_id(), msg));
+        Ok(())
     }
 
     /// Takes a batch of asynchronous messages to execute,
@@ -9,9 +9,10 @@ use crate::active_history::{ActiveHistory, HistorySearchResult};
 use massa_execution_exports::ExecutionError;
 use massa_execution_exports::StorageCostsConstants;
 use massa_final_state::FinalState;
-use massa_ledger_exports::{Applicable, LedgerChanges};
+use massa_ledger_exports::{Applicable, LedgerChanges, SetOrDelete, SetOrKeep, SetUpdateOrDelete};
 use massa_models::{address::Address, amount::Amount};
 use parking_lot::RwLock;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 use tracing::debug;
 
@@ -122,6 +123,26 @@ impl SpeculativeLedger {
         })
     }
 
+    /// Gets the size of the effective bytecode of an address, without copying it
+    ///
+    /// # Arguments:
+    /// `addr`: the address to query
+    ///
+    /// # Returns
+    /// `Some(u64)` if the address was found, otherwise None
+    pub fn get_bytecode_size(&self, addr: &Address) -> Option<u64> {
+        // try to read from added changes > history > final_state
+        self.added_changes.get_bytecode_size_or_else(addr, || {
+            match self.active_history.read().fetch_bytecode_size(addr) {
+                HistorySearchResult::Present(size) => Some(size),
+                HistorySearchResult::NoInfo => {
+                    self.final_state.read().ledger.get_bytecode_size(addr)
+                }
+                HistorySearchResult::Absent => None,
+            }
+        })
+    }
+
     /// Transfers coins from one address to another.
     /// No changes are retained in case of failure.
     /// The spending address, if defined, must exist.
@@ -546,4 +567,138 @@ impl SpeculativeLedger {
 
         Ok(())
     }
+
+    /// Gets the effective datastore keys of an address
+    ///
+    /// # Arguments
+    /// * `addr`: address to query
+    ///
+    /// # Returns
+    /// A `BTreeSet` of the datastore keys, empty if the address does not exist
+    fn get_datastore_keys(&self, addr: &Address) -> BTreeSet<Vec<u8>> {
+        // apply the changes of each history slot, then the added changes, over the final keys
+        let apply_changes =
+            |keys: &mut BTreeSet<Vec<u8>>, changes: &LedgerChanges| match changes.get(addr) {
+                None => (),
+                Some(SetUpdateOrDelete::Set(new_entry)) => {
+                    *keys = new_entry.datastore.keys().cloned().collect();
+                }
+                Some(SetUpdateOrDelete::Update(entry_updates)) => {
+                    for (key, update) in &entry_updates.datastore {
+                        match update {
+                            SetOrDelete::Set(_) => keys.insert(key.clone()),
+                            SetOrDelete::Delete => keys.remove(key),
+                        };
+                    }
+                }
+                Some(SetUpdateOrDelete::Delete) => keys.clear(),
+            };
+
+        let mut keys = self.final_state.read().ledger.get_datastore_keys(addr);
+        for output in self.active_history.read().outputs_up_to(None) {
+            apply_changes(&mut keys, &output.state_changes.ledger_changes);
+        }
+        apply_changes(&mut keys, &self.added_changes);
+        keys
+    }
+
+    /// Gets the effective storage size of an address:
+    /// the length of its bytecode plus the length of every datastore key and value.
+    ///
+    /// # Arguments
+    /// * `addr`: address to query
+    fn get_storage_size(&self, addr: &Address) -> u64 {
+        let bytecode_size = self.get_bytecode(addr).map_or(0, |b| b.len() as u64);
+        self.get_datastore_keys(addr)
+            .into_iter()
+            .fold(bytecode_size, |size, key| {
+                let value_size = self.get_data_entry(addr, &key).map_or(0, |v| v.len());
+                size.saturating_add((key.len() + value_size) as u64)
+            })
+    }
+
+    /// Gets the effective storage size of every address holding a bytecode or datastore entries.
+    ///
+    /// # Returns
+    /// A `BTreeMap` with the address as key and the size in bytes as value
+    pub fn get_storage_sizes(&self) -> BTreeMap<Address, u64> {
+        let mut sizes = self.final_state.read().ledger.get_storage_sizes();
+
+        // addresses whose storage changed since finality need their size to be computed again,
+        // balance updates leave it untouched
+        let mut touched_addresses: BTreeSet<Address> = self
+            .active_history
+            .read()
+            .outputs_up_to(None)
+            .flat_map(|output| get_storage_changes(&output.state_changes.ledger_changes))
+            .collect();
+        touched_addresses.extend(get_storage_changes(&self.added_changes));
+        for addr in touched_addresses {
+            match self.get_storage_size(&addr) {
+                0 => sizes.remove(&addr),
+                size => sizes.insert(addr, size),
+            };
+        }
+        sizes
+    }
+
+    /// Gets the storage costs paid for the ledger entry of an address as it currently is:
+    /// the base cost of the entry, the cost of its bytecode and the cost of each datastore entry.
+    ///
+    /// # Arguments
+    /// * `addr`: address to query
+    pub fn get_storage_cost(&self, addr: &Address) -> Result<Amount, ExecutionError> {
+        let overflow_error =
+            || ExecutionError::RuntimeError("overflow when calculating storage cost".to_string());
+        let bytecode_size = self.get_bytecode_size(addr).unwrap_or_default();
+        let mut cost = self
+            .storage_costs_constants
+            .ledger_cost_per_byte
+            .checked_mul_u64(bytecode_size)
+            .and_then(|bytecode_cost| {
+                bytecode_cost.checked_add(self.storage_costs_constants.ledger_entry_base_cost)
+            })
+            .ok_or_else(overflow_error)?;
+        for key in self.get_datastore_keys(addr) {
+            if let Some(value) = self.get_data_entry(addr, &key) {
+                cost = self
+                    .get_storage_cost_datastore_value(&value)?
+                    .checked_add(
+                        self.storage_costs_constants
+                            .ledger_entry_datastore_base_cost,
+                    )
+                    .and_then(|entry_cost| cost.checked_add(entry_cost))
+                    .ok_or_else(overflow_error)?;
+            }
+        }
+        Ok(cost)
+    }
+
+    /// Reclaims the storage of an address by deleting its bytecode and all its datastore entries.
+    /// Unlike `set_bytecode` and `delete_data_entry`, no storage costs are reimbursed.
+    ///
+    /// # Arguments
+    /// * `addr`: address whose storage is reclaimed
+    pub fn reclaim_storage(&mut self, addr: &Address) {
+        for key in self.get_datastore_keys(addr) {
+            self.added_changes.delete_data_entry(*addr, key);
+        }
+        if self.get_bytecode(addr).map_or(false, |b| !b.is_empty()) {
+            self.added_changes.set_bytecode(*addr, Vec::new());
+        }
+    }
+}
+
+/// Gets the addresses whose bytecode or datastore is changed by the given ledger changes
+fn get_storage_changes(changes: &LedgerChanges) -> impl Iterator<Item = Address> + '_ {
+    changes
+        .0
+        .iter()
+        .filter(|(_, change)| match change {
+            SetUpdateOrDelete::Set(_) | SetUpdateOrDelete::Delete => true,
+            SetUpdateOrDelete::Update(update) => {
+                matches!(update.bytecode, SetOrKeep::Set(_)) || !update.datastore.is_empty()
+            }
+        })
+        .map(|(addr, _)| *addr)
 }
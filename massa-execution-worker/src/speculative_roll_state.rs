@@ -118,6 +118,14 @@ impl SpeculativeRollState {
             )));
         }
 
+        // compute the value of the sold rolls
+        let reimbursement = roll_price.strict_mul_u64(roll_count).map_err(|err| {
+            ExecutionError::RollSellError(format!(
+                "value of the {} rolls sold by {} overflows: {}",
+                roll_count, seller_addr, err
+            ))
+        })?;
+
         let cur_cycle = slot.get_cycle(periods_per_cycle);
 
         // remove the rolls
@@ -129,14 +137,7 @@ impl SpeculativeRollState {
         *current_rolls = owned_count.saturating_sub(roll_count);
 
         // compute deferred credit slot
-        let target_slot = Slot::new_last_of_cycle(
-            cur_cycle
-                .checked_add(3)
-                .expect("unexpected cycle overflow in try_sell_rolls"),
-            periods_per_cycle,
-            thread_count,
-        )
-        .expect("unexepected slot overflot in try_sell_rolls");
+        let target_slot = get_roll_sale_credit_slot(cur_cycle, periods_per_cycle, thread_count);
 
         // add deferred reimbursement corresponding to the sold rolls value
         let credit = self
@@ -145,7 +146,7 @@ impl SpeculativeRollState {
             .0
             .entry(target_slot)
             .or_insert_with(PreHashMap::default);
-        credit.insert(*seller_addr, roll_price.saturating_mul_u64(roll_count));
+        credit.insert(*seller_addr, reimbursement);
 
         Ok(())
     }
@@ -279,6 +280,44 @@ impl SpeculativeRollState {
         res.into_iter().filter(|(_s, v)| !v.is_zero()).collect()
     }
 
+    /// Get the deferred credits of all addresses scheduled in a slot range
+    ///
+    /// # Arguments
+    /// * `start`: first slot of the range (included)
+    /// * `end`: slot bounding the range (excluded)
+    pub fn get_deferred_credits_range(
+        &self,
+        start: Slot,
+        end: Slot,
+    ) -> BTreeMap<(Slot, Address), Amount> {
+        let mut res: BTreeMap<(Slot, Address), Amount> = BTreeMap::new();
+        if start >= end {
+            return res;
+        }
+        // newer changes override older ones
+        let mut apply_credits = |credits: &DeferredCredits| {
+            for (slot, addr_amount) in credits.0.range(start..end) {
+                for (addr, amount) in addr_amount {
+                    res.insert((*slot, *addr), *amount);
+                }
+            }
+        };
+
+        // get values from final state
+        apply_credits(&self.final_state.read().pos_state.deferred_credits);
+
+        // get values from active history, oldest first
+        for hist_item in self.active_history.read().0.iter() {
+            apply_credits(&hist_item.state_changes.pos_changes.deferred_credits);
+        }
+
+        // get added values
+        apply_credits(&self.added_changes.deferred_credits);
+
+        res.retain(|_, amount| !amount.is_zero());
+        res
+    }
+
     /// Get the production statistics for a given address at a given cycle.
     pub fn get_address_cycle_infos(
         &self,
@@ -477,3 +516,20 @@ impl SpeculativeRollState {
         credits
     }
 }
+
+/// Gets the slot at which the rolls sold during cycle `sale_cycle` are reimbursed:
+/// the last slot of the third cycle after the sale
+pub(crate) fn get_roll_sale_credit_slot(
+    sale_cycle: u64,
+    periods_per_cycle: u64,
+    thread_count: u8,
+) -> Slot {
+    Slot::new_last_of_cycle(
+        sale_cycle
+            .checked_add(3)
+            .expect("unexpected cycle overflow in try_sell_rolls"),
+        periods_per_cycle,
+        thread_count,
+    )
+    .expect("unexepected slot overflot in try_sell_rolls")
+}
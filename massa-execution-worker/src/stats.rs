@@ -1,16 +1,21 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_hash::Hash;
+use massa_models::amount::Amount;
 use massa_models::slot::Slot;
-use massa_models::stats::ExecutionStats;
-use massa_time::MassaTime;
+use massa_models::stats::{
+    ExecutionStats, FeeRecommendation, MissRatioProjection, NetworkActivityStats,
+};
+use massa_time::{ClockCompensation, MassaTime};
+use parking_lot::Mutex;
 use std::collections::VecDeque;
 
 /// Execution statistics counter
 pub struct ExecutionStatsCounter {
     /// duration of the time window
     time_window_duration: MassaTime,
-    /// time compensation (milliseconds)
-    compensation_millis: i64,
+    /// shared time compensation
+    clock_compensation: ClockCompensation,
     /// final blocks in the time window (count, instant)
     final_blocks: VecDeque<(usize, MassaTime)>,
     /// final operations executed in the time window (count, instant)
@@ -19,10 +24,10 @@ pub struct ExecutionStatsCounter {
 
 impl ExecutionStatsCounter {
     /// create a new `ExecutionStatsCounter`
-    pub fn new(time_window_duration: MassaTime, compensation_millis: i64) -> Self {
+    pub fn new(time_window_duration: MassaTime, clock_compensation: ClockCompensation) -> Self {
         ExecutionStatsCounter {
             time_window_duration,
-            compensation_millis,
+            clock_compensation,
             final_blocks: Default::default(),
             final_executed_ops: Default::default(),
         }
@@ -53,24 +58,36 @@ impl ExecutionStatsCounter {
 
     /// register final blocks
     pub fn register_final_blocks(&mut self, count: usize) {
-        let current_time =
-            MassaTime::now(self.compensation_millis).expect("could not get current time");
+        let current_time = self
+            .clock_compensation
+            .now()
+            .expect("could not get current time");
         self.final_blocks.push_back((count, current_time));
         self.refresh(current_time);
     }
 
     /// register final executed operations
     pub fn register_final_executed_operations(&mut self, count: usize) {
-        let current_time =
-            MassaTime::now(self.compensation_millis).expect("could not get current time");
+        let current_time = self
+            .clock_compensation
+            .now()
+            .expect("could not get current time");
         self.final_executed_ops.push_back((count, current_time));
         self.refresh(current_time);
     }
 
     /// get statistics
-    pub fn get_stats(&self, active_cursor: Slot) -> ExecutionStats {
-        let current_time =
-            MassaTime::now(self.compensation_millis).expect("could not get current time");
+    pub fn get_stats(
+        &self,
+        active_cursor: Slot,
+        miss_ratio_warnings: Vec<MissRatioProjection>,
+        gas_costs_hash: Hash,
+        (readonly_cache_hits, readonly_cache_misses): (u64, u64),
+    ) -> ExecutionStats {
+        let current_time = self
+            .clock_compensation
+            .now()
+            .expect("could not get current time");
         let start_time = current_time.saturating_sub(self.time_window_duration);
         let map_func = |pair: &(usize, MassaTime)| -> usize {
             let (cnt, t) = pair;
@@ -86,6 +103,219 @@ impl ExecutionStatsCounter {
             time_window_start: start_time,
             time_window_end: current_time,
             active_cursor,
+            miss_ratio_warnings,
+            gas_costs_hash,
+            readonly_cache_hits,
+            readonly_cache_misses,
         }
     }
 }
+
+/// Execution summary of a final slot
+pub struct FinalSlotSummary {
+    /// executed slot
+    pub slot: Slot,
+    /// true if the slot holds a block
+    pub has_block: bool,
+    /// fees of the operations executed in the block of the slot
+    pub operation_fees: Vec<Amount>,
+    /// gas used by the operations of the block of the slot
+    pub gas_used: u64,
+    /// minimal fee per unit of gas of the gas-consuming operations of the block of the slot
+    pub min_fee_per_gas: Option<Amount>,
+    /// number of events emitted during the execution of the slot
+    pub event_count: u64,
+}
+
+/// Network activity counter over the latest final slots
+pub struct NetworkActivityCounter {
+    /// maximal number of final slots remembered
+    max_slots: usize,
+    /// duration of a slot
+    slot_duration: MassaTime,
+    /// maximal gas of a block
+    max_gas_per_block: u64,
+    /// summaries of the latest final slots, oldest first
+    final_slots: VecDeque<FinalSlotSummary>,
+    /// stats of the latest query with the window it was computed over, cleared when a slot becomes final
+    cache: Mutex<Option<(usize, NetworkActivityStats)>>,
+}
+
+impl NetworkActivityCounter {
+    /// create a new `NetworkActivityCounter`
+    ///
+    /// # Arguments
+    /// * `max_slots`: maximal number of final slots remembered
+    /// * `t0`: duration of a period
+    /// * `thread_count`: number of threads
+    /// * `max_gas_per_block`: maximal gas of a block
+    pub fn new(max_slots: usize, t0: MassaTime, thread_count: u8, max_gas_per_block: u64) -> Self {
+        NetworkActivityCounter {
+            max_slots,
+            slot_duration: t0
+                .checked_div_u64(thread_count as u64)
+                .expect("could not compute the slot duration"),
+            max_gas_per_block,
+            final_slots: VecDeque::with_capacity(max_slots),
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// maximal number of final slots statistics can be computed over
+    pub fn max_slots(&self) -> usize {
+        self.max_slots
+    }
+
+    /// register a final slot, forgetting the oldest one if needed
+    pub fn register_final_slot(&mut self, summary: FinalSlotSummary) {
+        self.final_slots.push_back(summary);
+        while self.final_slots.len() > self.max_slots {
+            self.final_slots.pop_front();
+        }
+        *self.cache.get_mut() = None;
+    }
+
+    /// get the activity statistics over the `window` latest final slots
+    pub fn get_stats(&self, window: usize) -> NetworkActivityStats {
+        let mut cache = self.cache.lock();
+        if let Some((cached_window, stats)) = cache.as_ref() {
+            if *cached_window == window {
+                return stats.clone();
+            }
+        }
+        let stats = self.compute_stats(window);
+        *cache = Some((window, stats.clone()));
+        stats
+    }
+
+    /// compute the activity statistics over the `window` latest final slots
+    fn compute_stats(&self, window: usize) -> NetworkActivityStats {
+        let slots = self
+            .final_slots
+            .range(self.final_slots.len().saturating_sub(window)..);
+        let mut block_count: u64 = 0;
+        let mut gas_used: u64 = 0;
+        let mut event_count: u64 = 0;
+        let mut fees: Vec<Amount> = Vec::new();
+        for summary in slots.clone() {
+            if summary.has_block {
+                block_count += 1;
+            }
+            gas_used = gas_used.saturating_add(summary.gas_used);
+            event_count = event_count.saturating_add(summary.event_count);
+            fees.extend(summary.operation_fees.iter().copied());
+        }
+        fees.sort_unstable();
+        let slot_count = slots.len() as u64;
+        let operation_count = fees.len() as u64;
+        let percentile = |p: usize| nearest_rank_percentile(&fees, p);
+        let total_fees = fees
+            .iter()
+            .fold(Amount::zero(), |acc, fee| acc.saturating_add(*fee));
+        let window_millis = self.slot_duration.to_millis().saturating_mul(slot_count);
+        let max_gas = self.max_gas_per_block.saturating_mul(block_count);
+
+        NetworkActivityStats {
+            window_start: slots.clone().next().map(|summary| summary.slot),
+            window_end: slots.last().map(|summary| summary.slot),
+            slot_count,
+            block_count,
+            operation_count,
+            operations_per_second: if window_millis == 0 {
+                0.0
+            } else {
+                operation_count as f64 * 1000.0 / window_millis as f64
+            },
+            average_fee: total_fees
+                .checked_div_u64(operation_count)
+                .unwrap_or_default(),
+            fee_p50: percentile(50),
+            fee_p90: percentile(90),
+            fee_p99: percentile(99),
+            average_gas_per_block: gas_used.checked_div(block_count).unwrap_or(0),
+            gas_utilization: if max_gas == 0 {
+                0.0
+            } else {
+                gas_used as f64 / max_gas as f64
+            },
+            event_count,
+        }
+    }
+    /// get fee suggestions from the inclusion of operations in the blocks of the `window` latest final slots
+    ///
+    /// # Arguments
+    /// * `window`: number of latest final slots to analyze
+    /// * `pool_operation_count`: number of operations waiting in the pool
+    pub fn get_fee_recommendation(
+        &self,
+        window: usize,
+        pool_operation_count: usize,
+    ) -> FeeRecommendation {
+        let mut block_count: u64 = 0;
+        let mut gas_used: u64 = 0;
+        let mut min_fee_per_gas: Option<Amount> = None;
+        let mut block_min_fees: Vec<Amount> = Vec::new();
+        let mut fees: Vec<Amount> = Vec::new();
+        for summary in self
+            .final_slots
+            .range(self.final_slots.len().saturating_sub(window)..)
+            .filter(|summary| summary.has_block)
+        {
+            block_count += 1;
+            gas_used = gas_used.saturating_add(summary.gas_used);
+            min_fee_per_gas = match (min_fee_per_gas, summary.min_fee_per_gas) {
+                (Some(current), Some(block)) => Some(current.min(block)),
+                (current, block) => current.or(block),
+            };
+            // an empty block means that any fee could have been included
+            block_min_fees.push(
+                summary
+                    .operation_fees
+                    .iter()
+                    .min()
+                    .copied()
+                    .unwrap_or_default(),
+            );
+            fees.extend(summary.operation_fees.iter().copied());
+        }
+        block_min_fees.sort_unstable();
+        fees.sort_unstable();
+
+        let max_gas = self.max_gas_per_block.saturating_mul(block_count);
+        let operations_per_block = if block_count == 0 {
+            0.0
+        } else {
+            fees.len() as f64 / block_count as f64
+        };
+        let pool_pressure = pool_operation_count as f64 / operations_per_block.max(1.0);
+
+        let low = nearest_rank_percentile(&block_min_fees, 25);
+        // outbid more blocks when the pool holds more than a block of operations
+        let medium_percentile = if pool_pressure > 1.0 { 75 } else { 50 };
+        let medium = nearest_rank_percentile(&block_min_fees, medium_percentile).max(low);
+        let high = nearest_rank_percentile(&fees, 90).max(medium);
+        FeeRecommendation {
+            block_count,
+            block_fill_rate: if max_gas == 0 {
+                0.0
+            } else {
+                gas_used as f64 / max_gas as f64
+            },
+            min_fee_per_gas: min_fee_per_gas.unwrap_or_default(),
+            pool_operation_count: pool_operation_count as u64,
+            pool_pressure,
+            low,
+            medium,
+            high,
+        }
+    }
+}
+
+/// Get the `p`-th percentile of sorted amounts with the nearest-rank method, zero if there are none
+fn nearest_rank_percentile(sorted: &[Amount], p: usize) -> Amount {
+    let rank = (p * sorted.len() + 99) / 100;
+    sorted
+        .get(rank.saturating_sub(1))
+        .copied()
+        .unwrap_or_default()
+}
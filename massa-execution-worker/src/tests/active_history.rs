@@ -0,0 +1,78 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::active_history::{ActiveHistory, HistorySearchResult};
+use massa_execution_exports::ExecutionOutput;
+use massa_hash::Hash;
+use massa_ledger_exports::{LedgerEntryUpdate, SetOrDelete, SetOrKeep, SetUpdateOrDelete};
+use massa_models::{address::Address, amount::Amount, slot::Slot};
+use massa_signature::KeyPair;
+use std::collections::BTreeMap;
+
+/// Builds a history of `length` consecutive slots, the slot of period `i` setting the balance
+/// of `addr` to `i` coins and its datastore entry `key` to `[i]`
+fn get_history(addr: Address, key: &[u8], length: u64) -> ActiveHistory {
+    let mut history = ActiveHistory::default();
+    for period in 1..=length {
+        let mut output = ExecutionOutput {
+            slot: Slot::new(period, 0),
+            block_id: None,
+            state_changes: Default::default(),
+            events: Default::default(),
+            operation_statuses: Default::default(),
+            address_activities: Default::default(),
+            gas_costs_hash: Hash::compute_from(&[]),
+        };
+        output.state_changes.ledger_changes.0.insert(
+            addr,
+            SetUpdateOrDelete::Update(LedgerEntryUpdate {
+                balance: SetOrKeep::Set(Amount::from_raw(period)),
+                bytecode: SetOrKeep::Keep,
+                datastore: BTreeMap::from([(key.to_vec(), SetOrDelete::Set(vec![period as u8]))]),
+            }),
+        );
+        history.0.push_back(output);
+    }
+    history
+}
+
+/// The slot at the read depth limit is taken into account, the next one is not
+#[test]
+fn test_candidate_read_depth() {
+    let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let key = b"key";
+    let max_depth = Some(3);
+
+    // the history is exactly as deep as the limit: its newest slot is read
+    let history = get_history(addr, key, 3);
+    assert!(matches!(
+        history.fetch_balance_up_to(&addr, max_depth),
+        HistorySearchResult::Present(balance) if balance == Amount::from_raw(3)
+    ));
+    assert!(matches!(
+        history.fetch_active_history_data_entry_up_to(&addr, key, max_depth),
+        HistorySearchResult::Present(value) if value == vec![3]
+    ));
+    assert_eq!(history.outputs_up_to(max_depth).count(), 3);
+
+    // the history is one slot deeper than the limit: its newest slot is ignored
+    let history = get_history(addr, key, 4);
+    assert!(matches!(
+        history.fetch_balance_up_to(&addr, max_depth),
+        HistorySearchResult::Present(balance) if balance == Amount::from_raw(3)
+    ));
+    assert!(matches!(
+        history.fetch_active_history_data_entry_up_to(&addr, key, max_depth),
+        HistorySearchResult::Present(value) if value == vec![3]
+    ));
+    assert_eq!(history.outputs_up_to(max_depth).count(), 3);
+    assert_eq!(
+        history.outputs_up_to(max_depth).last().unwrap().slot,
+        Slot::new(3, 0)
+    );
+
+    // without limit, the newest slot is always read
+    assert!(matches!(
+        history.fetch_balance_up_to(&addr, None),
+        HistorySearchResult::Present(balance) if balance == Amount::from_raw(4)
+    ));
+}
@@ -0,0 +1,65 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::active_history::ActiveHistory;
+use crate::context::ExecutionContext;
+use crate::interface_impl::InterfaceImpl;
+use crate::tests::mock::get_sample_state;
+use massa_execution_exports::{ExecutionConfig, ExecutionError, ExecutionStackElement};
+use massa_models::{address::Address, amount::Amount, slot::Slot};
+use massa_sc_runtime::Interface;
+use massa_signature::KeyPair;
+use parking_lot::{Mutex, RwLock};
+use serial_test::serial;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// A contract calling itself recursively fills the call stack up to `max_call_stack_depth`,
+/// then the next nested call fails with a call stack overflow.
+#[test]
+#[serial]
+fn test_call_stack_overflow() {
+    let config = ExecutionConfig {
+        max_call_stack_depth: 4,
+        ..ExecutionConfig::default()
+    };
+    let (final_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let address = Address::from_public_key(&keypair.get_public_key());
+
+    // execution context of an operation sent by the sample address
+    let mut context = ExecutionContext::active_slot(
+        config.clone(),
+        Slot::new(1, 0),
+        None,
+        final_state,
+        Arc::new(RwLock::new(ActiveHistory::default())),
+    );
+    context.max_gas = 1_000_000;
+    context.stack.push(ExecutionStackElement {
+        address,
+        coins: Amount::zero(),
+        owned_addresses: vec![address],
+        operation_datastore: None,
+    });
+    let interface = InterfaceImpl::new(config, Arc::new(Mutex::new(context)));
+
+    // the operation deploys a contract and calls it, the contract then calls itself
+    let contract = interface.create_module(b"bytecode").unwrap();
+    for _ in 1..4 {
+        assert_eq!(interface.init_call(&contract, 0).unwrap(), b"bytecode");
+    }
+
+    // the stack holds 4 elements: the next nested call overflows it
+    let err = interface.init_call(&contract, 0).unwrap_err();
+    match err.downcast_ref::<ExecutionError>() {
+        Some(ExecutionError::CallStackOverflow(message)) => {
+            assert!(message.contains("maximum depth of 4"));
+            assert!(message.contains(&address.to_string()));
+        }
+        _ => panic!("expected a call stack overflow, got: {}", err),
+    }
+
+    // once the innermost call finishes, the contract can call itself again
+    interface.finish_call().unwrap();
+    interface.init_call(&contract, 0).unwrap();
+}
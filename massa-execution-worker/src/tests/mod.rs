@@ -1,4 +1,9 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+mod active_history;
+mod call_stack;
 mod mock;
+mod op_executions;
 mod scenarios_mandatories;
+mod stats;
+mod storage_rent;
@@ -0,0 +1,92 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::execution::ExecutionState;
+use crate::tests::mock::get_sample_state;
+use massa_execution_exports::{ExecutionChannels, ExecutionConfig, ExecutionOutput};
+use massa_hash::Hash;
+use massa_models::{
+    amount::Amount,
+    api::{OperationExecutionInfo, OperationStatusNotification},
+    block::BlockId,
+    operation::OperationId,
+    slot::Slot,
+    wrapped::Id,
+};
+use serial_test::serial;
+
+/// Builds the output of a block at `slot` executing the operation `op_id`,
+/// failing if `execution_error` is set
+fn get_output(slot: Slot, op_id: OperationId, execution_error: Option<&str>) -> ExecutionOutput {
+    let block_id = BlockId(Hash::compute_from(&slot.to_bytes_key()));
+    ExecutionOutput {
+        slot,
+        block_id: Some(block_id),
+        state_changes: Default::default(),
+        events: Default::default(),
+        operation_statuses: vec![OperationStatusNotification {
+            id: op_id,
+            involved_addresses: Vec::new(),
+            fee: Amount::zero(),
+            execution: OperationExecutionInfo {
+                block_id,
+                slot,
+                is_final: false,
+                gas_used: 1_000,
+                execution_error: execution_error.map(String::from),
+            },
+        }],
+        address_activities: Default::default(),
+        gas_costs_hash: Hash::compute_from(&[]),
+    }
+}
+
+/// The outcomes of candidate and final operation executions are reported,
+/// and the oldest final outcomes are forgotten past `max_final_op_executions`
+#[test]
+#[serial]
+fn test_op_execution_outcomes() {
+    let config = ExecutionConfig {
+        max_final_op_executions: 2,
+        ..ExecutionConfig::default()
+    };
+    let (final_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let mut execution_state =
+        ExecutionState::new(config, final_state, ExecutionChannels::default());
+    let op_ids: Vec<OperationId> = (0..4u8)
+        .map(|index| OperationId::new(Hash::compute_from(&[index])))
+        .collect();
+
+    // three operations are executed in final blocks, the last one failing
+    execution_state.apply_final_execution_output(get_output(Slot::new(1, 0), op_ids[0], None));
+    execution_state.apply_final_execution_output(get_output(Slot::new(1, 1), op_ids[1], None));
+    execution_state.apply_final_execution_output(get_output(
+        Slot::new(1, 2),
+        op_ids[2],
+        Some("runtime error"),
+    ));
+    // the last operation is executed in a candidate block
+    execution_state.apply_active_execution_output(get_output(Slot::new(1, 3), op_ids[3], None));
+
+    // the oldest final outcome was forgotten
+    assert!(execution_state.get_op_execution_info(&op_ids[0]).is_none());
+
+    let outcome = execution_state.get_op_execution_info(&op_ids[1]).unwrap();
+    assert!(outcome.is_final);
+    assert_eq!(outcome.slot, Slot::new(1, 1));
+    assert_eq!(outcome.gas_used, 1_000);
+    assert!(outcome.execution_error.is_none());
+
+    let outcome = execution_state.get_op_execution_info(&op_ids[2]).unwrap();
+    assert!(outcome.is_final);
+    assert_eq!(outcome.execution_error.as_deref(), Some("runtime error"));
+
+    let outcome = execution_state.get_op_execution_info(&op_ids[3]).unwrap();
+    assert!(!outcome.is_final);
+    assert_eq!(outcome.slot, Slot::new(1, 3));
+
+    // an operation that was never executed has no outcome
+    let unknown_op_id = OperationId::new(Hash::compute_from(b"unknown"));
+    assert!(execution_state
+        .get_op_execution_info(&unknown_op_id)
+        .is_none());
+}
@@ -3,17 +3,18 @@
 use crate::start_execution_worker;
 use crate::tests::mock::{create_block, get_random_address_full, get_sample_state};
 use massa_execution_exports::{
-    ExecutionConfig, ExecutionController, ExecutionError, ReadOnlyExecutionRequest,
-    ReadOnlyExecutionTarget,
+    ExecutionChannels, ExecutionConfig, ExecutionController, ExecutionError, GasCosts,
+    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget, ABI_NAMES,
 };
 use massa_models::config::{LEDGER_ENTRY_BASE_SIZE, LEDGER_ENTRY_DATASTORE_BASE_SIZE};
 use massa_models::prehash::PreHashMap;
 use massa_models::{address::Address, amount::Amount, slot::Slot};
 use massa_models::{
-    api::EventFilter,
+    api::{AddressActivityKind, DeferredCreditInfo, EventFilter, ExecutionTraceOrigin, RewardRole},
     block::BlockId,
     datastore::Datastore,
     operation::{Operation, OperationSerializer, OperationType, WrappedOperation},
+    streaming_step::StreamingStep,
     wrapped::WrappedContent,
 };
 use massa_signature::KeyPair;
@@ -32,6 +33,7 @@ fn test_execution_shutdown() {
         ExecutionConfig::default(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
     );
     manager.stop();
 }
@@ -44,6 +46,7 @@ fn test_sending_command() {
         ExecutionConfig::default(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
     );
     controller.update_blockclique_status(
         Default::default(),
@@ -53,6 +56,41 @@ fn test_sending_command() {
     manager.stop();
 }
 
+#[test]
+#[serial]
+fn test_cycle_stats() {
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let (mut manager, controller) = start_execution_worker(
+        ExecutionConfig::default(),
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
+    );
+    // the initial cycle holds the 100 rolls of each of the two sample stakers
+    let stats = controller.get_cycle_stats(0, None, 1).unwrap();
+    assert!(!stats.complete);
+    assert_eq!(stats.staker_count, 2);
+    assert_eq!(stats.roll_count, 200);
+    assert_eq!((stats.ok_count, stats.nok_count), (0, 0));
+    assert_eq!(stats.miss_ratio_exceeded_count, 0);
+    assert_eq!(stats.stakers.len(), 1);
+    assert_eq!(stats.stakers[0].roll_count, 100);
+    // the next page starts after the last staker of the first one
+    let next_page = controller
+        .get_cycle_stats(0, Some(stats.stakers[0].address), 1)
+        .unwrap();
+    assert_eq!(next_page.stakers.len(), 1);
+    assert!(next_page.stakers[0].address > stats.stakers[0].address);
+    assert!(controller
+        .get_cycle_stats(0, Some(next_page.stakers[0].address), 1)
+        .unwrap()
+        .stakers
+        .is_empty());
+    // cycles missing from the final cycle history are unknown
+    assert!(controller.get_cycle_stats(1, None, 1).is_none());
+    manager.stop();
+}
+
 #[test]
 #[serial]
 fn test_readonly_execution() {
@@ -61,6 +99,7 @@ fn test_readonly_execution() {
         ExecutionConfig::default(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
     );
     let mut res = controller
         .execute_readonly_request(ReadOnlyExecutionRequest {
@@ -70,6 +109,8 @@ fn test_readonly_execution() {
             target: ReadOnlyExecutionTarget::BytecodeExecution(
                 include_bytes!("./wasm/event_test.wasm").to_vec(),
             ),
+            request_id: None,
+            deployment_simulation: None,
         })
         .expect("readonly execution failed");
 
@@ -79,6 +120,138 @@ fn test_readonly_execution() {
     manager.stop();
 }
 
+#[test]
+#[serial]
+fn test_readonly_execution_event_limits() {
+    let execute_event_test = |config: ExecutionConfig| {
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+        let (mut manager, controller) = start_execution_worker(
+            config,
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+            ExecutionChannels::default(),
+        );
+        let res = controller.execute_readonly_request(ReadOnlyExecutionRequest {
+            max_gas: 1_000_000,
+            simulated_gas_price: Amount::from_mantissa_scale(1_000_000, 0),
+            call_stack: vec![],
+            target: ReadOnlyExecutionTarget::BytecodeExecution(
+                include_bytes!("./wasm/event_test.wasm").to_vec(),
+            ),
+            request_id: None,
+            deployment_simulation: None,
+        });
+        manager.stop();
+        res
+    };
+
+    // the usage of the emitted event is reported
+    let res = execute_event_test(ExecutionConfig::default()).expect("readonly execution failed");
+    assert_eq!(res.event_usage.count, 1);
+    assert!(res.event_usage.size > 0);
+
+    // the call fails when the event exceeds one of the limits
+    let err = execute_event_test(ExecutionConfig {
+        max_event_count_per_call: 0,
+        ..ExecutionConfig::default()
+    })
+    .expect_err("the event count limit was not enforced");
+    assert!(err.to_string().contains("events"));
+    execute_event_test(ExecutionConfig {
+        max_event_size_per_slot: res.event_usage.size - 1,
+        ..ExecutionConfig::default()
+    })
+    .expect_err("the event size limit was not enforced");
+}
+
+#[test]
+#[serial]
+fn test_readonly_execution_gas_costs() {
+    let execute_event_test = |gas_costs: GasCosts| {
+        let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+        let (mut manager, controller) = start_execution_worker(
+            ExecutionConfig {
+                gas_costs_hash: gas_costs.compute_hash(),
+                gas_costs,
+                ..ExecutionConfig::default()
+            },
+            sample_state.clone(),
+            sample_state.read().pos_state.selector.clone(),
+            ExecutionChannels::default(),
+        );
+        let res = controller.execute_readonly_request(ReadOnlyExecutionRequest {
+            max_gas: 1_000_000,
+            simulated_gas_price: Amount::from_mantissa_scale(1_000_000, 0),
+            call_stack: vec![],
+            target: ReadOnlyExecutionTarget::BytecodeExecution(
+                include_bytes!("./wasm/event_test.wasm").to_vec(),
+            ),
+            request_id: None,
+            deployment_simulation: None,
+        });
+        manager.stop();
+        res
+    };
+    let table_with_event_cost = |cost: u64| GasCosts {
+        abi_costs: [("generate_event".to_string(), cost)].into_iter().collect(),
+        ..GasCosts::default()
+    };
+
+    // the extra cost of the ABI is added to the gas metered by the runtime
+    let base_gas_cost = execute_event_test(GasCosts::default()).unwrap().gas_cost;
+    let mut res = execute_event_test(table_with_event_cost(1_000)).unwrap();
+    assert_eq!(res.gas_cost, base_gas_cost + 1_000);
+    assert_eq!(res.out.events.take().len(), 1, "wrong number of events");
+    assert_ne!(
+        res.out.gas_costs_hash,
+        GasCosts::default().compute_hash(),
+        "the output must be tied to the gas cost table"
+    );
+
+    // the execution fails when the ABI costs more than the max gas
+    assert!(execute_event_test(table_with_event_cost(2_000_000)).is_err());
+
+    // the execution also fails when the ABI fits in the max gas on its own,
+    // but not on top of the gas metered by the runtime
+    assert!(execute_event_test(table_with_event_cost(1_000_000 - base_gas_cost + 1)).is_err());
+}
+
+#[test]
+fn test_gas_costs_abi_names() {
+    // every ABI called by the interface can be priced
+    let source = include_str!("../interface_impl.rs");
+    let mut called_count = 0;
+    for call in source.split("abi_call!(").skip(1) {
+        let name = call
+            .split('"')
+            .nth(1)
+            .expect("ABI name not found after abi_call!");
+        assert!(
+            ABI_NAMES.contains(&name),
+            "ABI {} missing from ABI_NAMES",
+            name
+        );
+        called_count += 1;
+    }
+    assert_eq!(called_count, ABI_NAMES.len());
+
+    // a table pricing an unknown ABI is rejected
+    let mut table = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut table, br#"{"abi_costs": {"generate_events": 1000}}"#).unwrap();
+    assert!(matches!(
+        GasCosts::load(table.path()),
+        Err(ExecutionError::GasCostsError(_))
+    ));
+    let mut table = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut table, br#"{"abi_costs": {"generate_event": 1000}}"#).unwrap();
+    assert_eq!(
+        GasCosts::load(table.path())
+            .unwrap()
+            .get_abi_cost("generate_event"),
+        1000
+    );
+}
+
 /// Feeds the execution worker with genesis blocks to start it
 fn init_execution_worker(
     config: &ExecutionConfig,
@@ -129,6 +302,7 @@ fn test_nested_call_gas_usage() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -269,6 +443,7 @@ fn send_and_receive_async_message() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -333,6 +508,7 @@ pub fn send_and_receive_transaction() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -354,6 +530,7 @@ pub fn send_and_receive_transaction() {
         &sender_keypair,
     )
     .unwrap();
+    let operation_id = operation.id;
     // create the block containing the transaction operation
     storage.store_operations(vec![operation.clone()]);
     let block = create_block(KeyPair::generate(), vec![operation], Slot::new(1, 0)).unwrap();
@@ -388,6 +565,139 @@ pub fn send_and_receive_transaction() {
                     .saturating_mul_u64(LEDGER_ENTRY_BASE_SIZE as u64)
             )
     );
+    // check the final activities of the sender, the recipient and the block producer
+    let sender_history = controller.get_address_history(
+        &Address::from_public_key(&sender_keypair.get_public_key()),
+        None,
+        None,
+    );
+    assert_eq!(sender_history.len(), 1);
+    assert!(sender_history[0].is_final);
+    assert_eq!(
+        sender_history[0].kind,
+        AddressActivityKind::OperationCreated {
+            operation_id,
+            block_id: block.id
+        }
+    );
+    let recipient_history = controller.get_address_history(&recipient_address, None, None);
+    assert_eq!(recipient_history.len(), 1);
+    assert_eq!(
+        recipient_history[0].kind,
+        AddressActivityKind::OperationCredit {
+            operation_id,
+            amount: Amount::from_str("100").unwrap()
+        }
+    );
+    assert_eq!(
+        controller
+            .get_address_history(&block.creator_address, None, None)
+            .into_iter()
+            .map(|activity| activity.kind)
+            .collect::<Vec<_>>(),
+        vec![
+            AddressActivityKind::BlockProduced { block_id: block.id },
+            AddressActivityKind::BlockReward {
+                block_id: block.id,
+                role: RewardRole::BlockCreator,
+                amount: exec_cfg.block_reward
+            }
+        ]
+    );
+    assert!(controller
+        .get_address_history(&recipient_address, Some(Slot::new(1, 1)), None)
+        .is_empty());
+    // check the network activity of the latest final slot
+    let network_stats = controller.get_network_stats(1);
+    assert_eq!(
+        network_stats.window_end,
+        Some(block.content.header.content.slot)
+    );
+    assert_eq!(network_stats.slot_count, 1);
+    assert_eq!(network_stats.block_count, 1);
+    assert_eq!(network_stats.operation_count, 1);
+    assert_eq!(network_stats.fee_p50, Amount::zero());
+    // stop the execution controller
+    manager.stop();
+}
+
+#[test]
+#[serial]
+pub fn simulate_block() {
+    // setup the period duration
+    let exec_cfg = ExecutionConfig {
+        t0: 100.into(),
+        cursor_delay: 0.into(),
+        ..ExecutionConfig::default()
+    };
+    // get a sample final state
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+
+    // init the storage
+    let storage = Storage::create_root();
+    // start the execution worker
+    let (mut manager, controller) = start_execution_worker(
+        exec_cfg.clone(),
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
+    );
+    // initialize the execution system with genesis blocks
+    init_execution_worker(&exec_cfg, &storage, controller.clone());
+    // create a transaction from a funded address and one from an address without coins
+    let sender_keypair =
+        KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let (recipient_address, broke_keypair) = get_random_address_full();
+    let create_transaction = |keypair: &KeyPair, chain_id: u64| {
+        Operation::new_signed(
+            Operation {
+                fee: Amount::from_str("1").unwrap(),
+                expire_period: 10,
+                op: OperationType::Transaction {
+                    recipient_address,
+                    amount: Amount::from_str("100").unwrap(),
+                },
+            },
+            keypair,
+            chain_id,
+            exec_cfg.chain_id_activation_period,
+        )
+        .unwrap()
+    };
+    let funded_operation = create_transaction(&sender_keypair, exec_cfg.chain_id);
+    let broke_operation = create_transaction(&broke_keypair, exec_cfg.chain_id);
+    let other_chain_operation =
+        create_transaction(&get_random_address_full().1, exec_cfg.chain_id + 1);
+    // simulate a block containing the three operations
+    let simulation = controller
+        .simulate_block(
+            Slot::new(1, 0),
+            vec![
+                funded_operation.clone(),
+                broke_operation.clone(),
+                other_chain_operation.clone(),
+            ],
+        )
+        .expect("block simulation failed");
+    assert_eq!(simulation.operations.len(), 3);
+    assert_eq!(simulation.operations[0].operation_id, funded_operation.id);
+    assert!(simulation.operations[0].success);
+    assert_eq!(simulation.operations[1].operation_id, broke_operation.id);
+    assert!(!simulation.operations[1].success);
+    assert!(simulation.operations[1].error.is_some());
+    // the operation signed for another network is not executed
+    assert_eq!(
+        simulation.operations[2].operation_id,
+        other_chain_operation.id
+    );
+    assert!(!simulation.operations[2].success);
+    assert_eq!(simulation.operations[2].gas_used, 0);
+    assert_eq!(simulation.total_fees, Amount::from_str("1").unwrap());
+    // check that the simulation did not affect the candidate state
+    assert_eq!(
+        controller.get_final_and_candidate_balance(&[recipient_address]),
+        vec![(None, None)]
+    );
     // stop the execution controller
     manager.stop();
 }
@@ -411,6 +721,7 @@ pub fn roll_buy() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -476,6 +787,7 @@ pub fn roll_sell() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -493,6 +805,7 @@ pub fn roll_sell() {
         &keypair,
     )
     .unwrap();
+    let operation_id = operation.id;
     // create the block containing the roll buy operation
     storage.store_operations(vec![operation.clone()]);
     let block = create_block(KeyPair::generate(), vec![operation], Slot::new(1, 0)).unwrap();
@@ -520,6 +833,127 @@ pub fn roll_sell() {
             .get_deferred_credits_at(&Slot::new(7, 1)),
         credits
     );
+    drop(sample_read);
+    // check the network-wide deferred credits listing
+    let expected_credit = DeferredCreditInfo {
+        slot: Slot::new(7, 1),
+        address,
+        amount: Amount::from_str("1000").unwrap(),
+    };
+    let (page, next_cursor) = controller.get_deferred_credits(
+        Slot::new(0, 0),
+        Slot::new(8, 0),
+        StreamingStep::Started,
+        1,
+    );
+    assert_eq!(page, vec![expected_credit.clone()]);
+    assert_eq!(next_cursor, StreamingStep::Finished);
+    let (page, _) = controller.get_deferred_credits(
+        Slot::new(0, 0),
+        Slot::new(7, 1),
+        StreamingStep::Started,
+        10,
+    );
+    assert!(page.is_empty());
+    // check that the sale is remembered with the credit it is reimbursed by
+    let seller_history = controller.get_address_history(&address, None, None);
+    assert!(seller_history.iter().any(|activity| activity.kind
+        == AddressActivityKind::RollSell {
+            operation_id,
+            roll_count: 10,
+            credit_slot: Slot::new(7, 1),
+            amount: Amount::from_str("1000").unwrap(),
+        }));
+    // stop the execution controller
+    manager.stop();
+}
+
+/// Only the successful roll sales are recorded, with the deferred credit reimbursing them
+#[test]
+#[serial]
+pub fn roll_sell_activities() {
+    // setup the period duration
+    let exec_cfg = ExecutionConfig {
+        t0: 100.into(),
+        periods_per_cycle: 2,
+        thread_count: 2,
+        cursor_delay: 0.into(),
+        ..Default::default()
+    };
+    // get a sample final state
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+
+    // init the storage
+    let mut storage = Storage::create_root();
+    // start the execution worker
+    let (mut manager, controller) = start_execution_worker(
+        exec_cfg.clone(),
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
+    );
+    // initialize the execution system with genesis blocks
+    init_execution_worker(&exec_cfg, &storage, controller.clone());
+    // generate the keypair and its corresponding address, owning 100 rolls
+    let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let address = Address::from_public_key(&keypair.get_public_key());
+    // create a sale of more rolls than owned, then a valid one
+    let operations: Vec<WrappedOperation> = [1000, 10]
+        .into_iter()
+        .map(|roll_count| {
+            Operation::new_wrapped(
+                Operation {
+                    fee: Amount::zero(),
+                    expire_period: 10,
+                    op: OperationType::RollSell { roll_count },
+                },
+                OperationSerializer::new(),
+                &keypair,
+            )
+            .unwrap()
+        })
+        .collect();
+    let sold_operation_id = operations[1].id;
+    // create the block containing the roll sell operations
+    storage.store_operations(operations.clone());
+    let block = create_block(KeyPair::generate(), operations, Slot::new(1, 0)).unwrap();
+    // store the block in storage
+    storage.store_block(block.clone());
+    // set the block as final so the sales are processed
+    let mut finalized_blocks: HashMap<Slot, BlockId> = Default::default();
+    finalized_blocks.insert(block.content.header.content.slot, block.id);
+    let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
+    block_storage.insert(block.id, storage.clone());
+    controller.update_blockclique_status(
+        finalized_blocks,
+        Default::default(),
+        block_storage.clone(),
+    );
+    std::thread::sleep(Duration::from_millis(350));
+    // check that only the valid sale is recorded
+    let sales: Vec<AddressActivityKind> = controller
+        .get_address_history(&address, None, None)
+        .into_iter()
+        .map(|activity| activity.kind)
+        .filter(|kind| matches!(kind, AddressActivityKind::RollSell { .. }))
+        .collect();
+    assert_eq!(
+        sales,
+        vec![AddressActivityKind::RollSell {
+            operation_id: sold_operation_id,
+            roll_count: 10,
+            credit_slot: Slot::new(7, 1),
+            amount: Amount::from_str("1000").unwrap(),
+        }]
+    );
+    // check that the recorded credit slot is the one of the upcoming deferred credit
+    let info = controller
+        .get_addresses_infos(&[address], &Default::default())
+        .remove(0);
+    assert_eq!(
+        info.future_deferred_credits.get(&Slot::new(7, 1)),
+        Some(&Amount::from_str("1000").unwrap())
+    );
     // stop the execution controller
     manager.stop();
 }
@@ -544,6 +978,7 @@ fn sc_execution_error() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -583,6 +1018,75 @@ fn sc_execution_error() {
     manager.stop();
 }
 
+#[test]
+#[serial]
+fn replay_slot_with_trace() {
+    // setup the period duration
+    let exec_cfg = ExecutionConfig {
+        t0: 100.into(),
+        cursor_delay: 0.into(),
+        ..ExecutionConfig::default()
+    };
+    // get a sample final state
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+
+    // init the storage
+    let mut storage = Storage::create_root();
+    // start the execution worker
+    let (mut manager, controller) = start_execution_worker(
+        exec_cfg.clone(),
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
+    );
+    // initialize the execution system with genesis blocks
+    init_execution_worker(&exec_cfg, &storage, controller.clone());
+    // keypair associated to thread 0
+    let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    // create a candidate block executing a smart contract that fails in an ABI call
+    let bytecode = include_bytes!("./wasm/execution_error.wasm");
+    let operation = create_execute_sc_operation(&keypair, bytecode).unwrap();
+    storage.store_operations(vec![operation.clone()]);
+    let block = create_block(
+        KeyPair::generate(),
+        vec![operation.clone()],
+        Slot::new(1, 0),
+    )
+    .unwrap();
+    storage.store_block(block.clone());
+    let mut blockclique: HashMap<Slot, BlockId> = Default::default();
+    blockclique.insert(block.content.header.content.slot, block.id);
+    let mut block_storage: PreHashMap<BlockId, Storage> = Default::default();
+    block_storage.insert(block.id, storage.clone());
+    controller.update_blockclique_status(Default::default(), Some(blockclique), block_storage);
+    // wait for the candidate slot to be executed
+    std::thread::sleep(Duration::from_millis(150));
+
+    // replay the candidate slot
+    let trace = controller
+        .replay_slot_with_trace(Slot::new(1, 0))
+        .expect("slot replay failed");
+    assert_eq!(trace.block_id, Some(block.id));
+    let step = trace.steps.last().expect("missing operation step");
+    assert_eq!(step.origin, ExecutionTraceOrigin::Operation(operation.id));
+    assert!(!step.abi_calls.is_empty());
+    assert!(step.gas_used.is_some());
+    assert!(step.error.is_some());
+    // the replay did not affect the candidate state
+    assert_eq!(
+        controller
+            .replay_slot_with_trace(Slot::new(1, 0))
+            .unwrap()
+            .steps
+            .len(),
+        trace.steps.len()
+    );
+    // final slots can not be replayed
+    assert!(controller.replay_slot_with_trace(Slot::new(0, 0)).is_err());
+    // stop the execution controller
+    manager.stop();
+}
+
 #[test]
 #[serial]
 fn sc_datastore() {
@@ -603,6 +1107,7 @@ fn sc_datastore() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -662,6 +1167,7 @@ fn set_bytecode_error() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -719,6 +1225,7 @@ fn datastore_manipulations() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -795,6 +1302,7 @@ fn events_from_switching_blockclique() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -957,6 +1465,7 @@ fn sc_builtins() {
         exec_cfg.clone(),
         sample_state.clone(),
         sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
     );
     // initialize the execution system with genesis blocks
     init_execution_worker(&exec_cfg, &storage, controller.clone());
@@ -1006,3 +1515,160 @@ fn sc_builtins() {
     // stop the execution controller
     manager.stop();
 }
+
+/// The watchdog of a slot execution records the ABI calls made after the time budget of the slot, without failing them.
+#[test]
+#[serial]
+fn test_slot_execution_watchdog() {
+    use crate::active_history::ActiveHistory;
+    use crate::context::ExecutionContext;
+    use parking_lot::RwLock;
+    use std::sync::Arc;
+
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let active_history = Arc::new(RwLock::new(ActiveHistory::default()));
+    let new_context = |max_slot_execution_time: u64| {
+        ExecutionContext::active_slot(
+            ExecutionConfig {
+                max_slot_execution_time: MassaTime::from_millis(max_slot_execution_time),
+                ..ExecutionConfig::default()
+            },
+            Slot::new(1, 0),
+            None,
+            sample_state.clone(),
+            active_history.clone(),
+        )
+    };
+
+    // the budget is exceeded
+    let mut context = new_context(1);
+    std::thread::sleep(Duration::from_millis(5));
+    context.check_execution_deadline();
+    assert!(context.execution_timed_out);
+
+    // the budget is not exceeded, or there is none
+    for max_slot_execution_time in [60000, 0] {
+        let mut context = new_context(max_slot_execution_time);
+        std::thread::sleep(Duration::from_millis(5));
+        context.check_execution_deadline();
+        assert!(!context.execution_timed_out);
+    }
+}
+
+/// The datastore keys and cycle infos of an address are returned page by page,
+/// and are skipped when no key or cycle info is requested.
+#[test]
+#[serial]
+fn test_get_addresses_infos_bounds() {
+    use massa_execution_exports::AddressInfoBounds;
+    use massa_ledger_exports::{
+        LedgerChanges, LedgerController, LedgerEntryUpdate, SetOrDelete, SetOrKeep,
+        SetUpdateOrDelete,
+    };
+
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let address = Address::from_public_key(&keypair.get_public_key());
+
+    // the sample address holds three final datastore keys
+    let mut changes = LedgerChanges::default();
+    changes.0.insert(
+        address,
+        SetUpdateOrDelete::Update(LedgerEntryUpdate {
+            balance: SetOrKeep::Keep,
+            bytecode: SetOrKeep::Keep,
+            datastore: BTreeMap::from([
+                (vec![1], SetOrDelete::Set(vec![1])),
+                (vec![2], SetOrDelete::Set(vec![2])),
+                (vec![3], SetOrDelete::Set(vec![3])),
+            ]),
+        }),
+    );
+    let final_slot = sample_state.read().slot;
+    sample_state
+        .write()
+        .ledger
+        .apply_changes(changes, final_slot);
+
+    let (mut manager, controller) = start_execution_worker(
+        ExecutionConfig::default(),
+        sample_state.clone(),
+        sample_state.read().pos_state.selector.clone(),
+        ExecutionChannels::default(),
+    );
+
+    // the first page holds two keys and points to the next one
+    let info = controller
+        .get_addresses_infos(
+            &[address],
+            &AddressInfoBounds {
+                datastore_keys_cursor: StreamingStep::Started,
+                max_datastore_keys: 2,
+                max_cycle_infos: 1,
+            },
+        )
+        .remove(0);
+    assert_eq!(
+        info.final_datastore_keys.into_iter().collect::<Vec<_>>(),
+        vec![vec![1], vec![2]]
+    );
+    assert_eq!(
+        info.candidate_datastore_keys
+            .into_iter()
+            .collect::<Vec<_>>(),
+        vec![vec![1], vec![2]]
+    );
+    assert_eq!(
+        info.next_datastore_keys_cursor,
+        StreamingStep::Ongoing(vec![2])
+    );
+    assert_eq!(info.final_balance, Amount::from_str("300_000").unwrap());
+
+    // only the latest cycle info is kept
+    let all_cycle_infos = controller
+        .get_addresses_infos(&[address], &AddressInfoBounds::default())
+        .remove(0)
+        .cycle_infos;
+    assert!(!all_cycle_infos.is_empty());
+    assert_eq!(info.cycle_infos.len(), 1);
+    assert_eq!(
+        info.cycle_infos[0].cycle,
+        all_cycle_infos.last().unwrap().cycle
+    );
+
+    // the last page holds the remaining key
+    let info = controller
+        .get_addresses_infos(
+            &[address],
+            &AddressInfoBounds {
+                datastore_keys_cursor: StreamingStep::Ongoing(vec![2]),
+                max_datastore_keys: 2,
+                max_cycle_infos: 1,
+            },
+        )
+        .remove(0);
+    assert_eq!(
+        info.final_datastore_keys.into_iter().collect::<Vec<_>>(),
+        vec![vec![3]]
+    );
+    assert_eq!(info.next_datastore_keys_cursor, StreamingStep::Finished);
+
+    // neither keys nor cycle infos are gathered when none is requested
+    let info = controller
+        .get_addresses_infos(
+            &[address],
+            &AddressInfoBounds {
+                datastore_keys_cursor: StreamingStep::Started,
+                max_datastore_keys: 0,
+                max_cycle_infos: 0,
+            },
+        )
+        .remove(0);
+    assert!(info.final_datastore_keys.is_empty());
+    assert!(info.candidate_datastore_keys.is_empty());
+    assert_eq!(info.next_datastore_keys_cursor, StreamingStep::Finished);
+    assert!(info.cycle_infos.is_empty());
+    assert_eq!(info.final_balance, Amount::from_str("300_000").unwrap());
+
+    manager.stop();
+}
@@ -0,0 +1,81 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::stats::{FinalSlotSummary, NetworkActivityCounter};
+use massa_models::{amount::Amount, slot::Slot};
+use massa_time::MassaTime;
+use std::str::FromStr;
+
+fn amount(value: &str) -> Amount {
+    Amount::from_str(value).unwrap()
+}
+
+/// Registers final slots holding blocks with the given fees, gas used and minimal fee per gas
+fn get_network_activity(blocks: Vec<(Vec<&str>, u64, Option<&str>)>) -> NetworkActivityCounter {
+    let mut counter = NetworkActivityCounter::new(100, MassaTime::from_millis(16000), 32, 1000);
+    // a slot without block is ignored
+    counter.register_final_slot(FinalSlotSummary {
+        slot: Slot::new(1, 0),
+        has_block: false,
+        operation_fees: vec![],
+        gas_used: 0,
+        min_fee_per_gas: None,
+        event_count: 0,
+    });
+    for (index, (fees, gas_used, min_fee_per_gas)) in blocks.into_iter().enumerate() {
+        counter.register_final_slot(FinalSlotSummary {
+            slot: Slot::new(1, index as u8 + 1),
+            has_block: true,
+            operation_fees: fees.into_iter().map(amount).collect(),
+            gas_used,
+            min_fee_per_gas: min_fee_per_gas.map(amount),
+            event_count: 0,
+        });
+    }
+    counter
+}
+
+#[test]
+fn test_fee_recommendation() {
+    let counter = get_network_activity(vec![
+        (vec!["1", "2"], 100, Some("0.1")),
+        (vec!["3"], 200, None),
+        (vec!["5", "6"], 300, Some("0.05")),
+        // an empty block includes any fee
+        (vec![], 0, None),
+    ]);
+
+    // the pool holds less than a block of operations
+    let recommendation = counter.get_fee_recommendation(100, 1);
+    assert_eq!(recommendation.block_count, 4);
+    assert_eq!(recommendation.block_fill_rate, 0.15);
+    assert_eq!(recommendation.min_fee_per_gas, amount("0.05"));
+    assert_eq!(recommendation.pool_operation_count, 1);
+    assert_eq!(recommendation.pool_pressure, 0.8);
+    assert_eq!(recommendation.low, amount("0"));
+    assert_eq!(recommendation.medium, amount("1"));
+    assert_eq!(recommendation.high, amount("6"));
+
+    // a busy pool raises the medium fee
+    let recommendation = counter.get_fee_recommendation(100, 10);
+    assert_eq!(recommendation.pool_pressure, 8.0);
+    assert_eq!(recommendation.medium, amount("3"));
+
+    // only the latest slots are analyzed
+    let recommendation = counter.get_fee_recommendation(2, 0);
+    assert_eq!(recommendation.block_count, 2);
+    assert_eq!(recommendation.low, amount("0"));
+    assert_eq!(recommendation.medium, amount("0"));
+    assert_eq!(recommendation.high, amount("6"));
+}
+
+#[test]
+fn test_fee_recommendation_without_blocks() {
+    let counter = get_network_activity(vec![]);
+    let recommendation = counter.get_fee_recommendation(100, 3);
+    assert_eq!(recommendation.block_count, 0);
+    assert_eq!(recommendation.block_fill_rate, 0.0);
+    assert_eq!(recommendation.pool_pressure, 3.0);
+    assert_eq!(recommendation.low, Amount::zero());
+    assert_eq!(recommendation.medium, Amount::zero());
+    assert_eq!(recommendation.high, Amount::zero());
+}
@@ -0,0 +1,85 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Tests of the storage rent charged at the end of each cycle
+
+use crate::active_history::ActiveHistory;
+use crate::context::ExecutionContext;
+use crate::tests::mock::get_sample_state;
+use massa_execution_exports::{ExecutionConfig, ExecutionStackElement};
+use massa_models::{address::Address, amount::Amount, slot::Slot};
+use massa_signature::KeyPair;
+use parking_lot::RwLock;
+use serial_test::serial;
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[test]
+#[serial]
+fn test_storage_rent_charge_and_reclaim() {
+    let config = ExecutionConfig {
+        storage_rent_per_byte_per_cycle: Some(Amount::from_str("0.001").unwrap()),
+        ..ExecutionConfig::default()
+    };
+    let (final_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let keypair = KeyPair::from_str("S1JJeHiZv1C1zZN5GLFcbz6EXYiccmUPLkYuDFA3kayjxP39kFQ").unwrap();
+    let address = Address::from_public_key(&keypair.get_public_key());
+
+    // the rent is charged at the last slot of a cycle
+    let slot = Slot::new(config.periods_per_cycle - 1, config.thread_count - 1);
+    let mut context = ExecutionContext::active_slot(
+        config,
+        slot,
+        None,
+        final_state.clone(),
+        Arc::new(RwLock::new(ActiveHistory::default())),
+    );
+    context.stack.push(ExecutionStackElement {
+        address,
+        coins: Amount::zero(),
+        owned_addresses: vec![address],
+        operation_datastore: None,
+    });
+
+    // a rich address storing 10 bytes, and a contract storing 100 bytes unable to pay its rent
+    context
+        .set_data_entry(&address, b"key".to_vec(), vec![1; 7])
+        .unwrap();
+    let contract = context.create_new_sc_address(vec![2; 100]).unwrap();
+    context
+        .transfer_coins(
+            Some(address),
+            Some(contract),
+            Amount::from_str("0.05").unwrap(),
+            false,
+        )
+        .unwrap();
+    let balance = context.get_balance(&address).unwrap();
+
+    let changes = context.settle_slot().state_changes.ledger_changes;
+
+    // the rent of the rich address is burnt from its balance
+    assert_eq!(
+        changes.get_balance_or_else(&address, || None),
+        balance.checked_sub(Amount::from_str("0.01").unwrap())
+    );
+    assert_eq!(
+        changes.get_data_entry_or_else(&address, b"key", || None),
+        Some(vec![1; 7])
+    );
+
+    // the contract loses its whole balance and its storage is reclaimed
+    assert_eq!(
+        changes.get_balance_or_else(&contract, || None),
+        Some(Amount::zero())
+    );
+    assert_eq!(
+        changes.get_bytecode_or_else(&contract, || None),
+        Some(Vec::new())
+    );
+
+    // the storage size index of the final ledger follows the finalized changes
+    final_state.write().ledger.apply_changes(changes, slot);
+    let sizes = final_state.read().ledger.get_storage_sizes();
+    assert_eq!(sizes.get(&address), Some(&10));
+    assert!(!sizes.contains_key(&contract));
+}
@@ -5,24 +5,29 @@
 //! orders active and final blocks in queues sorted by increasing slot number,
 //! and requests the execution of active and final slots from execution.rs.
 
+#[cfg(feature = "sandbox")]
+use crate::controller::SandboxCommand;
 use crate::controller::{ExecutionControllerImpl, ExecutionInputData, ExecutionManagerImpl};
 use crate::execution::ExecutionState;
 use crate::request_queue::RequestQueue;
 use crate::slot_sequencer::SlotSequencer;
 use massa_execution_exports::{
-    ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager,
-    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    BlockSimulationOutput, BlockSimulationRequest, ExecutionChannels, ExecutionConfig,
+    ExecutionController, ExecutionError, ExecutionManager, ReadOnlyExecutionOutput,
+    ReadOnlyExecutionRequest,
 };
 use massa_final_state::FinalState;
 use massa_models::block::BlockId;
-use massa_models::slot::Slot;
+use massa_models::prehash::PreHashMap;
+use massa_models::{api::SlotExecutionTrace, slot::Slot};
 use massa_pos_exports::SelectorController;
 use massa_storage::Storage;
 use massa_time::MassaTime;
 use parking_lot::{Condvar, Mutex, RwLock};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::thread;
-use tracing::debug;
+use tracing::{debug, info, info_span, warn};
 
 /// Structure gathering all elements needed by the execution thread
 pub(crate) struct ExecutionThread {
@@ -36,6 +41,10 @@ pub(crate) struct ExecutionThread {
     execution_state: Arc<RwLock<ExecutionState>>,
     /// queue for read-only requests and response MPSCs to send back their outputs
     readonly_requests: RequestQueue<ReadOnlyExecutionRequest, ReadOnlyExecutionOutput>,
+    /// queue for candidate block simulation requests and response MPSCs to send back their outputs
+    block_simulation_requests: RequestQueue<BlockSimulationRequest, BlockSimulationOutput>,
+    /// queue for trace mode slot replay requests and response MPSCs to send back their traces
+    slot_replay_requests: RequestQueue<Slot, SlotExecutionTrace>,
     /// Selector controller
     selector: Box<dyn SelectorController>,
 }
@@ -61,6 +70,8 @@ impl ExecutionThread {
         ExecutionThread {
             input_data,
             readonly_requests: RequestQueue::new(config.readonly_queue_length),
+            block_simulation_requests: RequestQueue::new(config.readonly_queue_length),
+            slot_replay_requests: RequestQueue::new(config.readonly_queue_length),
             execution_state,
             slot_sequencer: SlotSequencer::new(config.clone(), final_cursor),
             selector,
@@ -88,8 +99,13 @@ impl ExecutionThread {
         if let Some(req_resp) = self.readonly_requests.pop() {
             let (req, resp_tx) = req_resp.into_request_sender_pair();
 
+            // trace the execution with the id of the API request that triggered it
+            let span = info_span!("readonly_execution", request_id = ?req.request_id);
+            let _enter = span.enter();
+
             // Acquire read access to the execution state and execute the read-only request
             let outcome = self.execution_state.read().execute_readonly_request(req);
+            debug!("read-only execution done, success: {}", outcome.is_ok());
 
             // Send the execution output through resp_tx.
             // Ignore errors because they just mean that the request emitter dropped the received
@@ -101,6 +117,83 @@ impl ExecutionThread {
         false
     }
 
+    /// Simulates a candidate block from the queue, if any.
+    /// The result of the simulation is sent asynchronously through the response channel provided with the request.
+    ///
+    /// # Returns
+    /// true if a simulation was run, false otherwise
+    fn execute_one_block_simulation(&mut self) -> bool {
+        if let Some(req_resp) = self.block_simulation_requests.pop() {
+            let (req, resp_tx) = req_resp.into_request_sender_pair();
+
+            // Acquire read access to the execution state and simulate the block
+            let outcome = self.execution_state.read().simulate_block(req);
+
+            // Send the simulation output through resp_tx, ignoring errors (see execute_one_readonly_request).
+            let _ = resp_tx.send(Ok(outcome));
+
+            return true;
+        }
+        false
+    }
+
+    /// Replays a slot from the queue in trace mode, if any.
+    /// The trace is sent asynchronously through the response channel provided with the request.
+    ///
+    /// # Returns
+    /// true if a slot was replayed, false otherwise
+    fn execute_one_slot_replay(&mut self) -> bool {
+        if let Some(req_resp) = self.slot_replay_requests.pop() {
+            let (slot, resp_tx) = req_resp.into_request_sender_pair();
+
+            // Acquire read access to the execution state and replay the slot
+            let outcome = self
+                .execution_state
+                .read()
+                .replay_slot_with_trace(slot, self.selector.clone());
+
+            // Send the trace through resp_tx, ignoring errors (see execute_one_readonly_request).
+            let _ = resp_tx.send(outcome);
+
+            return true;
+        }
+        false
+    }
+
+    /// Applies the time-travel commands of the sandbox mode, in order
+    #[cfg(feature = "sandbox")]
+    fn apply_sandbox_commands(&mut self, commands: Vec<SandboxCommand>) {
+        for command in commands {
+            match command {
+                SandboxCommand::FastForward(slot_count) => {
+                    info!("sandbox: fast-forwarding {} slots", slot_count);
+                    self.slot_sequencer.sandbox_fast_forward(slot_count);
+                }
+                SandboxCommand::ExecuteBlockAtSlot {
+                    block_id,
+                    slot,
+                    block_storage,
+                } => {
+                    info!("sandbox: executing block {} at slot {}", block_id, slot);
+                    if !self
+                        .slot_sequencer
+                        .sandbox_inject_block(slot, block_id, block_storage)
+                    {
+                        warn!(
+                            "sandbox: block {} not executed because slot {} is already final",
+                            block_id, slot
+                        );
+                    }
+                }
+                SandboxCommand::ResetToFinal => {
+                    info!("sandbox: resetting the candidate state to the final state");
+                    self.slot_sequencer.sandbox_reset_to_final();
+                    self.execution_state.write().reset_active_history();
+                }
+            }
+        }
+    }
+
     /// Waits for an event to trigger a new iteration in the execution main loop.
     ///
     /// # Returns
@@ -124,25 +217,45 @@ impl ExecutionThread {
                 || !input_data.finalized_blocks.is_empty()
                 || !input_data.block_storage.is_empty()
                 || !input_data.readonly_requests.is_empty()
+                || !input_data.block_simulation_requests.is_empty()
+                || !input_data.slot_replay_requests.is_empty()
             {
                 return (input_data, false);
             }
 
+            // there are time-travel commands of the sandbox mode to apply
+            #[cfg(feature = "sandbox")]
+            if !input_data.sandbox_commands.is_empty() {
+                return (input_data, false);
+            }
+
+            // in light mode nothing is executed: wait for new input only
+            if self.config.light_mode {
+                self.input_data.0.wait(&mut input_data_lock);
+                continue;
+            }
+
             // the slot sequencer has a task available for execution
             if self.slot_sequencer.is_task_available() {
                 return (input_data, false);
             }
 
-            // there are read-only requests ready
-            if !self.readonly_requests.is_empty() {
+            // there are read-only requests or block simulations ready
+            if !self.readonly_requests.is_empty()
+                || !self.block_simulation_requests.is_empty()
+                || !self.slot_replay_requests.is_empty()
+            {
                 return (input_data, false);
             }
 
             // Compute when the next slot will be
             // This is useful to wait for the next speculative miss to append to active slots.
             let wakeup_deadline = self.slot_sequencer.get_next_slot_deadline();
-            let now =
-                MassaTime::now(self.config.clock_compensation).expect("could not get current time");
+            let now = self
+                .config
+                .clock_compensation
+                .now()
+                .expect("could not get current time");
             if wakeup_deadline <= now {
                 // next slot is right now: the loop needs to iterate
                 return (input_data, false);
@@ -153,31 +266,108 @@ impl ExecutionThread {
             let _ = self.input_data.0.wait_until(
                 &mut input_data_lock,
                 wakeup_deadline
-                    .estimate_instant(self.config.clock_compensation)
+                    .estimate_instant(self.config.clock_compensation.get())
                     .expect("could not estimate instant"),
             );
         }
     }
 
+    /// Executes the final slots already notified, then writes the final ledger to disk.
+    /// Candidate slots and read-only requests are not executed anymore.
+    fn drain_final_slots(
+        &mut self,
+        finalized_blocks: HashMap<Slot, BlockId>,
+        new_blockclique: Option<HashMap<Slot, BlockId>>,
+        block_storage: PreHashMap<BlockId, Storage>,
+    ) {
+        self.slot_sequencer
+            .update(finalized_blocks, new_blockclique, block_storage);
+        let mut executed_slots: u64 = 0;
+        while self
+            .slot_sequencer
+            .run_final_task_with(|slot, content| {
+                self.execution_state.write().execute_final_slot(
+                    slot,
+                    content,
+                    self.selector.clone(),
+                )
+            })
+            .is_some()
+        {
+            executed_slots += 1;
+        }
+        info!(
+            "{} pending final slots executed before stopping the execution worker",
+            executed_slots
+        );
+        if let Err(err) = self.execution_state.read().flush_final_ledger() {
+            warn!("could not flush the final ledger: {}", err);
+        }
+    }
+
     /// Main loop of the execution worker
     pub fn main_loop(&mut self) {
         // This loop restarts every time an execution happens for easier tracking.
         // It also prioritizes executions in the following order:
         // 1 - final executions
         // 2 - speculative executions
-        // 3 - read-only executions
+        // 3 - candidate block simulations
+        // 4 - trace mode slot replays
+        // 5 - read-only executions
         loop {
             let (input_data, stop) = self.wait_loop_event();
             debug!("Execution loop triggered, input_data = {}", input_data);
 
             // update the sequence of read-only requests
             self.update_readonly_requests(input_data.readonly_requests);
+            self.block_simulation_requests
+                .extend(input_data.block_simulation_requests);
+            self.slot_replay_requests
+                .extend(input_data.slot_replay_requests);
 
             if stop {
                 // we need to stop
+                // nothing is executed in light mode
+                if input_data.drain && !self.config.light_mode {
+                    self.drain_final_slots(
+                        input_data.finalized_blocks,
+                        input_data.new_blockclique,
+                        input_data.block_storage,
+                    );
+                }
                 break;
             }
 
+            // in light mode, blocks are not executed and read-only requests are rejected
+            if self.config.light_mode {
+                std::mem::replace(
+                    &mut self.readonly_requests,
+                    RequestQueue::new(self.config.readonly_queue_length),
+                )
+                .cancel(ExecutionError::ChannelError(
+                    "readonly execution is not available on a light node".into(),
+                ));
+                std::mem::replace(
+                    &mut self.block_simulation_requests,
+                    RequestQueue::new(self.config.readonly_queue_length),
+                )
+                .cancel(ExecutionError::ChannelError(
+                    "block simulation is not available on a light node".into(),
+                ));
+                std::mem::replace(
+                    &mut self.slot_replay_requests,
+                    RequestQueue::new(self.config.readonly_queue_length),
+                )
+                .cancel(ExecutionError::ChannelError(
+                    "slot replay is not available on a light node".into(),
+                ));
+                continue;
+            }
+
+            // apply the time-travel commands of the sandbox mode
+            #[cfg(feature = "sandbox")]
+            self.apply_sandbox_commands(input_data.sandbox_commands);
+
             // update slot sequencer
             self.slot_sequencer.update(
                 input_data.finalized_blocks,
@@ -208,6 +398,16 @@ impl ExecutionThread {
                 continue;
             }
 
+            // simulate a candidate block, if there is one ready
+            if self.execute_one_block_simulation() {
+                continue;
+            }
+
+            // replay a slot in trace mode, if requested
+            if self.execute_one_slot_replay() {
+                continue;
+            }
+
             // low priority: execute a read-only request (note that the queue is of finite length), if there is one ready.
             self.execute_one_readonly_request();
         }
@@ -218,12 +418,18 @@ impl ExecutionThread {
         let cancel_err = ExecutionError::ChannelError(
             "readonly execution cancelled because the execution worker is closing".into(),
         );
-        self.input_data
-            .1
-            .lock()
-            .take()
-            .readonly_requests
-            .cancel(cancel_err);
+        let mut input_data = self.input_data.1.lock().take();
+        input_data.readonly_requests.cancel(cancel_err);
+        input_data
+            .block_simulation_requests
+            .cancel(ExecutionError::ChannelError(
+                "block simulation cancelled because the execution worker is closing".into(),
+            ));
+        input_data
+            .slot_replay_requests
+            .cancel(ExecutionError::ChannelError(
+                "slot replay cancelled because the execution worker is closing".into(),
+            ));
     }
 }
 
@@ -232,6 +438,8 @@ impl ExecutionThread {
 /// # parameters
 /// * `config`: execution configuration
 /// * `final_state`: a thread-safe shared access to the final state for reading and writing
+/// * `selector`: access to the block producer and endorser draws
+/// * `channels`: channels broadcasting the execution outputs
 ///
 /// # Returns
 /// A pair `(execution_manager, execution_controller)` where:
@@ -241,11 +449,13 @@ pub fn start_execution_worker(
     config: ExecutionConfig,
     final_state: Arc<RwLock<FinalState>>,
     selector: Box<dyn SelectorController>,
+    channels: ExecutionChannels,
 ) -> (Box<dyn ExecutionManager>, Box<dyn ExecutionController>) {
     // create an execution state
     let execution_state = Arc::new(RwLock::new(ExecutionState::new(
         config.clone(),
         final_state,
+        channels,
     )));
 
     // define the input data interface
@@ -2,7 +2,9 @@
 
 //! This file defines the factory settings
 
-use massa_time::MassaTime;
+use crate::{ExecutionPolicy, OperationPackingAlgorithm};
+use massa_models::amount::Amount;
+use massa_time::{ClockCompensation, MassaTime};
 
 /// Structure defining the settings of the factory
 #[derive(Debug, Clone)]
@@ -16,8 +18,8 @@ pub struct FactoryConfig {
     /// period duration
     pub t0: MassaTime,
 
-    /// clock compensation in relative milliseconds
-    pub clock_compensation_millis: i64,
+    /// clock compensation, adjusted while the node runs
+    pub clock_compensation: ClockCompensation,
 
     /// initial delay before starting production, to avoid double-production on node restart
     pub initial_delay: MassaTime,
@@ -27,4 +29,34 @@ pub struct FactoryConfig {
 
     /// maximal block gas
     pub max_block_gas: u64,
+
+    /// price of a roll
+    pub roll_price: Amount,
+
+    /// automatically buy rolls with the coins accumulated by the staking addresses
+    pub roll_compounding: bool,
+
+    /// coins kept on each staking address and never spent on rolls
+    pub roll_compounding_reserve: Amount,
+
+    /// fee of the roll operations issued by the node for its staking addresses
+    pub roll_operation_fee: Amount,
+
+    /// maximal number of rolls bought by a single automatic roll buy operation
+    pub roll_compounding_max_rolls_per_operation: u64,
+
+    /// number of periods the roll operations issued by the node remain valid
+    pub roll_operation_validity_periods: u64,
+
+    /// algorithm selecting the operations of the produced blocks
+    pub operation_packing: OperationPackingAlgorithm,
+
+    /// number of blocks worth of operations requested from the pool as packing candidates
+    pub operation_packing_lookahead: u64,
+
+    /// number of upcoming slots for which the endorsements of our draws are signed in advance
+    pub endorsement_presign_slots: u64,
+
+    /// node-local policy on the smart contract operations included in the produced blocks
+    pub execution_policy: ExecutionPolicy,
 }
@@ -3,6 +3,11 @@
 //! This module exports generic traits representing interfaces for interacting
 //! with the factory worker.
 
+use crate::FactoryResult;
+use massa_models::{
+    address::Address, amount::Amount, operation::OperationId, stats::ExecutionPolicyStats,
+};
+
 /// Factory manager used to stop the factory thread
 pub trait FactoryManager {
     /// Stop the factory thread
@@ -10,4 +15,42 @@ pub trait FactoryManager {
     /// because it is not allowed to move out of Box<dyn FactoryManager>
     /// This will improve if the `unsized_fn_params` feature stabilizes enough to be safely usable.
     fn stop(&mut self);
+
+    /// Stop the block and endorsement production threads, the other factory threads keep running
+    fn stop_production(&mut self);
+
+    /// Start the block and endorsement production threads again, stopping them first if they are running
+    fn restart_production(&mut self);
+
+    /// Pause or resume block and endorsement production:
+    /// while paused, the production threads keep running but skip their slots
+    fn set_production_paused(&mut self, paused: bool);
+}
+
+/// Interface used to request roll operations from the staking addresses of the node
+pub trait FactoryController: Send + Sync {
+    /// Signs a roll sell operation with a staking address of the node and sends it to the pool and to the network
+    ///
+    /// # Arguments
+    /// * `address`: staking address selling its rolls
+    /// * `roll_count`: number of rolls to sell
+    ///
+    /// # Returns
+    /// The id of the sent operation
+    fn sell_rolls(&self, address: Address, roll_count: u64) -> FactoryResult<OperationId>;
+
+    /// Returns the operations left out of the produced blocks by the execution policy of the node
+    fn get_execution_policy_stats(&self) -> ExecutionPolicyStats;
+
+    /// Returns a boxed clone of self.
+    /// Useful to allow cloning `Box<dyn FactoryController>`.
+    fn clone_box(&self) -> Box<dyn FactoryController>;
+}
+
+/// Allow cloning `Box<dyn FactoryController>`
+/// Uses `FactoryController::clone_box` internally
+impl Clone for Box<dyn FactoryController> {
+    fn clone(&self) -> Box<dyn FactoryController> {
+        self.clone_box()
+    }
 }
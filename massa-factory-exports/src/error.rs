@@ -1,4 +1,5 @@
 use displaydoc::Display;
+use massa_models::address::Address;
 use thiserror::Error;
 
 /// factory result
@@ -10,4 +11,8 @@ pub type FactoryResult<T, E = FactoryError> = core::result::Result<T, E>;
 pub enum FactoryError {
     /// Generic error: {0}
     GenericError(String),
+    /// Address {0} is not a staking address of the node
+    NotStakingAddress(Address),
+    /// Channel error: {0}
+    ChannelError(String),
 }
@@ -9,11 +9,15 @@
 mod config;
 mod controller_traits;
 mod error;
+mod packing;
+mod policy;
 mod types;
 
 pub use config::FactoryConfig;
-pub use controller_traits::FactoryManager;
+pub use controller_traits::{FactoryController, FactoryManager};
 pub use error::*;
+pub use packing::{BlockCapacity, OperationPacker, OperationPackingAlgorithm};
+pub use policy::{ExecutionPolicy, ExecutionPolicyMode};
 pub use types::*;
 
 /// Tests utils
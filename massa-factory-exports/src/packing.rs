@@ -0,0 +1,43 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This file defines the extension point used by the block factory to select the operations of a block
+
+use massa_models::operation::OperationId;
+use massa_pool_exports::BlockOperationCandidate;
+use serde::Deserialize;
+
+/// Limits the operations of a block have to respect
+#[derive(Debug, Clone, Copy)]
+pub struct BlockCapacity {
+    /// maximal cumulated size of the operations, in bytes
+    pub max_size: usize,
+    /// maximal cumulated gas of the operations
+    pub max_gas: u64,
+}
+
+/// Algorithm selecting the operations of a block among the candidates provided by the pool
+pub trait OperationPacker: Send {
+    /// Selects operations among `candidates`, sorted from best to worst fee per byte, so that:
+    /// * their cumulated size and gas fit in `capacity`
+    /// * the creator of every selected operation can still pay its fee once the previous operations
+    ///   of the same creator spent their max spending
+    ///
+    /// # Returns
+    /// The ids of the selected operations, in block order
+    fn pack(
+        &self,
+        candidates: &[BlockOperationCandidate],
+        capacity: BlockCapacity,
+    ) -> Vec<OperationId>;
+}
+
+/// Operation packing algorithms shipped with the node
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationPackingAlgorithm {
+    /// takes the candidates in the pool order, skipping the ones that do not fit anymore
+    Greedy,
+    /// solves the knapsack over size and gas by fee density, then improves the selection
+    /// by swapping the least dense selected operations for better paying candidates
+    FeeDensity,
+}
@@ -0,0 +1,60 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Node-local policy on the smart contract operations included in the produced blocks.
+//! It only affects the blocks produced by the node: the blocks of other nodes are validated
+//! regardless of the policy.
+
+use massa_models::{address::Address, operation::OperationType, prehash::PreHashSet};
+use serde::Deserialize;
+
+/// How the addresses of the execution policy are used
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionPolicyMode {
+    /// all the smart contract operations are included
+    None,
+    /// the smart contract operations concerning the listed addresses are excluded
+    Blacklist,
+    /// only the smart contract operations concerning the listed addresses are included
+    Whitelist,
+}
+
+/// Node-local policy on the `CallSC` and `ExecuteSC` operations of the produced blocks.
+/// A `CallSC` operation concerns its target address, an `ExecuteSC` one concerns its sender
+/// as its bytecode has no address. The other operations are never excluded.
+#[derive(Debug, Clone)]
+pub struct ExecutionPolicy {
+    /// how the addresses are used
+    pub mode: ExecutionPolicyMode,
+    /// listed addresses
+    pub addresses: PreHashSet<Address>,
+}
+
+impl ExecutionPolicy {
+    /// Checks whether an operation has to be left out of the produced blocks
+    ///
+    /// # Arguments
+    /// * `op`: content of the operation
+    /// * `creator_address`: address of the creator of the operation
+    pub fn excludes(&self, op: &OperationType, creator_address: &Address) -> bool {
+        let concerned_address = match op {
+            OperationType::CallSC { target_addr, .. } => target_addr,
+            OperationType::ExecuteSC { .. } => creator_address,
+            _ => return false,
+        };
+        match self.mode {
+            ExecutionPolicyMode::None => false,
+            ExecutionPolicyMode::Blacklist => self.addresses.contains(concerned_address),
+            ExecutionPolicyMode::Whitelist => !self.addresses.contains(concerned_address),
+        }
+    }
+}
+
+impl Default for ExecutionPolicy {
+    fn default() -> Self {
+        ExecutionPolicy {
+            mode: ExecutionPolicyMode::None,
+            addresses: Default::default(),
+        }
+    }
+}
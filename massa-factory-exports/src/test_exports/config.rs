@@ -1,6 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::FactoryConfig;
+use crate::{FactoryConfig, OperationPackingAlgorithm};
 use massa_time::MassaTime;
 
 impl Default for FactoryConfig {
@@ -10,10 +10,20 @@ impl Default for FactoryConfig {
             thread_count: THREAD_COUNT,
             genesis_timestamp: MassaTime::now(0).expect("failed to get current time"),
             t0: T0,
-            clock_compensation_millis: 0,
+            clock_compensation: Default::default(),
             initial_delay: MassaTime::from(0),
             max_block_size: MAX_BLOCK_SIZE as u64,
             max_block_gas: MAX_GAS_PER_BLOCK,
+            roll_price: ROLL_PRICE,
+            roll_compounding: false,
+            roll_compounding_reserve: Default::default(),
+            roll_operation_fee: Default::default(),
+            roll_compounding_max_rolls_per_operation: 100,
+            roll_operation_validity_periods: 10,
+            operation_packing: OperationPackingAlgorithm::FeeDensity,
+            operation_packing_lookahead: 2,
+            endorsement_presign_slots: 2,
+            execution_policy: Default::default(),
         }
     }
 }
@@ -1,4 +1,5 @@
 use massa_consensus_exports::ConsensusCommandSender;
+use massa_execution_exports::ExecutionController;
 use massa_models::block::Block;
 use massa_pool_exports::PoolController;
 use massa_pos_exports::SelectorController;
@@ -16,6 +17,8 @@ pub struct FactoryChannels {
     pub selector: Box<dyn SelectorController>,
     /// consensus controller
     pub consensus: ConsensusCommandSender,
+    /// execution controller, to get the balances of the staking addresses
+    pub execution: Box<dyn ExecutionController>,
     /// pool controller
     pub pool: Box<dyn PoolController>,
     /// protocol controller
@@ -1,24 +1,34 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use massa_factory_exports::{FactoryChannels, FactoryConfig};
+use crate::operation_packing::create_operation_packer;
+use massa_factory_exports::{
+    BlockCapacity, ExecutionPolicyMode, FactoryChannels, FactoryConfig, OperationPacker,
+};
 use massa_hash::Hash;
 use massa_models::{
     block::{Block, BlockHeader, BlockHeaderSerializer, BlockId, BlockSerializer, WrappedHeader},
     endorsement::WrappedEndorsement,
+    operation::{OperationId, OperationType},
     prehash::PreHashSet,
     slot::Slot,
+    stats::ExecutionPolicyStats,
     timeslots::{get_block_slot_timestamp, get_closest_slot_to_timestamp},
     wrapped::WrappedContent,
 };
+use massa_pool_exports::BlockOperationCandidate;
+use massa_storage::Storage;
 use massa_time::MassaTime;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use std::{
-    sync::{mpsc, Arc},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
     thread,
     time::Instant,
 };
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 /// Structure gathering all elements needed by the factory thread
 pub(crate) struct BlockFactoryWorker {
@@ -26,6 +36,9 @@ pub(crate) struct BlockFactoryWorker {
     wallet: Arc<RwLock<Wallet>>,
     channels: FactoryChannels,
     factory_receiver: mpsc::Receiver<()>,
+    production_paused: Arc<AtomicBool>,
+    operation_packer: Box<dyn OperationPacker>,
+    execution_policy_stats: Arc<RwLock<ExecutionPolicyStats>>,
 }
 
 impl BlockFactoryWorker {
@@ -36,15 +49,20 @@ impl BlockFactoryWorker {
         wallet: Arc<RwLock<Wallet>>,
         channels: FactoryChannels,
         factory_receiver: mpsc::Receiver<()>,
+        production_paused: Arc<AtomicBool>,
+        execution_policy_stats: Arc<RwLock<ExecutionPolicyStats>>,
     ) -> thread::JoinHandle<()> {
         thread::Builder::new()
             .name("block-factory".into())
             .spawn(|| {
                 let mut this = Self {
+                    operation_packer: create_operation_packer(cfg.operation_packing),
                     cfg,
                     wallet,
                     channels,
                     factory_receiver,
+                    production_paused,
+                    execution_policy_stats,
                 };
                 this.run();
             })
@@ -56,8 +74,11 @@ impl BlockFactoryWorker {
     /// Extra safety against double-production caused by clock adjustments (this is the role of the `previous_slot` parameter).
     fn get_next_slot(&self, previous_slot: Option<Slot>) -> (Slot, Instant) {
         // get current absolute time
-        let now =
-            MassaTime::now(self.cfg.clock_compensation_millis).expect("could not get current time");
+        let now = self
+            .cfg
+            .clock_compensation
+            .now()
+            .expect("could not get current time");
 
         // if it's the first computed slot, add a time shift to prevent double-production on node restart with clock skew
         let base_time = if previous_slot.is_none() {
@@ -96,7 +117,7 @@ impl BlockFactoryWorker {
             next_slot,
         )
         .expect("could not get block slot timestamp")
-        .estimate_instant(self.cfg.clock_compensation_millis)
+        .estimate_instant(self.cfg.clock_compensation.get())
         .expect("could not estimate block slot instant");
 
         (next_slot, next_instant)
@@ -117,6 +138,160 @@ impl BlockFactoryWorker {
         }
     }
 
+    /// Packs the operation candidates provided by the pool into a block.
+    ///
+    /// # Returns
+    /// The selected operations in block order, and a storage holding references to them
+    fn pack_operations(&self, slot: Slot) -> (Vec<OperationId>, Storage) {
+        let lookahead = self.cfg.operation_packing_lookahead.max(1);
+        let max_candidates_size = self.cfg.max_block_size.saturating_mul(lookahead) as usize;
+        let (mut candidates, mut op_storage) = self
+            .channels
+            .pool
+            .get_block_operation_candidates(&slot, max_candidates_size);
+        self.apply_execution_policy(slot, &mut candidates, &mut op_storage);
+        let op_ids = self.operation_packer.pack(
+            &candidates,
+            BlockCapacity {
+                max_size: self.cfg.max_block_size as usize,
+                max_gas: self.cfg.max_block_gas,
+            },
+        );
+        let selected: PreHashSet<OperationId> = op_ids.iter().copied().collect();
+        let left_out: PreHashSet<OperationId> = candidates
+            .iter()
+            .map(|candidate| candidate.id)
+            .filter(|op_id| !selected.contains(op_id))
+            .collect();
+        op_storage.drop_operation_refs(&left_out);
+        debug!(
+            "block factory packed {} of {} candidate operations for slot {}",
+            op_ids.len(),
+            candidates.len(),
+            slot
+        );
+        (op_ids, op_storage)
+    }
+
+    /// Leaves out the candidates excluded by the execution policy of the node,
+    /// and drops their references from `op_storage`.
+    /// The blocks of other nodes are not affected by the policy.
+    fn apply_execution_policy(
+        &self,
+        slot: Slot,
+        candidates: &mut Vec<BlockOperationCandidate>,
+        op_storage: &mut Storage,
+    ) {
+        if self.cfg.execution_policy.mode == ExecutionPolicyMode::None {
+            return;
+        }
+        let mut skipped_call_sc_count: u64 = 0;
+        let mut skipped_execute_sc_count: u64 = 0;
+        let excluded: PreHashSet<OperationId> = {
+            let ops = op_storage.read_operations();
+            candidates
+                .iter()
+                .filter(|candidate| {
+                    let op = &ops
+                        .get(&candidate.id)
+                        .expect("candidate operation absent from storage")
+                        .content
+                        .op;
+                    if !self
+                        .cfg
+                        .execution_policy
+                        .excludes(op, &candidate.creator_address)
+                    {
+                        return false;
+                    }
+                    if let OperationType::CallSC { .. } = op {
+                        skipped_call_sc_count += 1;
+                    } else {
+                        skipped_execute_sc_count += 1;
+                    }
+                    true
+                })
+                .map(|candidate| candidate.id)
+                .collect()
+        };
+        if excluded.is_empty() {
+            return;
+        }
+        debug!(
+            "block factory leaves {} operations out of the block at slot {} because of the execution policy",
+            excluded.len(),
+            slot
+        );
+        candidates.retain(|candidate| !excluded.contains(&candidate.id));
+        op_storage.drop_operation_refs(&excluded);
+        let mut stats = self.execution_policy_stats.write();
+        stats.skipped_call_sc_count = stats
+            .skipped_call_sc_count
+            .saturating_add(skipped_call_sc_count);
+        stats.skipped_execute_sc_count = stats
+            .skipped_execute_sc_count
+            .saturating_add(skipped_execute_sc_count);
+        stats.affected_block_count = stats.affected_block_count.saturating_add(1);
+    }
+
+    /// Simulates the operations picked for a block and excludes those that would fail,
+    /// so that they do not waste block gas.
+    /// The references to the excluded operations are dropped from `op_storage`.
+    /// If the simulation can not be run, all the operations are kept.
+    ///
+    /// # Returns
+    /// The remaining operations, in block order
+    fn exclude_failing_operations(
+        &self,
+        slot: Slot,
+        op_ids: Vec<OperationId>,
+        op_storage: &mut Storage,
+    ) -> Vec<OperationId> {
+        if op_ids.is_empty() {
+            return op_ids;
+        }
+        let operations = {
+            let ops = op_storage.read_operations();
+            op_ids
+                .iter()
+                .map(|op_id| {
+                    ops.get(op_id)
+                        .expect("block operation absent from storage")
+                        .clone()
+                })
+                .collect()
+        };
+        let simulation = match self.channels.execution.simulate_block(slot, operations) {
+            Ok(simulation) => simulation,
+            Err(err) => {
+                warn!(
+                    "block factory could not simulate the operations for slot {}: {}",
+                    slot, err
+                );
+                return op_ids;
+            }
+        };
+        let failing: PreHashSet<OperationId> = simulation
+            .operations
+            .into_iter()
+            .filter(|outcome| !outcome.success)
+            .map(|outcome| {
+                debug!(
+                    "block factory excludes operation {} from the block at slot {}: {}",
+                    outcome.operation_id,
+                    slot,
+                    outcome.error.unwrap_or_default()
+                );
+                outcome.operation_id
+            })
+            .collect();
+        op_storage.drop_operation_refs(&failing);
+        op_ids
+            .into_iter()
+            .filter(|op_id| !failing.contains(op_id))
+            .collect()
+    }
+
     /// Process a slot: produce a block at that slot if one of the managed keys is drawn.
     fn process_slot(&mut self, slot: Slot) {
         // get block producer address for that slot
@@ -191,8 +366,9 @@ impl BlockFactoryWorker {
         };
         block_storage.extend(endo_storage);
 
-        // gather operations and compute global operations hash
-        let (op_ids, op_storage) = self.channels.pool.get_block_operations(&slot);
+        // pack operations, excluding those that would fail, and compute global operations hash
+        let (op_ids, mut op_storage) = self.pack_operations(slot);
+        let op_ids = self.exclude_failing_operations(slot, op_ids, &mut op_storage);
         block_storage.extend(op_storage);
         let global_operations_hash = Hash::compute_from(
             &op_ids
@@ -257,8 +433,10 @@ impl BlockFactoryWorker {
                 break;
             }
 
-            // process slot
-            self.process_slot(slot);
+            // process slot, unless production is paused
+            if !self.production_paused.load(Ordering::Relaxed) {
+                self.process_slot(slot);
+            }
 
             // update previous slot
             prev_slot = Some(slot);
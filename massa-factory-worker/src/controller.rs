@@ -0,0 +1,48 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This module implements a factory controller.
+//! See `massa-factory-exports/controller_traits.rs` for functional details.
+
+use crate::roll_factory::RollFactoryCommand;
+use massa_factory_exports::{FactoryController, FactoryError, FactoryResult};
+use massa_models::{address::Address, operation::OperationId, stats::ExecutionPolicyStats};
+use parking_lot::{Mutex, RwLock};
+use std::sync::{mpsc, Arc};
+
+/// Implementation of the factory controller
+/// Forwards the roll operation requests to the roll factory thread
+#[derive(Clone)]
+pub struct FactoryControllerImpl {
+    /// roll factory command sender
+    pub(crate) roll_worker_tx: Arc<Mutex<mpsc::Sender<RollFactoryCommand>>>,
+    /// operations left out by the execution policy, updated by the block factory
+    pub(crate) execution_policy_stats: Arc<RwLock<ExecutionPolicyStats>>,
+}
+
+impl FactoryController for FactoryControllerImpl {
+    /// Signs a roll sell operation with a staking address of the node and sends it to the pool and to the network
+    fn sell_rolls(&self, address: Address, roll_count: u64) -> FactoryResult<OperationId> {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.roll_worker_tx
+            .lock()
+            .send(RollFactoryCommand::SellRolls {
+                address,
+                roll_count,
+                response_tx,
+            })
+            .map_err(|_| FactoryError::ChannelError("roll factory is stopped".into()))?;
+        response_rx.recv().map_err(|_| {
+            FactoryError::ChannelError("roll factory stopped before answering".into())
+        })?
+    }
+
+    /// Returns the operations left out of the produced blocks by the execution policy of the node
+    fn get_execution_policy_stats(&self) -> ExecutionPolicyStats {
+        self.execution_policy_stats.read().clone()
+    }
+
+    /// Returns a boxed clone of self.
+    fn clone_box(&self) -> Box<dyn FactoryController> {
+        Box::new(self.clone())
+    }
+}
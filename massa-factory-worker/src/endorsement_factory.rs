@@ -2,31 +2,84 @@
 
 use massa_factory_exports::{FactoryChannels, FactoryConfig};
 use massa_models::{
+    address::Address,
     block::BlockId,
     endorsement::{Endorsement, EndorsementSerializer, WrappedEndorsement},
     slot::Slot,
     timeslots::{get_block_slot_timestamp, get_closest_slot_to_timestamp},
     wrapped::WrappedContent,
 };
+use massa_pos_exports::PosResult;
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use std::{
-    sync::{mpsc, Arc},
+    collections::{BTreeMap, BTreeSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
     thread,
     time::Instant,
 };
 use tracing::{debug, warn};
 
+/// Endorsements signed ahead of their slot, along the block they endorse
+#[derive(Default)]
+pub(crate) struct PresignedEndorsements {
+    slots: BTreeMap<Slot, (BlockId, Vec<WrappedEndorsement>)>,
+}
+
+impl PresignedEndorsements {
+    /// Checks if the endorsements of a slot are signed for a given endorsed block
+    pub(crate) fn is_presigned(&self, slot: &Slot, endorsed_block: &BlockId) -> bool {
+        matches!(self.slots.get(slot), Some((block_id, _)) if block_id == endorsed_block)
+    }
+
+    /// Caches the endorsements of a slot, replacing the previous ones
+    pub(crate) fn insert(
+        &mut self,
+        slot: Slot,
+        endorsed_block: BlockId,
+        endorsements: Vec<WrappedEndorsement>,
+    ) {
+        self.slots.insert(slot, (endorsed_block, endorsements));
+    }
+
+    /// Takes the endorsements of a slot, dropping the ones of the previous slots.
+    /// They are only returned if they endorse `endorsed_block` and match the current producers,
+    /// given as their address and endorsement index.
+    pub(crate) fn take(
+        &mut self,
+        slot: Slot,
+        endorsed_block: &BlockId,
+        producers: &BTreeSet<(Address, u32)>,
+    ) -> Option<Vec<WrappedEndorsement>> {
+        let cached = self.slots.remove(&slot);
+        self.slots = self.slots.split_off(&slot);
+        let (block_id, endorsements) = cached?;
+        let cached_producers: BTreeSet<(Address, u32)> = endorsements
+            .iter()
+            .map(|endorsement| (endorsement.creator_address, endorsement.content.index))
+            .collect();
+        if &block_id != endorsed_block || &cached_producers != producers {
+            return None;
+        }
+        Some(endorsements)
+    }
+}
+
 /// Structure gathering all elements needed by the factory thread
 pub(crate) struct EndorsementFactoryWorker {
     cfg: FactoryConfig,
     wallet: Arc<RwLock<Wallet>>,
     channels: FactoryChannels,
     factory_receiver: mpsc::Receiver<()>,
+    production_paused: Arc<AtomicBool>,
     half_t0: MassaTime,
     endorsement_serializer: EndorsementSerializer,
+    presigned_endorsements: PresignedEndorsements,
 }
 
 impl EndorsementFactoryWorker {
@@ -37,6 +90,7 @@ impl EndorsementFactoryWorker {
         wallet: Arc<RwLock<Wallet>>,
         channels: FactoryChannels,
         factory_receiver: mpsc::Receiver<()>,
+        production_paused: Arc<AtomicBool>,
     ) -> thread::JoinHandle<()> {
         thread::Builder::new()
             .name("endorsement-factory".into())
@@ -50,7 +104,9 @@ impl EndorsementFactoryWorker {
                     wallet,
                     channels,
                     factory_receiver,
+                    production_paused,
                     endorsement_serializer: EndorsementSerializer::new(),
+                    presigned_endorsements: Default::default(),
                 };
                 this.run();
             })
@@ -62,8 +118,11 @@ impl EndorsementFactoryWorker {
     /// Extra safety against double-production caused by clock adjustments (this is the role of the `previous_slot` parameter).
     fn get_next_slot(&self, previous_slot: Option<Slot>) -> (Slot, Instant) {
         // get delayed time
-        let now =
-            MassaTime::now(self.cfg.clock_compensation_millis).expect("could not get current time");
+        let now = self
+            .cfg
+            .clock_compensation
+            .now()
+            .expect("could not get current time");
 
         // if it's the first computed slot, add a time shift to prevent double-production on node restart with clock skew
         let base_time = if previous_slot.is_none() {
@@ -103,7 +162,7 @@ impl EndorsementFactoryWorker {
         )
         .expect("could not get block slot timestamp")
         .saturating_sub(self.half_t0)
-        .estimate_instant(self.cfg.clock_compensation_millis)
+        .estimate_instant(self.cfg.clock_compensation.get())
         .expect("could not estimate block slot instant");
 
         (next_slot, next_instant)
@@ -124,11 +183,51 @@ impl EndorsementFactoryWorker {
         }
     }
 
+    /// Gets the keypairs managed by our wallet that are drawn to endorse at a slot,
+    /// along their endorsement index
+    fn get_producers(&self, slot: Slot) -> PosResult<Vec<(KeyPair, u32)>> {
+        let producer_addrs = self.channels.selector.get_selection(slot)?.endorsements;
+        let wallet = self.wallet.read();
+        Ok(producer_addrs
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, producer_addr)| {
+                wallet
+                    .find_associated_keypair(&producer_addr)
+                    .map(|keypair| (keypair.clone(), index as u32))
+            })
+            .collect())
+    }
+
+    /// Signs the endorsements of the given producers at a slot
+    fn sign_endorsements(
+        &self,
+        slot: Slot,
+        endorsed_block: BlockId,
+        producers: &[(KeyPair, u32)],
+    ) -> Vec<WrappedEndorsement> {
+        producers
+            .iter()
+            .map(|(keypair, index)| {
+                Endorsement::new_wrapped(
+                    Endorsement {
+                        slot,
+                        index: *index,
+                        endorsed_block,
+                    },
+                    self.endorsement_serializer.clone(),
+                    keypair,
+                )
+                .expect("could not create endorsement")
+            })
+            .collect()
+    }
+
     /// Process a slot: produce an endorsement at that slot if one of the managed keys is drawn.
     fn process_slot(&mut self, slot: Slot) {
-        // get endorsement producer addresses for that slot
-        let producer_addrs = match self.channels.selector.get_selection(slot) {
-            Ok(sel) => sel.endorsements,
+        // get the endorsement producers for that slot if they are managed by our wallet
+        let producers = match self.get_producers(slot) {
+            Ok(producers) => producers,
             Err(err) => {
                 warn!(
                     "endorsement factory could not get selector draws for slot {}: {}",
@@ -138,26 +237,8 @@ impl EndorsementFactoryWorker {
             }
         };
 
-        // get creators if they are managed by our wallet
-        let mut producers_indices: Vec<(KeyPair, usize)> = Vec::new();
-        {
-            let wallet = self.wallet.read();
-            for (index, producer_addr) in producer_addrs.into_iter().enumerate() {
-                // check if the block producer address is handled by the wallet
-                let producer_keypair =
-                    if let Some(kp) = wallet.find_associated_keypair(&producer_addr) {
-                        // the selected block producer is managed locally => continue to attempt endorsement production
-                        kp.clone()
-                    } else {
-                        // the selected block producer is not managed locally => continue
-                        continue;
-                    };
-                producers_indices.push((producer_keypair, index));
-            }
-        }
-
         // quit if there is nothing to produce
-        if producers_indices.is_empty() {
+        if producers.is_empty() {
             return;
         }
 
@@ -180,27 +261,32 @@ impl EndorsementFactoryWorker {
             Ok(b_id) => b_id,
         };
 
-        // produce endorsements
-        let mut endorsements: Vec<WrappedEndorsement> = Vec::with_capacity(producers_indices.len());
-        for (keypair, index) in producers_indices {
-            let endorsement = Endorsement::new_wrapped(
-                Endorsement {
-                    slot,
-                    index: index as u32,
-                    endorsed_block,
-                },
-                self.endorsement_serializer.clone(),
-                &keypair,
-            )
-            .expect("could not create endorsement");
-
-            // log endorsement creation
+        // use the endorsements signed in advance if they still endorse the right block
+        let producer_set: BTreeSet<(Address, u32)> = producers
+            .iter()
+            .map(|(keypair, index)| (Address::from_public_key(&keypair.get_public_key()), *index))
+            .collect();
+        let endorsements =
+            match self
+                .presigned_endorsements
+                .take(slot, &endorsed_block, &producer_set)
+            {
+                Some(endorsements) => endorsements,
+                None => {
+                    debug!(
+                        "no valid pre-signed endorsements at slot {}, signing them now",
+                        slot
+                    );
+                    self.sign_endorsements(slot, endorsed_block, &producers)
+                }
+            };
+
+        // log endorsement creation
+        for endorsement in endorsements.iter() {
             debug!(
                 "endorsement {} created at slot {} by address {}",
                 endorsement.id, endorsement.content.slot, endorsement.creator_address
             );
-
-            endorsements.push(endorsement);
         }
 
         // store endorsements
@@ -215,6 +301,40 @@ impl EndorsementFactoryWorker {
         }
     }
 
+    /// Signs in advance the endorsements of our draws in the slots following `slot`,
+    /// for those whose endorsed block is already known.
+    /// Failures are silent: the endorsements are signed again when their slot comes.
+    fn presign_upcoming_slots(&mut self, slot: Slot) {
+        let mut upcoming_slot = slot;
+        for _ in 0..self.cfg.endorsement_presign_slots {
+            upcoming_slot = match upcoming_slot.get_next_slot(self.cfg.thread_count) {
+                Ok(next_slot) => next_slot,
+                Err(_) => return,
+            };
+            let producers = match self.get_producers(upcoming_slot) {
+                Ok(producers) if !producers.is_empty() => producers,
+                _ => continue,
+            };
+            let endorsed_block = match self
+                .channels
+                .consensus
+                .get_latest_blockclique_block_at_slot(upcoming_slot)
+            {
+                Ok(block_id) => block_id,
+                Err(_) => continue,
+            };
+            if self
+                .presigned_endorsements
+                .is_presigned(&upcoming_slot, &endorsed_block)
+            {
+                continue;
+            }
+            let endorsements = self.sign_endorsements(upcoming_slot, endorsed_block, &producers);
+            self.presigned_endorsements
+                .insert(upcoming_slot, endorsed_block, endorsements);
+        }
+    }
+
     /// main run loop of the endorsement creator thread
     fn run(&mut self) {
         let mut prev_slot = None;
@@ -227,8 +347,11 @@ impl EndorsementFactoryWorker {
                 break;
             }
 
-            // process slot
-            self.process_slot(slot);
+            // process slot, unless production is paused
+            if !self.production_paused.load(Ordering::Relaxed) {
+                self.process_slot(slot);
+                self.presign_upcoming_slots(slot);
+            }
 
             // update previous slot
             prev_slot = Some(slot);
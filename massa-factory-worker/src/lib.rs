@@ -3,8 +3,11 @@
 #![feature(deadline_api)]
 
 mod block_factory;
+mod controller;
 mod endorsement_factory;
 mod manager;
+mod operation_packing;
+mod roll_factory;
 mod run;
 
 pub use run::start_factory;
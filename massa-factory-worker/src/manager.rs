@@ -3,25 +3,97 @@
 //! This module implements a factory manager.
 //! See `massa-factory-exports/manager_traits.rs` for functional details.
 
-use std::{sync::mpsc, thread::JoinHandle};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread::JoinHandle,
+};
 
-use massa_factory_exports::FactoryManager;
+use crate::{
+    block_factory::BlockFactoryWorker, endorsement_factory::EndorsementFactoryWorker,
+    roll_factory::RollFactoryCommand,
+};
+use massa_factory_exports::{FactoryChannels, FactoryConfig, FactoryManager};
+use massa_models::stats::ExecutionPolicyStats;
+use massa_wallet::Wallet;
+use parking_lot::RwLock;
 use tracing::{info, warn};
 
 /// Implementation of the factory manager
 /// Allows stopping the factory worker
 pub struct FactoryManagerImpl {
+    /// factory configuration, to restart the production workers
+    pub(crate) cfg: FactoryConfig,
+
+    /// node wallet, to restart the production workers
+    pub(crate) wallet: Arc<RwLock<Wallet>>,
+
+    /// factory channels, to restart the production workers
+    pub(crate) channels: FactoryChannels,
+
+    /// set while block and endorsement production is paused
+    pub(crate) production_paused: Arc<AtomicBool>,
+
+    /// operations left out by the execution policy, kept across production restarts
+    pub(crate) execution_policy_stats: Arc<RwLock<ExecutionPolicyStats>>,
+
     /// block worker message sender and join handle
     pub(crate) block_worker: Option<(mpsc::Sender<()>, JoinHandle<()>)>,
 
     /// endorsement worker message sender and join handle
     pub(crate) endorsement_worker: Option<(mpsc::Sender<()>, JoinHandle<()>)>,
+
+    /// roll worker command sender and join handle
+    pub(crate) roll_worker: Option<(mpsc::Sender<RollFactoryCommand>, JoinHandle<()>)>,
+}
+
+impl FactoryManagerImpl {
+    /// spawns the block and endorsement workers
+    pub(crate) fn start_production_workers(&mut self) {
+        // start block factory worker
+        let (block_worker_tx, block_worker_rx) = mpsc::channel::<()>();
+        let block_worker_handle = BlockFactoryWorker::spawn(
+            self.cfg.clone(),
+            self.wallet.clone(),
+            self.channels.clone(),
+            block_worker_rx,
+            self.production_paused.clone(),
+            self.execution_policy_stats.clone(),
+        );
+        self.block_worker = Some((block_worker_tx, block_worker_handle));
+
+        // start endorsement factory worker
+        let (endorsement_worker_tx, endorsement_worker_rx) = mpsc::channel::<()>();
+        let endorsement_worker_handle = EndorsementFactoryWorker::spawn(
+            self.cfg.clone(),
+            self.wallet.clone(),
+            self.channels.clone(),
+            endorsement_worker_rx,
+            self.production_paused.clone(),
+        );
+        self.endorsement_worker = Some((endorsement_worker_tx, endorsement_worker_handle));
+    }
 }
 
 impl FactoryManager for FactoryManagerImpl {
     /// stops the worker
     fn stop(&mut self) {
         info!("stopping factory...");
+        self.stop_production();
+        if let Some((chan_tx, join_handle)) = self.roll_worker.take() {
+            // the controllers hold senders too: explicitly ask the worker to stop
+            let _ = chan_tx.send(RollFactoryCommand::Stop);
+            if let Err(err) = join_handle.join() {
+                warn!("roll factory worker panicked: {:?}", err);
+            }
+        }
+        info!("factory stopped");
+    }
+
+    /// stops the block and endorsement workers
+    fn stop_production(&mut self) {
         if let Some((chan_tx, join_handle)) = self.block_worker.take() {
             std::mem::drop(chan_tx);
             if let Err(err) = join_handle.join() {
@@ -34,6 +106,23 @@ impl FactoryManager for FactoryManagerImpl {
                 warn!("endorsement factory worker panicked: {:?}", err);
             }
         }
-        info!("factory stopped");
+    }
+
+    /// stops the block and endorsement workers if they run, and starts them again
+    fn restart_production(&mut self) {
+        info!("restarting block and endorsement production...");
+        self.stop_production();
+        self.start_production_workers();
+        info!("block and endorsement production restarted");
+    }
+
+    /// pauses or resumes block and endorsement production
+    fn set_production_paused(&mut self, paused: bool) {
+        self.production_paused.store(paused, Ordering::Relaxed);
+        if paused {
+            info!("block and endorsement production paused");
+        } else {
+            info!("block and endorsement production resumed");
+        }
     }
 }
@@ -0,0 +1,186 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Operation packing algorithms shipped with the block factory.
+//! See `massa-factory-exports/packing.rs` for the extension point they implement.
+
+use massa_factory_exports::{BlockCapacity, OperationPacker, OperationPackingAlgorithm};
+use massa_models::{address::Address, amount::Amount, operation::OperationId, prehash::PreHashMap};
+use massa_pool_exports::BlockOperationCandidate;
+
+/// Creates the packer implementing an operation packing algorithm
+pub(crate) fn create_operation_packer(
+    algorithm: OperationPackingAlgorithm,
+) -> Box<dyn OperationPacker> {
+    match algorithm {
+        OperationPackingAlgorithm::Greedy => Box::new(GreedyPacker),
+        OperationPackingAlgorithm::FeeDensity => Box::new(FeeDensityPacker),
+    }
+}
+
+/// Resources left in a block while operations are added to it
+struct PackingState {
+    /// remaining block space
+    remaining_size: usize,
+    /// remaining block gas
+    remaining_gas: u64,
+    /// remaining balance of the creators of the added operations
+    balances: PreHashMap<Address, Amount>,
+}
+
+impl PackingState {
+    fn new(capacity: BlockCapacity) -> Self {
+        PackingState {
+            remaining_size: capacity.max_size,
+            remaining_gas: capacity.max_gas,
+            balances: Default::default(),
+        }
+    }
+
+    /// Adds an operation to the block if it fits, returns false otherwise
+    fn try_add(&mut self, candidate: &BlockOperationCandidate) -> bool {
+        if candidate.size > self.remaining_size || candidate.max_gas > self.remaining_gas {
+            return false;
+        }
+        let balance = self
+            .balances
+            .entry(candidate.creator_address)
+            .or_insert(candidate.creator_balance);
+        if *balance < candidate.fee {
+            return false;
+        }
+        *balance = balance.saturating_sub(candidate.max_spending);
+        self.remaining_size -= candidate.size;
+        self.remaining_gas -= candidate.max_gas;
+        true
+    }
+}
+
+/// Takes the candidates in the pool order, skipping the ones that do not fit anymore
+pub(crate) struct GreedyPacker;
+
+impl OperationPacker for GreedyPacker {
+    fn pack(
+        &self,
+        candidates: &[BlockOperationCandidate],
+        capacity: BlockCapacity,
+    ) -> Vec<OperationId> {
+        let mut state = PackingState::new(capacity);
+        candidates
+            .iter()
+            .filter(|candidate| state.try_add(candidate))
+            .map(|candidate| candidate.id)
+            .collect()
+    }
+}
+
+/// Packs the candidates by decreasing fee density, the density being the fee paid per share of the block
+/// used by the operation, size and gas shares being added up.
+/// Once the block is full, every rejected candidate is looked at in turn: if evicting the least dense
+/// selected operations makes room for it while losing less fees than it pays, they are swapped.
+pub(crate) struct FeeDensityPacker;
+
+impl FeeDensityPacker {
+    /// Fee paid per share of the block used by an operation
+    fn density(candidate: &BlockOperationCandidate, capacity: BlockCapacity) -> f64 {
+        let size_share = candidate.size as f64 / capacity.max_size.max(1) as f64;
+        let gas_share = if capacity.max_gas == 0 {
+            0.0
+        } else {
+            candidate.max_gas as f64 / capacity.max_gas as f64
+        };
+        candidate.fee.to_raw() as f64 / (size_share + gas_share).max(f64::MIN_POSITIVE)
+    }
+
+    /// Checks that operations, given as candidate indexes in block order, fit together in a block
+    fn fits(
+        candidates: &[BlockOperationCandidate],
+        mut selection: impl Iterator<Item = usize>,
+        capacity: BlockCapacity,
+    ) -> bool {
+        let mut state = PackingState::new(capacity);
+        selection.all(|index| state.try_add(&candidates[index]))
+    }
+}
+
+impl OperationPacker for FeeDensityPacker {
+    fn pack(
+        &self,
+        candidates: &[BlockOperationCandidate],
+        capacity: BlockCapacity,
+    ) -> Vec<OperationId> {
+        // sort by decreasing density, the pool order breaking ties as the sort is stable
+        let densities: Vec<f64> = candidates
+            .iter()
+            .map(|candidate| Self::density(candidate, capacity))
+            .collect();
+        let mut order: Vec<usize> = (0..candidates.len()).collect();
+        order.sort_by(|a, b| densities[*b].total_cmp(&densities[*a]));
+
+        // fill the block by decreasing density
+        let mut state = PackingState::new(capacity);
+        let mut selected: Vec<bool> = vec![false; candidates.len()];
+        for index in order.iter() {
+            selected[*index] = state.try_add(&candidates[*index]);
+        }
+        let mut used_size = capacity.max_size - state.remaining_size;
+        let mut used_gas = capacity.max_gas - state.remaining_gas;
+
+        // look past the first rejected candidates for swaps raising the collected fees
+        for candidate_index in order.iter().copied() {
+            if selected[candidate_index] {
+                continue;
+            }
+            let candidate = &candidates[candidate_index];
+            let size_excess = (used_size + candidate.size).saturating_sub(capacity.max_size);
+            let gas_excess = (used_gas + candidate.max_gas).saturating_sub(capacity.max_gas);
+            if size_excess == 0 && gas_excess == 0 {
+                // rejected for the balance of its creator, evicting operations would not make room
+                continue;
+            }
+
+            // evict the least dense selected operations until the candidate fits
+            let mut evicted = Vec::new();
+            let mut freed_size = 0;
+            let mut freed_gas = 0;
+            let mut lost_fee = Amount::zero();
+            for index in order.iter().rev().copied() {
+                if freed_size >= size_excess && freed_gas >= gas_excess {
+                    break;
+                }
+                if !selected[index] {
+                    continue;
+                }
+                lost_fee = lost_fee.saturating_add(candidates[index].fee);
+                if lost_fee >= candidate.fee {
+                    break;
+                }
+                freed_size += candidates[index].size;
+                freed_gas += candidates[index].max_gas;
+                evicted.push(index);
+            }
+            if lost_fee >= candidate.fee || freed_size < size_excess || freed_gas < gas_excess {
+                continue;
+            }
+
+            // the swap must keep every creator able to pay, given the new block order
+            let swapped = order.iter().copied().filter(|index| {
+                *index == candidate_index || (selected[*index] && !evicted.contains(index))
+            });
+            if !Self::fits(candidates, swapped, capacity) {
+                continue;
+            }
+            for index in evicted {
+                selected[index] = false;
+            }
+            selected[candidate_index] = true;
+            used_size = used_size + candidate.size - freed_size;
+            used_gas = used_gas + candidate.max_gas - freed_gas;
+        }
+
+        order
+            .into_iter()
+            .filter(|index| selected[*index])
+            .map(|index| candidates[index].id)
+            .collect()
+    }
+}
@@ -0,0 +1,274 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use massa_execution_exports::AddressInfoBounds;
+use massa_factory_exports::{FactoryChannels, FactoryConfig, FactoryError, FactoryResult};
+use massa_models::{
+    address::Address,
+    amount::Amount,
+    operation::{Operation, OperationId, OperationOrigin, OperationType},
+    prehash::{PreHashMap, PreHashSet},
+    slot::Slot,
+    timeslots::{get_block_slot_timestamp, get_latest_block_slot_at_timestamp},
+};
+use massa_time::MassaTime;
+use massa_wallet::Wallet;
+use parking_lot::RwLock;
+use std::{
+    sync::{mpsc, Arc},
+    thread,
+    time::Instant,
+};
+use tracing::{info, warn};
+
+/// Commands sent to the roll factory thread
+pub(crate) enum RollFactoryCommand {
+    /// sell rolls of a staking address
+    SellRolls {
+        /// staking address selling its rolls
+        address: Address,
+        /// number of rolls to sell
+        roll_count: u64,
+        /// channel receiving the id of the sent operation
+        response_tx: mpsc::Sender<FactoryResult<OperationId>>,
+    },
+    /// stop the thread
+    Stop,
+}
+
+/// Structure gathering all elements needed by the roll factory thread
+pub(crate) struct RollFactoryWorker {
+    cfg: FactoryConfig,
+    wallet: Arc<RwLock<Wallet>>,
+    channels: FactoryChannels,
+    factory_receiver: mpsc::Receiver<RollFactoryCommand>,
+    /// expire period of the pending automatic roll buy of each staking address
+    pending_buys: PreHashMap<Address, u64>,
+    /// staking addresses that sold rolls on request, excluded from roll compounding
+    /// so that their coins are not spent on rolls again once credited
+    selling_addresses: PreHashSet<Address>,
+}
+
+impl RollFactoryWorker {
+    /// Creates the `RollFactoryWorker` structure to gather all data and references
+    /// needed by the roll factory worker thread.
+    pub(crate) fn spawn(
+        cfg: FactoryConfig,
+        wallet: Arc<RwLock<Wallet>>,
+        channels: FactoryChannels,
+        factory_receiver: mpsc::Receiver<RollFactoryCommand>,
+    ) -> thread::JoinHandle<()> {
+        thread::Builder::new()
+            .name("roll-factory".into())
+            .spawn(|| {
+                let mut this = Self {
+                    cfg,
+                    wallet,
+                    channels,
+                    factory_receiver,
+                    pending_buys: Default::default(),
+                    selling_addresses: Default::default(),
+                };
+                this.run();
+            })
+            .expect("failed to spawn thread : roll-factory")
+    }
+
+    /// Gets the latest period at the current time, `None` before genesis
+    fn get_current_period(&self) -> Option<u64> {
+        let now = self
+            .cfg
+            .clock_compensation
+            .now()
+            .expect("could not get current time");
+        get_latest_block_slot_at_timestamp(
+            self.cfg.thread_count,
+            self.cfg.t0,
+            self.cfg.genesis_timestamp,
+            now,
+        )
+        .expect("could not get latest block slot")
+        .map(|slot| slot.period)
+    }
+
+    /// Gets the instant at which the period following the current one starts
+    fn get_next_period_instant(&self) -> Instant {
+        let next_period = self
+            .get_current_period()
+            .map_or(0, |period| period.saturating_add(1));
+        get_block_slot_timestamp(
+            self.cfg.thread_count,
+            self.cfg.t0,
+            self.cfg.genesis_timestamp,
+            Slot::new(next_period, 0),
+        )
+        .expect("could not get block slot timestamp")
+        .estimate_instant(self.cfg.clock_compensation.get())
+        .expect("could not estimate block slot instant")
+    }
+
+    /// Gets the number of rolls a balance can buy,
+    /// keeping the configured reserve and the operation fee
+    fn get_affordable_roll_count(&self, balance: Amount) -> u64 {
+        if self.cfg.roll_price.is_zero() {
+            return 0;
+        }
+        let spendable = balance
+            .saturating_sub(self.cfg.roll_compounding_reserve)
+            .saturating_sub(self.cfg.roll_operation_fee);
+        (spendable.to_raw() / self.cfg.roll_price.to_raw())
+            .min(self.cfg.roll_compounding_max_rolls_per_operation)
+    }
+
+    /// Signs a roll operation with a staking address and sends it to the pool and to the network
+    fn send_roll_operation(
+        &mut self,
+        address: Address,
+        op: OperationType,
+        expire_period: u64,
+    ) -> FactoryResult<OperationId> {
+        let operation = self
+            .wallet
+            .read()
+            .create_operation(
+                Operation {
+                    fee: self.cfg.roll_operation_fee,
+                    expire_period,
+                    op,
+                },
+                address,
+            )
+            .map_err(|_| FactoryError::NotStakingAddress(address))?;
+        let operation_id = operation.id;
+
+        // store the operation
+        let mut op_storage = self.channels.storage.clone_without_refs();
+        op_storage.store_operations(vec![operation]);
+
+        // send the operation to pool for listing and to protocol for propagation
+        self.channels
+            .pool
+            .add_operations(op_storage.clone(), OperationOrigin::Local);
+        self.channels
+            .protocol
+            .propagate_operations_blocking(op_storage)
+            .map_err(|err| FactoryError::ChannelError(err.to_string()))?;
+        Ok(operation_id)
+    }
+
+    /// Sells rolls of a staking address on request.
+    /// The address is then excluded from roll compounding.
+    fn sell_rolls(&mut self, address: Address, roll_count: u64) -> FactoryResult<OperationId> {
+        if roll_count == 0 {
+            return Err(FactoryError::GenericError(
+                "the number of rolls to sell must be positive".into(),
+            ));
+        }
+        let current_period = self
+            .get_current_period()
+            .ok_or_else(|| FactoryError::GenericError("genesis is not reached yet".into()))?;
+        let operation_id = self.send_roll_operation(
+            address,
+            OperationType::RollSell { roll_count },
+            current_period.saturating_add(self.cfg.roll_operation_validity_periods),
+        )?;
+        if self.cfg.roll_compounding && self.selling_addresses.insert(address) {
+            info!(
+                "staking address {} sold rolls: it is excluded from roll compounding until the node restarts",
+                address
+            );
+        }
+        Ok(operation_id)
+    }
+
+    /// Buys rolls with the coins accumulated by the staking addresses
+    fn compound_rolls(&mut self) {
+        let current_period = match self.get_current_period() {
+            Some(period) => period,
+            None => return,
+        };
+
+        // forget the pending buys that expired: they were either executed or dropped
+        self.pending_buys
+            .retain(|_, expire_period| *expire_period >= current_period);
+
+        // get the staking addresses with no pending buy
+        let addresses: Vec<Address> = self
+            .wallet
+            .read()
+            .get_wallet_address_list()
+            .into_iter()
+            .filter(|address| {
+                !self.pending_buys.contains_key(address)
+                    && !self.selling_addresses.contains(address)
+            })
+            .collect();
+        if addresses.is_empty() {
+            return;
+        }
+
+        // only the balances are needed
+        let bounds = AddressInfoBounds {
+            max_datastore_keys: 0,
+            max_cycle_infos: 0,
+            ..Default::default()
+        };
+        let infos = self
+            .channels
+            .execution
+            .get_addresses_infos(&addresses, &bounds);
+        for (address, info) in addresses.into_iter().zip(infos) {
+            let roll_count = self.get_affordable_roll_count(info.candidate_balance);
+            if roll_count == 0 {
+                continue;
+            }
+            let expire_period =
+                current_period.saturating_add(self.cfg.roll_operation_validity_periods);
+            match self.send_roll_operation(
+                address,
+                OperationType::RollBuy { roll_count },
+                expire_period,
+            ) {
+                Ok(operation_id) => {
+                    info!(
+                        "staking address {} buys {} rolls in operation {}",
+                        address, roll_count, operation_id
+                    );
+                    self.pending_buys.insert(address, expire_period);
+                }
+                Err(err) => warn!(
+                    "could not buy rolls for staking address {}: {}",
+                    address, err
+                ),
+            }
+        }
+    }
+
+    /// main run loop of the roll factory thread
+    fn run(&mut self) {
+        loop {
+            // serve the requests until the next period starts
+            let deadline = self.get_next_period_instant();
+            loop {
+                match self.factory_receiver.recv_deadline(deadline) {
+                    Ok(RollFactoryCommand::SellRolls {
+                        address,
+                        roll_count,
+                        response_tx,
+                    }) => {
+                        let _ = response_tx.send(self.sell_rolls(address, roll_count));
+                    }
+                    // stop signal or channel disconnected => quit main loop
+                    Ok(RollFactoryCommand::Stop) | Err(mpsc::RecvTimeoutError::Disconnected) => {
+                        return
+                    }
+                    // next period reached
+                    Err(mpsc::RecvTimeoutError::Timeout) => break,
+                }
+            }
+
+            if self.cfg.roll_compounding {
+                self.compound_rolls();
+            }
+        }
+    }
+}
@@ -1,13 +1,15 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use std::sync::{mpsc, Arc};
 
 use crate::{
-    block_factory::BlockFactoryWorker, endorsement_factory::EndorsementFactoryWorker,
+    controller::FactoryControllerImpl,
     manager::FactoryManagerImpl,
+    roll_factory::{RollFactoryCommand, RollFactoryWorker},
 };
-use massa_factory_exports::{FactoryChannels, FactoryConfig, FactoryManager};
+use massa_factory_exports::{FactoryChannels, FactoryConfig, FactoryController, FactoryManager};
+use massa_models::stats::ExecutionPolicyStats;
 use massa_wallet::Wallet;
 
 /// Start factory
@@ -18,35 +20,47 @@ use massa_wallet::Wallet;
 /// * `channels`: channels to communicate with other modules
 ///
 /// # Return value
-/// Returns a factory manager allowing to stop the workers cleanly.
+/// Returns a factory manager allowing to stop the workers cleanly,
+/// and a factory controller allowing to request roll operations from the staking addresses.
 pub fn start_factory(
     cfg: FactoryConfig,
     wallet: Arc<RwLock<Wallet>>,
     channels: FactoryChannels,
-) -> Box<dyn FactoryManager> {
-    // create block factory channel
-    let (block_worker_tx, block_worker_rx) = mpsc::channel::<()>();
+) -> (Box<dyn FactoryManager>, Box<dyn FactoryController>) {
+    // create roll factory channel
+    let (roll_worker_tx, roll_worker_rx) = mpsc::channel::<RollFactoryCommand>();
 
-    // create endorsement factory channel
-    let (endorsement_worker_tx, endorsement_worker_rx) = mpsc::channel::<()>();
-
-    // start block factory worker
-    let block_worker_handle = BlockFactoryWorker::spawn(
+    // start roll factory worker
+    let roll_worker_handle = RollFactoryWorker::spawn(
         cfg.clone(),
         wallet.clone(),
         channels.clone(),
-        block_worker_rx,
+        roll_worker_rx,
     );
 
-    // start endorsement factory worker
-    let endorsement_worker_handle =
-        EndorsementFactoryWorker::spawn(cfg, wallet, channels, endorsement_worker_rx);
+    // operations left out by the execution policy, shared by the block factory and the controller
+    let execution_policy_stats: Arc<RwLock<ExecutionPolicyStats>> = Default::default();
+
+    // create factory controller
+    let controller = FactoryControllerImpl {
+        roll_worker_tx: Arc::new(Mutex::new(roll_worker_tx.clone())),
+        execution_policy_stats: execution_policy_stats.clone(),
+    };
 
     // create factory manager
-    let manager = FactoryManagerImpl {
-        block_worker: Some((block_worker_tx, block_worker_handle)),
-        endorsement_worker: Some((endorsement_worker_tx, endorsement_worker_handle)),
+    let mut manager = FactoryManagerImpl {
+        cfg,
+        wallet,
+        channels,
+        production_paused: Default::default(),
+        execution_policy_stats,
+        block_worker: None,
+        endorsement_worker: None,
+        roll_worker: Some((roll_worker_tx, roll_worker_handle)),
     };
 
-    Box::new(manager)
+    // start block and endorsement factory workers
+    manager.start_production_workers();
+
+    (Box::new(manager), Box::new(controller))
 }
@@ -0,0 +1,90 @@
+use crate::endorsement_factory::PresignedEndorsements;
+use massa_hash::Hash;
+use massa_models::{
+    address::Address,
+    block::BlockId,
+    endorsement::{Endorsement, EndorsementSerializer, WrappedEndorsement},
+    slot::Slot,
+    wrapped::WrappedContent,
+};
+use massa_signature::KeyPair;
+use std::collections::BTreeSet;
+
+/// Signs an endorsement and returns it along its producer
+fn create_endorsement(
+    keypair: &KeyPair,
+    slot: Slot,
+    index: u32,
+    endorsed_block: BlockId,
+) -> (WrappedEndorsement, (Address, u32)) {
+    let endorsement = Endorsement::new_wrapped(
+        Endorsement {
+            slot,
+            index,
+            endorsed_block,
+        },
+        EndorsementSerializer::new(),
+        keypair,
+    )
+    .unwrap();
+    let producer = (endorsement.creator_address, index);
+    (endorsement, producer)
+}
+
+/// Pre-signed endorsements are used only if they still endorse the block of their slot.
+#[test]
+fn presigned_endorsements_follow_the_endorsed_block() {
+    let keypair = KeyPair::generate();
+    let block_a = BlockId(Hash::compute_from(b"block a"));
+    let block_b = BlockId(Hash::compute_from(b"block b"));
+    let slot = Slot::new(1, 0);
+    let (endorsement, producer) = create_endorsement(&keypair, slot, 3, block_a);
+    let producers = BTreeSet::from([producer]);
+
+    let mut presigned = PresignedEndorsements::default();
+    presigned.insert(slot, block_a, vec![endorsement.clone()]);
+    assert!(presigned.is_presigned(&slot, &block_a));
+    assert!(!presigned.is_presigned(&slot, &block_b));
+
+    // the blockclique changed: the endorsements are discarded
+    assert!(presigned.take(slot, &block_b, &producers).is_none());
+    assert!(!presigned.is_presigned(&slot, &block_a));
+
+    // the endorsed block is unchanged: the endorsements are used once
+    presigned.insert(slot, block_a, vec![endorsement.clone()]);
+    let taken = presigned.take(slot, &block_a, &producers).unwrap();
+    assert_eq!(taken.len(), 1);
+    assert_eq!(taken[0].id, endorsement.id);
+    assert!(presigned.take(slot, &block_a, &producers).is_none());
+}
+
+/// Pre-signed endorsements are discarded if the producers changed, and when their slot is past.
+#[test]
+fn presigned_endorsements_follow_the_producers() {
+    let keypair = KeyPair::generate();
+    let block = BlockId(Hash::compute_from(b"block"));
+    let first_slot = Slot::new(1, 0);
+    let second_slot = Slot::new(1, 1);
+    let (first_endorsement, first_producer) = create_endorsement(&keypair, first_slot, 0, block);
+    let (second_endorsement, _) = create_endorsement(&keypair, second_slot, 0, block);
+
+    let mut presigned = PresignedEndorsements::default();
+    presigned.insert(first_slot, block, vec![first_endorsement]);
+    presigned.insert(second_slot, block, vec![second_endorsement]);
+
+    // the key was removed from the wallet in the meantime
+    let other_producer = (
+        Address::from_public_key(&KeyPair::generate().get_public_key()),
+        0,
+    );
+    assert!(presigned
+        .take(first_slot, &block, &BTreeSet::from([other_producer]))
+        .is_none());
+
+    // reaching a later slot drops the endorsements of the skipped slots
+    let third_slot = Slot::new(2, 0);
+    assert!(presigned
+        .take(third_slot, &block, &BTreeSet::from([first_producer]))
+        .is_none());
+    assert!(!presigned.is_presigned(&second_slot, &block));
+}
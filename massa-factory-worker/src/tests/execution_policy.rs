@@ -0,0 +1,65 @@
+use massa_factory_exports::{ExecutionPolicy, ExecutionPolicyMode};
+use massa_models::{address::Address, amount::Amount, operation::OperationType};
+use massa_signature::KeyPair;
+
+fn random_address() -> Address {
+    Address::from_public_key(&KeyPair::generate().get_public_key())
+}
+
+fn call_sc(target_addr: Address) -> OperationType {
+    OperationType::CallSC {
+        target_addr,
+        target_func: "main".into(),
+        param: String::new(),
+        max_gas: 1_000,
+        coins: Amount::zero(),
+        gas_price: Amount::zero(),
+    }
+}
+
+fn execute_sc() -> OperationType {
+    OperationType::ExecuteSC {
+        data: vec![1, 2, 3],
+        max_gas: 1_000,
+        gas_price: Amount::zero(),
+        datastore: Default::default(),
+    }
+}
+
+/// A blacklist excludes the calls to the listed contracts and the bytecode sent by the listed addresses,
+/// the other operations are never excluded.
+#[test]
+fn execution_policy_blacklist() {
+    let listed = random_address();
+    let other = random_address();
+    let policy = ExecutionPolicy {
+        mode: ExecutionPolicyMode::Blacklist,
+        addresses: [listed].into_iter().collect(),
+    };
+    assert!(policy.excludes(&call_sc(listed), &other));
+    assert!(!policy.excludes(&call_sc(other), &listed));
+    assert!(policy.excludes(&execute_sc(), &listed));
+    assert!(!policy.excludes(&execute_sc(), &other));
+    let transaction = OperationType::Transaction {
+        recipient_address: listed,
+        amount: Amount::zero(),
+    };
+    assert!(!policy.excludes(&transaction, &listed));
+}
+
+/// A whitelist only includes the smart contract operations concerning the listed addresses.
+#[test]
+fn execution_policy_whitelist() {
+    let listed = random_address();
+    let other = random_address();
+    let policy = ExecutionPolicy {
+        mode: ExecutionPolicyMode::Whitelist,
+        addresses: [listed].into_iter().collect(),
+    };
+    assert!(!policy.excludes(&call_sc(listed), &other));
+    assert!(policy.excludes(&call_sc(other), &listed));
+    assert!(!policy.excludes(&execute_sc(), &listed));
+    assert!(policy.excludes(&execute_sc(), &other));
+    assert!(!policy.excludes(&OperationType::RollBuy { roll_count: 1 }, &other));
+    assert!(!ExecutionPolicy::default().excludes(&call_sc(other), &other));
+}
@@ -1,3 +1,6 @@
+mod endorsement_presigning;
+mod execution_policy;
+mod operation_packing;
 mod scenarios;
 mod tools;
 
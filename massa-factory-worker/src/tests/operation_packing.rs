@@ -0,0 +1,96 @@
+use crate::operation_packing::{FeeDensityPacker, GreedyPacker};
+use massa_factory_exports::{BlockCapacity, OperationPacker};
+use massa_models::{address::Address, amount::Amount, operation::OperationId};
+use massa_pool_exports::BlockOperationCandidate;
+use massa_signature::KeyPair;
+
+/// Creates a candidate spending nothing but its fee, its id being derived from `index`
+fn create_candidate(
+    index: u8,
+    creator_address: Address,
+    size: usize,
+    max_gas: u64,
+    fee: u64,
+) -> BlockOperationCandidate {
+    BlockOperationCandidate {
+        id: OperationId::from_bytes(&[index; 32]),
+        size,
+        max_gas,
+        fee: Amount::from_raw(fee),
+        creator_address,
+        max_spending: Amount::from_raw(fee),
+        creator_balance: Amount::MAX,
+    }
+}
+
+/// The gas used by the operations is taken into account, unlike in the pool order.
+#[test]
+fn fee_density_packing_accounts_for_gas() {
+    let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let capacity = BlockCapacity {
+        max_size: 100,
+        max_gas: 100,
+    };
+    // pool order: best fee per byte first
+    let candidates = vec![
+        create_candidate(0, address, 10, 100, 20),
+        create_candidate(1, address, 40, 40, 40),
+        create_candidate(2, address, 40, 40, 40),
+    ];
+    assert_eq!(
+        GreedyPacker.pack(&candidates, capacity),
+        vec![candidates[0].id]
+    );
+    assert_eq!(
+        FeeDensityPacker.pack(&candidates, capacity),
+        vec![candidates[1].id, candidates[2].id]
+    );
+}
+
+/// A rejected candidate replaces the least dense operation if it pays more.
+#[test]
+fn fee_density_packing_swaps_operations() {
+    let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let capacity = BlockCapacity {
+        max_size: 100,
+        max_gas: 1_000,
+    };
+    let candidates = vec![
+        create_candidate(0, address, 50, 0, 100),
+        create_candidate(1, address, 45, 0, 50),
+        create_candidate(2, address, 50, 0, 54),
+    ];
+    assert_eq!(
+        GreedyPacker.pack(&candidates, capacity),
+        vec![candidates[0].id, candidates[1].id]
+    );
+    assert_eq!(
+        FeeDensityPacker.pack(&candidates, capacity),
+        vec![candidates[0].id, candidates[2].id]
+    );
+}
+
+/// Operations are left out once their creator can not pay their fee anymore.
+#[test]
+fn packing_respects_creator_balance() {
+    let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+    let capacity = BlockCapacity {
+        max_size: 100,
+        max_gas: 1_000,
+    };
+    let mut candidates = vec![
+        create_candidate(0, address, 10, 0, 30),
+        create_candidate(1, address, 10, 0, 20),
+    ];
+    for candidate in candidates.iter_mut() {
+        candidate.creator_balance = Amount::from_raw(40);
+    }
+    assert_eq!(
+        GreedyPacker.pack(&candidates, capacity),
+        vec![candidates[0].id]
+    );
+    assert_eq!(
+        FeeDensityPacker.pack(&candidates, capacity),
+        vec![candidates[0].id]
+    );
+}
@@ -6,16 +6,24 @@ use std::{
 };
 
 use massa_consensus_exports::{commands::ConsensusCommand, test_exports::MockConsensusController};
+use massa_execution_exports::test_exports::MockExecutionController;
 use massa_factory_exports::{
     test_exports::create_empty_block, FactoryChannels, FactoryConfig, FactoryManager,
 };
 use massa_models::{
-    address::Address, block::BlockId, config::ENDORSEMENT_COUNT, endorsement::WrappedEndorsement,
-    operation::WrappedOperation, prehash::PreHashMap, slot::Slot,
+    address::Address,
+    amount::Amount,
+    block::BlockId,
+    config::{ENDORSEMENT_COUNT, ROLL_PRICE},
+    endorsement::WrappedEndorsement,
+    operation::WrappedOperation,
+    prehash::PreHashMap,
+    slot::Slot,
     test_exports::get_next_slot_instant,
 };
-use massa_pool_exports::test_exports::{
-    MockPoolController, MockPoolControllerMessage, PoolEventReceiver,
+use massa_pool_exports::{
+    test_exports::{MockPoolController, MockPoolControllerMessage, PoolEventReceiver},
+    BlockOperationCandidate,
 };
 use massa_pos_exports::{
     test_exports::{MockSelectorController, MockSelectorControllerMessage},
@@ -77,7 +85,7 @@ impl TestFactory {
             .genesis_timestamp
             .checked_sub(factory_config.t0)
             .unwrap();
-        let factory_manager = start_factory(
+        let (factory_manager, _factory_controller) = start_factory(
             factory_config.clone(),
             Arc::new(RwLock::new(create_test_wallet(Some(accounts)))),
             FactoryChannels {
@@ -85,6 +93,7 @@ impl TestFactory {
                 consensus: consensus_command_sender,
                 pool: pool_controller.clone(),
                 protocol: protocol_command_sender,
+                execution: MockExecutionController::new_with_receiver().0,
                 storage: storage.clone_without_refs(),
             },
         );
@@ -185,15 +194,27 @@ impl TestFactory {
 
         self.pool_receiver
             .wait_command(MassaTime::from_millis(100), |command| match command {
-                MockPoolControllerMessage::GetBlockOperations {
+                MockPoolControllerMessage::GetBlockOperationCandidates {
                     slot: _,
+                    max_total_size: _,
                     response_tx,
                 } => {
                     if let Some(operations) = &operations {
-                        let ids = operations.iter().map(|op| op.id).collect();
+                        let candidates = operations
+                            .iter()
+                            .map(|op| BlockOperationCandidate {
+                                id: op.id,
+                                size: op.serialized_size(),
+                                max_gas: op.get_gas_usage(),
+                                fee: op.get_total_fee(),
+                                creator_address: op.creator_address,
+                                max_spending: op.get_max_spending(ROLL_PRICE),
+                                creator_balance: Amount::MAX,
+                            })
+                            .collect();
                         let mut storage = self.storage.clone_without_refs();
                         storage.store_operations(operations.clone());
-                        response_tx.send((ids, storage.clone())).unwrap();
+                        response_tx.send((candidates, storage.clone())).unwrap();
                         Some(())
                     } else {
                         response_tx.send((vec![], Storage::create_root())).unwrap();
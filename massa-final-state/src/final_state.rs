@@ -80,6 +80,31 @@ impl FinalState {
             .map_err(|err| FinalStateError::PosError(err.to_string()))
     }
 
+    /// Resets the final state to its content before bootstrap: empty ledger, async pool,
+    /// proof-of-stake state, executed operations and history, attached to the last genesis slot.
+    /// Used before bootstrapping the whole final state over a local one.
+    pub fn reset(&mut self) {
+        self.slot = Slot::new(0, self.config.thread_count.saturating_sub(1));
+        self.ledger.reset();
+        self.async_pool = AsyncPool::new(self.config.async_pool_config.clone());
+        self.pos_state.cycle_history.clear();
+        self.pos_state.deferred_credits = Default::default();
+        self.executed_ops.reset();
+        self.changes_history.clear();
+    }
+
+    /// Returns true if the changes that followed `slot` are no longer all in `self.changes_history`.
+    ///
+    /// An empty history is not considered too short: there are no changes to stream.
+    pub fn is_slot_too_old(&self, slot: Slot) -> bool {
+        match self.changes_history.front() {
+            Some((first_slot, _)) => slot
+                .get_next_slot(self.config.thread_count)
+                .map_or(true, |next_slot| next_slot < *first_slot),
+            None => false,
+        }
+    }
+
     /// Applies changes to the execution state at a given slot, and settles that slot forever.
     /// Once this is called, the state is attached at the output of the provided slot.
     ///
@@ -118,9 +143,10 @@ impl FinalState {
         }
 
         debug!(
-            "ledger hash at slot {}: {}",
+            "ledger hash and root at slot {}: {} {}",
             slot,
-            self.ledger.get_ledger_hash()
+            self.ledger.get_ledger_hash(),
+            self.ledger.get_ledger_root()
         );
         debug!(
             "executed_ops hash at slot {}: {:?}",
@@ -149,15 +175,15 @@ impl FinalState {
         ops_step: StreamingStep<Slot>,
     ) -> Result<Vec<(Slot, StateChanges)>, FinalStateError> {
         let position_slot = if let Some((first_slot, _)) = self.changes_history.front() {
-            // Safe because we checked that there is changes just above.
+            // index of the changes of the slot following `slot`, which may be the first of the history
             let index = slot
-                .slots_since(first_slot, self.config.thread_count)
+                .get_next_slot(self.config.thread_count)
+                .and_then(|next_slot| next_slot.slots_since(first_slot, self.config.thread_count))
                 .map_err(|_| {
                     FinalStateError::LedgerError(
                         "get_state_changes_part given slot is overflowing history.".to_string(),
                     )
-                })?
-                .saturating_add(1);
+                })?;
 
             // Check if the `slot` index isn't in the future
             if self.changes_history.len() as u64 <= index {
@@ -247,12 +273,14 @@ impl FinalState {
 #[cfg(test)]
 mod tests {
 
-    use std::collections::VecDeque;
+    use std::collections::{HashMap, VecDeque};
+    use std::path::PathBuf;
 
-    use crate::StateChanges;
+    use crate::{FinalState, FinalStateConfig, StateChanges};
     use massa_async_pool::test_exports::get_random_message;
-    use massa_ledger_exports::SetUpdateOrDelete;
-    use massa_models::{address::Address, slot::Slot};
+    use massa_ledger_exports::{LedgerConfig, SetUpdateOrDelete};
+    use massa_models::{address::Address, slot::Slot, streaming_step::StreamingStep};
+    use massa_pos_exports::{test_exports::MockSelectorController, PoSFinalState};
     use massa_signature::KeyPair;
 
     fn get_random_address() -> Address {
@@ -260,6 +288,97 @@ mod tests {
         Address::from_public_key(&keypair.get_public_key())
     }
 
+    /// Final state with 2 threads and a history of changes at the slots `(1, 0)` to `(2, 1)`
+    fn get_final_state_with_history(ledger_config: LedgerConfig) -> FinalState {
+        let config = FinalStateConfig {
+            ledger_config,
+            ..Default::default()
+        };
+        let (selector, _) = MockSelectorController::new_with_receiver();
+        let pos_state = PoSFinalState::new(
+            config.pos_config.clone(),
+            "",
+            &PathBuf::from("../massa-node/base_config/initial_rolls.json"),
+            selector,
+        )
+        .unwrap();
+        let mut final_state = FinalState::create_final_state(pos_state, config);
+        for slot in [
+            Slot::new(1, 0),
+            Slot::new(1, 1),
+            Slot::new(2, 0),
+            Slot::new(2, 1),
+        ] {
+            final_state
+                .changes_history
+                .push_back((slot, StateChanges::default()));
+        }
+        final_state.slot = Slot::new(2, 1);
+        final_state
+    }
+
+    /// Slots of the changes following `slot` in the history of `final_state`
+    fn get_changes_slots(final_state: &FinalState, slot: Slot) -> Option<Vec<Slot>> {
+        final_state
+            .get_state_changes_part(
+                slot,
+                StreamingStep::Finished,
+                StreamingStep::Finished,
+                StreamingStep::Finished,
+                StreamingStep::Finished,
+                StreamingStep::Finished,
+            )
+            .ok()
+            .map(|changes| changes.into_iter().map(|(slot, _)| slot).collect())
+    }
+
+    #[test]
+    fn test_changes_history_bounds() {
+        let (ledger_config, _initial_ledger, _disk_ledger) = LedgerConfig::sample(&HashMap::new());
+        let mut final_state = get_final_state_with_history(ledger_config);
+
+        // the slot preceding the history gets all of it
+        assert!(!final_state.is_slot_too_old(Slot::new(0, 1)));
+        assert_eq!(
+            get_changes_slots(&final_state, Slot::new(0, 1)),
+            Some(vec![
+                Slot::new(1, 0),
+                Slot::new(1, 1),
+                Slot::new(2, 0),
+                Slot::new(2, 1)
+            ])
+        );
+        assert!(!final_state.is_slot_too_old(Slot::new(1, 1)));
+        assert_eq!(
+            get_changes_slots(&final_state, Slot::new(1, 1)),
+            Some(vec![Slot::new(2, 0), Slot::new(2, 1)])
+        );
+
+        // the changes of an older slot are missing
+        assert!(final_state.is_slot_too_old(Slot::new(0, 0)));
+        assert_eq!(get_changes_slots(&final_state, Slot::new(0, 0)), None);
+
+        // an empty history has nothing to stream
+        final_state.changes_history.clear();
+        assert!(!final_state.is_slot_too_old(Slot::new(0, 0)));
+        assert_eq!(
+            get_changes_slots(&final_state, Slot::new(0, 0)),
+            Some(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_final_state_reset() {
+        let (ledger_config, _initial_ledger, _disk_ledger) = LedgerConfig::sample(&HashMap::new());
+        let mut final_state = get_final_state_with_history(ledger_config);
+        final_state.pos_state.create_initial_cycle();
+        final_state.reset();
+        assert_eq!(final_state.slot, Slot::new(0, 1));
+        assert!(final_state.changes_history.is_empty());
+        assert!(final_state.pos_state.cycle_history.is_empty());
+        assert!(final_state.executed_ops.is_empty());
+    }
+
     #[test]
     fn get_state_changes_part() {
         let message = get_random_message();
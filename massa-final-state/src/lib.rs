@@ -25,6 +25,10 @@
 //! Defines a structure to list and prune previously executed operations.
 //! Used to detect operation reuse.
 //!
+//! ## `snapshot.rs`
+//! Saves the final state to disk and loads it back, so that a node can restart
+//! from its local state instead of bootstrapping.
+//!
 //! ## `bootstrap.rs`
 //! Provides serializable structures and tools for bootstrapping the final state.
 //!
@@ -42,11 +46,15 @@
 mod config;
 mod error;
 mod final_state;
+mod snapshot;
 mod state_changes;
 
 pub use config::FinalStateConfig;
 pub use error::FinalStateError;
 pub use final_state::FinalState;
+pub use snapshot::{
+    read_snapshot_file, write_snapshot_file, FinalStateSnapshot, SNAPSHOT_VERSION,
+};
 pub use state_changes::{StateChanges, StateChangesDeserializer, StateChangesSerializer};
 
 #[cfg(test)]
@@ -0,0 +1,424 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This file defines the on-disk snapshot of the final state,
+//! used to restart a node from its local state instead of bootstrapping it from the network.
+//!
+//! A snapshot is a directory containing:
+//! * the ledger snapshot, as exported by `LedgerController::export_snapshot`
+//! * the rest of the final state (slot, async pool, PoS state and executed operations)
+//!
+//! Every snapshot file other than the ledger one starts with a header giving its kind
+//! and the snapshot format version, followed by the hash of its content.
+//! A file with another version or whose content does not match its hash is rejected.
+
+use crate::{error::FinalStateError, final_state::FinalState};
+use massa_async_pool::{AsyncPoolDeserializer, AsyncPoolSerializer};
+use massa_executed_ops::{ExecutedOpsDeserializer, ExecutedOpsSerializer};
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use massa_ledger_exports::LedgerSnapshotWriter;
+use massa_models::{
+    slot::{Slot, SlotDeserializer, SlotSerializer},
+    streaming_step::StreamingStep,
+};
+use massa_pos_exports::{
+    CycleInfoDeserializer, CycleInfoSerializer, DeferredCreditsDeserializer,
+    DeferredCreditsSerializer,
+};
+use massa_serialization::{
+    DeserializeError, Deserializer, SerializeError, Serializer, U64VarIntDeserializer,
+    U64VarIntSerializer,
+};
+use nom::multi::length_count;
+use nom::sequence::tuple;
+use nom::Parser;
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Bound::{Excluded, Included};
+use std::path::{Path, PathBuf};
+
+/// Version of the snapshot format, to be increased on every format change
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Magic bytes starting every snapshot file
+const SNAPSHOT_MAGIC: &[u8] = b"massa-snapshot";
+
+/// Name of the ledger snapshot file in a snapshot directory
+const LEDGER_SNAPSHOT_FILE: &str = "ledger.snapshot";
+
+/// Name of the final state snapshot file in a snapshot directory
+const FINAL_STATE_SNAPSHOT_FILE: &str = "final_state.snapshot";
+
+/// Kind of the final state snapshot file
+const FINAL_STATE_SNAPSHOT_KIND: &str = "final_state";
+
+/// Writes a snapshot file: header, hash of the content and then the content itself
+///
+/// # Arguments
+/// * `path`: path of the file to create
+/// * `kind`: kind of the content, checked when reading the file back
+/// * `content`: serialized content
+pub fn write_snapshot_file(path: &Path, kind: &str, content: &[u8]) -> Result<(), FinalStateError> {
+    let mut bytes = snapshot_header(kind);
+    bytes.extend(Hash::compute_from(content).to_bytes());
+    bytes.extend(content);
+    fs::write(path, bytes).map_err(|err| {
+        FinalStateError::SnapshotError(format!("could not write {}: {}", path.display(), err))
+    })
+}
+
+/// Reads a snapshot file written by `write_snapshot_file`,
+/// checking its kind, its version and the integrity of its content
+///
+/// # Returns
+/// The serialized content
+pub fn read_snapshot_file(path: &Path, kind: &str) -> Result<Vec<u8>, FinalStateError> {
+    let error = |msg: String| {
+        FinalStateError::SnapshotError(format!("invalid snapshot {}: {}", path.display(), msg))
+    };
+    let bytes = fs::read(path).map_err(|err| error(err.to_string()))?;
+    let header = snapshot_header(kind);
+    let header_len = header.len() - 4;
+    if bytes.len() < header.len() + HASH_SIZE_BYTES || bytes[..header_len] != header[..header_len] {
+        return Err(error(format!("not a {} snapshot", kind)));
+    }
+    if bytes[header_len..header.len()] != header[header_len..] {
+        let version = u32::from_be_bytes(
+            bytes[header_len..header.len()]
+                .try_into()
+                .expect("version is 4 bytes long"),
+        );
+        return Err(error(format!(
+            "snapshot version {} is not supported, expected {}",
+            version, SNAPSHOT_VERSION
+        )));
+    }
+    let (hash, content) = bytes[header.len()..].split_at(HASH_SIZE_BYTES);
+    if Hash::compute_from(content).to_bytes()[..] != hash[..] {
+        return Err(error("content does not match its hash".to_string()));
+    }
+    Ok(content.to_vec())
+}
+
+/// Header of a snapshot file: magic bytes, kind and format version
+fn snapshot_header(kind: &str) -> Vec<u8> {
+    let mut header = SNAPSHOT_MAGIC.to_vec();
+    header.extend((kind.len() as u8).to_be_bytes());
+    header.extend(kind.as_bytes());
+    header.extend(SNAPSHOT_VERSION.to_be_bytes());
+    header
+}
+
+/// Copy of the final state taken by `FinalState::take_snapshot`, written to its snapshot
+/// directory by `FinalStateSnapshot::write` without holding the final state lock
+pub struct FinalStateSnapshot {
+    /// snapshot directory
+    dir: PathBuf,
+    /// slot at the output of which the copied state is attached
+    slot: Slot,
+    /// writer of the ledger snapshot
+    ledger_writer: LedgerSnapshotWriter,
+    /// serialized rest of the final state
+    content: Vec<u8>,
+}
+
+impl FinalStateSnapshot {
+    /// Writes the snapshot files
+    ///
+    /// # Returns
+    /// The slot at the output of which the saved state is attached
+    pub fn write(self) -> Result<Slot, FinalStateError> {
+        // the ledger is attached to the same slot as the rest of the state
+        let ledger_slot =
+            (self.ledger_writer)().map_err(|err| FinalStateError::LedgerError(err.to_string()))?;
+        if ledger_slot != self.slot {
+            return Err(FinalStateError::SnapshotError(format!(
+                "ledger slot {} does not match final state slot {}",
+                ledger_slot, self.slot
+            )));
+        }
+        write_snapshot_file(
+            &self.dir.join(FINAL_STATE_SNAPSHOT_FILE),
+            FINAL_STATE_SNAPSHOT_KIND,
+            &self.content,
+        )?;
+        Ok(self.slot)
+    }
+}
+
+impl FinalState {
+    /// Copies the final state to be saved in a snapshot directory, created if needed.
+    /// The state is read at once: the caller should hold it for the whole call,
+    /// and then release it before writing the returned snapshot.
+    pub fn take_snapshot(&self, dir: &Path) -> Result<FinalStateSnapshot, FinalStateError> {
+        let ser_error = |err: SerializeError| FinalStateError::SnapshotError(err.to_string());
+        fs::create_dir_all(dir).map_err(|err| {
+            FinalStateError::SnapshotError(format!("could not create {}: {}", dir.display(), err))
+        })?;
+
+        let ledger_writer = self
+            .ledger
+            .export_snapshot(&dir.join(LEDGER_SNAPSHOT_FILE))
+            .map_err(|err| FinalStateError::LedgerError(err.to_string()))?;
+
+        let mut content = Vec::new();
+        SlotSerializer::new()
+            .serialize(&self.slot, &mut content)
+            .map_err(ser_error)?;
+
+        // async pool, gathered through the bootstrap parts
+        let mut messages = BTreeMap::new();
+        let mut cursor = StreamingStep::Started;
+        while !cursor.finished() {
+            let (part, new_cursor) = self.async_pool.get_pool_part(cursor);
+            messages.extend(part);
+            cursor = new_cursor;
+        }
+        AsyncPoolSerializer::new()
+            .serialize(&messages, &mut content)
+            .map_err(ser_error)?;
+
+        // proof-of-stake state
+        let u64_serializer = U64VarIntSerializer::new();
+        let cycle_serializer = CycleInfoSerializer::new();
+        u64_serializer
+            .serialize(&(self.pos_state.cycle_history.len() as u64), &mut content)
+            .map_err(ser_error)?;
+        for cycle_info in self.pos_state.cycle_history.iter() {
+            cycle_serializer
+                .serialize(cycle_info, &mut content)
+                .map_err(ser_error)?;
+        }
+        DeferredCreditsSerializer::new()
+            .serialize(&self.pos_state.deferred_credits, &mut content)
+            .map_err(ser_error)?;
+
+        // executed operations, gathered through the bootstrap parts as they may be on disk
+        let mut executed_ops = BTreeMap::new();
+        let mut cursor = StreamingStep::Started;
+        while !cursor.finished() {
+            let (part, new_cursor) = self.executed_ops.get_executed_ops_part(cursor);
+            executed_ops.extend(part);
+            cursor = new_cursor;
+        }
+        ExecutedOpsSerializer::new()
+            .serialize(&executed_ops, &mut content)
+            .map_err(ser_error)?;
+
+        Ok(FinalStateSnapshot {
+            dir: dir.to_path_buf(),
+            slot: self.slot,
+            ledger_writer,
+            content,
+        })
+    }
+
+    /// Loads a snapshot directory written by `FinalStateSnapshot::write` into a freshly created
+    /// final state. The content of the state is checked before the ledger is replaced,
+    /// and the ledger is left empty if the snapshot cannot be loaded.
+    ///
+    /// # Returns
+    /// The slot at the output of which the loaded state is attached
+    pub fn load_snapshot(&mut self, dir: &Path) -> Result<Slot, FinalStateError> {
+        let content = read_snapshot_file(
+            &dir.join(FINAL_STATE_SNAPSHOT_FILE),
+            FINAL_STATE_SNAPSHOT_KIND,
+        )?;
+
+        // the content was checked against its hash: the lengths are not bounded further
+        let thread_count = self.config.thread_count;
+        let slot_deserializer = SlotDeserializer::new(
+            (Included(0), Included(u64::MAX)),
+            (Included(0), Excluded(thread_count)),
+        );
+        let async_pool_deserializer = AsyncPoolDeserializer::new(
+            thread_count,
+            self.config.async_pool_config.max_length,
+            self.config.async_pool_config.max_async_message_data,
+        );
+        let length_deserializer = U64VarIntDeserializer::new(Included(0), Included(u64::MAX));
+        let cycle_deserializer = CycleInfoDeserializer::new(u64::MAX, u64::MAX);
+        let credits_deserializer = DeferredCreditsDeserializer::new(thread_count, u64::MAX);
+        let executed_ops_deserializer =
+            ExecutedOpsDeserializer::new(thread_count, u64::MAX, u64::MAX);
+        let (rest, (slot, messages, cycle_history, deferred_credits, executed_ops)) = tuple((
+            |input| slot_deserializer.deserialize(input),
+            |input| async_pool_deserializer.deserialize(input),
+            length_count(
+                |input| length_deserializer.deserialize(input),
+                |input| cycle_deserializer.deserialize(input),
+            ),
+            |input| credits_deserializer.deserialize(input),
+            |input| executed_ops_deserializer.deserialize(input),
+        ))
+        .parse(&content[..])
+        .map_err(|err: nom::Err<DeserializeError>| {
+            FinalStateError::SnapshotError(format!("could not deserialize final state: {}", err))
+        })?;
+        if !rest.is_empty() {
+            return Err(FinalStateError::SnapshotError(
+                "trailing bytes after the final state".to_string(),
+            ));
+        }
+
+        let ledger_slot = self
+            .ledger
+            .import_snapshot(&dir.join(LEDGER_SNAPSHOT_FILE))
+            .map_err(|err| FinalStateError::LedgerError(err.to_string()))?;
+        if ledger_slot != slot {
+            self.ledger.reset();
+            return Err(FinalStateError::SnapshotError(format!(
+                "ledger slot {} does not match final state slot {}",
+                ledger_slot, slot
+            )));
+        }
+
+        // restore the rest of the state through the bootstrap setters
+        self.slot = slot;
+        self.async_pool.set_pool_part(messages);
+        for cycle_info in cycle_history {
+            self.pos_state.set_cycle_history_part(Some(cycle_info));
+        }
+        self.pos_state.set_deferred_credits_part(deferred_credits);
+        self.executed_ops.set_executed_ops_part(executed_ops);
+        Ok(slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_exports::assert_eq_final_state, FinalStateConfig};
+    use massa_async_pool::{test_exports::get_random_message, AsyncPoolChanges, Change};
+    use massa_ledger_exports::{LedgerConfig, LedgerEntry};
+    use massa_models::{
+        address::Address, amount::Amount, config::THREAD_COUNT, operation::OperationId,
+        prehash::PreHashMap, wrapped::Id,
+    };
+    use massa_pos_exports::{test_exports::MockSelectorController, PoSFinalState};
+    use massa_signature::KeyPair;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::str::FromStr;
+
+    /// Final state at genesis, using the given ledger configuration
+    fn get_final_state(ledger_config: LedgerConfig) -> FinalState {
+        let config = FinalStateConfig {
+            ledger_config,
+            thread_count: THREAD_COUNT,
+            ..Default::default()
+        };
+        let (selector, _) = MockSelectorController::new_with_receiver();
+        let pos_state = PoSFinalState::new(
+            config.pos_config.clone(),
+            "",
+            &PathBuf::from("../massa-node/base_config/initial_rolls.json"),
+            selector,
+        )
+        .unwrap();
+        let mut final_state = FinalState::create_final_state(pos_state, config);
+        final_state.slot = Slot::new(0, THREAD_COUNT - 1);
+        final_state
+    }
+
+    #[test]
+    fn test_final_state_snapshot_round_trip() {
+        let dir =
+            std::env::temp_dir().join(format!("massa-snapshot-round-trip-{}", std::process::id()));
+
+        // final state with a ledger entry, an async message, a cycle and an executed operation
+        let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let initial_ledger = HashMap::from([(
+            address,
+            LedgerEntry {
+                balance: Amount::from_str("42").unwrap(),
+                ..Default::default()
+            },
+        )]);
+        let (ledger_config, _initial_ledger, _disk_ledger) = LedgerConfig::sample(&initial_ledger);
+        let mut final_state = get_final_state(ledger_config);
+        final_state.ledger.load_initial_ledger().unwrap();
+        let message = get_random_message();
+        final_state
+            .async_pool
+            .apply_changes_unchecked(&AsyncPoolChanges(vec![Change::Add(
+                message.compute_id(),
+                message,
+            )]));
+        final_state.pos_state.create_initial_cycle();
+        let mut executed_ops = PreHashMap::default();
+        executed_ops.insert(
+            OperationId::new(Hash::compute_from(b"operation")),
+            Slot::new(10, 0),
+        );
+        final_state
+            .executed_ops
+            .apply_changes(executed_ops, final_state.slot);
+
+        // save it and load it in a fresh final state
+        let snapshot = final_state.take_snapshot(&dir).unwrap();
+        assert_eq!(snapshot.write().unwrap(), final_state.slot);
+        assert!(!dir.join("ledger.checkpoint").exists());
+        let (ledger_config, _initial_ledger, _disk_ledger) = LedgerConfig::sample(&HashMap::new());
+        let mut loaded_state = get_final_state(ledger_config);
+        assert_eq!(loaded_state.load_snapshot(&dir).unwrap(), final_state.slot);
+        assert_eq_final_state(&final_state, &loaded_state);
+
+        // a ledger snapshot of another slot is rejected, leaving the ledger empty
+        let other_dir = dir.with_extension("other");
+        final_state
+            .ledger
+            .apply_changes(Default::default(), Slot::new(1, 0));
+        final_state.slot = Slot::new(1, 0);
+        final_state
+            .take_snapshot(&other_dir)
+            .unwrap()
+            .write()
+            .unwrap();
+        fs::copy(
+            other_dir.join(LEDGER_SNAPSHOT_FILE),
+            dir.join(LEDGER_SNAPSHOT_FILE),
+        )
+        .unwrap();
+        let (ledger_config, _initial_ledger, _disk_ledger) = LedgerConfig::sample(&HashMap::new());
+        let mut loaded_state = get_final_state(ledger_config);
+        assert!(loaded_state.load_snapshot(&dir).is_err());
+        assert!(loaded_state.ledger.get_every_address().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_dir_all(&other_dir).unwrap();
+    }
+
+    #[test]
+    fn test_snapshot_file_integrity() {
+        let dir = std::env::temp_dir().join(format!("massa-snapshot-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.snapshot");
+
+        // round trip
+        write_snapshot_file(&path, "test", b"final state content").unwrap();
+        assert_eq!(
+            read_snapshot_file(&path, "test").unwrap(),
+            b"final state content".to_vec()
+        );
+
+        // another kind of snapshot is rejected
+        assert!(read_snapshot_file(&path, "graph").is_err());
+
+        // a corrupted content is rejected
+        let mut bytes = fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        fs::write(&path, &bytes).unwrap();
+        assert!(read_snapshot_file(&path, "test").is_err());
+
+        // another version is rejected
+        let mut bytes = snapshot_header("test");
+        let version_index = bytes.len() - 1;
+        bytes[version_index] ^= 1;
+        bytes.extend(Hash::compute_from(b"content").to_bytes());
+        bytes.extend(b"content");
+        fs::write(&path, &bytes).unwrap();
+        assert!(read_snapshot_file(&path, "test").is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
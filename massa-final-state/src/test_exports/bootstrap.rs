@@ -2,12 +2,14 @@
 
 //! This file defines tools to test the final state bootstrap
 
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 
 use massa_async_pool::AsyncPool;
 use massa_executed_ops::ExecutedOps;
 use massa_ledger_exports::LedgerController;
-use massa_models::slot::Slot;
+use massa_models::{
+    operation::OperationId, prehash::PreHashSet, slot::Slot, streaming_step::StreamingStep,
+};
 use massa_pos_exports::PoSFinalState;
 
 use crate::{FinalState, FinalStateConfig, StateChanges};
@@ -46,16 +48,29 @@ pub fn assert_eq_final_state(v1: &FinalState, v2: &FinalState) {
     );
     massa_pos_exports::test_exports::assert_eq_pos_state(&v1.pos_state, &v2.pos_state);
     assert_eq!(
-        v1.executed_ops.ops.len(),
-        v2.executed_ops.ops.len(),
-        "executed_ops.ops lenght mismatch"
+        v1.executed_ops.len(),
+        v2.executed_ops.len(),
+        "executed_ops lenght mismatch"
     );
     assert_eq!(
-        v1.executed_ops.ops, v2.executed_ops.ops,
-        "executed_ops.ops mismatch"
+        v1.executed_ops.hash, v2.executed_ops.hash,
+        "executed_ops hash mismatch"
     );
     assert_eq!(
-        v1.executed_ops.sorted_ops, v2.executed_ops.sorted_ops,
-        "executed_ops.sorted_ops mismatch"
+        get_all_executed_ops(&v1.executed_ops),
+        get_all_executed_ops(&v2.executed_ops),
+        "executed_ops mismatch"
     );
 }
+
+/// gets every executed operation, sorted by expiration slot
+fn get_all_executed_ops(executed_ops: &ExecutedOps) -> BTreeMap<Slot, PreHashSet<OperationId>> {
+    let mut ops = BTreeMap::new();
+    let mut cursor = StreamingStep::Started;
+    while !cursor.finished() {
+        let (part, new_cursor) = executed_ops.get_executed_ops_part(cursor);
+        ops.extend(part);
+        cursor = new_cursor;
+    }
+    ops
+}
@@ -42,6 +42,8 @@ impl Default for FinalStateConfig {
             executed_ops_config: ExecutedOpsConfig {
                 thread_count: THREAD_COUNT,
                 bootstrap_part_size: EXECUTED_OPS_BOOTSTRAP_PART_SIZE,
+                disk_path: None,
+                bloom_filter_size: 1000,
             },
             pos_config: PoSConfig {
                 periods_per_cycle: PERIODS_PER_CYCLE,
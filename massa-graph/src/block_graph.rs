@@ -11,7 +11,10 @@ use massa_hash::Hash;
 use massa_logging::massa_trace;
 use massa_models::prehash::{CapacityAllocator, PreHashMap, PreHashSet};
 use massa_models::{
-    active_block::ActiveBlock, api::BlockGraphStatus, clique::Clique, wrapped::WrappedContent,
+    active_block::ActiveBlock,
+    api::{BlockConfirmation, BlockDiscardKind, BlockGraphStatus},
+    clique::Clique,
+    wrapped::WrappedContent,
 };
 use massa_models::{
     address::Address,
@@ -53,14 +56,46 @@ impl HeaderOrBlock {
 /// Something can be discarded
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DiscardReason {
-    /// Block is invalid, either structurally, or because of some incompatibility. The String contains the reason for info or debugging.
-    Invalid(String),
+    /// Block is invalid, either structurally, or because of some incompatibility.
+    /// The kind tells why in a machine-readable way, the String contains details for info or debugging.
+    Invalid(BlockDiscardKind, String),
     /// Block is incompatible with a final block.
     Stale,
+    /// Block is not newer than the latest final block of its thread.
+    Late,
     /// Block has enough fitness.
     Final,
 }
 
+impl DiscardReason {
+    /// Gets the machine-readable kind of the reason
+    pub fn kind(&self) -> BlockDiscardKind {
+        match self {
+            DiscardReason::Invalid(kind, _) => *kind,
+            DiscardReason::Stale => BlockDiscardKind::Stale,
+            DiscardReason::Late => BlockDiscardKind::Late,
+            DiscardReason::Final => BlockDiscardKind::Final,
+        }
+    }
+
+    /// Returns true if the block is stale: late or incompatible with a final block
+    pub fn is_stale(&self) -> bool {
+        matches!(self, DiscardReason::Stale | DiscardReason::Late)
+    }
+
+    /// Gets the reason to discard a block depending on a block discarded for this reason
+    fn inherit(&self, details: impl FnOnce(&str) -> String) -> DiscardReason {
+        match self {
+            DiscardReason::Invalid(_, reason) => DiscardReason::Invalid(
+                BlockDiscardKind::InvalidDependency,
+                details(reason.as_str()),
+            ),
+            DiscardReason::Late => DiscardReason::Stale,
+            reason => reason.clone(),
+        }
+    }
+}
+
 /// Enum used in `BlockGraph`'s state machine
 #[derive(Debug, Clone)]
 enum BlockStatus {
@@ -276,6 +311,8 @@ pub struct BlockGraph {
     new_final_blocks: PreHashSet<BlockId>,
     /// Newly stale block mapped to creator and slot
     new_stale_blocks: PreHashMap<BlockId, (Address, Slot)>,
+    /// Blocks whose confirmation weight newly crossed a configured threshold
+    new_confirmations: Vec<BlockConfirmation>,
     /// Shared storage,
     storage: Storage,
     /// Selector controller
@@ -386,6 +423,7 @@ impl BlockGraph {
                         block_id,
                         slot: block.content.header.content.slot,
                         fitness: block.get_fitness(),
+                        confirmation_weight: block.get_fitness(),
                     }),
                     storage,
                 },
@@ -433,6 +471,7 @@ impl BlockGraph {
                 attack_attempts: Default::default(),
                 new_final_blocks: Default::default(),
                 new_stale_blocks: Default::default(),
+                new_confirmations: Default::default(),
                 storage,
                 selector_controller,
                 block_statuses: final_blocks
@@ -473,18 +512,25 @@ impl BlockGraph {
             }
 
             // list active block parents
-            let active_blocks_map: PreHashMap<BlockId, (Slot, Vec<BlockId>)> = res_graph
+            let active_blocks_map: PreHashMap<BlockId, (Slot, u64, Vec<BlockId>)> = res_graph
                 .block_statuses
                 .iter()
                 .filter_map(|(h, s)| {
                     if let BlockStatus::Active { a_block: a, .. } = s {
-                        return Some((*h, (a.slot, a.parents.iter().map(|(ph, _)| *ph).collect())));
+                        return Some((
+                            *h,
+                            (
+                                a.slot,
+                                a.fitness,
+                                a.parents.iter().map(|(ph, _)| *ph).collect(),
+                            ),
+                        ));
                     }
                     None
                 })
                 .collect();
-            // deduce children and descendants
-            for (b_id, (b_slot, b_parents)) in active_blocks_map.into_iter() {
+            // deduce children, descendants and confirmation weights
+            for (b_id, (b_slot, b_fitness, b_parents)) in active_blocks_map.into_iter() {
                 // deduce children
                 for parent_id in &b_parents {
                     if let Some(BlockStatus::Active {
@@ -506,6 +552,7 @@ impl BlockGraph {
                         res_graph.block_statuses.get_mut(&ancestor_h)
                     {
                         ab.descendants.insert(b_id);
+                        ab.confirmation_weight = ab.confirmation_weight.saturating_add(b_fitness);
                         for (ancestor_parent_h, _) in ab.parents.iter() {
                             ancestors.push_front(*ancestor_parent_h);
                         }
@@ -536,6 +583,7 @@ impl BlockGraph {
                 attack_attempts: Default::default(),
                 new_final_blocks: Default::default(),
                 new_stale_blocks: Default::default(),
+                new_confirmations: Default::default(),
                 storage,
                 selector_controller,
             })
@@ -638,7 +686,9 @@ impl BlockGraph {
                     BlockGraphStatus::ActiveInAlternativeCliques
                 }
             }
-            Some(BlockStatus::Discarded { .. }) => BlockGraphStatus::Discarded,
+            Some(BlockStatus::Discarded { reason, .. }) => {
+                BlockGraphStatus::Discarded(reason.kind())
+            }
             Some(BlockStatus::Incoming(_)) => BlockGraphStatus::Incoming,
             Some(BlockStatus::WaitingForDependencies { .. }) => {
                 BlockGraphStatus::WaitingForDependencies
@@ -933,7 +983,7 @@ impl BlockGraph {
                         self.maybe_note_attack_attempt(&reason, &block_id);
                         massa_trace!("consensus.block_graph.process.incoming_header.discarded", {"block_id": block_id, "reason": reason});
                         // count stales
-                        if reason == DiscardReason::Stale {
+                        if reason.is_stale() {
                             self.new_stale_blocks
                                 .insert(block_id, (header.creator_address, header.content.slot));
                         }
@@ -1049,7 +1099,7 @@ impl BlockGraph {
                         self.maybe_note_attack_attempt(&reason, &block_id);
                         massa_trace!("consensus.block_graph.process.incoming_block.discarded", {"block_id": block_id, "reason": reason});
                         // count stales
-                        if reason == DiscardReason::Stale {
+                        if reason.is_stale() {
                             self.new_stale_blocks.insert(
                                 block_id,
                                 (
@@ -1184,7 +1234,8 @@ impl BlockGraph {
         block_id: &BlockId,
         header: WrappedHeader,
     ) -> Result<(), GraphError> {
-        let reason = DiscardReason::Invalid("invalid".to_string());
+        let reason =
+            DiscardReason::Invalid(BlockDiscardKind::InvalidContent, "invalid".to_string());
         self.maybe_note_attack_attempt(&reason, block_id);
         massa_trace!("consensus.block_graph.process.invalid_block", {"block_id": block_id, "reason": reason});
 
@@ -1208,7 +1259,7 @@ impl BlockGraph {
     fn maybe_note_attack_attempt(&mut self, reason: &DiscardReason, hash: &BlockId) {
         massa_trace!("consensus.block_graph.maybe_note_attack_attempt", {"hash": hash, "reason": reason});
         // If invalid, note the attack attempt.
-        if let DiscardReason::Invalid(reason) = reason {
+        if let DiscardReason::Invalid(_, reason) = reason {
             info!(
                 "consensus.block_graph.maybe_note_attack_attempt DiscardReason::Invalid:{}",
                 reason
@@ -1289,7 +1340,7 @@ impl BlockGraph {
         if header.content.slot.period
             <= self.latest_final_blocks_periods[header.content.slot.thread as usize].1
         {
-            return Ok(HeaderCheckOutcome::Discard(DiscardReason::Stale));
+            return Ok(HeaderCheckOutcome::Discard(DiscardReason::Late));
         }
 
         // check if block slot is too much in the future
@@ -1311,6 +1362,7 @@ impl BlockGraph {
         if creator_addr != slot_draw_address {
             // it was not the creator's turn to create a block for this slot
             return Ok(HeaderCheckOutcome::Discard(DiscardReason::Invalid(
+                BlockDiscardKind::WrongCreator,
                 format!("Bad creator turn for the slot:{}", header.content.slot),
             )));
         }
@@ -1332,13 +1384,14 @@ impl BlockGraph {
             match self.block_statuses.get(&parent_hash) {
                 Some(BlockStatus::Discarded { reason, .. }) => {
                     // parent is discarded
-                    return Ok(HeaderCheckOutcome::Discard(match reason {
-                        DiscardReason::Invalid(invalid_reason) => DiscardReason::Invalid(format!(
+                    return Ok(HeaderCheckOutcome::Discard(reason.inherit(
+                        |invalid_reason| {
+                            format!(
                             "discarded because a parent was discarded for the following reason: {}",
                             invalid_reason
-                        )),
-                        r => r.clone(),
-                    }));
+                        )
+                        },
+                    )));
                 }
                 Some(BlockStatus::Active {
                     a_block: parent, ..
@@ -1348,6 +1401,7 @@ impl BlockGraph {
                     // check that the parent is from an earlier slot in the right thread
                     if parent.slot.thread != parent_thread || parent.slot >= header.content.slot {
                         return Ok(HeaderCheckOutcome::Discard(DiscardReason::Invalid(
+                            BlockDiscardKind::ParentMismatch,
                             format!(
                                 "Bad parent {} in thread:{} or slot:{} for {}.",
                                 parent_hash, parent_thread, parent.slot, header.content.slot
@@ -1360,6 +1414,7 @@ impl BlockGraph {
                     if let Some(p_incomp) = self.gi_head.get(&parent_hash) {
                         if !p_incomp.is_disjoint(&parent_set) {
                             return Ok(HeaderCheckOutcome::Discard(DiscardReason::Invalid(
+                                BlockDiscardKind::ParentMismatch,
                                 "Parent not mutually compatible".to_string(),
                             )));
                         }
@@ -1400,6 +1455,7 @@ impl BlockGraph {
                 if parent_period < gp_max_slots[parent_i as usize] {
                     // a parent is earlier than a block known by another parent in that thread
                     return Ok(HeaderCheckOutcome::Discard(DiscardReason::Invalid(
+                        BlockDiscardKind::ParentMismatch,
                         "a parent is earlier than a block known by another parent in that thread"
                             .to_string(),
                     )));
@@ -1417,7 +1473,14 @@ impl BlockGraph {
                     match self.block_statuses.get(&gp_h) {
                         // this grandpa is discarded
                         Some(BlockStatus::Discarded { reason, .. }) => {
-                            return Ok(HeaderCheckOutcome::Discard(reason.clone()));
+                            return Ok(HeaderCheckOutcome::Discard(reason.inherit(
+                                |invalid_reason| {
+                                    format!(
+                                        "discarded because a grandparent was discarded for the following reason: {}",
+                                        invalid_reason
+                                    )
+                                },
+                            )));
                         }
                         // this grandpa is active
                         Some(BlockStatus::Active { a_block: gp, .. }) => {
@@ -1425,6 +1488,7 @@ impl BlockGraph {
                                 if gp_i < parent_i {
                                     return Ok(HeaderCheckOutcome::Discard(
                                         DiscardReason::Invalid(
+                                            BlockDiscardKind::ParentMismatch,
                                             "grandpa error: gp_i < parent_i".to_string(),
                                         ),
                                     ));
@@ -1531,6 +1595,7 @@ impl BlockGraph {
         // check if the block is incompatible with a parent
         if !incomp.is_disjoint(&parents.iter().map(|(h, _p)| *h).collect()) {
             return Ok(HeaderCheckOutcome::Discard(DiscardReason::Invalid(
+                BlockDiscardKind::ParentMismatch,
                 "Block incompatible with a parent".to_string(),
             )));
         }
@@ -1579,6 +1644,7 @@ impl BlockGraph {
             if endorsement.creator_address != endorsement_draws[endorsement.content.index as usize]
             {
                 return Ok(EndorsementsCheckOutcome::Discard(DiscardReason::Invalid(
+                    BlockDiscardKind::WrongEndorser,
                     format!(
                         "endorser draw mismatch for header in slot: {}",
                         header.content.slot
@@ -1601,6 +1667,19 @@ impl BlockGraph {
         &self.genesis_hashes
     }
 
+    /// Gets the confirmation weight of a block, if it is active
+    pub fn get_confirmation_weight(&self, block_id: &BlockId) -> Option<u64> {
+        match self.block_statuses.get(block_id) {
+            Some(BlockStatus::Active { a_block, .. }) => Some(a_block.confirmation_weight),
+            _ => None,
+        }
+    }
+
+    /// get the ids of all active blocks
+    pub fn get_active_block_ids(&self) -> &PreHashSet<BlockId> {
+        &self.active_index
+    }
+
     /// Computes max cliques of compatible blocks
     pub fn compute_max_cliques(&self) -> Vec<PreHashSet<BlockId>> {
         let mut max_cliques: Vec<PreHashSet<BlockId>> = Vec::new();
@@ -1686,6 +1765,7 @@ impl BlockGraph {
                     is_final: false,
                     slot: add_block_slot,
                     fitness,
+                    confirmation_weight: fitness,
                 }),
                 storage,
             },
@@ -1708,7 +1788,8 @@ impl BlockGraph {
             }
         }
 
-        // add as descendant to ancestors. Note: descendants are never removed.
+        // add as descendant to ancestors, adding its fitness to their confirmation weight.
+        // Note: descendants are never removed.
         {
             let mut ancestors: VecDeque<BlockId> =
                 parents_hash_period.iter().map(|(h, _)| *h).collect();
@@ -1721,6 +1802,18 @@ impl BlockGraph {
                     self.block_statuses.get_mut(&ancestor_h)
                 {
                     ab.descendants.insert(add_block_id);
+                    let prev_weight = ab.confirmation_weight;
+                    ab.confirmation_weight = prev_weight.saturating_add(fitness);
+                    for threshold in self.cfg.confirmation_weight_thresholds.iter() {
+                        if prev_weight < *threshold && ab.confirmation_weight >= *threshold {
+                            self.new_confirmations.push(BlockConfirmation {
+                                id: ancestor_h,
+                                slot: ab.slot,
+                                threshold: *threshold,
+                                weight: ab.confirmation_weight,
+                            });
+                        }
+                    }
                     for (ancestor_parent_h, _) in ab.parents.iter() {
                         ancestors.push_front(*ancestor_parent_h);
                     }
@@ -2361,12 +2454,15 @@ impl BlockGraph {
                         {
                             discarded_dep_found = true;
                             match reason {
-                                DiscardReason::Invalid(reason) => {
-                                    discard_reason = Some(DiscardReason::Invalid(format!("discarded because depend on block:{} that has discard reason:{}", block_id, reason)));
+                                DiscardReason::Invalid(..) => {
+                                    discard_reason = Some(reason.inherit(|reason| format!("discarded because depend on block:{} that has discard reason:{}", block_id, reason)));
                                     break;
                                 }
-                                DiscardReason::Stale => discard_reason = Some(DiscardReason::Stale),
-                                DiscardReason::Final => discard_reason = Some(DiscardReason::Stale),
+                                DiscardReason::Stale
+                                | DiscardReason::Late
+                                | DiscardReason::Final => {
+                                    discard_reason = Some(DiscardReason::Stale)
+                                }
                             }
                         }
                     }
@@ -2378,7 +2474,7 @@ impl BlockGraph {
                     // is at least as old as the latest final block in its thread => discard as stale
                     let slot = header_or_block.get_slot();
                     if slot.period <= self.latest_final_blocks_periods[slot.thread as usize].1 {
-                        to_discard.insert(*block_id, Some(DiscardReason::Stale));
+                        to_discard.insert(*block_id, Some(DiscardReason::Late));
                         continue;
                     }
 
@@ -2404,16 +2500,15 @@ impl BlockGraph {
                         if let Some(reason) = to_discard.get(dep) {
                             dep_to_discard_found = true;
                             match reason {
-                                Some(DiscardReason::Invalid(reason)) => {
-                                    discard_reason = Some(DiscardReason::Invalid(format!("discarded because depend on block:{} that has discard reason:{}", hash, reason)));
+                                Some(reason @ DiscardReason::Invalid(..)) => {
+                                    discard_reason = Some(reason.inherit(|reason| format!("discarded because depend on block:{} that has discard reason:{}", hash, reason)));
                                     break;
                                 }
-                                Some(DiscardReason::Stale) => {
-                                    discard_reason = Some(DiscardReason::Stale)
-                                }
-                                Some(DiscardReason::Final) => {
-                                    discard_reason = Some(DiscardReason::Stale)
-                                }
+                                Some(
+                                    DiscardReason::Stale
+                                    | DiscardReason::Late
+                                    | DiscardReason::Final,
+                                ) => discard_reason = Some(DiscardReason::Stale),
                                 None => {} // leave as None
                             }
                         }
@@ -2480,7 +2575,7 @@ impl BlockGraph {
 
                 if let Some(reason) = reason_opt {
                     // add to stats if reason is Stale
-                    if reason == DiscardReason::Stale {
+                    if reason.is_stale() {
                         self.new_stale_blocks
                             .insert(block_id, (header.creator_address, header.content.slot));
                     }
@@ -2739,4 +2834,10 @@ impl BlockGraph {
     pub fn get_new_stale_blocks(&mut self) -> PreHashMap<BlockId, (Address, Slot)> {
         mem::take(&mut self.new_stale_blocks)
     }
+
+    /// Get the blocks whose confirmation weight crossed a configured threshold.
+    /// Must be called by the consensus worker within `block_db_changed`.
+    pub fn get_new_confirmations(&mut self) -> Vec<BlockConfirmation> {
+        mem::take(&mut self.new_confirmations)
+    }
 }
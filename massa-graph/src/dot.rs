@@ -0,0 +1,158 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Rendering of an exported block graph in the DOT language of graphviz.
+//!
+//! Each block is a node grouped with the other blocks of its thread, with an edge to each of its parents.
+//! Final blocks are green, active blocks of the blockclique blue, the other active blocks white,
+//! stale blocks red and the other discarded blocks grey. The label of an active block lists the
+//! maximal cliques containing it, the blockclique being starred, and incompatible active blocks
+//! are linked by dashed red edges.
+
+use crate::{BlockGraphExport, DiscardReason};
+use massa_models::{block::BlockId, slot::Slot};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Length of the prefix of the block ids shown in the labels
+const LABEL_ID_LENGTH: usize = 8;
+
+impl BlockGraphExport {
+    /// Gets the slot and the parents of an exported block, active or discarded
+    pub fn get_slot_and_parents(&self, id: &BlockId) -> Option<(Slot, &[BlockId])> {
+        if let Some(block) = self.active_blocks.get(id) {
+            return Some((block.header.content.slot, &block.header.content.parents));
+        }
+        self.discarded_blocks
+            .get(id)
+            .map(|(_, (slot, _, parents))| (*slot, parents.as_slice()))
+    }
+
+    /// Gets the ids of the exported blocks, active and discarded, sorted by slot then by id.
+    /// The parents of a block being at earlier slots, they always come before it.
+    pub fn get_blocks_by_slot(&self) -> Vec<(Slot, BlockId)> {
+        let mut blocks: Vec<(Slot, BlockId)> = self
+            .active_blocks
+            .iter()
+            .map(|(id, block)| (block.header.content.slot, *id))
+            .chain(
+                self.discarded_blocks
+                    .iter()
+                    .map(|(id, (_, (slot, _, _)))| (*slot, *id)),
+            )
+            .collect();
+        blocks.sort_unstable();
+        blocks
+    }
+
+    /// Gets the indexes of the maximal cliques containing a block
+    pub fn get_block_cliques(&self, id: &BlockId) -> Vec<usize> {
+        self.max_cliques
+            .iter()
+            .enumerate()
+            .filter(|(_, clique)| clique.block_ids.contains(id))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Renders the exported graph in the DOT language
+    pub fn to_dot(&self) -> String {
+        let blocks = self.get_blocks_by_slot();
+        let mut threads: BTreeMap<u8, Vec<String>> = BTreeMap::new();
+        for (slot, id) in &blocks {
+            threads
+                .entry(slot.thread)
+                .or_default()
+                .push(self.dot_node(id, slot));
+        }
+
+        // writing to a String cannot fail
+        let mut dot = String::new();
+        let _ = writeln!(dot, "digraph block_graph {{");
+        let _ = writeln!(dot, "    rankdir=LR;");
+        let _ = writeln!(
+            dot,
+            "    node [shape=box, style=filled, fontname=\"monospace\"];"
+        );
+        let _ = writeln!(dot, "    edge [dir=back];");
+        for (thread, nodes) in threads {
+            let _ = writeln!(dot, "    subgraph cluster_thread_{} {{", thread);
+            let _ = writeln!(dot, "        label=\"thread {}\";", thread);
+            for node in nodes {
+                let _ = writeln!(dot, "        {}", node);
+            }
+            let _ = writeln!(dot, "    }}");
+        }
+        // edges from the parents to their children, drawn as arrows towards the parents
+        for (_, id) in &blocks {
+            if let Some((_, parents)) = self.get_slot_and_parents(id) {
+                for parent in parents {
+                    if self.get_slot_and_parents(parent).is_some() {
+                        let _ = writeln!(dot, "    \"{}\" -> \"{}\";", parent, id);
+                    }
+                }
+            }
+        }
+        for (id, incompatibilities) in &self.gi_head {
+            for other in incompatibilities {
+                if id < other
+                    && self.active_blocks.contains_key(id)
+                    && self.active_blocks.contains_key(other)
+                {
+                    let _ = writeln!(
+                        dot,
+                        "    \"{}\" -> \"{}\" [dir=none, style=dashed, color=red, constraint=false];",
+                        id, other
+                    );
+                }
+            }
+        }
+        let _ = writeln!(dot, "}}");
+        dot
+    }
+
+    /// Renders the node of an exported block
+    fn dot_node(&self, id: &BlockId, slot: &Slot) -> String {
+        let id_str = id.to_string();
+        let mut label = format!(
+            "{}\\nperiod {}",
+            &id_str[..LABEL_ID_LENGTH.min(id_str.len())],
+            slot.period
+        );
+        let color = if let Some(block) = self.active_blocks.get(id) {
+            let cliques = self.get_block_cliques(id);
+            let in_blockclique = cliques
+                .iter()
+                .any(|index| self.max_cliques[*index].is_blockclique);
+            let cliques: Vec<String> = cliques
+                .into_iter()
+                .map(|index| {
+                    if self.max_cliques[index].is_blockclique {
+                        format!("{}*", index)
+                    } else {
+                        index.to_string()
+                    }
+                })
+                .collect();
+            if !cliques.is_empty() {
+                let _ = write!(label, "\\ncliques {}", cliques.join(","));
+            }
+            if block.is_final {
+                "palegreen"
+            } else if in_blockclique {
+                "lightblue"
+            } else {
+                "white"
+            }
+        } else if let Some((reason, _)) = self.discarded_blocks.get(id) {
+            let _ = write!(label, "\\n{}", reason.kind());
+            match reason {
+                DiscardReason::Final => "palegreen",
+                reason if reason.is_stale() => "lightcoral",
+                _ => "lightgrey",
+            }
+        } else {
+            "white"
+        };
+        format!("\"{}\" [label=\"{}\", fillcolor={}];", id_str, label, color)
+    }
+}
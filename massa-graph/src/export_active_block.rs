@@ -116,6 +116,7 @@ impl ExportActiveBlock {
             is_final: self.is_final,
             slot: self.block.content.header.content.slot,
             fitness: self.block.get_fitness(),
+            confirmation_weight: self.block.get_fitness(), // descendants are added once the full graph is available
         };
 
         // add block to storage and claim ref
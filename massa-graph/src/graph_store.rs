@@ -0,0 +1,158 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Disk storage of the active blocks of the graph.
+//!
+//! The final blocks needed to restart are part of the state snapshot: this store keeps the
+//! whole active graph, including the non-final blocks, so that a restarting node replays them
+//! instead of downloading them again.
+//! Only the blocks that changed since the previous sync are written.
+
+use crate::{
+    block_graph::BlockGraph,
+    error::{GraphError, GraphResult as Result},
+    export_active_block::{
+        ExportActiveBlock, ExportActiveBlockDeserializer, ExportActiveBlockSerializer,
+    },
+    settings::GraphConfig,
+};
+use massa_models::{
+    block::BlockId,
+    config::constants::{
+        MAX_DATASTORE_VALUE_LENGTH, MAX_FUNCTION_NAME_LENGTH, MAX_OPERATIONS_PER_BLOCK,
+        MAX_OPERATION_DATASTORE_ENTRY_COUNT, MAX_OPERATION_DATASTORE_KEY_LENGTH,
+        MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE,
+    },
+    prehash::{PreHashMap, PreHashSet},
+};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use rocksdb::{ColumnFamilyDescriptor, IteratorMode, Options, WriteBatch, DB};
+use std::fmt::Debug;
+use std::path::Path;
+use tracing::warn;
+
+const BLOCKS_CF: &str = "blocks";
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const CF_ERROR: &str = "critical: rocksdb column family operation failed";
+
+/// Disk storage of the active blocks of the graph.
+///
+/// The `blocks` column family associates each block id to its serialized `ExportActiveBlock`.
+pub struct BlockGraphStore {
+    db: DB,
+    /// stored block ids along their finality at the time they were written
+    stored: PreHashMap<BlockId, bool>,
+    serializer: ExportActiveBlockSerializer,
+    deserializer: ExportActiveBlockDeserializer,
+}
+
+impl Debug for BlockGraphStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#?}", self.db)
+    }
+}
+
+impl BlockGraphStore {
+    /// Opens the store at `path`, keeping the blocks written by a previous run
+    pub fn new(path: &Path, cfg: &GraphConfig) -> Self {
+        let mut db_opts = Options::default();
+        db_opts.create_if_missing(true);
+        db_opts.create_missing_column_families(true);
+        let db = DB::open_cf_descriptors(
+            &db_opts,
+            path,
+            vec![ColumnFamilyDescriptor::new(BLOCKS_CF, Options::default())],
+        )
+        .expect(OPEN_ERROR);
+        BlockGraphStore {
+            db,
+            stored: Default::default(),
+            serializer: ExportActiveBlockSerializer::new(),
+            deserializer: ExportActiveBlockDeserializer::new(
+                cfg.thread_count,
+                cfg.endorsement_count,
+                MAX_OPERATIONS_PER_BLOCK,
+                MAX_DATASTORE_VALUE_LENGTH,
+                MAX_FUNCTION_NAME_LENGTH,
+                MAX_PARAMETERS_SIZE,
+                MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+                MAX_OPERATION_DATASTORE_KEY_LENGTH,
+                MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+            ),
+        }
+    }
+
+    /// Reads the stored blocks, sorted by slot so that parents come before their children.
+    ///
+    /// Entries that cannot be read back are dropped from the store.
+    pub fn load(&mut self) -> Vec<(BlockId, ExportActiveBlock)> {
+        let handle = self.db.cf_handle(BLOCKS_CF).expect(CF_ERROR);
+        let mut batch = WriteBatch::default();
+        let mut blocks = Vec::new();
+        for (key, value) in self.db.iterator_cf(handle, IteratorMode::Start).flatten() {
+            let block = self
+                .deserializer
+                .deserialize::<DeserializeError>(&value)
+                .ok()
+                .filter(|(rest, _)| rest.is_empty())
+                .map(|(_, block)| block)
+                .filter(|block| block.block.id.to_bytes()[..] == key[..]);
+            match block {
+                Some(block) => {
+                    self.stored.insert(block.block.id, block.is_final);
+                    blocks.push((block.block.id, block));
+                }
+                None => {
+                    warn!("dropping an unreadable entry of the block graph store");
+                    batch.delete_cf(handle, key);
+                }
+            }
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+        blocks.sort_unstable_by_key(|(_, block)| block.block.content.header.content.slot);
+        blocks
+    }
+
+    /// Writes the active blocks of `graph` that are new or became final since the last sync,
+    /// and removes the blocks that left the active graph.
+    pub fn sync(&mut self, graph: &BlockGraph) -> Result<()> {
+        let handle = self.db.cf_handle(BLOCKS_CF).expect(CF_ERROR);
+        let genesis_ids: PreHashSet<BlockId> =
+            graph.get_genesis_block_ids().iter().copied().collect();
+        let mut batch = WriteBatch::default();
+        let mut active: PreHashSet<BlockId> = Default::default();
+        for block_id in graph.get_active_block_ids() {
+            if genesis_ids.contains(block_id) {
+                continue;
+            }
+            let (a_block, storage) = graph.get_active_block(block_id).ok_or_else(|| {
+                GraphError::ContainerInconsistency(format!(
+                    "active block {} missing from the graph",
+                    block_id
+                ))
+            })?;
+            active.insert(*block_id);
+            if self.stored.get(block_id) == Some(&a_block.is_final) {
+                continue;
+            }
+            let mut bytes = Vec::new();
+            self.serializer
+                .serialize(
+                    &ExportActiveBlock::from_active_block(a_block, storage),
+                    &mut bytes,
+                )
+                .map_err(|err| GraphError::ContainerInconsistency(err.to_string()))?;
+            batch.put_cf(handle, block_id.to_bytes(), bytes);
+            self.stored.insert(*block_id, a_block.is_final);
+        }
+        self.stored.retain(|block_id, _| {
+            if active.contains(block_id) {
+                return true;
+            }
+            batch.delete_cf(handle, block_id.to_bytes());
+            false
+        });
+        self.db.write(batch).expect(CRUD_ERROR);
+        Ok(())
+    }
+}
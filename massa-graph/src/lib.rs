@@ -19,6 +19,11 @@ pub use bootstrapable_graph::{
 mod block_graph;
 pub use block_graph::*;
 
+mod dot;
+
+mod graph_store;
+pub use graph_store::BlockGraphStore;
+
 /// graph errors
 pub mod error;
 
@@ -31,4 +31,6 @@ pub struct GraphConfig {
     pub endorsement_count: u32,
     /// pub `block_db_prune_interval`: `MassaTime`,
     pub max_item_return_count: usize,
+    /// confirmation weights at which a block is notified as more likely to become final
+    pub confirmation_weight_thresholds: Vec<u64>,
 }
@@ -76,6 +76,21 @@ impl Hash {
         Hash(blake3::hash(data))
     }
 
+    /// Compute a hash from all the data of a reader, streaming it through the hasher.
+    /// Gives the same hash as `compute_from` on the whole data, without loading it in memory.
+    ///
+    /// # Example
+    ///  ```
+    /// # use massa_hash::Hash;
+    /// let hash = Hash::compute_from_reader(&mut "hello world".as_bytes()).unwrap();
+    /// assert_eq!(hash, Hash::compute_from("hello world".as_bytes()));
+    /// ```
+    pub fn compute_from_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let mut hasher = blake3::Hasher::new();
+        std::io::copy(reader, &mut hasher)?;
+        Ok(Hash(hasher.finalize()))
+    }
+
     /// Serialize a Hash using `bs58` encoding with checksum.
     ///
     /// # Example
@@ -347,4 +362,13 @@ mod tests {
         ];
         assert_eq!(hash.into_bytes(), hash_ref);
     }
+
+    #[test]
+    #[serial]
+    fn test_hash_from_reader() {
+        // larger than the copy buffer, so that the data is streamed in several chunks
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let hash = Hash::compute_from_reader(&mut data.as_slice()).unwrap();
+        assert_eq!(hash, Hash::compute_from(&data));
+    }
 }
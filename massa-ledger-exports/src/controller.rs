@@ -1,11 +1,20 @@
 use massa_hash::Hash;
 use massa_models::{
-    address::Address, amount::Amount, error::ModelsError, slot::Slot, streaming_step::StreamingStep,
+    address::Address, amount::Amount, api::ContractInfo, error::ModelsError, slot::Slot,
+    stats::LedgerIntegrityReport, streaming_step::StreamingStep,
 };
 use std::collections::BTreeSet;
 use std::fmt::Debug;
+use std::path::Path;
 
-use crate::{LedgerChanges, LedgerError};
+use crate::{LedgerChanges, LedgerEntryProof, LedgerError, LedgerScrubReport};
+
+/// Writes a ledger snapshot taken by `LedgerController::export_snapshot` to its file.
+/// It does not borrow the ledger, so that the final state lock can be released meanwhile.
+///
+/// # Returns
+/// The slot of the exported ledger
+pub type LedgerSnapshotWriter = Box<dyn FnOnce() -> Result<Slot, LedgerError> + Send>;
 
 pub trait LedgerController: Send + Sync + Debug {
     /// Allows applying `LedgerChanges` to the final ledger
@@ -26,6 +35,12 @@ pub trait LedgerController: Send + Sync + Debug {
     /// A copy of the found bytecode, or None if the ledger entry was not found
     fn get_bytecode(&self, addr: &Address) -> Option<Vec<u8>>;
 
+    /// Gets the size of the bytecode of a ledger entry, without copying it
+    ///
+    /// # Returns
+    /// The size of the bytecode in bytes, or None if the ledger entry was not found
+    fn get_bytecode_size(&self, addr: &Address) -> Option<u64>;
+
     /// Checks if a ledger entry exists
     ///
     /// # Returns
@@ -58,9 +73,83 @@ pub trait LedgerController: Send + Sync + Debug {
     /// A `BTreeSet` of the datastore keys
     fn get_datastore_keys(&self, addr: &Address) -> BTreeSet<Vec<u8>>;
 
+    /// Get a page of the datastore keys of a given address starting with a given prefix.
+    ///
+    /// # Arguments
+    /// * `cursor`: `Started` to get the first keys, `Ongoing(last_key)` to get the keys following `last_key`
+    /// * `max_count`: maximum number of keys to return
+    ///
+    /// # Returns
+    /// The keys of the page and the cursor of the next one, `Finished` if there are no more keys
+    fn get_datastore_keys_page(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: StreamingStep<Vec<u8>>,
+        max_count: usize,
+    ) -> (BTreeSet<Vec<u8>>, StreamingStep<Vec<u8>>);
+
+    /// Get a page of the addresses holding a non-empty bytecode.
+    ///
+    /// # Arguments
+    /// * `cursor`: `Started` to get the first contracts, `Ongoing(last_address)` to get the contracts following `last_address`
+    /// * `max_count`: maximum number of contracts to return
+    ///
+    /// # Returns
+    /// The contracts of the page sorted by address and the cursor of the next one, `Finished` if there are no more contracts
+    fn get_contracts_page(
+        &self,
+        cursor: StreamingStep<Address>,
+        max_count: usize,
+    ) -> (Vec<ContractInfo>, StreamingStep<Address>);
+
+    /// Get the storage size of every address holding a bytecode or datastore entries,
+    /// from an index kept up to date by every change applied to the ledger.
+    ///
+    /// # Returns
+    /// A `BTreeMap` with the address as key and the size in bytes as value
+    fn get_storage_sizes(&self) -> std::collections::BTreeMap<Address, u64>;
+
     /// Get the current disk ledger hash
     fn get_ledger_hash(&self) -> Hash;
 
+    /// Get the root of the Merkle tree of the ledger, that entry proofs lead to
+    fn get_ledger_root(&self) -> Hash;
+
+    /// Get a proof that an entry is part of the ledger.
+    ///
+    /// # Arguments
+    /// * `addr`: address of the entry
+    /// * `datastore_key`: key of the datastore entry to prove, `None` to prove the balance
+    ///
+    /// # Returns
+    /// The proof, `None` if the entry is absent
+    fn get_entry_proof(
+        &self,
+        addr: &Address,
+        datastore_key: Option<&[u8]>,
+    ) -> Option<LedgerEntryProof>;
+
+    /// Check that a range of ledger entries is consistent with the Merkle tree maintained along the ledger hash.
+    /// Meant to be called repeatedly to scrub the whole ledger in the background.
+    ///
+    /// # Arguments
+    /// * `cursor`: `Started` to check the first entries, `Ongoing(last_key)` to check the entries following `last_key`
+    /// * `max_count`: maximum number of entries to check
+    fn scrub_ledger_range(
+        &self,
+        cursor: StreamingStep<Vec<u8>>,
+        max_count: usize,
+    ) -> LedgerScrubReport;
+
+    /// Check the whole ledger: recompute the ledger hash from all the entries, compare it to the stored one,
+    /// and report the entries that are not indexed in the Merkle tree.
+    /// Meant to be called as a maintenance routine, after an unclean shutdown or disk issues.
+    ///
+    /// # Arguments
+    /// * `repair`: if an inconsistency is found, rebuild the ledger hash and the Merkle tree from the entries
+    fn verify_ledger_integrity(&mut self, repair: bool) -> LedgerIntegrityReport;
+
     /// Get a part of the ledger
     /// Used for bootstrap
     /// Return: Tuple with data and last key
@@ -74,6 +163,24 @@ pub trait LedgerController: Send + Sync + Debug {
     /// Return: Last key inserted
     fn set_ledger_part(&self, data: Vec<u8>) -> Result<StreamingStep<Vec<u8>>, ModelsError>;
 
+    /// Delete every entry of the ledger along with its metadata, before bootstrapping it again from scratch
+    fn reset(&mut self);
+
+    /// Write the pending changes of the disk ledger to disk, used before stopping the node
+    fn flush(&self) -> Result<(), LedgerError>;
+
+    /// Take a consistent snapshot of the disk ledger and its metadata, to be exported to a file
+    ///
+    /// # Returns
+    /// The writer exporting the snapshot to `path`
+    fn export_snapshot(&self, path: &Path) -> Result<LedgerSnapshotWriter, LedgerError>;
+
+    /// Replace the disk ledger by a snapshot file created with `export_snapshot`
+    ///
+    /// # Returns
+    /// The slot of the imported ledger
+    fn import_snapshot(&mut self, path: &Path) -> Result<Slot, LedgerError>;
+
     /// Get every address and their corresponding balance.
     ///
     /// IMPORTANT: This should only be used for debug and test purposes.
@@ -621,6 +621,37 @@ impl LedgerChanges {
         }
     }
 
+    /// Tries to return the size of the bytecode of an entry
+    /// or gets it from a function if the bytecode is unknown.
+    ///
+    /// This function is used as an optimization:
+    /// if the value can be deduced unambiguously from the `LedgerChanges`,
+    /// no need to dig further (for example in the `FinalLedger`).
+    ///
+    /// # Arguments
+    /// * `addr`: address for which to get the bytecode size
+    /// * `f`: fallback function with no arguments and returning `Option<u64>`
+    ///
+    /// # Returns
+    /// * Some(size) if a bytecode is present
+    /// * None if the entry is absent
+    /// * f() if the bytecode is unknown
+    pub fn get_bytecode_size_or_else<F: FnOnce() -> Option<u64>>(
+        &self,
+        addr: &Address,
+        f: F,
+    ) -> Option<u64> {
+        match self.0.get(addr) {
+            Some(SetUpdateOrDelete::Set(v)) => Some(v.bytecode.len() as u64),
+            Some(SetUpdateOrDelete::Update(LedgerEntryUpdate { bytecode, .. })) => match bytecode {
+                SetOrKeep::Set(v) => Some(v.len() as u64),
+                SetOrKeep::Keep => f(),
+            },
+            Some(SetUpdateOrDelete::Delete) => None,
+            None => f(),
+        }
+    }
+
     /// Tries to return whether an entry exists
     /// or gets the information from a function if the entry's status is unknown.
     ///
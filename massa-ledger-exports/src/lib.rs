@@ -10,10 +10,12 @@ mod error;
 mod key;
 mod ledger_changes;
 mod ledger_entry;
+mod proof;
+mod scrub;
 mod types;
 
 pub use config::LedgerConfig;
-pub use controller::LedgerController;
+pub use controller::{LedgerController, LedgerSnapshotWriter};
 pub use error::LedgerError;
 pub use key::{
     get_address_from_key, KeyDeserializer, KeySerializer, BALANCE_IDENT, BYTECODE_IDENT,
@@ -25,6 +27,11 @@ pub use ledger_changes::{
     LedgerEntryUpdateDeserializer, LedgerEntryUpdateSerializer,
 };
 pub use ledger_entry::{LedgerEntry, LedgerEntryDeserializer, LedgerEntrySerializer};
+pub use proof::{
+    get_bucket_hash, get_ledger_bucket, get_ledger_entry_hash, get_node_hash, LedgerEntryProof,
+    EMPTY_NODE_HASH, LEDGER_PROOF_TREE_DEPTH,
+};
+pub use scrub::LedgerScrubReport;
 pub use types::{Applicable, SetOrDelete, SetOrKeep, SetUpdateOrDelete};
 
 #[cfg(feature = "testing")]
@@ -0,0 +1,132 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Merkle proofs of inclusion of ledger entries.
+//!
+//! Alongside its hash, the disk ledger maintains a Merkle tree of its entries.
+//! Every ledger key falls in one of the `2^LEDGER_PROOF_TREE_DEPTH` buckets of the tree, given by the hash of the key.
+//! The leaf of a bucket is the hash of the sorted hashes of its entries,
+//! and every node above is the hash of its two children. Empty subtrees hash to zero.
+
+use crate::{get_address_from_key, LedgerError};
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use massa_models::{address::Address, slot::Slot};
+use massa_serialization::{Serializer, U64VarIntSerializer};
+use serde::{Deserialize, Serialize};
+
+/// depth of the Merkle tree of the ledger
+pub const LEDGER_PROOF_TREE_DEPTH: u8 = 16;
+
+/// hash of an empty subtree
+pub const EMPTY_NODE_HASH: [u8; HASH_SIZE_BYTES] = [0; HASH_SIZE_BYTES];
+
+/// Returns the bucket of the Merkle tree a ledger key falls in
+pub fn get_ledger_bucket(key: &[u8]) -> u32 {
+    let key_hash = Hash::compute_from(key);
+    let bytes = key_hash.to_bytes();
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+        >> (u32::BITS - LEDGER_PROOF_TREE_DEPTH as u32)
+}
+
+/// Returns the hash of a ledger entry, as used by both the ledger hash and its Merkle tree
+pub fn get_ledger_entry_hash(key: &[u8], value: &[u8]) -> Hash {
+    let mut len_bytes = Vec::new();
+    U64VarIntSerializer::new()
+        .serialize(&(key.len() as u64), &mut len_bytes)
+        .expect("critical: key length serialization failed");
+    Hash::compute_from(&[&len_bytes, key, value].concat())
+}
+
+/// Returns the leaf hash of a bucket from the hashes of its entries, in any order
+pub fn get_bucket_hash(entry_hashes: &[Hash]) -> Hash {
+    if entry_hashes.is_empty() {
+        return Hash::from_bytes(&EMPTY_NODE_HASH);
+    }
+    let mut sorted_hashes: Vec<&[u8; HASH_SIZE_BYTES]> =
+        entry_hashes.iter().map(|hash| hash.to_bytes()).collect();
+    sorted_hashes.sort_unstable();
+    Hash::compute_from(&sorted_hashes.concat())
+}
+
+/// Returns the hash of a node of the tree from the hashes of its children
+pub fn get_node_hash(left: &Hash, right: &Hash) -> Hash {
+    if left.to_bytes() == &EMPTY_NODE_HASH && right.to_bytes() == &EMPTY_NODE_HASH {
+        return Hash::from_bytes(&EMPTY_NODE_HASH);
+    }
+    Hash::compute_from(&[left.to_bytes(), right.to_bytes()].concat())
+}
+
+/// Proof that an entry is part of the final ledger
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LedgerEntryProof {
+    /// slot of the final ledger the proof was made at
+    pub slot: Slot,
+    /// ledger key of the entry
+    pub key: Vec<u8>,
+    /// value of the entry
+    pub value: Vec<u8>,
+    /// hashes of the entries of the bucket of the key, the proven one included
+    pub bucket_entry_hashes: Vec<Hash>,
+    /// hashes of the siblings of the nodes on the path from the bucket to the root, bottom-up
+    pub siblings: Vec<Hash>,
+}
+
+impl LedgerEntryProof {
+    /// Address the proven entry belongs to
+    pub fn address(&self) -> Option<Address> {
+        get_address_from_key(&self.key)
+    }
+
+    /// Computes the root of the ledger Merkle tree the proof leads to
+    pub fn compute_root(&self) -> Result<Hash, LedgerError> {
+        let entry_hash = get_ledger_entry_hash(&self.key, &self.value);
+        if !self.bucket_entry_hashes.contains(&entry_hash) {
+            return Err(LedgerError::ContainerInconsistency(
+                "the proven entry is not part of its bucket".to_string(),
+            ));
+        }
+        if self.siblings.len() != LEDGER_PROOF_TREE_DEPTH as usize {
+            return Err(LedgerError::ContainerInconsistency(format!(
+                "a ledger proof must have {} siblings, got {}",
+                LEDGER_PROOF_TREE_DEPTH,
+                self.siblings.len()
+            )));
+        }
+        let mut index = get_ledger_bucket(&self.key);
+        let mut node = get_bucket_hash(&self.bucket_entry_hashes);
+        for sibling in &self.siblings {
+            node = if index & 1 == 0 {
+                get_node_hash(&node, sibling)
+            } else {
+                get_node_hash(sibling, &node)
+            };
+            index >>= 1;
+        }
+        Ok(node)
+    }
+
+    /// Checks that the proof shows the entry is part of the ledger with the given Merkle root.
+    ///
+    /// The ledger root is not committed in the blocks nor checked by consensus:
+    /// nodes only compare it with the root of their bootstrap server.
+    /// A valid proof thus only shows that the entry is part of the ledger whose root the serving node reports,
+    /// so the root has to be obtained from a node the verifier trusts, or checked against several nodes.
+    pub fn verify(&self, root: &Hash) -> bool {
+        matches!(self.compute_root(), Ok(computed_root) if computed_root == *root)
+    }
+}
+
+impl std::fmt::Display for LedgerEntryProof {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Slot: {}", self.slot)?;
+        if let Some(address) = self.address() {
+            writeln!(f, "Address: {}", address)?;
+        }
+        writeln!(f, "Key: {:?}", self.key)?;
+        writeln!(f, "Value: {:?}", self.value)?;
+        writeln!(f, "Bucket entries: {}", self.bucket_entry_hashes.len())?;
+        match self.compute_root() {
+            Ok(root) => writeln!(f, "Ledger root: {}", root),
+            Err(err) => writeln!(f, "Invalid proof: {}", err),
+        }
+    }
+}
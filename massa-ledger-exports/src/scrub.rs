@@ -0,0 +1,16 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Incremental integrity checks of the disk ledger.
+
+use massa_models::streaming_step::StreamingStep;
+
+/// Outcome of the integrity check of a range of the ledger
+#[derive(Debug, Clone)]
+pub struct LedgerScrubReport {
+    /// number of ledger entries checked
+    pub checked_entries: usize,
+    /// description of every inconsistency found in the range
+    pub anomalies: Vec<String>,
+    /// cursor of the next range to check, `Finished` once the end of the ledger is reached
+    pub cursor: StreamingStep<Vec<u8>>,
+}
@@ -5,19 +5,23 @@
 use crate::ledger_db::{LedgerDB, LedgerSubEntry};
 use massa_hash::Hash;
 use massa_ledger_exports::{
-    LedgerChanges, LedgerConfig, LedgerController, LedgerEntry, LedgerError,
+    LedgerChanges, LedgerConfig, LedgerController, LedgerEntry, LedgerEntryProof, LedgerError,
+    LedgerScrubReport, LedgerSnapshotWriter,
 };
 use massa_models::{
     address::Address,
     amount::{Amount, AmountDeserializer},
+    api::ContractInfo,
     error::ModelsError,
     slot::Slot,
+    stats::LedgerIntegrityReport,
     streaming_step::StreamingStep,
 };
 use massa_serialization::{DeserializeError, Deserializer};
 use nom::AsBytes;
 use std::collections::{BTreeSet, HashMap};
 use std::ops::Bound::Included;
+use std::path::Path;
 
 /// Represents a final ledger associating addresses to their balances, bytecode and data.
 /// The final ledger is part of the final state which is attached to a final slot, can be bootstrapped and allows others to bootstrap.
@@ -111,6 +115,15 @@ impl LedgerController for FinalLedger {
             .get_sub_entry(addr, LedgerSubEntry::Bytecode)
     }
 
+    /// Gets the size of the bytecode of a ledger entry, without copying it
+    ///
+    /// # Returns
+    /// The size of the bytecode in bytes, or None if the ledger entry was not found
+    fn get_bytecode_size(&self, addr: &Address) -> Option<u64> {
+        self.sorted_ledger
+            .get_sub_entry_size(addr, LedgerSubEntry::Bytecode)
+    }
+
     /// Checks if a ledger entry exists
     ///
     /// # Returns
@@ -156,11 +169,81 @@ impl LedgerController for FinalLedger {
         self.sorted_ledger.get_datastore_keys(addr)
     }
 
+    /// Get a page of the datastore keys of a given address starting with a given prefix.
+    ///
+    /// # Returns
+    /// The keys of the page and the cursor of the next one, `Finished` if there are no more keys
+    fn get_datastore_keys_page(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: StreamingStep<Vec<u8>>,
+        max_count: usize,
+    ) -> (BTreeSet<Vec<u8>>, StreamingStep<Vec<u8>>) {
+        self.sorted_ledger
+            .get_datastore_keys_page(addr, prefix, cursor, max_count)
+    }
+
+    /// Get a page of the addresses holding a non-empty bytecode.
+    ///
+    /// # Returns
+    /// The contracts of the page sorted by address and the cursor of the next one, `Finished` if there are no more contracts
+    fn get_contracts_page(
+        &self,
+        cursor: StreamingStep<Address>,
+        max_count: usize,
+    ) -> (Vec<ContractInfo>, StreamingStep<Address>) {
+        self.sorted_ledger.get_contracts_page(cursor, max_count)
+    }
+
+    /// Get the storage size of every address holding a bytecode or datastore entries.
+    ///
+    /// # Returns
+    /// A `BTreeMap` with the address as key and the size in bytes as value
+    fn get_storage_sizes(&self) -> std::collections::BTreeMap<Address, u64> {
+        self.sorted_ledger.get_storage_sizes()
+    }
+
     /// Get the current disk ledger hash
     fn get_ledger_hash(&self) -> Hash {
         self.sorted_ledger.get_ledger_hash()
     }
 
+    /// Get the root of the Merkle tree of the disk ledger
+    fn get_ledger_root(&self) -> Hash {
+        self.sorted_ledger.get_ledger_root()
+    }
+
+    /// Get a proof that an entry is part of the disk ledger.
+    ///
+    /// # Returns
+    /// The proof of the datastore entry, or of the balance if `datastore_key` is `None`. `None` if the entry is absent
+    fn get_entry_proof(
+        &self,
+        addr: &Address,
+        datastore_key: Option<&[u8]>,
+    ) -> Option<LedgerEntryProof> {
+        let ty = match datastore_key {
+            Some(datastore_key) => LedgerSubEntry::Datastore(datastore_key.to_vec()),
+            None => LedgerSubEntry::Balance,
+        };
+        self.sorted_ledger.get_entry_proof(addr, ty)
+    }
+
+    /// Check that a range of the disk ledger is consistent with its Merkle tree
+    fn scrub_ledger_range(
+        &self,
+        cursor: StreamingStep<Vec<u8>>,
+        max_count: usize,
+    ) -> LedgerScrubReport {
+        self.sorted_ledger.scrub_range(cursor, max_count)
+    }
+
+    /// Check the whole disk ledger against its hash and Merkle tree, optionally rebuilding them
+    fn verify_ledger_integrity(&mut self, repair: bool) -> LedgerIntegrityReport {
+        self.sorted_ledger.verify_integrity(repair)
+    }
+
     /// Get a part of the disk ledger.
     ///
     /// Solely used by the bootstrap.
@@ -184,6 +267,32 @@ impl LedgerController for FinalLedger {
         self.sorted_ledger.set_ledger_part(data.as_bytes())
     }
 
+    /// Delete every entry of the ledger along with its metadata, before bootstrapping it again from scratch
+    fn reset(&mut self) {
+        self.sorted_ledger.clear();
+    }
+
+    /// Write the pending changes of the disk ledger to disk, used before stopping the node
+    fn flush(&self) -> Result<(), LedgerError> {
+        self.sorted_ledger.flush()
+    }
+
+    /// Take a consistent snapshot of the disk ledger and its metadata, to be exported to a file
+    ///
+    /// # Returns
+    /// The writer exporting the snapshot to `path`
+    fn export_snapshot(&self, path: &Path) -> Result<LedgerSnapshotWriter, LedgerError> {
+        self.sorted_ledger.export_snapshot(path)
+    }
+
+    /// Replace the disk ledger by a snapshot file created with `export_snapshot`
+    ///
+    /// # Returns
+    /// The slot of the imported ledger
+    fn import_snapshot(&mut self, path: &Path) -> Result<Slot, LedgerError> {
+        self.sorted_ledger.import_snapshot(path)
+    }
+
     /// Get every address and their corresponding balance.
     ///
     /// IMPORTANT: This should only be used for debug and test purposes.
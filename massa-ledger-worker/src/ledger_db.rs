@@ -5,22 +5,26 @@
 use massa_hash::{Hash, HASH_SIZE_BYTES};
 use massa_ledger_exports::*;
 use massa_models::{
-    address::{Address, ADDRESS_SIZE_BYTES},
+    address::{Address, AddressDeserializer, ADDRESS_SIZE_BYTES},
     amount::AmountSerializer,
+    api::ContractInfo,
     error::ModelsError,
     serialization::{VecU8Deserializer, VecU8Serializer},
-    slot::{Slot, SlotSerializer},
+    slot::{Slot, SlotDeserializer, SlotSerializer},
+    stats::LedgerIntegrityReport,
     streaming_step::StreamingStep,
 };
-use massa_serialization::{Deserializer, Serializer, U64VarIntSerializer};
+use massa_serialization::{DeserializeError, Deserializer, Serializer, U64VarIntSerializer};
 use nom::multi::many0;
 use nom::sequence::tuple;
 use rocksdb::{
-    ColumnFamily, ColumnFamilyDescriptor, Direction, IteratorMode, Options, ReadOptions,
-    WriteBatch, DB,
+    checkpoint::Checkpoint, ColumnFamily, ColumnFamilyDescriptor, Direction, IteratorMode, Options,
+    ReadOptions, WriteBatch, DB,
 };
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 use std::ops::Bound;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::{collections::BTreeMap, fmt::Debug};
 use std::{
@@ -33,14 +37,21 @@ use massa_models::amount::{Amount, AmountDeserializer};
 
 const LEDGER_CF: &str = "ledger";
 const METADATA_CF: &str = "metadata";
+const MERKLE_CF: &str = "merkle";
+const STORAGE_CF: &str = "storage";
 const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
 const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
 const CF_ERROR: &str = "critical: rocksdb column family operation failed";
 const LEDGER_HASH_ERROR: &str = "critical: saved ledger hash is corrupted";
 const KEY_LEN_SER_ERROR: &str = "critical: key length serialization failed";
+const KEY_DESER_ERROR: &str = "critical: ledger key deserialization failed";
+const STORAGE_SIZE_ERROR: &str = "critical: storage size index is corrupted";
 const SLOT_KEY: &[u8; 1] = b"s";
 const LEDGER_HASH_KEY: &[u8; 1] = b"h";
 const LEDGER_HASH_INITIAL_BYTES: &[u8; 32] = &[0; HASH_SIZE_BYTES];
+const SNAPSHOT_HEADER: &[u8] = b"massa-ledger-snapshot-v1";
+const MERKLE_ENTRY_PREFIX: u8 = b'e';
+const MERKLE_NODE_PREFIX: u8 = b'n';
 
 /// Ledger sub entry enum
 pub enum LedgerSubEntry {
@@ -90,12 +101,139 @@ fn end_prefix(prefix: &[u8]) -> Option<Vec<u8>> {
     }
 }
 
+/// Deserialize the slot metadata of the disk ledger
+fn deserialize_slot(slot_bytes: &[u8], thread_count: u8) -> Result<Slot, LedgerError> {
+    SlotDeserializer::new(
+        (Bound::Included(0), Bound::Included(u64::MAX)),
+        (Bound::Included(0), Bound::Excluded(thread_count)),
+    )
+    .deserialize::<DeserializeError>(slot_bytes)
+    .map(|(_, slot)| slot)
+    .map_err(|_| LedgerError::ContainerInconsistency("invalid ledger slot".to_string()))
+}
+
+/// Write a ledger snapshot file from a `RocksDB` checkpoint of the disk ledger
+///
+/// # Returns
+/// The slot of the exported ledger
+fn write_snapshot(
+    checkpoint_path: &Path,
+    path: &Path,
+    thread_count: u8,
+) -> Result<Slot, LedgerError> {
+    let io_error = |err: std::io::Error| {
+        LedgerError::FileError(format!(
+            "error writing ledger snapshot {}: {}",
+            path.display(),
+            err
+        ))
+    };
+    let db = DB::open_cf_for_read_only(
+        &Options::default(),
+        checkpoint_path,
+        [LEDGER_CF, METADATA_CF],
+        false,
+    )
+    .map_err(|err| {
+        LedgerError::FileError(format!(
+            "error opening ledger checkpoint {}: {}",
+            checkpoint_path.display(),
+            err
+        ))
+    })?;
+    let ledger_handle = db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+    let metadata_handle = db.cf_handle(METADATA_CF).expect(CF_ERROR);
+
+    // read the metadata
+    let slot_bytes = db
+        .get_cf(metadata_handle, SLOT_KEY)
+        .expect(CRUD_ERROR)
+        .ok_or_else(|| LedgerError::MissingEntry("disk ledger has no slot".to_string()))?;
+    let slot = deserialize_slot(&slot_bytes, thread_count)?;
+    let ledger_hash_bytes = db
+        .get_cf(metadata_handle, LEDGER_HASH_KEY)
+        .expect(CRUD_ERROR)
+        .unwrap_or_else(|| LEDGER_HASH_INITIAL_BYTES.to_vec());
+
+    // write the header, the metadata and then every key/value pair
+    let mut writer = BufWriter::new(File::create(path).map_err(io_error)?);
+    writer.write_all(SNAPSHOT_HEADER).map_err(io_error)?;
+    write_snapshot_field(&mut writer, &slot_bytes).map_err(io_error)?;
+    write_snapshot_field(&mut writer, &ledger_hash_bytes).map_err(io_error)?;
+    for (key, value) in db.iterator_cf(ledger_handle, IteratorMode::Start).flatten() {
+        write_snapshot_field(&mut writer, &key).map_err(io_error)?;
+        write_snapshot_field(&mut writer, &value).map_err(io_error)?;
+    }
+    writer.flush().map_err(io_error)?;
+    Ok(slot)
+}
+
+/// Write a length-prefixed field to a ledger snapshot
+fn write_snapshot_field(writer: &mut impl Write, field: &[u8]) -> std::io::Result<()> {
+    writer.write_all(&(field.len() as u64).to_le_bytes())?;
+    writer.write_all(field)
+}
+
+/// Read a length-prefixed field from a ledger snapshot.
+/// Returns `None` if the end of the snapshot was reached.
+fn read_snapshot_field(reader: &mut impl BufRead) -> std::io::Result<Option<Vec<u8>>> {
+    if reader.fill_buf()?.is_empty() {
+        return Ok(None);
+    }
+    let mut len_bytes = [0u8; 8];
+    reader.read_exact(&mut len_bytes)?;
+    let len = u64::from_le_bytes(len_bytes);
+    // read through `take` so that a corrupted length does not trigger a huge allocation
+    let mut field = Vec::new();
+    reader.take(len).read_to_end(&mut field)?;
+    if field.len() as u64 != len {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(Some(field))
+}
+
 #[test]
 fn test_end_prefix() {
     assert_eq!(end_prefix(&[5, 6, 7]), Some(vec![5, 6, 8]));
     assert_eq!(end_prefix(&[5, 6, 255]), Some(vec![5, 7]));
 }
 
+/// Describe a key of the disk ledger in the anomalies found by the integrity checks
+fn describe_ledger_key(key: &[u8]) -> String {
+    match get_address_from_key(key) {
+        Some(addr) => format!("ledger entry of address {}", addr),
+        None => format!("ledger key {:?}", key),
+    }
+}
+
+/// Split a ledger key into its address and the length of the key counted in the storage size of
+/// that address, `None` for the keys that are not part of the storage (balances)
+fn get_storage_key(key: &[u8]) -> Option<(Address, u64)> {
+    let (rest, address) = AddressDeserializer::new()
+        .deserialize::<DeserializeError>(key)
+        .expect(KEY_DESER_ERROR);
+    match rest.first() {
+        Some(&BYTECODE_IDENT) => Some((address, 0)),
+        Some(&DATASTORE_IDENT) => Some((address, (rest.len() - 1) as u64)),
+        _ => None,
+    }
+}
+
+/// Key of the Merkle tree index entry of a ledger entry hash
+fn merkle_entry_key(bucket: u32, entry_hash: &Hash) -> Vec<u8> {
+    [
+        &[MERKLE_ENTRY_PREFIX][..],
+        &bucket.to_be_bytes(),
+        entry_hash.to_bytes(),
+    ]
+    .concat()
+}
+
+/// Key of a node of the Merkle tree, level 0 being the root
+fn merkle_node_key(level: u8, index: u32) -> Vec<u8> {
+    [&[MERKLE_NODE_PREFIX, level][..], &index.to_be_bytes()].concat()
+}
+
 /// Batch containing write operations to perform on disk and cache for the ledger hash computing
 pub struct LedgerBatch {
     // Rocksdb write batch
@@ -104,6 +242,14 @@ pub struct LedgerBatch {
     ledger_hash: Hash,
     // Added entry hashes in the current batch
     aeh_list: BTreeMap<Vec<u8>, Hash>,
+    // Entry hashes of the keys written in the current batch, `None` if deleted
+    merkle_entries: BTreeMap<Vec<u8>, Option<Hash>>,
+    // Entry hashes removed from (`false`) or added to (`true`) each Merkle tree bucket in the current batch, in order
+    bucket_changes: BTreeMap<u32, Vec<(Hash, bool)>>,
+    // Value lengths of the storage keys written in the current batch, `None` if deleted
+    value_lengths: BTreeMap<Vec<u8>, Option<u64>>,
+    // Storage sizes of the addresses whose bytecode or datastore changed in the current batch
+    storage_sizes: BTreeMap<Address, u64>,
 }
 
 impl LedgerBatch {
@@ -112,6 +258,10 @@ impl LedgerBatch {
             write_batch: WriteBatch::default(),
             ledger_hash,
             aeh_list: BTreeMap::new(),
+            merkle_entries: BTreeMap::new(),
+            bucket_changes: BTreeMap::new(),
+            value_lengths: BTreeMap::new(),
+            storage_sizes: BTreeMap::new(),
         }
     }
 }
@@ -131,17 +281,24 @@ impl LedgerDB {
         db_opts.create_if_missing(true);
         db_opts.create_missing_column_families(true);
 
+        // ledgers created before the storage size index need it to be built once
+        let build_storage_index = DB::list_cf(&db_opts, &path)
+            .map(|cfs| !cfs.iter().any(|cf| cf == STORAGE_CF))
+            .unwrap_or(false);
+
         let db = DB::open_cf_descriptors(
             &db_opts,
             path,
             vec![
                 ColumnFamilyDescriptor::new(LEDGER_CF, Options::default()),
                 ColumnFamilyDescriptor::new(METADATA_CF, Options::default()),
+                ColumnFamilyDescriptor::new(MERKLE_CF, Options::default()),
+                ColumnFamilyDescriptor::new(STORAGE_CF, Options::default()),
             ],
         )
         .expect(OPEN_ERROR);
 
-        LedgerDB {
+        let ledger_db = LedgerDB {
             db,
             thread_count,
             amount_serializer: AmountSerializer::new(),
@@ -154,7 +311,75 @@ impl LedgerDB {
                 Bound::Included(Amount::MIN),
                 Bound::Included(Amount::MAX),
             ),
+        };
+        if build_storage_index {
+            ledger_db.build_storage_index();
+        }
+        // ledgers created before the Merkle tree, or whose tree column family was lost, need it to be built once
+        if ledger_db.is_merkle_tree_missing() {
+            ledger_db.build_merkle_tree();
+        }
+        ledger_db
+    }
+
+    /// Internal function computing the storage size of every address from the ledger entries
+    /// and writing them to the storage size index
+    fn build_storage_index(&self) {
+        let ledger_handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let storage_handle = self.db.cf_handle(STORAGE_CF).expect(CF_ERROR);
+
+        let mut sizes: BTreeMap<Address, u64> = BTreeMap::new();
+        for (key, value) in self
+            .db
+            .iterator_cf(ledger_handle, IteratorMode::Start)
+            .flatten()
+        {
+            if let Some((address, key_len)) = get_storage_key(&key) {
+                *sizes.entry(address).or_default() += key_len + value.len() as u64;
+            }
+        }
+        let mut write_batch = WriteBatch::default();
+        for (address, size) in sizes {
+            if size > 0 {
+                write_batch.put_cf(storage_handle, address.to_bytes(), size.to_be_bytes());
+            }
         }
+        self.db.write(write_batch).expect(CRUD_ERROR);
+    }
+
+    /// Internal function telling whether the ledger has entries but no Merkle tree
+    fn is_merkle_tree_missing(&self) -> bool {
+        let ledger_handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let merkle_handle = self.db.cf_handle(MERKLE_CF).expect(CF_ERROR);
+        let is_empty = |handle: &ColumnFamily| {
+            self.db
+                .iterator_cf(handle, IteratorMode::Start)
+                .flatten()
+                .next()
+                .is_none()
+        };
+        !is_empty(ledger_handle) && is_empty(merkle_handle)
+    }
+
+    /// Internal function building the Merkle tree from the ledger entries,
+    /// the ledger hash being left untouched
+    fn build_merkle_tree(&self) {
+        let ledger_handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+
+        let mut batch = LedgerBatch::new(self.get_ledger_hash());
+        for (key, value) in self
+            .db
+            .iterator_cf(ledger_handle, IteratorMode::Start)
+            .flatten()
+        {
+            batch
+                .bucket_changes
+                .entry(get_ledger_bucket(&key))
+                .or_default()
+                .push((get_ledger_entry_hash(&key, &value), true));
+        }
+        self.update_merkle_tree(&mut batch);
+        self.db.write(batch.write_batch).expect(CRUD_ERROR);
     }
 
     /// Loads the initial disk ledger
@@ -212,6 +437,19 @@ impl LedgerDB {
 
     /// Apply the given operation batch to the disk ledger
     fn write_batch(&self, mut batch: LedgerBatch) {
+        self.update_merkle_tree(&mut batch);
+        let storage_handle = self.db.cf_handle(STORAGE_CF).expect(CF_ERROR);
+        for (address, size) in &batch.storage_sizes {
+            if *size == 0 {
+                batch
+                    .write_batch
+                    .delete_cf(storage_handle, address.to_bytes());
+            } else {
+                batch
+                    .write_batch
+                    .put_cf(storage_handle, address.to_bytes(), size.to_be_bytes());
+            }
+        }
         let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
         batch
             .write_batch
@@ -270,9 +508,442 @@ impl LedgerDB {
         let hash = Hash::compute_from(&[&len_bytes, key, value].concat());
         batch.ledger_hash ^= hash;
         batch.aeh_list.insert(key.to_vec(), hash);
+        self.set_merkle_entry(handle, batch, key, Some(hash));
+        self.update_storage_size(handle, batch, key, Some(value.len() as u64));
         batch.write_batch.put_cf(handle, key, value);
     }
 
+    /// Internal function updating the storage size of the address of a key
+    /// to the new length of its value, `None` if deleted
+    fn update_storage_size(
+        &self,
+        handle: &ColumnFamily,
+        batch: &mut LedgerBatch,
+        key: &[u8],
+        value_len: Option<u64>,
+    ) {
+        let (address, key_len) = match get_storage_key(key) {
+            Some(storage_key) => storage_key,
+            None => return,
+        };
+        let prev_len = match batch.value_lengths.get(key) {
+            Some(prev_len) => *prev_len,
+            None => self
+                .db
+                .get_cf(handle, key)
+                .expect(CRUD_ERROR)
+                .map(|prev_bytes| prev_bytes.len() as u64),
+        };
+        batch.value_lengths.insert(key.to_vec(), value_len);
+        let size = batch
+            .storage_sizes
+            .entry(address)
+            .or_insert_with(|| self.get_storage_size(&address));
+        *size = size
+            .saturating_sub(prev_len.map_or(0, |len| key_len + len))
+            .saturating_add(value_len.map_or(0, |len| key_len + len));
+    }
+
+    /// Internal function recording the new hash of a ledger entry, `None` if deleted, for the Merkle tree update
+    fn set_merkle_entry(
+        &self,
+        handle: &ColumnFamily,
+        batch: &mut LedgerBatch,
+        key: &[u8],
+        hash: Option<Hash>,
+    ) {
+        let prev_hash = match batch.merkle_entries.get(key) {
+            Some(prev_hash) => *prev_hash,
+            None => self
+                .db
+                .get_cf(handle, key)
+                .expect(CRUD_ERROR)
+                .map(|prev_bytes| get_ledger_entry_hash(key, &prev_bytes)),
+        };
+        if prev_hash == hash {
+            return;
+        }
+        let changes = batch
+            .bucket_changes
+            .entry(get_ledger_bucket(key))
+            .or_default();
+        if let Some(prev_hash) = prev_hash {
+            changes.push((prev_hash, false));
+        }
+        if let Some(hash) = hash {
+            changes.push((hash, true));
+        }
+        batch.merkle_entries.insert(key.to_vec(), hash);
+    }
+
+    /// Internal function applying the entry changes of a batch to the Merkle tree:
+    /// the leaves of the changed buckets are recomputed, then their ancestors up to the root.
+    ///
+    /// Every node of the tree is stored in `MERKLE_CF`, so the siblings are read instead of recomputed:
+    /// each changed bucket costs a read of its own entry hashes, then `LEDGER_PROOF_TREE_DEPTH` sibling reads
+    /// and node writes. No other part of the tree is read or written.
+    fn update_merkle_tree(&self, batch: &mut LedgerBatch) {
+        let handle = self.db.cf_handle(MERKLE_CF).expect(CF_ERROR);
+
+        // leaves
+        let mut nodes: BTreeMap<u32, Hash> = BTreeMap::new();
+        for (bucket, changes) in std::mem::take(&mut batch.bucket_changes) {
+            let mut entry_hashes: BTreeSet<[u8; HASH_SIZE_BYTES]> = self
+                .get_bucket_entry_hashes(bucket)
+                .iter()
+                .map(|hash| hash.into_bytes())
+                .collect();
+            for (hash, added) in changes {
+                if added {
+                    entry_hashes.insert(hash.into_bytes());
+                    batch
+                        .write_batch
+                        .put_cf(handle, merkle_entry_key(bucket, &hash), []);
+                } else {
+                    entry_hashes.remove(hash.to_bytes());
+                    batch
+                        .write_batch
+                        .delete_cf(handle, merkle_entry_key(bucket, &hash));
+                }
+            }
+            let entry_hashes: Vec<Hash> = entry_hashes.iter().map(Hash::from_bytes).collect();
+            nodes.insert(bucket, get_bucket_hash(&entry_hashes));
+        }
+
+        // nodes, bottom-up
+        for level in (0..=LEDGER_PROOF_TREE_DEPTH).rev() {
+            for (index, hash) in &nodes {
+                if hash.to_bytes() == &EMPTY_NODE_HASH {
+                    batch
+                        .write_batch
+                        .delete_cf(handle, merkle_node_key(level, *index));
+                } else {
+                    batch.write_batch.put_cf(
+                        handle,
+                        merkle_node_key(level, *index),
+                        hash.to_bytes(),
+                    );
+                }
+            }
+            if level == 0 {
+                break;
+            }
+            let mut parents = BTreeMap::new();
+            for index in nodes.keys() {
+                let parent = index >> 1;
+                if parents.contains_key(&parent) {
+                    continue;
+                }
+                let child = |index: u32| {
+                    nodes
+                        .get(&index)
+                        .copied()
+                        .unwrap_or_else(|| self.get_merkle_node(level, index))
+                };
+                parents.insert(
+                    parent,
+                    get_node_hash(&child(parent << 1), &child((parent << 1) | 1)),
+                );
+            }
+            nodes = parents;
+        }
+    }
+
+    /// Internal function reading the entry hashes of a Merkle tree bucket
+    fn get_bucket_entry_hashes(&self, bucket: u32) -> Vec<Hash> {
+        let handle = self.db.cf_handle(MERKLE_CF).expect(CF_ERROR);
+        let prefix = [&[MERKLE_ENTRY_PREFIX][..], &bucket.to_be_bytes()].concat();
+        let mut opt = ReadOptions::default();
+        if let Some(end_key) = end_prefix(&prefix) {
+            opt.set_iterate_upper_bound(end_key);
+        }
+        self.db
+            .iterator_cf_opt(handle, opt, IteratorMode::From(&prefix, Direction::Forward))
+            .flatten()
+            .map(|(key, _)| {
+                Hash::from_bytes(
+                    key[prefix.len()..]
+                        .try_into()
+                        .expect("critical: saved Merkle tree entry is corrupted"),
+                )
+            })
+            .collect()
+    }
+
+    /// Internal function reading a node of the Merkle tree, level 0 being the root
+    fn get_merkle_node(&self, level: u8, index: u32) -> Hash {
+        let handle = self.db.cf_handle(MERKLE_CF).expect(CF_ERROR);
+        match self
+            .db
+            .get_cf(handle, merkle_node_key(level, index))
+            .expect(CRUD_ERROR)
+        {
+            Some(bytes) => Hash::from_bytes(
+                &bytes
+                    .try_into()
+                    .expect("critical: saved Merkle tree node is corrupted"),
+            ),
+            None => Hash::from_bytes(&EMPTY_NODE_HASH),
+        }
+    }
+
+    /// Get the root of the Merkle tree of the disk ledger, read from its stored node
+    pub fn get_ledger_root(&self) -> Hash {
+        self.get_merkle_node(0, 0)
+    }
+
+    /// Get a proof that a sub-entry is part of the disk ledger.
+    ///
+    /// # Arguments
+    /// * `addr`: associated address
+    /// * `ty`: type of the sub-entry to prove
+    ///
+    /// # Returns
+    /// The proof, `None` if the sub-entry is absent
+    pub fn get_entry_proof(&self, addr: &Address, ty: LedgerSubEntry) -> Option<LedgerEntryProof> {
+        let key = match ty {
+            LedgerSubEntry::Balance => balance_key!(addr),
+            LedgerSubEntry::Bytecode => bytecode_key!(addr),
+            LedgerSubEntry::Datastore(hash) => data_key!(addr, hash),
+        };
+        // the ledger is only written under the write lock of the final state: the reads are consistent
+        let ledger_handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let metadata_handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        let value = self.db.get_cf(ledger_handle, &key).expect(CRUD_ERROR)?;
+        let slot_bytes = self
+            .db
+            .get_cf(metadata_handle, SLOT_KEY)
+            .expect(CRUD_ERROR)?;
+        let slot = deserialize_slot(&slot_bytes, self.thread_count).ok()?;
+
+        let bucket = get_ledger_bucket(&key);
+        let siblings = (1..=LEDGER_PROOF_TREE_DEPTH)
+            .rev()
+            .map(|level| {
+                let index = bucket >> (LEDGER_PROOF_TREE_DEPTH - level);
+                self.get_merkle_node(level, index ^ 1)
+            })
+            .collect();
+        Some(LedgerEntryProof {
+            slot,
+            key,
+            value,
+            bucket_entry_hashes: self.get_bucket_entry_hashes(bucket),
+            siblings,
+        })
+    }
+
+    /// Check that a range of the disk ledger is consistent with its Merkle tree:
+    /// every entry of the range must be indexed in its bucket with the hash of its current value,
+    /// and the leaf of each bucket of the range must match the entry hashes it indexes.
+    ///
+    /// All the reads are made on a single `RocksDB` snapshot so that concurrent writes do not raise false anomalies.
+    ///
+    /// # Arguments
+    /// * `cursor`: `Started` to check the first entries, `Ongoing(last_key)` to check the entries following `last_key`
+    /// * `max_count`: maximum number of entries to check
+    pub fn scrub_range(
+        &self,
+        cursor: StreamingStep<Vec<u8>>,
+        max_count: usize,
+    ) -> LedgerScrubReport {
+        let ledger_handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let merkle_handle = self.db.cf_handle(MERKLE_CF).expect(CF_ERROR);
+        let snapshot = self.db.snapshot();
+        let mut report = LedgerScrubReport {
+            checked_entries: 0,
+            anomalies: Vec::new(),
+            cursor: StreamingStep::Finished,
+        };
+
+        // start after the last checked key, if any
+        let db_iterator = match &cursor {
+            StreamingStep::Started => snapshot.iterator_cf(ledger_handle, IteratorMode::Start),
+            StreamingStep::Ongoing(last_key) => {
+                let mut iter = snapshot.iterator_cf(
+                    ledger_handle,
+                    IteratorMode::From(last_key, Direction::Forward),
+                );
+                iter.next();
+                iter
+            }
+            StreamingStep::Finished => return report,
+        };
+        // every entry must be indexed in its bucket with its current hash
+        let mut buckets = BTreeSet::new();
+        for (key, value) in db_iterator.take(max_count).flatten() {
+            let bucket = get_ledger_bucket(&key);
+            let entry_hash = get_ledger_entry_hash(&key, &value);
+            if snapshot
+                .get_cf(merkle_handle, merkle_entry_key(bucket, &entry_hash))
+                .expect(CRUD_ERROR)
+                .is_none()
+            {
+                report.anomalies.push(format!(
+                    "{} does not match the hash indexed in Merkle bucket {}",
+                    describe_ledger_key(&key),
+                    bucket
+                ));
+            }
+            buckets.insert(bucket);
+            report.checked_entries += 1;
+            report.cursor = StreamingStep::Ongoing(key.to_vec());
+        }
+        if report.checked_entries < max_count {
+            report.cursor = StreamingStep::Finished;
+        }
+
+        // the leaves of the buckets must match the entry hashes they index
+        for bucket in buckets {
+            let prefix = [&[MERKLE_ENTRY_PREFIX][..], &bucket.to_be_bytes()].concat();
+            let mut opt = ReadOptions::default();
+            if let Some(end_key) = end_prefix(&prefix) {
+                opt.set_iterate_upper_bound(end_key);
+            }
+            let entry_hashes: Vec<Hash> = snapshot
+                .iterator_cf_opt(
+                    merkle_handle,
+                    opt,
+                    IteratorMode::From(&prefix, Direction::Forward),
+                )
+                .flatten()
+                .filter_map(|(key, _)| {
+                    key[prefix.len()..]
+                        .try_into()
+                        .ok()
+                        .map(|bytes: &[u8; HASH_SIZE_BYTES]| Hash::from_bytes(bytes))
+                })
+                .collect();
+            let leaf = snapshot
+                .get_cf(
+                    merkle_handle,
+                    merkle_node_key(LEDGER_PROOF_TREE_DEPTH, bucket),
+                )
+                .expect(CRUD_ERROR)
+                .unwrap_or_else(|| EMPTY_NODE_HASH.to_vec());
+            if leaf[..] != get_bucket_hash(&entry_hashes).to_bytes()[..] {
+                report.anomalies.push(format!(
+                    "leaf of Merkle bucket {} does not match its {} indexed entries",
+                    bucket,
+                    entry_hashes.len()
+                ));
+            }
+        }
+        report
+    }
+
+    /// Check the whole disk ledger: the ledger hash is recomputed from all the entries and the ledger slot
+    /// and compared to the stored one, and every entry must be indexed in the Merkle tree with its current hash.
+    /// All the reads are made on a single `RocksDB` snapshot.
+    ///
+    /// # Arguments
+    /// * `repair`: if an inconsistency is found, rebuild the ledger hash and the Merkle tree from the entries
+    pub fn verify_integrity(&mut self, repair: bool) -> LedgerIntegrityReport {
+        let ledger_handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let metadata_handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        let merkle_handle = self.db.cf_handle(MERKLE_CF).expect(CF_ERROR);
+        let mut entry_hashes = Vec::new();
+        let mut report = {
+            let snapshot = self.db.snapshot();
+
+            // a missing ledger hash is the initial one, see `get_ledger_hash`
+            let stored_hash = match snapshot
+                .get_cf(metadata_handle, LEDGER_HASH_KEY)
+                .expect(CRUD_ERROR)
+            {
+                Some(bytes) => bytes
+                    .try_into()
+                    .ok()
+                    .map(|bytes: [u8; HASH_SIZE_BYTES]| Hash::from_bytes(&bytes)),
+                None => Some(Hash::from_bytes(LEDGER_HASH_INITIAL_BYTES)),
+            };
+            let mut computed_hash = Hash::from_bytes(LEDGER_HASH_INITIAL_BYTES);
+            if let Some(slot_bytes) = snapshot
+                .get_cf(metadata_handle, SLOT_KEY)
+                .expect(CRUD_ERROR)
+            {
+                computed_hash ^= Hash::compute_from(&slot_bytes);
+            }
+
+            // every entry must be indexed in its bucket with its current hash
+            let mut checked_entries: u64 = 0;
+            let mut indexed_entries: u64 = 0;
+            let mut mismatched_keys = Vec::new();
+            for (key, value) in snapshot
+                .iterator_cf(ledger_handle, IteratorMode::Start)
+                .flatten()
+            {
+                let bucket = get_ledger_bucket(&key);
+                let entry_hash = get_ledger_entry_hash(&key, &value);
+                computed_hash ^= entry_hash;
+                if snapshot
+                    .get_cf(merkle_handle, merkle_entry_key(bucket, &entry_hash))
+                    .expect(CRUD_ERROR)
+                    .is_some()
+                {
+                    indexed_entries += 1;
+                } else {
+                    mismatched_keys.push(describe_ledger_key(&key));
+                }
+                if repair {
+                    entry_hashes.push((bucket, entry_hash));
+                }
+                checked_entries += 1;
+            }
+
+            // the Merkle tree must not index more entries than the ledger holds
+            let mut opt = ReadOptions::default();
+            if let Some(end_key) = end_prefix(&[MERKLE_ENTRY_PREFIX]) {
+                opt.set_iterate_upper_bound(end_key);
+            }
+            let merkle_entry_count = snapshot
+                .iterator_cf_opt(
+                    merkle_handle,
+                    opt,
+                    IteratorMode::From(&[MERKLE_ENTRY_PREFIX], Direction::Forward),
+                )
+                .flatten()
+                .count() as u64;
+
+            LedgerIntegrityReport {
+                checked_entries,
+                stored_hash,
+                computed_hash,
+                mismatched_keys,
+                orphan_merkle_entries: merkle_entry_count.saturating_sub(indexed_entries),
+                repaired: false,
+            }
+        };
+
+        if repair && !report.is_consistent() {
+            self.rebuild_metadata(report.computed_hash, entry_hashes);
+            report.repaired = true;
+        }
+        report
+    }
+
+    /// Internal function rebuilding the Merkle tree from the hashes of all the ledger entries,
+    /// and storing the given ledger hash along the new tree
+    fn rebuild_metadata(&mut self, ledger_hash: Hash, entry_hashes: Vec<(u32, Hash)>) {
+        // forget the whole Merkle tree first, as its update reads the current buckets
+        let handle = self.db.cf_handle(MERKLE_CF).expect(CF_ERROR);
+        let mut write_batch = WriteBatch::default();
+        for (key, _) in self.db.iterator_cf(handle, IteratorMode::Start).flatten() {
+            write_batch.delete_cf(handle, key);
+        }
+        self.db.write(write_batch).expect(CRUD_ERROR);
+
+        let mut batch = LedgerBatch::new(ledger_hash);
+        for (bucket, entry_hash) in entry_hashes {
+            batch
+                .bucket_changes
+                .entry(bucket)
+                .or_default()
+                .push((entry_hash, true));
+        }
+        self.write_batch(batch);
+    }
+
     /// Add every sub-entry individually for a given entry.
     ///
     /// # Arguments
@@ -326,6 +997,29 @@ impl LedgerDB {
         }
     }
 
+    /// Get the size of the given sub-entry of a given address.
+    /// The value is read in place from the database and is not copied.
+    ///
+    /// # Arguments
+    /// * `addr`: associated address
+    /// * `ty`: type of the queried sub-entry
+    ///
+    /// # Returns
+    /// An Option of the size of the sub-entry value in bytes
+    pub fn get_sub_entry_size(&self, addr: &Address, ty: LedgerSubEntry) -> Option<u64> {
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+
+        let key = match ty {
+            LedgerSubEntry::Balance => balance_key!(addr),
+            LedgerSubEntry::Bytecode => bytecode_key!(addr),
+            LedgerSubEntry::Datastore(hash) => data_key!(addr, hash),
+        };
+        self.db
+            .get_pinned_cf(handle, key)
+            .expect(CRUD_ERROR)
+            .map(|value| value.len() as u64)
+    }
+
     /// Get every key of the datastore for a given address.
     ///
     /// # Returns
@@ -347,6 +1041,160 @@ impl LedgerDB {
             .collect()
     }
 
+    /// Get a page of the datastore keys of a given address starting with a given prefix.
+    ///
+    /// # Arguments
+    /// * `addr`: associated address
+    /// * `prefix`: prefix of the queried keys
+    /// * `cursor`: `Started` to get the first keys, `Ongoing(last_key)` to get the keys following `last_key`
+    /// * `max_count`: maximum number of keys to return
+    ///
+    /// # Returns
+    /// The keys of the page and the cursor of the next one, `Finished` if there are no more keys
+    pub fn get_datastore_keys_page(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: StreamingStep<Vec<u8>>,
+        max_count: usize,
+    ) -> (BTreeSet<Vec<u8>>, StreamingStep<Vec<u8>>) {
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let prefix_key = [&data_prefix!(addr)[..], prefix].concat();
+
+        // start at the prefix, or right after the cursor if it is further
+        let (start_key, last_key) = match cursor {
+            StreamingStep::Started => (prefix_key.clone(), None),
+            StreamingStep::Ongoing(last_key) => (
+                std::cmp::max(prefix_key.clone(), data_key!(addr, last_key)),
+                Some(last_key),
+            ),
+            StreamingStep::Finished => return (BTreeSet::new(), StreamingStep::Finished),
+        };
+        let mut opt = ReadOptions::default();
+        if let Some(end_key) = end_prefix(&prefix_key) {
+            opt.set_iterate_upper_bound(end_key);
+        }
+
+        let mut keys = BTreeSet::new();
+        for (key, _) in self
+            .db
+            .iterator_cf_opt(
+                handle,
+                opt,
+                IteratorMode::From(&start_key, Direction::Forward),
+            )
+            .flatten()
+        {
+            let datastore_key = key.split_at(ADDRESS_SIZE_BYTES + 1).1.to_vec();
+            if last_key.as_ref() == Some(&datastore_key) {
+                continue;
+            }
+            if keys.len() >= max_count {
+                // more keys remain: the next page starts after the last returned key
+                let next_cursor = match keys.iter().next_back() {
+                    Some(key) => StreamingStep::Ongoing(key.clone()),
+                    None => last_key.map_or(StreamingStep::Started, StreamingStep::Ongoing),
+                };
+                return (keys, next_cursor);
+            }
+            keys.insert(datastore_key);
+        }
+        (keys, StreamingStep::Finished)
+    }
+
+    /// Get a page of the addresses holding a non-empty bytecode.
+    ///
+    /// The ledger is walked address by address, seeking over datastore entries without reading them.
+    ///
+    /// # Arguments
+    /// * `cursor`: `Started` to get the first contracts, `Ongoing(last_address)` to get the contracts following `last_address`
+    /// * `max_count`: maximum number of contracts to return
+    ///
+    /// # Returns
+    /// The contracts of the page sorted by address and the cursor of the next one, `Finished` if there are no more contracts
+    pub fn get_contracts_page(
+        &self,
+        cursor: StreamingStep<Address>,
+        max_count: usize,
+    ) -> (Vec<ContractInfo>, StreamingStep<Address>) {
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let address_deserializer = AddressDeserializer::new();
+        let mut iter = self.db.raw_iterator_cf(handle);
+        match &cursor {
+            StreamingStep::Started => iter.seek_to_first(),
+            StreamingStep::Ongoing(last_address) => match end_prefix(last_address.to_bytes()) {
+                Some(next_key) => iter.seek(next_key),
+                None => return (Vec::new(), StreamingStep::Finished),
+            },
+            StreamingStep::Finished => return (Vec::new(), StreamingStep::Finished),
+        }
+
+        let mut contracts: Vec<ContractInfo> = Vec::new();
+        while let Some(key) = iter.key() {
+            let (_, address) = address_deserializer
+                .deserialize::<DeserializeError>(key)
+                .expect(KEY_DESER_ERROR);
+            if contracts.len() >= max_count {
+                // more addresses remain: the next page starts after the last returned contract
+                let next_cursor = match contracts.last() {
+                    Some(contract) => StreamingStep::Ongoing(contract.address),
+                    None => cursor,
+                };
+                return (contracts, next_cursor);
+            }
+            let bytecode_key = bytecode_key!(address);
+            iter.seek(&bytecode_key);
+            if iter.key() == Some(&bytecode_key[..]) {
+                if let Some(bytecode) = iter.value().filter(|bytecode| !bytecode.is_empty()) {
+                    contracts.push(ContractInfo {
+                        address,
+                        bytecode_size: bytecode.len() as u64,
+                        bytecode_hash: Hash::compute_from(bytecode),
+                    });
+                }
+            }
+            // skip the datastore of the address
+            match end_prefix(address.to_bytes()) {
+                Some(next_key) => iter.seek(next_key),
+                None => break,
+            }
+        }
+        (contracts, StreamingStep::Finished)
+    }
+
+    /// Get the storage size of an address, 0 if it holds no bytecode nor datastore entries.
+    ///
+    /// The size of an entry is the length of its bytecode
+    /// plus the length of every datastore key and value.
+    /// It is read from the storage size index maintained on every write.
+    pub fn get_storage_size(&self, addr: &Address) -> u64 {
+        let handle = self.db.cf_handle(STORAGE_CF).expect(CF_ERROR);
+        self.db
+            .get_cf(handle, addr.to_bytes())
+            .expect(CRUD_ERROR)
+            .map(|bytes| u64::from_be_bytes(bytes.try_into().expect(STORAGE_SIZE_ERROR)))
+            .unwrap_or_default()
+    }
+
+    /// Get the storage size of every address holding a bytecode or datastore entries,
+    /// as defined in `get_storage_size`.
+    ///
+    /// # Returns
+    /// A `BTreeMap` with the address as key and the size in bytes as value
+    pub fn get_storage_sizes(&self) -> BTreeMap<Address, u64> {
+        let handle = self.db.cf_handle(STORAGE_CF).expect(CF_ERROR);
+        self.db
+            .iterator_cf(handle, IteratorMode::Start)
+            .flatten()
+            .map(|(key, value)| {
+                (
+                    Address::from_bytes(&key[..].try_into().expect(STORAGE_SIZE_ERROR)),
+                    u64::from_be_bytes(value[..].try_into().expect(STORAGE_SIZE_ERROR)),
+                )
+            })
+            .collect()
+    }
+
     /// Internal function to update a key & value and perform the ledger hash XORs
     fn update_key_value(
         &self,
@@ -367,6 +1215,8 @@ impl LedgerDB {
         let hash = Hash::compute_from(&[&len_bytes, key, value].concat());
         batch.ledger_hash ^= hash;
         batch.aeh_list.insert(key.to_vec(), hash);
+        self.set_merkle_entry(handle, batch, key, Some(hash));
+        self.update_storage_size(handle, batch, key, Some(value.len() as u64));
         batch.write_batch.put_cf(handle, key, value);
     }
 
@@ -420,6 +1270,8 @@ impl LedgerDB {
                 .expect(KEY_LEN_SER_ERROR);
             batch.ledger_hash ^= Hash::compute_from(&[&len_bytes, key, &prev_bytes].concat());
         }
+        self.set_merkle_entry(handle, batch, key, None);
+        self.update_storage_size(handle, batch, key, None);
         batch.write_batch.delete_cf(handle, key);
     }
 
@@ -548,6 +1400,154 @@ impl LedgerDB {
         }
     }
 
+    /// Write the in-memory `RocksDB` tables of the disk ledger to disk,
+    /// so that the next start does not have to replay the write-ahead log
+    pub fn flush(&self) -> Result<(), LedgerError> {
+        self.db
+            .flush()
+            .map_err(|err| LedgerError::FileError(format!("error flushing the ledger: {}", err)))
+    }
+
+    /// Take a consistent snapshot of the disk ledger, to be exported to a file.
+    ///
+    /// The snapshot is a `RocksDB` checkpoint, which shares the files of the disk ledger and is
+    /// quick to create. The returned writer then reads every ledger key and value, along with
+    /// the slot and hash metadata, from the checkpoint so that it does not need the ledger.
+    ///
+    /// # Arguments
+    /// * `path`: path of the snapshot file to create
+    ///
+    /// # Returns
+    /// The writer exporting the snapshot to `path`
+    pub fn export_snapshot(&self, path: &Path) -> Result<LedgerSnapshotWriter, LedgerError> {
+        let checkpoint_path = path.with_extension("checkpoint");
+        let checkpoint_error = |err: String| {
+            LedgerError::FileError(format!(
+                "error creating ledger checkpoint {}: {}",
+                checkpoint_path.display(),
+                err
+            ))
+        };
+        if checkpoint_path.exists() {
+            std::fs::remove_dir_all(&checkpoint_path)
+                .map_err(|err| checkpoint_error(err.to_string()))?;
+        }
+        Checkpoint::new(&self.db)
+            .and_then(|checkpoint| checkpoint.create_checkpoint(&checkpoint_path))
+            .map_err(|err| checkpoint_error(err.to_string()))?;
+
+        let path = path.to_path_buf();
+        let thread_count = self.thread_count;
+        Ok(Box::new(move || {
+            let result = write_snapshot(&checkpoint_path, &path, thread_count);
+            let _ = std::fs::remove_dir_all(&checkpoint_path);
+            result
+        }))
+    }
+
+    /// Replace the content of the disk ledger by a snapshot created with `export_snapshot`.
+    ///
+    /// The ledger hash is recomputed while importing and checked against the one of the snapshot.
+    /// On error, the disk ledger is left empty.
+    ///
+    /// # Arguments
+    /// * `path`: path of the snapshot file to import
+    ///
+    /// # Returns
+    /// The slot of the imported ledger
+    pub fn import_snapshot(&mut self, path: &Path) -> Result<Slot, LedgerError> {
+        self.clear();
+        let result = self.import_snapshot_entries(path);
+        if result.is_err() {
+            self.clear();
+        }
+        result
+    }
+
+    /// Internal function writing the content of a snapshot to an empty disk ledger
+    fn import_snapshot_entries(&self, path: &Path) -> Result<Slot, LedgerError> {
+        let io_error = |err: std::io::Error| {
+            LedgerError::FileError(format!(
+                "error reading ledger snapshot {}: {}",
+                path.display(),
+                err
+            ))
+        };
+        let missing_field =
+            || LedgerError::FileError(format!("truncated ledger snapshot {}", path.display()));
+        let mut reader = BufReader::new(File::open(path).map_err(io_error)?);
+
+        // check the header and read the metadata
+        let mut header = vec![0u8; SNAPSHOT_HEADER.len()];
+        reader.read_exact(&mut header).map_err(io_error)?;
+        if header != SNAPSHOT_HEADER {
+            return Err(LedgerError::FileError(format!(
+                "{} is not a ledger snapshot",
+                path.display()
+            )));
+        }
+        let slot_bytes = read_snapshot_field(&mut reader)
+            .map_err(io_error)?
+            .ok_or_else(missing_field)?;
+        let slot = deserialize_slot(&slot_bytes, self.thread_count)?;
+        let ledger_hash_bytes = read_snapshot_field(&mut reader)
+            .map_err(io_error)?
+            .ok_or_else(missing_field)?;
+        let expected_ledger_hash = Hash::from_bytes(
+            &ledger_hash_bytes
+                .try_into()
+                .map_err(|_| LedgerError::FileError("invalid snapshot ledger hash".to_string()))?,
+        );
+
+        // write the entries by batches of the size of a bootstrap ledger part
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let key_deserializer = KeyDeserializer::new(self.max_datastore_key_length);
+        let mut batch = LedgerBatch::new(Hash::from_bytes(LEDGER_HASH_INITIAL_BYTES));
+        let mut batch_size: u64 = 0;
+        while let Some(key) = read_snapshot_field(&mut reader).map_err(io_error)? {
+            let value = read_snapshot_field(&mut reader)
+                .map_err(io_error)?
+                .ok_or_else(missing_field)?;
+            match key_deserializer.deserialize::<DeserializeError>(&key) {
+                Ok((rest, _)) if rest.is_empty() => {}
+                _ => {
+                    return Err(LedgerError::FileError(
+                        "invalid key in ledger snapshot".to_string(),
+                    ))
+                }
+            }
+            self.put_entry_value(handle, &mut batch, &key, &value);
+            batch_size = batch_size.saturating_add((key.len() + value.len()) as u64);
+            if batch_size >= self.ledger_part_size_message_bytes {
+                let ledger_hash = batch.ledger_hash;
+                self.write_batch(batch);
+                batch = LedgerBatch::new(ledger_hash);
+                batch_size = 0;
+            }
+        }
+        self.set_slot(slot, &mut batch);
+        if batch.ledger_hash != expected_ledger_hash {
+            return Err(LedgerError::ContainerInconsistency(format!(
+                "imported ledger hash {} does not match the snapshot ledger hash {}",
+                batch.ledger_hash, expected_ledger_hash
+            )));
+        }
+        self.write_batch(batch);
+        Ok(slot)
+    }
+
+    /// Delete every key of the disk ledger, metadata included
+    pub fn clear(&self) {
+        let mut write_batch = WriteBatch::default();
+        for cf in [LEDGER_CF, METADATA_CF, MERKLE_CF, STORAGE_CF] {
+            let handle = self.db.cf_handle(cf).expect(CF_ERROR);
+            for (key, _) in self.db.iterator_cf(handle, IteratorMode::Start).flatten() {
+                write_batch.delete_cf(handle, key);
+            }
+        }
+        self.db.write(write_batch).expect(CRUD_ERROR);
+    }
+
     /// Get every address and their corresponding balance.
     ///
     /// IMPORTANT: This should only be used for debug purposes.
@@ -558,9 +1558,6 @@ impl LedgerDB {
     pub fn get_every_address(
         &self,
     ) -> std::collections::BTreeMap<Address, massa_models::amount::Amount> {
-        use massa_models::address::AddressDeserializer;
-        use massa_serialization::DeserializeError;
-
         let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
 
         let ledger = self
@@ -621,12 +1618,19 @@ impl LedgerDB {
 #[cfg(test)]
 mod tests {
     use super::LedgerDB;
-    use crate::ledger_db::{LedgerBatch, LedgerSubEntry, LEDGER_HASH_INITIAL_BYTES};
+    use crate::ledger_db::{
+        LedgerBatch, LedgerSubEntry, CF_ERROR, LEDGER_CF, LEDGER_HASH_INITIAL_BYTES, MERKLE_CF,
+        MERKLE_NODE_PREFIX, STORAGE_CF,
+    };
     use massa_hash::Hash;
-    use massa_ledger_exports::{LedgerEntry, LedgerEntryUpdate, SetOrKeep};
+    use massa_ledger_exports::{
+        data_key, LedgerEntry, LedgerEntryUpdate, SetOrDelete, SetOrKeep, EMPTY_NODE_HASH,
+        LEDGER_PROOF_TREE_DEPTH,
+    };
     use massa_models::{
         address::Address,
         amount::{Amount, AmountDeserializer},
+        slot::Slot,
         streaming_step::StreamingStep,
     };
     use massa_serialization::{DeserializeError, Deserializer};
@@ -706,6 +1710,329 @@ mod tests {
         assert!(db.get_entire_datastore(&addr).is_empty());
     }
 
+    #[test]
+    fn test_ledger_storage_sizes() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (mut db, data) = init_test_ledger(addr);
+        let expected_size: usize = data.iter().map(|(k, v)| k.len() + v.len()).sum();
+        let sizes = db.get_storage_sizes();
+        assert_eq!(sizes.len(), 1);
+        assert_eq!(sizes.get(&addr), Some(&(expected_size as u64)));
+
+        // the index follows the bytecode and datastore updates, balances are not counted
+        let mut datastore = BTreeMap::new();
+        datastore.insert(b"1".to_vec(), SetOrDelete::Set(b"abcd".to_vec()));
+        datastore.insert(b"2".to_vec(), SetOrDelete::Delete);
+        datastore.insert(b"45".to_vec(), SetOrDelete::Set(b"e".to_vec()));
+        let entry_update = LedgerEntryUpdate {
+            balance: SetOrKeep::Set(Amount::from_str("12").unwrap()),
+            bytecode: SetOrKeep::Set(vec![0; 10]),
+            datastore,
+        };
+        let mut batch = LedgerBatch::new(db.get_ledger_hash());
+        db.update_entry(&addr, entry_update, &mut batch);
+        // a key written twice in a batch is only counted once
+        db.update_key_value(
+            db.db.cf_handle(LEDGER_CF).expect(CF_ERROR),
+            &mut batch,
+            &data_key!(addr, b"1".to_vec()),
+            b"ab",
+        );
+        db.write_batch(batch);
+        // bytecode 10, "1" => "ab" 3, "3" => "c" 2, "45" => "e" 3
+        assert_eq!(db.get_storage_size(&addr), 18);
+
+        // the index matches the one built from the entries on open
+        let storage_handle = db.db.cf_handle(STORAGE_CF).expect(CF_ERROR);
+        db.db.delete_cf(storage_handle, addr.to_bytes()).unwrap();
+        assert_eq!(db.get_storage_size(&addr), 0);
+        db.build_storage_index();
+        assert_eq!(db.get_storage_size(&addr), 18);
+
+        // deleted entries have no storage left
+        let mut batch = LedgerBatch::new(db.get_ledger_hash());
+        db.delete_entry(&addr, &mut batch);
+        db.write_batch(batch);
+        assert_eq!(db.get_storage_size(&addr), 0);
+        assert!(db.get_storage_sizes().is_empty());
+    }
+
+    #[test]
+    fn test_sub_entry_size() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (db, data) = init_test_ledger(addr);
+        for (key, value) in data {
+            assert_eq!(
+                db.get_sub_entry_size(&addr, LedgerSubEntry::Datastore(key)),
+                Some(value.len() as u64)
+            );
+        }
+        assert_eq!(
+            db.get_sub_entry_size(&addr, LedgerSubEntry::Datastore(b"4".to_vec())),
+            None
+        );
+        assert_eq!(
+            db.get_sub_entry_size(&addr, LedgerSubEntry::Bytecode),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_datastore_keys_page() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (db, _) = init_test_ledger(addr);
+
+        // paginate over every key
+        let (keys, cursor) = db.get_datastore_keys_page(&addr, &[], StreamingStep::Started, 2);
+        assert_eq!(
+            keys.into_iter().collect::<Vec<_>>(),
+            vec![b"1".to_vec(), b"2".to_vec()]
+        );
+        assert_eq!(cursor, StreamingStep::Ongoing(b"2".to_vec()));
+        let (keys, cursor) = db.get_datastore_keys_page(&addr, &[], cursor, 2);
+        assert_eq!(keys.into_iter().collect::<Vec<_>>(), vec![b"3".to_vec()]);
+        assert_eq!(cursor, StreamingStep::Finished);
+
+        // filter by prefix, with a cursor before the prefix
+        let (keys, cursor) =
+            db.get_datastore_keys_page(&addr, b"3", StreamingStep::Ongoing(b"1".to_vec()), 2);
+        assert_eq!(keys.into_iter().collect::<Vec<_>>(), vec![b"3".to_vec()]);
+        assert_eq!(cursor, StreamingStep::Finished);
+    }
+
+    #[test]
+    fn test_contracts_page() {
+        let user = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (mut db, _) = init_test_ledger(user);
+        let mut contracts: Vec<ContractInfo> = Vec::new();
+        let mut batch = LedgerBatch::new(db.get_ledger_hash());
+        for bytecode in [b"contract a".to_vec(), b"contract b".to_vec()] {
+            let address = Address::from_public_key(&KeyPair::generate().get_public_key());
+            contracts.push(ContractInfo {
+                address,
+                bytecode_size: bytecode.len() as u64,
+                bytecode_hash: Hash::compute_from(&bytecode),
+            });
+            let entry = LedgerEntry {
+                bytecode,
+                ..Default::default()
+            };
+            db.put_entry(&address, entry, &mut batch);
+        }
+        db.write_batch(batch);
+        contracts.sort_unstable_by_key(|contract| contract.address);
+
+        // the address without bytecode is skipped
+        let (page, cursor) = db.get_contracts_page(StreamingStep::Started, 1);
+        assert_eq!(page, contracts[..1]);
+        assert_eq!(cursor, StreamingStep::Ongoing(contracts[0].address));
+        let (page, cursor) = db.get_contracts_page(cursor, 1);
+        assert_eq!(page, contracts[1..]);
+        let (page, cursor) = db.get_contracts_page(cursor, 1);
+        assert!(page.is_empty());
+        assert_eq!(cursor, StreamingStep::Finished);
+    }
+
+    #[test]
+    fn test_ledger_entry_proof() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (db, _) = init_test_ledger(addr);
+        let mut batch = LedgerBatch::new(db.get_ledger_hash());
+        db.set_slot(Slot::new(1, 0), &mut batch);
+        db.write_batch(batch);
+        let root = db.get_ledger_root();
+        assert_ne!(root, Hash::from_bytes(&EMPTY_NODE_HASH));
+
+        // proofs of a balance and a datastore entry
+        let balance_proof = db.get_entry_proof(&addr, LedgerSubEntry::Balance).unwrap();
+        assert_eq!(balance_proof.slot, Slot::new(1, 0));
+        assert_eq!(balance_proof.address(), Some(addr));
+        assert!(balance_proof.verify(&root));
+        let data_proof = db
+            .get_entry_proof(&addr, LedgerSubEntry::Datastore(b"2".to_vec()))
+            .unwrap();
+        assert_eq!(data_proof.value, b"b".to_vec());
+        assert!(data_proof.verify(&root));
+        assert!(db
+            .get_entry_proof(&addr, LedgerSubEntry::Datastore(b"4".to_vec()))
+            .is_none());
+
+        // a tampered value is rejected
+        let mut tampered_proof = data_proof.clone();
+        tampered_proof.value = b"z".to_vec();
+        assert!(!tampered_proof.verify(&root));
+
+        // updates change the root and old proofs no longer verify against it
+        let entry_update = LedgerEntryUpdate {
+            datastore: BTreeMap::from([(b"2".to_vec(), SetOrDelete::Set(b"z".to_vec()))]),
+            ..Default::default()
+        };
+        let mut batch = LedgerBatch::new(db.get_ledger_hash());
+        db.update_entry(&addr, entry_update, &mut batch);
+        db.write_batch(batch);
+        let updated_root = db.get_ledger_root();
+        assert_ne!(updated_root, root);
+        assert!(!data_proof.verify(&updated_root));
+        let updated_proof = db
+            .get_entry_proof(&addr, LedgerSubEntry::Datastore(b"2".to_vec()))
+            .unwrap();
+        assert_eq!(updated_proof.value, b"z".to_vec());
+        assert!(updated_proof.verify(&updated_root));
+
+        // deleting the entry empties the tree
+        let mut batch = LedgerBatch::new(db.get_ledger_hash());
+        db.delete_entry(&addr, &mut batch);
+        db.write_batch(batch);
+        assert_eq!(db.get_ledger_root(), Hash::from_bytes(&EMPTY_NODE_HASH));
+    }
+
+    #[test]
+    fn test_ledger_merkle_tree_rebuild() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), 32, 255, 1_000_000);
+        let mut batch = LedgerBatch::new(Hash::from_bytes(LEDGER_HASH_INITIAL_BYTES));
+        db.put_entry(
+            &addr,
+            LedgerEntry {
+                balance: Amount::from_str("42").unwrap(),
+                datastore: BTreeMap::from([(b"1".to_vec(), b"a".to_vec())]),
+                ..Default::default()
+            },
+            &mut batch,
+        );
+        db.set_slot(Slot::new(1, 0), &mut batch);
+        db.write_batch(batch);
+        let ledger_hash = db.get_ledger_hash();
+        let root = db.get_ledger_root();
+
+        // lose the Merkle tree, as a ledger written before it was introduced
+        let merkle_handle = db.db.cf_handle(MERKLE_CF).expect(CF_ERROR);
+        let merkle_keys: Vec<_> = db
+            .db
+            .iterator_cf(merkle_handle, rocksdb::IteratorMode::Start)
+            .flatten()
+            .map(|(key, _)| key)
+            .collect();
+        for key in merkle_keys {
+            db.db.delete_cf(merkle_handle, key).unwrap();
+        }
+        assert_eq!(db.get_ledger_root(), Hash::from_bytes(&EMPTY_NODE_HASH));
+        assert!(db.get_entry_proof(&addr, LedgerSubEntry::Balance).is_some());
+        drop(db);
+
+        // the tree is rebuilt when the ledger is opened again
+        let db = LedgerDB::new(temp_dir.path().to_path_buf(), 32, 255, 1_000_000);
+        assert_eq!(db.get_ledger_root(), root);
+        assert_eq!(db.get_ledger_hash(), ledger_hash);
+        let proof = db
+            .get_entry_proof(&addr, LedgerSubEntry::Datastore(b"1".to_vec()))
+            .unwrap();
+        assert!(proof.verify(&root));
+    }
+
+    #[test]
+    fn test_ledger_merkle_tree_update() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (mut db, _) = init_test_ledger(addr);
+        let merkle_nodes = |db: &LedgerDB| -> BTreeMap<Vec<u8>, Vec<u8>> {
+            let merkle_handle = db.db.cf_handle(MERKLE_CF).expect(CF_ERROR);
+            db.db
+                .iterator_cf(
+                    merkle_handle,
+                    rocksdb::IteratorMode::From(&[MERKLE_NODE_PREFIX], rocksdb::Direction::Forward),
+                )
+                .flatten()
+                .take_while(|(key, _)| key.first() == Some(&MERKLE_NODE_PREFIX))
+                .map(|(key, value)| (key.to_vec(), value.to_vec()))
+                .collect()
+        };
+        let nodes = merkle_nodes(&db);
+
+        // updating an entry only rewrites the nodes on the path from its bucket to the root
+        let entry_update = LedgerEntryUpdate {
+            datastore: BTreeMap::from([(b"2".to_vec(), SetOrDelete::Set(b"z".to_vec()))]),
+            ..Default::default()
+        };
+        let mut batch = LedgerBatch::new(db.get_ledger_hash());
+        db.update_entry(&addr, entry_update, &mut batch);
+        db.write_batch(batch);
+        let updated_nodes = merkle_nodes(&db);
+        let changed_count = updated_nodes
+            .iter()
+            .filter(|(key, value)| nodes.get(*key) != Some(*value))
+            .count();
+        assert_eq!(changed_count, LEDGER_PROOF_TREE_DEPTH as usize + 1);
+        assert_eq!(updated_nodes.len(), nodes.len());
+
+        // the incrementally updated tree matches a tree built from scratch
+        let merkle_handle = db.db.cf_handle(MERKLE_CF).expect(CF_ERROR);
+        for key in updated_nodes.keys() {
+            db.db.delete_cf(merkle_handle, key).unwrap();
+        }
+        db.build_merkle_tree();
+        assert_eq!(merkle_nodes(&db), updated_nodes);
+    }
+
+    #[test]
+    fn test_ledger_scrub() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (db, _) = init_test_ledger(addr);
+
+        // scrub the balance, the bytecode and the 3 datastore entries by ranges of 2
+        let mut cursor = StreamingStep::Started;
+        let mut checked_entries = 0;
+        while !cursor.finished() {
+            let report = db.scrub_range(cursor, 2);
+            assert!(report.anomalies.is_empty());
+            checked_entries += report.checked_entries;
+            cursor = report.cursor;
+        }
+        assert_eq!(checked_entries, 5);
+
+        // corrupt a value behind the back of the ledger hash and Merkle tree
+        let handle = db.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        db.db
+            .put_cf(handle, data_key!(addr, b"2".to_vec()), b"corrupted")
+            .unwrap();
+        let report = db.scrub_range(StreamingStep::Started, 10);
+        assert_eq!(report.checked_entries, 5);
+        assert_eq!(report.anomalies.len(), 1);
+        assert_eq!(report.cursor, StreamingStep::Finished);
+    }
+
+    #[test]
+    fn test_ledger_integrity() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (mut db, _) = init_test_ledger(addr);
+        let report = db.verify_integrity(true);
+        assert!(report.is_consistent());
+        assert!(!report.repaired);
+        assert_eq!(report.checked_entries, 5);
+        assert_eq!(report.stored_hash, Some(db.get_ledger_hash()));
+
+        // corrupt a value behind the back of the ledger hash and Merkle tree
+        let handle = db.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        db.db
+            .put_cf(handle, data_key!(addr, b"2".to_vec()), b"corrupted")
+            .unwrap();
+        let report = db.verify_integrity(false);
+        assert!(!report.is_consistent());
+        assert!(!report.repaired);
+        assert_ne!(report.stored_hash, Some(report.computed_hash));
+        assert_eq!(report.mismatched_keys.len(), 1);
+        assert_eq!(report.orphan_merkle_entries, 1);
+
+        // the metadata is rebuilt from the entries
+        let report = db.verify_integrity(true);
+        assert!(report.repaired);
+        assert_eq!(db.get_ledger_hash(), report.computed_hash);
+        let report = db.verify_integrity(false);
+        assert!(report.is_consistent());
+        let scrub_report = db.scrub_range(StreamingStep::Started, 10);
+        assert!(scrub_report.anomalies.is_empty());
+    }
+
     #[test]
     fn test_ledger_parts() {
         let pub_a = KeyPair::generate().get_public_key();
@@ -714,4 +2041,45 @@ mod tests {
         let res = db.get_ledger_part(StreamingStep::Started).unwrap();
         db.set_ledger_part(&res.0[..]).unwrap();
     }
+
+    #[test]
+    fn test_ledger_snapshot() {
+        let addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (db, data) = init_test_ledger(addr);
+        let slot = Slot::new(3, 7);
+        let mut batch = LedgerBatch::new(db.get_ledger_hash());
+        db.set_slot(slot, &mut batch);
+        db.write_batch(batch);
+
+        // export the ledger and import it in another one
+        let snapshot_dir = TempDir::new().unwrap();
+        let snapshot_path = snapshot_dir.path().join("ledger_snapshot");
+        let snapshot_writer = db.export_snapshot(&snapshot_path).unwrap();
+
+        // the writes following the snapshot are not exported
+        let mut batch = LedgerBatch::new(db.get_ledger_hash());
+        db.set_slot(Slot::new(4, 0), &mut batch);
+        db.write_batch(batch);
+        assert_eq!(snapshot_writer().unwrap(), slot);
+        assert!(!snapshot_path.with_extension("checkpoint").exists());
+        let other_addr = Address::from_public_key(&KeyPair::generate().get_public_key());
+        let (mut imported_db, _) = init_test_ledger(other_addr);
+        assert_eq!(imported_db.import_snapshot(&snapshot_path).unwrap(), slot);
+
+        // check the imported content
+        assert_eq!(imported_db.get_ledger_hash(), db.get_ledger_hash());
+        assert_eq!(imported_db.get_entire_datastore(&addr), data);
+        assert!(imported_db.get_entire_datastore(&other_addr).is_empty());
+
+        // a corrupted snapshot is rejected
+        let mut bytes = std::fs::read(&snapshot_path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        std::fs::write(&snapshot_path, bytes).unwrap();
+        assert!(imported_db.import_snapshot(&snapshot_path).is_err());
+        assert_eq!(
+            imported_db.get_ledger_hash(),
+            Hash::from_bytes(LEDGER_HASH_INITIAL_BYTES)
+        );
+    }
 }
@@ -27,4 +27,6 @@ pub struct ActiveBlock {
     pub slot: Slot,
     /// Fitness
     pub fitness: u64,
+    /// Confirmation weight: sum of the fitness of the block and of its descendants
+    pub confirmation_weight: u64,
 }
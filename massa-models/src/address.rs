@@ -6,7 +6,7 @@ use massa_hash::{Hash, HashDeserializer};
 use massa_serialization::{
     DeserializeError, Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
-use massa_signature::PublicKey;
+use massa_signature::{PublicKey, KEYPAIR_VERSION_ED25519};
 use nom::error::{context, ContextError, ParseError};
 use nom::{IResult, Parser};
 use serde::{Deserialize, Serialize};
@@ -23,6 +23,36 @@ pub struct Address(pub Hash);
 const ADDRESS_PREFIX: char = 'A';
 const ADDRESS_VERSION: u64 = 0;
 
+/// Prefix of the checksummed textual format, never produced by the base58 one
+/// because `0` is not part of the base58 alphabet
+const CHECKSUMMED_ADDRESS_PREFIX: &str = "A0";
+/// Size of the character checksum appended to the checksummed textual format, in bytes
+const CHECKSUMMED_ADDRESS_CHECKSUM_BYTES: usize = 4;
+
+/// Applies the case checksum to a lowercase hexadecimal string: every letter whose matching
+/// nibble in the hash of the lowercase string is at least 8 is uppercased (EIP-55 style)
+fn apply_case_checksum(lowercase_hex: &str) -> String {
+    let hash = Hash::compute_from(lowercase_hex.as_bytes());
+    let hash_bytes = hash.to_bytes();
+    lowercase_hex
+        .chars()
+        .enumerate()
+        .map(|(index, c)| {
+            let byte = hash_bytes[(index / 2) % hash_bytes.len()];
+            let nibble = if index % 2 == 0 {
+                byte >> 4
+            } else {
+                byte & 0x0f
+            };
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
 impl std::fmt::Display for Address {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let u64_serializer = U64VarIntSerializer::new();
@@ -66,7 +96,8 @@ impl<'de> ::serde::Deserialize<'de> for Address {
                 type Value = Address;
 
                 fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
-                    formatter.write_str("A + base58::encode(version + hash)")
+                    formatter
+                        .write_str("A + base58::encode(version + hash) or a checksummed address")
                 }
 
                 fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
@@ -114,6 +145,9 @@ impl<'de> ::serde::Deserialize<'de> for Address {
 impl FromStr for Address {
     type Err = ModelsError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(data) = s.strip_prefix(CHECKSUMMED_ADDRESS_PREFIX) {
+            return Address::from_checksummed_str(data);
+        }
         let mut chars = s.chars();
         match chars.next() {
             Some(prefix) if prefix == ADDRESS_PREFIX => {
@@ -147,9 +181,114 @@ fn test_address_str_format() {
     assert!(address == b);
 }
 
+#[test]
+fn test_address_checksummed_str_format() {
+    use massa_signature::KeyPair;
+
+    let keypair = KeyPair::generate();
+    let address = Address::from_public_key(&keypair.get_public_key());
+    let checksummed = address.to_checksummed_string();
+    assert!(checksummed.starts_with(CHECKSUMMED_ADDRESS_PREFIX));
+    assert_eq!(Address::from_str(&checksummed).unwrap(), address);
+
+    // addresses typed in a single case skip the case checksum but keep the character one
+    let data = &checksummed[CHECKSUMMED_ADDRESS_PREFIX.len()..];
+    let lowercase = format!(
+        "{}{}",
+        CHECKSUMMED_ADDRESS_PREFIX,
+        data.to_ascii_lowercase()
+    );
+    let uppercase = format!(
+        "{}{}",
+        CHECKSUMMED_ADDRESS_PREFIX,
+        data.to_ascii_uppercase()
+    );
+    assert_eq!(Address::from_str(&lowercase).unwrap(), address);
+    assert_eq!(Address::from_str(&uppercase).unwrap(), address);
+
+    // a changed character breaks the character checksum
+    let mut typo = lowercase.into_bytes();
+    let last = typo.len() - 1;
+    typo[last] = if typo[last] == b'0' { b'1' } else { b'0' };
+    assert!(Address::from_str(&String::from_utf8(typo).unwrap()).is_err());
+
+    // a changed case breaks the case checksum
+    let mut wrong_case = checksummed.clone().into_bytes();
+    let index = wrong_case
+        .iter()
+        .rposition(|c| c.is_ascii_alphabetic())
+        .unwrap();
+    wrong_case[index] ^= 0x20;
+    let wrong_case = String::from_utf8(wrong_case).unwrap();
+    let data = &wrong_case[CHECKSUMMED_ADDRESS_PREFIX.len()..];
+    if data.chars().any(|c| c.is_ascii_uppercase()) && data.chars().any(|c| c.is_ascii_lowercase())
+    {
+        assert!(Address::from_str(&wrong_case).is_err());
+    }
+}
+
 impl PreHashed for Address {}
 
 impl Address {
+    /// Checksummed textual representation of the address: `A0` followed by the hexadecimal
+    /// encoding of the version, the hash and a 4 bytes checksum of both, where the case of
+    /// the letters carries a second checksum (EIP-55 style).
+    /// `Address::from_str` accepts it along with the base58 representation.
+    pub fn to_checksummed_string(&self) -> String {
+        let mut bytes: Vec<u8> = Vec::new();
+        U64VarIntSerializer::new()
+            .serialize(&ADDRESS_VERSION, &mut bytes)
+            .expect("critical: address version serialization failed");
+        bytes.extend(self.0.to_bytes());
+        let checksum = Hash::compute_from(&bytes);
+        bytes.extend(&checksum.to_bytes()[..CHECKSUMMED_ADDRESS_CHECKSUM_BYTES]);
+        let lowercase_hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        format!(
+            "{}{}",
+            CHECKSUMMED_ADDRESS_PREFIX,
+            apply_case_checksum(&lowercase_hex)
+        )
+    }
+
+    /// Parses the part of a checksummed address following its prefix.
+    /// The case checksum is only checked when the letters are mixed-case, so that addresses
+    /// typed in a single case are accepted on the strength of the character checksum.
+    fn from_checksummed_str(data: &str) -> Result<Address, ModelsError> {
+        if data.len() % 2 != 0 || !data.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ModelsError::AddressParseError);
+        }
+        let lowercase_hex = data.to_ascii_lowercase();
+        let mixed_case = data.chars().any(|c| c.is_ascii_uppercase())
+            && data.chars().any(|c| c.is_ascii_lowercase());
+        if mixed_case && apply_case_checksum(&lowercase_hex) != data {
+            return Err(ModelsError::AddressParseError);
+        }
+        let bytes = (0..lowercase_hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&lowercase_hex[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| ModelsError::AddressParseError)?;
+        if bytes.len() < CHECKSUMMED_ADDRESS_CHECKSUM_BYTES {
+            return Err(ModelsError::AddressParseError);
+        }
+        let (payload, checksum) = bytes.split_at(bytes.len() - CHECKSUMMED_ADDRESS_CHECKSUM_BYTES);
+        if Hash::compute_from(payload).to_bytes()[..CHECKSUMMED_ADDRESS_CHECKSUM_BYTES] != *checksum
+        {
+            return Err(ModelsError::AddressParseError);
+        }
+        let u64_deserializer = U64VarIntDeserializer::new(Included(0), Included(u64::MAX));
+        let (rest, version) = u64_deserializer
+            .deserialize::<DeserializeError>(payload)
+            .map_err(|_| ModelsError::AddressParseError)?;
+        if version != ADDRESS_VERSION {
+            return Err(ModelsError::AddressParseError);
+        }
+        Ok(Address(Hash::from_bytes(
+            rest.try_into()
+                .map_err(|_| ModelsError::AddressParseError)?,
+        )))
+    }
+
     /// Gets the associated thread. Depends on the `thread_count`
     pub fn get_thread(&self, thread_count: u8) -> u8 {
         (self.to_bytes()[0])
@@ -157,9 +296,15 @@ impl Address {
             .unwrap_or(0)
     }
 
-    /// Computes address associated with given public key
+    /// Computes address associated with given public key.
+    /// The version of the key is hashed along its bytes, except for the original Ed25519 keys
+    /// whose addresses are kept unchanged.
     pub fn from_public_key(public_key: &PublicKey) -> Self {
-        Address(Hash::compute_from(public_key.to_bytes()))
+        if public_key.get_version() == KEYPAIR_VERSION_ED25519 {
+            Address(Hash::compute_from(public_key.to_bytes()))
+        } else {
+            Address(Hash::compute_from(&public_key.to_versioned_bytes()))
+        }
     }
 
     /// ## Example
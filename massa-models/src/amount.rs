@@ -14,6 +14,10 @@ use std::str::FromStr;
 /// decimal factor for the internal representation
 pub const AMOUNT_DECIMAL_FACTOR: u64 = 1_000_000_000;
 
+/// Formats accepted by `Amount::from_str_with_unit`, listed in its errors
+const AMOUNT_FORMATS: &str =
+    "\"1.5\" or \"1.5 MAS\" (up to 9 decimals), \"1500000000 nMAS\" (whole number)";
+
 /// A structure representing a decimal Amount of coins with safe operations
 /// this allows ensuring that there is never an uncontrolled overflow or precision loss
 /// while providing a convenient decimal interface for users
@@ -153,6 +157,48 @@ impl Amount {
     pub fn checked_div_u64(self, factor: u64) -> Option<Self> {
         self.0.checked_div(factor).map(Amount)
     }
+
+    /// add another amount to self, returning an error on overflow
+    ///
+    /// With the `strict-math` feature, the overflow panics in debug builds.
+    /// ```
+    /// # use massa_models::amount::Amount;
+    /// # use std::str::FromStr;
+    /// let amount_1 : Amount = Amount::from_str("42").unwrap();
+    /// let amount_2 : Amount = Amount::from_str("7").unwrap();
+    /// let res : Amount = amount_1.strict_add(amount_2).unwrap();
+    /// assert_eq!(res, Amount::from_str("49").unwrap())
+    /// ```
+    pub fn strict_add(self, amount: Amount) -> Result<Self, ModelsError> {
+        self.checked_add(amount)
+            .ok_or_else(|| strict_math_failure(ModelsError::AmountOverflowError))
+    }
+
+    /// subtract another amount from self, returning an error on underflow
+    ///
+    /// With the `strict-math` feature, the underflow panics in debug builds.
+    pub fn strict_sub(self, amount: Amount) -> Result<Self, ModelsError> {
+        self.checked_sub(amount)
+            .ok_or_else(|| strict_math_failure(ModelsError::AmountUnderflowError))
+    }
+
+    /// multiply self with a `u64`, returning an error on overflow
+    ///
+    /// With the `strict-math` feature, the overflow panics in debug builds.
+    pub fn strict_mul_u64(self, factor: u64) -> Result<Self, ModelsError> {
+        self.checked_mul_u64(factor)
+            .ok_or_else(|| strict_math_failure(ModelsError::AmountOverflowError))
+    }
+}
+
+/// Handles a failed strict `Amount` operation.
+/// Panics in debug builds with the `strict-math` feature, so that silent economic bugs surface in tests,
+/// and returns the error otherwise.
+fn strict_math_failure(error: ModelsError) -> ModelsError {
+    if cfg!(all(feature = "strict-math", debug_assertions)) {
+        panic!("strict math failure: {}", error);
+    }
+    error
 }
 
 /// display an Amount in decimal string form (like "10.33")
@@ -222,6 +268,120 @@ impl FromStr for Amount {
     }
 }
 
+/// Units in which amounts are written by users and displayed to them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmountUnit {
+    /// MAS, the unit of the decimal representation of amounts
+    Mas,
+    /// nMAS, a billionth of MAS, the unit of the raw representation of amounts
+    NanoMas,
+}
+
+impl AmountUnit {
+    /// Symbol of the unit, as written after amounts
+    pub const fn symbol(&self) -> &'static str {
+        match self {
+            AmountUnit::Mas => "MAS",
+            AmountUnit::NanoMas => "nMAS",
+        }
+    }
+}
+
+impl FromStr for AmountUnit {
+    type Err = ModelsError;
+
+    /// Parses a unit symbol, ignoring case
+    fn from_str(symbol: &str) -> Result<Self, Self::Err> {
+        [AmountUnit::Mas, AmountUnit::NanoMas]
+            .into_iter()
+            .find(|unit| unit.symbol().eq_ignore_ascii_case(symbol))
+            .ok_or_else(|| {
+                ModelsError::AmountParseError(format!(
+                    "unknown unit \"{}\", accepted formats: {}",
+                    symbol, AMOUNT_FORMATS
+                ))
+            })
+    }
+}
+
+/// Display of an amount in a given unit, like "1.5 MAS"
+///
+/// ```
+/// # use massa_models::amount::{Amount, AmountUnit};
+/// # use std::str::FromStr;
+/// let value = Amount::from_str("1.5").unwrap();
+/// assert_eq!(value.with_unit(AmountUnit::Mas).to_string(), "1.5 MAS");
+/// assert_eq!(value.with_unit(AmountUnit::NanoMas).to_string(), "1500000000 nMAS");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct AmountWithUnit {
+    amount: Amount,
+    unit: AmountUnit,
+}
+
+impl fmt::Display for AmountWithUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.unit {
+            AmountUnit::Mas => write!(f, "{} {}", self.amount, self.unit.symbol()),
+            AmountUnit::NanoMas => write!(f, "{} {}", self.amount.to_raw(), self.unit.symbol()),
+        }
+    }
+}
+
+impl Amount {
+    /// Parses an amount written by a user, with an optional unit: a decimal number of MAS,
+    /// optionally followed by "MAS", or a whole number followed by "nMAS".
+    /// The unit may be separated from the number by spaces, and its case is ignored.
+    /// Errors list the accepted formats.
+    ///
+    /// ```
+    /// # use massa_models::amount::Amount;
+    /// # use std::str::FromStr;
+    /// let value = Amount::from_str("1.5").unwrap();
+    /// assert_eq!(Amount::from_str_with_unit("1.5").unwrap(), value);
+    /// assert_eq!(Amount::from_str_with_unit("1.5 MAS").unwrap(), value);
+    /// assert_eq!(Amount::from_str_with_unit("1.5mas").unwrap(), value);
+    /// assert_eq!(Amount::from_str_with_unit("1500000000 nMAS").unwrap(), value);
+    /// assert!(Amount::from_str_with_unit("1.5 nMAS").is_err());
+    /// assert!(Amount::from_str_with_unit("1.5 MASS").is_err());
+    /// assert!(Amount::from_str_with_unit("MAS").is_err());
+    /// ```
+    pub fn from_str_with_unit(input: &str) -> Result<Self, ModelsError> {
+        let input = input.trim();
+        let unit_start = input
+            .find(|c: char| c.is_ascii_alphabetic())
+            .unwrap_or(input.len());
+        let (number, unit) = input.split_at(unit_start);
+        let unit = if unit.is_empty() {
+            AmountUnit::Mas
+        } else {
+            unit.parse()?
+        };
+        let parsed = match unit {
+            AmountUnit::Mas => Amount::from_str(number.trim_end()).map_err(|err| match err {
+                ModelsError::AmountParseError(msg) => msg,
+                err => err.to_string(),
+            }),
+            AmountUnit::NanoMas => number
+                .trim_end()
+                .parse::<u64>()
+                .map(Amount::from_raw)
+                .map_err(|err| err.to_string()),
+        };
+        parsed.map_err(|err| {
+            ModelsError::AmountParseError(format!(
+                "invalid amount \"{}\" ({}), accepted formats: {}",
+                input, err, AMOUNT_FORMATS
+            ))
+        })
+    }
+
+    /// Displays the amount in a given unit
+    pub const fn with_unit(self, unit: AmountUnit) -> AmountWithUnit {
+        AmountWithUnit { amount: self, unit }
+    }
+}
+
 /// Serializer for amount
 #[derive(Clone)]
 pub struct AmountSerializer {
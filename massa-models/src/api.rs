@@ -2,19 +2,32 @@
 
 use crate::address::ExecutionAddressCycleInfo;
 use crate::endorsement::{EndorsementId, WrappedEndorsement};
+use crate::error::ModelsError;
+use crate::execution::DeploymentSimulation;
 use crate::ledger_models::LedgerData;
 use crate::node::NodeId;
-use crate::operation::{OperationId, WrappedOperation};
-use crate::stats::{ConsensusStats, ExecutionStats, NetworkStats};
+use crate::operation::{OperationId, OperationOriginCounts, OperationPoolStatus, WrappedOperation};
+use crate::output_event::SCOutputEvent;
+use crate::stats::{
+    BootstrapServerStats, ConsensusStats, DiskSpaceStatus, ExecutionStats, LedgerIntegrityStatus,
+    NetworkStats, ProtocolStats,
+};
 use crate::{
-    address::Address, amount::Amount, block::Block, block::BlockId, config::CompactConfig,
-    slot::Slot, version::Version,
+    address::Address,
+    amount::{Amount, AmountUnit},
+    block::Block,
+    block::BlockId,
+    config::CompactConfig,
+    slot::Slot,
+    version::Version,
 };
+use massa_hash::Hash;
 use massa_signature::{PublicKey, Signature};
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::net::IpAddr;
+use std::str::FromStr;
 
 /// operation input
 #[derive(Serialize, Deserialize, Debug)]
@@ -36,6 +49,10 @@ pub struct NodeStatus {
     pub node_ip: Option<IpAddr>,
     /// node version
     pub version: Version,
+    /// chain id of the network the node belongs to
+    pub chain_id: u64,
+    /// first period from which operations are signed along the chain id
+    pub chain_id_activation_period: u64,
     /// now
     pub current_time: MassaTime,
     /// current cycle
@@ -50,10 +67,20 @@ pub struct NodeStatus {
     pub consensus_stats: ConsensusStats,
     /// pool stats (operation count and endorsement count)
     pub pool_stats: (usize, usize),
+    /// number of operations of the pool per origin, parked operations included
+    pub pool_operation_origins: OperationOriginCounts,
     /// network stats
     pub network_stats: NetworkStats,
+    /// protocol stats
+    pub protocol_stats: ProtocolStats,
     /// execution stats
     pub execution_stats: ExecutionStats,
+    /// free disk space status
+    pub disk_status: DiskSpaceStatus,
+    /// background ledger integrity scrubbing status
+    pub ledger_integrity: LedgerIntegrityStatus,
+    /// sessions served by our bootstrap server, none if it is disabled
+    pub bootstrap_server_stats: Option<BootstrapServerStats>,
     /// compact configuration
     pub config: CompactConfig,
 }
@@ -69,6 +96,7 @@ impl std::fmt::Display for NodeStatus {
         writeln!(f)?;
 
         writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Chain id: {}", self.chain_id)?;
         writeln!(f, "Config:\n{}", self.config)?;
         writeln!(f)?;
 
@@ -78,6 +106,11 @@ impl std::fmt::Display for NodeStatus {
             writeln!(f, "Last slot: {}", self.last_slot.unwrap())?;
         }
         writeln!(f, "Next slot: {}", self.next_slot)?;
+        writeln!(f, "Disk space: {}", self.disk_status)?;
+        writeln!(f, "Ledger integrity: {}", self.ledger_integrity)?;
+        if let Some(bootstrap_server_stats) = &self.bootstrap_server_stats {
+            writeln!(f, "Bootstrap server: {}", bootstrap_server_stats)?;
+        }
         writeln!(f)?;
 
         writeln!(f, "{}", self.consensus_stats)?;
@@ -86,6 +119,8 @@ impl std::fmt::Display for NodeStatus {
 
         writeln!(f, "{}", self.network_stats)?;
 
+        writeln!(f, "{}", self.protocol_stats)?;
+
         writeln!(f, "Connected nodes:")?;
         for (node_id, (ip_addr, is_outgoing)) in &self.connected_nodes {
             writeln!(
@@ -112,6 +147,10 @@ pub struct OperationInfo {
     pub in_blocks: Vec<BlockId>,
     /// true if the operation is final (for example in a final block)
     pub is_final: bool,
+    /// progress of the operation, from the pool to its final execution
+    pub execution_status: OperationExecutionStatus,
+    /// outcome of the execution of the operation, if it was executed recently enough to be remembered
+    pub execution: Option<OperationExecutionInfo>,
     /// the operation itself
     pub operation: WrappedOperation,
 }
@@ -125,15 +164,256 @@ impl std::fmt::Display for OperationInfo {
             display_if_true(self.in_pool, " (in pool)"),
             display_if_true(self.is_final, " (final)")
         )?;
+        writeln!(f, "Status: {}", self.execution_status)?;
         writeln!(f, "In blocks:")?;
         for block_id in &self.in_blocks {
             writeln!(f, "\t- {}", block_id)?;
         }
+        if let Some(execution) = &self.execution {
+            write!(f, "{}", execution)?;
+        }
         writeln!(f, "{}", self.operation)?;
         Ok(())
     }
 }
 
+/// Progress of an operation, from the pool to its final execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum OperationExecutionStatus {
+    /// waiting in the pool to be included in a block
+    Pending,
+    /// included in a block, but not executed yet
+    Included,
+    /// executed successfully in a candidate block
+    ExecutedSuccess,
+    /// executed in a candidate block, but the execution failed
+    ExecutedFailure,
+    /// the execution, successful or not, is final
+    Final,
+}
+
+impl OperationExecutionStatus {
+    /// Deduces the status of an operation from its inclusion and execution information
+    ///
+    /// # Arguments
+    /// * `in_blocks`: true if the operation is included in at least one block
+    /// * `is_final`: true if the operation is included in a final block
+    /// * `execution`: outcome of the execution of the operation, if known
+    pub fn new(
+        in_blocks: bool,
+        is_final: bool,
+        execution: Option<&OperationExecutionInfo>,
+    ) -> Self {
+        match execution {
+            Some(execution) if execution.is_final => OperationExecutionStatus::Final,
+            Some(execution) if execution.execution_error.is_some() => {
+                OperationExecutionStatus::ExecutedFailure
+            }
+            Some(_) => OperationExecutionStatus::ExecutedSuccess,
+            None if is_final => OperationExecutionStatus::Final,
+            None if in_blocks => OperationExecutionStatus::Included,
+            None => OperationExecutionStatus::Pending,
+        }
+    }
+}
+
+impl std::fmt::Display for OperationExecutionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationExecutionStatus::Pending => write!(f, "pending"),
+            OperationExecutionStatus::Included => write!(f, "included"),
+            OperationExecutionStatus::ExecutedSuccess => write!(f, "executed (success)"),
+            OperationExecutionStatus::ExecutedFailure => write!(f, "executed (failure)"),
+            OperationExecutionStatus::Final => write!(f, "final"),
+        }
+    }
+}
+
+/// Outcome of the execution of an operation
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct OperationExecutionInfo {
+    /// block the operation was executed in
+    pub block_id: BlockId,
+    /// slot of that block
+    pub slot: Slot,
+    /// true if the execution is final
+    pub is_final: bool,
+    /// gas consumed by the execution
+    pub gas_used: u64,
+    /// error of the execution, `None` if it succeeded
+    pub execution_error: Option<String>,
+}
+
+impl std::fmt::Display for OperationExecutionInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Executed in block {} at slot {}{}, using {} gas",
+            self.block_id,
+            self.slot,
+            display_if_true(self.is_final, " (final)"),
+            self.gas_used
+        )?;
+        match &self.execution_error {
+            Some(err) => writeln!(f, "Execution error: {}", err),
+            None => writeln!(f, "Execution succeeded"),
+        }
+    }
+}
+
+/// Activity of an address recorded by the execution at a given slot
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AddressActivity {
+    /// address concerned by the activity
+    pub address: Address,
+    /// slot at which the activity was executed
+    pub slot: Slot,
+    /// true if the execution of that slot is final
+    pub is_final: bool,
+    /// what happened
+    pub kind: AddressActivityKind,
+}
+
+impl std::fmt::Display for AddressActivity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Slot {}{}: {}",
+            self.slot,
+            display_if_true(self.is_final, " (final)"),
+            self.kind
+        )
+    }
+}
+
+/// Kind of activity of an address
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum AddressActivityKind {
+    /// the address produced a block
+    BlockProduced {
+        /// produced block
+        block_id: BlockId,
+    },
+    /// an operation created by the address was executed
+    OperationCreated {
+        /// created operation
+        operation_id: OperationId,
+        /// block the operation was executed in
+        block_id: BlockId,
+    },
+    /// the address was credited by an operation: it is the recipient of a transaction or the target of a call with coins
+    OperationCredit {
+        /// crediting operation
+        operation_id: OperationId,
+        /// credited amount
+        amount: Amount,
+    },
+    /// rolls were sold by an operation created by the address, to be reimbursed by a deferred credit
+    RollSell {
+        /// selling operation
+        operation_id: OperationId,
+        /// number of sold rolls
+        roll_count: u64,
+        /// slot at which the sold rolls are reimbursed
+        credit_slot: Slot,
+        /// reimbursed amount
+        amount: Amount,
+    },
+    /// a deferred credit of the address was paid
+    DeferredCredit {
+        /// credited amount
+        amount: Amount,
+    },
+    /// the address was rewarded for the production of a block or of one of its endorsements
+    BlockReward {
+        /// rewarding block
+        block_id: BlockId,
+        /// reason of the reward
+        role: RewardRole,
+        /// credited amount
+        amount: Amount,
+    },
+}
+
+/// Reason of a block reward
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RewardRole {
+    /// creator of the rewarding block
+    BlockCreator,
+    /// creator of an endorsement included in the rewarding block
+    EndorsementCreator,
+    /// creator of a block endorsed by the rewarding block
+    EndorsedBlockCreator,
+}
+
+impl std::fmt::Display for RewardRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RewardRole::BlockCreator => write!(f, "block creator"),
+            RewardRole::EndorsementCreator => write!(f, "endorsement creator"),
+            RewardRole::EndorsedBlockCreator => write!(f, "endorsed block creator"),
+        }
+    }
+}
+
+impl std::fmt::Display for AddressActivityKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddressActivityKind::BlockProduced { block_id } => {
+                write!(f, "produced block {}", block_id)
+            }
+            AddressActivityKind::OperationCreated {
+                operation_id,
+                block_id,
+            } => write!(
+                f,
+                "created operation {} executed in block {}",
+                operation_id, block_id
+            ),
+            AddressActivityKind::OperationCredit {
+                operation_id,
+                amount,
+            } => write!(
+                f,
+                "credited {} by operation {}",
+                amount.with_unit(AmountUnit::Mas),
+                operation_id
+            ),
+            AddressActivityKind::RollSell {
+                operation_id,
+                roll_count,
+                credit_slot,
+                amount,
+            } => write!(
+                f,
+                "sold {} rolls by operation {}, {} to be credited at slot {}",
+                roll_count,
+                operation_id,
+                amount.with_unit(AmountUnit::Mas),
+                credit_slot
+            ),
+            AddressActivityKind::DeferredCredit { amount } => {
+                write!(
+                    f,
+                    "credited {} of deferred credits",
+                    amount.with_unit(AmountUnit::Mas)
+                )
+            }
+            AddressActivityKind::BlockReward {
+                block_id,
+                role,
+                amount,
+            } => write!(
+                f,
+                "rewarded {} by block {} as {}",
+                amount.with_unit(AmountUnit::Mas),
+                block_id,
+                role
+            ),
+        }
+    }
+}
+
 /// Block status within the graph
 #[derive(Eq, PartialEq, Debug, Deserialize, Serialize)]
 pub enum BlockGraphStatus {
@@ -149,12 +429,48 @@ pub enum BlockGraphStatus {
     ActiveInBlockclique,
     /// forever applies
     Final,
-    /// discarded for any reason
-    Discarded,
+    /// discarded, for the given reason
+    Discarded(BlockDiscardKind),
     /// not found in graph
     NotFound,
 }
 
+/// Machine-readable reason why a block was discarded from the graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum BlockDiscardKind {
+    /// not newer than the latest final block of its thread when it was processed
+    Late,
+    /// incompatible with a final block, or depending on a stale block
+    Stale,
+    /// final and pruned from the graph
+    Final,
+    /// its creator was not drawn to produce a block at its slot
+    WrongCreator,
+    /// one of its endorsements was not created by the address drawn for it
+    WrongEndorser,
+    /// its parents are in the wrong threads or slots, or are incompatible with each other or with it
+    ParentMismatch,
+    /// it depends on an invalid block
+    InvalidDependency,
+    /// its content was found invalid when it was received, its header being valid
+    InvalidContent,
+}
+
+impl std::fmt::Display for BlockDiscardKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockDiscardKind::Late => write!(f, "late"),
+            BlockDiscardKind::Stale => write!(f, "stale"),
+            BlockDiscardKind::Final => write!(f, "final"),
+            BlockDiscardKind::WrongCreator => write!(f, "wrong creator"),
+            BlockDiscardKind::WrongEndorser => write!(f, "wrong endorser"),
+            BlockDiscardKind::ParentMismatch => write!(f, "parent mismatch"),
+            BlockDiscardKind::InvalidDependency => write!(f, "invalid dependency"),
+            BlockDiscardKind::InvalidContent => write!(f, "invalid content"),
+        }
+    }
+}
+
 /// Current balance ledger info
 #[derive(Debug, Deserialize, Serialize, Clone, Copy)]
 pub struct LedgerInfo {
@@ -168,13 +484,23 @@ pub struct LedgerInfo {
 
 impl std::fmt::Display for LedgerInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "\tFinal balance: {}", self.final_ledger_info.balance)?;
+        writeln!(
+            f,
+            "\tFinal balance: {}",
+            self.final_ledger_info.balance.with_unit(AmountUnit::Mas)
+        )?;
         writeln!(
             f,
             "\tCandidate balance: {}",
-            self.candidate_ledger_info.balance
+            self.candidate_ledger_info
+                .balance
+                .with_unit(AmountUnit::Mas)
+        )?;
+        writeln!(
+            f,
+            "\tLocked balance: {}",
+            self.locked_balance.with_unit(AmountUnit::Mas)
         )?;
-        writeln!(f, "\tLocked balance: {}", self.locked_balance)?;
         Ok(())
     }
 }
@@ -220,6 +546,9 @@ pub struct AddressInfo {
     pub candidate_roll_count: u64,
     /// candidate datastore keys
     pub candidate_datastore_keys: Vec<Vec<u8>>,
+    /// `datastore_keys_last_key` to query the next datastore keys with, `None` if there are no more keys
+    #[serde(default)]
+    pub next_datastore_keys_last_key: Option<Vec<u8>>,
 
     /// deferred credits
     pub deferred_credits: Vec<SlotAmount>,
@@ -246,14 +575,16 @@ impl std::fmt::Display for AddressInfo {
         writeln!(
             f,
             "\tBalance: final={}, candidate={}",
-            self.final_balance, self.candidate_balance
+            self.final_balance.with_unit(AmountUnit::Mas),
+            self.candidate_balance.with_unit(AmountUnit::Mas)
         )?;
         writeln!(f, "\tLocked coins:")?;
         for slot_amount in &self.deferred_credits {
             writeln!(
                 f,
                 "\t\t{} locked coins will be unlocked at slot {}",
-                slot_amount.amount, slot_amount.slot
+                slot_amount.amount.with_unit(AmountUnit::Mas),
+                slot_amount.slot
             )?;
         }
         writeln!(f, "\tCycle infos:")?;
@@ -316,6 +647,123 @@ impl std::fmt::Display for IndexedSlot {
     }
 }
 
+/// Block and endorsement draws of an address over a slot range
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AddressDraws {
+    /// the address
+    pub address: Address,
+    /// slots where the address is drawn to produce a block
+    pub block_draws: Vec<Slot>,
+    /// slots and indices where the address is drawn to produce an endorsement
+    pub endorsement_draws: Vec<IndexedSlot>,
+    /// last slot for which draws are known, if any:
+    /// the draws of later slots depend on lookback data that is not available yet
+    pub last_drawn_slot: Option<Slot>,
+}
+
+impl std::fmt::Display for AddressDraws {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address: {}", self.address)?;
+        match self.last_drawn_slot {
+            Some(slot) => writeln!(f, "Draws known up to slot {}", slot)?,
+            None => writeln!(f, "No draws known")?,
+        }
+        writeln!(f, "Block draws:")?;
+        for slot in &self.block_draws {
+            writeln!(f, "\t{}", slot)?;
+        }
+        writeln!(f, "Endorsement draws:")?;
+        for IndexedSlot { slot, index } in &self.endorsement_draws {
+            writeln!(f, "\t{} index {}", slot, index)?;
+        }
+        Ok(())
+    }
+}
+
+/// Draws query input structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct AddressDrawsQuery {
+    /// addresses to get the draws of
+    pub addresses: Vec<Address>,
+    /// optional start slot (included), defaults to the current slot
+    pub start: Option<Slot>,
+    /// optional end slot (excluded), defaults to the slot after the last one with known draws
+    pub end: Option<Slot>,
+}
+
+/// Address history query input structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct AddressHistoryQuery {
+    /// address to get the history of
+    pub address: Address,
+    /// optional start slot (included), defaults to the oldest remembered activity
+    pub start: Option<Slot>,
+    /// optional end slot (excluded), defaults to the latest executed activity
+    pub end: Option<Slot>,
+    /// optional max number of activities to return, capped by the node.
+    /// The page always holds all the activities of its last slot, and may exceed that count because of them
+    #[serde(default)]
+    pub max_count: Option<u64>,
+}
+
+/// Cycle statistics query input structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct CycleStatsQuery {
+    /// cycle to get the statistics of
+    pub cycle: u64,
+    /// last staker address of the previous page, `None` to get the first page
+    #[serde(default)]
+    pub start_after: Option<Address>,
+    /// optional max number of stakers to return, capped by the node
+    #[serde(default)]
+    pub max_count: Option<u64>,
+}
+
+/// Bounded address information query input structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct AddressesPageInput {
+    /// addresses to get the information of
+    pub addresses: Vec<Address>,
+    /// last datastore key of the previous page, `None` to get the first page
+    pub datastore_keys_last_key: Option<Vec<u8>>,
+    /// maximum number of final datastore keys to return per address
+    pub max_datastore_keys: u64,
+    /// maximum number of cycle infos to return per address, the latest cycles being kept
+    pub max_cycle_infos: u64,
+    /// information to return, the other fields of the address infos being left empty
+    #[serde(default)]
+    pub fields: AddressInfoFields,
+}
+
+/// Selection of the fields filled in an `AddressInfo`. Balances and roll counts are always filled
+#[derive(Debug, Deserialize, Clone, Copy, Serialize)]
+#[serde(default)]
+pub struct AddressInfoFields {
+    /// final and candidate datastore keys
+    pub datastore_keys: bool,
+    /// deferred credits
+    pub deferred_credits: bool,
+    /// next block and endorsement draws
+    pub draws: bool,
+    /// created blocks, operations and endorsements
+    pub created_objects: bool,
+    /// cycle infos
+    pub cycle_infos: bool,
+}
+
+impl Default for AddressInfoFields {
+    /// all the fields
+    fn default() -> Self {
+        AddressInfoFields {
+            datastore_keys: true,
+            deferred_credits: true,
+            draws: true,
+            created_objects: true,
+            cycle_infos: true,
+        }
+    }
+}
+
 /// Less information about an address
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CompactAddressInfo {
@@ -341,7 +789,8 @@ impl std::fmt::Display for CompactAddressInfo {
         writeln!(
             f,
             "\tBalance: final={}, candidate={}",
-            self.final_balance, self.candidate_balance
+            self.final_balance.with_unit(AmountUnit::Mas),
+            self.candidate_balance.with_unit(AmountUnit::Mas)
         )?;
         writeln!(
             f,
@@ -415,6 +864,11 @@ pub struct BlockInfoContent {
     pub is_candidate: bool,
     /// true if discarded
     pub is_discarded: bool,
+    /// why the block was discarded, if it was
+    #[serde(default)]
+    pub discard_reason: Option<BlockDiscardKind>,
+    /// sum of the fitness of the block and of its descendants, if the block is active
+    pub confirmation_weight: Option<u64>,
     /// block
     pub block: Block,
 }
@@ -431,6 +885,12 @@ impl std::fmt::Display for BlockInfo {
                 display_if_true(content.is_in_blockclique, " (blockclique)"),
                 display_if_true(content.is_discarded, " (discarded)"),
             )?;
+            if let Some(reason) = content.discard_reason {
+                writeln!(f, "Discard reason: {}", reason)?;
+            }
+            if let Some(weight) = content.confirmation_weight {
+                writeln!(f, "Confirmation weight: {}", weight)?;
+            }
             writeln!(f, "Block: {}", content.block)?;
         } else {
             writeln!(f, "Block {} not found", self.id)?;
@@ -440,7 +900,7 @@ impl std::fmt::Display for BlockInfo {
 }
 
 /// A block resume (without the block itself)
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BlockSummary {
     /// id
     pub id: BlockId,
@@ -448,6 +908,9 @@ pub struct BlockSummary {
     pub is_final: bool,
     /// true if incompatible with a final block
     pub is_stale: bool,
+    /// why the block was discarded, if it was
+    #[serde(default)]
+    pub discard_reason: Option<BlockDiscardKind>,
     /// true if in the greatest block clique
     pub is_in_blockclique: bool,
     /// the slot the block is in
@@ -470,6 +933,9 @@ impl std::fmt::Display for BlockSummary {
         )?;
         writeln!(f, "Slot: {}", self.slot)?;
         writeln!(f, "Creator: {}", self.creator)?;
+        if let Some(reason) = self.discard_reason {
+            writeln!(f, "Discard reason: {}", reason)?;
+        }
         writeln!(f, "Parents' IDs:")?;
         for parent in &self.parents {
             writeln!(f, "\t- {}", parent)?;
@@ -478,6 +944,399 @@ impl std::fmt::Display for BlockSummary {
     }
 }
 
+/// Blockclique re-organization: previously notified blockclique blocks
+/// that were replaced by blocks of another clique
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReorgInfo {
+    /// time at which the re-organization was detected
+    pub time: MassaTime,
+    /// maximum number of blocks dropped from the blockclique in a single thread
+    pub depth: u64,
+    /// blocks dropped from the blockclique, sorted by slot
+    pub dropped_blocks: Vec<(BlockId, Slot)>,
+    /// blocks that entered the blockclique, sorted by slot
+    pub new_blocks: Vec<(BlockId, Slot)>,
+}
+
+impl std::fmt::Display for ReorgInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Re-organization of depth {} at {}",
+            self.depth,
+            self.time.to_utc_string()
+        )?;
+        writeln!(f, "Dropped blocks:")?;
+        for (block_id, slot) in &self.dropped_blocks {
+            writeln!(f, "\t- {} at slot {}", block_id, slot)?;
+        }
+        writeln!(f, "New blocks:")?;
+        for (block_id, slot) in &self.new_blocks {
+            writeln!(f, "\t- {} at slot {}", block_id, slot)?;
+        }
+        Ok(())
+    }
+}
+
+/// Block whose confirmation weight crossed one of the configured thresholds
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BlockConfirmation {
+    /// block id
+    pub id: BlockId,
+    /// block slot
+    pub slot: Slot,
+    /// crossed threshold
+    pub threshold: u64,
+    /// confirmation weight of the block when the threshold was crossed
+    pub weight: u64,
+}
+
+impl std::fmt::Display for BlockConfirmation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Block {} at slot {} reached confirmation weight {} (threshold {})",
+            self.id, self.slot, self.weight, self.threshold
+        )
+    }
+}
+
+/// Candidate block content for a slot, as the block factory would assemble it.
+/// The template is neither signed nor broadcast.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BlockTemplate {
+    /// slot of the template
+    pub slot: Slot,
+    /// address drawn to produce a block at that slot
+    pub producer: Address,
+    /// best parents (one per thread)
+    pub parents: Vec<BlockId>,
+    /// endorsements picked from pool, `None` where no endorsement is available for an index
+    pub endorsements: Vec<Option<EndorsementId>>,
+    /// operations picked from pool, in block order
+    pub operations: Vec<OperationId>,
+    /// sum of the max gas of the selected operations
+    pub estimated_gas: u64,
+    /// sum of the fees of the selected operations
+    pub total_fees: Amount,
+}
+
+impl std::fmt::Display for BlockTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Slot: {}", self.slot)?;
+        writeln!(f, "Producer: {}", self.producer)?;
+        writeln!(f, "Parents' IDs:")?;
+        for parent in &self.parents {
+            writeln!(f, "\t- {}", parent)?;
+        }
+        writeln!(f, "Endorsements:")?;
+        for (index, endorsement) in self.endorsements.iter().enumerate() {
+            match endorsement {
+                Some(id) => writeln!(f, "\t- {}: {}", index, id)?,
+                None => writeln!(f, "\t- {}: missing", index)?,
+            }
+        }
+        writeln!(f, "Operations ({}):", self.operations.len())?;
+        for op_id in &self.operations {
+            writeln!(f, "\t- {}", op_id)?;
+        }
+        writeln!(f, "Estimated gas: {}", self.estimated_gas)?;
+        writeln!(
+            f,
+            "Total fees: {}",
+            self.total_fees.with_unit(AmountUnit::Mas)
+        )?;
+        Ok(())
+    }
+}
+
+/// Outcome of an endorsement draw of an address
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EndorsementDiagnostic {
+    /// address that was drawn
+    pub address: Address,
+    /// slot of the draw
+    pub slot: Slot,
+    /// endorsement index the address was drawn for
+    pub index: u32,
+    /// id of the endorsement created by the address for that slot and index, if it is known by the node
+    pub produced: Option<EndorsementId>,
+    /// true if the produced endorsement is in the endorsement pool
+    pub in_pool: bool,
+    /// blocks including the produced endorsement
+    pub included_in: Vec<BlockId>,
+}
+
+impl std::fmt::Display for EndorsementDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Address {} / slot {} / index {}: ",
+            self.address, self.slot, self.index
+        )?;
+        match &self.produced {
+            None => writeln!(f, "missed (no endorsement produced)")?,
+            Some(id) if !self.included_in.is_empty() => writeln!(
+                f,
+                "endorsement {} included in block(s) {}",
+                id,
+                self.included_in
+                    .iter()
+                    .map(|b_id| b_id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?,
+            Some(id) => writeln!(
+                f,
+                "endorsement {} not included{}",
+                id,
+                if self.in_pool { " (in pool)" } else { "" }
+            )?,
+        }
+        Ok(())
+    }
+}
+
+/// Staking performance of a staking address of the node during a cycle
+#[derive(Debug, Deserialize, Serialize)]
+pub struct StakingCycleReport {
+    /// cycle number
+    pub cycle: u64,
+    /// true if that cycle is final
+    pub is_final: bool,
+    /// number of active rolls the address had at that cycle (if still available)
+    pub active_rolls: Option<u64>,
+    /// number of blocks produced by the address
+    pub produced_blocks: u64,
+    /// number of blocks missed by the address
+    pub missed_blocks: u64,
+    /// number of endorsements of the address included in executed blocks
+    pub produced_endorsements: u64,
+    /// number of endorsement draws of the address up to the execution cursor,
+    /// `None` if the draws of the cycle are not available anymore
+    pub expected_endorsements: Option<u64>,
+    /// rewards earned by the address, among the remembered activities
+    pub rewards: Amount,
+}
+
+impl std::fmt::Display for StakingCycleReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "\tCycle {}{}:",
+            self.cycle,
+            display_if_true(self.is_final, " (final)")
+        )?;
+        if let Some(active_rolls) = self.active_rolls {
+            writeln!(f, "\t\tActive rolls: {}", active_rolls)?;
+        }
+        writeln!(
+            f,
+            "\t\tBlocks: {} produced, {} missed",
+            self.produced_blocks, self.missed_blocks
+        )?;
+        match self.expected_endorsements {
+            Some(expected) => writeln!(
+                f,
+                "\t\tEndorsements: {} produced out of {} expected",
+                self.produced_endorsements, expected
+            )?,
+            None => writeln!(
+                f,
+                "\t\tEndorsements: {} produced (draws not available)",
+                self.produced_endorsements
+            )?,
+        }
+        writeln!(
+            f,
+            "\t\tRewards: {}",
+            self.rewards.with_unit(AmountUnit::Mas)
+        )
+    }
+}
+
+/// Staking performance report of a staking address of the node
+#[derive(Debug, Deserialize, Serialize)]
+pub struct StakingReport {
+    /// the staking address
+    pub address: Address,
+    /// performance over the cycles remembered by the node, oldest first
+    pub cycles: Vec<StakingCycleReport>,
+    /// rewards earned by the address, among the remembered activities
+    pub total_rewards: Amount,
+    /// block miss ratio of the address during the latest cycle it was drawn in
+    pub miss_ratio: f64,
+    /// miss ratio above which the rolls of the address are deactivated
+    pub max_miss_ratio: f64,
+    /// true if the miss ratio exceeds `max_miss_ratio`
+    pub above_max_miss_ratio: bool,
+}
+
+impl std::fmt::Display for StakingReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address: {}", self.address)?;
+        writeln!(
+            f,
+            "Miss ratio: {:.2}% (roll deactivation above {:.2}%){}",
+            self.miss_ratio * 100.0,
+            self.max_miss_ratio * 100.0,
+            if self.above_max_miss_ratio {
+                " - rolls at risk"
+            } else {
+                ""
+            }
+        )?;
+        writeln!(
+            f,
+            "Total rewards: {}",
+            self.total_rewards.with_unit(AmountUnit::Mas)
+        )?;
+        for cycle in &self.cycles {
+            write!(f, "{}", cycle)?;
+        }
+        Ok(())
+    }
+}
+
+/// Staking key rotation request
+#[derive(Debug, Deserialize, Serialize)]
+pub struct StakingKeyRotationInput {
+    /// secret keys of the new staking addresses
+    pub new_secret_keys: Vec<String>,
+    /// staking addresses retired once the rolls of the new ones are active
+    pub old_addresses: Vec<Address>,
+}
+
+/// Progress of a staking key rotation
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum StakingKeyRotationStatus {
+    /// the new and old addresses both stake until the rolls of the new addresses are active
+    WaitingForActiveRolls,
+    /// the old addresses were removed from the staking addresses
+    Completed,
+    /// the old addresses were kept, for the given reason
+    Aborted(String),
+}
+
+impl std::fmt::Display for StakingKeyRotationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StakingKeyRotationStatus::WaitingForActiveRolls => {
+                write!(f, "waiting for the rolls of the new addresses to be active")
+            }
+            StakingKeyRotationStatus::Completed => write!(f, "completed"),
+            StakingKeyRotationStatus::Aborted(reason) => write!(f, "aborted: {}", reason),
+        }
+    }
+}
+
+/// Staking key rotation of the node
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StakingKeyRotation {
+    /// rotation number, starting at 0 for the first rotation since the node started
+    pub id: u64,
+    /// staking addresses added by the rotation
+    pub new_addresses: Vec<Address>,
+    /// staking addresses retired by the rotation
+    pub old_addresses: Vec<Address>,
+    /// cycle during which the rotation started
+    pub start_cycle: u64,
+    /// latest cycle checked for the active rolls of the new addresses
+    pub last_checked_cycle: u64,
+    /// progress of the rotation
+    pub status: StakingKeyRotationStatus,
+}
+
+impl std::fmt::Display for StakingKeyRotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Rotation {} (started in cycle {}):",
+            self.id, self.start_cycle
+        )?;
+        writeln!(
+            f,
+            "\tNew addresses: {}",
+            self.new_addresses
+                .iter()
+                .map(|address| address.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        writeln!(
+            f,
+            "\tOld addresses: {}",
+            self.old_addresses
+                .iter()
+                .map(|address| address.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        writeln!(
+            f,
+            "\tStatus: {} (last checked in cycle {})",
+            self.status, self.last_checked_cycle
+        )
+    }
+}
+
+/// Known peer with its historical quality, as used to rank outgoing connection candidates
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PeerScoreInfo {
+    /// peer ip address
+    pub ip: IpAddr,
+    /// peer category (Standard, WhiteListed, Bootstrap)
+    pub peer_type: String,
+    /// true if the peer is banned
+    pub banned: bool,
+    /// true if there is an active connection with the peer
+    pub connected: bool,
+    /// decayed time spent connected to the peer, in seconds
+    pub uptime: f64,
+    /// decayed number of successful handshakes
+    pub successful_connections: f64,
+    /// decayed number of failed handshakes
+    pub handshake_failures: f64,
+    /// decayed number of bans
+    pub bans: f64,
+    /// quality score, the higher the better
+    pub score: f64,
+}
+
+impl std::fmt::Display for PeerScoreInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} ({}) {}{}: score {:.2}",
+            self.ip,
+            self.peer_type,
+            display_if_true(self.connected, "connected"),
+            display_if_true(self.banned, "banned"),
+            self.score
+        )?;
+        writeln!(
+            f,
+            "\tuptime: {:.0}s, successful connections: {:.2}, handshake failures: {:.2}, bans: {:.2}",
+            self.uptime, self.successful_connections, self.handshake_failures, self.bans
+        )
+    }
+}
+
+/// Status of an operation in the pool
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OperationPoolStatusInfo {
+    /// operation id
+    pub id: OperationId,
+    /// status of the operation in the pool
+    pub status: OperationPoolStatus,
+}
+
+impl std::fmt::Display for OperationPoolStatusInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Operation {}: {}", self.id, self.status)
+    }
+}
+
 /// Dumb utils function to display nicely boolean value
 fn display_if_true(value: bool, text: &str) -> String {
     if value {
@@ -496,6 +1355,17 @@ pub struct TimeInterval {
     pub end: Option<MassaTime>,
 }
 
+/// Block graph rendering query input structure
+#[derive(Debug, Deserialize, Clone, Copy, Serialize)]
+pub struct GraphDotQuery {
+    /// optional start slot (included)
+    #[serde(default)]
+    pub start: Option<Slot>,
+    /// optional end slot (excluded)
+    #[serde(default)]
+    pub end: Option<Slot>,
+}
+
 /// Datastore entry query input structure
 #[derive(Debug, Deserialize, Clone, Serialize)]
 pub struct DatastoreEntryInput {
@@ -522,6 +1392,217 @@ impl std::fmt::Display for DatastoreEntryOutput {
     }
 }
 
+/// Datastore keys query input structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct DatastoreKeysInput {
+    /// address whose datastore keys are queried
+    pub address: Address,
+    /// only the keys starting with this prefix are returned
+    pub prefix: Vec<u8>,
+    /// last key of the previous page, `None` to get the first page
+    pub last_key: Option<Vec<u8>>,
+    /// maximum number of final keys to return
+    pub max_count: u64,
+}
+
+/// Datastore keys query output structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct DatastoreKeysOutput {
+    /// final datastore keys of the page
+    pub final_keys: Vec<Vec<u8>>,
+    /// candidate datastore keys of the page
+    pub candidate_keys: Vec<Vec<u8>>,
+    /// `last_key` to query the next page with, `None` if there are no more keys
+    pub next_last_key: Option<Vec<u8>>,
+}
+
+impl std::fmt::Display for DatastoreKeysOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "final keys:")?;
+        for key in &self.final_keys {
+            writeln!(f, "\t{:?}", key)?;
+        }
+        writeln!(f, "candidate keys:")?;
+        for key in &self.candidate_keys {
+            writeln!(f, "\t{:?}", key)?;
+        }
+        if let Some(next_last_key) = &self.next_last_key {
+            writeln!(f, "next page after key: {:?}", next_last_key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Contract listing query input structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct ContractsInput {
+    /// last address of the previous page, `None` to get the first page
+    pub last_address: Option<Address>,
+    /// maximum number of contracts to return
+    pub max_count: u64,
+}
+
+/// Final address holding a non-empty bytecode
+#[derive(Debug, Deserialize, Clone, Serialize, PartialEq, Eq)]
+pub struct ContractInfo {
+    /// contract address
+    pub address: Address,
+    /// size of the bytecode in bytes
+    pub bytecode_size: u64,
+    /// hash of the bytecode
+    pub bytecode_hash: Hash,
+}
+
+impl std::fmt::Display for ContractInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} bytes of bytecode, bytecode hash {}",
+            self.address, self.bytecode_size, self.bytecode_hash
+        )
+    }
+}
+
+/// Deferred credits listing query input structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct DeferredCreditsInput {
+    /// first slot of the credits (included), `None` to start from the oldest credits
+    pub start: Option<Slot>,
+    /// slot bounding the credits (excluded), `None` to get all the upcoming credits
+    pub end: Option<Slot>,
+    /// slot and address of the last credit of the previous page, `None` to get the first page
+    pub last: Option<(Slot, Address)>,
+    /// maximum number of credits to return
+    pub max_count: u64,
+}
+
+/// Deferred credit scheduled for an address
+#[derive(Debug, Deserialize, Clone, Serialize, PartialEq, Eq)]
+pub struct DeferredCreditInfo {
+    /// slot at which the address is credited
+    pub slot: Slot,
+    /// credited address
+    pub address: Address,
+    /// credited amount
+    pub amount: Amount,
+}
+
+impl std::fmt::Display for DeferredCreditInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} credited at slot {}",
+            self.address, self.amount, self.slot
+        )
+    }
+}
+
+/// Deferred credit of an address, upcoming or already paid
+#[derive(Debug, Deserialize, Clone, Serialize, PartialEq, Eq)]
+pub struct DeferredCreditScheduleEntry {
+    /// slot at which the address is credited
+    pub slot: Slot,
+    /// credited amount
+    pub amount: Amount,
+    /// true if the credit was already paid
+    pub is_credited: bool,
+    /// roll sell operations reimbursed by the credit, among the remembered activities of the address
+    pub sell_operations: Vec<OperationId>,
+}
+
+impl std::fmt::Display for DeferredCreditScheduleEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Slot {}: {} {}",
+            self.slot,
+            self.amount.with_unit(AmountUnit::Mas),
+            if self.is_credited {
+                "credited"
+            } else {
+                "to be credited"
+            }
+        )?;
+        if !self.sell_operations.is_empty() {
+            write!(f, " for roll sells")?;
+            for operation_id in &self.sell_operations {
+                write!(f, " {}", operation_id)?;
+            }
+        }
+        writeln!(f)
+    }
+}
+
+/// Deferred credits of an address, upcoming and already paid
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct DeferredCreditSchedule {
+    /// credited address
+    pub address: Address,
+    /// credits sorted by slot
+    pub credits: Vec<DeferredCreditScheduleEntry>,
+}
+
+impl std::fmt::Display for DeferredCreditSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Deferred credits of address {}:", self.address)?;
+        for credit in &self.credits {
+            write!(f, "\t{}", credit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deferred credits listing query output structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct DeferredCreditsOutput {
+    /// deferred credits of the page, sorted by slot then address
+    pub credits: Vec<DeferredCreditInfo>,
+    /// `last` to query the next page with, `None` if there are no more credits
+    pub next_last: Option<(Slot, Address)>,
+}
+
+impl std::fmt::Display for DeferredCreditsOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for credit in &self.credits {
+            writeln!(f, "{}", credit)?;
+        }
+        if let Some((slot, address)) = &self.next_last {
+            writeln!(f, "next page after slot {} and address {}", slot, address)?;
+        }
+        Ok(())
+    }
+}
+
+/// Contract listing query output structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct ContractsOutput {
+    /// final contracts of the page, sorted by address
+    pub contracts: Vec<ContractInfo>,
+    /// `last_address` to query the next page with, `None` if there are no more contracts
+    pub next_last_address: Option<Address>,
+}
+
+impl std::fmt::Display for ContractsOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for contract in &self.contracts {
+            writeln!(f, "{}", contract)?;
+        }
+        if let Some(next_last_address) = &self.next_last_address {
+            writeln!(f, "next page after address: {}", next_last_address)?;
+        }
+        Ok(())
+    }
+}
+
+/// Final ledger entry proof query input structure
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct LedgerProofInput {
+    /// address of the entry
+    pub address: Address,
+    /// key of the datastore entry to prove, `None` to prove the balance
+    pub datastore_key: Option<Vec<u8>>,
+}
+
 /// filter used when retrieving SC output events
 #[derive(Default, Debug, Deserialize, Clone, Serialize)]
 pub struct EventFilter {
@@ -543,6 +1624,69 @@ pub struct EventFilter {
     pub is_final: Option<bool>,
 }
 
+impl EventFilter {
+    /// Returns true if the event passes the filter
+    pub fn matches(&self, event: &SCOutputEvent) -> bool {
+        if let Some(start) = self.start {
+            if event.context.slot < start {
+                return false;
+            }
+        }
+        if let Some(end) = self.end {
+            if event.context.slot >= end {
+                return false;
+            }
+        }
+        if let Some(is_final) = self.is_final {
+            if event.context.is_final != is_final {
+                return false;
+            }
+        }
+        match (self.emitter_address, event.context.call_stack.front()) {
+            (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
+            (Some(_), None) => return false,
+            _ => (),
+        }
+        match (
+            self.original_caller_address,
+            event.context.call_stack.back(),
+        ) {
+            (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
+            (Some(_), None) => return false,
+            _ => (),
+        }
+        match (
+            self.original_operation_id,
+            event.context.origin_operation_id,
+        ) {
+            (Some(addr1), Some(addr2)) if addr1 != addr2 => return false,
+            (Some(_), None) => return false,
+            _ => (),
+        }
+        true
+    }
+}
+
+/// Execution status of an operation, notified to the subscribers watching one of its addresses
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OperationStatusNotification {
+    /// id of the operation
+    pub id: OperationId,
+    /// addresses whose ledger entries the operation may change, its creator included
+    pub involved_addresses: Vec<Address>,
+    /// fee paid by the operation
+    pub fee: Amount,
+    /// outcome of the execution
+    pub execution: OperationExecutionInfo,
+}
+
+impl std::fmt::Display for OperationStatusNotification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Operation {}", self.id)?;
+        write!(f, "{}", self.execution)
+    }
+}
+
 /// read only bytecode execution request
 #[derive(Debug, Deserialize, Clone, Serialize)]
 pub struct ReadOnlyBytecodeExecution {
@@ -556,6 +1700,9 @@ pub struct ReadOnlyBytecodeExecution {
     pub address: Option<Address>,
     /// Operation datastore, optional
     pub operation_datastore: Option<Vec<u8>>,
+    /// Simulation of a deployment, to get the addresses of the created smart contracts, optional
+    #[serde(default)]
+    pub deployment_simulation: Option<DeploymentSimulation>,
 }
 
 /// read SC call request
@@ -574,3 +1721,217 @@ pub struct ReadOnlyCall {
     /// caller's address, optional
     pub caller_address: Option<Address>,
 }
+
+/// Subsystem of the node that can be managed individually through the private API
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum NodeSubsystem {
+    /// block and endorsement production
+    Production,
+    /// public API
+    PublicApi,
+}
+
+impl std::fmt::Display for NodeSubsystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeSubsystem::Production => write!(f, "production"),
+            NodeSubsystem::PublicApi => write!(f, "public API"),
+        }
+    }
+}
+
+impl FromStr for NodeSubsystem {
+    type Err = ModelsError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "production" => Ok(NodeSubsystem::Production),
+            "publicapi" | "public_api" => Ok(NodeSubsystem::PublicApi),
+            _ => Err(ModelsError::DeserializeError(format!(
+                "unknown node subsystem {}, expected Production or PublicApi",
+                s
+            ))),
+        }
+    }
+}
+
+/// Action on a subsystem of the node
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SubsystemAction {
+    /// stop the subsystem
+    Stop,
+    /// start the subsystem again, stopping it first if it is running
+    Restart,
+    /// keep the subsystem running but suspend its activity
+    Pause,
+    /// resume the activity of a paused subsystem
+    Resume,
+}
+
+impl std::fmt::Display for SubsystemAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubsystemAction::Stop => write!(f, "stop"),
+            SubsystemAction::Restart => write!(f, "restart"),
+            SubsystemAction::Pause => write!(f, "pause"),
+            SubsystemAction::Resume => write!(f, "resume"),
+        }
+    }
+}
+
+impl FromStr for SubsystemAction {
+    type Err = ModelsError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "stop" => Ok(SubsystemAction::Stop),
+            "restart" => Ok(SubsystemAction::Restart),
+            "pause" => Ok(SubsystemAction::Pause),
+            "resume" => Ok(SubsystemAction::Resume),
+            _ => Err(ModelsError::DeserializeError(format!(
+                "unknown subsystem action {}, expected Stop, Restart, Pause or Resume",
+                s
+            ))),
+        }
+    }
+}
+
+/// ABI call made by the bytecode of a slot replayed in trace mode
+#[derive(Debug, Deserialize, Clone, Serialize, PartialEq, Eq)]
+pub struct AbiCallTrace {
+    /// name of the ABI
+    pub name: String,
+    /// arguments of the call, byte arrays being summarized by their length
+    pub arguments: Vec<String>,
+}
+
+/// What triggered a step of the execution of a slot
+#[derive(Debug, Deserialize, Clone, Serialize, PartialEq, Eq)]
+pub enum ExecutionTraceOrigin {
+    /// execution of an asynchronous message
+    AsyncMessage {
+        /// sender of the message
+        sender: Address,
+        /// destination of the message
+        destination: Address,
+        /// function of the destination handling the message
+        handler: String,
+    },
+    /// execution of an operation of the block
+    Operation(OperationId),
+}
+
+/// Step of the execution of a slot replayed in trace mode
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct ExecutionTraceStep {
+    /// message or operation executed
+    pub origin: ExecutionTraceOrigin,
+    /// ABI calls made during the step, in call order
+    pub abi_calls: Vec<AbiCallTrace>,
+    /// gas used by the operation, `None` for asynchronous messages
+    pub gas_used: Option<u64>,
+    /// block gas left after the operation, `None` for asynchronous messages
+    pub remaining_block_gas: Option<u64>,
+    /// error that made the step fail, if any
+    pub error: Option<String>,
+}
+
+/// Structured trace of the re-execution of a slot
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct SlotExecutionTrace {
+    /// replayed slot
+    pub slot: Slot,
+    /// block at the slot, `None` on a miss
+    pub block_id: Option<BlockId>,
+    /// execution steps, asynchronous messages first then the operations of the block
+    pub steps: Vec<ExecutionTraceStep>,
+}
+
+impl std::fmt::Display for SlotExecutionTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.block_id {
+            Some(block_id) => writeln!(f, "Slot {} (block {})", self.slot, block_id)?,
+            None => writeln!(f, "Slot {} (miss)", self.slot)?,
+        }
+        for step in self.steps.iter() {
+            match &step.origin {
+                ExecutionTraceOrigin::AsyncMessage {
+                    sender,
+                    destination,
+                    handler,
+                } => writeln!(
+                    f,
+                    "\tAsync message from {} to {}::{}",
+                    sender, destination, handler
+                )?,
+                ExecutionTraceOrigin::Operation(operation_id) => {
+                    writeln!(f, "\tOperation {}", operation_id)?
+                }
+            }
+            for call in step.abi_calls.iter() {
+                writeln!(f, "\t\t{}({})", call.name, call.arguments.join(", "))?;
+            }
+            if let (Some(gas_used), Some(remaining_block_gas)) =
+                (step.gas_used, step.remaining_block_gas)
+            {
+                writeln!(
+                    f,
+                    "\t\tGas used: {}, remaining block gas: {}",
+                    gas_used, remaining_block_gas
+                )?;
+            }
+            if let Some(error) = &step.error {
+                writeln!(f, "\t\tError: {}", error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EndorsementDiagnostic;
+    use crate::{address::Address, block::BlockId, endorsement::EndorsementId, slot::Slot};
+    use massa_hash::Hash;
+
+    #[test]
+    fn test_endorsement_diagnostic_display() {
+        let address = Address(Hash::compute_from(b"address"));
+        let endorsement_id =
+            EndorsementId::from_bytes(Hash::compute_from(b"endorsement").to_bytes());
+        let block_id = BlockId(Hash::compute_from(b"block"));
+        let diagnostic = |produced, in_pool, included_in| EndorsementDiagnostic {
+            address,
+            slot: Slot::new(3, 1),
+            index: 2,
+            produced,
+            in_pool,
+            included_in,
+        };
+        let prefix = format!(
+            "Address {} / slot (period: 3, thread: 1) / index 2: ",
+            address
+        );
+
+        assert_eq!(
+            diagnostic(None, false, Vec::new()).to_string(),
+            format!("{}missed (no endorsement produced)\n", prefix)
+        );
+        assert_eq!(
+            diagnostic(Some(endorsement_id), true, Vec::new()).to_string(),
+            format!(
+                "{}endorsement {} not included (in pool)\n",
+                prefix, endorsement_id
+            )
+        );
+        assert_eq!(
+            diagnostic(Some(endorsement_id), false, Vec::new()).to_string(),
+            format!("{}endorsement {} not included\n", prefix, endorsement_id)
+        );
+        assert_eq!(
+            diagnostic(Some(endorsement_id), true, vec![block_id, block_id]).to_string(),
+            format!(
+                "{}endorsement {} included in block(s) {}, {}\n",
+                prefix, endorsement_id, block_id, block_id
+            )
+        );
+    }
+}
@@ -1,5 +1,8 @@
 use super::*;
 use crate::amount::Amount;
+use crate::error::ModelsError;
+use crate::slot::Slot;
+use crate::timeslots::get_block_slot_timestamp;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
@@ -46,6 +49,13 @@ impl Default for CompactConfig {
     }
 }
 
+impl CompactConfig {
+    /// Gets the timestamp in milliseconds of a slot of the network described by this configuration
+    pub fn get_slot_timestamp(&self, slot: Slot) -> Result<MassaTime, ModelsError> {
+        get_block_slot_timestamp(self.thread_count, self.t0, self.genesis_timestamp, slot)
+    }
+}
+
 impl Display for CompactConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(
@@ -71,12 +71,19 @@ lazy_static::lazy_static! {
     };
 }
 
+/// Chain id of the testnet
+pub const TESTNET_CHAIN_ID: u64 = 77658366;
+/// Chain id of sandbox networks
+pub const SANDBOX_CHAIN_ID: u64 = 77;
 /// Price of a roll in the network
 pub const ROLL_PRICE: Amount = Amount::from_mantissa_scale(100, 0);
 /// Block reward is given for each block creation
 pub const BLOCK_REWARD: Amount = Amount::from_mantissa_scale(3, 1);
 /// Cost to store one byte in the ledger
 pub const LEDGER_COST_PER_BYTE: Amount = Amount::from_mantissa_scale(25, 5);
+/// Rent charged at the end of each cycle for each byte of bytecode and datastore held by a ledger entry.
+/// Storage rent is disabled if `None`.
+pub const STORAGE_RENT_PER_BYTE_PER_CYCLE: Option<Amount> = None;
 /// Cost for a base entry (address + balance (5 bytes constant))
 pub const LEDGER_ENTRY_BASE_SIZE: usize = ADDRESS_SIZE_BYTES + 8;
 /// Cost for a base entry datastore 10 bytes constant to avoid paying more for longer keys
@@ -99,6 +106,10 @@ pub const MAX_BLOCK_SIZE: u32 = 500_000;
 pub const MAX_ASYNC_POOL_LENGTH: u64 = 10_000;
 /// Maximum data size in async message
 pub const MAX_ASYNC_MESSAGE_DATA: u64 = 1_000_000;
+/// Maximum number of pending async messages sent to the same destination address
+pub const MAX_ASYNC_MESSAGES_PER_DESTINATION: u64 = MAX_ASYNC_POOL_LENGTH / 10;
+/// Maximum total gas of the pending async messages sent to the same destination address
+pub const MAX_ASYNC_GAS_PER_DESTINATION: u64 = 100 * MAX_ASYNC_GAS;
 /// Maximum operation validity period count
 pub const OPERATION_VALIDITY_PERIODS: u64 = 10;
 /// cycle duration in periods
@@ -133,6 +144,16 @@ pub const MAX_OPERATION_DATASTORE_KEY_LENGTH: u8 = MAX_DATASTORE_KEY_LENGTH;
 pub const MAX_DATASTORE_VALUE_LENGTH: u64 = 10_000_000;
 /// Maximum length of a datastore value
 pub const MAX_BYTECODE_LENGTH: u64 = 10_000_000;
+/// Maximum number of elements in the call stack of a smart contract execution
+pub const MAX_CALL_STACK_DEPTH: usize = 64;
+/// Maximum number of events a smart contract call can emit
+pub const MAX_EVENT_COUNT_PER_CALL: u64 = 256;
+/// Maximum total size in bytes of the events a smart contract call can emit
+pub const MAX_EVENT_SIZE_PER_CALL: u64 = 65_536;
+/// Maximum number of events the smart contracts executed at a slot can emit
+pub const MAX_EVENT_COUNT_PER_SLOT: u64 = 4_096;
+/// Maximum total size in bytes of the events the smart contracts executed at a slot can emit
+pub const MAX_EVENT_SIZE_PER_SLOT: u64 = 1_048_576;
 /// Maximum length of an operation datastore value
 pub const MAX_OPERATION_DATASTORE_VALUE_LENGTH: u64 = 1_000;
 /// Maximum ledger changes in a block
@@ -187,6 +208,8 @@ pub const PROTOCOL_CONTROLLER_CHANNEL_SIZE: usize = 1024;
 pub const PROTOCOL_EVENT_CHANNEL_SIZE: usize = 1024;
 /// Pool controller channel size
 pub const POOL_CONTROLLER_CHANNEL_SIZE: usize = 1024;
+/// Size of the channels broadcasting consensus and execution outputs to the API subscribers
+pub const BROADCAST_CHANNEL_SIZE: usize = 1024;
 
 // ***********************
 // Constants used for execution module (injected from ConsensusConfig)
@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::address::Address;
 use crate::prehash::PreHashed;
 use crate::slot::{Slot, SlotDeserializer, SlotSerializer};
 use crate::wrapped::{Id, Wrapped, WrappedContent};
@@ -321,6 +322,37 @@ impl Deserializer<Endorsement> for EndorsementDeserializerLW {
     }
 }
 
+/// Conflicting endorsements created by the same address for the same slot and index
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EndorsementEquivocation {
+    /// address that created the conflicting endorsements
+    pub creator: Address,
+    /// slot in which the endorsements can be included
+    pub slot: Slot,
+    /// endorsement index inside the including block
+    pub index: u32,
+    /// the conflicting endorsements with the block each of them endorses, first received first
+    pub endorsements: Vec<(EndorsementId, BlockId)>,
+}
+
+impl Display for EndorsementEquivocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Creator {} equivocated at slot {} index {}:",
+            self.creator, self.slot, self.index
+        )?;
+        for (endorsement_id, endorsed_block) in &self.endorsements {
+            writeln!(
+                f,
+                "\tendorsement {} endorsing {}",
+                endorsement_id, endorsed_block
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::wrapped::{WrappedDeserializer, WrappedSerializer};
@@ -28,7 +28,7 @@ pub enum ModelsError {
     ThreadOverflowError,
     /// period overflow error
     PeriodOverflowError,
-    /// amount parse error
+    /// amount parse error: {0}
     AmountParseError(String),
     /// address parsing error
     AddressParseError,
@@ -46,6 +46,8 @@ pub enum ModelsError {
     InvalidRollUpdate(String),
     /// Ledger changes, Amount overflow
     AmountOverflowError,
+    /// Amount underflow
+    AmountUnderflowError,
     /// Wrong prefix for hash: expected {0}, got {1}
     WrongPrefix(String, String),
     /// Wrong operation id size deduced on join
@@ -1,6 +1,6 @@
 use std::{collections::VecDeque, fmt::Display};
 
-use crate::{output_event::SCOutputEvent, slot::Slot};
+use crate::{address::Address, amount::Amount, output_event::SCOutputEvent, slot::Slot};
 use serde::{Deserialize, Serialize};
 
 /// The result of the read-only execution.
@@ -13,6 +13,17 @@ pub enum ReadOnlyResult {
     Ok,
 }
 
+/// Deployment simulation of a read-only execution: the smart contracts it creates get the addresses
+/// they would get in an operation executed at `slot`, after `created_addr_index` other creations at that slot.
+/// Read-only executions otherwise create addresses that can never exist in an active execution.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DeploymentSimulation {
+    /// slot of the simulated execution
+    pub slot: Slot,
+    /// number of addresses created at that slot before the simulated execution
+    pub created_addr_index: u64,
+}
+
 /// The response to a request for a read-only execution.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ExecuteReadOnlyResponse {
@@ -24,6 +35,12 @@ pub struct ExecuteReadOnlyResponse {
     pub output_events: VecDeque<SCOutputEvent>,
     /// The gas cost for the execution
     pub gas_cost: u64,
+    /// The smart contract addresses created by the execution, in creation order
+    #[serde(default)]
+    pub created_addresses: Vec<Address>,
+    /// The storage costs of the created addresses, as paid by their creator
+    #[serde(default)]
+    pub storage_cost: Amount,
 }
 
 impl Display for ExecuteReadOnlyResponse {
@@ -38,6 +55,12 @@ impl Display for ExecuteReadOnlyResponse {
                 ReadOnlyResult::Ok => "ok".to_string(),
             }
         )?;
+        if !self.created_addresses.is_empty() {
+            writeln!(f, "Created addresses (storage cost {}):", self.storage_cost)?;
+            for address in self.created_addresses.iter() {
+                writeln!(f, "\t{}", address)?;
+            }
+        }
         if !self.output_events.is_empty() {
             writeln!(f, "Generated events:",)?;
             for event in self.output_events.iter() {
@@ -1,11 +1,11 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use crate::datastore::{Datastore, DatastoreDeserializer, DatastoreSerializer};
-use crate::prehash::{PreHashSet, PreHashed};
+use crate::prehash::{PreHashMap, PreHashSet, PreHashed};
 use crate::wrapped::{Id, Wrapped, WrappedContent, WrappedDeserializer, WrappedSerializer};
 use crate::{
     address::{Address, AddressDeserializer},
-    amount::{Amount, AmountDeserializer, AmountSerializer},
+    amount::{Amount, AmountDeserializer, AmountSerializer, AmountUnit},
     error::ModelsError,
     serialization::{StringDeserializer, StringSerializer, VecU8Deserializer, VecU8Serializer},
 };
@@ -14,6 +14,7 @@ use massa_serialization::{
     Deserializer, SerializeError, Serializer, U16VarIntDeserializer, U16VarIntSerializer,
     U32VarIntDeserializer, U32VarIntSerializer, U64VarIntDeserializer, U64VarIntSerializer,
 };
+use massa_signature::KeyPair;
 use nom::error::context;
 use nom::multi::length_count;
 use nom::sequence::tuple;
@@ -214,7 +215,7 @@ pub struct Operation {
 
 impl std::fmt::Display for Operation {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "Fee: {}", self.fee)?;
+        writeln!(f, "Fee: {}", self.fee.with_unit(AmountUnit::Mas))?;
         writeln!(f, "Expire period: {}", self.expire_period)?;
         writeln!(f, "Operation type: {}", self.op)?;
         Ok(())
@@ -226,6 +227,203 @@ pub type WrappedOperation = Wrapped<Operation, OperationId>;
 
 impl WrappedContent for Operation {}
 
+impl Operation {
+    /// Creates a wrapped operation whose creator signs the hash given by `compute_operation_signed_hash`
+    pub fn new_signed(
+        content: Operation,
+        keypair: &KeyPair,
+        chain_id: u64,
+        chain_id_activation_period: u64,
+    ) -> Result<WrappedOperation, ModelsError> {
+        let expire_period = content.expire_period;
+        Operation::new_wrapped_signing(content, OperationSerializer::new(), keypair, |id_hash| {
+            compute_operation_signed_hash(
+                id_hash,
+                expire_period,
+                chain_id,
+                chain_id_activation_period,
+            )
+        })
+    }
+}
+
+impl WrappedOperation {
+    /// get the hash signed by the creator of the operation, see `compute_operation_signed_hash`
+    pub fn compute_operation_signed_hash(
+        &self,
+        chain_id: u64,
+        chain_id_activation_period: u64,
+    ) -> Hash {
+        compute_operation_signed_hash(
+            self.id.get_hash(),
+            self.content.expire_period,
+            chain_id,
+            chain_id_activation_period,
+        )
+    }
+
+    /// check if the operation has been signed by its creator for the network of `chain_id`
+    pub fn verify_operation_signature(
+        &self,
+        chain_id: u64,
+        chain_id_activation_period: u64,
+    ) -> Result<(), ModelsError> {
+        Ok(self.creator_public_key.verify_signature(
+            &self.compute_operation_signed_hash(chain_id, chain_id_activation_period),
+            &self.signature,
+        )?)
+    }
+}
+
+/// Why an operation left the pool, or was refused by it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OperationEvictionReason {
+    /// the validity range of the operation ended at or before the latest final period of its thread
+    Expired {
+        /// last period of the validity range of the operation
+        expire_period: u64,
+        /// latest final period of the thread of the operation
+        last_final_period: u64,
+    },
+    /// the validity range of the operation started too far in the future
+    TooFarInTheFuture {
+        /// first period of the validity range of the operation
+        validity_start_period: u64,
+        /// max validity start period accepted in the thread of the operation
+        max_validity_start_period: u64,
+    },
+    /// the pool of the thread of the operation was full, and its fee was among the lowest
+    LowFee,
+    /// the parking area was full, and the validity of the operation started later than the other parked ones
+    ParkingAreaFull,
+    /// the fee of the operation was below the minimal fee accepted by the pool
+    FeeTooLow {
+        /// fee of the operation
+        fee: Amount,
+        /// minimal fee accepted by the pool
+        min_fee: Amount,
+    },
+    /// the signature of the operation was invalid
+    InvalidSignature,
+    /// the candidate balance of the creator of the operation could not cover its fee
+    InsufficientBalance {
+        /// fee of the operation
+        fee: Amount,
+        /// candidate balance of the creator of the operation
+        balance: Amount,
+    },
+}
+
+impl std::fmt::Display for OperationEvictionReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationEvictionReason::Expired {
+                expire_period,
+                last_final_period,
+            } => write!(
+                f,
+                "expired at period {}, the latest final period being {}",
+                expire_period, last_final_period
+            ),
+            OperationEvictionReason::TooFarInTheFuture {
+                validity_start_period,
+                max_validity_start_period,
+            } => write!(
+                f,
+                "validity starting at period {}, later than the max accepted period {}",
+                validity_start_period, max_validity_start_period
+            ),
+            OperationEvictionReason::LowFee => write!(f, "fee too low for the full pool"),
+            OperationEvictionReason::ParkingAreaFull => {
+                write!(f, "validity starting too late for the full parking area")
+            }
+            OperationEvictionReason::FeeTooLow { fee, min_fee } => {
+                write!(f, "fee of {} below the minimal fee of {}", fee, min_fee)
+            }
+            OperationEvictionReason::InvalidSignature => write!(f, "invalid signature"),
+            OperationEvictionReason::InsufficientBalance { fee, balance } => write!(
+                f,
+                "creator balance of {} not covering the fee of {}",
+                balance, fee
+            ),
+        }
+    }
+}
+
+/// Record of an operation that left the pool or was refused by it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationEviction {
+    /// why the operation was evicted
+    pub reason: OperationEvictionReason,
+    /// latest final period of the thread of the operation at the time of the eviction
+    pub final_period: u64,
+}
+
+/// Status of an operation in the pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OperationPoolStatus {
+    /// waiting in the pool to be included in a block
+    Pending,
+    /// waiting in the parking area for its validity start to get close enough
+    Parked,
+    /// recently removed from the pool or refused by it
+    Evicted(OperationEviction),
+    /// neither in the pool nor among its recent evictions
+    Unknown,
+}
+
+impl std::fmt::Display for OperationPoolStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationPoolStatus::Pending => write!(f, "pending"),
+            OperationPoolStatus::Parked => write!(f, "parked"),
+            OperationPoolStatus::Evicted(eviction) => write!(
+                f,
+                "evicted at final period {}: {}",
+                eviction.final_period, eviction.reason
+            ),
+            OperationPoolStatus::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// How an operation reached the pool of the node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OperationOrigin {
+    /// submitted through the API of the node, or created by the node itself
+    Local,
+    /// propagated by the other nodes of the network
+    Network,
+}
+
+/// Number of operations of the pool per origin
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationOriginCounts {
+    /// operations submitted through the API of the node or created by the node itself
+    pub local: usize,
+    /// operations propagated by the other nodes of the network
+    pub network: usize,
+}
+
+/// Computes the hash signed by the creator of an operation, from its id hash.
+///
+/// Operations expiring at or after `chain_id_activation_period` sign their id hash prefixed by the chain id
+/// of their network, so that they can not be replayed on another network.
+/// Operations expiring before it sign their id hash alone, as signed by the previous node and client versions.
+pub fn compute_operation_signed_hash(
+    id_hash: &Hash,
+    expire_period: u64,
+    chain_id: u64,
+    chain_id_activation_period: u64,
+) -> Hash {
+    if expire_period < chain_id_activation_period {
+        return *id_hash;
+    }
+    let mut hash_data = chain_id.to_be_bytes().to_vec();
+    hash_data.extend(id_hash.to_bytes());
+    Hash::compute_from(&hash_data)
+}
+
 /// Serializer for `Operation`
 pub struct OperationSerializer {
     u64_serializer: U64VarIntSerializer,
@@ -435,7 +633,7 @@ impl std::fmt::Display for OperationType {
             } => {
                 writeln!(f, "Transaction:")?;
                 writeln!(f, "\t- Recipient:{}", recipient_address)?;
-                writeln!(f, "\t  Amount:{}", amount)?;
+                writeln!(f, "\t  Amount:{}", amount.with_unit(AmountUnit::Mas))?;
             }
             OperationType::RollBuy { roll_count } => {
                 writeln!(f, "Buy rolls:")?;
@@ -453,7 +651,7 @@ impl std::fmt::Display for OperationType {
             } => {
                 writeln!(f, "ExecuteSC: ")?;
                 writeln!(f, "\t- max_gas:{}", max_gas)?;
-                writeln!(f, "\t- gas_price:{}", gas_price)?;
+                writeln!(f, "\t- gas_price:{}", gas_price.with_unit(AmountUnit::Mas))?;
             },
             OperationType::CallSC {
                 max_gas,
@@ -468,8 +666,8 @@ impl std::fmt::Display for OperationType {
                 writeln!(f, "\t- target function:{}", target_func)?;
                 writeln!(f, "\t- target parameter:{}", param)?;
                 writeln!(f, "\t- max_gas:{}", max_gas)?;
-                writeln!(f, "\t- gas_price:{}", gas_price)?;
-                writeln!(f, "\t- coins:{}", coins)?;
+                writeln!(f, "\t- gas_price:{}", gas_price.with_unit(AmountUnit::Mas))?;
+                writeln!(f, "\t- coins:{}", coins.with_unit(AmountUnit::Mas))?;
             }
         }
         Ok(())
@@ -823,6 +1021,12 @@ impl WrappedOperation {
         self.get_gas_coins().saturating_add(self.content.fee)
     }
 
+    /// Get the fee density of the operation:
+    /// the total fee in nanoMAS per byte of the serialized operation
+    pub fn get_fee_density(&self) -> u64 {
+        self.get_total_fee().to_raw() / (self.serialized_size() as u64).max(1)
+    }
+
     /// get the addresses that are involved in this operation from a ledger point of view
     pub fn get_ledger_involved_addresses(&self) -> PreHashSet<Address> {
         let mut res = PreHashSet::<Address>::default();
@@ -889,6 +1093,9 @@ impl WrappedOperation {
 /// Set of operation id's prefix
 pub type OperationPrefixIds = PreHashSet<OperationPrefixId>;
 
+/// Announced operations: operation id's prefix mapped to the fee density of the operation
+pub type OperationAnnouncements = PreHashMap<OperationPrefixId, u64>;
+
 /// Serializer for `Vec<OperationId>`
 pub struct OperationIdsSerializer {
     u32_serializer: U32VarIntSerializer,
@@ -1142,6 +1349,119 @@ impl Serializer<OperationPrefixIds> for OperationPrefixIdsSerializer {
     }
 }
 
+/// Serializer for `OperationAnnouncements`
+pub struct OperationAnnouncementsSerializer {
+    u32_serializer: U32VarIntSerializer,
+    u64_serializer: U64VarIntSerializer,
+}
+
+impl OperationAnnouncementsSerializer {
+    /// Creates a new `OperationAnnouncementsSerializer`
+    pub const fn new() -> Self {
+        Self {
+            u32_serializer: U32VarIntSerializer::new(),
+            u64_serializer: U64VarIntSerializer::new(),
+        }
+    }
+}
+
+impl Default for OperationAnnouncementsSerializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Serializer<OperationAnnouncements> for OperationAnnouncementsSerializer {
+    /// ## Example:
+    /// ```rust
+    /// use massa_models::operation::{OperationPrefixId, OperationAnnouncements, OperationAnnouncementsSerializer, OPERATION_ID_PREFIX_SIZE_BYTES};
+    /// use massa_serialization::Serializer;
+    ///
+    /// let mut announcements = OperationAnnouncements::default();
+    /// announcements.insert(OperationPrefixId::from(&[20; OPERATION_ID_PREFIX_SIZE_BYTES]), 1000);
+    /// let mut buffer = Vec::new();
+    /// OperationAnnouncementsSerializer::new().serialize(&announcements, &mut buffer).unwrap();
+    /// ```
+    fn serialize(
+        &self,
+        value: &OperationAnnouncements,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), SerializeError> {
+        let list_len: u32 = value.len().try_into().map_err(|_| {
+            SerializeError::NumberTooBig(
+                "could not encode OperationAnnouncements list length as u32".into(),
+            )
+        })?;
+        self.u32_serializer.serialize(&list_len, buffer)?;
+        for (prefix, fee_density) in value {
+            buffer.extend(Vec::<u8>::from(prefix));
+            self.u64_serializer.serialize(fee_density, buffer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deserializer for `OperationAnnouncements`
+pub struct OperationAnnouncementsDeserializer {
+    length_deserializer: U32VarIntDeserializer,
+    pref_deserializer: OperationPrefixIdDeserializer,
+    fee_density_deserializer: U64VarIntDeserializer,
+}
+
+impl OperationAnnouncementsDeserializer {
+    /// Creates a new `OperationAnnouncementsDeserializer`
+    pub const fn new(max_operations_per_message: u32) -> Self {
+        Self {
+            length_deserializer: U32VarIntDeserializer::new(
+                Included(0),
+                Included(max_operations_per_message),
+            ),
+            pref_deserializer: OperationPrefixIdDeserializer::new(),
+            fee_density_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+        }
+    }
+}
+
+impl Deserializer<OperationAnnouncements> for OperationAnnouncementsDeserializer {
+    /// ## Example:
+    /// ```rust
+    /// use massa_models::operation::{OperationPrefixId, OperationAnnouncements, OperationAnnouncementsSerializer, OperationAnnouncementsDeserializer, OPERATION_ID_PREFIX_SIZE_BYTES};
+    /// use massa_serialization::{Serializer, Deserializer, DeserializeError};
+    ///
+    /// let mut announcements = OperationAnnouncements::default();
+    /// announcements.insert(OperationPrefixId::from(&[20; OPERATION_ID_PREFIX_SIZE_BYTES]), 1000);
+    /// announcements.insert(OperationPrefixId::from(&[21; OPERATION_ID_PREFIX_SIZE_BYTES]), 0);
+    /// let mut buffer = Vec::new();
+    /// OperationAnnouncementsSerializer::new().serialize(&announcements, &mut buffer).unwrap();
+    /// let (rest, deserialized) = OperationAnnouncementsDeserializer::new(1000).deserialize::<DeserializeError>(&buffer).unwrap();
+    /// assert_eq!(rest.len(), 0);
+    /// assert_eq!(deserialized, announcements);
+    /// ```
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], OperationAnnouncements, E> {
+        context(
+            "Failed OperationAnnouncements deserialization",
+            length_count(
+                context("Failed length deserialization", |input| {
+                    self.length_deserializer.deserialize(input)
+                }),
+                tuple((
+                    context("Failed OperationPrefixId deserialization", |input| {
+                        self.pref_deserializer.deserialize(input)
+                    }),
+                    context("Failed fee density deserialization", |input| {
+                        self.fee_density_deserializer.deserialize(input)
+                    }),
+                )),
+            ),
+        )
+        .map(|announcements| announcements.into_iter().collect())
+        .parse(buffer)
+    }
+}
+
 /// Serializer for `Operations`
 pub struct OperationsSerializer {
     u32_serializer: U32VarIntSerializer,
@@ -1291,12 +1611,11 @@ mod tests {
     use crate::config::{
         MAX_DATASTORE_VALUE_LENGTH, MAX_FUNCTION_NAME_LENGTH, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
         MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH,
-        MAX_PARAMETERS_SIZE,
+        MAX_PARAMETERS_SIZE, TESTNET_CHAIN_ID,
     };
 
     use super::*;
     use massa_serialization::DeserializeError;
-    use massa_signature::KeyPair;
     use serial_test::serial;
     use std::collections::BTreeMap;
 
@@ -1371,6 +1690,24 @@ mod tests {
         assert_eq!(res_op, op);
 
         assert_eq!(op.get_validity_range(10), 40..=50);
+
+        // operations expiring before the activation period sign their id hash alone
+        res_op.verify_signature().unwrap();
+        res_op
+            .verify_operation_signature(TESTNET_CHAIN_ID, 51)
+            .unwrap();
+        assert!(res_op
+            .verify_operation_signature(TESTNET_CHAIN_ID, 50)
+            .is_err());
+
+        // the other ones only hold for the chain id of their network
+        let op = Operation::new_signed(op.content, &sender_keypair, TESTNET_CHAIN_ID, 50).unwrap();
+        assert_eq!(op.id, res_op.id);
+        op.verify_operation_signature(TESTNET_CHAIN_ID, 50).unwrap();
+        assert!(op
+            .verify_operation_signature(TESTNET_CHAIN_ID + 1, 50)
+            .is_err());
+        assert!(op.verify_signature().is_err());
     }
 
     #[test]
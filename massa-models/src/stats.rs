@@ -1,6 +1,9 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::address::Address;
+use crate::amount::Amount;
 use crate::slot::Slot;
+use massa_hash::Hash;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
 use std::fmt::Formatter;
@@ -18,6 +21,14 @@ pub struct ExecutionStats {
     pub final_executed_operations_count: usize,
     /// active execution cursor slot
     pub active_cursor: Slot,
+    /// block producers on track to exceed the maximal miss ratio of the current cycle
+    pub miss_ratio_warnings: Vec<MissRatioProjection>,
+    /// hash of the gas cost table, identical on all the nodes of the network
+    pub gas_costs_hash: Hash,
+    /// number of read-only requests served from the cache since the start of the node
+    pub readonly_cache_hits: u64,
+    /// number of read-only requests executed since the start of the node, caching enabled
+    pub readonly_cache_misses: u64,
 }
 
 impl std::fmt::Display for ExecutionStats {
@@ -44,6 +55,205 @@ impl std::fmt::Display for ExecutionStats {
             self.final_executed_operations_count
         )?;
         writeln!(f, "\tActive cursor: {}", self.active_cursor)?;
+        writeln!(f, "\tGas cost table hash: {}", self.gas_costs_hash)?;
+        writeln!(
+            f,
+            "\tRead-only cache hits/misses: {}/{}",
+            self.readonly_cache_hits, self.readonly_cache_misses
+        )?;
+        if !self.miss_ratio_warnings.is_empty() {
+            writeln!(f, "\tBlock producers on track to lose their rolls:")?;
+            for projection in &self.miss_ratio_warnings {
+                writeln!(f, "\t\t{}", projection)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// projected end-of-cycle block production of an address
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MissRatioProjection {
+    /// block producer address
+    pub address: Address,
+    /// cycle of the projection
+    pub cycle: u64,
+    /// final blocks produced so far in the cycle
+    pub block_success_count: u64,
+    /// final blocks missed so far in the cycle
+    pub block_failure_count: u64,
+    /// block production draws of the address left in the cycle
+    pub remaining_draws: u64,
+    /// miss ratio at the end of the cycle if the address keeps missing blocks at its current rate
+    pub projected_miss_ratio: f64,
+    /// minimal number of remaining draws to produce to stay within the maximal miss ratio,
+    /// `None` if the rolls will be lost whatever happens
+    pub required_successes: Option<u64>,
+}
+
+impl std::fmt::Display for MissRatioProjection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} in cycle {}: {} produced, {} missed, projected miss ratio {:.2}%, ",
+            self.address,
+            self.cycle,
+            self.block_success_count,
+            self.block_failure_count,
+            self.projected_miss_ratio * 100.0
+        )?;
+        match self.required_successes {
+            Some(count) => write!(
+                f,
+                "{} of the {} remaining draws must be produced",
+                count, self.remaining_draws
+            ),
+            None => write!(
+                f,
+                "rolls will be lost even if the {} remaining draws are produced",
+                self.remaining_draws
+            ),
+        }
+    }
+}
+
+/// free disk space status of the node's storage paths
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiskSpaceStatus {
+    /// enough free space
+    #[default]
+    Ok,
+    /// free space is under the warning threshold
+    Low,
+    /// free space is under the critical threshold: the node stops accepting new operations
+    /// and pauses the writes of its block graph store and state snapshots
+    Critical,
+}
+
+impl std::fmt::Display for DiskSpaceStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiskSpaceStatus::Ok => write!(f, "ok"),
+            DiskSpaceStatus::Low => write!(f, "low"),
+            DiskSpaceStatus::Critical => write!(f, "critically low"),
+        }
+    }
+}
+
+/// outcome of the background integrity scrubbing of the final ledger
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LedgerIntegrityStatus {
+    /// number of complete scrubbing passes over the ledger since the node started
+    pub completed_passes: u64,
+    /// number of ledger entries checked since the node started
+    pub checked_entries: u64,
+    /// number of anomalies found since the node started
+    pub anomaly_count: u64,
+    /// description of the latest anomaly found, if any
+    pub last_anomaly: Option<String>,
+}
+
+impl std::fmt::Display for LedgerIntegrityStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} anomalies found in {} checked entries ({} complete passes)",
+            self.anomaly_count, self.checked_entries, self.completed_passes
+        )?;
+        if let Some(anomaly) = &self.last_anomaly {
+            write!(f, ", latest: {}", anomaly)?;
+        }
+        Ok(())
+    }
+}
+
+/// sessions served by the bootstrap server since the node started
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct BootstrapServerStats {
+    /// number of bootstrap sessions currently running
+    pub active_sessions: usize,
+    /// number of bootstrap sessions that completed successfully
+    pub served_sessions: u64,
+    /// number of bootstrap sessions that ended with an error
+    pub failed_sessions: u64,
+    /// number of sessions started by allowlisted IPs, which bypass the limits
+    pub allowlisted_sessions: u64,
+    /// number of connections refused because the server or the subnet of the client had no session available
+    pub refused_no_slot: u64,
+    /// number of connections refused because the IP or the subnet of the client retried too early
+    pub refused_rate_limited: u64,
+}
+
+impl std::fmt::Display for BootstrapServerStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} active sessions, {} served, {} failed ({} allowlisted), refused {} without available session and {} rate limited",
+            self.active_sessions,
+            self.served_sessions,
+            self.failed_sessions,
+            self.allowlisted_sessions,
+            self.refused_no_slot,
+            self.refused_rate_limited
+        )
+    }
+}
+
+/// outcome of a full integrity check of the final ledger, see `verify_ledger_integrity`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LedgerIntegrityReport {
+    /// number of ledger entries checked
+    pub checked_entries: u64,
+    /// ledger hash stored in the ledger metadata, `None` if it is corrupted
+    pub stored_hash: Option<Hash>,
+    /// ledger hash recomputed from all the ledger entries and the ledger slot
+    pub computed_hash: Hash,
+    /// description of the ledger entries whose hash is not indexed in the Merkle tree
+    pub mismatched_keys: Vec<String>,
+    /// number of entries indexed in the Merkle tree without a matching ledger entry
+    pub orphan_merkle_entries: u64,
+    /// true if the ledger hash and the Merkle tree were rebuilt from the ledger entries
+    pub repaired: bool,
+}
+
+impl LedgerIntegrityReport {
+    /// true if the stored metadata matched the ledger entries when checked
+    pub fn is_consistent(&self) -> bool {
+        self.stored_hash == Some(self.computed_hash)
+            && self.mismatched_keys.is_empty()
+            && self.orphan_merkle_entries == 0
+    }
+}
+
+impl std::fmt::Display for LedgerIntegrityReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Ledger integrity check:")?;
+        writeln!(f, "\tChecked entries: {}", self.checked_entries)?;
+        match self.stored_hash {
+            Some(stored_hash) => writeln!(f, "\tStored ledger hash: {}", stored_hash)?,
+            None => writeln!(f, "\tStored ledger hash: corrupted")?,
+        }
+        writeln!(f, "\tComputed ledger hash: {}", self.computed_hash)?;
+        writeln!(
+            f,
+            "\tEntries missing from the Merkle tree: {}",
+            self.mismatched_keys.len()
+        )?;
+        for key in &self.mismatched_keys {
+            writeln!(f, "\t\t{}", key)?;
+        }
+        writeln!(
+            f,
+            "\tOrphan Merkle tree entries: {}",
+            self.orphan_merkle_entries
+        )?;
+        if self.is_consistent() {
+            writeln!(f, "\tThe ledger is consistent")?;
+        } else if self.repaired {
+            writeln!(f, "\tThe ledger metadata was rebuilt from the entries")?;
+        } else {
+            writeln!(f, "\tThe ledger is inconsistent")?;
+        }
         Ok(())
     }
 }
@@ -61,6 +271,10 @@ pub struct NetworkStats {
     pub banned_peer_count: u64,
     /// active node count
     pub active_node_count: u64,
+    /// bytes read from all the connections since the start of the node
+    pub bytes_read: u64,
+    /// bytes written to all the connections since the start of the node
+    pub bytes_written: u64,
 }
 
 impl std::fmt::Display for NetworkStats {
@@ -71,6 +285,80 @@ impl std::fmt::Display for NetworkStats {
         writeln!(f, "\tKnown peers: {}", self.known_peer_count)?;
         writeln!(f, "\tBanned peers: {}", self.banned_peer_count)?;
         writeln!(f, "\tActive nodes: {}", self.active_node_count)?;
+        writeln!(f, "\tBytes read: {}", self.bytes_read)?;
+        writeln!(f, "\tBytes written: {}", self.bytes_written)?;
+        Ok(())
+    }
+}
+
+/// stats of the network events waiting to be processed by the protocol module
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProtocolStats {
+    /// queued block, header and connection events
+    pub block_queue_depth: usize,
+    /// queued endorsement events
+    pub endorsement_queue_depth: usize,
+    /// queued operation events
+    pub operation_queue_depth: usize,
+    /// endorsement events dropped because their queue was full
+    pub shed_endorsement_events: u64,
+    /// operation events dropped because their queue was full
+    pub shed_operation_events: u64,
+    /// endorsement propagation cache counters
+    pub endorsement_cache: EndorsementCacheStats,
+}
+
+impl std::fmt::Display for ProtocolStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Protocol stats:")?;
+        writeln!(f, "	Queued block events: {}", self.block_queue_depth)?;
+        writeln!(
+            f,
+            "	Queued endorsement events: {} ({} dropped)",
+            self.endorsement_queue_depth, self.shed_endorsement_events
+        )?;
+        writeln!(
+            f,
+            "	Queued operation events: {} ({} dropped)",
+            self.operation_queue_depth, self.shed_operation_events
+        )?;
+        write!(f, "{}", self.endorsement_cache)?;
+        Ok(())
+    }
+}
+
+/// counters of the cache deduplicating the endorsements received from and sent to the peers
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct EndorsementCacheStats {
+    /// endorsements received from peers
+    pub received_endorsements: u64,
+    /// received endorsements that were already checked, their signature was not verified again
+    pub duplicate_endorsements: u64,
+    /// endorsements sent to peers
+    pub sent_endorsements: u64,
+    /// endorsements not sent to a peer because it already knew them
+    pub skipped_sends: u64,
+    /// number of peers whose known endorsements are tracked
+    pub tracked_nodes: usize,
+}
+
+impl std::fmt::Display for EndorsementCacheStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "\tReceived endorsements: {} ({} already checked)",
+            self.received_endorsements, self.duplicate_endorsements
+        )?;
+        writeln!(
+            f,
+            "\tSent endorsements: {} ({} skipped as known by the peer)",
+            self.sent_endorsements, self.skipped_sends
+        )?;
+        writeln!(
+            f,
+            "\tPeers with tracked endorsements: {}",
+            self.tracked_nodes
+        )?;
         Ok(())
     }
 }
@@ -88,6 +376,8 @@ pub struct ConsensusStats {
     pub stale_block_count: u64,
     ///  number of actives cliques
     pub clique_count: u64,
+    /// latency between the slot of the blocks and their finality, per thread
+    pub finality_latencies: Vec<FinalityLatencyStats>,
 }
 
 impl std::fmt::Display for ConsensusStats {
@@ -106,6 +396,261 @@ impl std::fmt::Display for ConsensusStats {
         writeln!(f, "\tFinal block count: {}", self.final_block_count)?;
         writeln!(f, "\tStale block count: {}", self.stale_block_count)?;
         writeln!(f, "\tClique count: {}", self.clique_count)?;
+        writeln!(f, "\tFinality latencies:")?;
+        for latency in &self.finality_latencies {
+            writeln!(f, "\t\t{}", latency)?;
+        }
+        Ok(())
+    }
+}
+
+/// distribution of the time elapsed between the slot timestamp of the blocks of a thread and their finality
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinalityLatencyStats {
+    /// thread of the blocks
+    pub thread: u8,
+    /// number of blocks that became final in the time span
+    pub sample_count: u64,
+    /// median latency, zero without samples
+    pub p50: MassaTime,
+    /// 90th percentile of the latencies, zero without samples
+    pub p90: MassaTime,
+    /// 99th percentile of the latencies, zero without samples
+    pub p99: MassaTime,
+    /// maximum latency, zero without samples
+    pub max: MassaTime,
+}
+
+impl FinalityLatencyStats {
+    /// Computes the latency percentiles of a thread from the latencies of its blocks, in any order
+    pub fn from_samples(thread: u8, mut samples: Vec<MassaTime>) -> Self {
+        samples.sort_unstable();
+        // nearest-rank percentile
+        let percentile = |p: usize| {
+            let rank = (p * samples.len() + 99) / 100;
+            samples
+                .get(rank.saturating_sub(1))
+                .copied()
+                .unwrap_or_else(|| MassaTime::from_millis(0))
+        };
+        FinalityLatencyStats {
+            thread,
+            sample_count: samples.len() as u64,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: samples
+                .last()
+                .copied()
+                .unwrap_or_else(|| MassaTime::from_millis(0)),
+        }
+    }
+}
+
+impl std::fmt::Display for FinalityLatencyStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.sample_count == 0 {
+            return write!(f, "Thread {}: no final block", self.thread);
+        }
+        write!(
+            f,
+            "Thread {}: {} final blocks, p50 {} ms, p90 {} ms, p99 {} ms, max {} ms",
+            self.thread,
+            self.sample_count,
+            self.p50.to_millis(),
+            self.p90.to_millis(),
+            self.p99.to_millis(),
+            self.max.to_millis()
+        )
+    }
+}
+
+/// network-wide activity computed over the latest final slots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkActivityStats {
+    /// first final slot of the window, `None` if no slot is final yet
+    pub window_start: Option<Slot>,
+    /// last final slot of the window, `None` if no slot is final yet
+    pub window_end: Option<Slot>,
+    /// number of final slots in the window
+    pub slot_count: u64,
+    /// number of final blocks in the window
+    pub block_count: u64,
+    /// number of operations executed in the final blocks of the window
+    pub operation_count: u64,
+    /// executed operations per second over the duration of the window
+    pub operations_per_second: f64,
+    /// average fee of the executed operations, zero without operations
+    pub average_fee: Amount,
+    /// median fee of the executed operations, zero without operations
+    pub fee_p50: Amount,
+    /// 90th percentile of the fees of the executed operations, zero without operations
+    pub fee_p90: Amount,
+    /// 99th percentile of the fees of the executed operations, zero without operations
+    pub fee_p99: Amount,
+    /// average gas used per final block
+    pub average_gas_per_block: u64,
+    /// ratio of the gas used by the final blocks to their maximal gas, between 0 and 1
+    pub gas_utilization: f64,
+    /// number of events emitted in the window
+    pub event_count: u64,
+}
+
+impl std::fmt::Display for NetworkActivityStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Network activity stats:")?;
+        match (self.window_start, self.window_end) {
+            (Some(start), Some(end)) => writeln!(
+                f,
+                "	Window: {} final slots from {} to {}",
+                self.slot_count, start, end
+            )?,
+            _ => writeln!(f, "	Window: no final slot yet")?,
+        }
+        writeln!(f, "	Final blocks: {}", self.block_count)?;
+        writeln!(
+            f,
+            "	Executed operations: {} ({:.2} per second)",
+            self.operation_count, self.operations_per_second
+        )?;
+        writeln!(
+            f,
+            "	Fees: average {}, p50 {}, p90 {}, p99 {}",
+            self.average_fee, self.fee_p50, self.fee_p90, self.fee_p99
+        )?;
+        writeln!(
+            f,
+            "	Gas: {} per block on average, {:.2}% utilization",
+            self.average_gas_per_block,
+            self.gas_utilization * 100.0
+        )?;
+        writeln!(f, "	Events: {}", self.event_count)?;
+        Ok(())
+    }
+}
+
+/// fee suggestions computed from the inclusion of operations in the latest final blocks and from the pressure on the pool.
+///
+/// `low` and `medium` are the 25th and 50th percentiles of the minimal fee included in each analyzed block,
+/// `medium` becoming the 75th percentile when the pool holds more operations than a block includes on average.
+/// `high` is the 90th percentile of all the included fees. Each suggestion is at least the previous one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeRecommendation {
+    /// number of final blocks analyzed
+    pub block_count: u64,
+    /// ratio of the gas used by the analyzed blocks to their maximal gas, between 0 and 1
+    pub block_fill_rate: f64,
+    /// minimal fee per unit of gas of the gas-consuming operations of the analyzed blocks, zero without such operations
+    pub min_fee_per_gas: Amount,
+    /// number of operations waiting in the pool
+    pub pool_operation_count: u64,
+    /// number of blocks needed to include all the operations of the pool at the inclusion rate of the analyzed blocks
+    pub pool_pressure: f64,
+    /// fee suggested for an operation that can wait for a less busy network
+    pub low: Amount,
+    /// fee suggested for an operation to be included in the coming periods
+    pub medium: Amount,
+    /// fee suggested for an operation to be included as soon as possible
+    pub high: Amount,
+}
+
+impl std::fmt::Display for FeeRecommendation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Fee recommendation:")?;
+        writeln!(f, "\tLow: {}", self.low)?;
+        writeln!(f, "\tMedium: {}", self.medium)?;
+        writeln!(f, "\tHigh: {}", self.high)?;
+        writeln!(
+            f,
+            "\tAnalyzed blocks: {} ({:.2}% gas fill rate, min fee per gas {})",
+            self.block_count,
+            self.block_fill_rate * 100.0,
+            self.min_fee_per_gas
+        )?;
+        writeln!(
+            f,
+            "\tPool: {} operations ({:.2} blocks to include them)",
+            self.pool_operation_count, self.pool_pressure
+        )?;
+        Ok(())
+    }
+}
+
+/// block production of a staker over a cycle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakerCycleStats {
+    /// address of the staker
+    pub address: Address,
+    /// rolls owned at the end of the cycle (latest value if the cycle is not complete),
+    /// after the rolls of the stakers exceeding the max miss ratio were sold
+    pub roll_count: u64,
+    /// number of blocks produced
+    pub ok_count: u64,
+    /// number of blocks missed
+    pub nok_count: u64,
+    /// the miss ratio of the staker exceeds the max: its rolls are implicitly sold when the cycle completes
+    pub miss_ratio_exceeded: bool,
+}
+
+/// proof-of-stake statistics of a cycle of the final state, with a page of its stakers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CycleStats {
+    /// cycle
+    pub cycle: u64,
+    /// whether all the slots of the cycle are final
+    pub complete: bool,
+    /// total number of stakers of the cycle: addresses owning rolls or having production stats
+    pub staker_count: u64,
+    /// total number of rolls at the end of the cycle
+    pub roll_count: u64,
+    /// total number of blocks produced during the cycle
+    pub ok_count: u64,
+    /// total number of blocks missed during the cycle
+    pub nok_count: u64,
+    /// total number of stakers exceeding the max miss ratio
+    pub miss_ratio_exceeded_count: u64,
+    /// page of the stakers, sorted by address
+    pub stakers: Vec<StakerCycleStats>,
+}
+
+impl std::fmt::Display for CycleStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Cycle {} ({}):",
+            self.cycle,
+            if self.complete { "complete" } else { "ongoing" }
+        )?;
+        writeln!(
+            f,
+            "\tStakers: {} owning {} rolls",
+            self.staker_count, self.roll_count
+        )?;
+        writeln!(
+            f,
+            "\tBlocks: {} produced, {} missed",
+            self.ok_count, self.nok_count
+        )?;
+        writeln!(
+            f,
+            "\tStakers exceeding the max miss ratio: {}",
+            self.miss_ratio_exceeded_count
+        )?;
+        for staker in &self.stakers {
+            writeln!(
+                f,
+                "\t{}: {} rolls, {} produced, {} missed{}",
+                staker.address,
+                staker.roll_count,
+                staker.ok_count,
+                staker.nok_count,
+                if staker.miss_ratio_exceeded {
+                    ", max miss ratio exceeded"
+                } else {
+                    ""
+                }
+            )?;
+        }
         Ok(())
     }
 }
@@ -127,3 +672,58 @@ impl std::fmt::Display for PoolStats {
         Ok(())
     }
 }
+
+/// operations left out of the blocks produced by the node because of its local execution policy,
+/// counted since the start of the node. An operation left out of several blocks is counted each time.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ExecutionPolicyStats {
+    /// number of times a `CallSC` operation was left out of a produced block
+    pub skipped_call_sc_count: u64,
+    /// number of times an `ExecuteSC` operation was left out of a produced block
+    pub skipped_execute_sc_count: u64,
+    /// number of produced blocks from which operations were left out
+    pub affected_block_count: u64,
+}
+
+impl std::fmt::Display for ExecutionPolicyStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Execution policy stats:")?;
+        writeln!(
+            f,
+            "\tSkipped CallSC operations: {}",
+            self.skipped_call_sc_count
+        )?;
+        writeln!(
+            f,
+            "\tSkipped ExecuteSC operations: {}",
+            self.skipped_execute_sc_count
+        )?;
+        writeln!(f, "\tAffected blocks: {}", self.affected_block_count)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finality_latency_percentiles() {
+        let samples = (1..=100).rev().map(MassaTime::from_millis).collect();
+        let stats = FinalityLatencyStats::from_samples(3, samples);
+        assert_eq!(stats.thread, 3);
+        assert_eq!(stats.sample_count, 100);
+        assert_eq!(stats.p50, MassaTime::from_millis(50));
+        assert_eq!(stats.p90, MassaTime::from_millis(90));
+        assert_eq!(stats.p99, MassaTime::from_millis(99));
+        assert_eq!(stats.max, MassaTime::from_millis(100));
+
+        let stats = FinalityLatencyStats::from_samples(0, vec![MassaTime::from_millis(7)]);
+        assert_eq!(stats.p50, MassaTime::from_millis(7));
+        assert_eq!(stats.p99, MassaTime::from_millis(7));
+
+        let stats = FinalityLatencyStats::from_samples(0, Vec::new());
+        assert_eq!(stats.sample_count, 0);
+        assert_eq!(stats.max, MassaTime::from_millis(0));
+    }
+}
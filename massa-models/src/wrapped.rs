@@ -54,6 +54,17 @@ where
         content: Self,
         content_serializer: SC,
         keypair: &KeyPair,
+    ) -> Result<Wrapped<Self, U>, ModelsError> {
+        Self::new_wrapped_signing(content, content_serializer, keypair, |id_hash| *id_hash)
+    }
+
+    /// Creates a wrapped version of the object, whose creator signs the hash computed
+    /// by `compute_signed_hash` from the hash giving its id
+    fn new_wrapped_signing<SC: Serializer<Self>, U: Id, F: FnOnce(&Hash) -> Hash>(
+        content: Self,
+        content_serializer: SC,
+        keypair: &KeyPair,
+        compute_signed_hash: F,
     ) -> Result<Wrapped<Self, U>, ModelsError> {
         let mut content_serialized = Vec::new();
         content_serializer.serialize(&content, &mut content_serialized)?;
@@ -64,7 +75,7 @@ where
         let hash = Hash::compute_from(&hash_data);
         let creator_address = Address::from_public_key(&public_key);
         Ok(Wrapped {
-            signature: keypair.sign(&hash)?,
+            signature: keypair.sign(&compute_signed_hash(&hash))?,
             creator_public_key: public_key,
             creator_address,
             content,
@@ -163,7 +174,8 @@ where
     T: Display + WrappedContent,
     U: Id,
 {
-    /// check if self has been signed by public key
+    /// check if self has been signed by public key.
+    /// Operations are signed along the chain id of their network, see `WrappedOperation::verify_operation_signature`
     pub fn verify_signature(&self) -> Result<(), ModelsError> {
         Ok(self
             .creator_public_key
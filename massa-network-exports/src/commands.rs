@@ -69,13 +69,13 @@
 //! Look at `massa-protocol-worker/src/node-info.rs` to look further how we
 //! remember which node know what.
 
-use crate::{BootstrapPeers, ConnectionClosureReason, Peers};
+use crate::{BootstrapPeers, ConnectionClosureReason, PeerFeatures, Peers};
 use massa_models::{
     block::{BlockId, WrappedHeader},
     composite::PubkeySig,
     endorsement::WrappedEndorsement,
     node::NodeId,
-    operation::{OperationId, OperationPrefixIds, WrappedOperation},
+    operation::{OperationAnnouncements, OperationId, OperationPrefixIds, WrappedOperation},
     stats::NetworkStats,
 };
 use serde::{Deserialize, Serialize};
@@ -100,6 +100,8 @@ pub enum NodeCommand {
     SendOperations(Vec<WrappedOperation>),
     /// Send a batch of operation ids
     SendOperationAnnouncements(OperationPrefixIds),
+    /// Send a batch of operation ids along their fee density
+    SendOperationFeeAnnouncements(OperationAnnouncements),
     /// Ask for a set of operations
     AskForOperations(OperationPrefixIds),
     /// Endorsements
@@ -108,6 +110,35 @@ pub enum NodeCommand {
     AskPeerList,
 }
 
+/// Priority class of the messages sent to a node.
+/// Each class has its own queue, so that block propagation never waits behind operation batches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessagePriority {
+    /// block headers, block requests and replies, endorsements, peer lists and connection closure
+    Block,
+    /// operations, their announcements and requests
+    Operation,
+}
+
+impl NodeCommand {
+    /// Priority class of the messages sent for the command
+    pub fn priority(&self) -> MessagePriority {
+        match self {
+            NodeCommand::SendOperations(_)
+            | NodeCommand::SendOperationAnnouncements(_)
+            | NodeCommand::SendOperationFeeAnnouncements(_)
+            | NodeCommand::AskForOperations(_) => MessagePriority::Operation,
+            NodeCommand::SendPeerList(_)
+            | NodeCommand::SendBlockHeader(_)
+            | NodeCommand::AskForBlocks(_)
+            | NodeCommand::ReplyForBlocks(_)
+            | NodeCommand::Close(_)
+            | NodeCommand::SendEndorsements(_)
+            | NodeCommand::AskPeerList => MessagePriority::Block,
+        }
+    }
+}
+
 /// Event types that node worker can emit
 /// Append on receive something from inside and outside.
 /// Outside initialization with `Received` prefix.
@@ -126,8 +157,8 @@ pub enum NodeEventType {
     ReceivedReplyForBlocks(Vec<(BlockId, BlockInfoReply)>),
     /// Received full operations.
     ReceivedOperations(Vec<WrappedOperation>),
-    /// Received an operation id batch announcing new operations
-    ReceivedOperationAnnouncements(OperationPrefixIds),
+    /// Received an operation id batch announcing new operations along their fee density
+    ReceivedOperationAnnouncements(OperationAnnouncements),
     /// Receive a list of wanted operations
     ReceivedAskForOperations(OperationPrefixIds),
     /// Receive a set of endorsement
@@ -206,6 +237,11 @@ pub enum NetworkCommand {
         /// response channels
         response_tx: oneshot::Sender<NetworkStats>,
     },
+    /// gets the clock offsets of the connected nodes relatively to ours, in milliseconds
+    GetClockOffsets {
+        /// response channels
+        response_tx: oneshot::Sender<Vec<i64>>,
+    },
     /// Send a batch of full operations
     SendOperations {
         /// to node id
@@ -220,6 +256,13 @@ pub enum NetworkCommand {
         /// batch of operation ids
         batch: OperationPrefixIds,
     },
+    /// Send operation ids batch to a node, along the fee density of the operations
+    SendOperationFeeAnnouncements {
+        /// to node id
+        to_node: NodeId,
+        /// batch of operation ids along their fee density
+        batch: OperationAnnouncements,
+    },
     /// Ask for operation
     AskForOperations {
         /// to node id
@@ -251,8 +294,8 @@ pub enum BlockInfoReply {
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum NetworkEvent {
-    /// new connection from node
-    NewConnection(NodeId),
+    /// new connection from node, with the features supported by both ends of the connection
+    NewConnection(NodeId, PeerFeatures),
     /// connection to node was closed
     ConnectionClosed(NodeId),
     /// Info about a block was received
@@ -287,8 +330,8 @@ pub enum NetworkEvent {
     ReceivedOperationAnnouncements {
         /// from node id
         node: NodeId,
-        /// operation prefix ids
-        operation_prefix_ids: OperationPrefixIds,
+        /// operation prefix ids along their fee density
+        operation_prefix_ids: OperationAnnouncements,
     },
     /// Receive a list of asked operations from `node`
     ReceiveAskForOperations {
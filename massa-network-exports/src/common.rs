@@ -14,6 +14,43 @@ impl std::fmt::Display for ConnectionId {
     }
 }
 
+/// Optional protocol features supported by a node, announced as bits during the handshake.
+/// The features of a connection are the ones supported by both ends:
+/// a message whose encoding depends on a feature is only sent that way to the peers supporting it.
+/// Unknown bits are kept but never used, so that new features can be announced to older nodes.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PeerFeatures(pub u64);
+
+impl PeerFeatures {
+    /// compressed message payloads
+    pub const COMPRESSION: PeerFeatures = PeerFeatures(1 << 0);
+    /// BLS-signed endorsements
+    pub const BLS_ENDORSEMENTS: PeerFeatures = PeerFeatures(1 << 1);
+    /// new operation encoding
+    pub const NEW_OPERATIONS_ENCODING: PeerFeatures = PeerFeatures(1 << 2);
+    /// operation announcements carrying the fee density of the operations
+    pub const OPERATION_FEE_DENSITY: PeerFeatures = PeerFeatures(1 << 3);
+
+    /// features implemented by this node, announced in its handshakes
+    pub const SUPPORTED: PeerFeatures = PeerFeatures::OPERATION_FEE_DENSITY;
+
+    /// true if all the features of `other` are in `self`
+    pub fn contains(&self, other: PeerFeatures) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// features both in `self` and `other`
+    pub fn intersection(&self, other: PeerFeatures) -> PeerFeatures {
+        PeerFeatures(self.0 & other.0)
+    }
+}
+
+impl std::fmt::Display for PeerFeatures {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
 /// Possible reasons for a connection closure
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum ConnectionClosureReason {
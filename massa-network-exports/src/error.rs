@@ -55,6 +55,8 @@ pub enum NetworkError {
     SerializeError(#[from] SerializeError),
     /// container inconsistency error: {0}
     ContainerInconsistencyError(String),
+    /// NAT traversal error: {0}
+    NatTraversalError(String),
 }
 
 /// Handshake error type
@@ -5,17 +5,17 @@
 #![feature(ip)]
 
 pub use commands::{
-    AskForBlocksInfo, BlockInfoReply, NetworkCommand, NetworkEvent, NetworkManagementCommand,
-    NodeCommand, NodeEvent, NodeEventType,
+    AskForBlocksInfo, BlockInfoReply, MessagePriority, NetworkCommand, NetworkEvent,
+    NetworkManagementCommand, NodeCommand, NodeEvent, NodeEventType,
 };
 
-pub use common::{ConnectionClosureReason, ConnectionId};
+pub use common::{ConnectionClosureReason, ConnectionId, PeerFeatures};
 pub use error::{HandshakeErrorType, NetworkConnectionErrorType, NetworkError};
 pub use establisher::{Establisher, Listener, ReadHalf, WriteHalf};
 pub use network_controller::{NetworkCommandSender, NetworkEventReceiver, NetworkManager};
 pub use peers::{
     BootstrapPeers, BootstrapPeersDeserializer, BootstrapPeersSerializer, ConnectionCount, Peer,
-    PeerInfo, PeerType, Peers,
+    PeerInfo, PeerQuality, PeerType, Peers,
 };
 pub use settings::NetworkConfig;
 
@@ -10,7 +10,7 @@ use massa_models::{
     composite::PubkeySig,
     endorsement::WrappedEndorsement,
     node::NodeId,
-    operation::{OperationPrefixIds, WrappedOperation},
+    operation::{OperationAnnouncements, OperationPrefixIds, WrappedOperation},
     stats::NetworkStats,
 };
 use std::{
@@ -160,6 +160,20 @@ impl NetworkCommandSender {
             .map_err(|_| NetworkError::ChannelError("could not send GetStats upstream".into()))
     }
 
+    /// get the clock offsets of the connected nodes relatively to ours, in milliseconds
+    pub async fn get_clock_offsets(&self) -> Result<Vec<i64>, NetworkError> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.0
+            .send(NetworkCommand::GetClockOffsets { response_tx })
+            .await
+            .map_err(|_| {
+                NetworkError::ChannelError("could not send GetClockOffsets command".into())
+            })?;
+        response_rx.await.map_err(|_| {
+            NetworkError::ChannelError("could not send GetClockOffsets upstream".into())
+        })
+    }
+
     /// Send the order to get bootstrap peers.
     pub async fn get_bootstrap_peers(&self) -> Result<BootstrapPeers, NetworkError> {
         let (response_tx, response_rx) = oneshot::channel::<BootstrapPeers>();
@@ -217,6 +231,34 @@ impl NetworkCommandSender {
         Ok(())
     }
 
+    /// Create a new call to the network, sending a announcement of operation ID prefixes,
+    /// along the fee density of the operations, to a target node (`to_node`)
+    ///
+    /// # Returns
+    /// Can return a `[NetworkError::ChannelError]` that must be managed by the direct caller of the
+    /// function.
+    pub async fn announce_operation_fees(
+        &self,
+        to_node: NodeId,
+        batch: OperationAnnouncements,
+    ) -> Result<(), NetworkError> {
+        match self
+            .0
+            .try_send(NetworkCommand::SendOperationFeeAnnouncements { to_node, batch })
+        {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                warn!("Failed to send NetworkCommand SendOperationFeeAnnouncements channel full");
+            }
+            Err(TrySendError::Closed(_)) => {
+                return Err(NetworkError::ChannelError(
+                    "could not send SendOperationFeeAnnouncements command".into(),
+                ));
+            }
+        };
+        Ok(())
+    }
+
     /// Create a new call to the network, sending a `wishlist` of `operationIds` to a
     /// target node (`to_node`) in order to receive the full operations in the future.
     ///
@@ -202,6 +202,9 @@ pub struct PeerInfo {
     /// Isn't dump into peer file.
     #[serde(default = "usize::default")]
     pub active_in_connections: usize,
+    /// Historical quality of the peer, decaying over time
+    #[serde(default)]
+    pub quality: PeerQuality,
 }
 
 impl PeerInfo {
@@ -244,6 +247,7 @@ impl PeerInfo {
             active_in_connections: 0,
             peer_type: Default::default(),
             banned: false,
+            quality: Default::default(),
         }
     }
 
@@ -264,6 +268,71 @@ impl PeerInfo {
     }
 }
 
+/// weight of an hour of uptime in the peer score
+const UPTIME_HOUR_WEIGHT: f64 = 1.0;
+/// weight of a successful handshake in the peer score
+const SUCCESSFUL_CONNECTION_WEIGHT: f64 = 0.5;
+/// weight of a failed handshake in the peer score
+const HANDSHAKE_FAILURE_WEIGHT: f64 = 1.0;
+/// weight of a ban in the peer score
+const BAN_WEIGHT: f64 = 10.0;
+
+/// Historical quality metrics of a peer, persisted in the peers file.
+/// All metrics decay exponentially so that old events weigh less than recent ones.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct PeerQuality {
+    /// decayed time spent connected to the peer, in seconds
+    pub uptime: f64,
+    /// decayed number of successful handshakes
+    pub successful_connections: f64,
+    /// decayed number of failed handshakes
+    pub handshake_failures: f64,
+    /// decayed number of bans
+    pub bans: f64,
+    /// time of the last decay, None if the metrics were never updated
+    pub last_update: Option<MassaTime>,
+}
+
+impl PeerQuality {
+    /// Decays the metrics for the time elapsed since the last update,
+    /// accounting that time as uptime if the peer is connected.
+    ///
+    /// # Arguments
+    /// * `now`: current time
+    /// * `half_life`: time after which the metrics are halved
+    /// * `connected`: true if there is an active connection with the peer
+    pub fn decay(&mut self, now: MassaTime, half_life: MassaTime, connected: bool) {
+        let elapsed = match self.last_update {
+            Some(last_update) => now.saturating_sub(last_update),
+            None => MassaTime::from_millis(0),
+        };
+        self.last_update = Some(now);
+        if elapsed.to_millis() == 0 {
+            return;
+        }
+        let factor = if half_life.to_millis() == 0 {
+            0.0
+        } else {
+            0.5f64.powf(elapsed.to_millis() as f64 / half_life.to_millis() as f64)
+        };
+        self.uptime *= factor;
+        self.successful_connections *= factor;
+        self.handshake_failures *= factor;
+        self.bans *= factor;
+        if connected {
+            self.uptime += elapsed.to_millis() as f64 / 1000.0;
+        }
+    }
+
+    /// Score of the peer, the higher the better. A peer with no history scores 0.
+    pub fn score(&self) -> f64 {
+        UPTIME_HOUR_WEIGHT * self.uptime / 3600.0
+            + SUCCESSFUL_CONNECTION_WEIGHT * self.successful_connections
+            - HANDSHAKE_FAILURE_WEIGHT * self.handshake_failures
+            - BAN_WEIGHT * self.bans
+    }
+}
+
 /// Connection count for a category
 #[derive(Default, Debug)]
 pub struct ConnectionCount {
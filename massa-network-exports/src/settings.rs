@@ -16,6 +16,14 @@ pub struct NetworkConfig {
     pub routable_ip: Option<IpAddr>,
     /// Protocol port
     pub protocol_port: u16,
+    /// Map the protocol port on the local router through UPnP or NAT-PMP,
+    /// and use the external IP it reports as our routable IP if none is configured.
+    pub nat_traversal: bool,
+    /// NAT-PMP gateway, guessed from the local IP if None
+    pub nat_pmp_gateway: Option<IpAddr>,
+    /// Lease duration of the port mapping, renewed at half of it.
+    /// In milliseconds
+    pub nat_mapping_lease: MassaTime,
     /// Time interval spent waiting for a response from a peer.
     /// In milliseconds
     pub connect_timeout: MassaTime,
@@ -38,6 +46,9 @@ pub struct NetworkConfig {
     pub max_banned_peers: usize,
     /// Peer database is dumped every `peers_file_dump_interval` in milliseconds
     pub peers_file_dump_interval: MassaTime,
+    /// Half-life of the peer quality metrics used to rank dialing candidates.
+    /// In milliseconds
+    pub peer_quality_half_life: MassaTime,
     /// After `message_timeout` milliseconds we are no longer waiting on handshake message
     pub message_timeout: MassaTime,
     /// Every `ask_peer_list_interval` in milliseconds we ask every one for its advertisable peers list.
@@ -58,6 +69,14 @@ pub struct NetworkConfig {
     pub max_bytes_read: f64,
     /// Write limitation for a connection in bytes per seconds
     pub max_bytes_write: f64,
+    /// Read limitation for a connection with a whitelisted peer in bytes per seconds
+    pub max_bytes_read_whitelisted: f64,
+    /// Write limitation for a connection with a whitelisted peer in bytes per seconds
+    pub max_bytes_write_whitelisted: f64,
+    /// Read limitation for all the connections together in bytes per seconds
+    pub max_total_bytes_read: f64,
+    /// Write limitation for all the connections together in bytes per seconds
+    pub max_total_bytes_write: f64,
     /// Max number ids in ask blocks message
     pub max_ask_blocks: u32,
     /// Max operations per block
@@ -88,8 +107,11 @@ pub struct NetworkConfig {
     pub controller_channel_size: usize,
     /// Event channel size
     pub event_channel_size: usize,
-    /// Node command channel size
+    /// Size of each of the command queues of a node, one per message priority class
     pub node_command_channel_size: usize,
+    /// Number of block class messages sent to a node for each operation class message
+    /// when messages of both classes are waiting to be sent (at least 1)
+    pub block_message_weight: u32,
     /// Node event channel size
     pub node_event_channel_size: usize,
 }
@@ -148,6 +170,9 @@ pub mod tests {
                 bind: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080),
                 routable_ip: Some(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))),
                 protocol_port: 0,
+                nat_traversal: false,
+                nat_pmp_gateway: None,
+                nat_mapping_lease: MassaTime::from_millis(3_600_000),
                 connect_timeout: MassaTime::from_millis(180_000),
                 wakeup_interval: MassaTime::from_millis(10_000),
                 peers_file: std::path::PathBuf::new(),
@@ -155,6 +180,7 @@ pub mod tests {
                 max_idle_peers: 3,
                 max_banned_peers: 3,
                 peers_file_dump_interval: MassaTime::from_millis(10_000),
+                peer_quality_half_life: MassaTime::from_millis(86_400_000),
                 message_timeout: MassaTime::from_millis(5000u64),
                 ask_peer_list_interval: MassaTime::from_millis(50000u64),
                 keypair_file: std::path::PathBuf::new(),
@@ -168,6 +194,10 @@ pub mod tests {
                 max_operations_per_message: MAX_OPERATIONS_PER_MESSAGE,
                 max_bytes_read: std::f64::INFINITY,
                 max_bytes_write: std::f64::INFINITY,
+                max_bytes_read_whitelisted: std::f64::INFINITY,
+                max_bytes_write_whitelisted: std::f64::INFINITY,
+                max_total_bytes_read: std::f64::INFINITY,
+                max_total_bytes_write: std::f64::INFINITY,
                 max_ask_blocks: MAX_ASK_BLOCKS_PER_MESSAGE,
                 endorsement_count: ENDORSEMENT_COUNT,
                 max_endorsements_per_message: MAX_ENDORSEMENTS_PER_MESSAGE,
@@ -184,6 +214,7 @@ pub mod tests {
                 controller_channel_size: NETWORK_CONTROLLER_CHANNEL_SIZE,
                 event_channel_size: NETWORK_EVENT_CHANNEL_SIZE,
                 node_command_channel_size: NETWORK_NODE_COMMAND_CHANNEL_SIZE,
+                block_message_weight: 8,
                 node_event_channel_size: NETWORK_NODE_EVENT_CHANNEL_SIZE,
             }
         }
@@ -215,6 +246,9 @@ pub mod tests {
                 bind,
                 routable_ip,
                 protocol_port: port,
+                nat_traversal: false,
+                nat_pmp_gateway: None,
+                nat_mapping_lease: MassaTime::from_millis(3_600_000),
                 connect_timeout: MassaTime::from_millis(3000),
                 peers_file: peers_file.to_path_buf(),
                 wakeup_interval: MassaTime::from_millis(3000),
@@ -222,6 +256,7 @@ pub mod tests {
                 max_idle_peers: 100,
                 max_banned_peers: 100,
                 peers_file_dump_interval: MassaTime::from_millis(30000),
+                peer_quality_half_life: MassaTime::from_millis(86_400_000),
                 message_timeout: MassaTime::from_millis(5000u64),
                 ask_peer_list_interval: MassaTime::from_millis(50000u64),
                 keypair_file: get_temp_keypair_file().path().to_path_buf(),
@@ -235,6 +270,10 @@ pub mod tests {
                 max_operations_per_message: MAX_OPERATIONS_PER_MESSAGE,
                 max_bytes_read: std::f64::INFINITY,
                 max_bytes_write: std::f64::INFINITY,
+                max_bytes_read_whitelisted: std::f64::INFINITY,
+                max_bytes_write_whitelisted: std::f64::INFINITY,
+                max_total_bytes_read: std::f64::INFINITY,
+                max_total_bytes_write: std::f64::INFINITY,
                 max_ask_blocks: 10,
                 endorsement_count: 8,
                 max_endorsements_per_message: MAX_ENDORSEMENTS_PER_MESSAGE,
@@ -251,6 +290,7 @@ pub mod tests {
                 controller_channel_size: NETWORK_CONTROLLER_CHANNEL_SIZE,
                 event_channel_size: NETWORK_EVENT_CHANNEL_SIZE,
                 node_command_channel_size: NETWORK_NODE_COMMAND_CHANNEL_SIZE,
+                block_message_weight: 8,
                 node_event_channel_size: NETWORK_NODE_EVENT_CHANNEL_SIZE,
             }
         }
@@ -0,0 +1,148 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Bandwidth caps of the connections.
+//!
+//! Each direction (read and write) is limited by a token bucket shared by all the connections
+//! and by a token bucket per connection, whose rate depends on the peer type.
+//! Buckets hold at most one second of traffic, and a transfer exceeding the available tokens
+//! puts the bucket in debt: the connection then waits until the debt is paid back.
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use tokio::time::{Duration, Instant};
+
+/// Token bucket refilled at a constant rate
+#[derive(Debug)]
+struct TokenBucket {
+    /// refill rate in bytes per second, unlimited if infinite or not positive
+    rate: f64,
+    /// available bytes, negative when in debt
+    tokens: f64,
+    /// last time the bucket was refilled
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a full bucket refilled at `rate` bytes per second
+    fn new(rate: f64) -> Self {
+        TokenBucket {
+            rate,
+            tokens: rate,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Takes `bytes` tokens from the bucket
+    ///
+    /// # Returns
+    /// The time to wait for the bucket to be out of debt
+    fn take(&mut self, bytes: usize) -> Duration {
+        if !self.rate.is_finite() || self.rate <= 0.0 {
+            return Duration::ZERO;
+        }
+        let now = Instant::now();
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.last_refill = now;
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.tokens -= bytes as f64;
+        if self.tokens >= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(-self.tokens / self.rate)
+    }
+}
+
+/// Bandwidth shared by all the connections in one direction
+#[derive(Clone, Debug)]
+pub(crate) struct SharedBandwidth {
+    /// bucket of the global cap
+    bucket: Arc<Mutex<TokenBucket>>,
+    /// bytes transferred since the start of the node
+    total_bytes: Arc<AtomicU64>,
+}
+
+impl SharedBandwidth {
+    /// Creates the shared bandwidth of a direction capped at `max_bytes_per_sec`
+    pub fn new(max_bytes_per_sec: f64) -> Self {
+        SharedBandwidth {
+            bucket: Arc::new(Mutex::new(TokenBucket::new(max_bytes_per_sec))),
+            total_bytes: Default::default(),
+        }
+    }
+
+    /// Creates a limiter for a new connection capped at `max_bytes_per_sec`
+    pub fn connection_limiter(&self, max_bytes_per_sec: f64) -> BandwidthLimiter {
+        BandwidthLimiter {
+            shared: self.clone(),
+            connection: TokenBucket::new(max_bytes_per_sec),
+        }
+    }
+
+    /// Gets the number of bytes transferred since the start of the node
+    pub fn get_total_bytes(&self) -> u64 {
+        self.total_bytes.load(Ordering::Relaxed)
+    }
+}
+
+/// Bandwidth limiter of a connection in one direction
+#[derive(Debug)]
+pub(crate) struct BandwidthLimiter {
+    /// bandwidth shared with the other connections
+    shared: SharedBandwidth,
+    /// bucket of the cap of the connection
+    connection: TokenBucket,
+}
+
+impl BandwidthLimiter {
+    /// Creates a limiter without any cap, that is not shared with other connections
+    #[cfg(test)]
+    pub fn unlimited() -> Self {
+        SharedBandwidth::new(f64::INFINITY).connection_limiter(f64::INFINITY)
+    }
+
+    /// Accounts for the transfer of `bytes` on the connection
+    ///
+    /// # Returns
+    /// The time to wait before the next transfer to stay within the caps
+    pub fn consume(&mut self, bytes: usize) -> Duration {
+        self.shared
+            .total_bytes
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+        let shared_wait = self
+            .shared
+            .bucket
+            .lock()
+            .expect("bandwidth bucket lock poisoned")
+            .take(bytes);
+        shared_wait.max(self.connection.take(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bandwidth_caps() {
+        let shared = SharedBandwidth::new(1000.0);
+        let mut limiter = shared.connection_limiter(500.0);
+        // the connection cap is the lowest: about half a second of debt after 750 bytes
+        assert!(limiter.consume(500).is_zero());
+        let wait = limiter.consume(250);
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+        // the global cap is shared with the other connections
+        let mut other = shared.connection_limiter(f64::INFINITY);
+        let wait = other.consume(500);
+        assert!(wait > Duration::from_millis(200) && wait <= Duration::from_millis(250));
+        assert_eq!(shared.get_total_bytes(), 1250);
+        // the buckets are refilled over time
+        let one_sec_ago = Instant::now() - Duration::from_secs(1);
+        limiter.connection.last_refill = one_sec_ago;
+        shared.bucket.lock().unwrap().last_refill = one_sec_ago;
+        assert!(limiter.consume(250).is_zero());
+    }
+}
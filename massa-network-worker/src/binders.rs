@@ -1,6 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 //! `Flexbuffer` layer between raw data and our objects.
+use crate::bandwidth::BandwidthLimiter;
 use crate::messages::{MessageDeserializer, MessageSerializer};
 
 use super::messages::Message;
@@ -13,6 +14,7 @@ use massa_serialization::Serializer;
 use massa_serialization::{DeserializeError, Deserializer};
 use std::convert::TryInto;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::{sleep, sleep_until, Instant};
 use tracing::warn;
 
 /// Used to serialize and send data.
@@ -20,19 +22,21 @@ pub struct WriteBinder {
     pub(crate) write_half: WriteHalf,
     message_index: u64,
     max_message_size: u32,
+    limiter: BandwidthLimiter,
 }
 
 impl WriteBinder {
-    /// Creates a new `WriteBinder` with a bandwidth `limit` in bytes per second.
+    /// Creates a new `WriteBinder` with a bandwidth `limiter`.
     ///
     /// # Argument
     /// * `write_half`: writer half.
-    /// * `limit`: limit max bytes per second write
-    pub fn new(write_half: WriteHalf, _limit: f64, max_message_size: u32) -> Self {
+    /// * `limiter`: caps of the bytes written per second
+    pub fn new(write_half: WriteHalf, limiter: BandwidthLimiter, max_message_size: u32) -> Self {
         WriteBinder {
             write_half,
             message_index: 0,
             max_message_size,
+            limiter,
         }
     }
 
@@ -48,6 +52,15 @@ impl WriteBinder {
             .len()
             .try_into()
             .map_err(|_| NetworkError::GeneralProtocolError("message too long".into()))?;
+
+        // wait for the bandwidth caps to allow the message
+        let wait = self
+            .limiter
+            .consume(u32::be_bytes_min_length(self.max_message_size) + buf.len());
+        if !wait.is_zero() {
+            sleep(wait).await;
+        }
+
         self.write_half
             .write_all(&msg_size.to_be_bytes_min(self.max_message_size)?[..])
             .await?;
@@ -71,17 +84,20 @@ pub struct ReadBinder {
     msg_size: Option<u32>,
     max_message_size: u32,
     message_deserializer: MessageDeserializer,
+    limiter: BandwidthLimiter,
+    /// instant before which nothing is read, to stay within the bandwidth caps
+    throttled_until: Option<Instant>,
 }
 
 impl ReadBinder {
-    /// Creates a new `ReadBinder` with a bandwidth `limit` in bytes per second.
+    /// Creates a new `ReadBinder` with a bandwidth `limiter`.
     ///
     /// # Argument
     /// * `read_half`: reader half.
-    /// * `limit`: limit max bytes per second read.
+    /// * `limiter`: caps of the bytes read per second.
     pub fn new(
         read_half: ReadHalf,
-        _limit: f64,
+        limiter: BandwidthLimiter,
         max_message_size: u32,
         message_deserializer: MessageDeserializer,
     ) -> Self {
@@ -93,7 +109,24 @@ impl ReadBinder {
             msg_size: None,
             max_message_size,
             message_deserializer,
+            limiter,
+            throttled_until: None,
+        }
+    }
+
+    /// Reads available bytes into the buffer at the cursor once the bandwidth caps allow it.
+    /// Asynchronous cancel-safe: the throttling deadline is kept across calls.
+    async fn read_throttled(&mut self) -> std::io::Result<usize> {
+        if let Some(deadline) = self.throttled_until {
+            sleep_until(deadline).await;
+            self.throttled_until = None;
+        }
+        let nr = self.read_half.read(&mut self.buf[self.cursor..]).await?;
+        let wait = self.limiter.consume(nr);
+        if !wait.is_zero() {
+            self.throttled_until = Some(Instant::now() + wait);
         }
+        Ok(nr)
     }
 
     /// Awaits the next incoming message and deserializes it. Asynchronous cancel-safe.
@@ -121,7 +154,7 @@ impl ReadBinder {
             // We need to keep all states (buffer and cursor) to ensure that if the function restarts at the read's await,
             // the state will remain consistent and resume the readout smoothly.
             while self.cursor < size_field_len {
-                match self.read_half.read(&mut self.buf[self.cursor..]).await {
+                match self.read_throttled().await {
                     Ok(nr) => {
                         if nr == 0 {
                             return Ok(None);
@@ -153,7 +186,7 @@ impl ReadBinder {
         // read message in the same cancel-safe way as msg_size above
         while self.cursor < self.msg_size.unwrap() as usize {
             // does not panic
-            match self.read_half.read(&mut self.buf[self.cursor..]).await {
+            match self.read_throttled().await {
                 Ok(nr) => {
                     if nr == 0 {
                         return Ok(None);
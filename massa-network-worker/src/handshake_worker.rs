@@ -2,7 +2,8 @@
 
 //! Here are happening handshakes.
 
-use crate::messages::MessageDeserializer;
+use crate::bandwidth::BandwidthLimiter;
+use crate::messages::{MessageDeserializer, HANDSHAKE_VERSION};
 
 use super::{
     binders::{ReadBinder, WriteBinder},
@@ -25,8 +26,8 @@ use massa_models::{
     node::NodeId,
 };
 use massa_network_exports::{
-    throw_handshake_error as throw, ConnectionId, HandshakeErrorType, NetworkError, ReadHalf,
-    WriteHalf,
+    throw_handshake_error as throw, ConnectionId, HandshakeErrorType, NetworkError, PeerFeatures,
+    ReadHalf, WriteHalf,
 };
 use massa_signature::KeyPair;
 use massa_time::MassaTime;
@@ -34,8 +35,11 @@ use rand::{rngs::StdRng, RngCore, SeedableRng};
 use tokio::{task::JoinHandle, time::timeout};
 use tracing::debug;
 
-/// Type alias for more readability
-pub type HandshakeReturnType = Result<(NodeId, ReadBinder, WriteBinder), NetworkError>;
+/// Type alias for more readability.
+/// On success, also returns the estimated offset of the peer's clock relatively to ours, in milliseconds,
+/// if the peer sent its time, and the features supported by both ends of the connection.
+pub type HandshakeReturnType =
+    Result<(NodeId, ReadBinder, WriteBinder, Option<i64>, PeerFeatures), NetworkError>;
 
 /// Manages handshakes.
 pub struct HandshakeWorker {
@@ -69,6 +73,8 @@ impl HandshakeWorker {
     /// * `timeout_duration`: after `timeout_duration` milliseconds, the handshake attempt is dropped.
     /// * `connection_id`: Node we are trying to connect for debugging
     /// * `version`: Node version used in handshake initialization (check peers compatibility)
+    /// * `read_limiter`: bandwidth caps of the bytes read on the connection
+    /// * `write_limiter`: bandwidth caps of the bytes written on the connection
     #[allow(clippy::too_many_arguments)]
     pub fn spawn(
         socket_reader: ReadHalf,
@@ -78,8 +84,8 @@ impl HandshakeWorker {
         timeout_duration: MassaTime,
         version: Version,
         connection_id: ConnectionId,
-        max_bytes_read: f64,
-        max_bytes_write: f64,
+        read_limiter: BandwidthLimiter,
+        write_limiter: BandwidthLimiter,
     ) -> JoinHandle<(ConnectionId, HandshakeReturnType)> {
         debug!("starting handshake with connection_id={}", connection_id);
         massa_trace!("network_worker.new_connection", {
@@ -93,7 +99,7 @@ impl HandshakeWorker {
                 HandshakeWorker {
                     reader: ReadBinder::new(
                         socket_reader,
-                        max_bytes_read,
+                        read_limiter,
                         MAX_MESSAGE_SIZE,
                         MessageDeserializer::new(
                             THREAD_COUNT,
@@ -111,7 +117,7 @@ impl HandshakeWorker {
                             MAX_OPERATION_DATASTORE_VALUE_LENGTH,
                         ),
                     ),
-                    writer: WriteBinder::new(socket_writer, max_bytes_write, MAX_MESSAGE_SIZE),
+                    writer: WriteBinder::new(socket_writer, write_limiter, MAX_MESSAGE_SIZE),
                     self_node_id,
                     keypair,
                     timeout_duration,
@@ -137,14 +143,17 @@ impl HandshakeWorker {
             public_key: self.self_node_id.0,
             random_bytes: self_random_bytes,
             version: self.version,
+            handshake_version: HANDSHAKE_VERSION,
+            features: PeerFeatures::SUPPORTED,
         };
+        let init_sent_time = MassaTime::now(0)?;
         let send_init_fut = self.writer.send(&msg);
 
         // receive handshake init future
         let recv_init_fut = self.reader.next();
 
         // join send_init_fut and recv_init_fut with a timeout, and match result
-        let (other_node_id, other_random_bytes, other_version) = match timeout(
+        let (other_node_id, other_random_bytes, other_version, other_features) = match timeout(
             self.timeout_duration.to_duration(),
             try_join(send_init_fut, recv_init_fut),
         )
@@ -158,7 +167,9 @@ impl HandshakeWorker {
                     public_key: pk,
                     random_bytes: rb,
                     version,
-                } => (NodeId(pk), rb, version),
+                    handshake_version: _,
+                    features,
+                } => (NodeId(pk), rb, version, features),
                 Message::PeerList(list) => throw!(PeerListReceived, list),
                 _ => throw!(HandshakeWrongMessage),
             },
@@ -174,6 +185,10 @@ impl HandshakeWorker {
             throw!(IncompatibleVersion)
         }
 
+        // only the features supported by both ends can be used on the connection,
+        // nodes that do not announce features are considered as supporting none
+        let features = PeerFeatures::SUPPORTED.intersection(other_features);
+
         // sign their random bytes
         let other_random_hash = Hash::compute_from(&other_random_bytes);
         let self_signature = self.keypair.sign(&other_random_hash)?;
@@ -181,6 +196,7 @@ impl HandshakeWorker {
         // send handshake reply future
         let msg = Message::HandshakeReply {
             signature: self_signature,
+            timestamp: Some(MassaTime::now(0)?),
         };
         let send_reply_fut = self.writer.send(&msg);
 
@@ -188,7 +204,7 @@ impl HandshakeWorker {
         let recv_reply_fut = self.reader.next();
 
         // join send_reply_fut and recv_reply_fut with a timeout, and match result
-        let other_signature = match timeout(
+        let (other_signature, other_timestamp) = match timeout(
             self.timeout_duration.to_duration(),
             try_join(send_reply_fut, recv_reply_fut),
         )
@@ -198,7 +214,10 @@ impl HandshakeWorker {
             Ok(Err(e)) => return Err(e),
             Ok(Ok((_, None))) => throw!(HandshakeInterruption, "repl".into()),
             Ok(Ok((_, Some((_, msg))))) => match msg {
-                Message::HandshakeReply { signature: sig } => sig,
+                Message::HandshakeReply {
+                    signature: sig,
+                    timestamp,
+                } => (sig, timestamp),
                 _ => throw!(HandshakeWrongMessage),
             },
         };
@@ -211,6 +230,22 @@ impl HandshakeWorker {
                 NetworkError::HandshakeError(HandshakeErrorType::HandshakeInvalidSignature)
             })?;
 
-        Ok((other_node_id, self.reader, self.writer))
+        // The peer replied after receiving our initiation: its timestamp is compared to the middle
+        // of the interval between the sending of our initiation and the reception of its reply.
+        let reply_received_time = MassaTime::now(0)?;
+        let clock_offset = other_timestamp.map(|other_timestamp| {
+            let clock_offset = other_timestamp.to_millis() as i128
+                - (init_sent_time.to_millis() as i128 + reply_received_time.to_millis() as i128)
+                    / 2;
+            clock_offset.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+        });
+
+        Ok((
+            other_node_id,
+            self.reader,
+            self.writer,
+            clock_offset,
+            features,
+        ))
     }
 }
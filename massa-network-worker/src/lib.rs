@@ -18,13 +18,16 @@ use massa_network_exports::{
     NetworkEvent, NetworkEventReceiver, NetworkManagementCommand, NetworkManager,
 };
 use massa_signature::KeyPair;
+use massa_time::ClockCompensation;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
 //pub use establisher::Establisher;
+mod bandwidth;
 mod binders;
 mod handshake_worker;
 mod messages;
+mod nat_traversal;
 mod network_cmd_impl;
 mod network_event;
 mod network_worker;
@@ -41,7 +44,7 @@ pub mod tests;
 pub async fn start_network_controller(
     network_settings: &NetworkConfig,
     mut establisher: Establisher,
-    clock_compensation: i64,
+    clock_compensation: ClockCompensation,
     initial_peers: Option<BootstrapPeers>,
     version: Version,
 ) -> Result<
@@ -71,7 +74,9 @@ pub async fn start_network_controller(
             .map_err(|err| {
                 std::io::Error::new(err.kind(), format!("could not load node key file: {}", err))
             })?;
-        serde_json::from_slice::<KeyPair>(keypair_bs58_check_encoded.as_bytes())?
+        let keypair = serde_json::from_slice::<KeyPair>(keypair_bs58_check_encoded.as_bytes())?;
+        keypair.check_wire_compatible()?;
+        keypair
     } else {
         // node file does not exist: generate the key and save it
         let keypair = KeyPair::generate();
@@ -6,22 +6,26 @@ use massa_models::{
     config::HANDSHAKE_RANDOMNESS_SIZE_BYTES,
     endorsement::{Endorsement, EndorsementDeserializer, WrappedEndorsement},
     operation::{
-        OperationIdsDeserializer, OperationIdsSerializer, OperationPrefixIds,
-        OperationPrefixIdsDeserializer, OperationPrefixIdsSerializer, OperationsDeserializer,
-        OperationsSerializer, WrappedOperation,
+        OperationAnnouncements, OperationAnnouncementsDeserializer,
+        OperationAnnouncementsSerializer, OperationIdsDeserializer, OperationIdsSerializer,
+        OperationPrefixIds, OperationPrefixIdsDeserializer, OperationPrefixIdsSerializer,
+        OperationsDeserializer, OperationsSerializer, WrappedOperation,
     },
     serialization::array_from_slice,
     serialization::{IpAddrDeserializer, IpAddrSerializer},
     version::{Version, VersionDeserializer, VersionSerializer},
     wrapped::{WrappedDeserializer, WrappedSerializer},
 };
-use massa_network_exports::{AskForBlocksInfo, BlockInfoReply};
+use massa_network_exports::{AskForBlocksInfo, BlockInfoReply, PeerFeatures};
 use massa_serialization::{
     Deserializer, SerializeError, Serializer, U32VarIntDeserializer, U32VarIntSerializer,
+    U64VarIntDeserializer, U64VarIntSerializer,
 };
 use massa_signature::{PublicKey, PublicKeyDeserializer, Signature, SignatureDeserializer};
+use massa_time::{MassaTime, MassaTimeDeserializer, MassaTimeSerializer};
 use nom::{
     bytes::complete::take,
+    combinator::opt,
     error::{context, ContextError, ParseError},
     multi::length_count,
     sequence::tuple,
@@ -45,11 +49,18 @@ pub enum Message {
         /// let us know their public key.
         random_bytes: [u8; HANDSHAKE_RANDOMNESS_SIZE_BYTES],
         version: Version,
+        /// Version of the handshake, `LEGACY_HANDSHAKE_VERSION` for nodes that do not announce features.
+        handshake_version: u32,
+        /// Features supported by the sender.
+        features: PeerFeatures,
     },
     /// Reply to a handshake initiation message.
     HandshakeReply {
         /// Signature of the received random bytes with our `keypair`.
         signature: Signature,
+        /// System time of the sender when replying, not compensated, used to estimate clock drifts.
+        /// Absent from the replies of the nodes that do not send it.
+        timestamp: Option<MassaTime>,
     },
     /// Block header
     BlockHeader(WrappedHeader),
@@ -72,6 +83,9 @@ pub enum Message {
     Operations(Vec<WrappedOperation>),
     /// Endorsements
     Endorsements(Vec<WrappedEndorsement>),
+    /// Batch of operation ids along the fee density of the operations.
+    /// Only sent to the nodes that negotiated `PeerFeatures::OPERATION_FEE_DENSITY`.
+    OperationFeeAnnouncements(OperationAnnouncements),
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -88,6 +102,7 @@ pub(crate) enum MessageTypeId {
     AskForOperations,
     OperationsAnnouncement,
     ReplyForBlocks,
+    OperationFeeAnnouncements,
 }
 
 #[derive(IntoPrimitive, Debug, Eq, PartialEq, TryFromPrimitive)]
@@ -99,15 +114,26 @@ pub(crate) enum BlockInfoType {
     NotFound,
 }
 
+/// Version of the handshakes of the nodes that do not announce features:
+/// their handshake initiation ends after the node version
+pub const LEGACY_HANDSHAKE_VERSION: u32 = 0;
+
+/// Version of the handshakes of this node, whose initiation is followed by the handshake version and the features.
+/// Older nodes ignore these trailing fields
+pub const HANDSHAKE_VERSION: u32 = 1;
+
 /// Basic serializer for `Message`.
 pub struct MessageSerializer {
     version_serializer: VersionSerializer,
     u32_serializer: U32VarIntSerializer,
+    u64_serializer: U64VarIntSerializer,
     wrapped_serializer: WrappedSerializer,
     operation_prefix_ids_serializer: OperationPrefixIdsSerializer,
+    operation_announcements_serializer: OperationAnnouncementsSerializer,
     operations_ids_serializer: OperationIdsSerializer,
     operations_serializer: OperationsSerializer,
     ip_addr_serializer: IpAddrSerializer,
+    time_serializer: MassaTimeSerializer,
 }
 
 impl MessageSerializer {
@@ -116,11 +142,14 @@ impl MessageSerializer {
         MessageSerializer {
             version_serializer: VersionSerializer::new(),
             u32_serializer: U32VarIntSerializer::new(),
+            u64_serializer: U64VarIntSerializer::new(),
             wrapped_serializer: WrappedSerializer::new(),
             operation_prefix_ids_serializer: OperationPrefixIdsSerializer::new(),
+            operation_announcements_serializer: OperationAnnouncementsSerializer::new(),
             operations_ids_serializer: OperationIdsSerializer::new(),
             operations_serializer: OperationsSerializer::new(),
             ip_addr_serializer: IpAddrSerializer::new(),
+            time_serializer: MassaTimeSerializer::new(),
         }
     }
 }
@@ -140,17 +169,30 @@ impl Serializer<Message> for MessageSerializer {
                 public_key,
                 random_bytes,
                 version,
+                handshake_version,
+                features,
             } => {
                 self.u32_serializer
                     .serialize(&(MessageTypeId::HandshakeInitiation as u32), buffer)?;
                 buffer.extend(public_key.to_bytes());
                 buffer.extend(random_bytes);
                 self.version_serializer.serialize(version, buffer)?;
+                if *handshake_version != LEGACY_HANDSHAKE_VERSION {
+                    self.u32_serializer.serialize(handshake_version, buffer)?;
+                    self.u64_serializer.serialize(&features.0, buffer)?;
+                }
             }
-            Message::HandshakeReply { signature } => {
+            Message::HandshakeReply {
+                signature,
+                timestamp,
+            } => {
                 self.u32_serializer
                     .serialize(&(MessageTypeId::HandshakeReply as u32), buffer)?;
                 buffer.extend(signature.to_bytes());
+                // older nodes ignore this trailing field
+                if let Some(timestamp) = timestamp {
+                    self.time_serializer.serialize(timestamp, buffer)?;
+                }
             }
             Message::BlockHeader(header) => {
                 self.u32_serializer
@@ -241,6 +283,12 @@ impl Serializer<Message> for MessageSerializer {
                     self.wrapped_serializer.serialize(endorsement, buffer)?;
                 }
             }
+            Message::OperationFeeAnnouncements(operation_announcements) => {
+                self.u32_serializer
+                    .serialize(&(MessageTypeId::OperationFeeAnnouncements as u32), buffer)?;
+                self.operation_announcements_serializer
+                    .serialize(operation_announcements, buffer)?;
+            }
         }
         Ok(())
     }
@@ -251,6 +299,8 @@ pub struct MessageDeserializer {
     public_key_deserializer: PublicKeyDeserializer,
     signature_deserializer: SignatureDeserializer,
     version_deserializer: VersionDeserializer,
+    handshake_version_deserializer: U32VarIntDeserializer,
+    features_deserializer: U64VarIntDeserializer,
     id_deserializer: U32VarIntDeserializer,
     ask_block_number_deserializer: U32VarIntDeserializer,
     peer_list_length_deserializer: U32VarIntDeserializer,
@@ -260,8 +310,10 @@ pub struct MessageDeserializer {
     endorsements_length_deserializer: U32VarIntDeserializer,
     endorsement_deserializer: WrappedDeserializer<Endorsement, EndorsementDeserializer>,
     operation_prefix_ids_deserializer: OperationPrefixIdsDeserializer,
+    operation_announcements_deserializer: OperationAnnouncementsDeserializer,
     infos_deserializer: OperationIdsDeserializer,
     ip_addr_deserializer: IpAddrDeserializer,
+    time_deserializer: MassaTimeDeserializer,
 }
 
 impl MessageDeserializer {
@@ -286,6 +338,11 @@ impl MessageDeserializer {
             public_key_deserializer: PublicKeyDeserializer::new(),
             signature_deserializer: SignatureDeserializer::new(),
             version_deserializer: VersionDeserializer::new(),
+            handshake_version_deserializer: U32VarIntDeserializer::new(
+                Excluded(LEGACY_HANDSHAKE_VERSION),
+                Included(u32::MAX),
+            ),
+            features_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
             id_deserializer: U32VarIntDeserializer::new(Included(0), Included(u32::MAX)),
             ask_block_number_deserializer: U32VarIntDeserializer::new(
                 Included(0),
@@ -320,8 +377,15 @@ impl MessageDeserializer {
             operation_prefix_ids_deserializer: OperationPrefixIdsDeserializer::new(
                 max_operations_per_message,
             ),
+            operation_announcements_deserializer: OperationAnnouncementsDeserializer::new(
+                max_operations_per_message,
+            ),
             infos_deserializer: OperationIdsDeserializer::new(max_operations_per_block),
             ip_addr_deserializer: IpAddrDeserializer::new(),
+            time_deserializer: MassaTimeDeserializer::new((
+                Included(MassaTime::from_millis(0)),
+                Included(MassaTime::from_millis(u64::MAX)),
+            )),
         }
     }
 }
@@ -353,24 +417,50 @@ impl Deserializer<Message> for MessageDeserializer {
                         context("Failed version deserialization", |input| {
                             self.version_deserializer.deserialize(input)
                         }),
+                        // absent from the handshakes of the nodes that do not announce features
+                        opt(tuple((
+                            context("Failed handshake_version deserialization", |input| {
+                                self.handshake_version_deserializer.deserialize(input)
+                            }),
+                            context("Failed features deserialization", |input| {
+                                self.features_deserializer.deserialize(input)
+                            }),
+                        ))),
                     ))
-                    .map(|(public_key, random_bytes, version)| {
+                    .map(|(public_key, random_bytes, version, handshake)| {
+                        let (handshake_version, features) = handshake
+                            .map(|(handshake_version, features)| {
+                                (handshake_version, PeerFeatures(features))
+                            })
+                            .unwrap_or((LEGACY_HANDSHAKE_VERSION, PeerFeatures::default()));
                         // Unwrap safety: we checked above that we took enough bytes
                         Message::HandshakeInitiation {
                             public_key,
                             random_bytes: array_from_slice(random_bytes).unwrap(),
                             version,
+                            handshake_version,
+                            features,
                         }
                     }),
                 )
                 .parse(input),
-                MessageTypeId::HandshakeReply => {
-                    context("Failed HandshakeReply deserialization", |input| {
-                        self.signature_deserializer.deserialize(input)
-                    })
-                    .map(|signature| Message::HandshakeReply { signature })
-                    .parse(input)
-                }
+                MessageTypeId::HandshakeReply => context(
+                    "Failed HandshakeReply deserialization",
+                    tuple((
+                        context("Failed signature deserialization", |input| {
+                            self.signature_deserializer.deserialize(input)
+                        }),
+                        // absent from the replies of the nodes that do not send it
+                        opt(context("Failed timestamp deserialization", |input| {
+                            self.time_deserializer.deserialize(input)
+                        })),
+                    )),
+                )
+                .map(|(signature, timestamp)| Message::HandshakeReply {
+                    signature,
+                    timestamp,
+                })
+                .parse(input),
                 MessageTypeId::BlockHeader => {
                     context("Failed BlockHeader deserialization", |input| {
                         self.block_header_deserializer.deserialize(input)
@@ -527,6 +617,12 @@ impl Deserializer<Message> for MessageDeserializer {
                 )
                 .map(Message::Endorsements)
                 .parse(input),
+                MessageTypeId::OperationFeeAnnouncements => context(
+                    "Failed OperationFeeAnnouncements deserialization",
+                    |input| self.operation_announcements_deserializer.deserialize(input),
+                )
+                .map(Message::OperationFeeAnnouncements)
+                .parse(input),
             }
         })
         .parse(buffer)
@@ -543,6 +639,7 @@ mod tests {
         MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH,
         MAX_PARAMETERS_SIZE, THREAD_COUNT,
     };
+    use massa_models::operation::{OperationPrefixId, OPERATION_ID_PREFIX_SIZE_BYTES};
     use massa_serialization::DeserializeError;
     use massa_signature::KeyPair;
     use rand::{prelude::StdRng, RngCore, SeedableRng};
@@ -575,6 +672,8 @@ mod tests {
             public_key: keypair.get_public_key(),
             random_bytes,
             version: Version::from_str("TEST.1.10").unwrap(),
+            handshake_version: HANDSHAKE_VERSION,
+            features: PeerFeatures::COMPRESSION,
         };
         let mut ser = Vec::new();
         message_serializer.serialize(&msg, &mut ser).unwrap();
@@ -587,16 +686,197 @@ mod tests {
                     public_key: pk1,
                     random_bytes: rb1,
                     version: v1,
+                    handshake_version: hv1,
+                    features: f1,
                 },
                 Message::HandshakeInitiation {
                     public_key,
                     random_bytes,
                     version,
+                    handshake_version,
+                    features,
                 },
             ) => {
                 assert_eq!(pk1, public_key);
                 assert_eq!(rb1, random_bytes);
                 assert_eq!(v1, version);
+                assert_eq!(hv1, handshake_version);
+                assert_eq!(f1, features);
+            }
+            _ => panic!("unexpected message"),
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_legacy_handshake_initiation() {
+        let message_serializer = MessageSerializer::new();
+        let message_deserializer = MessageDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_ADVERTISE_LENGTH,
+            MAX_ASK_BLOCKS_PER_MESSAGE,
+            MAX_OPERATIONS_PER_BLOCK,
+            MAX_OPERATIONS_PER_MESSAGE,
+            MAX_ENDORSEMENTS_PER_MESSAGE,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        );
+        let keypair = KeyPair::generate();
+        // a legacy initiation ends after the node version, whatever the features
+        let msg = Message::HandshakeInitiation {
+            public_key: keypair.get_public_key(),
+            random_bytes: [0u8; 32],
+            version: Version::from_str("TEST.1.10").unwrap(),
+            handshake_version: LEGACY_HANDSHAKE_VERSION,
+            features: PeerFeatures::COMPRESSION,
+        };
+        let mut ser = Vec::new();
+        message_serializer.serialize(&msg, &mut ser).unwrap();
+        let (rest, deser) = message_deserializer
+            .deserialize::<DeserializeError>(&ser)
+            .unwrap();
+        assert!(rest.is_empty());
+        match deser {
+            Message::HandshakeInitiation {
+                handshake_version,
+                features,
+                ..
+            } => {
+                assert_eq!(handshake_version, LEGACY_HANDSHAKE_VERSION);
+                assert_eq!(features, PeerFeatures::default());
+            }
+            _ => panic!("unexpected message"),
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_handshake_reply_timestamp() {
+        let message_serializer = MessageSerializer::new();
+        let message_deserializer = MessageDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_ADVERTISE_LENGTH,
+            MAX_ASK_BLOCKS_PER_MESSAGE,
+            MAX_OPERATIONS_PER_BLOCK,
+            MAX_OPERATIONS_PER_MESSAGE,
+            MAX_ENDORSEMENTS_PER_MESSAGE,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        );
+        let keypair = KeyPair::generate();
+        let signature = keypair
+            .sign(&massa_hash::Hash::compute_from(&[0u8; 32]))
+            .unwrap();
+        // the replies of older nodes end after the signature
+        for timestamp in [Some(MassaTime::from_millis(1234)), None] {
+            let msg = Message::HandshakeReply {
+                signature,
+                timestamp,
+            };
+            let mut ser = Vec::new();
+            message_serializer.serialize(&msg, &mut ser).unwrap();
+            let (rest, deser) = message_deserializer
+                .deserialize::<DeserializeError>(&ser)
+                .unwrap();
+            assert!(rest.is_empty());
+            match deser {
+                Message::HandshakeReply {
+                    signature: deser_signature,
+                    timestamp: deser_timestamp,
+                } => {
+                    assert_eq!(deser_signature, signature);
+                    assert_eq!(deser_timestamp, timestamp);
+                }
+                _ => panic!("unexpected message"),
+            }
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_operation_announcements() {
+        let message_serializer = MessageSerializer::new();
+        let message_deserializer = MessageDeserializer::new(
+            THREAD_COUNT,
+            ENDORSEMENT_COUNT,
+            MAX_ADVERTISE_LENGTH,
+            MAX_ASK_BLOCKS_PER_MESSAGE,
+            MAX_OPERATIONS_PER_BLOCK,
+            MAX_OPERATIONS_PER_MESSAGE,
+            MAX_ENDORSEMENTS_PER_MESSAGE,
+            MAX_DATASTORE_VALUE_LENGTH,
+            MAX_FUNCTION_NAME_LENGTH,
+            MAX_PARAMETERS_SIZE,
+            MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+            MAX_OPERATION_DATASTORE_KEY_LENGTH,
+            MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+        );
+        let prefix_1 = OperationPrefixId::from(&[1; OPERATION_ID_PREFIX_SIZE_BYTES]);
+        let prefix_2 = OperationPrefixId::from(&[2; OPERATION_ID_PREFIX_SIZE_BYTES]);
+
+        // the legacy announcements keep their type id and format
+        let prefix_ids: OperationPrefixIds = [prefix_1, prefix_2].into_iter().collect();
+        let mut ser = Vec::new();
+        message_serializer
+            .serialize(
+                &Message::OperationsAnnouncement(prefix_ids.clone()),
+                &mut ser,
+            )
+            .unwrap();
+        let mut expected = Vec::new();
+        U32VarIntSerializer::new()
+            .serialize(
+                &(MessageTypeId::OperationsAnnouncement as u32),
+                &mut expected,
+            )
+            .unwrap();
+        OperationPrefixIdsSerializer::new()
+            .serialize(&prefix_ids, &mut expected)
+            .unwrap();
+        assert_eq!(ser, expected);
+        let (rest, deser) = message_deserializer
+            .deserialize::<DeserializeError>(&ser)
+            .unwrap();
+        assert!(rest.is_empty());
+        match deser {
+            Message::OperationsAnnouncement(deser_prefix_ids) => {
+                assert_eq!(deser_prefix_ids, prefix_ids)
+            }
+            _ => panic!("unexpected message"),
+        }
+
+        // the fee densities are sent with their own type id
+        let announcements: OperationAnnouncements =
+            [(prefix_1, 1000), (prefix_2, 0)].into_iter().collect();
+        let mut ser = Vec::new();
+        message_serializer
+            .serialize(
+                &Message::OperationFeeAnnouncements(announcements.clone()),
+                &mut ser,
+            )
+            .unwrap();
+        let (rest, id) = U32VarIntDeserializer::new(Included(0), Included(u32::MAX))
+            .deserialize::<DeserializeError>(&ser)
+            .unwrap();
+        assert_eq!(id, MessageTypeId::OperationFeeAnnouncements as u32);
+        assert!(!rest.is_empty());
+        let (rest, deser) = message_deserializer
+            .deserialize::<DeserializeError>(&ser)
+            .unwrap();
+        assert!(rest.is_empty());
+        match deser {
+            Message::OperationFeeAnnouncements(deser_announcements) => {
+                assert_eq!(deser_announcements, announcements)
             }
             _ => panic!("unexpected message"),
         }
@@ -0,0 +1,349 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! NAT traversal.
+//!
+//! Maps the protocol port on the local router so that a node behind a NAT accepts inbound connections.
+//! UPnP IGD is tried first, then NAT-PMP (RFC 6886). The mapping is renewed at half of its lease
+//! and removed when the network worker stops. Every successful mapping reports the external IP
+//! of the router to the network worker, which advertises it if no routable IP is configured.
+
+use igd::aio::Gateway;
+use igd::{PortMappingProtocol, SearchOptions};
+use massa_network_exports::{NetworkConfig, NetworkError};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// NAT-PMP server port on the gateway
+const NAT_PMP_PORT: u16 = 5351;
+
+/// Number of NAT-PMP request attempts, the response timeout doubling at each attempt
+const NAT_PMP_ATTEMPTS: u32 = 4;
+
+/// First NAT-PMP response timeout
+const NAT_PMP_INITIAL_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// UPnP gateway discovery timeout
+const UPNP_SEARCH_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Description of the UPnP port mapping shown by the router
+const UPNP_MAPPING_DESCRIPTION: &str = "massa node";
+
+/// Parameters of the port mapping
+#[derive(Debug, Clone, Copy)]
+struct NatTraversalConfig {
+    /// local port the protocol listens on
+    local_port: u16,
+    /// port advertised to the other nodes, mapped on the router
+    external_port: u16,
+    /// NAT-PMP gateway, guessed from the local IP if None
+    nat_pmp_gateway: Option<IpAddr>,
+    /// lease duration of the mapping in seconds
+    lease_secs: u32,
+}
+
+/// Port mapping created on the router
+enum PortMapping {
+    /// mapping created through UPnP IGD
+    Upnp(Gateway),
+    /// mapping created through NAT-PMP
+    NatPmp(SocketAddr),
+}
+
+/// Allows stopping the NAT traversal task
+pub(crate) struct NatTraversalManager {
+    stop_tx: oneshot::Sender<()>,
+    join_handle: JoinHandle<()>,
+}
+
+impl NatTraversalManager {
+    /// stop the NAT traversal task, removing the port mapping
+    pub async fn stop(self) {
+        let _ = self.stop_tx.send(());
+        if let Err(err) = self.join_handle.await {
+            warn!("NAT traversal task panicked: {}", err);
+        }
+    }
+}
+
+/// Starts the task mapping the protocol port on the router.
+///
+/// # Arguments
+/// * `cfg`: network configuration
+/// * `external_ip_tx`: channel receiving the external IP reported by the router after each mapping
+pub(crate) fn start_nat_traversal(
+    cfg: &NetworkConfig,
+    external_ip_tx: mpsc::Sender<IpAddr>,
+) -> NatTraversalManager {
+    let config = NatTraversalConfig {
+        local_port: cfg.bind.port(),
+        external_port: cfg.protocol_port,
+        nat_pmp_gateway: cfg.nat_pmp_gateway,
+        lease_secs: cfg
+            .nat_mapping_lease
+            .to_duration()
+            .as_secs()
+            .clamp(1, u32::MAX as u64) as u32,
+    };
+    let (stop_tx, stop_rx) = oneshot::channel();
+    let join_handle = tokio::spawn(run(config, external_ip_tx, stop_rx));
+    NatTraversalManager {
+        stop_tx,
+        join_handle,
+    }
+}
+
+/// main loop: map the port, then renew the mapping at half of its lease until stopped
+async fn run(
+    config: NatTraversalConfig,
+    external_ip_tx: mpsc::Sender<IpAddr>,
+    mut stop_rx: oneshot::Receiver<()>,
+) {
+    let renew_interval = Duration::from_secs((config.lease_secs / 2).max(1) as u64);
+    let mut mapping = None;
+    loop {
+        match map_port(&config).await {
+            Ok((new_mapping, external_ip)) => {
+                debug!(
+                    "protocol port {} mapped on the router, external IP is {}",
+                    config.external_port, external_ip
+                );
+                // the worker only keeps the latest IP: if it is busy, the next renewal will report it again
+                let _ = external_ip_tx.try_send(external_ip);
+                mapping = Some(new_mapping);
+            }
+            Err(err) => warn!(
+                "could not map protocol port {} on the router: {}",
+                config.external_port, err
+            ),
+        }
+        tokio::select! {
+            _ = &mut stop_rx => break,
+            _ = tokio::time::sleep(renew_interval) => {}
+        }
+    }
+
+    if let Some(mapping) = mapping {
+        if let Err(err) = unmap_port(&config, mapping).await {
+            debug!("could not remove the protocol port mapping: {}", err);
+        }
+    }
+}
+
+/// Maps the protocol port through UPnP, or NAT-PMP if UPnP is unavailable.
+/// Returns the mapping and the external IP of the router.
+async fn map_port(config: &NatTraversalConfig) -> Result<(PortMapping, IpAddr), NetworkError> {
+    let upnp_err = match map_port_upnp(config).await {
+        Ok((gateway, external_ip)) => return Ok((PortMapping::Upnp(gateway), external_ip)),
+        Err(err) => err,
+    };
+    match map_port_nat_pmp(config).await {
+        Ok((gateway, external_ip)) => Ok((PortMapping::NatPmp(gateway), external_ip)),
+        Err(nat_pmp_err) => Err(NetworkError::NatTraversalError(format!(
+            "UPnP: {}, NAT-PMP: {}",
+            upnp_err, nat_pmp_err
+        ))),
+    }
+}
+
+/// Removes a port mapping
+async fn unmap_port(config: &NatTraversalConfig, mapping: PortMapping) -> Result<(), NetworkError> {
+    match mapping {
+        PortMapping::Upnp(gateway) => gateway
+            .remove_port(PortMappingProtocol::TCP, config.external_port)
+            .await
+            .map_err(|err| NetworkError::NatTraversalError(err.to_string())),
+        PortMapping::NatPmp(gateway) => nat_pmp_request_mapping(gateway, config.local_port, 0, 0)
+            .await
+            .map(|_| ()),
+    }
+}
+
+/// Gets the local IP used to reach an address, without sending anything
+async fn get_local_ip_towards(addr: SocketAddr) -> Result<IpAddr, NetworkError> {
+    let socket = UdpSocket::bind(SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0)).await?;
+    socket.connect(addr).await?;
+    Ok(socket.local_addr()?.ip())
+}
+
+/// Maps the protocol port through UPnP IGD
+async fn map_port_upnp(config: &NatTraversalConfig) -> Result<(Gateway, IpAddr), NetworkError> {
+    let gateway = igd::aio::search_gateway(SearchOptions {
+        timeout: Some(UPNP_SEARCH_TIMEOUT),
+        ..Default::default()
+    })
+    .await
+    .map_err(|err| NetworkError::NatTraversalError(err.to_string()))?;
+    let local_ip = match get_local_ip_towards(SocketAddr::V4(gateway.addr)).await? {
+        IpAddr::V4(ip) => ip,
+        IpAddr::V6(ip) => return Err(NetworkError::InvalidIpError(IpAddr::V6(ip))),
+    };
+    gateway
+        .add_port(
+            PortMappingProtocol::TCP,
+            config.external_port,
+            SocketAddrV4::new(local_ip, config.local_port),
+            config.lease_secs,
+            UPNP_MAPPING_DESCRIPTION,
+        )
+        .await
+        .map_err(|err| NetworkError::NatTraversalError(err.to_string()))?;
+    let external_ip = gateway
+        .get_external_ip()
+        .await
+        .map_err(|err| NetworkError::NatTraversalError(err.to_string()))?;
+    Ok((gateway, IpAddr::V4(external_ip)))
+}
+
+/// Maps the protocol port through NAT-PMP
+async fn map_port_nat_pmp(
+    config: &NatTraversalConfig,
+) -> Result<(SocketAddr, IpAddr), NetworkError> {
+    let gateway_ip = match config.nat_pmp_gateway {
+        Some(ip) => ip,
+        None => {
+            // the gateway is usually the first address of the local network.
+            // Connecting to a global address only selects the local IP, nothing is sent.
+            match get_local_ip_towards(SocketAddr::new(Ipv4Addr::new(1, 1, 1, 1).into(), 53))
+                .await?
+            {
+                IpAddr::V4(ip) => {
+                    let [a, b, c, _] = ip.octets();
+                    IpAddr::V4(Ipv4Addr::new(a, b, c, 1))
+                }
+                ip => return Err(NetworkError::InvalidIpError(ip)),
+            }
+        }
+    };
+    let gateway = SocketAddr::new(gateway_ip, NAT_PMP_PORT);
+
+    // external address request: version 0, opcode 0
+    let response = nat_pmp_request(gateway, &[0, 0], 12).await?;
+    let external_ip = IpAddr::V4(Ipv4Addr::new(
+        response[8],
+        response[9],
+        response[10],
+        response[11],
+    ));
+
+    let mapped_port = nat_pmp_request_mapping(
+        gateway,
+        config.local_port,
+        config.external_port,
+        config.lease_secs,
+    )
+    .await?;
+    if mapped_port != config.external_port {
+        // other nodes would not reach us on the advertised port: give the mapping back
+        let _ = nat_pmp_request_mapping(gateway, config.local_port, 0, 0).await;
+        return Err(NetworkError::NatTraversalError(format!(
+            "the router mapped port {} instead of {}",
+            mapped_port, config.external_port
+        )));
+    }
+    Ok((gateway, external_ip))
+}
+
+/// Requests a TCP port mapping through NAT-PMP, a zero lifetime removing it.
+/// Returns the mapped external port.
+async fn nat_pmp_request_mapping(
+    gateway: SocketAddr,
+    local_port: u16,
+    external_port: u16,
+    lifetime_secs: u32,
+) -> Result<u16, NetworkError> {
+    // version 0, opcode 2 (TCP), reserved, internal port, suggested external port, lifetime
+    let mut request = vec![0u8, 2, 0, 0];
+    request.extend(local_port.to_be_bytes());
+    request.extend(external_port.to_be_bytes());
+    request.extend(lifetime_secs.to_be_bytes());
+    let response = nat_pmp_request(gateway, &request, 16).await?;
+    Ok(u16::from_be_bytes([response[10], response[11]]))
+}
+
+/// Sends a NAT-PMP request, retrying with a doubling timeout, and checks the result code of the response
+async fn nat_pmp_request(
+    gateway: SocketAddr,
+    request: &[u8],
+    response_len: usize,
+) -> Result<Vec<u8>, NetworkError> {
+    let socket = UdpSocket::bind(SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0)).await?;
+    socket.connect(gateway).await?;
+    let mut timeout = NAT_PMP_INITIAL_TIMEOUT;
+    let mut response = vec![0u8; response_len];
+    for _ in 0..NAT_PMP_ATTEMPTS {
+        socket.send(request).await?;
+        match tokio::time::timeout(timeout, socket.recv(&mut response)).await {
+            Ok(Ok(len)) => {
+                // the response opcode is the request opcode + 128
+                if len < response_len || response[1] != request[1] + 128 {
+                    return Err(NetworkError::NatTraversalError(
+                        "invalid NAT-PMP response".into(),
+                    ));
+                }
+                let result_code = u16::from_be_bytes([response[2], response[3]]);
+                if result_code != 0 {
+                    return Err(NetworkError::NatTraversalError(format!(
+                        "NAT-PMP result code {}",
+                        result_code
+                    )));
+                }
+                return Ok(response);
+            }
+            Ok(Err(err)) => return Err(err.into()),
+            Err(_) => timeout *= 2,
+        }
+    }
+    Err(NetworkError::NatTraversalError(format!(
+        "no NAT-PMP response from {}",
+        gateway
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_nat_pmp_mapping_request() {
+        // fake gateway answering a mapping request with another external port
+        let gateway_socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let gateway = gateway_socket.local_addr().unwrap();
+        let gateway_task = tokio::spawn(async move {
+            let mut request = [0u8; 12];
+            let (len, from) = gateway_socket.recv_from(&mut request).await.unwrap();
+            assert_eq!(len, 12);
+            assert_eq!(&request[..4], &[0, 2, 0, 0]);
+            assert_eq!(u16::from_be_bytes([request[4], request[5]]), 31244);
+            assert_eq!(u16::from_be_bytes([request[6], request[7]]), 31245);
+            assert_eq!(
+                u32::from_be_bytes([request[8], request[9], request[10], request[11]]),
+                3600
+            );
+            let mut response = vec![0u8, 130, 0, 0, 0, 0, 0, 1];
+            response.extend(31244u16.to_be_bytes());
+            response.extend(40000u16.to_be_bytes());
+            response.extend(3600u32.to_be_bytes());
+            gateway_socket.send_to(&response, from).await.unwrap();
+
+            // an error result code is reported
+            let (_, from) = gateway_socket.recv_from(&mut request).await.unwrap();
+            response[3] = 2;
+            gateway_socket.send_to(&response, from).await.unwrap();
+        });
+
+        assert_eq!(
+            nat_pmp_request_mapping(gateway, 31244, 31245, 3600)
+                .await
+                .unwrap(),
+            40000
+        );
+        assert!(nat_pmp_request_mapping(gateway, 31244, 31245, 3600)
+            .await
+            .is_err());
+        gateway_task.await.unwrap();
+    }
+}
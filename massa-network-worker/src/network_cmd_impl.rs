@@ -28,7 +28,7 @@ use massa_models::{
     composite::PubkeySig,
     endorsement::WrappedEndorsement,
     node::NodeId,
-    operation::{OperationPrefixIds, WrappedOperation},
+    operation::{OperationAnnouncements, OperationPrefixIds, WrappedOperation},
     stats::NetworkStats,
 };
 use massa_network_exports::{
@@ -138,7 +138,7 @@ async fn get_peers(worker: &mut NetworkWorker, response_tx: oneshot::Sender<Peer
         })
         .collect();
 
-    // HashMap<NodeId, (ConnectionId, mpsc::Sender<NodeCommand>)
+    // HashMap<NodeId, (ConnectionId, NodeCommandSender)
     if response_tx
         .send(Peers {
             peers,
@@ -339,12 +339,24 @@ pub async fn on_get_stats_cmd(
             .filter(|(_, p)| p.banned)
             .fold(0, |acc, _| acc + 1),
         active_node_count: worker.active_nodes.len() as u64,
+        bytes_read: worker.read_bandwidth.get_total_bytes(),
+        bytes_written: worker.write_bandwidth.get_total_bytes(),
     };
     if response_tx.send(res).is_err() {
         warn!("network: could not send NodeSignMessage response upstream");
     }
 }
 
+pub async fn on_get_clock_offsets_cmd(
+    worker: &mut NetworkWorker,
+    response_tx: oneshot::Sender<Vec<i64>>,
+) {
+    let res = worker.clock_offsets.values().copied().collect();
+    if response_tx.send(res).is_err() {
+        warn!("network: could not send GetClockOffsets response upstream");
+    }
+}
+
 /// Network worker received the command `NetworkCommand::SendOperations` from
 /// the controller. Happen when the program has received a new set of operation
 /// or run a kind of "send operations" loop.
@@ -392,6 +404,27 @@ pub async fn on_send_operation_batches_cmd(
     while futs.next().await.is_some() {}
 }
 
+/// On the command `[massa_network_exports::NetworkCommand::SendOperationFeeAnnouncements]`
+/// is called, forward the command to the `NodeWorker` and propagate to the network
+pub async fn on_send_operation_fee_batches_cmd(
+    worker: &mut NetworkWorker,
+    to_node: NodeId,
+    batch: OperationAnnouncements,
+) {
+    massa_trace!(
+        "network_worker.manage_network_command receive NetworkCommand::SendOperationFeeAnnouncements",
+        { "batch": batch.keys().collect::<Vec<_>>() }
+    );
+    worker
+        .event
+        .forward(
+            to_node,
+            worker.active_nodes.get(&to_node),
+            NodeCommand::SendOperationFeeAnnouncements(batch),
+        )
+        .await;
+}
+
 /// Network worker received the command `NetworkCommand::AskForOperations` from
 /// the controller. Happen when the program run a kind of "ask operations" loop
 /// or received a new batch.
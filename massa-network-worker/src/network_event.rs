@@ -1,7 +1,13 @@
+use crate::node_worker::NodeCommandSender;
 use massa_models::node::NodeId;
-use massa_network_exports::{ConnectionId, NetworkError, NetworkEvent, NodeCommand, NodeEvent};
+use massa_network_exports::{
+    ConnectionId, MessagePriority, NetworkError, NetworkEvent, NodeCommand, NodeEvent,
+};
 use std::time::Duration;
-use tokio::sync::mpsc::{self, error::SendTimeoutError};
+use tokio::sync::mpsc::{
+    self,
+    error::{SendTimeoutError, TrySendError},
+};
 use tracing::debug;
 
 pub struct EventSender {
@@ -47,14 +53,31 @@ impl EventSender {
     }
 
     /// Forward a message to a node worker. If it fails, notify upstream about connection closure.
+    ///
+    /// The operation class messages are dropped if the queue of the node is full,
+    /// so that a slow node does not delay the block propagation to the other ones.
     pub async fn forward(
         &self,
         node_id: NodeId,
-        node: Option<&(ConnectionId, mpsc::Sender<NodeCommand>)>,
+        node: Option<&(ConnectionId, NodeCommandSender)>,
         message: NodeCommand,
     ) {
         if let Some((_, node_command_tx)) = node {
-            if node_command_tx.send(message).await.is_err() {
+            let res = match message.priority() {
+                MessagePriority::Block => node_command_tx.send(message).await.is_ok(),
+                MessagePriority::Operation => match node_command_tx.try_send(message) {
+                    Ok(()) => true,
+                    Err(TrySendError::Full(_)) => {
+                        debug!(
+                            "operation message to node {} dropped: its queue is full",
+                            node_id
+                        );
+                        true
+                    }
+                    Err(TrySendError::Closed(_)) => false,
+                },
+            };
+            if !res {
                 debug!(
                     "{}",
                     NetworkError::ChannelError("contact with node worker lost while trying to send it a message. Probably a peer disconnect.".into())
@@ -83,7 +106,7 @@ pub mod event_impl {
         block::{BlockId, WrappedHeader},
         endorsement::WrappedEndorsement,
         node::NodeId,
-        operation::{OperationPrefixIds, WrappedOperation},
+        operation::{OperationAnnouncements, OperationPrefixIds, WrappedOperation},
         wrapped::Id,
     };
     use massa_network_exports::{AskForBlocksInfo, BlockInfoReply, NodeCommand};
@@ -218,11 +241,11 @@ pub mod event_impl {
     pub async fn on_received_operations_annoncement(
         worker: &mut NetworkWorker,
         from: NodeId,
-        operation_prefix_ids: OperationPrefixIds,
+        operation_prefix_ids: OperationAnnouncements,
     ) {
         massa_trace!(
             "network_worker.on_node_event receive NetworkEvent::ReceivedOperationAnnouncements",
-            { "operations": operation_prefix_ids }
+            { "operations": operation_prefix_ids.keys().collect::<Vec<_>>() }
         );
         if let Err(err) = worker
             .event
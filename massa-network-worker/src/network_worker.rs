@@ -2,12 +2,16 @@
 
 //! The network worker actually does the job of managing connections
 use super::{
-    handshake_worker::HandshakeReturnType, node_worker::NodeWorker, peer_info_database::*,
+    handshake_worker::HandshakeReturnType,
+    node_worker::{node_command_channel, NodeCommandSender, NodeWorker},
+    peer_info_database::*,
 };
 use crate::{
+    bandwidth::{BandwidthLimiter, SharedBandwidth},
     binders::{ReadBinder, WriteBinder},
     handshake_worker::HandshakeWorker,
     messages::{Message, MessageDeserializer},
+    nat_traversal::{start_nat_traversal, NatTraversalManager},
     network_event::EventSender,
 };
 use futures::{stream::FuturesUnordered, StreamExt};
@@ -16,7 +20,7 @@ use massa_models::{node::NodeId, version::Version};
 use massa_network_exports::{
     ConnectionClosureReason, ConnectionId, Establisher, HandshakeErrorType, Listener,
     NetworkCommand, NetworkConfig, NetworkConnectionErrorType, NetworkError, NetworkEvent,
-    NetworkManagementCommand, NodeCommand, NodeEvent, NodeEventType, ReadHalf, WriteHalf,
+    NetworkManagementCommand, NodeCommand, NodeEvent, NodeEventType, PeerType, ReadHalf, WriteHalf,
 };
 use massa_signature::KeyPair;
 use std::{
@@ -25,7 +29,7 @@ use std::{
 };
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
-use tracing::{debug, trace, warn};
+use tracing::{debug, info, trace, warn};
 
 /// Real job is done by network worker
 pub struct NetworkWorker {
@@ -54,7 +58,9 @@ pub struct NetworkWorker {
     /// Receiving channel for node events.
     node_event_rx: mpsc::Receiver<NodeEvent>,
     /// Ids of active nodes mapped to Connection id, node command sender and handle on the associated node worker.
-    pub(crate) active_nodes: HashMap<NodeId, (ConnectionId, mpsc::Sender<NodeCommand>)>,
+    pub(crate) active_nodes: HashMap<NodeId, (ConnectionId, NodeCommandSender)>,
+    /// Clock offsets of the active nodes relatively to ours, in milliseconds, estimated during the handshake
+    pub(crate) clock_offsets: HashMap<NodeId, i64>,
     /// Node worker handles
     node_worker_handles:
         FuturesUnordered<JoinHandle<(NodeId, Result<ConnectionClosureReason, NetworkError>)>>,
@@ -64,6 +70,14 @@ pub struct NetworkWorker {
     version: Version,
     /// Event sender
     pub(crate) event: EventSender,
+    /// NAT traversal task, if enabled
+    nat_traversal: Option<NatTraversalManager>,
+    /// Receiver for the external IPs reported by the router
+    external_ip_rx: mpsc::Receiver<IpAddr>,
+    /// Bandwidth read by all the connections
+    pub(crate) read_bandwidth: SharedBandwidth,
+    /// Bandwidth written by all the connections
+    pub(crate) write_bandwidth: SharedBandwidth,
 }
 
 pub struct NetworkWorkerChannels {
@@ -102,6 +116,14 @@ impl NetworkWorker {
         let (node_event_tx, node_event_rx) =
             mpsc::channel::<NodeEvent>(cfg.node_event_channel_size);
         let max_wait_event = cfg.max_send_wait_network_event.to_duration();
+        let (external_ip_tx, external_ip_rx) = mpsc::channel::<IpAddr>(1);
+        let nat_traversal = if cfg.nat_traversal {
+            Some(start_nat_traversal(&cfg, external_ip_tx))
+        } else {
+            None
+        };
+        let read_bandwidth = SharedBandwidth::new(cfg.max_total_bytes_read);
+        let write_bandwidth = SharedBandwidth::new(cfg.max_total_bytes_write);
         NetworkWorker {
             cfg,
             self_node_id,
@@ -117,12 +139,41 @@ impl NetworkWorker {
             handshake_peer_list_futures: FuturesUnordered::new(),
             node_event_rx,
             active_nodes: HashMap::new(),
+            clock_offsets: HashMap::new(),
             node_worker_handles: FuturesUnordered::new(),
             active_connections: HashMap::new(),
             version,
+            nat_traversal,
+            external_ip_rx,
+            read_bandwidth,
+            write_bandwidth,
         }
     }
 
+    /// Creates the read and write bandwidth limiters of a new connection with `ip`,
+    /// capped according to the type of the peer
+    fn connection_limiters(&self, ip: &IpAddr) -> (BandwidthLimiter, BandwidthLimiter) {
+        let peer_type = self
+            .peer_info_db
+            .peers
+            .get(ip)
+            .map(|peer| peer.peer_type)
+            .unwrap_or_default();
+        let (max_bytes_read, max_bytes_write) = match peer_type {
+            PeerType::WhiteListed => (
+                self.cfg.max_bytes_read_whitelisted,
+                self.cfg.max_bytes_write_whitelisted,
+            ),
+            PeerType::Standard | PeerType::Bootstrap => {
+                (self.cfg.max_bytes_read, self.cfg.max_bytes_write)
+            }
+        };
+        (
+            self.read_bandwidth.connection_limiter(max_bytes_read),
+            self.write_bandwidth.connection_limiter(max_bytes_write),
+        )
+    }
+
     /// Runs the main loop of the network worker
     /// There is a `tokio::select!` inside the loop
     pub async fn run_loop(mut self) -> Result<(), NetworkError> {
@@ -167,6 +218,7 @@ impl NetworkWorker {
                     * node closed (no worries if processed a bit late)
                     * out connecting events (no problem if a bit late)
                     * listener event (HIGH FREQUENCY) non-critical
+                    * external IP reported by the router (LOW FREQUENCY) non-critical
             */
             tokio::select! {
                 // listen to manager commands
@@ -232,6 +284,7 @@ impl NetworkWorker {
                     let _ = self
                         .event.send(NetworkEvent::ConnectionClosed(node_id))
                         .await;
+                    self.clock_offsets.remove(&node_id);
                     if let Some((connection_id, _)) = self
                         .active_nodes
                         .remove(&node_id) {
@@ -259,9 +312,17 @@ impl NetworkWorker {
                         &mut cur_connection_id,
                     ).await?
                 }
+
+                // external IP reported by the router
+                Some(ip) = self.external_ip_rx.recv() => self.on_external_ip(ip),
             }
         }
 
+        // remove the port mapping
+        if let Some(nat_traversal) = self.nat_traversal.take() {
+            nat_traversal.stop().await;
+        }
+
         // wait for out-connectors to finish
         while out_connecting_futures.next().await.is_some() {}
 
@@ -311,6 +372,25 @@ impl NetworkWorker {
         Ok(())
     }
 
+    /// Advertises the external IP reported by the router as our routable IP,
+    /// unless one is configured.
+    fn on_external_ip(&mut self, ip: IpAddr) {
+        if self.cfg.routable_ip.is_some()
+            || self.peer_info_db.network_settings.routable_ip == Some(ip)
+        {
+            return;
+        }
+        if !ip.is_global() {
+            warn!(
+                "the router reported the non-routable external IP {}, the node may be behind several NATs",
+                ip
+            );
+            return;
+        }
+        info!("advertising the external IP {} reported by the router", ip);
+        self.peer_info_db.set_routable_ip(ip);
+    }
+
     /// Manages finished handshakes.
     /// Only used by the worker.
     ///
@@ -327,7 +407,7 @@ impl NetworkWorker {
         });
         match outcome {
             // a handshake finished, and succeeded
-            Ok((new_node_id, socket_reader, socket_writer)) => {
+            Ok((new_node_id, socket_reader, socket_writer, clock_offset, features)) => {
                 debug!(
                     "handshake with connection_id={} succeeded => node_id={}",
                     new_connection_id, new_node_id
@@ -384,7 +464,7 @@ impl NetworkWorker {
 
                         // spawn node_controller_fn
                         let (node_command_tx, node_command_rx) =
-                            mpsc::channel::<NodeCommand>(self.cfg.node_command_channel_size);
+                            node_command_channel(self.cfg.node_command_channel_size);
                         let node_event_tx_clone = self.event.clone_node_sender();
                         let cfg_copy = self.cfg.clone();
                         let node_worker_command_tx = node_command_tx.clone();
@@ -403,11 +483,14 @@ impl NetworkWorker {
                             (new_node_id, res)
                         });
                         entry.insert((new_connection_id, node_command_tx.clone()));
+                        if let Some(clock_offset) = clock_offset {
+                            self.clock_offsets.insert(new_node_id, clock_offset);
+                        }
                         self.node_worker_handles.push(node_fn_handle);
 
                         let res = self
                             .event
-                            .send(NetworkEvent::NewConnection(new_node_id))
+                            .send(NetworkEvent::NewConnection(new_node_id, features))
                             .await;
 
                         // If we failed to send the event to protocol, close the connection.
@@ -447,6 +530,9 @@ impl NetworkWorker {
                     "err": err.to_string()
                 });
                 self.running_handshakes.remove(&new_connection_id);
+                if let Some((ip, _)) = self.active_connections.get(&new_connection_id) {
+                    self.peer_info_db.peer_handshake_failed(ip)?;
+                }
                 self.connection_closed(new_connection_id, ConnectionClosureReason::Failed)
                     .await?;
             }
@@ -530,6 +616,9 @@ impl NetworkWorker {
             NetworkCommand::SendOperationAnnouncements { to_node, batch } => {
                 on_send_operation_batches_cmd(self, to_node, batch).await
             }
+            NetworkCommand::SendOperationFeeAnnouncements { to_node, batch } => {
+                on_send_operation_fee_batches_cmd(self, to_node, batch).await
+            }
             NetworkCommand::AskForOperations { to_node, wishlist } => {
                 on_ask_for_operations_cmd(self, to_node, wishlist).await
             }
@@ -542,6 +631,9 @@ impl NetworkWorker {
             NetworkCommand::NodeUnbanByIds(ids) => on_node_unban_by_ids_cmd(self, ids).await?,
             NetworkCommand::NodeUnbanByIps(ips) => on_node_unban_by_ips_cmd(self, ips).await?,
             NetworkCommand::GetStats { response_tx } => on_get_stats_cmd(self, response_tx).await,
+            NetworkCommand::GetClockOffsets { response_tx } => {
+                on_get_clock_offsets_cmd(self, response_tx).await
+            }
             NetworkCommand::Whitelist(ips) => on_whitelist_cmd(self, ips).await?,
             NetworkCommand::RemoveFromWhitelist(ips) => {
                 on_remove_from_whitelist_cmd(self, ips).await?
@@ -582,7 +674,7 @@ impl NetworkWorker {
                     cur_connection_id.0 += 1;
                     self.active_connections
                         .insert(connection_id, (ip_addr, true));
-                    self.manage_successful_connection(connection_id, reader, writer)?;
+                    self.manage_successful_connection(connection_id, ip_addr, reader, writer)?;
                 } else {
                     debug!("out connection towards ip={} refused", ip_addr);
                     massa_trace!("out_connection_refused", { "ip": ip_addr });
@@ -637,7 +729,12 @@ impl NetworkWorker {
                         cur_connection_id.0 += 1;
                         self.active_connections
                             .insert(connection_id, (remote_addr.ip(), false));
-                        self.manage_successful_connection(connection_id, reader, writer)?;
+                        self.manage_successful_connection(
+                            connection_id,
+                            remote_addr.ip(),
+                            reader,
+                            writer,
+                        )?;
                     }
                     Err(NetworkError::PeerConnectionError(
                         NetworkConnectionErrorType::MaxPeersConnectionReached(_),
@@ -695,8 +792,7 @@ impl NetworkWorker {
         if self.cfg.max_in_connection_overflow > self.handshake_peer_list_futures.len() {
             let msg = Message::PeerList(self.peer_info_db.get_advertisable_peer_ips());
             let timeout = self.cfg.peer_list_send_timeout.to_duration();
-            let max_bytes_read = self.cfg.max_bytes_read;
-            let max_bytes_write = self.cfg.max_bytes_write;
+            let (read_limiter, write_limiter) = self.connection_limiters(&remote_addr.ip());
             let max_ask_blocks = self.cfg.max_ask_blocks;
             let max_operations_per_block = self.cfg.max_operations_per_block;
             let thread_count = self.cfg.thread_count;
@@ -713,10 +809,10 @@ impl NetworkWorker {
             let max_op_datastore_value_length = self.cfg.max_op_datastore_value_length;
             self.handshake_peer_list_futures
                 .push(tokio::spawn(async move {
-                    let mut writer = WriteBinder::new(writer, max_bytes_read, max_message_size);
+                    let mut writer = WriteBinder::new(writer, write_limiter, max_message_size);
                     let mut reader = ReadBinder::new(
                         reader,
-                        max_bytes_write,
+                        read_limiter,
                         max_message_size,
                         MessageDeserializer::new(
                             thread_count,
@@ -761,6 +857,7 @@ impl NetworkWorker {
     fn manage_successful_connection(
         &mut self,
         connection_id: ConnectionId,
+        ip: IpAddr,
         reader: ReadHalf,
         writer: WriteHalf,
     ) -> Result<(), NetworkError> {
@@ -769,6 +866,7 @@ impl NetworkWorker {
                 HandshakeErrorType::HandshakeIdAlreadyExist(format!("{}", connection_id)),
             ));
         }
+        let (read_limiter, write_limiter) = self.connection_limiters(&ip);
         self.handshake_futures.push(HandshakeWorker::spawn(
             reader,
             writer,
@@ -777,8 +875,8 @@ impl NetworkWorker {
             self.cfg.connect_timeout,
             self.version,
             connection_id,
-            self.cfg.max_bytes_read,
-            self.cfg.max_bytes_write,
+            read_limiter,
+            write_limiter,
         ));
         Ok(())
     }
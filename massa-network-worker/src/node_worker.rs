@@ -8,16 +8,95 @@ use itertools::Itertools;
 use massa_logging::massa_trace;
 use massa_models::{node::NodeId, wrapped::Id};
 use massa_network_exports::{
-    ConnectionClosureReason, NetworkConfig, NetworkError, NodeCommand, NodeEvent, NodeEventType,
+    ConnectionClosureReason, MessagePriority, NetworkConfig, NetworkError, NodeCommand, NodeEvent,
+    NodeEventType,
 };
 use massa_time::MassaTime;
+use std::collections::VecDeque;
 use tokio::{
     sync::mpsc,
-    sync::mpsc::{error::SendTimeoutError, Sender},
+    sync::mpsc::{
+        error::{SendError, SendTimeoutError, TryRecvError, TrySendError},
+        Sender,
+    },
     time::timeout,
 };
 use tracing::{debug, trace, warn};
 
+/// Creates the command queues of a node worker, one per message priority class,
+/// each of them holding at most `size` commands
+pub fn node_command_channel(size: usize) -> (NodeCommandSender, NodeCommandReceiver) {
+    let (block_tx, block_rx) = mpsc::channel(size);
+    let (operation_tx, operation_rx) = mpsc::channel(size);
+    (
+        NodeCommandSender {
+            block_tx,
+            operation_tx,
+        },
+        NodeCommandReceiver {
+            block_rx,
+            operation_rx,
+        },
+    )
+}
+
+/// Sends commands to a node worker, in the queue of their priority class
+#[derive(Clone)]
+pub struct NodeCommandSender {
+    /// queue of the block class commands
+    block_tx: mpsc::Sender<NodeCommand>,
+    /// queue of the operation class commands
+    operation_tx: mpsc::Sender<NodeCommand>,
+}
+
+impl NodeCommandSender {
+    /// Returns the queue of a priority class
+    fn queue(&self, priority: MessagePriority) -> &mpsc::Sender<NodeCommand> {
+        match priority {
+            MessagePriority::Block => &self.block_tx,
+            MessagePriority::Operation => &self.operation_tx,
+        }
+    }
+
+    /// Sends a command, waiting for room in the queue of its class
+    pub async fn send(&self, command: NodeCommand) -> Result<(), SendError<NodeCommand>> {
+        self.queue(command.priority()).send(command).await
+    }
+
+    /// Sends a command, failing if the queue of its class is full
+    pub fn try_send(&self, command: NodeCommand) -> Result<(), TrySendError<NodeCommand>> {
+        self.queue(command.priority()).try_send(command)
+    }
+}
+
+/// Receives the commands of a node worker from the queues of their priority class
+pub struct NodeCommandReceiver {
+    /// queue of the block class commands
+    block_rx: mpsc::Receiver<NodeCommand>,
+    /// queue of the operation class commands
+    operation_rx: mpsc::Receiver<NodeCommand>,
+}
+
+impl NodeCommandReceiver {
+    /// Waits for the next command of any class, the block class ones first.
+    /// Returns `None` if the senders were dropped.
+    async fn recv(&mut self) -> Option<NodeCommand> {
+        tokio::select! {
+            biased;
+            command = self.block_rx.recv() => command,
+            command = self.operation_rx.recv() => command,
+        }
+    }
+
+    /// Takes a command of a priority class if one is already queued
+    fn try_recv(&mut self, priority: MessagePriority) -> Result<NodeCommand, TryRecvError> {
+        match priority {
+            MessagePriority::Block => self.block_rx.try_recv(),
+            MessagePriority::Operation => self.operation_rx.try_recv(),
+        }
+    }
+}
+
 /// Manages connections
 /// One worker per node.
 pub struct NodeWorker {
@@ -30,9 +109,9 @@ pub struct NodeWorker {
     /// Optional writer to send data.
     socket_writer_opt: Option<WriteBinder>,
     /// Channel to send node commands.
-    node_command_tx: mpsc::Sender<NodeCommand>,
+    node_command_tx: NodeCommandSender,
     /// Channel to receive node commands.
-    node_command_rx: mpsc::Receiver<NodeCommand>,
+    node_command_rx: NodeCommandReceiver,
     /// Channel to send node events.
     node_event_tx: mpsc::Sender<NodeEvent>,
 }
@@ -53,8 +132,8 @@ impl NodeWorker {
         node_id: NodeId,
         socket_reader: ReadBinder,
         socket_writer: WriteBinder,
-        node_command_tx: mpsc::Sender<NodeCommand>,
-        node_command_rx: mpsc::Receiver<NodeCommand>,
+        node_command_tx: NodeCommandSender,
+        node_command_rx: NodeCommandReceiver,
         node_event_tx: mpsc::Sender<NodeEvent>,
     ) -> NodeWorker {
         NodeWorker {
@@ -85,6 +164,7 @@ impl NodeWorker {
                 self.cfg.max_ask_blocks,
                 self.cfg.max_operations_per_message,
                 self.cfg.max_endorsements_per_message,
+                self.cfg.block_message_weight,
             )
             .await
         });
@@ -184,130 +264,234 @@ impl NodeWorker {
     }
 }
 
-/// Handle incoming node command, convert to message(s) and write that to socket
+/// Messages waiting to be written to the socket of a node, per priority class
+struct OutgoingMessages {
+    /// block class messages
+    block: VecDeque<Message>,
+    /// operation class messages
+    operation: VecDeque<Message>,
+    /// number of block class messages written for each operation class one when both are waiting
+    block_message_weight: u32,
+    /// block class messages written in a row while operation class ones were waiting
+    block_streak: u32,
+}
+
+impl OutgoingMessages {
+    /// Creates empty queues
+    fn new(block_message_weight: u32) -> Self {
+        OutgoingMessages {
+            block: VecDeque::new(),
+            operation: VecDeque::new(),
+            block_message_weight: block_message_weight.max(1),
+            block_streak: 0,
+        }
+    }
+
+    /// Returns the queue of a priority class
+    fn queue(&mut self, priority: MessagePriority) -> &mut VecDeque<Message> {
+        match priority {
+            MessagePriority::Block => &mut self.block,
+            MessagePriority::Operation => &mut self.operation,
+        }
+    }
+
+    /// Returns true if no message is waiting
+    fn is_empty(&self) -> bool {
+        self.block.is_empty() && self.operation.is_empty()
+    }
+
+    /// Takes the next message to write: the block class ones first,
+    /// an operation class one being interleaved every `block_message_weight` block class ones
+    fn pop(&mut self) -> Option<Message> {
+        if self.operation.is_empty() {
+            self.block_streak = 0;
+            return self.block.pop_front();
+        }
+        if !self.block.is_empty() && self.block_streak < self.block_message_weight {
+            self.block_streak += 1;
+            return self.block.pop_front();
+        }
+        self.block_streak = 0;
+        self.operation.pop_front()
+    }
+}
+
+/// Converts a node command to the message(s) to send
+///
+/// # Returns
+/// `Err` with the closure reason if the command closes the connection
+fn command_messages(
+    command: NodeCommand,
+    node_id: NodeId,
+    max_ask_blocks: u32,
+    max_operations_per_message: u32,
+    max_endorsements_per_message: u32,
+) -> Result<Vec<Message>, ConnectionClosureReason> {
+    let messages = match command {
+        NodeCommand::Close(r) => return Err(r),
+        NodeCommand::SendPeerList(ip_vec) => {
+            massa_trace!("node_worker.run_loop. send Message::PeerList", {"peerlist": ip_vec, "node": node_id});
+            vec![Message::PeerList(ip_vec)]
+        }
+        NodeCommand::SendBlockHeader(header) => {
+            massa_trace!("node_worker.run_loop. send Message::BlockHeader", {"hash": header.id, "node": node_id});
+            vec![Message::BlockHeader(header)]
+        }
+        NodeCommand::AskForBlocks(list) => {
+            // cut hash list on sub list if exceed max_ask_blocks_per_message
+            massa_trace!("node_worker.run_loop. send Message::AskForBlocks", {"hashlist": list, "node": node_id});
+            list.chunks(max_ask_blocks as usize)
+                .map(|to_send| Message::AskForBlocks(to_send.to_vec()))
+                .collect()
+        }
+        NodeCommand::ReplyForBlocks(list) => {
+            // cut hash list on sub list if exceed max_ask_blocks_per_message
+            massa_trace!("node_worker.run_loop. send Message::ReplyForBlocks", {"hashlist": list, "node": node_id});
+            list.chunks(max_ask_blocks as usize)
+                .map(|to_send| Message::ReplyForBlocks(to_send.to_vec()))
+                .collect()
+        }
+        NodeCommand::SendOperations(operations) => {
+            massa_trace!("node_worker.run_loop. send Message::SendOperations", {"node": node_id, "operations": operations});
+            operations
+                .chunks(max_operations_per_message as usize)
+                .map(|to_send| Message::Operations(to_send.to_vec()))
+                .collect()
+        }
+        NodeCommand::SendOperationAnnouncements(operation_prefix_ids) => {
+            massa_trace!("node_worker.run_loop. send Message::OperationsAnnouncement", {"node": node_id, "operation_ids": operation_prefix_ids});
+            operation_prefix_ids
+                .into_iter()
+                .chunks(max_operations_per_message as usize)
+                .into_iter()
+                .map(|chunk| chunk.collect())
+                .map(Message::OperationsAnnouncement)
+                .collect()
+        }
+        NodeCommand::SendOperationFeeAnnouncements(operation_announcements) => {
+            massa_trace!("node_worker.run_loop. send Message::OperationFeeAnnouncements", {"node": node_id, "operation_ids": operation_announcements.keys().collect::<Vec<_>>()});
+            // announce the operations with the highest fee density first
+            operation_announcements
+                .into_iter()
+                .sorted_by_key(|(_, fee_density)| std::cmp::Reverse(*fee_density))
+                .chunks(max_operations_per_message as usize)
+                .into_iter()
+                .map(|chunk| chunk.collect())
+                .map(Message::OperationFeeAnnouncements)
+                .collect()
+        }
+        NodeCommand::AskForOperations(operation_prefix_ids) => {
+            massa_trace!(
+                "node_worker.run_loop. send Message::AskForOperations",
+                {"node": node_id, "operation_ids": operation_prefix_ids}
+            );
+            operation_prefix_ids
+                .into_iter()
+                .chunks(max_operations_per_message as usize)
+                .into_iter()
+                .map(|chunk| chunk.collect())
+                .map(Message::AskForOperations)
+                .collect()
+        }
+        NodeCommand::SendEndorsements(endorsements) => {
+            massa_trace!("node_worker.run_loop. send Message::SendEndorsements", {"node": node_id, "endorsements": endorsements});
+            // cut endorsement list if it exceed max_endorsements_per_message
+            endorsements
+                .chunks(max_endorsements_per_message as usize)
+                .map(|endos| Message::Endorsements(endos.to_vec()))
+                .collect()
+        }
+        NodeCommand::AskPeerList => vec![Message::AskPeerList],
+    };
+    Ok(messages)
+}
+
+/// Handle incoming node commands, convert them to messages and write them to the socket.
+/// The messages of each priority class are queued separately so that the block class ones
+/// are not written after the operation batches received before them.
+#[allow(clippy::too_many_arguments)]
 async fn node_writer_handle(
     socket_writer: &mut WriteBinder,
-    node_command_rx: &mut mpsc::Receiver<NodeCommand>,
+    node_command_rx: &mut NodeCommandReceiver,
     write_timeout: MassaTime,
     node_id: NodeId,
     max_ask_blocks: u32,
     max_operations_per_message: u32,
     max_endorsements_per_message: u32,
+    block_message_weight: u32,
 ) -> ConnectionClosureReason {
     let mut exit_reason = ConnectionClosureReason::Normal;
+    let mut outgoing = OutgoingMessages::new(block_message_weight);
 
     'writer_loop: loop {
-        let messages_: Option<Vec<Message>> = match node_command_rx.recv().await {
-            Some(NodeCommand::Close(r)) => {
-                exit_reason = r;
-                None
-            }
-            Some(NodeCommand::SendPeerList(ip_vec)) => {
-                massa_trace!("node_worker.run_loop. send Message::PeerList", {"peerlist": ip_vec, "node": node_id});
-                Some(vec![Message::PeerList(ip_vec)])
-            }
-            Some(NodeCommand::SendBlockHeader(header)) => {
-                massa_trace!("node_worker.run_loop. send Message::BlockHeader", {"hash": header.id, "node": node_id});
-                Some(vec![Message::BlockHeader(header)])
-            }
-            Some(NodeCommand::AskForBlocks(list)) => {
-                // cut hash list on sub list if exceed max_ask_blocks_per_message
-                massa_trace!("node_worker.run_loop. send Message::AskForBlocks", {"hashlist": list, "node": node_id});
-                let messages = list
-                    .chunks(max_ask_blocks as usize)
-                    .map(|to_send| Message::AskForBlocks(to_send.to_vec()))
-                    .collect();
-                Some(messages)
-            }
-            Some(NodeCommand::ReplyForBlocks(list)) => {
-                // cut hash list on sub list if exceed max_ask_blocks_per_message
-                massa_trace!("node_worker.run_loop. send Message::ReplyForBlocks", {"hashlist": list, "node": node_id});
-                let messages = list
-                    .chunks(max_ask_blocks as usize)
-                    .map(|to_send| Message::ReplyForBlocks(to_send.to_vec()))
-                    .collect();
-                Some(messages)
-            }
-            Some(NodeCommand::SendOperations(operations)) => {
-                massa_trace!("node_worker.run_loop. send Message::SendOperations", {"node": node_id, "operations": operations});
-                let messages = operations
-                    .chunks(max_operations_per_message as usize)
-                    .map(|to_send| Message::Operations(to_send.to_vec()))
-                    .collect();
-                Some(messages)
-            }
-            Some(NodeCommand::SendOperationAnnouncements(operation_prefix_ids)) => {
-                massa_trace!("node_worker.run_loop. send Message::OperationsAnnouncement", {"node": node_id, "operation_ids": operation_prefix_ids});
-                let messages = operation_prefix_ids
-                    .into_iter()
-                    .chunks(max_operations_per_message as usize)
-                    .into_iter()
-                    .map(|chunk| chunk.collect())
-                    .map(Message::OperationsAnnouncement)
-                    .collect();
-                Some(messages)
-            }
-            Some(NodeCommand::AskForOperations(operation_prefix_ids)) => {
-                massa_trace!(
-                    "node_worker.run_loop. send Message::AskForOperations",
-                    {"node": node_id, "operation_ids": operation_prefix_ids}
-                );
-                let messages = operation_prefix_ids
-                    .into_iter()
-                    .chunks(max_operations_per_message as usize)
-                    .into_iter()
-                    .map(|chunk| chunk.collect())
-                    .map(Message::AskForOperations)
-                    .collect();
-                Some(messages)
-            }
-            Some(NodeCommand::SendEndorsements(endorsements)) => {
-                massa_trace!("node_worker.run_loop. send Message::SendEndorsements", {"node": node_id, "endorsements": endorsements});
-                // cut endorsement list if it exceed max_endorsements_per_message
-                let messages = endorsements
-                    .chunks(max_endorsements_per_message as usize)
-                    .map(|endos| Message::Endorsements(endos.to_vec()))
-                    .collect();
-                Some(messages)
-            }
-            Some(NodeCommand::AskPeerList) => Some(vec![Message::AskPeerList]),
-            None => {
-                // Note: this should never happen,
-                // since it implies the network worker dropped its node command sender
-                // before having shut-down the node and joined on its handle.
-                exit_reason = ConnectionClosureReason::Failed;
-                break 'writer_loop;
+        // take the commands already queued for the classes without pending messages,
+        // and wait for one if there is nothing to write
+        let mut commands = Vec::new();
+        for priority in [MessagePriority::Block, MessagePriority::Operation] {
+            if outgoing.queue(priority).is_empty() {
+                match node_command_rx.try_recv(priority) {
+                    Ok(command) => commands.push(command),
+                    Err(TryRecvError::Empty) => {}
+                    Err(TryRecvError::Disconnected) => {
+                        // Note: this should never happen,
+                        // since it implies the network worker dropped its node command sender
+                        // before having shut-down the node and joined on its handle.
+                        exit_reason = ConnectionClosureReason::Failed;
+                        break 'writer_loop;
+                    }
+                }
             }
-        };
-
-        if messages_.is_none() {
-            break;
         }
-        // safe to unwrap here
-        let messages = messages_.unwrap();
-
-        for msg in messages.iter() {
-            match timeout(write_timeout.to_duration(), socket_writer.send(msg)).await {
-                Err(err) => {
-                    massa_trace!("node_worker.run_loop.loop.writer_command_rx.recv.send.timeout", {
-                        "node": node_id,
-                    });
-                    debug!("Node data writing timed out: {}", err);
+        if commands.is_empty() && outgoing.is_empty() {
+            match node_command_rx.recv().await {
+                Some(command) => commands.push(command),
+                None => {
                     exit_reason = ConnectionClosureReason::Failed;
                     break 'writer_loop;
                 }
-                Ok(Err(err)) => {
-                    massa_trace!("node_worker.run_loop.loop.writer_command_rx.recv.send.error", {
-                        "node": node_id, "err":  format!("{}", err),
-                    });
-                    debug!("Node data writing error: {:?}", err);
-                    exit_reason = ConnectionClosureReason::Failed;
+            }
+        }
+        for command in commands {
+            let priority = command.priority();
+            match command_messages(
+                command,
+                node_id,
+                max_ask_blocks,
+                max_operations_per_message,
+                max_endorsements_per_message,
+            ) {
+                Ok(messages) => outgoing.queue(priority).extend(messages),
+                Err(reason) => {
+                    exit_reason = reason;
                     break 'writer_loop;
                 }
-                Ok(Ok(id)) => {
-                    massa_trace!("node_worker.run_loop.loop.writer_command_rx.recv.send.ok", {
-                                    "node": node_id, "msg_id": id});
-                }
+            }
+        }
+
+        let msg = match outgoing.pop() {
+            Some(msg) => msg,
+            None => continue,
+        };
+        match timeout(write_timeout.to_duration(), socket_writer.send(&msg)).await {
+            Err(err) => {
+                massa_trace!("node_worker.run_loop.loop.writer_command_rx.recv.send.timeout", {
+                    "node": node_id,
+                });
+                debug!("Node data writing timed out: {}", err);
+                exit_reason = ConnectionClosureReason::Failed;
+                break 'writer_loop;
+            }
+            Ok(Err(err)) => {
+                massa_trace!("node_worker.run_loop.loop.writer_command_rx.recv.send.error", {
+                    "node": node_id, "err":  format!("{}", err),
+                });
+                debug!("Node data writing error: {:?}", err);
+                exit_reason = ConnectionClosureReason::Failed;
+                break 'writer_loop;
+            }
+            Ok(Ok(id)) => {
+                massa_trace!("node_worker.run_loop.loop.writer_command_rx.recv.send.ok", {
+                                "node": node_id, "msg_id": id});
             }
         }
     }
@@ -383,9 +567,23 @@ async fn node_reader_handle(
                     }
                     Message::OperationsAnnouncement(operation_prefix_ids) => {
                         massa_trace!("node_worker.run_loop. receive Message::OperationsBatch", {"node": node_id, "operation_prefix_ids": operation_prefix_ids});
+                        // the legacy announcements do not tell the fee density of the operations
+                        let event = NodeEvent(
+                            node_id,
+                            NodeEventType::ReceivedOperationAnnouncements(
+                                operation_prefix_ids
+                                    .into_iter()
+                                    .map(|prefix| (prefix, 0))
+                                    .collect(),
+                            ),
+                        );
+                        send_node_event(node_event_tx, event, max_send_wait).await
+                    }
+                    Message::OperationFeeAnnouncements(operation_announcements) => {
+                        massa_trace!("node_worker.run_loop. receive Message::OperationFeeAnnouncements", {"node": node_id, "operation_prefix_ids": operation_announcements.keys().collect::<Vec<_>>()});
                         let event = NodeEvent(
                             node_id,
-                            NodeEventType::ReceivedOperationAnnouncements(operation_prefix_ids),
+                            NodeEventType::ReceivedOperationAnnouncements(operation_announcements),
                         );
                         send_node_event(node_event_tx, event, max_send_wait).await
                     }
@@ -446,3 +644,29 @@ async fn send_node_event(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outgoing_messages_weights() {
+        let mut outgoing = OutgoingMessages::new(2);
+        for _ in 0..3 {
+            outgoing
+                .queue(MessagePriority::Block)
+                .push_back(Message::AskPeerList);
+        }
+        for _ in 0..3 {
+            outgoing
+                .queue(MessagePriority::Operation)
+                .push_back(Message::Operations(Vec::new()));
+        }
+        // two block class messages are written for each operation class one while both wait
+        let order: Vec<bool> = std::iter::from_fn(|| outgoing.pop())
+            .map(|msg| matches!(msg, Message::Operations(_)))
+            .collect();
+        assert_eq!(order, vec![false, false, true, false, true, true]);
+        assert!(outgoing.is_empty());
+    }
+}
@@ -10,7 +10,7 @@ use massa_network_exports::NetworkConnectionErrorType;
 use massa_network_exports::NetworkError;
 use massa_network_exports::PeerInfo;
 use massa_network_exports::PeerType;
-use massa_time::MassaTime;
+use massa_time::{ClockCompensation, MassaTime};
 use serde_json::json;
 use std::cmp::Reverse;
 use std::collections::HashMap;
@@ -35,7 +35,7 @@ pub struct PeerInfoDatabase {
     /// Every `wakeup_interval` we try to establish a connection with known inactive peers
     pub(crate) wakeup_interval: MassaTime,
     /// Clock compensation.
-    pub(crate) clock_compensation: i64,
+    pub(crate) clock_compensation: ClockCompensation,
 }
 
 /// Saves advertised and non standard peers to a file.
@@ -58,6 +58,7 @@ async fn dump_peers(
                 "last_alive": peer.last_alive,
                 "last_failure": peer.last_failure,
                 "advertised": peer.advertised,
+                "quality": peer.quality,
             })
         })
         .collect();
@@ -173,7 +174,10 @@ impl PeerInfoDatabase {
     /// # Argument
     /// * `cfg`: network configuration
     /// * `clock_compensation`: sync with server
-    pub async fn new(cfg: &NetworkConfig, clock_compensation: i64) -> Result<Self, NetworkError> {
+    pub async fn new(
+        cfg: &NetworkConfig,
+        clock_compensation: ClockCompensation,
+    ) -> Result<Self, NetworkError> {
         // wakeup interval
         let wakeup_interval = cfg.wakeup_interval;
 
@@ -202,7 +206,13 @@ impl PeerInfoDatabase {
         }
 
         // cleanup
-        cleanup_peers(cfg, &mut peers, None, clock_compensation, cfg.ban_timeout)?;
+        cleanup_peers(
+            cfg,
+            &mut peers,
+            None,
+            clock_compensation.get(),
+            cfg.ban_timeout,
+        )?;
 
         // setup saver
         let peers_file = cfg.peers_file.clone();
@@ -291,13 +301,20 @@ impl PeerInfoDatabase {
     ///////////////////////
 
     /// Refreshes the peer list. Should be called at regular intervals.
-    /// Performs multiple cleanup tasks e.g. remove old banned peers
+    /// Performs multiple cleanup tasks e.g. remove old banned peers,
+    /// and decays the quality metrics of the peers.
     pub fn update(&mut self) -> Result<(), NetworkError> {
+        let now = self.clock_compensation.now()?;
+        let half_life = self.network_settings.peer_quality_half_life;
+        for peer in self.peers.values_mut() {
+            let connected = peer.active_out_connections > 0 || peer.active_in_connections > 0;
+            peer.quality.decay(now, half_life, connected);
+        }
         cleanup_peers(
             &self.network_settings,
             &mut self.peers,
             None,
-            self.clock_compensation,
+            self.clock_compensation.get(),
             self.network_settings.ban_timeout,
         )?;
         Ok(())
@@ -327,7 +344,7 @@ impl PeerInfoDatabase {
             &self.network_settings,
             &mut self.peers,
             Some(&new_peers.to_vec()),
-            self.clock_compensation,
+            self.clock_compensation.get(),
             self.network_settings.ban_timeout,
         )?;
         self.request_dump()
@@ -460,12 +477,27 @@ impl PeerInfoDatabase {
         self.update()
     }
 
-    /// Sets the peer status as alive.
+    /// Sets the peer status as alive after a successful handshake.
     /// Requests a subsequent dump.
     ///
     /// # Argument
     /// * ip : ip address of the considered peer.
     pub fn peer_alive(&mut self, ip: &IpAddr) -> Result<(), NetworkError> {
+        let ip = ip.to_canonical();
+        let peer = self.peers.get_mut(&ip).ok_or_else(|| {
+            NetworkError::PeerConnectionError(NetworkConnectionErrorType::PeerInfoNotFoundError(ip))
+        })?;
+        peer.last_alive = Some(self.clock_compensation.now()?);
+        peer.quality.successful_connections += 1.0;
+        self.request_dump()
+    }
+
+    /// Records a failed handshake with the peer in its quality metrics.
+    /// Requests a dump.
+    ///
+    /// # Argument
+    /// * ip : ip address of the considered peer.
+    pub fn peer_handshake_failed(&mut self, ip: &IpAddr) -> Result<(), NetworkError> {
         let ip = ip.to_canonical();
         self.peers
             .get_mut(&ip)
@@ -474,7 +506,8 @@ impl PeerInfoDatabase {
                     NetworkConnectionErrorType::PeerInfoNotFoundError(ip),
                 )
             })?
-            .last_alive = Some(MassaTime::now(self.clock_compensation)?);
+            .quality
+            .handshake_failures += 1.0;
         self.request_dump()
     }
 
@@ -492,7 +525,7 @@ impl PeerInfoDatabase {
                     NetworkConnectionErrorType::PeerInfoNotFoundError(ip),
                 )
             })?
-            .last_failure = Some(MassaTime::now(self.clock_compensation)?);
+            .last_failure = Some(self.clock_compensation.now()?);
         self.request_dump()
     }
 
@@ -508,9 +541,10 @@ impl PeerInfoDatabase {
             .peers
             .entry(ip)
             .or_insert_with(|| PeerInfo::new(ip, false));
-        peer.last_failure = Some(MassaTime::now(self.clock_compensation)?);
+        peer.last_failure = Some(self.clock_compensation.now()?);
         if !peer.banned {
             peer.banned = true;
+            peer.quality.bans += 1.0;
             if !peer.is_active() {
                 self.update()?
             }
@@ -643,7 +677,7 @@ impl PeerInfoDatabase {
             peer.advertised = true; // we just connected to it. Assume advertised.
 
             if peer.banned {
-                peer.last_failure = Some(MassaTime::now(self.clock_compensation)?);
+                peer.last_failure = Some(self.clock_compensation.now()?);
                 if !peer.is_active() && peer.peer_type == Default::default() {
                     self.update()?;
                 }
@@ -685,7 +719,7 @@ impl PeerInfoDatabase {
                 )
             })?;
             peer.active_out_connection_attempts -= 1;
-            peer.last_failure = Some(MassaTime::now(self.clock_compensation)?);
+            peer.last_failure = Some(self.clock_compensation.now()?);
             let pt = peer.peer_type;
             if !peer.is_active() && peer.peer_type == PeerType::Standard {
                 self.update()?;
@@ -697,6 +731,11 @@ impl PeerInfoDatabase {
     }
 
     /// An ip has successfully connected to us.
+    /// Sets our routable IP, advertised to the other peers
+    pub fn set_routable_ip(&mut self, ip: IpAddr) {
+        self.network_settings.routable_ip = Some(ip);
+    }
+
     /// returns true if some in slots for connections are left.
     /// If the corresponding peer exists, it is updated,
     /// otherwise it is created (not advertised).
@@ -745,7 +784,7 @@ impl PeerInfoDatabase {
             // is there a attempt slot available
             if peer.banned {
                 massa_trace!("in_connection_refused_peer_banned", {"ip": peer.ip});
-                peer.last_failure = Some(MassaTime::now(self.clock_compensation)?);
+                peer.last_failure = Some(self.clock_compensation.now()?);
                 self.request_dump()?;
                 return Err(NetworkError::PeerConnectionError(
                     NetworkConnectionErrorType::BannedPeerTryingToConnect(ip),
@@ -771,7 +810,7 @@ impl PeerInfoDatabase {
     // public getters //
     ////////////////////
 
-    /// Sorts peers by `( last_failure, rev(last_success) )`
+    /// Sorts peers by `( rev(score), last_failure, rev(last_success) )`
     /// and returns as many peers as there are available slots to attempt outgoing connections to.
     pub fn get_out_connection_candidate_ips(&self) -> Result<Vec<IpAddr>, NetworkError> {
         let mut connections = vec![];
@@ -852,21 +891,29 @@ impl PeerInfoDatabase {
         cfg: &PeerTypeConnectionConfig,
     ) -> Result<Vec<IpAddr>, NetworkError> {
         let available_slots = count.get_available_out_connection_attempts(cfg);
-        let now = MassaTime::now(self.clock_compensation)?;
+        let now = self.clock_compensation.now()?;
         let f = move |p: &&PeerInfo| {
             if p.peer_type != peer_type || !p.advertised || p.is_active() || p.banned {
                 return false;
             }
             p.is_peer_ready(self.wakeup_interval, now)
         };
-        let mut res: Vec<_> = self
-            .peers
-            .values()
-            .filter(f)
+        let mut res: Vec<_> = self.peers.values().filter(f).collect();
+        // prefer the peers with the best history
+        res.sort_unstable_by(|a, b| {
+            b.quality
+                .score()
+                .total_cmp(&a.quality.score())
+                .then_with(|| {
+                    (a.last_failure, std::cmp::Reverse(a.last_alive))
+                        .cmp(&(b.last_failure, std::cmp::Reverse(b.last_alive)))
+                })
+        });
+        Ok(res
+            .into_iter()
             .take(available_slots)
-            .collect();
-        res.sort_unstable_by_key(|&p| (p.last_failure, std::cmp::Reverse(p.last_alive)));
-        Ok(res.into_iter().map(|p| p.ip).collect())
+            .map(|p| p.ip)
+            .collect())
     }
 
     fn get_peer_type(&self, ip: &IpAddr) -> Option<PeerType> {
@@ -3,11 +3,12 @@
 // To start alone RUST_BACKTRACE=1 cargo test -- --nocapture --test-threads=1
 use super::tools;
 use crate::messages::{Message, MessageDeserializer};
-use crate::node_worker::NodeWorker;
+use crate::node_worker::{node_command_channel, NodeWorker};
 use crate::tests::tools::{get_dummy_block_id, get_transaction};
 use crate::NetworkError;
 use crate::NetworkEvent;
 use crate::{
+    bandwidth::BandwidthLimiter,
     binders::{ReadBinder, WriteBinder},
     NetworkConfig,
 };
@@ -77,7 +78,7 @@ async fn test_node_worker_shutdown() {
     let (duplex_mock_read, duplex_mock_write) = tokio::io::split(duplex_controller);
     let reader = ReadBinder::new(
         duplex_mock_read,
-        f64::INFINITY,
+        BandwidthLimiter::unlimited(),
         MAX_MESSAGE_SIZE,
         MessageDeserializer::new(
             THREAD_COUNT,
@@ -95,10 +96,14 @@ async fn test_node_worker_shutdown() {
             MAX_OPERATION_DATASTORE_VALUE_LENGTH,
         ),
     );
-    let writer = WriteBinder::new(duplex_mock_write, f64::INFINITY, MAX_MESSAGE_SIZE);
+    let writer = WriteBinder::new(
+        duplex_mock_write,
+        BandwidthLimiter::unlimited(),
+        MAX_MESSAGE_SIZE,
+    );
 
     // Note: both channels have size 1.
-    let (node_command_tx, node_command_rx) = mpsc::channel::<NodeCommand>(1);
+    let (node_command_tx, node_command_rx) = node_command_channel(1);
     let (node_event_tx, _node_event_rx) = mpsc::channel::<NodeEvent>(1);
 
     let keypair = KeyPair::generate();
@@ -151,7 +156,7 @@ async fn test_node_worker_operations_message() {
     let (duplex_mock_read, duplex_mock_write) = tokio::io::split(duplex_controller);
     let reader = ReadBinder::new(
         duplex_mock_read,
-        f64::INFINITY,
+        BandwidthLimiter::unlimited(),
         MAX_MESSAGE_SIZE,
         MessageDeserializer::new(
             THREAD_COUNT,
@@ -169,10 +174,14 @@ async fn test_node_worker_operations_message() {
             MAX_OPERATION_DATASTORE_VALUE_LENGTH,
         ),
     );
-    let writer = WriteBinder::new(duplex_mock_write, f64::INFINITY, MAX_MESSAGE_SIZE);
+    let writer = WriteBinder::new(
+        duplex_mock_write,
+        BandwidthLimiter::unlimited(),
+        MAX_MESSAGE_SIZE,
+    );
 
     // Note: both channels have size 1.
-    let (node_command_tx, node_command_rx) = mpsc::channel::<NodeCommand>(1);
+    let (node_command_tx, node_command_rx) = node_command_channel(1);
     let (node_event_tx, _node_event_rx) = mpsc::channel::<NodeEvent>(1);
 
     let keypair = KeyPair::generate();
@@ -632,6 +641,7 @@ async fn test_advertised_and_wakeup_interval() {
         active_out_connections: 0,
         active_in_connections: 0,
         banned: false,
+        quality: Default::default(),
     }]);
     let network_conf = NetworkConfig {
         wakeup_interval: MassaTime::from_millis(500),
@@ -722,7 +732,7 @@ async fn test_advertised_and_wakeup_interval() {
                 &mut network_event_receiver,
                 1000.into(),
                 |msg| match msg {
-                    NetworkEvent::NewConnection(_) => Some(()),
+                    NetworkEvent::NewConnection(..) => Some(()),
                     _ => None,
                 },
             )
@@ -767,6 +777,7 @@ async fn test_block_not_found() {
         active_out_connections: 0,
         active_in_connections: 0,
         banned: false,
+        quality: Default::default(),
     }]);
     let network_conf = NetworkConfig {
         peer_types_config: default_testing_peer_type_enum_map(),
@@ -949,6 +960,7 @@ async fn test_retry_connection_closed() {
         active_out_connections: 0,
         active_in_connections: 0,
         banned: false,
+        quality: Default::default(),
     }]);
     let network_conf = NetworkConfig {
         peer_types_config: default_testing_peer_type_enum_map(),
@@ -1048,6 +1060,7 @@ async fn test_operation_messages() {
         active_out_connections: 0,
         active_in_connections: 0,
         banned: false,
+        quality: Default::default(),
     }]);
     let network_conf = NetworkConfig {
         peer_types_config: default_testing_peer_type_enum_map(),
@@ -1163,6 +1176,7 @@ async fn test_endorsements_messages() {
         active_out_connections: 0,
         active_in_connections: 0,
         banned: false,
+        quality: Default::default(),
     }]);
     let network_conf = NetworkConfig {
         peer_types_config: default_testing_peer_type_enum_map(),
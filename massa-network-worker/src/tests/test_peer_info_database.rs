@@ -6,7 +6,7 @@ use enum_map::enum_map;
 use massa_network_exports::{
     settings::PeerTypeConnectionConfig, NetworkConnectionErrorType, PeerInfo, PeerType,
 };
-use massa_time::MassaTime;
+use massa_time::{ClockCompensation, MassaTime};
 use serial_test::serial;
 use std::{collections::HashMap, net::IpAddr};
 use tokio::sync::watch;
@@ -54,7 +54,7 @@ async fn test_try_new_in_connection_in_connection_closed() {
         saver_join_handle,
         saver_watch_tx,
         wakeup_interval,
-        clock_compensation: 0,
+        clock_compensation: ClockCompensation::default(),
         peer_types_connection_count: Default::default(),
     };
 
@@ -158,7 +158,7 @@ async fn test_out_connection_attempt_failed() {
         saver_watch_tx,
         peer_types_connection_count: Default::default(),
         wakeup_interval,
-        clock_compensation: 0,
+        clock_compensation: ClockCompensation::default(),
     };
 
     // test with no connection attempt before
@@ -269,7 +269,7 @@ async fn test_try_out_connection_attempt_success() {
         saver_watch_tx,
         peer_types_connection_count: Default::default(),
         wakeup_interval,
-        clock_compensation: 0,
+        clock_compensation: ClockCompensation::default(),
     };
 
     // test with no connection attempt before
@@ -361,7 +361,7 @@ async fn test_new_out_connection_closed() {
         saver_watch_tx,
         peer_types_connection_count: Default::default(),
         wakeup_interval,
-        clock_compensation: 0,
+        clock_compensation: ClockCompensation::default(),
     };
 
     //
@@ -442,7 +442,7 @@ async fn test_new_out_connection_attempt() {
         saver_watch_tx,
         peer_types_connection_count: Default::default(),
         wakeup_interval,
-        clock_compensation: 0,
+        clock_compensation: ClockCompensation::default(),
     };
 
     // test with no peers.
@@ -529,7 +529,7 @@ async fn test_get_advertisable_peer_ips() {
         saver_watch_tx,
         peer_types_connection_count: Default::default(),
         wakeup_interval,
-        clock_compensation: 0,
+        clock_compensation: ClockCompensation::default(),
     };
 
     // test with no peers.
@@ -651,7 +651,7 @@ async fn test_get_out_connection_candidate_ips() {
         saver_watch_tx,
         peer_types_connection_count: Default::default(),
         wakeup_interval,
-        clock_compensation: 0,
+        clock_compensation: ClockCompensation::default(),
     };
 
     // test with no peers.
@@ -680,6 +680,68 @@ async fn test_get_out_connection_candidate_ips() {
     );
 }
 
+#[tokio::test]
+#[serial]
+async fn test_out_connection_candidates_sorted_by_quality() {
+    let network_settings = NetworkConfig {
+        peer_quality_half_life: MassaTime::from_millis(1000),
+        ..NetworkConfig::default()
+    };
+    let mut peers: HashMap<IpAddr, PeerInfo> = HashMap::new();
+
+    // peer with no history
+    let unknown_peer =
+        default_peer_info_not_connected(IpAddr::V4(std::net::Ipv4Addr::new(169, 202, 0, 11)));
+    peers.insert(unknown_peer.ip, unknown_peer);
+
+    // peer failing its handshakes
+    let mut failing_peer =
+        default_peer_info_not_connected(IpAddr::V4(std::net::Ipv4Addr::new(169, 202, 0, 12)));
+    failing_peer.quality.handshake_failures = 3.0;
+    peers.insert(failing_peer.ip, failing_peer);
+
+    // peer with a long uptime
+    let mut good_peer =
+        default_peer_info_not_connected(IpAddr::V4(std::net::Ipv4Addr::new(169, 202, 0, 13)));
+    good_peer.quality.uptime = 7200.0;
+    good_peer.quality.successful_connections = 2.0;
+    peers.insert(good_peer.ip, good_peer);
+
+    let wakeup_interval = network_settings.wakeup_interval;
+    let (saver_watch_tx, _) = watch::channel(peers.clone());
+    let saver_join_handle = tokio::spawn(async move {});
+    let mut db = PeerInfoDatabase {
+        network_settings,
+        peers,
+        saver_join_handle,
+        saver_watch_tx,
+        peer_types_connection_count: Default::default(),
+        wakeup_interval,
+        clock_compensation: ClockCompensation::default(),
+    };
+
+    // the best scored peers are dialed first
+    let ip_list = db.get_out_connection_candidate_ips().unwrap();
+    assert_eq!(
+        ip_list,
+        vec![good_peer.ip, unknown_peer.ip, failing_peer.ip]
+    );
+
+    // a handshake failure and a ban are recorded in the quality of the peer
+    db.peer_handshake_failed(&unknown_peer.ip).unwrap();
+    db.peer_banned(&unknown_peer.ip).unwrap();
+    let quality = db.peers.get(&unknown_peer.ip).unwrap().quality;
+    assert_eq!(quality.handshake_failures, 1.0);
+    assert_eq!(quality.bans, 1.0);
+
+    // metrics decay over time
+    db.update().unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
+    db.update().unwrap();
+    let quality = db.peers.get(&failing_peer.ip).unwrap().quality;
+    assert!(quality.handshake_failures < 1.6 && quality.handshake_failures > 1.0);
+}
+
 #[tokio::test]
 #[serial]
 async fn test_cleanup_peers() {
@@ -825,6 +887,7 @@ fn default_peer_info_connected(ip: IpAddr) -> PeerInfo {
         active_out_connections: 1,
         active_in_connections: 0,
         banned: false,
+        quality: Default::default(),
     }
 }
 
@@ -839,6 +902,7 @@ fn default_peer_info_not_connected(ip: IpAddr) -> PeerInfo {
         active_out_connections: 0,
         active_in_connections: 0,
         banned: false,
+        quality: Default::default(),
     }
 }
 
@@ -879,6 +943,7 @@ impl From<u32> for PeerInfoDatabase {
                 active_out_connections: 0,
                 active_in_connections: 0,
                 banned: ip[1] % 5 == 0,
+                quality: Default::default(),
             };
             peers.insert(peer.ip, peer);
         }
@@ -893,7 +958,7 @@ impl From<u32> for PeerInfoDatabase {
             saver_watch_tx,
             peer_types_connection_count: Default::default(),
             wakeup_interval,
-            clock_compensation: 0,
+            clock_compensation: ClockCompensation::default(),
         }
     }
 }
@@ -2,6 +2,7 @@
 
 use super::super::binders::{ReadBinder, WriteBinder};
 use super::tools;
+use crate::bandwidth::BandwidthLimiter;
 use crate::handshake_worker::HandshakeWorker;
 use crate::messages::Message;
 use crate::start_network_controller;
@@ -24,7 +25,7 @@ use massa_network_exports::{
     ConnectionId, NetworkCommandSender, NetworkEventReceiver, NetworkManager, PeerInfo,
 };
 use massa_signature::KeyPair;
-use massa_time::MassaTime;
+use massa_time::{ClockCompensation, MassaTime};
 use std::str::FromStr;
 use std::{
     future::Future,
@@ -92,8 +93,8 @@ pub async fn full_connection_to_controller(
         rw_timeout_ms.into(),
         Version::from_str("TEST.1.10").unwrap(),
         connection_id,
-        f64::INFINITY,
-        f64::INFINITY,
+        BandwidthLimiter::unlimited(),
+        BandwidthLimiter::unlimited(),
     )
     .await
     .expect("handshake creation failed")
@@ -105,7 +106,7 @@ pub async fn full_connection_to_controller(
         network_event_receiver,
         event_timeout_ms.into(),
         |msg| match msg {
-            NetworkEvent::NewConnection(conn_node_id) => {
+            NetworkEvent::NewConnection(conn_node_id, _) => {
                 if conn_node_id == mock_node_id {
                     Some(())
                 } else {
@@ -151,8 +152,8 @@ pub async fn rejected_connection_to_controller(
         rw_timeout_ms.into(),
         Version::from_str("TEST.1.10").unwrap(),
         connection_id,
-        f64::INFINITY,
-        f64::INFINITY,
+        BandwidthLimiter::unlimited(),
+        BandwidthLimiter::unlimited(),
     )
     .await
     .expect("handshake creation failed")
@@ -169,7 +170,7 @@ pub async fn rejected_connection_to_controller(
         network_event_receiver,
         event_timeout_ms.into(),
         |msg| match msg {
-            NetworkEvent::NewConnection(conn_node_id) => {
+            NetworkEvent::NewConnection(conn_node_id, _) => {
                 if conn_node_id == mock_node_id {
                     Some(())
                 } else {
@@ -236,8 +237,8 @@ pub async fn full_connection_from_controller(
         rw_timeout_ms.into(),
         Version::from_str("TEST.1.10").unwrap(),
         connection_id,
-        f64::INFINITY,
-        f64::INFINITY,
+        BandwidthLimiter::unlimited(),
+        BandwidthLimiter::unlimited(),
     )
     .await
     .expect("handshake creation failed")
@@ -249,7 +250,7 @@ pub async fn full_connection_from_controller(
         network_event_receiver,
         event_timeout_ms.into(),
         |evt| match evt {
-            NetworkEvent::NewConnection(node_id) => {
+            NetworkEvent::NewConnection(node_id, _) => {
                 if node_id == mock_node_id {
                     Some(())
                 } else {
@@ -370,7 +371,7 @@ pub async fn network_test<F, V>(
         start_network_controller(
             &network_settings,
             establisher,
-            0,
+            ClockCompensation::default(),
             None,
             Version::from_str("TEST.1.10").unwrap(),
         )
@@ -0,0 +1,222 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Background watcher of the free disk space on the node's storage paths.
+//!
+//! The computed `DiskSpaceStatus` is shared with the API, which reports it in `get_status`
+//! and refuses new operations while the status is critical. The block graph store and the state
+//! snapshots are not written either while it is critical, so that the remaining space is kept
+//! for consensus and execution.
+
+use massa_models::stats::DiskSpaceStatus;
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use tracing::{error, info, warn};
+
+/// Disk monitor configuration
+#[derive(Debug, Clone)]
+pub struct DiskMonitorConfig {
+    /// paths to watch, each one is checked on the file system that holds it
+    pub paths: Vec<PathBuf>,
+    /// interval between two checks
+    pub check_interval: MassaTime,
+    /// free bytes under which the status becomes `Low`
+    pub warn_threshold: u64,
+    /// free bytes under which the status becomes `Critical`
+    pub critical_threshold: u64,
+}
+
+/// Allows stopping the disk monitor thread
+pub struct DiskMonitorManager {
+    stop_tx: mpsc::Sender<()>,
+    join_handle: thread::JoinHandle<()>,
+}
+
+impl DiskMonitorManager {
+    /// stop the disk monitor thread
+    pub fn stop(self) {
+        info!("stopping disk monitor...");
+        let _ = self.stop_tx.send(());
+        if self.join_handle.join().is_err() {
+            warn!("disk monitor thread panicked");
+        }
+        info!("disk monitor stopped");
+    }
+}
+
+/// Starts the disk monitor thread.
+///
+/// # Return value
+/// The manager used to stop the thread and the shared status updated at every check.
+pub fn start_disk_monitor(
+    config: DiskMonitorConfig,
+) -> (DiskMonitorManager, Arc<RwLock<DiskSpaceStatus>>) {
+    let status = Arc::new(RwLock::new(DiskSpaceStatus::Ok));
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+    let thread_status = status.clone();
+    let join_handle = thread::Builder::new()
+        .name("disk-monitor".into())
+        .spawn(move || run(config, thread_status, stop_rx))
+        .expect("failed to spawn thread : disk-monitor");
+    (
+        DiskMonitorManager {
+            stop_tx,
+            join_handle,
+        },
+        status,
+    )
+}
+
+/// main loop: check, publish the new status, then wait for the next check or a stop signal
+fn run(
+    config: DiskMonitorConfig,
+    status: Arc<RwLock<DiskSpaceStatus>>,
+    stop_rx: mpsc::Receiver<()>,
+) {
+    loop {
+        let new_status = check(&config);
+        let prev_status = std::mem::replace(&mut *status.write(), new_status);
+        if new_status != prev_status {
+            match new_status {
+                DiskSpaceStatus::Ok => info!("free disk space is back to normal"),
+                DiskSpaceStatus::Low => warn!("free disk space is low"),
+                DiskSpaceStatus::Critical => error!(
+                    "free disk space is critically low: new operations are refused by the API, \
+                    the block graph store and the state snapshots are paused"
+                ),
+            }
+        }
+        match stop_rx.recv_timeout(config.check_interval.to_duration()) {
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            // stop message received or sender dropped
+            _ => break,
+        }
+    }
+}
+
+/// Returns the worst status among the watched paths
+fn check(config: &DiskMonitorConfig) -> DiskSpaceStatus {
+    let mut worst = DiskSpaceStatus::Ok;
+    for path in &config.paths {
+        let available = match available_space(path) {
+            Ok(v) => v,
+            Err(err) if err.kind() == std::io::ErrorKind::Unsupported => continue,
+            Err(err) => {
+                warn!(
+                    "could not get free disk space for {}: {}",
+                    path.display(),
+                    err
+                );
+                continue;
+            }
+        };
+        worst = worst_status(worst, path_status(config, available));
+    }
+    worst
+}
+
+/// Status of a path given the free bytes of its file system
+fn path_status(config: &DiskMonitorConfig, available: u64) -> DiskSpaceStatus {
+    if available < config.critical_threshold {
+        DiskSpaceStatus::Critical
+    } else if available < config.warn_threshold {
+        DiskSpaceStatus::Low
+    } else {
+        DiskSpaceStatus::Ok
+    }
+}
+
+/// Worst of two statuses
+fn worst_status(a: DiskSpaceStatus, b: DiskSpaceStatus) -> DiskSpaceStatus {
+    match (a, b) {
+        (DiskSpaceStatus::Critical, _) | (_, DiskSpaceStatus::Critical) => {
+            DiskSpaceStatus::Critical
+        }
+        (DiskSpaceStatus::Low, _) | (_, DiskSpaceStatus::Low) => DiskSpaceStatus::Low,
+        _ => DiskSpaceStatus::Ok,
+    }
+}
+
+/// Free bytes available to the node on the file system holding `path`.
+/// Watched paths may not exist yet, in which case their closest existing ancestor is used.
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+fn available_space(path: &Path) -> std::io::Result<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .unwrap_or_else(|| Path::new("."));
+    let c_path = std::ffi::CString::new(existing.as_os_str().as_bytes())?;
+    // SAFETY: `c_path` is a valid NUL-terminated string and `stat` is only read on success
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+}
+
+/// Free disk space is only probed on unix platforms
+#[cfg(not(unix))]
+fn available_space(_path: &Path) -> std::io::Result<u64> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(
+        paths: Vec<PathBuf>,
+        warn_threshold: u64,
+        critical_threshold: u64,
+    ) -> DiskMonitorConfig {
+        DiskMonitorConfig {
+            paths,
+            check_interval: MassaTime::from_millis(1000),
+            warn_threshold,
+            critical_threshold,
+        }
+    }
+
+    #[test]
+    fn test_path_status_thresholds() {
+        let config = config(Vec::new(), 1000, 100);
+        assert_eq!(path_status(&config, 0), DiskSpaceStatus::Critical);
+        assert_eq!(path_status(&config, 99), DiskSpaceStatus::Critical);
+        assert_eq!(path_status(&config, 100), DiskSpaceStatus::Low);
+        assert_eq!(path_status(&config, 999), DiskSpaceStatus::Low);
+        assert_eq!(path_status(&config, 1000), DiskSpaceStatus::Ok);
+        assert_eq!(path_status(&config, u64::MAX), DiskSpaceStatus::Ok);
+    }
+
+    #[test]
+    fn test_worst_status() {
+        use DiskSpaceStatus::{Critical, Low, Ok};
+        assert_eq!(worst_status(Ok, Ok), Ok);
+        assert_eq!(worst_status(Ok, Low), Low);
+        assert_eq!(worst_status(Low, Ok), Low);
+        assert_eq!(worst_status(Low, Critical), Critical);
+        assert_eq!(worst_status(Critical, Ok), Critical);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_watched_paths() {
+        let dir = std::env::temp_dir();
+        // paths that do not exist yet are checked on the file system of their closest ancestor
+        let paths = vec![dir.clone(), dir.join("not_created_yet").join("ledger")];
+        assert_eq!(check(&config(paths.clone(), 0, 0)), DiskSpaceStatus::Ok);
+        assert_eq!(
+            check(&config(paths.clone(), u64::MAX, 0)),
+            DiskSpaceStatus::Low
+        );
+        assert_eq!(
+            check(&config(paths, u64::MAX, u64::MAX)),
+            DiskSpaceStatus::Critical
+        );
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Background scrubbing of the final ledger.
+//!
+//! The ledger is checked range by range against the Merkle tree maintained along its hash,
+//! so that a corrupted disk ledger is reported before it makes the node diverge from consensus.
+//! Each range is checked under a short read lock of the final state, and ranges are spaced
+//! by a configurable interval so that slot execution is not disturbed.
+//! The resulting `LedgerIntegrityStatus` is shared with the API, which reports it in `get_status`.
+
+use massa_final_state::FinalState;
+use massa_models::{stats::LedgerIntegrityStatus, streaming_step::StreamingStep};
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use tracing::{error, info, warn};
+
+/// Ledger scrubber configuration
+#[derive(Debug, Clone)]
+pub struct LedgerScrubberConfig {
+    /// interval between the checks of two ranges of the ledger
+    pub scrub_interval: MassaTime,
+    /// number of ledger entries checked per range, 0 to disable scrubbing
+    pub scrub_batch_size: usize,
+}
+
+/// Allows stopping the ledger scrubber thread
+pub struct LedgerScrubberManager {
+    stop_tx: mpsc::Sender<()>,
+    join_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl LedgerScrubberManager {
+    /// stop the ledger scrubber thread
+    pub fn stop(self) {
+        let join_handle = match self.join_handle {
+            Some(join_handle) => join_handle,
+            None => return,
+        };
+        info!("stopping ledger scrubber...");
+        let _ = self.stop_tx.send(());
+        if join_handle.join().is_err() {
+            warn!("ledger scrubber thread panicked");
+        }
+        info!("ledger scrubber stopped");
+    }
+}
+
+/// Starts the ledger scrubber thread, unless scrubbing is disabled.
+///
+/// # Return value
+/// The manager used to stop the thread and the shared status updated after every checked range.
+pub fn start_ledger_scrubber(
+    config: LedgerScrubberConfig,
+    final_state: Arc<RwLock<FinalState>>,
+) -> (LedgerScrubberManager, Arc<RwLock<LedgerIntegrityStatus>>) {
+    let status = Arc::new(RwLock::new(LedgerIntegrityStatus::default()));
+    let (stop_tx, stop_rx) = mpsc::channel::<()>();
+    let join_handle = if config.scrub_batch_size == 0 {
+        None
+    } else {
+        let thread_status = status.clone();
+        Some(
+            thread::Builder::new()
+                .name("ledger-scrubber".into())
+                .spawn(move || run(config, final_state, thread_status, stop_rx))
+                .expect("failed to spawn thread : ledger-scrubber"),
+        )
+    };
+    (
+        LedgerScrubberManager {
+            stop_tx,
+            join_handle,
+        },
+        status,
+    )
+}
+
+/// main loop: check a range, publish the new status, then wait for the next range or a stop signal
+fn run(
+    config: LedgerScrubberConfig,
+    final_state: Arc<RwLock<FinalState>>,
+    status: Arc<RwLock<LedgerIntegrityStatus>>,
+    stop_rx: mpsc::Receiver<()>,
+) {
+    let mut cursor = StreamingStep::Started;
+    loop {
+        let report = final_state
+            .read()
+            .ledger
+            .scrub_ledger_range(cursor, config.scrub_batch_size);
+        for anomaly in &report.anomalies {
+            error!("final ledger integrity check failed: {}", anomaly);
+        }
+        {
+            let mut status = status.write();
+            status.checked_entries = status
+                .checked_entries
+                .saturating_add(report.checked_entries as u64);
+            status.anomaly_count = status
+                .anomaly_count
+                .saturating_add(report.anomalies.len() as u64);
+            if let Some(anomaly) = report.anomalies.last() {
+                status.last_anomaly = Some(anomaly.clone());
+            }
+            if report.cursor.finished() {
+                status.completed_passes = status.completed_passes.saturating_add(1);
+            }
+        }
+
+        // start a new pass once the end of the ledger is reached
+        cursor = match report.cursor {
+            StreamingStep::Finished => StreamingStep::Started,
+            cursor => cursor,
+        };
+        match stop_rx.recv_timeout(config.scrub_interval.to_duration()) {
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            // stop message received or sender dropped
+            _ => break,
+        }
+    }
+}
@@ -4,46 +4,66 @@
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
 extern crate massa_logging;
+use crate::disk_monitor::{start_disk_monitor, DiskMonitorConfig, DiskMonitorManager};
+use crate::ledger_scrubber::{start_ledger_scrubber, LedgerScrubberConfig, LedgerScrubberManager};
+use crate::pool_persistence::PoolPersistence;
 use crate::settings::SETTINGS;
+use crate::state_snapshot::{
+    load_state_snapshot, start_state_snapshot_worker, StateSnapshotConfig, StateSnapshotManager,
+};
+use crate::time_health::{start_time_health_worker, TimeHealthConfig, TimeHealthManager};
 
 use dialoguer::Password;
-use massa_api::{APIConfig, Private, Public, RpcServer, StopHandle, API};
+use massa_api::{
+    APIConfig, Private, Public, RpcServer, StopHandle, Subscriptions, SubsystemCommand, API,
+};
 use massa_async_pool::AsyncPoolConfig;
-use massa_bootstrap::{get_state, start_bootstrap_server, BootstrapConfig, BootstrapManager};
+use massa_bootstrap::{
+    get_state, start_bootstrap_server, BootstrapConfig, BootstrapManager, GlobalBootstrapState,
+};
 use massa_consensus_exports::ConsensusManager;
 use massa_consensus_exports::{
-    events::ConsensusEvent, settings::ConsensusChannels, ConsensusConfig, ConsensusEventReceiver,
+    events::ConsensusEvent,
+    settings::{ConsensusBroadcasts, ConsensusChannels},
+    ConsensusConfig, ConsensusEventReceiver,
 };
 use massa_consensus_worker::start_consensus_controller;
 use massa_executed_ops::ExecutedOpsConfig;
-use massa_execution_exports::{ExecutionConfig, ExecutionManager, StorageCostsConstants};
+use massa_execution_exports::{
+    ExecutionChannels, ExecutionConfig, ExecutionManager, GasCosts, StorageCostsConstants,
+};
 use massa_execution_worker::start_execution_worker;
-use massa_factory_exports::{FactoryChannels, FactoryConfig, FactoryManager};
+use massa_factory_exports::{ExecutionPolicy, FactoryChannels, FactoryConfig, FactoryManager};
 use massa_factory_worker::start_factory;
 use massa_final_state::{FinalState, FinalStateConfig};
-use massa_ledger_exports::LedgerConfig;
+use massa_ledger_exports::{LedgerConfig, LedgerController};
 use massa_ledger_worker::FinalLedger;
 use massa_logging::massa_trace;
 use massa_models::address::Address;
+use massa_models::api::{NodeSubsystem, SubsystemAction};
 use massa_models::config::constants::{
-    ASYNC_POOL_BOOTSTRAP_PART_SIZE, BLOCK_REWARD, BOOTSTRAP_RANDOMNESS_SIZE_BYTES, CHANNEL_SIZE,
-    DEFERRED_CREDITS_BOOTSTRAP_PART_SIZE, DELTA_F0, ENDORSEMENT_COUNT, END_TIMESTAMP,
-    EXECUTED_OPS_BOOTSTRAP_PART_SIZE, GENESIS_KEY, GENESIS_TIMESTAMP, INITIAL_DRAW_SEED,
-    LEDGER_COST_PER_BYTE, LEDGER_ENTRY_BASE_SIZE, LEDGER_ENTRY_DATASTORE_BASE_SIZE,
-    LEDGER_PART_SIZE_MESSAGE_BYTES, MAX_ADVERTISE_LENGTH, MAX_ASK_BLOCKS_PER_MESSAGE,
-    MAX_ASYNC_GAS, MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH, MAX_BLOCK_SIZE,
-    MAX_BOOTSTRAP_ASYNC_POOL_CHANGES, MAX_BOOTSTRAP_BLOCKS, MAX_BOOTSTRAP_ERROR_LENGTH,
-    MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE, MAX_BOOTSTRAP_MESSAGE_SIZE, MAX_BYTECODE_LENGTH,
-    MAX_DATASTORE_ENTRY_COUNT, MAX_DATASTORE_KEY_LENGTH, MAX_DATASTORE_VALUE_LENGTH,
-    MAX_DEFERRED_CREDITS_LENGTH, MAX_ENDORSEMENTS_PER_MESSAGE, MAX_EXECUTED_OPS_CHANGES_LENGTH,
-    MAX_EXECUTED_OPS_LENGTH, MAX_FUNCTION_NAME_LENGTH, MAX_GAS_PER_BLOCK, MAX_LEDGER_CHANGES_COUNT,
-    MAX_MESSAGE_SIZE, MAX_OPERATIONS_PER_BLOCK, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+    ASYNC_POOL_BOOTSTRAP_PART_SIZE, BLOCK_REWARD, BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
+    BROADCAST_CHANNEL_SIZE, CHANNEL_SIZE, DEFERRED_CREDITS_BOOTSTRAP_PART_SIZE, DELTA_F0,
+    ENDORSEMENT_COUNT, END_TIMESTAMP, EXECUTED_OPS_BOOTSTRAP_PART_SIZE, GENESIS_KEY,
+    GENESIS_TIMESTAMP, INITIAL_DRAW_SEED, LEDGER_COST_PER_BYTE, LEDGER_ENTRY_BASE_SIZE,
+    LEDGER_ENTRY_DATASTORE_BASE_SIZE, LEDGER_PART_SIZE_MESSAGE_BYTES, MAX_ADVERTISE_LENGTH,
+    MAX_ASK_BLOCKS_PER_MESSAGE, MAX_ASYNC_GAS, MAX_ASYNC_GAS_PER_DESTINATION,
+    MAX_ASYNC_MESSAGES_PER_DESTINATION, MAX_ASYNC_MESSAGE_DATA, MAX_ASYNC_POOL_LENGTH,
+    MAX_BLOCK_SIZE, MAX_BOOTSTRAP_ASYNC_POOL_CHANGES, MAX_BOOTSTRAP_BLOCKS,
+    MAX_BOOTSTRAP_ERROR_LENGTH, MAX_BOOTSTRAP_FINAL_STATE_PARTS_SIZE, MAX_BOOTSTRAP_MESSAGE_SIZE,
+    MAX_BYTECODE_LENGTH, MAX_CALL_STACK_DEPTH, MAX_DATASTORE_ENTRY_COUNT, MAX_DATASTORE_KEY_LENGTH,
+    MAX_DATASTORE_VALUE_LENGTH, MAX_DEFERRED_CREDITS_LENGTH, MAX_ENDORSEMENTS_PER_MESSAGE,
+    MAX_EVENT_COUNT_PER_CALL, MAX_EVENT_COUNT_PER_SLOT, MAX_EVENT_SIZE_PER_CALL,
+    MAX_EVENT_SIZE_PER_SLOT, MAX_EXECUTED_OPS_CHANGES_LENGTH, MAX_EXECUTED_OPS_LENGTH,
+    MAX_FUNCTION_NAME_LENGTH, MAX_GAS_PER_BLOCK, MAX_LEDGER_CHANGES_COUNT, MAX_MESSAGE_SIZE,
+    MAX_OPERATIONS_PER_BLOCK, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
     MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE,
     MAX_PRODUCTION_STATS_LENGTH, MAX_ROLLS_COUNT_LENGTH, NETWORK_CONTROLLER_CHANNEL_SIZE,
     NETWORK_EVENT_CHANNEL_SIZE, NETWORK_NODE_COMMAND_CHANNEL_SIZE, NETWORK_NODE_EVENT_CHANNEL_SIZE,
     OPERATION_VALIDITY_PERIODS, PERIODS_PER_CYCLE, POOL_CONTROLLER_CHANNEL_SIZE,
     POS_MISS_RATE_DEACTIVATION_THRESHOLD, POS_SAVED_CYCLES, PROTOCOL_CONTROLLER_CHANNEL_SIZE,
-    PROTOCOL_EVENT_CHANNEL_SIZE, ROLL_PRICE, T0, THREAD_COUNT, VERSION,
+    PROTOCOL_EVENT_CHANNEL_SIZE, ROLL_PRICE, STORAGE_RENT_PER_BYTE_PER_CYCLE, T0, THREAD_COUNT,
+    VERSION,
 };
 use massa_network_exports::{Establisher, NetworkConfig, NetworkManager};
 use massa_network_worker::start_network_controller;
@@ -54,7 +74,7 @@ use massa_pos_worker::start_selector_worker;
 use massa_protocol_exports::{ProtocolConfig, ProtocolManager};
 use massa_protocol_worker::start_protocol_controller;
 use massa_storage::Storage;
-use massa_time::MassaTime;
+use massa_time::{ClockCompensation, MassaTime};
 use massa_wallet::Wallet;
 use parking_lot::RwLock;
 use std::path::PathBuf;
@@ -62,14 +82,23 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::{path::Path, process, sync::Arc};
 use structopt::StructOpt;
 use tokio::signal;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 use tracing::{error, info, warn};
 use tracing_subscriber::filter::{filter_fn, LevelFilter};
 
+mod disk_monitor;
+mod ledger_scrubber;
+mod pool_persistence;
 mod settings;
+mod state_snapshot;
+mod time_health;
 
 async fn launch(
     node_wallet: Arc<RwLock<Wallet>>,
+    ledger_snapshot_path: Option<PathBuf>,
+    resume_from_disk: bool,
+    verify_ledger_integrity: bool,
+    repair_ledger: bool,
 ) -> (
     ConsensusEventReceiver,
     Option<BootstrapManager>,
@@ -80,9 +109,17 @@ async fn launch(
     ProtocolManager,
     NetworkManager,
     Box<dyn FactoryManager>,
-    mpsc::Receiver<()>,
+    DiskMonitorManager,
+    LedgerScrubberManager,
+    StateSnapshotManager,
+    TimeHealthManager,
+    PoolPersistence,
+    mpsc::Receiver<bool>,
+    mpsc::Receiver<SubsystemCommand>,
     StopHandle,
     StopHandle,
+    Box<dyn Fn() -> StopHandle>,
+    Option<StopHandle>,
 ) {
     info!("Node version : {}", *VERSION);
     if let Some(end) = *END_TIMESTAMP {
@@ -117,6 +154,8 @@ async fn launch(
     let executed_ops_config = ExecutedOpsConfig {
         thread_count: THREAD_COUNT,
         bootstrap_part_size: EXECUTED_OPS_BOOTSTRAP_PART_SIZE,
+        disk_path: SETTINGS.ledger.executed_ops_disk_path.clone(),
+        bloom_filter_size: SETTINGS.ledger.executed_ops_bloom_filter_size,
     };
     let final_state_config = FinalStateConfig {
         ledger_config: ledger_config.clone(),
@@ -138,7 +177,30 @@ async fn launch(
     }
 
     // Create final ledger
-    let ledger = FinalLedger::new(ledger_config.clone());
+    let mut ledger = FinalLedger::new(ledger_config.clone());
+
+    // Replace it by a ledger snapshot if one was given
+    if let Some(path) = ledger_snapshot_path {
+        let slot = ledger
+            .import_snapshot(&path)
+            .expect("could not import ledger snapshot");
+        info!(
+            "Imported ledger snapshot {} at slot {}",
+            path.display(),
+            slot
+        );
+    }
+
+    // launch disk monitor
+    let (disk_monitor_manager, disk_status) = start_disk_monitor(DiskMonitorConfig {
+        paths: vec![
+            SETTINGS.ledger.disk_ledger_path.clone(),
+            SETTINGS.factory.staking_wallet_path.clone(),
+        ],
+        check_interval: SETTINGS.disk_monitor.check_interval,
+        warn_threshold: SETTINGS.disk_monitor.warn_threshold,
+        critical_threshold: SETTINGS.disk_monitor.critical_threshold,
+    });
 
     // launch selector worker
     let (selector_manager, selector_controller) = start_selector_worker(SelectorConfig {
@@ -161,6 +223,47 @@ async fn launch(
         .expect("could not init final state"),
     ));
 
+    // launch ledger scrubber
+    let (ledger_scrubber_manager, ledger_integrity) = start_ledger_scrubber(
+        LedgerScrubberConfig {
+            scrub_interval: SETTINGS.ledger.scrub_interval,
+            scrub_batch_size: SETTINGS.ledger.scrub_batch_size,
+        },
+        final_state.clone(),
+    );
+
+    // resume from the local state snapshot if asked to, bootstrapping otherwise
+    let resumed_graph = if resume_from_disk {
+        match load_state_snapshot(&SETTINGS.state_snapshot.path, &final_state) {
+            Ok(graph) => Some(graph),
+            Err(err) => {
+                warn!(
+                    "could not resume from the state snapshot, bootstrapping instead: {}",
+                    err
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // check the imported or resumed ledger if asked to
+    if verify_ledger_integrity || repair_ledger {
+        let report = final_state
+            .write()
+            .ledger
+            .verify_ledger_integrity(repair_ledger);
+        if report.is_consistent() {
+            info!(
+                "final ledger integrity verified over {} entries",
+                report.checked_entries
+            );
+        } else {
+            error!("final ledger integrity check failed: {}", report);
+        }
+    }
+
     // interrupt signal listener
     let stop_signal = signal::ctrl_c();
     tokio::pin!(stop_signal);
@@ -169,7 +272,13 @@ async fn launch(
         bootstrap_list: SETTINGS.bootstrap.bootstrap_list.clone(),
         bootstrap_whitelist_file: SETTINGS.bootstrap.bootstrap_whitelist_file.clone(),
         bootstrap_blacklist_file: SETTINGS.bootstrap.bootstrap_blacklist_file.clone(),
-        bind: SETTINGS.bootstrap.bind,
+        bootstrap_allowlist_file: SETTINGS.bootstrap.bootstrap_allowlist_file.clone(),
+        // a light node does not store the operations of its blocks: it cannot serve bootstrap
+        bind: if SETTINGS.protocol.light_mode {
+            None
+        } else {
+            SETTINGS.bootstrap.bind
+        },
         connect_timeout: SETTINGS.bootstrap.connect_timeout,
         read_timeout: SETTINGS.bootstrap.read_timeout,
         write_timeout: SETTINGS.bootstrap.write_timeout,
@@ -182,12 +291,22 @@ async fn launch(
         max_simultaneous_bootstraps: SETTINGS.bootstrap.max_simultaneous_bootstraps,
         per_ip_min_interval: SETTINGS.bootstrap.per_ip_min_interval,
         ip_list_max_size: SETTINGS.bootstrap.ip_list_max_size,
+        max_simultaneous_bootstraps_per_subnet: SETTINGS
+            .bootstrap
+            .max_simultaneous_bootstraps_per_subnet,
+        subnet_bucket_capacity: SETTINGS.bootstrap.subnet_bucket_capacity,
+        subnet_bucket_refill_interval: SETTINGS.bootstrap.subnet_bucket_refill_interval,
+        ipv4_subnet_prefix_length: SETTINGS.bootstrap.ipv4_subnet_prefix_length,
+        ipv6_subnet_prefix_length: SETTINGS.bootstrap.ipv6_subnet_prefix_length,
         max_bytes_read_write: SETTINGS.bootstrap.max_bytes_read_write,
         max_bootstrap_message_size: MAX_BOOTSTRAP_MESSAGE_SIZE,
         max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
         randomness_size_bytes: BOOTSTRAP_RANDOMNESS_SIZE_BYTES,
         thread_count: THREAD_COUNT,
         periods_per_cycle: PERIODS_PER_CYCLE,
+        t0: T0,
+        max_resync_slots: SETTINGS.bootstrap.max_resync_slots,
+        bootstrap_verification_sources: SETTINGS.bootstrap.bootstrap_verification_sources,
         endorsement_count: ENDORSEMENT_COUNT,
         max_advertise_length: MAX_ADVERTISE_LENGTH,
         max_bootstrap_blocks_length: MAX_BOOTSTRAP_BLOCKS,
@@ -213,22 +332,31 @@ async fn launch(
         max_ops_changes_length: MAX_EXECUTED_OPS_CHANGES_LENGTH,
     };
 
-    // bootstrap
-    let bootstrap_state = tokio::select! {
-        _ = &mut stop_signal => {
-            info!("interrupt signal received in bootstrap loop");
-            process::exit(0);
-        },
-        res = get_state(
-            &bootstrap_config,
-            final_state.clone(),
-            massa_bootstrap::types::Establisher::default(),
-            *VERSION,
-            *GENESIS_TIMESTAMP,
-            *END_TIMESTAMP,
-        ) => match res {
-            Ok(vals) => vals,
-            Err(err) => panic!("critical error detected in the bootstrap process: {}", err)
+    // bootstrap, unless the state was resumed from disk
+    let bootstrap_state = if let Some(graph) = resumed_graph {
+        GlobalBootstrapState {
+            graph: Some(graph),
+            compensation_millis: 0,
+            peers: None,
+            final_state: final_state.clone(),
+        }
+    } else {
+        tokio::select! {
+            _ = &mut stop_signal => {
+                info!("interrupt signal received in bootstrap loop");
+                process::exit(0);
+            },
+            res = get_state(
+                &bootstrap_config,
+                final_state.clone(),
+                massa_bootstrap::types::Establisher::default(),
+                *VERSION,
+                *GENESIS_TIMESTAMP,
+                *END_TIMESTAMP,
+            ) => match res {
+                Ok(vals) => vals,
+                Err(err) => panic!("critical error detected in the bootstrap process: {}", err)
+            }
         }
     };
 
@@ -236,6 +364,9 @@ async fn launch(
         bind: SETTINGS.network.bind,
         routable_ip: SETTINGS.network.routable_ip,
         protocol_port: SETTINGS.network.protocol_port,
+        nat_traversal: SETTINGS.network.nat_traversal,
+        nat_pmp_gateway: SETTINGS.network.nat_pmp_gateway,
+        nat_mapping_lease: SETTINGS.network.nat_mapping_lease,
         connect_timeout: SETTINGS.network.connect_timeout,
         wakeup_interval: SETTINGS.network.wakeup_interval,
         initial_peers_file: SETTINGS.network.initial_peers_file.clone(),
@@ -246,6 +377,7 @@ async fn launch(
         max_idle_peers: SETTINGS.network.max_idle_peers,
         max_banned_peers: SETTINGS.network.max_banned_peers,
         peers_file_dump_interval: SETTINGS.network.peers_file_dump_interval,
+        peer_quality_half_life: SETTINGS.network.peer_quality_half_life,
         message_timeout: SETTINGS.network.message_timeout,
         ask_peer_list_interval: SETTINGS.network.ask_peer_list_interval,
         max_send_wait_node_event: SETTINGS.network.max_send_wait_node_event,
@@ -256,6 +388,10 @@ async fn launch(
         max_operations_per_message: SETTINGS.network.max_operations_per_message,
         max_bytes_read: SETTINGS.network.max_bytes_read,
         max_bytes_write: SETTINGS.network.max_bytes_write,
+        max_bytes_read_whitelisted: SETTINGS.network.max_bytes_read_whitelisted,
+        max_bytes_write_whitelisted: SETTINGS.network.max_bytes_write_whitelisted,
+        max_total_bytes_read: SETTINGS.network.max_total_bytes_read,
+        max_total_bytes_write: SETTINGS.network.max_total_bytes_write,
         max_ask_blocks: MAX_ASK_BLOCKS_PER_MESSAGE,
         max_operations_per_block: MAX_OPERATIONS_PER_BLOCK,
         thread_count: THREAD_COUNT,
@@ -272,15 +408,19 @@ async fn launch(
         controller_channel_size: NETWORK_CONTROLLER_CHANNEL_SIZE,
         event_channel_size: NETWORK_EVENT_CHANNEL_SIZE,
         node_command_channel_size: NETWORK_NODE_COMMAND_CHANNEL_SIZE,
+        block_message_weight: SETTINGS.network.block_message_weight,
         node_event_channel_size: NETWORK_NODE_EVENT_CHANNEL_SIZE,
     };
 
+    // clock compensation shared with the modules following the time health adjustments
+    let clock_compensation = ClockCompensation::new(bootstrap_state.compensation_millis);
+
     // launch network controller
     let (network_command_sender, network_event_receiver, network_manager, private_key, node_id) =
         start_network_controller(
             &network_config,
             Establisher::new(),
-            bootstrap_state.compensation_millis,
+            clock_compensation.clone(),
             bootstrap_state.peers,
             *VERSION,
         )
@@ -293,26 +433,49 @@ async fn launch(
         .compute_initial_draws()
         .expect("could not compute initial draws"); // TODO: this might just mean a bad bootstrap, no need to panic, just reboot
 
+    // Gas cost table, possibly repricing the ledger storage
+    let gas_costs =
+        GasCosts::load(&SETTINGS.execution.gas_costs_path).expect("could not load gas cost table");
+    let ledger_cost_per_byte = gas_costs
+        .ledger_cost_per_byte
+        .unwrap_or(LEDGER_COST_PER_BYTE);
+
     // Storage costs constants
     let storage_costs_constants = StorageCostsConstants {
-        ledger_cost_per_byte: LEDGER_COST_PER_BYTE,
-        ledger_entry_base_cost: LEDGER_COST_PER_BYTE
+        ledger_cost_per_byte,
+        ledger_entry_base_cost: ledger_cost_per_byte
             .checked_mul_u64(LEDGER_ENTRY_BASE_SIZE as u64)
             .expect("Overflow when creating constant ledger_entry_base_cost"),
-        ledger_entry_datastore_base_cost: LEDGER_COST_PER_BYTE
+        ledger_entry_datastore_base_cost: ledger_cost_per_byte
             .checked_mul_u64(LEDGER_ENTRY_DATASTORE_BASE_SIZE as u64)
             .expect("Overflow when creating constant ledger_entry_datastore_base_size"),
     };
+    // channels broadcasting the execution outputs to the API subscribers
+    let execution_channels = ExecutionChannels {
+        operation_status_sender: broadcast::channel(BROADCAST_CHANNEL_SIZE).0,
+        sc_event_sender: broadcast::channel(BROADCAST_CHANNEL_SIZE).0,
+    };
+
     // launch execution module
     let execution_config = ExecutionConfig {
         max_final_events: SETTINGS.execution.max_final_events,
+        max_final_op_executions: SETTINGS.execution.max_final_op_executions,
+        max_final_address_activities: SETTINGS.execution.max_final_address_activities,
+        max_network_stats_slots: SETTINGS.execution.max_network_stats_slots,
+        fee_recommendation_slots: SETTINGS.execution.fee_recommendation_slots,
         readonly_queue_length: SETTINGS.execution.readonly_queue_length,
+        readonly_cache_size: SETTINGS.execution.readonly_cache_size,
         cursor_delay: SETTINGS.execution.cursor_delay,
-        clock_compensation: bootstrap_state.compensation_millis,
+        max_candidate_read_depth: SETTINGS.execution.max_candidate_read_depth,
+        clock_compensation: clock_compensation.clone(),
         max_async_gas: MAX_ASYNC_GAS,
+        max_async_messages_per_destination: MAX_ASYNC_MESSAGES_PER_DESTINATION,
+        max_async_gas_per_destination: MAX_ASYNC_GAS_PER_DESTINATION,
         max_gas_per_block: MAX_GAS_PER_BLOCK,
         roll_price: ROLL_PRICE,
         thread_count: THREAD_COUNT,
+        chain_id: SETTINGS.execution.chain_id,
+        chain_id_activation_period: SETTINGS.execution.chain_id_activation_period,
         t0: T0,
         genesis_timestamp: *GENESIS_TIMESTAMP,
         block_reward: BLOCK_REWARD,
@@ -320,16 +483,37 @@ async fn launch(
         operation_validity_period: OPERATION_VALIDITY_PERIODS,
         periods_per_cycle: PERIODS_PER_CYCLE,
         stats_time_window_duration: SETTINGS.execution.stats_time_window_duration,
+        log_saturations: SETTINGS.execution.log_saturations,
         max_miss_ratio: *POS_MISS_RATE_DEACTIVATION_THRESHOLD,
         max_datastore_key_length: MAX_DATASTORE_KEY_LENGTH,
         max_bytecode_size: MAX_BYTECODE_LENGTH,
+        max_call_stack_depth: MAX_CALL_STACK_DEPTH,
+        max_event_count_per_call: MAX_EVENT_COUNT_PER_CALL,
+        max_event_size_per_call: MAX_EVENT_SIZE_PER_CALL,
+        max_event_count_per_slot: MAX_EVENT_COUNT_PER_SLOT,
+        max_event_size_per_slot: MAX_EVENT_SIZE_PER_SLOT,
         max_datastore_value_size: MAX_DATASTORE_VALUE_LENGTH,
         storage_costs_constants,
+        gas_costs_hash: gas_costs.compute_hash(),
+        gas_costs,
+        storage_rent_per_byte_per_cycle: STORAGE_RENT_PER_BYTE_PER_CYCLE,
+        light_mode: SETTINGS.protocol.light_mode,
+        header_fast_relay: SETTINGS.protocol.header_fast_relay,
+        block_body_ask_timeout: SETTINGS.protocol.block_body_ask_timeout,
+        block_body_retry_delay: SETTINGS.protocol.block_body_retry_delay,
+        max_block_body_sources: SETTINGS.protocol.max_block_body_sources,
+        max_simultaneous_ask_block_bodies_per_node: SETTINGS
+            .protocol
+            .max_simultaneous_ask_block_bodies_per_node,
+        max_slot_execution_time: SETTINGS.execution.max_slot_execution_time,
+        name_registry_address: SETTINGS.execution.name_registry_address,
+        name_registry_max_gas: SETTINGS.execution.name_registry_max_gas,
     };
     let (execution_manager, execution_controller) = start_execution_worker(
         execution_config,
         final_state.clone(),
         selector_controller.clone(),
+        execution_channels.clone(),
     );
 
     // launch pool controller
@@ -340,12 +524,33 @@ async fn launch(
         roll_price: ROLL_PRICE,
         max_block_endorsement_count: ENDORSEMENT_COUNT,
         operation_validity_periods: OPERATION_VALIDITY_PERIODS,
+        max_operation_future_validity_start_periods: SETTINGS
+            .pool
+            .max_operation_future_validity_start_periods,
+        far_future_operation_policy: SETTINGS.pool.far_future_operation_policy,
+        max_parked_operations: SETTINGS.pool.max_parked_operations,
         max_operation_pool_size_per_thread: SETTINGS.pool.max_pool_size_per_thread,
         max_endorsements_pool_size_per_thread: SETTINGS.pool.max_pool_size_per_thread,
+        max_endorsement_equivocation_records: SETTINGS.pool.max_endorsement_equivocation_records,
+        max_operation_eviction_records: SETTINGS.pool.max_operation_eviction_records,
+        min_operation_fee: SETTINGS.pool.min_operation_fee,
+        verify_operation_signatures: SETTINGS.pool.verify_operation_signatures,
+        chain_id: SETTINGS.execution.chain_id,
+        chain_id_activation_period: SETTINGS.execution.chain_id_activation_period,
+        check_operation_creator_balance: SETTINGS.pool.check_operation_creator_balance,
+        prioritize_local_operations: SETTINGS.pool.prioritize_local_operations,
         channels_size: POOL_CONTROLLER_CHANNEL_SIZE,
     };
-    let (pool_manager, pool_controller) =
-        start_pool_controller(pool_config, &shared_storage, execution_controller.clone());
+    let (pool_manager, pool_controller) = start_pool_controller(
+        pool_config,
+        &shared_storage.clone_for_owner("pool"),
+        execution_controller.clone(),
+    );
+    let pool_persistence = PoolPersistence::load(
+        SETTINGS.pool.operations_file.clone(),
+        pool_controller.clone(),
+        &shared_storage,
+    );
 
     // launch protocol controller
     let protocol_config = ProtocolConfig {
@@ -368,8 +573,13 @@ async fn launch(
             .operation_announcement_buffer_capacity,
         operation_batch_proc_period: SETTINGS.protocol.operation_batch_proc_period,
         asked_operations_pruning_period: SETTINGS.protocol.asked_operations_pruning_period,
+        operation_ask_timeout: SETTINGS.protocol.operation_ask_timeout,
+        max_operation_ask_retries: SETTINGS.protocol.max_operation_ask_retries,
         operation_announcement_interval: SETTINGS.protocol.operation_announcement_interval,
         max_operations_per_message: SETTINGS.protocol.max_operations_per_message,
+        parallel_sig_verification_threshold: SETTINGS.protocol.parallel_sig_verification_threshold,
+        chain_id: SETTINGS.execution.chain_id,
+        chain_id_activation_period: SETTINGS.execution.chain_id_activation_period,
         max_serialized_operations_size_per_block: MAX_BLOCK_SIZE as usize,
         controller_channel_size: PROTOCOL_CONTROLLER_CHANNEL_SIZE,
         event_channel_size: PROTOCOL_EVENT_CHANNEL_SIZE,
@@ -377,6 +587,10 @@ async fn launch(
         t0: T0,
         max_operations_propagation_time: SETTINGS.protocol.max_operations_propagation_time,
         max_endorsements_propagation_time: SETTINGS.protocol.max_endorsements_propagation_time,
+        max_queued_block_events: SETTINGS.protocol.max_queued_block_events,
+        max_queued_endorsement_events: SETTINGS.protocol.max_queued_endorsement_events,
+        max_queued_operation_events: SETTINGS.protocol.max_queued_operation_events,
+        light_mode: SETTINGS.protocol.light_mode,
     };
     let (protocol_command_sender, protocol_event_receiver, protocol_manager) =
         start_protocol_controller(
@@ -384,7 +598,7 @@ async fn launch(
             network_command_sender.clone(),
             network_event_receiver,
             pool_controller.clone(),
-            shared_storage.clone(),
+            shared_storage.clone_for_owner("protocol"),
         )
         .await
         .expect("could not start protocol controller");
@@ -409,9 +623,20 @@ async fn launch(
         endorsement_count: ENDORSEMENT_COUNT,
         block_db_prune_interval: SETTINGS.consensus.block_db_prune_interval,
         max_item_return_count: SETTINGS.consensus.max_item_return_count,
+        max_reorg_history: SETTINGS.consensus.max_reorg_history,
         max_gas_per_block: MAX_GAS_PER_BLOCK,
         channel_size: CHANNEL_SIZE,
+        graph_store_path: SETTINGS.consensus.graph_store_path.clone(),
+        confirmation_weight_thresholds: SETTINGS.consensus.confirmation_weight_thresholds.clone(),
+    };
+    // channels broadcasting block updates to the API subscribers
+    let consensus_broadcasts = ConsensusBroadcasts {
+        blockclique_block_sender: broadcast::channel(BROADCAST_CHANNEL_SIZE).0,
+        final_block_sender: broadcast::channel(BROADCAST_CHANNEL_SIZE).0,
+        reorg_sender: broadcast::channel(BROADCAST_CHANNEL_SIZE).0,
+        confirmation_sender: broadcast::channel(BROADCAST_CHANNEL_SIZE).0,
     };
+
     // launch consensus controller
     let (consensus_command_sender, consensus_event_receiver, consensus_manager) =
         start_consensus_controller(
@@ -422,32 +647,81 @@ async fn launch(
                 protocol_event_receiver,
                 pool_command_sender: pool_controller.clone(),
                 selector_controller: selector_controller.clone(),
+                broadcasts: consensus_broadcasts.clone(),
+                disk_status: disk_status.clone(),
             },
             bootstrap_state.graph,
-            shared_storage.clone(),
-            bootstrap_state.compensation_millis,
+            shared_storage.clone_for_owner("consensus"),
+            clock_compensation.clone(),
         )
         .await
         .expect("could not start consensus controller");
 
+    // launch the periodic state snapshots
+    let state_snapshot_manager = start_state_snapshot_worker(
+        StateSnapshotConfig {
+            path: SETTINGS.state_snapshot.path.clone(),
+            interval: SETTINGS.state_snapshot.interval,
+        },
+        final_state.clone(),
+        consensus_command_sender.clone(),
+        disk_status.clone(),
+    );
+
+    // launch the clock drift checks
+    let time_health_manager = start_time_health_worker(
+        TimeHealthConfig {
+            interval: SETTINGS.time_health.interval,
+            max_drift: SETTINGS.time_health.max_drift,
+            min_peer_samples: SETTINGS.time_health.min_peer_samples,
+            ntp_server: SETTINGS.time_health.ntp_server.clone(),
+            auto_adjust: SETTINGS.time_health.auto_adjust,
+            max_adjustment: SETTINGS.time_health.max_adjustment,
+        },
+        clock_compensation.clone(),
+        network_command_sender.clone(),
+    );
+
     // launch factory
     let factory_config = FactoryConfig {
         thread_count: THREAD_COUNT,
         genesis_timestamp: *GENESIS_TIMESTAMP,
         t0: T0,
-        clock_compensation_millis: bootstrap_state.compensation_millis,
+        clock_compensation: clock_compensation.clone(),
         initial_delay: SETTINGS.factory.initial_delay,
         max_block_size: MAX_BLOCK_SIZE as u64,
         max_block_gas: MAX_GAS_PER_BLOCK,
+        roll_price: ROLL_PRICE,
+        roll_compounding: SETTINGS.factory.roll_compounding,
+        roll_compounding_reserve: SETTINGS.factory.roll_compounding_reserve,
+        roll_operation_fee: SETTINGS.factory.roll_operation_fee,
+        roll_compounding_max_rolls_per_operation: SETTINGS
+            .factory
+            .roll_compounding_max_rolls_per_operation,
+        roll_operation_validity_periods: SETTINGS.factory.roll_operation_validity_periods,
+        operation_packing: SETTINGS.factory.operation_packing,
+        operation_packing_lookahead: SETTINGS.factory.operation_packing_lookahead,
+        endorsement_presign_slots: SETTINGS.factory.endorsement_presign_slots,
+        execution_policy: ExecutionPolicy {
+            mode: SETTINGS.factory.execution_policy,
+            addresses: SETTINGS
+                .factory
+                .execution_policy_addresses
+                .iter()
+                .copied()
+                .collect(),
+        },
     };
     let factory_channels = FactoryChannels {
         selector: selector_controller.clone(),
         consensus: consensus_command_sender.clone(),
         pool: pool_controller.clone(),
         protocol: protocol_command_sender.clone(),
-        storage: shared_storage.clone(),
+        execution: execution_controller.clone(),
+        storage: shared_storage.clone_for_owner("factory"),
     };
-    let factory_manager = start_factory(factory_config, node_wallet.clone(), factory_channels);
+    let (factory_manager, factory_controller) =
+        start_factory(factory_config, node_wallet.clone(), factory_channels);
 
     // launch bootstrap server
     let bootstrap_manager = start_bootstrap_server(
@@ -457,17 +731,28 @@ async fn launch(
         bootstrap_config,
         massa_bootstrap::Establisher::new(),
         private_key,
-        bootstrap_state.compensation_millis,
+        clock_compensation.clone(),
         *VERSION,
     )
     .await
     .unwrap();
+    let bootstrap_server_stats = bootstrap_manager
+        .as_ref()
+        .map(|bootstrap_manager| bootstrap_manager.get_stats());
 
     let api_config: APIConfig = APIConfig {
         bind_private: SETTINGS.api.bind_private,
         bind_public: SETTINGS.api.bind_public,
         draw_lookahead_period_count: SETTINGS.api.draw_lookahead_period_count,
+        endorsement_diagnostics_lookback_period_count: SETTINGS
+            .api
+            .endorsement_diagnostics_lookback_period_count,
         max_arguments: SETTINGS.api.max_arguments,
+        max_datastore_keys_per_page: SETTINGS.api.max_datastore_keys_per_page,
+        max_contracts_per_page: SETTINGS.api.max_contracts_per_page,
+        max_deferred_credits_per_page: SETTINGS.api.max_deferred_credits_per_page,
+        max_address_activities_per_page: SETTINGS.api.max_address_activities_per_page,
+        max_cycle_stakers_per_page: SETTINGS.api.max_cycle_stakers_per_page,
         openrpc_spec_path: SETTINGS.api.openrpc_spec_path.clone(),
         max_datastore_value_length: MAX_DATASTORE_VALUE_LENGTH,
         max_op_datastore_entry_count: MAX_OPERATION_DATASTORE_ENTRY_COUNT,
@@ -475,35 +760,69 @@ async fn launch(
         max_op_datastore_value_length: MAX_OPERATION_DATASTORE_VALUE_LENGTH,
         max_function_name_length: MAX_FUNCTION_NAME_LENGTH,
         max_parameter_size: MAX_PARAMETERS_SIZE,
+        max_ws_subscriptions: SETTINGS.api.max_ws_subscriptions,
+        light_mode: SETTINGS.protocol.light_mode,
+        max_miss_ratio: *POS_MISS_RATE_DEACTIVATION_THRESHOLD,
+        chain_id: SETTINGS.execution.chain_id,
+        chain_id_activation_period: SETTINGS.execution.chain_id_activation_period,
+        checksummed_addresses: SETTINGS.api.checksummed_addresses,
     };
     // spawn private API
-    let (api_private, api_private_stop_rx) = API::<Private>::new(
+    let (api_private, api_private_stop_rx, api_private_subsystem_rx) = API::<Private>::new(
         consensus_command_sender.clone(),
         network_command_sender.clone(),
         execution_controller.clone(),
+        factory_controller,
+        selector_controller.clone(),
         api_config.clone(),
         consensus_config.clone(),
         node_wallet,
+        final_state.clone(),
     );
     let api_private_handle = api_private.serve(&SETTINGS.api.bind_private);
 
-    // spawn public API
-    let api_public = API::<Public>::new(
-        consensus_command_sender.clone(),
-        execution_controller.clone(),
-        api_config,
-        selector_controller.clone(),
-        consensus_config,
-        pool_controller.clone(),
-        protocol_command_sender.clone(),
-        network_config,
-        *VERSION,
-        network_command_sender.clone(),
-        bootstrap_state.compensation_millis,
-        node_id,
-        shared_storage.clone(),
-    );
-    let api_public_handle = api_public.serve(&SETTINGS.api.bind_public);
+    // spawn public API, keeping the means to restart it on request of the private API
+    let public_api_launcher: Box<dyn Fn() -> StopHandle> = {
+        let consensus_command_sender = consensus_command_sender.clone();
+        let execution_controller = execution_controller.clone();
+        let api_config = api_config.clone();
+        let selector_controller = selector_controller.clone();
+        let pool_controller = pool_controller.clone();
+        let protocol_command_sender = protocol_command_sender.clone();
+        let network_command_sender = network_command_sender.clone();
+        let shared_storage = shared_storage.clone();
+        Box::new(move || {
+            let api_public = API::<Public>::new(
+                consensus_command_sender.clone(),
+                execution_controller.clone(),
+                api_config.clone(),
+                selector_controller.clone(),
+                consensus_config.clone(),
+                pool_controller.clone(),
+                protocol_command_sender.clone(),
+                network_config.clone(),
+                *VERSION,
+                network_command_sender.clone(),
+                clock_compensation.clone(),
+                node_id,
+                shared_storage.clone(),
+                disk_status.clone(),
+                ledger_integrity.clone(),
+                bootstrap_server_stats.clone(),
+            );
+            api_public.serve(&SETTINGS.api.bind_public)
+        })
+    };
+    let api_public_handle = public_api_launcher();
+
+    // spawn WebSocket subscriptions API
+    let api_ws_handle = if SETTINGS.api.enable_ws {
+        let api_ws =
+            API::<Subscriptions>::new(consensus_broadcasts, execution_channels, api_config);
+        Some(api_ws.serve_ws(&SETTINGS.api.bind_ws))
+    } else {
+        None
+    };
 
     #[cfg(feature = "deadlock_detection")]
     {
@@ -544,9 +863,17 @@ async fn launch(
         protocol_manager,
         network_manager,
         factory_manager,
+        disk_monitor_manager,
+        ledger_scrubber_manager,
+        state_snapshot_manager,
+        time_health_manager,
+        pool_persistence,
         api_private_stop_rx,
+        api_private_subsystem_rx,
         api_private_handle,
         api_public_handle,
+        public_api_launcher,
+        api_ws_handle,
     )
 }
 
@@ -559,12 +886,17 @@ struct Managers {
     protocol_manager: ProtocolManager,
     network_manager: NetworkManager,
     factory_manager: Box<dyn FactoryManager>,
+    disk_monitor_manager: DiskMonitorManager,
+    ledger_scrubber_manager: LedgerScrubberManager,
+    state_snapshot_manager: StateSnapshotManager,
+    time_health_manager: TimeHealthManager,
+    pool_persistence: PoolPersistence,
 }
 
 async fn stop(
     consensus_event_receiver: ConsensusEventReceiver,
     Managers {
-        bootstrap_manager,
+        mut bootstrap_manager,
         mut execution_manager,
         consensus_manager,
         mut selector_manager,
@@ -572,27 +904,50 @@ async fn stop(
         protocol_manager,
         network_manager,
         mut factory_manager,
+        disk_monitor_manager,
+        ledger_scrubber_manager,
+        state_snapshot_manager,
+        time_health_manager,
+        pool_persistence,
     }: Managers,
     api_private_handle: StopHandle,
-    api_public_handle: StopHandle,
+    mut api_public_handle: Option<StopHandle>,
+    mut api_ws_handle: Option<StopHandle>,
+    save_snapshot: bool,
+    graceful: bool,
 ) {
-    // stop bootstrap
-    if let Some(bootstrap_manager) = bootstrap_manager {
-        bootstrap_manager
-            .stop()
-            .await
-            .expect("bootstrap server shutdown failed")
+    // on graceful stop, stop accepting new blocks and operations first
+    // so that the saved pool and state are not outdated right away
+    if graceful {
+        stop_intake(
+            &mut bootstrap_manager,
+            &mut api_ws_handle,
+            &mut api_public_handle,
+            factory_manager.as_mut(),
+        )
+        .await;
+        // save the pool operations while the pool is still running
+        pool_persistence.save();
     }
 
-    // stop public API
-    api_public_handle.stop();
+    // save a last state snapshot while consensus and execution are still running
+    state_snapshot_manager.stop(save_snapshot).await;
+
+    // stop the clock drift checks
+    time_health_manager.stop().await;
+
+    // stop bootstrap, APIs and factory, if not done yet
+    stop_intake(
+        &mut bootstrap_manager,
+        &mut api_ws_handle,
+        &mut api_public_handle,
+        factory_manager.as_mut(),
+    )
+    .await;
 
     // stop private API
     api_private_handle.stop();
 
-    // stop factory
-    factory_manager.stop();
-
     let protocol_event_receiver = consensus_manager
         .stop(consensus_event_receiver)
         .await
@@ -601,8 +956,13 @@ async fn stop(
     // stop pool
     pool_manager.stop();
 
-    // stop execution controller
-    execution_manager.stop();
+    // stop execution controller, executing the pending final slots and flushing the ledger first
+    // on graceful stop. Consensus is stopped at this point so no more blocks are finalized.
+    if graceful {
+        execution_manager.stop_gracefully();
+    } else {
+        execution_manager.stop();
+    }
 
     // stop selector controller
     selector_manager.stop();
@@ -623,14 +983,103 @@ async fn stop(
         .await
         .expect("network shutdown failed");
 
+    // stop disk monitor
+    disk_monitor_manager.stop();
+
+    // stop ledger scrubber
+    ledger_scrubber_manager.stop();
+
     // note that FinalLedger gets destroyed as soon as its Arc count goes to zero
 }
 
+/// Stop the components through which new blocks and operations enter the node:
+/// bootstrap server, public and WebSocket APIs, and block and endorsement production.
+/// Components already stopped are skipped.
+async fn stop_intake(
+    bootstrap_manager: &mut Option<BootstrapManager>,
+    api_ws_handle: &mut Option<StopHandle>,
+    api_public_handle: &mut Option<StopHandle>,
+    factory_manager: &mut dyn FactoryManager,
+) {
+    // stop bootstrap
+    if let Some(bootstrap_manager) = bootstrap_manager.take() {
+        bootstrap_manager
+            .stop()
+            .await
+            .expect("bootstrap server shutdown failed")
+    }
+
+    // stop WebSocket subscriptions API
+    if let Some(api_ws_handle) = api_ws_handle.take() {
+        api_ws_handle.stop();
+    }
+
+    // stop public API, unless it was stopped from the private API
+    if let Some(api_public_handle) = api_public_handle.take() {
+        api_public_handle.stop();
+    }
+
+    // stop factory
+    factory_manager.stop();
+}
+
+/// Apply an action requested from the private API to an individual subsystem of the node
+fn manage_subsystem(
+    subsystem: NodeSubsystem,
+    action: SubsystemAction,
+    factory_manager: &mut dyn FactoryManager,
+    api_public_handle: &mut Option<StopHandle>,
+    public_api_launcher: &dyn Fn() -> StopHandle,
+) -> Result<(), String> {
+    match (subsystem, action) {
+        (NodeSubsystem::Production, SubsystemAction::Stop) => factory_manager.stop_production(),
+        (NodeSubsystem::Production, SubsystemAction::Restart) => {
+            factory_manager.restart_production()
+        }
+        (NodeSubsystem::Production, SubsystemAction::Pause) => {
+            factory_manager.set_production_paused(true)
+        }
+        (NodeSubsystem::Production, SubsystemAction::Resume) => {
+            factory_manager.set_production_paused(false)
+        }
+        (NodeSubsystem::PublicApi, SubsystemAction::Stop) => match api_public_handle.take() {
+            Some(handle) => handle.stop(),
+            None => return Err("the public API is already stopped".into()),
+        },
+        (NodeSubsystem::PublicApi, SubsystemAction::Restart) => {
+            if let Some(handle) = api_public_handle.take() {
+                handle.stop();
+            }
+            *api_public_handle = Some(public_api_launcher());
+        }
+        (NodeSubsystem::PublicApi, SubsystemAction::Pause | SubsystemAction::Resume) => {
+            return Err(format!(
+                "the {} can only be stopped or restarted",
+                subsystem
+            ))
+        }
+    }
+    Ok(())
+}
+
 #[derive(StructOpt)]
 struct Args {
     /// Wallet password
     #[structopt(short = "p", long = "pwd")]
     password: Option<String>,
+    /// Ledger snapshot to import at startup, as exported by `node_export_ledger_snapshot`.
+    /// A full bootstrap replaces it by the ledger of the bootstrap server.
+    #[structopt(long = "import-ledger-snapshot", parse(from_os_str))]
+    import_ledger_snapshot: Option<PathBuf>,
+    /// Restart from the state snapshot saved on disk instead of bootstrapping
+    #[structopt(long = "resume-from-disk", conflicts_with = "import-ledger-snapshot")]
+    resume_from_disk: bool,
+    /// Check the ledger imported or resumed at startup against its hash and Merkle tree
+    #[structopt(long = "verify-ledger-integrity")]
+    verify_ledger_integrity: bool,
+    /// Check the ledger imported or resumed at startup, and rebuild its hash and Merkle tree if they do not match its entries
+    #[structopt(long = "repair-ledger")]
+    repair_ledger: bool,
 }
 
 /// Load wallet, asking for passwords if necessary
@@ -705,6 +1154,9 @@ async fn run(args: Args) -> anyhow::Result<()> {
     // load or create wallet, asking for password if necessary
     let node_wallet = load_wallet(args.password, &SETTINGS.factory.staking_wallet_path)?;
 
+    // the snapshots are only loaded at startup, a restart bootstraps again
+    let mut ledger_snapshot_path = args.import_ledger_snapshot;
+    let mut resume_from_disk = args.resume_from_disk;
     loop {
         let (
             mut consensus_event_receiver,
@@ -715,17 +1167,34 @@ async fn run(args: Args) -> anyhow::Result<()> {
             pool_manager,
             protocol_manager,
             network_manager,
-            factory_manager,
+            mut factory_manager,
+            disk_monitor_manager,
+            ledger_scrubber_manager,
+            state_snapshot_manager,
+            time_health_manager,
+            pool_persistence,
             mut api_private_stop_rx,
+            mut api_private_subsystem_rx,
             api_private_handle,
             api_public_handle,
-        ) = launch(node_wallet.clone()).await;
+            public_api_launcher,
+            api_ws_handle,
+        ) = launch(
+            node_wallet.clone(),
+            ledger_snapshot_path.take(),
+            resume_from_disk,
+            args.verify_ledger_integrity,
+            args.repair_ledger,
+        )
+        .await;
+        resume_from_disk = false;
+        let mut api_public_handle = Some(api_public_handle);
 
         // interrupt signal listener
         let stop_signal = signal::ctrl_c();
         tokio::pin!(stop_signal);
-        // loop over messages
-        let restart = loop {
+        // loop over messages, until a restart is needed or a (graceful) stop is requested
+        let (restart, graceful) = loop {
             massa_trace!("massa-node.main.run.select", {});
             tokio::select! {
                 evt = consensus_event_receiver.wait_event() => {
@@ -733,11 +1202,18 @@ async fn run(args: Args) -> anyhow::Result<()> {
                     match evt {
                         Ok(ConsensusEvent::NeedSync) => {
                             warn!("in response to a desynchronization, the node is going to bootstrap again");
-                            break true;
+                            break (true, false);
+                        },
+                        Ok(ConsensusEvent::Reorg(reorg)) => {
+                            info!(
+                                "blockclique re-organization of depth {}: {} blocks dropped",
+                                reorg.depth,
+                                reorg.dropped_blocks.len()
+                            );
                         },
                         Err(err) => {
                             error!("consensus_event_receiver.wait_event error: {}", err);
-                            break false;
+                            break (false, false);
                         }
                     }
                 },
@@ -745,12 +1221,29 @@ async fn run(args: Args) -> anyhow::Result<()> {
                 _ = &mut stop_signal => {
                     massa_trace!("massa-node.main.run.select.stop", {});
                     info!("interrupt signal received");
-                    break false;
+                    break (false, false);
+                }
+
+                graceful = api_private_stop_rx.recv() => {
+                    let graceful = graceful.unwrap_or(false);
+                    if graceful {
+                        info!("graceful stop command received from private API");
+                    } else {
+                        info!("stop command received from private API");
+                    }
+                    break (false, graceful);
                 }
 
-                _ = api_private_stop_rx.recv() => {
-                    info!("stop command received from private API");
-                    break false;
+                Some((subsystem, action, response_tx)) = api_private_subsystem_rx.recv() => {
+                    info!("{} {} command received from private API", subsystem, action);
+                    let res = manage_subsystem(
+                        subsystem,
+                        action,
+                        factory_manager.as_mut(),
+                        &mut api_public_handle,
+                        public_api_launcher.as_ref(),
+                    );
+                    let _ = response_tx.send(res);
                 }
             }
         };
@@ -765,9 +1258,18 @@ async fn run(args: Args) -> anyhow::Result<()> {
                 protocol_manager,
                 network_manager,
                 factory_manager,
+                disk_monitor_manager,
+                ledger_scrubber_manager,
+                state_snapshot_manager,
+                time_health_manager,
+                pool_persistence,
             },
             api_private_handle,
             api_public_handle,
+            api_ws_handle,
+            // a desynchronized state is not worth resuming
+            !restart,
+            graceful,
         )
         .await;
 
@@ -0,0 +1,142 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Persistence of the operation pool across graceful shutdowns.
+//!
+//! On graceful shutdown, the operations of the pool are written to a file, which is read and
+//! removed at the next start: the operations that are still valid then go back to the pool
+//! instead of having to be sent again by their creators.
+
+use crate::settings::SETTINGS;
+use massa_final_state::{read_snapshot_file, write_snapshot_file};
+use massa_models::config::constants::{
+    MAX_DATASTORE_VALUE_LENGTH, MAX_FUNCTION_NAME_LENGTH, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+    MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE,
+};
+use massa_models::operation::{
+    OperationOrigin, OperationsDeserializer, OperationsSerializer, WrappedOperation,
+};
+use massa_pool_exports::PoolController;
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use massa_storage::Storage;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Kind of the pool operations file
+const POOL_OPERATIONS_FILE_KIND: &str = "pool";
+
+/// Saves the operations of the pool on graceful shutdown
+pub struct PoolPersistence {
+    /// file of the saved operations
+    path: PathBuf,
+    pool_controller: Box<dyn PoolController>,
+}
+
+impl PoolPersistence {
+    /// Adds the operations saved at `path` by a previous graceful shutdown to the pool, if any,
+    /// and removes the file
+    pub fn load(
+        path: PathBuf,
+        mut pool_controller: Box<dyn PoolController>,
+        storage: &Storage,
+    ) -> PoolPersistence {
+        if path.exists() {
+            match read_pool_operations(&path) {
+                Ok(operations) => {
+                    info!(
+                        "{} operations saved on shutdown added back to the pool",
+                        operations.len()
+                    );
+                    let mut op_storage = storage.clone_without_refs();
+                    op_storage.store_operations(operations);
+                    // the origin of the operations is not saved: they are not prioritized anymore
+                    pool_controller.add_operations(op_storage, OperationOrigin::Network);
+                }
+                Err(err) => warn!(
+                    "could not read the pool operations {}: {}",
+                    path.display(),
+                    err
+                ),
+            }
+            // the file is not read again at the next start, even if it is invalid
+            if let Err(err) = std::fs::remove_file(&path) {
+                warn!(
+                    "could not remove the pool operations {}: {}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+        PoolPersistence {
+            path,
+            pool_controller,
+        }
+    }
+
+    /// Writes the operations of the pool to the file, to be added back at the next start
+    pub fn save(self) {
+        match self.write_pool_operations() {
+            Ok(count) => info!("{} pool operations saved in {}", count, self.path.display()),
+            Err(err) => warn!(
+                "could not save the pool operations in {}: {}",
+                self.path.display(),
+                err
+            ),
+        }
+    }
+
+    /// Writes the operations of the pool to the file
+    ///
+    /// # Returns
+    /// The number of saved operations
+    fn write_pool_operations(&self) -> Result<usize, String> {
+        let storage = self.pool_controller.get_operations();
+        let operations: Vec<WrappedOperation> = {
+            let ops = storage.read_operations();
+            storage
+                .get_op_refs()
+                .iter()
+                .filter_map(|id| ops.get(id).cloned())
+                .collect()
+        };
+        let mut bytes = Vec::new();
+        OperationsSerializer::new()
+            .serialize(&operations, &mut bytes)
+            .map_err(|err| err.to_string())?;
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+        }
+        write_snapshot_file(&self.path, POOL_OPERATIONS_FILE_KIND, &bytes)
+            .map_err(|err| err.to_string())?;
+        Ok(operations.len())
+    }
+}
+
+/// Reads the operations written by `PoolPersistence::save`, dropping the ones whose signature is invalid
+fn read_pool_operations(path: &Path) -> Result<Vec<WrappedOperation>, String> {
+    let bytes =
+        read_snapshot_file(path, POOL_OPERATIONS_FILE_KIND).map_err(|err| err.to_string())?;
+    let (rest, operations) = OperationsDeserializer::new(
+        u32::MAX,
+        MAX_DATASTORE_VALUE_LENGTH,
+        MAX_FUNCTION_NAME_LENGTH,
+        MAX_PARAMETERS_SIZE,
+        MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+        MAX_OPERATION_DATASTORE_KEY_LENGTH,
+        MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+    )
+    .deserialize::<DeserializeError>(&bytes)
+    .map_err(|err| format!("could not deserialize the operations: {}", err))?;
+    if !rest.is_empty() {
+        return Err("trailing bytes after the operations".to_string());
+    }
+    Ok(operations
+        .into_iter()
+        .filter(|op| {
+            op.verify_operation_signature(
+                SETTINGS.execution.chain_id,
+                SETTINGS.execution.chain_id_activation_period,
+            )
+            .is_ok()
+        })
+        .collect())
+}
@@ -4,7 +4,10 @@
 use std::path::PathBuf;
 
 use enum_map::EnumMap;
+use massa_factory_exports::{ExecutionPolicyMode, OperationPackingAlgorithm};
 use massa_models::config::build_massa_settings;
+use massa_models::{address::Address, amount::Amount};
+use massa_pool_exports::FarFutureOperationPolicy;
 use massa_signature::PublicKey;
 use massa_time::MassaTime;
 use serde::Deserialize;
@@ -23,10 +26,23 @@ pub struct LoggingSettings {
 
 #[derive(Clone, Debug, Deserialize)]
 pub struct ExecutionSettings {
+    pub chain_id: u64,
+    pub chain_id_activation_period: u64,
     pub max_final_events: usize,
+    pub max_final_op_executions: usize,
+    pub max_final_address_activities: usize,
+    pub max_network_stats_slots: usize,
+    pub fee_recommendation_slots: usize,
     pub readonly_queue_length: usize,
+    pub readonly_cache_size: usize,
     pub cursor_delay: MassaTime,
+    pub max_candidate_read_depth: Option<usize>,
     pub stats_time_window_duration: MassaTime,
+    pub log_saturations: bool,
+    pub gas_costs_path: PathBuf,
+    pub max_slot_execution_time: MassaTime,
+    pub name_registry_address: Option<Address>,
+    pub name_registry_max_gas: u64,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -40,6 +56,33 @@ pub struct LedgerSettings {
     pub initial_ledger_path: PathBuf,
     pub disk_ledger_path: PathBuf,
     pub final_history_length: usize,
+    pub scrub_interval: MassaTime,
+    pub scrub_batch_size: usize,
+    pub executed_ops_disk_path: Option<PathBuf>,
+    pub executed_ops_bloom_filter_size: usize,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct StateSnapshotSettings {
+    pub path: PathBuf,
+    pub interval: MassaTime,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct DiskMonitorSettings {
+    pub check_interval: MassaTime,
+    pub warn_threshold: u64,
+    pub critical_threshold: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TimeHealthSettings {
+    pub interval: MassaTime,
+    pub max_drift: MassaTime,
+    pub min_peer_samples: usize,
+    pub ntp_server: Option<String>,
+    pub auto_adjust: bool,
+    pub max_adjustment: MassaTime,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -47,6 +90,9 @@ pub struct NetworkSettings {
     pub bind: SocketAddr,
     pub routable_ip: Option<IpAddr>,
     pub protocol_port: u16,
+    pub nat_traversal: bool,
+    pub nat_pmp_gateway: Option<IpAddr>,
+    pub nat_mapping_lease: MassaTime,
     pub connect_timeout: MassaTime,
     pub wakeup_interval: MassaTime,
     pub initial_peers_file: PathBuf,
@@ -57,6 +103,7 @@ pub struct NetworkSettings {
     pub max_idle_peers: usize,
     pub max_banned_peers: usize,
     pub peers_file_dump_interval: MassaTime,
+    pub peer_quality_half_life: MassaTime,
     pub message_timeout: MassaTime,
     pub ask_peer_list_interval: MassaTime,
     pub max_send_wait_node_event: MassaTime,
@@ -65,8 +112,13 @@ pub struct NetworkSettings {
     pub peer_list_send_timeout: MassaTime,
     pub max_in_connection_overflow: usize,
     pub max_operations_per_message: u32,
+    pub block_message_weight: u32,
     pub max_bytes_read: f64,
     pub max_bytes_write: f64,
+    pub max_bytes_read_whitelisted: f64,
+    pub max_bytes_write_whitelisted: f64,
+    pub max_total_bytes_read: f64,
+    pub max_total_bytes_write: f64,
 }
 
 /// Bootstrap configuration.
@@ -75,6 +127,7 @@ pub struct BootstrapSettings {
     pub bootstrap_list: Vec<(SocketAddr, PublicKey)>,
     pub bootstrap_whitelist_file: std::path::PathBuf,
     pub bootstrap_blacklist_file: std::path::PathBuf,
+    pub bootstrap_allowlist_file: std::path::PathBuf,
     pub bind: Option<SocketAddr>,
     pub connect_timeout: MassaTime,
     pub read_timeout: MassaTime,
@@ -88,7 +141,14 @@ pub struct BootstrapSettings {
     pub max_simultaneous_bootstraps: u32,
     pub per_ip_min_interval: MassaTime,
     pub ip_list_max_size: usize,
+    pub max_simultaneous_bootstraps_per_subnet: u32,
+    pub subnet_bucket_capacity: u32,
+    pub subnet_bucket_refill_interval: MassaTime,
+    pub ipv4_subnet_prefix_length: u8,
+    pub ipv6_subnet_prefix_length: u8,
     pub max_bytes_read_write: f64,
+    pub max_resync_slots: u64,
+    pub bootstrap_verification_sources: usize,
 }
 
 /// Factory settings
@@ -98,6 +158,26 @@ pub struct FactorySettings {
     pub initial_delay: MassaTime,
     /// Staking wallet file
     pub staking_wallet_path: PathBuf,
+    /// Automatically buy rolls with the coins accumulated by the staking addresses
+    pub roll_compounding: bool,
+    /// Coins kept on each staking address and never spent on rolls
+    pub roll_compounding_reserve: Amount,
+    /// Fee of the roll operations issued by the node
+    pub roll_operation_fee: Amount,
+    /// Maximal number of rolls bought by a single automatic roll buy operation
+    pub roll_compounding_max_rolls_per_operation: u64,
+    /// Number of periods the roll operations issued by the node remain valid
+    pub roll_operation_validity_periods: u64,
+    /// Algorithm selecting the operations of the produced blocks
+    pub operation_packing: OperationPackingAlgorithm,
+    /// Number of blocks worth of operations considered when packing a block
+    pub operation_packing_lookahead: u64,
+    /// Number of upcoming slots for which the endorsements of the node are signed in advance
+    pub endorsement_presign_slots: u64,
+    /// How `execution_policy_addresses` are used to filter the smart contract operations of the produced blocks
+    pub execution_policy: ExecutionPolicyMode,
+    /// Addresses of the node-local execution policy
+    pub execution_policy_addresses: Vec<Address>,
 }
 
 /// Pool configuration, read from a file configuration
@@ -105,18 +185,39 @@ pub struct FactorySettings {
 pub struct PoolSettings {
     pub max_pool_size_per_thread: usize,
     pub max_operation_future_validity_start_periods: u64,
+    pub far_future_operation_policy: FarFutureOperationPolicy,
+    pub max_parked_operations: usize,
     pub max_endorsement_count: u64,
     pub max_item_return_count: usize,
+    pub max_endorsement_equivocation_records: usize,
+    pub max_operation_eviction_records: usize,
+    pub min_operation_fee: Amount,
+    pub verify_operation_signatures: bool,
+    pub check_operation_creator_balance: bool,
+    pub prioritize_local_operations: bool,
+    pub operations_file: PathBuf,
 }
 
 /// API configuration, read from a file configuration
 #[derive(Debug, Deserialize, Clone)]
 pub struct APISettings {
     pub draw_lookahead_period_count: u64,
+    pub endorsement_diagnostics_lookback_period_count: u64,
     pub bind_private: SocketAddr,
     pub bind_public: SocketAddr,
     pub max_arguments: u64,
+    pub max_datastore_keys_per_page: u64,
+    pub max_contracts_per_page: u64,
+    pub max_deferred_credits_per_page: u64,
+    pub max_address_activities_per_page: u64,
+    pub max_cycle_stakers_per_page: u64,
     pub openrpc_spec_path: PathBuf,
+    pub enable_ws: bool,
+    pub bind_ws: SocketAddr,
+    pub max_ws_subscriptions: usize,
+    /// return addresses in the checksummed format instead of the base58 one in the version 2 API responses
+    #[serde(default)]
+    pub checksummed_addresses: bool,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -132,6 +233,9 @@ pub struct Settings {
     pub ledger: LedgerSettings,
     pub selector: SelectionSettings,
     pub factory: FactorySettings,
+    pub disk_monitor: DiskMonitorSettings,
+    pub state_snapshot: StateSnapshotSettings,
+    pub time_health: TimeHealthSettings,
 }
 
 /// Consensus configuration
@@ -156,6 +260,12 @@ pub struct ConsensusSettings {
     pub block_db_prune_interval: MassaTime,
     /// max number of items returned while querying
     pub max_item_return_count: usize,
+    /// max number of blockclique re-organizations kept in history
+    pub max_reorg_history: usize,
+    /// directory of the disk store of the active blocks, replayed at startup
+    pub graph_store_path: Option<PathBuf>,
+    /// confirmation weights at which a block is notified to the subscribers
+    pub confirmation_weight_thresholds: Vec<u64>,
 }
 
 /// Protocol Configuration, read from toml user configuration file
@@ -191,14 +301,46 @@ pub struct ProtocolSettings {
     pub operation_batch_proc_period: MassaTime,
     /// All operations asked are prune each `operation_asked_pruning_period` millisecond
     pub asked_operations_pruning_period: MassaTime,
+    /// Operations asked to a node and still missing after `operation_ask_timeout` milliseconds are asked to it again
+    pub operation_ask_timeout: MassaTime,
+    /// Maximum number of times missing operations are asked again to a node,
+    /// which is also the maximum number of times operations are sent again to a node asking for them
+    pub max_operation_ask_retries: u32,
     /// Interval at which operations are announced in batches.
     pub operation_announcement_interval: MassaTime,
     /// Maximum of operations sent in one message.
     pub max_operations_per_message: u64,
+    /// Minimal number of signatures in a received batch for them to be verified in parallel
+    pub parallel_sig_verification_threshold: usize,
     /// Time threshold after which operation are not propagated
     pub max_operations_propagation_time: MassaTime,
     /// Time threshold after which operation are not propagated
     pub max_endorsements_propagation_time: MassaTime,
+    /// Maximum number of queued block, header and connection network events.
+    /// Network events are not read anymore while it is reached.
+    pub max_queued_block_events: usize,
+    /// Maximum number of queued endorsement network events.
+    /// The oldest ones are dropped if overflow.
+    pub max_queued_endorsement_events: usize,
+    /// Maximum number of queued operation network events.
+    /// The oldest ones are dropped if overflow.
+    pub max_queued_operation_events: usize,
+    /// Light mode: only block headers and endorsements are downloaded and checked,
+    /// operations are neither fetched nor executed.
+    pub light_mode: bool,
+    /// Header fast relay: headers are relayed as soon as they are checked instead of once their
+    /// block is integrated, and block bodies are only asked to the first nodes known to have them
+    pub header_fast_relay: bool,
+    /// In fast relay mode, after `block_body_ask_timeout` milliseconds we try to ask the operation
+    /// list or the operations of a block to another node
+    pub block_body_ask_timeout: MassaTime,
+    /// In fast relay mode, a node that did not send a block body in time is asked for it again
+    /// after `block_body_retry_delay` milliseconds
+    pub block_body_retry_delay: MassaTime,
+    /// In fast relay mode, block bodies are only asked to `max_block_body_sources` nodes
+    pub max_block_body_sources: usize,
+    /// In fast relay mode, max number of block bodies asked simultaneously to a node
+    pub max_simultaneous_ask_block_bodies_per_node: usize,
 }
 
 #[cfg(test)]
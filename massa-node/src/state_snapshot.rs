@@ -0,0 +1,222 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Snapshots of the node state on the local disk.
+//!
+//! The final state and the final blocks of the consensus graph are saved periodically and on
+//! clean shutdown, so that a node started with `--resume-from-disk` can skip the network bootstrap.
+//! A snapshot is written to a temporary directory which then replaces the previous snapshot:
+//! an interrupted save never leaves a partial snapshot behind.
+//! No snapshot is saved while free disk space is critically low.
+
+use massa_consensus_exports::ConsensusCommandSender;
+use massa_final_state::{read_snapshot_file, write_snapshot_file, FinalState};
+use massa_graph::{
+    BootstrapableGraph, BootstrapableGraphDeserializer, BootstrapableGraphSerializer,
+};
+use massa_models::config::constants::{
+    ENDORSEMENT_COUNT, MAX_BOOTSTRAP_BLOCKS, MAX_DATASTORE_VALUE_LENGTH, MAX_FUNCTION_NAME_LENGTH,
+    MAX_OPERATIONS_PER_BLOCK, MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+    MAX_OPERATION_DATASTORE_KEY_LENGTH, MAX_OPERATION_DATASTORE_VALUE_LENGTH, MAX_PARAMETERS_SIZE,
+    THREAD_COUNT,
+};
+use massa_models::slot::Slot;
+use massa_models::stats::DiskSpaceStatus;
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use massa_time::MassaTime;
+use parking_lot::RwLock;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Name of the consensus graph file in a snapshot directory
+const GRAPH_SNAPSHOT_FILE: &str = "graph.snapshot";
+
+/// Kind of the consensus graph snapshot file
+const GRAPH_SNAPSHOT_KIND: &str = "graph";
+
+/// State snapshot configuration
+#[derive(Debug, Clone)]
+pub struct StateSnapshotConfig {
+    /// snapshot directory
+    pub path: PathBuf,
+    /// interval between two periodic snapshots, 0 to only save a snapshot on clean shutdown
+    pub interval: MassaTime,
+}
+
+/// Allows stopping the state snapshot task
+pub struct StateSnapshotManager {
+    /// sends whether a last snapshot must be saved before stopping
+    stop_tx: oneshot::Sender<bool>,
+    join_handle: JoinHandle<()>,
+}
+
+impl StateSnapshotManager {
+    /// stop the state snapshot task, saving a last snapshot if `save` is true
+    pub async fn stop(self, save: bool) {
+        info!("stopping state snapshots...");
+        let _ = self.stop_tx.send(save);
+        if self.join_handle.await.is_err() {
+            warn!("state snapshot task panicked");
+        }
+        info!("state snapshots stopped");
+    }
+}
+
+/// Starts the task saving the state snapshots
+pub fn start_state_snapshot_worker(
+    config: StateSnapshotConfig,
+    final_state: Arc<RwLock<FinalState>>,
+    consensus_command_sender: ConsensusCommandSender,
+    disk_status: Arc<RwLock<DiskSpaceStatus>>,
+) -> StateSnapshotManager {
+    let (stop_tx, mut stop_rx) = oneshot::channel::<bool>();
+    let join_handle = tokio::spawn(async move {
+        let save = loop {
+            if config.interval.to_millis() == 0 {
+                break (&mut stop_rx).await.unwrap_or(false);
+            }
+            tokio::select! {
+                save = &mut stop_rx => break save.unwrap_or(false),
+                _ = tokio::time::sleep(config.interval.to_duration()) => {
+                    save_state_snapshot(
+                        &config.path,
+                        &final_state,
+                        &consensus_command_sender,
+                        &disk_status,
+                    )
+                    .await;
+                }
+            }
+        };
+        if save {
+            save_state_snapshot(
+                &config.path,
+                &final_state,
+                &consensus_command_sender,
+                &disk_status,
+            )
+            .await;
+        }
+    });
+    StateSnapshotManager {
+        stop_tx,
+        join_handle,
+    }
+}
+
+/// Saves a snapshot unless free disk space is critically low, logging the outcome
+async fn save_state_snapshot(
+    path: &Path,
+    final_state: &Arc<RwLock<FinalState>>,
+    consensus_command_sender: &ConsensusCommandSender,
+    disk_status: &Arc<RwLock<DiskSpaceStatus>>,
+) {
+    if *disk_status.read() == DiskSpaceStatus::Critical {
+        warn!(
+            "state snapshot in {} skipped: free disk space is critically low",
+            path.display()
+        );
+        return;
+    }
+    match try_save_state_snapshot(path, final_state, consensus_command_sender).await {
+        Ok(slot) => info!(
+            "state snapshot saved at slot {} in {}",
+            slot,
+            path.display()
+        ),
+        Err(err) => warn!(
+            "could not save state snapshot in {}: {}",
+            path.display(),
+            err
+        ),
+    }
+}
+
+/// Saves the final state and then the consensus graph, whose final blocks are thus at least
+/// as recent as the final state, as during a bootstrap
+async fn try_save_state_snapshot(
+    path: &Path,
+    final_state: &Arc<RwLock<FinalState>>,
+    consensus_command_sender: &ConsensusCommandSender,
+) -> Result<Slot, String> {
+    let tmp_path = path.with_extension("tmp");
+    if tmp_path.exists() {
+        std::fs::remove_dir_all(&tmp_path).map_err(|err| err.to_string())?;
+    }
+
+    // the final state is only locked while it is copied, and then written without the lock
+    let state_path = tmp_path.clone();
+    let final_state = final_state.clone();
+    let slot = tokio::task::spawn_blocking(move || {
+        let snapshot = final_state.read().take_snapshot(&state_path)?;
+        snapshot.write()
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(|err| err.to_string())?;
+
+    let graph = consensus_command_sender
+        .get_bootstrap_state()
+        .await
+        .map_err(|err| err.to_string())?;
+    let mut graph_bytes = Vec::new();
+    BootstrapableGraphSerializer::new()
+        .serialize(&graph, &mut graph_bytes)
+        .map_err(|err| err.to_string())?;
+    write_snapshot_file(
+        &tmp_path.join(GRAPH_SNAPSHOT_FILE),
+        GRAPH_SNAPSHOT_KIND,
+        &graph_bytes,
+    )
+    .map_err(|err| err.to_string())?;
+
+    // replace the previous snapshot
+    if path.exists() {
+        std::fs::remove_dir_all(path).map_err(|err| err.to_string())?;
+    }
+    std::fs::rename(&tmp_path, path).map_err(|err| err.to_string())?;
+    Ok(slot)
+}
+
+/// Loads a snapshot saved by the state snapshot task into a freshly created final state
+///
+/// # Returns
+/// The consensus graph of the snapshot
+pub fn load_state_snapshot(
+    path: &Path,
+    final_state: &Arc<RwLock<FinalState>>,
+) -> Result<BootstrapableGraph, String> {
+    // check the graph before replacing the final state
+    let graph_bytes = read_snapshot_file(&path.join(GRAPH_SNAPSHOT_FILE), GRAPH_SNAPSHOT_KIND)
+        .map_err(|err| err.to_string())?;
+    let (rest, graph) = BootstrapableGraphDeserializer::new(
+        THREAD_COUNT,
+        ENDORSEMENT_COUNT,
+        MAX_BOOTSTRAP_BLOCKS,
+        MAX_DATASTORE_VALUE_LENGTH,
+        MAX_FUNCTION_NAME_LENGTH,
+        MAX_PARAMETERS_SIZE,
+        MAX_OPERATIONS_PER_BLOCK,
+        MAX_OPERATION_DATASTORE_ENTRY_COUNT,
+        MAX_OPERATION_DATASTORE_KEY_LENGTH,
+        MAX_OPERATION_DATASTORE_VALUE_LENGTH,
+    )
+    .deserialize::<DeserializeError>(&graph_bytes)
+    .map_err(|err| format!("could not deserialize the consensus graph: {}", err))?;
+    if !rest.is_empty() {
+        return Err("trailing bytes after the consensus graph".to_string());
+    }
+
+    let slot = final_state
+        .write()
+        .load_snapshot(path)
+        .map_err(|err| err.to_string())?;
+    info!(
+        "resumed from the state snapshot {} at slot {}",
+        path.display(),
+        slot
+    );
+    Ok(graph)
+}
@@ -0,0 +1,315 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Time health checks.
+//!
+//! The clock compensation set at bootstrap is re-evaluated periodically against the timestamps
+//! advertised by the peers during their handshake, and optionally against an NTP server.
+//! A drift above the threshold is logged and, if enabled, the shared `ClockCompensation`
+//! is adjusted so that all the modules follow the reference time.
+//!
+//! The adjustment is bounded around the compensation set at bootstrap: the offsets advertised
+//! by the peers are not authenticated, and a majority of malicious peers must not be able to
+//! shift the clock of the node arbitrarily.
+
+use massa_network_exports::NetworkCommandSender;
+use massa_time::{ClockCompensation, MassaTime, MonotonicClock};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Seconds between the NTP epoch (1900) and the unix epoch (1970)
+const NTP_UNIX_EPOCH_DELTA_SECS: u64 = 2_208_988_800;
+
+/// Maximum duration of an NTP query
+const NTP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time health configuration
+#[derive(Debug, Clone)]
+pub struct TimeHealthConfig {
+    /// interval between two checks, 0 to disable the checks
+    pub interval: MassaTime,
+    /// drift between the compensated time and the reference time above which a warning is logged
+    pub max_drift: MassaTime,
+    /// minimal number of connected peers for their clock offsets to be used as reference
+    pub min_peer_samples: usize,
+    /// optional NTP server (`host:port`), preferred to the peers when it answers
+    pub ntp_server: Option<String>,
+    /// whether the clock compensation is adjusted to the reference time
+    pub auto_adjust: bool,
+    /// maximal distance between the adjusted compensation and the compensation set at bootstrap
+    pub max_adjustment: MassaTime,
+}
+
+/// Allows stopping the time health task
+pub struct TimeHealthManager {
+    stop_tx: oneshot::Sender<()>,
+    join_handle: JoinHandle<()>,
+}
+
+impl TimeHealthManager {
+    /// stop the time health task
+    pub async fn stop(self) {
+        info!("stopping time health checks...");
+        let _ = self.stop_tx.send(());
+        if self.join_handle.await.is_err() {
+            warn!("time health task panicked");
+        }
+        info!("time health checks stopped");
+    }
+}
+
+/// Starts the task checking the clock drift
+pub fn start_time_health_worker(
+    config: TimeHealthConfig,
+    compensation: ClockCompensation,
+    network_command_sender: NetworkCommandSender,
+) -> TimeHealthManager {
+    let (stop_tx, mut stop_rx) = oneshot::channel::<()>();
+    let initial_compensation = compensation.get();
+    let join_handle = tokio::spawn(async move {
+        if config.interval.to_millis() == 0 {
+            let _ = stop_rx.await;
+            return;
+        }
+        let mut clock = match MonotonicClock::new() {
+            Ok(clock) => clock,
+            Err(err) => {
+                warn!("time health checks disabled: {}", err);
+                let _ = stop_rx.await;
+                return;
+            }
+        };
+        loop {
+            tokio::select! {
+                _ = &mut stop_rx => break,
+                _ = tokio::time::sleep(config.interval.to_duration()) => {
+                    check_time_health(
+                        &config,
+                        &compensation,
+                        initial_compensation,
+                        &network_command_sender,
+                        &mut clock,
+                    )
+                    .await;
+                }
+            }
+        }
+    });
+    TimeHealthManager {
+        stop_tx,
+        join_handle,
+    }
+}
+
+/// Compares the compensated time to the reference time and adjusts the compensation if needed
+async fn check_time_health(
+    config: &TimeHealthConfig,
+    compensation: &ClockCompensation,
+    initial_compensation: i64,
+    network_command_sender: &NetworkCommandSender,
+    clock: &mut MonotonicClock,
+) {
+    // detect the adjustments of the system time since the last check
+    match clock.system_time_shift() {
+        Ok(shift) if shift.unsigned_abs() > config.max_drift.to_millis() => {
+            warn!(
+                "the system time jumped by {} ms since the last check",
+                shift
+            )
+        }
+        Ok(_) => {}
+        Err(err) => warn!(
+            "could not compare the system time to the monotonic clock: {}",
+            err
+        ),
+    }
+    if let Ok(new_clock) = MonotonicClock::new() {
+        *clock = new_clock;
+    }
+
+    // offset of the reference time relatively to the local system time, in milliseconds
+    let target = match &config.ntp_server {
+        Some(server) => match ntp_offset(server).await {
+            Ok(offset) => Some(offset),
+            Err(err) => {
+                debug!("NTP query to {} failed: {}", server, err);
+                None
+            }
+        },
+        None => None,
+    };
+    let target = match target {
+        Some(offset) => Some(offset),
+        None => match network_command_sender.get_clock_offsets().await {
+            Ok(offsets) if offsets.len() >= config.min_peer_samples && !offsets.is_empty() => {
+                Some(median(offsets))
+            }
+            Ok(_) => None,
+            Err(err) => {
+                warn!("could not get the clock offsets of the peers: {}", err);
+                None
+            }
+        },
+    };
+    let target = match target {
+        Some(target) => target,
+        None => return,
+    };
+
+    let current = compensation.get();
+    let drift = target.saturating_sub(current);
+    if drift.unsigned_abs() > config.max_drift.to_millis() {
+        warn!(
+            "clock drift of {} ms detected (compensation {} ms, reference offset {} ms)",
+            drift, current, target
+        );
+    }
+    if config.auto_adjust {
+        let adjusted = bounded_compensation(
+            initial_compensation,
+            target,
+            config.max_adjustment.to_millis(),
+        );
+        if adjusted != target {
+            warn!(
+                "reference offset {} ms out of the allowed adjustment of {} ms around {} ms",
+                target, config.max_adjustment, initial_compensation
+            );
+        }
+        if adjusted != current {
+            debug!(
+                "clock compensation adjusted from {} ms to {} ms",
+                current, adjusted
+            );
+            compensation.set(adjusted);
+        }
+    }
+}
+
+/// Compensation closest to `target` that stays within `max_adjustment` milliseconds of `initial`
+fn bounded_compensation(initial: i64, target: i64, max_adjustment: u64) -> i64 {
+    let max_adjustment = i64::try_from(max_adjustment).unwrap_or(i64::MAX);
+    target.clamp(
+        initial.saturating_sub(max_adjustment),
+        initial.saturating_add(max_adjustment),
+    )
+}
+
+/// Median of a non-empty list of offsets
+fn median(mut offsets: Vec<i64>) -> i64 {
+    offsets.sort_unstable();
+    let mid = offsets.len() / 2;
+    if offsets.len() % 2 == 0 {
+        offsets[mid - 1] / 2 + offsets[mid] / 2
+    } else {
+        offsets[mid]
+    }
+}
+
+/// Offset of the time of the NTP `server` relatively to the local system time, in milliseconds.
+///
+/// A single SNTP request is sent and the server transmit time is compared to the middle of the
+/// request round trip.
+async fn ntp_offset(server: &str) -> std::io::Result<i64> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(server).await?;
+    // leap indicator 0, version 3, client mode
+    let mut request = [0u8; 48];
+    request[0] = 0x1b;
+    let sent = system_millis()?;
+    let mut response = [0u8; 48];
+    let read = tokio::time::timeout(NTP_TIMEOUT, async {
+        socket.send(&request).await?;
+        socket.recv(&mut response).await
+    })
+    .await
+    .map_err(|_| std::io::Error::from(std::io::ErrorKind::TimedOut))??;
+    let received = system_millis()?;
+    ntp_response_offset(&response[..read], sent, received)
+}
+
+/// Offset of the transmit time of an SNTP `response` relatively to the middle of the round trip
+/// between `sent` and `received`, local times in milliseconds since the unix epoch.
+fn ntp_response_offset(response: &[u8], sent: u64, received: u64) -> std::io::Result<i64> {
+    if response.len() < 48 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "truncated NTP response",
+        ));
+    }
+    // transmit timestamp: seconds and fraction since the NTP epoch
+    let secs = u32::from_be_bytes([response[40], response[41], response[42], response[43]]) as u64;
+    let frac = u32::from_be_bytes([response[44], response[45], response[46], response[47]]) as u64;
+    let server_millis = secs
+        .checked_sub(NTP_UNIX_EPOCH_DELTA_SECS)
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid NTP time"))?
+        * 1000
+        + ((frac * 1000) >> 32);
+    let local_millis = sent / 2 + received / 2;
+    Ok(server_millis as i64 - local_millis as i64)
+}
+
+/// Current system time in milliseconds since the unix epoch, not compensated
+fn system_millis() -> std::io::Result<u64> {
+    MassaTime::now(0)
+        .map(|time| time.to_millis())
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// SNTP response whose transmit timestamp is `millis` milliseconds after the unix epoch
+    fn ntp_response(millis: u64) -> [u8; 48] {
+        let mut response = [0u8; 48];
+        let secs = (millis / 1000 + NTP_UNIX_EPOCH_DELTA_SECS) as u32;
+        let frac = (((millis % 1000) << 32) / 1000) as u32 + 1;
+        response[40..44].copy_from_slice(&secs.to_be_bytes());
+        response[44..48].copy_from_slice(&frac.to_be_bytes());
+        response
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(vec![5]), 5);
+        assert_eq!(median(vec![30, -10, 20]), 20);
+        assert_eq!(median(vec![40, -10, 20, 0]), 10);
+        assert_eq!(median(vec![-7, -3]), -5);
+        // a minority of outliers does not move the median
+        assert_eq!(median(vec![0, 1, -1, i64::MAX, i64::MIN + 1, 2, 3]), 1);
+    }
+
+    #[test]
+    fn test_bounded_compensation() {
+        assert_eq!(bounded_compensation(100, 150, 100), 150);
+        assert_eq!(bounded_compensation(100, 50, 100), 50);
+        assert_eq!(bounded_compensation(100, 1_000_000, 100), 200);
+        assert_eq!(bounded_compensation(100, -1_000_000, 100), 0);
+        assert_eq!(bounded_compensation(100, 1_000_000, 0), 100);
+        assert_eq!(bounded_compensation(0, i64::MAX, u64::MAX), i64::MAX);
+    }
+
+    #[test]
+    fn test_ntp_response_offset() {
+        let local = 1_600_000_000_000;
+        // server ahead of the middle of the round trip
+        let response = ntp_response(local + 1_250);
+        assert_eq!(
+            ntp_response_offset(&response, local - 100, local + 100).unwrap(),
+            1_250
+        );
+        // server behind
+        let response = ntp_response(local - 3_500);
+        assert_eq!(
+            ntp_response_offset(&response, local, local).unwrap(),
+            -3_500
+        );
+        // truncated response
+        assert!(ntp_response_offset(&response[..40], local, local).is_err());
+        // transmit timestamp before the unix epoch
+        assert!(ntp_response_offset(&[0u8; 48], local, local).is_err());
+    }
+}
@@ -0,0 +1,22 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use massa_models::{address::Address, amount::Amount, operation::OperationId};
+
+/// Operation of the pool that can be included in a block, with the metadata needed to pack it
+#[derive(Debug, Clone)]
+pub struct BlockOperationCandidate {
+    /// id of the operation
+    pub id: OperationId,
+    /// serialized size of the operation, in bytes
+    pub size: usize,
+    /// max gas the operation may use
+    pub max_gas: u64,
+    /// total fee paid by the operation to the block producer
+    pub fee: Amount,
+    /// address of the creator of the operation
+    pub creator_address: Address,
+    /// max amount that the operation might spend from the balance of its creator, fee included
+    pub max_spending: Amount,
+    /// balance of the creator when the candidates were gathered
+    pub creator_balance: Amount,
+}
@@ -16,12 +16,46 @@ pub struct PoolConfig {
     pub roll_price: Amount,
     /// operation validity periods
     pub operation_validity_periods: u64,
+    /// max number of periods between the latest final period of a thread
+    /// and the start of the validity range of the operations accepted in that thread
+    pub max_operation_future_validity_start_periods: u64,
+    /// what to do with the operations whose validity starts further in the future
+    pub far_future_operation_policy: FarFutureOperationPolicy,
+    /// max number of far future operations held in the parking area (in number of operations)
+    pub max_parked_operations: usize,
     /// max operation pool size per thread (in number of operations)
     pub max_operation_pool_size_per_thread: usize,
     /// max endorsement pool size per thread (in number of endorsements)
     pub max_endorsements_pool_size_per_thread: usize,
     /// max number of endorsements per block
     pub max_block_endorsement_count: u32,
+    /// max number of endorsement equivocation records kept
+    pub max_endorsement_equivocation_records: usize,
+    /// max number of operation eviction records kept
+    pub max_operation_eviction_records: usize,
+    /// minimal fee of the operations accepted in the pool
+    pub min_operation_fee: Amount,
+    /// batch verify the signatures of the operations before accepting them in the pool
+    pub verify_operation_signatures: bool,
+    /// chain id of the network, signed along the operations
+    pub chain_id: u64,
+    /// first period from which the operations are signed along the chain id
+    pub chain_id_activation_period: u64,
+    /// check that the candidate balance of the creator of an operation covers its fee before accepting it in the pool
+    pub check_operation_creator_balance: bool,
+    /// include the operations submitted to the node before the ones propagated by the network when producing blocks
+    pub prioritize_local_operations: bool,
     /// operations and endorsements communication channels size
     pub channels_size: usize,
 }
+
+/// What the pool does with operations whose validity range starts too far in the future
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FarFutureOperationPolicy {
+    /// the operations are rejected
+    Reject,
+    /// the operations are held in a parking area of capped size,
+    /// and enter the pool once their validity start gets close enough
+    Park,
+}
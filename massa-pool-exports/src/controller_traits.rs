@@ -1,14 +1,22 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::{
-    block::BlockId, endorsement::EndorsementId, operation::OperationId, slot::Slot,
+    block::BlockId,
+    endorsement::{EndorsementEquivocation, EndorsementId},
+    operation::{
+        OperationId, OperationOrigin, OperationOriginCounts, OperationPoolStatus, WrappedOperation,
+    },
+    slot::Slot,
 };
 use massa_storage::Storage;
 
+use crate::{BlockOperationCandidate, OperationRejection};
+
 /// Trait defining a pool controller
 pub trait PoolController: Send + Sync {
     /// Asynchronously add operations to pool. Simply print a warning on failure.
-    fn add_operations(&mut self, ops: Storage);
+    /// `origin` tells how the operations reached the node.
+    fn add_operations(&mut self, ops: Storage, origin: OperationOrigin);
 
     /// Asynchronously add endorsements to pool. Simply print a warning on failure.
     fn add_endorsements(&mut self, endorsements: Storage);
@@ -19,6 +27,15 @@ pub trait PoolController: Send + Sync {
     /// Get operations for block creation.
     fn get_block_operations(&self, slot: &Slot) -> (Vec<OperationId>, Storage);
 
+    /// Get the operations that can be included in a block at the given slot, from best to worst fee per byte,
+    /// with the metadata needed to pack them. Operations are gathered until their cumulated size reaches `max_total_size`.
+    /// The returned storage holds references to all the candidates.
+    fn get_block_operation_candidates(
+        &self,
+        slot: &Slot,
+        max_total_size: usize,
+    ) -> (Vec<BlockOperationCandidate>, Storage);
+
     /// Get endorsements for a block.
     fn get_block_endorsements(
         &self,
@@ -32,12 +49,27 @@ pub trait PoolController: Send + Sync {
     /// Get the number of operations in the pool
     fn get_operation_count(&self) -> usize;
 
+    /// Get the number of operations in the pool per origin, including the parked ones
+    fn get_operation_origin_counts(&self) -> OperationOriginCounts;
+
+    /// Get a storage referencing all the operations of the pool, including the parked ones
+    fn get_operations(&self) -> Storage;
+
     /// Check if the pool contains a list of endorsements. Returns one boolean per item.
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool>;
 
     /// Check if the pool contains a list of operations. Returns one boolean per item.
     fn contains_operations(&self, operations: &[OperationId]) -> Vec<bool>;
 
+    /// Check whether the pool would accept a list of operations. Returns the reason of the rejection of each item, if any.
+    fn check_operations(&self, operations: &[WrappedOperation]) -> Vec<Option<OperationRejection>>;
+
+    /// Get the retained endorsement equivocation records, from oldest to newest.
+    fn get_endorsement_equivocations(&self) -> Vec<EndorsementEquivocation>;
+
+    /// Get the status of a list of operations in the pool, including their recent evictions. Returns one status per item.
+    fn get_operation_pool_status(&self, operations: &[OperationId]) -> Vec<OperationPoolStatus>;
+
     /// Returns a boxed clone of self.
     /// Useful to allow cloning `Box<dyn PoolController>`.
     fn clone_box(&self) -> Box<dyn PoolController>;
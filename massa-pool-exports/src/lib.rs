@@ -5,11 +5,15 @@
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
 
+mod candidate;
 mod config;
 mod controller_traits;
+mod rejection;
 
-pub use config::PoolConfig;
+pub use candidate::BlockOperationCandidate;
+pub use config::{FarFutureOperationPolicy, PoolConfig};
 pub use controller_traits::{PoolController, PoolManager};
+pub use rejection::OperationRejection;
 
 /// Test utils
 #[cfg(feature = "testing")]
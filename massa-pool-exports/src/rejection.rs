@@ -0,0 +1,101 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use massa_models::{amount::Amount, operation::OperationEvictionReason};
+use serde::{Deserialize, Serialize};
+
+/// Reason why the pool does not accept an operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OperationRejection {
+    /// the validity range of the operation ended at or before the latest final period of its thread
+    Expired {
+        /// last period of the validity range of the operation
+        expire_period: u64,
+        /// latest final period of the thread of the operation
+        last_final_period: u64,
+    },
+    /// the validity range of the operation starts too far in the future
+    TooFarInTheFuture {
+        /// first period of the validity range of the operation
+        validity_start_period: u64,
+        /// max validity start period currently accepted in the thread of the operation
+        max_validity_start_period: u64,
+    },
+    /// the fee of the operation is below the minimal fee accepted by the pool
+    FeeTooLow {
+        /// fee of the operation
+        fee: Amount,
+        /// minimal fee accepted by the pool
+        min_fee: Amount,
+    },
+    /// the signature of the operation is invalid
+    InvalidSignature,
+    /// the candidate balance of the creator of the operation can not cover its fee
+    InsufficientBalance {
+        /// fee of the operation
+        fee: Amount,
+        /// candidate balance of the creator of the operation
+        balance: Amount,
+    },
+}
+
+impl From<OperationRejection> for OperationEvictionReason {
+    fn from(rejection: OperationRejection) -> Self {
+        match rejection {
+            OperationRejection::Expired {
+                expire_period,
+                last_final_period,
+            } => OperationEvictionReason::Expired {
+                expire_period,
+                last_final_period,
+            },
+            OperationRejection::TooFarInTheFuture {
+                validity_start_period,
+                max_validity_start_period,
+            } => OperationEvictionReason::TooFarInTheFuture {
+                validity_start_period,
+                max_validity_start_period,
+            },
+            OperationRejection::FeeTooLow { fee, min_fee } => {
+                OperationEvictionReason::FeeTooLow { fee, min_fee }
+            }
+            OperationRejection::InvalidSignature => OperationEvictionReason::InvalidSignature,
+            OperationRejection::InsufficientBalance { fee, balance } => {
+                OperationEvictionReason::InsufficientBalance { fee, balance }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for OperationRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OperationRejection::Expired {
+                expire_period,
+                last_final_period,
+            } => write!(
+                f,
+                "operation expired at period {}, the latest final period being {}",
+                expire_period, last_final_period
+            ),
+            OperationRejection::TooFarInTheFuture {
+                validity_start_period,
+                max_validity_start_period,
+            } => write!(
+                f,
+                "operation validity starts at period {}, later than the max accepted period {}",
+                validity_start_period, max_validity_start_period
+            ),
+            OperationRejection::FeeTooLow { fee, min_fee } => write!(
+                f,
+                "operation fee of {} is below the minimal fee of {}",
+                fee, min_fee
+            ),
+            OperationRejection::InvalidSignature => write!(f, "operation signature is invalid"),
+            OperationRejection::InsufficientBalance { fee, balance } => write!(
+                f,
+                "operation creator balance of {} can not cover the fee of {}",
+                balance, fee
+            ),
+        }
+    }
+}
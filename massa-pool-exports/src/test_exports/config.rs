@@ -1,23 +1,35 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use massa_models::amount::Amount;
 use massa_models::config::{
     ENDORSEMENT_COUNT, MAX_BLOCK_SIZE, MAX_GAS_PER_BLOCK, OPERATION_VALIDITY_PERIODS, ROLL_PRICE,
-    THREAD_COUNT,
+    TESTNET_CHAIN_ID, THREAD_COUNT,
 };
 
-use crate::PoolConfig;
+use crate::{FarFutureOperationPolicy, PoolConfig};
 
 impl Default for PoolConfig {
     fn default() -> Self {
         Self {
             thread_count: THREAD_COUNT,
             operation_validity_periods: OPERATION_VALIDITY_PERIODS,
+            max_operation_future_validity_start_periods: 100,
+            far_future_operation_policy: FarFutureOperationPolicy::Reject,
+            max_parked_operations: 1000,
             max_block_gas: MAX_GAS_PER_BLOCK,
             roll_price: ROLL_PRICE,
             max_block_size: MAX_BLOCK_SIZE,
             max_operation_pool_size_per_thread: 1000,
             max_endorsements_pool_size_per_thread: 1000,
             max_block_endorsement_count: ENDORSEMENT_COUNT,
+            max_endorsement_equivocation_records: 1000,
+            max_operation_eviction_records: 1000,
+            min_operation_fee: Amount::zero(),
+            verify_operation_signatures: true,
+            chain_id: TESTNET_CHAIN_ID,
+            chain_id_activation_period: 0,
+            check_operation_creator_balance: false,
+            prioritize_local_operations: false,
             channels_size: 1024,
         }
     }
@@ -6,12 +6,17 @@ use std::sync::{
 };
 
 use massa_models::{
-    block::BlockId, endorsement::EndorsementId, operation::OperationId, slot::Slot,
+    block::BlockId,
+    endorsement::{EndorsementEquivocation, EndorsementId},
+    operation::{
+        OperationId, OperationOrigin, OperationOriginCounts, OperationPoolStatus, WrappedOperation,
+    },
+    slot::Slot,
 };
 use massa_storage::Storage;
 use massa_time::MassaTime;
 
-use crate::PoolController;
+use crate::{BlockOperationCandidate, OperationRejection, PoolController};
 
 /// Test tool to mock pool controller responses
 pub struct PoolEventReceiver(pub Receiver<MockPoolControllerMessage>);
@@ -30,6 +35,8 @@ pub enum MockPoolControllerMessage {
     AddOperations {
         /// Storage that contains all operations
         operations: Storage,
+        /// How the operations reached the node
+        origin: OperationOrigin,
     },
     /// Get block endorsements
     GetBlockEndorsements {
@@ -47,6 +54,15 @@ pub enum MockPoolControllerMessage {
         /// Response channel
         response_tx: mpsc::Sender<(Vec<OperationId>, Storage)>,
     },
+    /// Get the operation candidates of a block
+    GetBlockOperationCandidates {
+        /// Slot of the block to search operations in
+        slot: Slot,
+        /// Maximal cumulated size of the candidates
+        max_total_size: usize,
+        /// Response channel
+        response_tx: mpsc::Sender<(Vec<BlockOperationCandidate>, Storage)>,
+    },
     /// Get endorsement ids
     GetEndorsementCount {
         /// Response channel
@@ -57,6 +73,16 @@ pub enum MockPoolControllerMessage {
         /// Response channel
         response_tx: mpsc::Sender<usize>,
     },
+    /// Get the number of operations per origin
+    GetOperationOriginCounts {
+        /// Response channel
+        response_tx: mpsc::Sender<OperationOriginCounts>,
+    },
+    /// Get all the operations
+    GetOperations {
+        /// Response channel
+        response_tx: mpsc::Sender<Storage>,
+    },
     /// Contains endorsements
     ContainsEndorsements {
         /// ids to search
@@ -71,6 +97,25 @@ pub enum MockPoolControllerMessage {
         /// Response channel
         response_tx: mpsc::Sender<Vec<bool>>,
     },
+    /// Check whether operations would be accepted
+    CheckOperations {
+        /// ids of the checked operations
+        ids: Vec<OperationId>,
+        /// Response channel
+        response_tx: mpsc::Sender<Vec<Option<OperationRejection>>>,
+    },
+    /// Get endorsement equivocation records
+    GetEndorsementEquivocations {
+        /// Response channel
+        response_tx: mpsc::Sender<Vec<EndorsementEquivocation>>,
+    },
+    /// Get the pool status of operations
+    GetOperationPoolStatus {
+        /// ids of the operations
+        ids: Vec<OperationId>,
+        /// Response channel
+        response_tx: mpsc::Sender<Vec<OperationPoolStatus>>,
+    },
     /// Get stats of the pool
     GetStats {
         /// Response channel
@@ -131,11 +176,11 @@ impl PoolController for MockPoolController {
             .unwrap();
     }
 
-    fn add_operations(&mut self, operations: Storage) {
+    fn add_operations(&mut self, operations: Storage, origin: OperationOrigin) {
         self.0
             .lock()
             .unwrap()
-            .send(MockPoolControllerMessage::AddOperations { operations })
+            .send(MockPoolControllerMessage::AddOperations { operations, origin })
             .unwrap();
     }
 
@@ -170,6 +215,24 @@ impl PoolController for MockPoolController {
         response_rx.recv().unwrap()
     }
 
+    fn get_block_operation_candidates(
+        &self,
+        slot: &Slot,
+        max_total_size: usize,
+    ) -> (Vec<BlockOperationCandidate>, Storage) {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockPoolControllerMessage::GetBlockOperationCandidates {
+                slot: *slot,
+                max_total_size,
+                response_tx,
+            })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn get_endorsement_count(&self) -> usize {
         let (response_tx, response_rx) = mpsc::channel();
         self.0
@@ -190,6 +253,26 @@ impl PoolController for MockPoolController {
         response_rx.recv().unwrap()
     }
 
+    fn get_operation_origin_counts(&self) -> OperationOriginCounts {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockPoolControllerMessage::GetOperationOriginCounts { response_tx })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
+    fn get_operations(&self) -> Storage {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockPoolControllerMessage::GetOperations { response_tx })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool> {
         let (response_tx, response_rx) = mpsc::channel();
         self.0
@@ -216,6 +299,42 @@ impl PoolController for MockPoolController {
         response_rx.recv().unwrap()
     }
 
+    fn check_operations(&self, operations: &[WrappedOperation]) -> Vec<Option<OperationRejection>> {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockPoolControllerMessage::CheckOperations {
+                ids: operations.iter().map(|op| op.id).collect(),
+                response_tx,
+            })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
+    fn get_endorsement_equivocations(&self) -> Vec<EndorsementEquivocation> {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockPoolControllerMessage::GetEndorsementEquivocations { response_tx })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
+    fn get_operation_pool_status(&self, operations: &[OperationId]) -> Vec<OperationPoolStatus> {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockPoolControllerMessage::GetOperationPoolStatus {
+                ids: operations.to_vec(),
+                response_tx,
+            })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn notify_final_cs_periods(&mut self, final_cs_periods: &[u64]) {
         self.0
             .lock()
@@ -3,21 +3,33 @@
 //! Pool controller implementation
 
 use massa_models::{
-    block::BlockId, endorsement::EndorsementId, operation::OperationId, slot::Slot,
+    block::BlockId,
+    endorsement::{EndorsementEquivocation, EndorsementId},
+    operation::{
+        OperationId, OperationOrigin, OperationOriginCounts, OperationPoolStatus, WrappedOperation,
+    },
+    slot::Slot,
+};
+use massa_pool_exports::{
+    BlockOperationCandidate, OperationRejection, PoolConfig, PoolController, PoolManager,
 };
-use massa_pool_exports::{PoolConfig, PoolController, PoolManager};
 use massa_storage::Storage;
 use parking_lot::RwLock;
 use std::sync::mpsc::TrySendError;
 use std::sync::{mpsc::SyncSender, Arc};
-use tracing::{info, warn};
+use tracing::{info, warn, Span};
 
-use crate::{endorsement_pool::EndorsementPool, operation_pool::OperationPool};
+use crate::{
+    endorsement_pool::EndorsementPool, operation_pool::OperationPool, types::OperationInfo,
+};
 
 /// A generic command to send commands to a pool
 pub enum Command {
     /// Add items to the pool
     AddItems(Storage),
+    /// Add operations to the pool, along with how they reached the node
+    /// and the tracing span of their submitter
+    AddOperations(Storage, OperationOrigin, Span),
     /// Notify of new final consensus periods
     NotifyFinalCsPeriods(Vec<u64>),
     /// Stop the worker
@@ -41,10 +53,12 @@ pub struct PoolControllerImpl {
 
 impl PoolController for PoolControllerImpl {
     /// Asynchronously add operations to pool. Simply print a warning on failure.
-    fn add_operations(&mut self, ops: Storage) {
+    /// The operations are added within the current tracing span, so that the logs of the pool
+    /// can be correlated with the API request that submitted them.
+    fn add_operations(&mut self, ops: Storage, origin: OperationOrigin) {
         match self
             .operations_input_sender
-            .try_send(Command::AddItems(ops))
+            .try_send(Command::AddOperations(ops, origin, Span::current()))
         {
             Err(TrySendError::Disconnected(_)) => {
                 warn!("Could not add operations to pool: worker is unreachable.");
@@ -112,6 +126,17 @@ impl PoolController for PoolControllerImpl {
         self.operation_pool.read().get_block_operations(slot)
     }
 
+    /// get the operations that can be included in a block, with their packing metadata
+    fn get_block_operation_candidates(
+        &self,
+        slot: &Slot,
+        max_total_size: usize,
+    ) -> (Vec<BlockOperationCandidate>, Storage) {
+        self.operation_pool
+            .read()
+            .get_block_operation_candidates(slot, max_total_size)
+    }
+
     /// get endorsements for a block
     fn get_block_endorsements(
         &self,
@@ -139,6 +164,16 @@ impl PoolController for PoolControllerImpl {
         self.operation_pool.read().len()
     }
 
+    /// Get the number of operations in the pool per origin, including the parked ones
+    fn get_operation_origin_counts(&self) -> OperationOriginCounts {
+        self.operation_pool.read().get_origin_counts()
+    }
+
+    /// Get a storage referencing all the operations of the pool, including the parked ones
+    fn get_operations(&self) -> Storage {
+        self.operation_pool.read().get_operations()
+    }
+
     /// Check if the pool contains a list of endorsements. Returns one boolean per item.
     fn contains_endorsements(&self, endorsements: &[EndorsementId]) -> Vec<bool> {
         let lck = self.endorsement_pool.read();
@@ -150,6 +185,37 @@ impl PoolController for PoolControllerImpl {
         let lck = self.operation_pool.read();
         operations.iter().map(|id| lck.contains(id)).collect()
     }
+
+    /// Check whether the pool would accept a list of operations, running them through its pre-validation pipeline.
+    /// Returns one rejection reason per rejected item.
+    fn check_operations(&self, operations: &[WrappedOperation]) -> Vec<Option<OperationRejection>> {
+        let op_infos: Vec<OperationInfo> = operations
+            .iter()
+            .map(|op| {
+                OperationInfo::from_op(
+                    op,
+                    self._config.operation_validity_periods,
+                    self._config.roll_price,
+                    self._config.thread_count,
+                    OperationOrigin::Local,
+                )
+            })
+            .collect();
+        self.operation_pool
+            .read()
+            .validate_operations(&operations.iter().zip(op_infos.iter()).collect::<Vec<_>>())
+    }
+
+    /// Get the retained endorsement equivocation records, from oldest to newest.
+    fn get_endorsement_equivocations(&self) -> Vec<EndorsementEquivocation> {
+        self.endorsement_pool.read().get_equivocations()
+    }
+
+    /// Get the status of a list of operations in the pool, including their recent evictions. Returns one status per item.
+    fn get_operation_pool_status(&self, operations: &[OperationId]) -> Vec<OperationPoolStatus> {
+        let lck = self.operation_pool.read();
+        operations.iter().map(|id| lck.get_status(id)).collect()
+    }
 }
 
 /// Implementation of the pool manager.
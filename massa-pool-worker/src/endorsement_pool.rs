@@ -1,14 +1,16 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_models::{
+    address::Address,
     block::BlockId,
-    endorsement::EndorsementId,
-    prehash::{CapacityAllocator, PreHashSet},
+    endorsement::{EndorsementEquivocation, EndorsementId},
+    prehash::{CapacityAllocator, PreHashMap, PreHashSet},
     slot::Slot,
 };
 use massa_pool_exports::PoolConfig;
 use massa_storage::Storage;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{hash_map, BTreeMap, HashMap, VecDeque};
+use tracing::warn;
 
 pub struct EndorsementPool {
     /// configuration
@@ -21,6 +23,16 @@ pub struct EndorsementPool {
     /// indexed by thread, then `BTreeMap<(inclusion_slot, index, target_block), endorsement_id>`
     endorsements_sorted: Vec<BTreeMap<(Slot, u32, BlockId), EndorsementId>>,
 
+    /// first endorsement received for each (slot, index, creator) with the block it endorses,
+    /// used to detect equivocations
+    endorsements_by_creator: HashMap<(Slot, u32, Address), (EndorsementId, BlockId)>,
+
+    /// equivocation records, from oldest to newest
+    equivocations: VecDeque<EndorsementEquivocation>,
+
+    /// number of retained equivocation records per creator
+    equivocators: PreHashMap<Address, usize>,
+
     /// storage
     storage: Storage,
 
@@ -34,6 +46,9 @@ impl EndorsementPool {
             last_cs_final_periods: vec![0u64; config.thread_count as usize],
             endorsements_indexed: Default::default(),
             endorsements_sorted: vec![Default::default(); config.thread_count as usize],
+            endorsements_by_creator: Default::default(),
+            equivocations: Default::default(),
+            equivocators: Default::default(),
             config,
             storage: storage.clone_without_refs(),
         }
@@ -49,6 +64,64 @@ impl EndorsementPool {
         self.storage.get_endorsement_refs().contains(id)
     }
 
+    /// Checks whether an address has retained equivocation records.
+    fn is_equivocator(&self, addr: &Address) -> bool {
+        self.equivocators.contains_key(addr)
+    }
+
+    /// Get the retained equivocation records, from oldest to newest
+    pub fn get_equivocations(&self) -> Vec<EndorsementEquivocation> {
+        self.equivocations.iter().cloned().collect()
+    }
+
+    /// Record that `endorsement_id` (endorsing `endorsed_block`) conflicts with
+    /// another endorsement of the same creator for the same slot and index
+    fn record_equivocation(
+        &mut self,
+        (slot, index, creator): (Slot, u32, Address),
+        first: (EndorsementId, BlockId),
+        endorsement_id: EndorsementId,
+        endorsed_block: BlockId,
+    ) {
+        if let Some(record) = self
+            .equivocations
+            .iter_mut()
+            .find(|r| r.creator == creator && r.slot == slot && r.index == index)
+        {
+            if !record
+                .endorsements
+                .iter()
+                .any(|(id, _)| id == &endorsement_id)
+            {
+                record.endorsements.push((endorsement_id, endorsed_block));
+            }
+            return;
+        }
+        warn!(
+            "address {} created conflicting endorsements at slot {} index {}",
+            creator, slot, index
+        );
+        self.equivocations.push_back(EndorsementEquivocation {
+            creator,
+            slot,
+            index,
+            endorsements: vec![first, (endorsement_id, endorsed_block)],
+        });
+        *self.equivocators.entry(creator).or_default() += 1;
+
+        // forget the oldest records
+        while self.equivocations.len() > self.config.max_endorsement_equivocation_records {
+            // won't panic because len was checked above
+            let removed = self.equivocations.pop_front().unwrap();
+            if let hash_map::Entry::Occupied(mut occ) = self.equivocators.entry(removed.creator) {
+                *occ.get_mut() -= 1;
+                if *occ.get() == 0 {
+                    occ.remove();
+                }
+            }
+        }
+    }
+
     /// notify of new final CS periods
     pub(crate) fn notify_final_cs_periods(&mut self, final_cs_periods: &[u64]) {
         // update internal final CS period counter
@@ -72,6 +145,11 @@ impl EndorsementPool {
             }
         }
         self.storage.drop_endorsement_refs(&removed);
+
+        // forget the creators of endorsements that can't be included anymore
+        let last_cs_final_periods = &self.last_cs_final_periods;
+        self.endorsements_by_creator
+            .retain(|(slot, _, _), _| slot.period > last_cs_final_periods[slot.thread as usize]);
     }
 
     /// Add a list of endorsements to the pool
@@ -100,6 +178,31 @@ impl EndorsementPool {
                     continue;
                 }
 
+                // detect conflicting endorsements of the same creator:
+                // the first admitted one is kept, the others are refused
+                let creator_key = (endo.content.slot, endo.content.index, endo.creator_address);
+                if let Some(&first) = self.endorsements_by_creator.get(&creator_key) {
+                    if first.0 != endo.id {
+                        if first.1 != endo.content.endorsed_block {
+                            self.record_equivocation(
+                                creator_key,
+                                first,
+                                endo.id,
+                                endo.content.endorsed_block,
+                            );
+                        }
+                        continue;
+                    }
+                }
+
+                // endorsements of known equivocators are not admitted in a full pool
+                if self.endorsements_sorted[endo.content.slot.thread as usize].len()
+                    >= self.config.max_endorsements_pool_size_per_thread
+                    && self.is_equivocator(&endo.creator_address)
+                {
+                    continue;
+                }
+
                 // insert
                 let key = (
                     endo.content.slot,
@@ -114,12 +217,15 @@ impl EndorsementPool {
                     {
                         panic!("endorsement is expected to be absent from endorsements_sorted at this point");
                     }
+                    self.endorsements_by_creator
+                        .insert(creator_key, (endo.id, endo.content.endorsed_block));
                     added.insert(endo.id);
                 }
             }
         }
 
         // prune excess endorsements
+        let mut refused: PreHashSet<EndorsementId> = Default::default();
         for thread in 0..self.config.thread_count {
             while self.endorsements_sorted[thread as usize].len()
                 > self.config.max_endorsements_pool_size_per_thread
@@ -128,12 +234,20 @@ impl EndorsementPool {
                 let (_key, endo_id) = self.endorsements_sorted[thread as usize]
                     .pop_last()
                     .unwrap();
-                if !added.remove(&endo_id) {
+                if added.remove(&endo_id) {
+                    refused.insert(endo_id);
+                } else {
                     removed.insert(endo_id);
                 }
             }
         }
 
+        // forget the creators of the endorsements refused because the pool is full
+        if !refused.is_empty() {
+            self.endorsements_by_creator
+                .retain(|_, (endo_id, _)| !refused.contains(endo_id));
+        }
+
         // take ownership on added endorsements
         self.storage.extend(endorsement_storage.split_off(
             &Default::default(),
@@ -4,13 +4,19 @@ use massa_execution_exports::ExecutionController;
 use massa_models::{
     address::Address,
     amount::Amount,
-    operation::OperationId,
+    operation::{
+        OperationEviction, OperationEvictionReason, OperationId, OperationOrigin,
+        OperationOriginCounts, OperationPoolStatus, WrappedOperation,
+    },
     prehash::{CapacityAllocator, PreHashMap, PreHashSet},
     slot::Slot,
 };
-use massa_pool_exports::PoolConfig;
+use massa_pool_exports::{
+    BlockOperationCandidate, FarFutureOperationPolicy, OperationRejection, PoolConfig,
+};
+use massa_signature::verify_signature_batch;
 use massa_storage::Storage;
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, VecDeque};
 
 use crate::types::{OperationInfo, PoolOperationCursor};
 
@@ -27,6 +33,12 @@ pub struct OperationPool {
     /// operations sorted by increasing expiration slot
     ops_per_expiration: BTreeSet<(Slot, OperationId)>,
 
+    /// far future operations waiting to enter the pool
+    parked_ops: PreHashMap<OperationId, OperationInfo>,
+
+    /// parked operations sorted by increasing validity start slot
+    parked_ops_per_start: BTreeSet<(Slot, OperationId)>,
+
     /// storage instance
     pub(crate) storage: Storage,
 
@@ -35,6 +47,12 @@ pub struct OperationPool {
 
     /// last consensus final periods, per thread
     last_cs_final_periods: Vec<u64>,
+
+    /// recent eviction records
+    evictions: PreHashMap<OperationId, OperationEviction>,
+
+    /// evicted operations, from oldest to newest record
+    evictions_order: VecDeque<OperationId>,
 }
 
 impl OperationPool {
@@ -47,7 +65,11 @@ impl OperationPool {
             operations: Default::default(),
             sorted_ops_per_thread: vec![Default::default(); config.thread_count as usize],
             ops_per_expiration: Default::default(),
+            parked_ops: Default::default(),
+            parked_ops_per_start: Default::default(),
             last_cs_final_periods: vec![0u64; config.thread_count as usize],
+            evictions: Default::default(),
+            evictions_order: Default::default(),
             config,
             storage: storage.clone_without_refs(),
             execution_controller,
@@ -59,9 +81,68 @@ impl OperationPool {
         self.operations.len()
     }
 
-    /// Checks whether an element is stored in the pool.
+    /// Get a storage referencing all the operations of the pool, including its parking area
+    pub fn get_operations(&self) -> Storage {
+        let ids: PreHashSet<OperationId> = self
+            .operations
+            .keys()
+            .chain(self.parked_ops.keys())
+            .copied()
+            .collect();
+        let mut storage = self.storage.clone_without_refs();
+        storage.claim_operation_refs(&ids);
+        storage
+    }
+
+    /// Get the number of operations per origin, including the ones of the parking area
+    pub fn get_origin_counts(&self) -> OperationOriginCounts {
+        let mut counts = OperationOriginCounts::default();
+        for op_info in self.operations.values().chain(self.parked_ops.values()) {
+            match op_info.origin {
+                OperationOrigin::Local => counts.local += 1,
+                OperationOrigin::Network => counts.network += 1,
+            }
+        }
+        counts
+    }
+
+    /// Get the number of far future operations held in the parking area
+    pub fn parked_len(&self) -> usize {
+        self.parked_ops.len()
+    }
+
+    /// Checks whether an element is stored in the pool, including its parking area.
     pub fn contains(&self, id: &OperationId) -> bool {
-        self.operations.contains_key(id)
+        self.operations.contains_key(id) || self.parked_ops.contains_key(id)
+    }
+
+    /// Get the status of an operation in the pool, or its latest eviction record
+    pub fn get_status(&self, id: &OperationId) -> OperationPoolStatus {
+        if self.operations.contains_key(id) {
+            OperationPoolStatus::Pending
+        } else if self.parked_ops.contains_key(id) {
+            OperationPoolStatus::Parked
+        } else if let Some(eviction) = self.evictions.get(id) {
+            OperationPoolStatus::Evicted(*eviction)
+        } else {
+            OperationPoolStatus::Unknown
+        }
+    }
+
+    /// Record the eviction of an operation, forgetting the oldest records in excess of the limit
+    fn record_eviction(&mut self, id: OperationId, thread: u8, reason: OperationEvictionReason) {
+        let eviction = OperationEviction {
+            reason,
+            final_period: self.last_cs_final_periods[thread as usize],
+        };
+        if self.evictions.insert(id, eviction).is_none() {
+            self.evictions_order.push_back(id);
+        }
+        while self.evictions_order.len() > self.config.max_operation_eviction_records {
+            if let Some(oldest) = self.evictions_order.pop_front() {
+                self.evictions.remove(&oldest);
+            }
+        }
     }
 
     /// notify of new final slot
@@ -83,9 +164,48 @@ impl OperationPool {
             if !self.sorted_ops_per_thread[expire_slot.thread as usize].remove(&op_info.cursor) {
                 panic!("expected op presence in sorted list")
             }
+            self.record_eviction(
+                op_id,
+                op_info.thread,
+                OperationEvictionReason::Expired {
+                    expire_period: expire_slot.period,
+                    last_final_period: self.last_cs_final_periods[expire_slot.thread as usize],
+                },
+            );
             removed_ops.insert(op_id);
         }
 
+        // move the parked ops whose validity start got close enough into the pool
+        let unparked: Vec<(Slot, OperationId)> = self
+            .parked_ops_per_start
+            .iter()
+            .filter(|(start_slot, _)| {
+                start_slot.period <= self.max_validity_start_period(start_slot.thread)
+            })
+            .copied()
+            .collect();
+        for key in unparked {
+            self.parked_ops_per_start.remove(&key);
+            let op_info = self
+                .parked_ops
+                .remove(&key.1)
+                .expect("expected op presence in parked ops");
+            if self.is_operation_relevant(&op_info) {
+                self.insert_operation(op_info);
+            } else {
+                self.record_eviction(
+                    key.1,
+                    op_info.thread,
+                    OperationEvictionReason::Expired {
+                        expire_period: *op_info.validity_period_range.end(),
+                        last_final_period: self.last_cs_final_periods[op_info.thread as usize],
+                    },
+                );
+                removed_ops.insert(key.1);
+            }
+        }
+        removed_ops.extend(self.prune_excess_operations());
+
         // notify storage that pool has lost references to removed_ops
         self.storage.drop_operation_refs(&removed_ops);
     }
@@ -94,51 +214,162 @@ impl OperationPool {
     pub(crate) fn is_operation_relevant(&self, op_info: &OperationInfo) -> bool {
         // too old
         *op_info.validity_period_range.end() > self.last_cs_final_periods[op_info.thread as usize]
-        // todo check if validity not started yet
     }
 
-    /// Add a list of operations to the pool
-    pub(crate) fn add_operations(&mut self, mut ops_storage: Storage) {
-        let items = ops_storage
-            .get_op_refs()
-            .iter()
-            .copied()
-            .collect::<Vec<_>>();
+    /// Get the max validity start period of the operations accepted in a thread
+    fn max_validity_start_period(&self, thread: u8) -> u64 {
+        self.last_cs_final_periods[thread as usize]
+            .saturating_add(self.config.max_operation_future_validity_start_periods)
+    }
 
-        let mut added = PreHashSet::with_capacity(items.len());
-        let mut removed = PreHashSet::with_capacity(items.len());
+    /// Checks if the validity range of an operation starts too far in the future to enter the pool
+    fn is_operation_too_far_in_the_future(&self, op_info: &OperationInfo) -> bool {
+        *op_info.validity_period_range.start() > self.max_validity_start_period(op_info.thread)
+    }
 
-        // add items to pool
+    /// Get the reason why the pool would not accept an operation, if any.
+    /// Far future operations are only rejected with the `Reject` policy.
+    pub(crate) fn check_operation(&self, op_info: &OperationInfo) -> Option<OperationRejection> {
+        if !self.is_operation_relevant(op_info) {
+            return Some(OperationRejection::Expired {
+                expire_period: *op_info.validity_period_range.end(),
+                last_final_period: self.last_cs_final_periods[op_info.thread as usize],
+            });
+        }
+        if self.config.far_future_operation_policy == FarFutureOperationPolicy::Reject
+            && self.is_operation_too_far_in_the_future(op_info)
         {
-            let ops = ops_storage.read_operations();
-            for op_id in items {
-                let op_info = OperationInfo::from_op(
-                    ops.get(&op_id).expect(
-                        "attempting to add operation to pool, but it is absent from storage",
-                    ),
-                    self.config.operation_validity_periods,
-                    self.config.roll_price,
-                    self.config.thread_count,
-                );
-                if !self.is_operation_relevant(&op_info) {
-                    continue;
+            return Some(OperationRejection::TooFarInTheFuture {
+                validity_start_period: *op_info.validity_period_range.start(),
+                max_validity_start_period: self.max_validity_start_period(op_info.thread),
+            });
+        }
+        None
+    }
+
+    /// Run the pre-validation pipeline on operations before admitting them into the pool:
+    /// expiry sanity, minimal fee, batch signature verification and creator balance check against the candidate ledger,
+    /// the last two being enabled by the configuration.
+    /// Returns one rejection reason per rejected operation.
+    pub(crate) fn validate_operations(
+        &self,
+        ops: &[(&WrappedOperation, &OperationInfo)],
+    ) -> Vec<Option<OperationRejection>> {
+        // expiry sanity and minimal fee
+        let mut rejections: Vec<Option<OperationRejection>> = ops
+            .iter()
+            .map(|(_op, op_info)| {
+                self.check_operation(op_info).or_else(|| {
+                    (op_info.fee < self.config.min_operation_fee).then_some(
+                        OperationRejection::FeeTooLow {
+                            fee: op_info.fee,
+                            min_fee: self.config.min_operation_fee,
+                        },
+                    )
+                })
+            })
+            .collect();
+
+        // batch verify the signatures, and look for the invalid ones only if the batch fails
+        if self.config.verify_operation_signatures {
+            let batch: Vec<_> = ops
+                .iter()
+                .zip(rejections.iter())
+                .filter(|(_, rejection)| rejection.is_none())
+                .map(|((op, _), _)| {
+                    (
+                        op.compute_operation_signed_hash(
+                            self.config.chain_id,
+                            self.config.chain_id_activation_period,
+                        ),
+                        op.signature,
+                        op.creator_public_key,
+                    )
+                })
+                .collect();
+            if verify_signature_batch(&batch).is_err() {
+                for ((op, _), rejection) in ops.iter().zip(rejections.iter_mut()) {
+                    if rejection.is_none()
+                        && op
+                            .verify_operation_signature(
+                                self.config.chain_id,
+                                self.config.chain_id_activation_period,
+                            )
+                            .is_err()
+                    {
+                        *rejection = Some(OperationRejection::InvalidSignature);
+                    }
                 }
-                if let Ok(op_info) = self.operations.try_insert(op_info.id, op_info) {
-                    if !self.sorted_ops_per_thread[op_info.thread as usize].insert(op_info.cursor) {
-                        panic!("sorted ops should not contain the op at this point");
+            }
+        }
+
+        // check that the candidate balances of the creators cover the fees
+        if self.config.check_operation_creator_balance {
+            let addresses: Vec<Address> = ops
+                .iter()
+                .zip(rejections.iter())
+                .filter(|(_, rejection)| rejection.is_none())
+                .map(|((_, op_info), _)| op_info.creator_address)
+                .collect::<PreHashSet<Address>>()
+                .into_iter()
+                .collect();
+            if !addresses.is_empty() {
+                let balances: PreHashMap<Address, Amount> = addresses
+                    .iter()
+                    .copied()
+                    .zip(
+                        self.execution_controller
+                            .get_final_and_candidate_balance(&addresses),
+                    )
+                    .filter_map(|(address, balances)| {
+                        balances.1.or(balances.0).map(|balance| (address, balance))
+                    })
+                    .collect();
+                for ((_, op_info), rejection) in ops.iter().zip(rejections.iter_mut()) {
+                    if rejection.is_some() {
+                        continue;
                     }
-                    if !self.ops_per_expiration.insert((
-                        Slot::new(*op_info.validity_period_range.end(), op_info.thread),
-                        op_info.id,
-                    )) {
-                        panic!("expiration indexed ops should not contain the op at this point");
+                    let balance = balances
+                        .get(&op_info.creator_address)
+                        .copied()
+                        .unwrap_or_default();
+                    if balance < op_info.fee {
+                        *rejection = Some(OperationRejection::InsufficientBalance {
+                            fee: op_info.fee,
+                            balance,
+                        });
                     }
-                    added.insert(op_info.id);
                 }
             }
         }
 
-        // prune excess operations
+        rejections
+    }
+
+    /// Insert an operation in the pool indexes. Returns false if it was already present.
+    fn insert_operation(&mut self, op_info: OperationInfo) -> bool {
+        if let Ok(op_info) = self.operations.try_insert(op_info.id, op_info) {
+            if !self.sorted_ops_per_thread[op_info.thread as usize].insert(op_info.cursor) {
+                panic!("sorted ops should not contain the op at this point");
+            }
+            if !self.ops_per_expiration.insert((
+                Slot::new(*op_info.validity_period_range.end(), op_info.thread),
+                op_info.id,
+            )) {
+                panic!("expiration indexed ops should not contain the op at this point");
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Remove the worst operations of each thread in excess of the pool size limit,
+    /// and the furthest parked operations in excess of the parking area size limit.
+    /// Returns the IDs of the removed operations.
+    fn prune_excess_operations(&mut self) -> PreHashSet<OperationId> {
+        let mut removed = PreHashSet::default();
+        let mut evicted = Vec::new();
         self.sorted_ops_per_thread.iter_mut().for_each(|ops| {
             while ops.len() > self.config.max_operation_pool_size_per_thread {
                 // the unwrap below won't panic because the loop condition tests for non-emptines of self.operations
@@ -152,8 +383,97 @@ impl OperationPool {
                     panic!("the operation should be in self.ops_per_expiration at this point");
                 }
                 removed.insert(op_info.id);
+                evicted.push((op_info.id, op_info.thread, OperationEvictionReason::LowFee));
             }
         });
+        while self.parked_ops_per_start.len() > self.config.max_parked_operations {
+            // the unwrap below won't panic because the loop condition tests for non-emptines of self.parked_ops_per_start
+            let (start_slot, op_id) = self.parked_ops_per_start.pop_last().unwrap();
+            self.parked_ops
+                .remove(&op_id)
+                .expect("the operation should be in self.parked_ops at this point");
+            removed.insert(op_id);
+            evicted.push((op_id, start_slot.thread, OperationEvictionReason::ParkingAreaFull));
+        }
+        for (op_id, thread, reason) in evicted {
+            self.record_eviction(op_id, thread, reason);
+        }
+        removed
+    }
+
+    /// Add a list of operations to the pool, `origin` telling how they reached the node.
+    /// The operations of the pool submitted again locally are considered local from then on.
+    pub(crate) fn add_operations(&mut self, mut ops_storage: Storage, origin: OperationOrigin) {
+        let items = ops_storage
+            .get_op_refs()
+            .iter()
+            .copied()
+            .collect::<Vec<_>>();
+
+        if origin == OperationOrigin::Local {
+            for op_id in items.iter() {
+                if let Some(op_info) = self
+                    .operations
+                    .get_mut(op_id)
+                    .or_else(|| self.parked_ops.get_mut(op_id))
+                {
+                    op_info.origin = OperationOrigin::Local;
+                }
+            }
+        }
+
+        let mut added = PreHashSet::with_capacity(items.len());
+
+        // add items to pool
+        {
+            let ops = ops_storage.read_operations();
+            let new_ops: Vec<(&WrappedOperation, OperationInfo)> = items
+                .into_iter()
+                .filter(|op_id| !self.contains(op_id))
+                .map(|op_id| {
+                    let op = ops.get(&op_id).expect(
+                        "attempting to add operation to pool, but it is absent from storage",
+                    );
+                    let op_info = OperationInfo::from_op(
+                        op,
+                        self.config.operation_validity_periods,
+                        self.config.roll_price,
+                        self.config.thread_count,
+                        origin,
+                    );
+                    (op, op_info)
+                })
+                .collect();
+
+            // run the pre-validation pipeline
+            let rejections = self.validate_operations(
+                &new_ops
+                    .iter()
+                    .map(|(op, op_info)| (*op, op_info))
+                    .collect::<Vec<_>>(),
+            );
+
+            for ((_op, op_info), rejection) in new_ops.into_iter().zip(rejections) {
+                if let Some(rejection) = rejection {
+                    self.record_eviction(op_info.id, op_info.thread, rejection.into());
+                    continue;
+                }
+                let op_id = op_info.id;
+                if self.is_operation_too_far_in_the_future(&op_info) {
+                    // park the op until its validity start gets close enough
+                    let start_slot =
+                        Slot::new(*op_info.validity_period_range.start(), op_info.thread);
+                    self.parked_ops_per_start.insert((start_slot, op_id));
+                    self.parked_ops.insert(op_id, op_info);
+                } else if !self.insert_operation(op_info) {
+                    continue;
+                }
+                added.insert(op_id);
+            }
+        }
+
+        // prune excess operations
+        let removed = self.prune_excess_operations();
 
         // This will add the new ops to the storage without taking locks.
         // It just take the local references from `ops_storage` if they are not in `self.storage` yet.
@@ -171,6 +491,24 @@ impl OperationPool {
         self.storage.drop_operation_refs(&removed);
     }
 
+    /// Iterates over the operations of a thread in the order they are considered for block production:
+    /// from best to worst, the local ones coming first if `prioritize_local_operations` is set
+    fn block_production_order(&self, thread: u8) -> impl Iterator<Item = &OperationInfo> {
+        let prioritize_local = self.config.prioritize_local_operations;
+        let ops = self.sorted_ops_per_thread[thread as usize]
+            .iter()
+            .map(move |cursor| {
+                self.operations
+                    .get(&cursor.get_id())
+                    .expect("the operation should be in self.operations at this point")
+            });
+        ops.clone()
+            .filter(move |op_info| !prioritize_local || op_info.origin == OperationOrigin::Local)
+            .chain(ops.filter(move |op_info| {
+                prioritize_local && op_info.origin != OperationOrigin::Local
+            }))
+    }
+
     /// get operations for block creation
     pub fn get_block_operations(&self, slot: &Slot) -> (Vec<OperationId>, Storage) {
         // init list of selected operation IDs
@@ -184,12 +522,7 @@ impl OperationPool {
         let mut balance_cache: PreHashMap<Address, Amount> = Default::default();
 
         // iterate over pool operations in the right thread, from best to worst
-        for cursor in self.sorted_ops_per_thread[slot.thread as usize].iter() {
-            let op_info = self
-                .operations
-                .get(&cursor.get_id())
-                .expect("the operation should be in self.operations at this point");
-
+        for op_info in self.block_production_order(slot.thread) {
             // exclude ops for which the block slot is outside of their validity range
             if !op_info.validity_period_range.contains(&slot.period) {
                 continue;
@@ -262,4 +595,83 @@ impl OperationPool {
 
         (op_ids, res_storage)
     }
+
+    /// Get the operations that can be included in a block at the given slot, from best to worst fee per byte,
+    /// with the metadata needed to pack them. Unlike `get_block_operations`, the operations are not packed:
+    /// the candidates are gathered until their cumulated size reaches `max_total_size`,
+    /// and only the operations that could not be included in any block are excluded.
+    pub fn get_block_operation_candidates(
+        &self,
+        slot: &Slot,
+        max_total_size: usize,
+    ) -> (Vec<BlockOperationCandidate>, Storage) {
+        let mut candidates = Vec::new();
+        let mut total_size: usize = 0;
+        // cache of balances, `None` for the addresses that do not exist
+        let mut balance_cache: PreHashMap<Address, Option<Amount>> = Default::default();
+
+        // iterate over pool operations in the right thread, from best to worst
+        for op_info in self.block_production_order(slot.thread) {
+            if total_size >= max_total_size {
+                break;
+            }
+
+            // exclude ops for which the block slot is outside of their validity range
+            if !op_info.validity_period_range.contains(&slot.period) {
+                continue;
+            }
+
+            // exclude ops that do not fit in a block, even alone
+            if op_info.size > self.config.max_block_size as usize
+                || op_info.max_gas > self.config.max_block_gas
+            {
+                continue;
+            }
+
+            // check if the op was already executed
+            if self
+                .execution_controller
+                .unexecuted_ops_among(&vec![op_info.id].into_iter().collect(), slot.thread)
+                .is_empty()
+            {
+                continue;
+            }
+
+            // exclude ops whose creator can not even pay the fee
+            let creator_balance = *balance_cache
+                .entry(op_info.creator_address)
+                .or_insert_with(|| {
+                    self.execution_controller
+                        .get_final_and_candidate_balance(&[op_info.creator_address])
+                        .get(0)
+                        .and_then(|balances| balances.1.or(balances.0))
+                });
+            let creator_balance = match creator_balance {
+                Some(balance) if balance >= op_info.fee => balance,
+                _ => continue,
+            };
+
+            total_size = total_size.saturating_add(op_info.size);
+            candidates.push(BlockOperationCandidate {
+                id: op_info.id,
+                size: op_info.size,
+                max_gas: op_info.max_gas,
+                fee: op_info.fee,
+                creator_address: op_info.creator_address,
+                max_spending: op_info.max_spending,
+                creator_balance,
+            });
+        }
+
+        // generate storage
+        let mut res_storage = self.storage.clone_without_refs();
+        let claim_ops: PreHashSet<OperationId> =
+            candidates.iter().map(|candidate| candidate.id).collect();
+        let claimed_ops = res_storage.claim_operation_refs(&claim_ops);
+        if claimed_ops.len() != claim_ops.len() {
+            panic!("could not claim all operations from storage");
+        }
+
+        (candidates, res_storage)
+    }
 }
@@ -0,0 +1,86 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+//! # Endorsement pool units tests
+//! Units tests that are internals to the endorsement pool.
+//!
+//! # Endorsement equivocations
+//! Function: [`test_refused_endorsements_not_indexed`]
+//! Endorsements refused by the pool, because their creator is a known
+//! equivocator or because the pool is full, are not kept as the first
+//! endorsement of their creator, so they cause no equivocation record.
+
+use crate::endorsement_pool::EndorsementPool;
+use massa_hash::Hash;
+use massa_models::{
+    block::BlockId,
+    endorsement::{Endorsement, EndorsementSerializer, WrappedEndorsement},
+    slot::Slot,
+    wrapped::WrappedContent,
+};
+use massa_pool_exports::PoolConfig;
+use massa_signature::KeyPair;
+use massa_storage::Storage;
+
+fn create_endorsement(
+    keypair: &KeyPair,
+    slot: Slot,
+    index: u32,
+    endorsed_block: &str,
+) -> WrappedEndorsement {
+    let content = Endorsement {
+        slot,
+        index,
+        endorsed_block: BlockId(Hash::compute_from(endorsed_block.as_bytes())),
+    };
+    Endorsement::new_wrapped(content, EndorsementSerializer::new(), keypair).unwrap()
+}
+
+#[test]
+fn test_refused_endorsements_not_indexed() {
+    let config = PoolConfig {
+        max_endorsements_pool_size_per_thread: 1,
+        ..PoolConfig::default()
+    };
+    let storage = Storage::create_root();
+    let mut pool = EndorsementPool::init(config, &storage);
+    let add = |pool: &mut EndorsementPool, endorsement: WrappedEndorsement| {
+        let mut to_add = storage.clone_without_refs();
+        to_add.store_endorsements(vec![endorsement]);
+        pool.add_endorsements(to_add);
+    };
+    let equivocator = KeyPair::generate();
+    let other = KeyPair::generate();
+
+    // the first endorsement of a creator is admitted and a conflicting one is recorded
+    let first = create_endorsement(&equivocator, Slot::new(1, 0), 0, "a");
+    add(&mut pool, first.clone());
+    add(
+        &mut pool,
+        create_endorsement(&equivocator, Slot::new(1, 0), 0, "b"),
+    );
+    assert_eq!(pool.len(), 1);
+    assert!(pool.contains(&first.id));
+    assert_eq!(pool.get_equivocations().len(), 1);
+
+    // an endorsement of the equivocator refused by the full pool does not make
+    // the following endorsement of the same slot and index an equivocation
+    let refused = create_endorsement(&equivocator, Slot::new(2, 0), 0, "a");
+    add(&mut pool, refused.clone());
+    assert!(!pool.contains(&refused.id));
+    add(
+        &mut pool,
+        create_endorsement(&equivocator, Slot::new(2, 0), 0, "b"),
+    );
+    assert_eq!(pool.get_equivocations().len(), 1);
+
+    // the same goes for an endorsement pruned right away from the full pool
+    let pruned = create_endorsement(&other, Slot::new(2, 0), 1, "a");
+    add(&mut pool, pruned.clone());
+    assert!(!pool.contains(&pruned.id));
+    add(
+        &mut pool,
+        create_endorsement(&other, Slot::new(2, 0), 1, "b"),
+    );
+    assert_eq!(pool.get_equivocations().len(), 1);
+    assert_eq!(pool.len(), 1);
+    assert!(pool.contains(&first.id));
+}
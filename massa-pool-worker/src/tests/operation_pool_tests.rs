@@ -12,32 +12,59 @@
 //! Same as classic but we try to add irrelevant operation. (See the definition
 //! chapter below)
 //!
+//! # Add far future operations
+//! Function: [`test_add_far_future_operation`]
+//! Operations whose validity starts too far in the future are rejected or
+//! parked depending on the configured policy. Parked ones enter the pool
+//! once the final periods get close enough.
+//!
+//! # Operation eviction records
+//! Function: [`test_operation_eviction_records`]
+//! Operations pruned from a full pool or expired are reported with the reason
+//! of their eviction, within the limit of retained records.
+//!
+//! # Operation pre-validation
+//! Function: [`test_operation_pre_validation`]
+//! Operations with a fee below the minimum, an invalid signature or a creator
+//! whose candidate balance can not cover their fee are refused by the pool.
+//!
+//! # Operation origins
+//! Function: [`test_operation_origins`]
+//! Operations are counted by origin, and the ones received from the network
+//! become local when they are submitted again to the node.
+//!
 //! # Definition
 //! Relevant operation: Operation with a validity range corresponding to the
 //! latest period given his own thread. All operation which doesn't fit these
 //! requirements are "irrelevant"
 //!
 use super::tools::{create_some_operations, operation_pool_test};
-use crate::operation_pool::OperationPool;
-use massa_execution_exports::test_exports::MockExecutionController;
+use crate::{operation_pool::OperationPool, types::OperationInfo};
+use massa_execution_exports::test_exports::{
+    MockExecutionController, MockExecutionControllerMessage,
+};
 use massa_models::{
     address::Address,
     amount::Amount,
-    operation::{Operation, OperationSerializer, OperationType, WrappedOperation},
+    config::TESTNET_CHAIN_ID,
+    operation::{
+        Operation, OperationEvictionReason, OperationOrigin, OperationOriginCounts,
+        OperationPoolStatus, OperationType, WrappedOperation,
+    },
     prehash::PreHashMap,
     slot::Slot,
-    wrapped::WrappedContent,
 };
-use massa_pool_exports::PoolConfig;
+use massa_pool_exports::{FarFutureOperationPolicy, OperationRejection, PoolConfig};
 use massa_signature::KeyPair;
 use massa_storage::Storage;
 use std::str::FromStr;
+use std::time::Duration;
 
 #[test]
 fn test_add_operation() {
     operation_pool_test(PoolConfig::default(), |mut operation_pool, mut storage| {
         storage.store_operations(create_some_operations(10, &KeyPair::generate(), 2));
-        operation_pool.add_operations(storage);
+        operation_pool.add_operations(storage, OperationOrigin::Network);
         assert_eq!(operation_pool.storage.get_op_refs().len(), 10);
     });
 }
@@ -51,9 +78,190 @@ fn test_add_irrelevant_operation() {
     operation_pool_test(PoolConfig::default(), |mut operation_pool, mut storage| {
         storage.store_operations(create_some_operations(10, &KeyPair::generate(), 1));
         operation_pool.notify_final_cs_periods(&vec![51; thread_count.into()]);
-        operation_pool.add_operations(storage);
+        operation_pool.add_operations(storage, OperationOrigin::Network);
+        assert_eq!(operation_pool.storage.get_op_refs().len(), 0);
+    });
+}
+
+/// Test that far future operations are rejected or parked depending on the policy
+#[test]
+fn test_add_far_future_operation() {
+    let pool_config = PoolConfig::default();
+    let thread_count = pool_config.thread_count;
+    let expire_period = pool_config.operation_validity_periods
+        + pool_config.max_operation_future_validity_start_periods
+        + 10;
+    operation_pool_test(pool_config, |mut operation_pool, mut storage| {
+        storage.store_operations(create_some_operations(
+            10,
+            &KeyPair::generate(),
+            expire_period,
+        ));
+        operation_pool.add_operations(storage, OperationOrigin::Network);
         assert_eq!(operation_pool.storage.get_op_refs().len(), 0);
     });
+
+    let pool_config = PoolConfig {
+        far_future_operation_policy: FarFutureOperationPolicy::Park,
+        max_parked_operations: 5,
+        ..PoolConfig::default()
+    };
+    operation_pool_test(pool_config, |mut operation_pool, mut storage| {
+        storage.store_operations(create_some_operations(
+            10,
+            &KeyPair::generate(),
+            expire_period,
+        ));
+        operation_pool.add_operations(storage, OperationOrigin::Network);
+        assert_eq!(operation_pool.storage.get_op_refs().len(), 5);
+        assert_eq!(operation_pool.parked_len(), 5);
+        assert_eq!(operation_pool.len(), 0);
+
+        operation_pool.notify_final_cs_periods(&vec![10; thread_count.into()]);
+        assert_eq!(operation_pool.storage.get_op_refs().len(), 5);
+        assert_eq!(operation_pool.parked_len(), 0);
+        assert_eq!(operation_pool.len(), 5);
+    });
+}
+
+/// Test the rejection reasons reported for expired and far future operations
+#[test]
+fn test_check_operation() {
+    let pool_config = PoolConfig::default();
+    let thread_count = pool_config.thread_count;
+    operation_pool_test(pool_config, |mut operation_pool, _storage| {
+        operation_pool.notify_final_cs_periods(&vec![20; thread_count.into()]);
+        let check = |op: &WrappedOperation| {
+            operation_pool.check_operation(&OperationInfo::from_op(
+                op,
+                pool_config.operation_validity_periods,
+                pool_config.roll_price,
+                thread_count,
+                OperationOrigin::Network,
+            ))
+        };
+        assert_eq!(
+            check(&get_transaction(20, 1)),
+            Some(OperationRejection::Expired {
+                expire_period: 20,
+                last_final_period: 20
+            })
+        );
+        assert_eq!(check(&get_transaction(21, 1)), None);
+        let expire_period = pool_config.operation_validity_periods
+            + pool_config.max_operation_future_validity_start_periods
+            + 21;
+        assert_eq!(
+            check(&get_transaction(expire_period, 1)),
+            Some(OperationRejection::TooFarInTheFuture {
+                validity_start_period: 121,
+                max_validity_start_period: 120
+            })
+        );
+    });
+}
+
+/// Test the statuses of pending, evicted and unknown operations
+#[test]
+fn test_operation_eviction_records() {
+    let pool_config = PoolConfig {
+        max_operation_pool_size_per_thread: 2,
+        max_operation_eviction_records: 2,
+        ..PoolConfig::default()
+    };
+    let thread_count = pool_config.thread_count;
+    operation_pool_test(pool_config, |mut operation_pool, mut storage| {
+        let ops = create_some_operations(3, &KeyPair::generate(), 10);
+        let ids: Vec<_> = ops.iter().map(|op| op.id).collect();
+        storage.store_operations(ops);
+        operation_pool.add_operations(storage, OperationOrigin::Network);
+
+        // the pool of the thread is full: one of the operations is evicted
+        let statuses: Vec<_> = ids.iter().map(|id| operation_pool.get_status(id)).collect();
+        let evicted_id = ids
+            .iter()
+            .zip(statuses.iter())
+            .find_map(|(id, status)| match status {
+                OperationPoolStatus::Evicted(eviction) => {
+                    assert_eq!(eviction.reason, OperationEvictionReason::LowFee);
+                    Some(*id)
+                }
+                _ => None,
+            })
+            .expect("an operation should have been evicted");
+        assert_eq!(
+            statuses
+                .iter()
+                .filter(|status| **status == OperationPoolStatus::Pending)
+                .count(),
+            2
+        );
+        assert_eq!(
+            operation_pool.get_status(&get_transaction(10, 1).id),
+            OperationPoolStatus::Unknown
+        );
+
+        // the remaining operations expire, the oldest record being forgotten
+        operation_pool.notify_final_cs_periods(&vec![10; thread_count.into()]);
+        assert_eq!(
+            operation_pool.get_status(&evicted_id),
+            OperationPoolStatus::Unknown
+        );
+        for id in ids.iter().filter(|id| **id != evicted_id) {
+            match operation_pool.get_status(id) {
+                OperationPoolStatus::Evicted(eviction) => {
+                    assert_eq!(
+                        eviction.reason,
+                        OperationEvictionReason::Expired {
+                            expire_period: 10,
+                            last_final_period: 10
+                        }
+                    );
+                    assert_eq!(eviction.final_period, 10);
+                }
+                status => panic!("unexpected status {}", status),
+            }
+        }
+    });
+}
+
+/// Test the counts of operations per origin, and the update of the origin of resubmitted operations
+#[test]
+fn test_operation_origins() {
+    operation_pool_test(PoolConfig::default(), |mut operation_pool, storage| {
+        let network_ops = create_some_operations(3, &KeyPair::generate(), 10);
+        let local_ops = create_some_operations(2, &KeyPair::generate(), 10);
+
+        let mut network_storage = storage.clone_without_refs();
+        network_storage.store_operations(network_ops.clone());
+        operation_pool.add_operations(network_storage, OperationOrigin::Network);
+        let mut local_storage = storage.clone_without_refs();
+        local_storage.store_operations(local_ops);
+        operation_pool.add_operations(local_storage, OperationOrigin::Local);
+        assert_eq!(
+            operation_pool.get_origin_counts(),
+            OperationOriginCounts {
+                local: 2,
+                network: 3
+            }
+        );
+
+        // an operation submitted locally after being received from the network becomes local
+        let mut resubmitted_storage = storage.clone_without_refs();
+        resubmitted_storage.store_operations(vec![network_ops[0].clone()]);
+        operation_pool.add_operations(resubmitted_storage, OperationOrigin::Local);
+        // but not the other way around
+        let mut received_storage = storage.clone_without_refs();
+        received_storage.store_operations(vec![network_ops[0].clone()]);
+        operation_pool.add_operations(received_storage, OperationOrigin::Network);
+        assert_eq!(
+            operation_pool.get_origin_counts(),
+            OperationOriginCounts {
+                local: 3,
+                network: 2
+            }
+        );
+    });
 }
 
 fn get_transaction(expire_period: u64, fee: u64) -> WrappedOperation {
@@ -70,7 +278,85 @@ fn get_transaction(expire_period: u64, fee: u64) -> WrappedOperation {
         op,
         expire_period,
     };
-    Operation::new_wrapped(content, OperationSerializer::new(), &sender_keypair).unwrap()
+    Operation::new_signed(content, &sender_keypair, TESTNET_CHAIN_ID, 0).unwrap()
+}
+
+/// Test the rejections of the pre-validation pipeline: minimal fee, invalid signature and insufficient creator balance
+#[test]
+fn test_operation_pre_validation() {
+    let pool_config = PoolConfig {
+        min_operation_fee: Amount::from_str("2").unwrap(),
+        check_operation_creator_balance: true,
+        ..PoolConfig::default()
+    };
+    let (execution_controller, execution_receiver) = MockExecutionController::new_with_receiver();
+    let operation_pool =
+        OperationPool::init(pool_config, &Storage::create_root(), execution_controller);
+
+    let low_fee_op = get_transaction(10, 1);
+    let mut wrong_signature_op = get_transaction(10, 5);
+    wrong_signature_op.signature = get_transaction(10, 5).signature;
+    let poor_creator_op = get_transaction(10, 5);
+    let valid_op = get_transaction(10, 5);
+    let ops = vec![low_fee_op, wrong_signature_op, poor_creator_op, valid_op];
+
+    // the candidate balance of the creator of the last operation covers its fee
+    let balances: PreHashMap<Address, Amount> = [
+        (ops[2].creator_address, Amount::from_str("4").unwrap()),
+        (ops[3].creator_address, Amount::from_str("5").unwrap()),
+    ]
+    .into_iter()
+    .collect();
+    let responder = std::thread::spawn(move || {
+        match execution_receiver.recv_timeout(Duration::from_millis(1000)) {
+            Ok(MockExecutionControllerMessage::GetFinalAndCandidateBalance {
+                addresses,
+                response_tx,
+            }) => {
+                assert_eq!(addresses.len(), 2);
+                response_tx
+                    .send(
+                        addresses
+                            .iter()
+                            .map(|address| (None, balances.get(address).copied()))
+                            .collect(),
+                    )
+                    .unwrap();
+            }
+            _ => panic!("the creator balances were not requested"),
+        }
+    });
+
+    let op_infos: Vec<OperationInfo> = ops
+        .iter()
+        .map(|op| {
+            OperationInfo::from_op(
+                op,
+                pool_config.operation_validity_periods,
+                pool_config.roll_price,
+                pool_config.thread_count,
+                OperationOrigin::Network,
+            )
+        })
+        .collect();
+    let rejections =
+        operation_pool.validate_operations(&ops.iter().zip(op_infos.iter()).collect::<Vec<_>>());
+    responder.join().unwrap();
+    assert_eq!(
+        rejections,
+        vec![
+            Some(OperationRejection::FeeTooLow {
+                fee: Amount::from_str("1").unwrap(),
+                min_fee: Amount::from_str("2").unwrap()
+            }),
+            Some(OperationRejection::InvalidSignature),
+            Some(OperationRejection::InsufficientBalance {
+                fee: Amount::from_str("5").unwrap(),
+                balance: Amount::from_str("4").unwrap()
+            }),
+            None
+        ]
+    );
 }
 
 /// TODO refactor old tests
@@ -94,14 +380,14 @@ fn test_pool() {
         ops.insert(id, op.clone());
         let mut storage = storage_base.clone_without_refs();
         storage.store_operations(ops.values().cloned().collect());
-        pool.add_operations(storage);
+        pool.add_operations(storage, OperationOrigin::Network);
         //TODO: compare
         // assert_eq!(storage.get_op_refs(), &Set::<OperationId>::default());
 
         // duplicate
         let mut storage = storage_base.clone_without_refs();
         storage.store_operations(ops.values().cloned().collect());
-        pool.add_operations(storage);
+        pool.add_operations(storage, OperationOrigin::Network);
         //TODO: compare
         //assert_eq!(storage.get_op_refs(), &ops.keys().copied().collect::<Set<OperationId>>());
 
@@ -182,7 +468,7 @@ fn test_pool() {
         let op = get_transaction(expire_period, fee);
         let mut storage = Storage::create_root();
         storage.store_operations(vec![op.clone()]);
-        pool.add_operations(storage);
+        pool.add_operations(storage, OperationOrigin::Network);
         //TODO: compare
         //assert_eq!(storage.get_op_refs(), &Set::<OperationId>::default());
         let op_thread = op.creator_address.get_thread(pool_config.thread_count);
@@ -19,7 +19,7 @@ use crate::tests::tools::create_some_operations;
 use crate::tests::tools::pool_test;
 use massa_execution_exports::test_exports::MockExecutionControllerMessage as ControllerMsg;
 use massa_models::address::Address;
-use massa_models::operation::OperationId;
+use massa_models::operation::{OperationId, OperationOrigin};
 use massa_models::prehash::PreHashSet;
 use massa_models::slot::Slot;
 use massa_pool_exports::PoolConfig;
@@ -56,7 +56,7 @@ fn test_simple_get_operations() {
             let creator_address = Address::from_public_key(&keypair.get_public_key());
             let creator_thread = creator_address.get_thread(config.thread_count);
             let unexecuted_ops = storage.get_op_refs().clone();
-            pool_controller.add_operations(storage);
+            pool_controller.add_operations(storage, OperationOrigin::Network);
 
             // start mock execution thread
             std::thread::spawn(move || {
@@ -166,7 +166,7 @@ fn test_get_operations_overflow() {
             storage.store_operations(operations);
 
             let unexecuted_ops = storage.get_op_refs().clone();
-            pool_controller.add_operations(storage);
+            pool_controller.add_operations(storage, OperationOrigin::Network);
 
             // start mock execution thread
             launch_basic_get_block_operation_execution_mock(
@@ -9,8 +9,9 @@ use massa_models::{
     address::Address,
     amount::Amount,
     block::BlockId,
+    config::TESTNET_CHAIN_ID,
     endorsement::{Endorsement, EndorsementSerializer, WrappedEndorsement},
-    operation::{Operation, OperationSerializer, OperationType, WrappedOperation},
+    operation::{Operation, OperationType, WrappedOperation},
     slot::Slot,
     wrapped::WrappedContent,
 };
@@ -38,7 +39,7 @@ pub fn create_operation_with_expire_period(
         op,
         expire_period,
     };
-    Operation::new_wrapped(content, OperationSerializer::new(), keypair).unwrap()
+    Operation::new_signed(content, keypair, TESTNET_CHAIN_ID, 0).unwrap()
 }
 
 /// Return `n` wrapped operations
@@ -94,7 +95,7 @@ pub fn _get_transaction(expire_period: u64, fee: u64) -> WrappedOperation {
         op,
         expire_period,
     };
-    Operation::new_wrapped(content, OperationSerializer::new(), &sender_keypair).unwrap()
+    Operation::new_signed(content, &sender_keypair, TESTNET_CHAIN_ID, 0).unwrap()
 }
 
 /// Creates an endorsement for use in pool tests.
@@ -124,7 +125,7 @@ pub fn _get_transaction_with_addresses(
         op,
         expire_period,
     };
-    Operation::new_wrapped(content, OperationSerializer::new(), sender_keypair).unwrap()
+    Operation::new_signed(content, sender_keypair, TESTNET_CHAIN_ID, 0).unwrap()
 }
 
 pub fn _create_executesc(
@@ -149,5 +150,5 @@ pub fn _create_executesc(
         expire_period,
         op,
     };
-    Operation::new_wrapped(content, OperationSerializer::new(), &keypair).unwrap()
+    Operation::new_signed(content, &keypair, TESTNET_CHAIN_ID, 0).unwrap()
 }
@@ -1,7 +1,7 @@
 use massa_models::{
     address::Address,
     amount::Amount,
-    operation::{OperationId, WrappedOperation},
+    operation::{OperationId, OperationOrigin, WrappedOperation},
 };
 use num::rational::Ratio;
 use std::cmp::Reverse;
@@ -36,6 +36,8 @@ pub struct OperationInfo {
     /// max amount that the op might spend from the sender's balance
     pub max_spending: Amount,
     pub validity_period_range: RangeInclusive<u64>,
+    /// how the op reached the node
+    pub origin: OperationOrigin,
 }
 
 impl OperationInfo {
@@ -44,6 +46,7 @@ impl OperationInfo {
         operation_validity_periods: u64,
         roll_price: Amount,
         thread_count: u8,
+        origin: OperationOrigin,
     ) -> Self {
         OperationInfo {
             id: op.id,
@@ -55,6 +58,7 @@ impl OperationInfo {
             thread: op.creator_address.get_thread(thread_count),
             validity_period_range: op.get_validity_range(operation_validity_periods),
             max_spending: op.get_max_spending(roll_price),
+            origin,
         }
     }
 }
@@ -17,6 +17,7 @@ use std::{
     sync::Arc,
     thread::JoinHandle,
 };
+use tracing::debug;
 
 /// Endorsement pool write thread instance
 pub(crate) struct EndorsementPoolThread {
@@ -53,6 +54,9 @@ impl EndorsementPoolThread {
                 Ok(Command::AddItems(endorsements)) => {
                     self.endorsement_pool.write().add_endorsements(endorsements)
                 }
+                Ok(Command::AddOperations(..)) => {
+                    unreachable!("operations are not sent to the endorsement pool")
+                }
                 Ok(Command::NotifyFinalCsPeriods(final_cs_periods)) => self
                     .endorsement_pool
                     .write()
@@ -94,8 +98,19 @@ impl OperationPoolThread {
             match self.receiver.recv() {
                 Err(RecvError) => break,
                 Ok(Command::Stop) => break,
-                Ok(Command::AddItems(operations)) => {
-                    self.operation_pool.write().add_operations(operations)
+                Ok(Command::AddOperations(operations, origin, span)) => {
+                    let _enter = span.enter();
+                    debug!(
+                        "adding {} {:?} operations to the pool",
+                        operations.get_op_refs().len(),
+                        origin
+                    );
+                    self.operation_pool
+                        .write()
+                        .add_operations(operations, origin)
+                }
+                Ok(Command::AddItems(_)) => {
+                    unreachable!("operations are sent to the operation pool with their origin")
                 }
                 Ok(Command::NotifyFinalCsPeriods(final_cs_periods)) => self
                     .operation_pool
@@ -8,7 +8,7 @@ use std::collections::BTreeMap;
 use crate::PosResult;
 use massa_hash::Hash;
 use massa_models::address::Address;
-use massa_models::api::IndexedSlot;
+use massa_models::api::{AddressDraws, IndexedSlot};
 use massa_models::slot::Slot;
 
 #[cfg(feature = "testing")]
@@ -58,6 +58,16 @@ pub trait SelectorController: Send + Sync {
         end: Slot,
     ) -> PosResult<(Vec<Slot>, Vec<IndexedSlot>)>;
 
+    /// Return the slots where `address` is drawn to produce a block and the slots and indices
+    /// where it is drawn to produce an endorsement, from the `start` slot (included) to the `end` slot (excluded).
+    /// The search stops at the last slot with computed draws, which is returned along with them.
+    fn get_address_draws(
+        &self,
+        address: &Address,
+        start: Slot,
+        end: Slot,
+    ) -> PosResult<AddressDraws>;
+
     /// Get [Address] of the selected block producer for a given slot
     /// # Arguments
     /// * `slot`: target slot of the selection
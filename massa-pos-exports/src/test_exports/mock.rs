@@ -10,7 +10,11 @@ use std::{
 };
 
 use massa_hash::Hash;
-use massa_models::{address::Address, api::IndexedSlot, slot::Slot};
+use massa_models::{
+    address::Address,
+    api::{AddressDraws, IndexedSlot},
+    slot::Slot,
+};
 
 use crate::{PosResult, Selection, SelectorController};
 
@@ -38,6 +42,18 @@ pub enum MockSelectorControllerMessage {
         /// Receiver to send the result to
         response_tx: mpsc::Sender<PosResult<(Vec<Slot>, Vec<IndexedSlot>)>>,
     },
+    /// Get the block and endorsement draws of an address from the start slot to the end slot,
+    /// as far as draws are available.
+    GetAddressDraws {
+        /// Address to search
+        address: Address,
+        /// Start of the search range
+        start: Slot,
+        /// End of the search range
+        end: Slot,
+        /// Receiver to send the result to
+        response_tx: mpsc::Sender<PosResult<AddressDraws>>,
+    },
     /// Get the entire selection of PoS. used for testing only
     GetEntireSelection {
         /// response channel
@@ -148,6 +164,25 @@ impl SelectorController for MockSelectorController {
         response_rx.recv().unwrap()
     }
 
+    fn get_address_draws(
+        &self,
+        address: &Address,
+        start: Slot,
+        end: Slot,
+    ) -> PosResult<AddressDraws> {
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .send(MockSelectorControllerMessage::GetAddressDraws {
+                address: *address,
+                start,
+                end,
+                response_tx,
+            })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
     fn get_producer(&self, slot: Slot) -> PosResult<Address> {
         let (response_tx, response_rx) = mpsc::channel();
         self.0
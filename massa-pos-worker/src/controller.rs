@@ -5,9 +5,13 @@
 
 use std::collections::BTreeMap;
 
-use crate::{Command, DrawCachePtr};
+use crate::{Command, DrawCache, DrawCachePtr};
 use massa_hash::Hash;
-use massa_models::{address::Address, api::IndexedSlot, slot::Slot};
+use massa_models::{
+    address::Address,
+    api::{AddressDraws, IndexedSlot},
+    slot::Slot,
+};
 use massa_pos_exports::{PosError, PosResult, Selection, SelectorController, SelectorManager};
 #[cfg(feature = "testing")]
 use std::collections::{HashMap, VecDeque};
@@ -27,6 +31,53 @@ pub struct SelectorControllerImpl {
     pub(crate) input_mpsc: SyncSender<Command>,
 }
 
+impl SelectorControllerImpl {
+    /// Get the first and last slots of the cycles held by the draw cache, if any
+    fn get_drawn_slots(&self, cache: &DrawCache) -> Option<(Slot, Slot)> {
+        let first_cycle = cache.0.front()?.cycle;
+        let last_cycle = cache.0.back()?.cycle;
+        Some((
+            Slot::new(first_cycle.saturating_mul(self.periods_per_cycle), 0),
+            Slot::new(
+                last_cycle
+                    .saturating_add(1)
+                    .saturating_mul(self.periods_per_cycle)
+                    .saturating_sub(1),
+                self.thread_count.saturating_sub(1),
+            ),
+        ))
+    }
+
+    /// Call `f` on the selection of each slot from `start` (included) to `end` (excluded),
+    /// in order. The range is clamped to the cycles held by the draw cache,
+    /// so that the number of visited slots is bounded by the size of the cache.
+    fn for_each_selection(
+        &self,
+        cache: &DrawCache,
+        start: Slot,
+        end: Slot,
+        mut f: impl FnMut(Slot, &Selection),
+    ) {
+        let (first_slot, last_slot) = match self.get_drawn_slots(cache) {
+            Some(drawn_slots) => drawn_slots,
+            None => return,
+        };
+        let mut slot = std::cmp::max(start, first_slot);
+        while slot < end && slot <= last_slot {
+            if let Some(selection) = cache
+                .get(slot.get_cycle(self.periods_per_cycle))
+                .and_then(|selections| selections.draws.get(&slot))
+            {
+                f(slot, selection);
+            }
+            slot = match slot.get_next_slot(self.thread_count) {
+                Ok(next_slot) => next_slot,
+                _ => break,
+            };
+        }
+    }
+}
+
 impl SelectorController for SelectorControllerImpl {
     /// Waits for draws to reach at least a given cycle number.
     /// Returns the latest cycle number reached (can be higher than `cycle`).
@@ -118,7 +169,7 @@ impl SelectorController for SelectorControllerImpl {
     fn get_address_selections(
         &self,
         address: &Address,
-        mut slot: Slot, /* starting slot */
+        start: Slot,
         end: Slot,
     ) -> PosResult<(Vec<Slot>, Vec<IndexedSlot>)> {
         let (_cache_cv, cache_lock) = &*self.cache;
@@ -126,26 +177,51 @@ impl SelectorController for SelectorControllerImpl {
         let cache = cache_guard.as_ref().map_err(|err| err.clone())?;
         let mut slot_producers = vec![];
         let mut slot_endorsers = vec![];
-        while slot < end {
-            if let Some(selection) = cache
-                .get(slot.get_cycle(self.periods_per_cycle))
-                .and_then(|selections| selections.draws.get(&slot))
-            {
-                if selection.producer == *address {
-                    slot_producers.push(slot);
-                } else if let Some(index) = selection.endorsements.iter().position(|e| e == address)
-                {
-                    slot_endorsers.push(IndexedSlot { slot, index });
-                }
+        self.for_each_selection(cache, start, end, |slot, selection| {
+            if selection.producer == *address {
+                slot_producers.push(slot);
+            } else if let Some(index) = selection.endorsements.iter().position(|e| e == address) {
+                slot_endorsers.push(IndexedSlot { slot, index });
             }
-            slot = match slot.get_next_slot(self.thread_count) {
-                Ok(next_slot) => next_slot,
-                _ => break,
-            };
-        }
+        });
         Ok((slot_producers, slot_endorsers))
     }
 
+    /// Return the slots where `address` is drawn to produce a block and the slots and indices
+    /// where it is drawn to produce an endorsement, from the `start` slot (included) to the `end` slot (excluded).
+    /// The search stops at the last slot with computed draws, which is returned along with them.
+    fn get_address_draws(
+        &self,
+        address: &Address,
+        start: Slot,
+        end: Slot,
+    ) -> PosResult<AddressDraws> {
+        let (_cache_cv, cache_lock) = &*self.cache;
+        let cache_guard = cache_lock.read();
+        let cache = cache_guard.as_ref().map_err(|err| err.clone())?;
+        let mut block_draws = vec![];
+        let mut endorsement_draws = vec![];
+        self.for_each_selection(cache, start, end, |slot, selection| {
+            if selection.producer == *address {
+                block_draws.push(slot);
+            }
+            endorsement_draws.extend(
+                selection
+                    .endorsements
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, e)| *e == address)
+                    .map(|(index, _)| IndexedSlot { slot, index }),
+            );
+        });
+        Ok(AddressDraws {
+            address: *address,
+            block_draws,
+            endorsement_draws,
+            last_drawn_slot: self.get_drawn_slots(cache).map(|(_, last)| last),
+        })
+    }
+
     /// Returns a boxed clone of self.
     /// Allows cloning `Box<dyn SelectorController>`,
     /// see `massa-pos-exports/controller_traits.rs`
@@ -195,3 +271,115 @@ impl SelectorManager for SelectorManagerImpl {
         info!("selector worker stopped");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::SelectorControllerImpl;
+    use crate::{CycleDraws, DrawCache, RwLockCondvar};
+    use massa_models::{address::Address, api::IndexedSlot, slot::Slot};
+    use massa_pos_exports::{Selection, SelectorController};
+    use parking_lot::RwLock;
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::Arc;
+
+    const PERIODS_PER_CYCLE: u64 = 2;
+    const THREAD_COUNT: u8 = 2;
+
+    /// Selector whose cache holds the draws of cycles 2 and 3:
+    /// `producer` produces the blocks of thread 0 and endorses the blocks of thread 1 twice,
+    /// `other` does the opposite
+    fn get_test_selector(producer: Address, other: Address) -> SelectorControllerImpl {
+        let cycle_draws = |cycle: u64| {
+            let mut draws = HashMap::new();
+            for period in cycle * PERIODS_PER_CYCLE..(cycle + 1) * PERIODS_PER_CYCLE {
+                draws.insert(
+                    Slot::new(period, 0),
+                    Selection {
+                        producer,
+                        endorsements: vec![other, other],
+                    },
+                );
+                draws.insert(
+                    Slot::new(period, 1),
+                    Selection {
+                        producer: other,
+                        endorsements: vec![producer, other, producer],
+                    },
+                );
+            }
+            CycleDraws { cycle, draws }
+        };
+        let cache = DrawCache(VecDeque::from(vec![cycle_draws(2), cycle_draws(3)]));
+        SelectorControllerImpl {
+            periods_per_cycle: PERIODS_PER_CYCLE,
+            thread_count: THREAD_COUNT,
+            cache: Arc::new((RwLockCondvar::default(), RwLock::new(Ok(cache)))),
+            input_mpsc: std::sync::mpsc::sync_channel(1).0,
+        }
+    }
+
+    #[test]
+    fn test_address_draws_clamped_to_cache() {
+        let producer = Address::from_bytes(&[1; 32]);
+        let other = Address::from_bytes(&[2; 32]);
+        let selector = get_test_selector(producer, other);
+
+        // a range starting and ending far outside the cache only visits the cached cycles
+        let draws = selector
+            .get_address_draws(&producer, Slot::new(0, 0), Slot::new(u64::MAX, 0))
+            .unwrap();
+        assert_eq!(
+            draws.block_draws,
+            (4..8)
+                .map(|period| Slot::new(period, 0))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            draws.endorsement_draws,
+            (4..8)
+                .flat_map(|period| [0, 2].map(|index| IndexedSlot {
+                    slot: Slot::new(period, 1),
+                    index
+                }))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(draws.last_drawn_slot, Some(Slot::new(7, 1)));
+
+        // the end slot is excluded
+        let draws = selector
+            .get_address_draws(&producer, Slot::new(5, 1), Slot::new(6, 1))
+            .unwrap();
+        assert_eq!(draws.block_draws, vec![Slot::new(6, 0)]);
+        assert_eq!(draws.endorsement_draws.len(), 2);
+
+        // nothing is drawn after the cache
+        let draws = selector
+            .get_address_draws(&producer, Slot::new(8, 0), Slot::new(100, 0))
+            .unwrap();
+        assert!(draws.block_draws.is_empty());
+        assert!(draws.endorsement_draws.is_empty());
+        assert_eq!(draws.last_drawn_slot, Some(Slot::new(7, 1)));
+    }
+
+    #[test]
+    fn test_address_selections_clamped_to_cache() {
+        let producer = Address::from_bytes(&[1; 32]);
+        let other = Address::from_bytes(&[2; 32]);
+        let selector = get_test_selector(producer, other);
+
+        // only the first endorsement index of a slot is returned
+        let (blocks, endorsements) = selector
+            .get_address_selections(&producer, Slot::new(0, 0), Slot::new(u64::MAX, 0))
+            .unwrap();
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(
+            endorsements,
+            (4..8)
+                .map(|period| IndexedSlot {
+                    slot: Slot::new(period, 1),
+                    index: 0
+                })
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -13,11 +13,15 @@ use massa_models::{
 use massa_models::{
     prehash::{PreHashMap, PreHashSet},
     slot::Slot,
+    stats::ProtocolStats,
 };
 use massa_network_exports::NetworkEventReceiver;
 use massa_storage::Storage;
 use serde::Serialize;
-use tokio::{sync::mpsc, task::JoinHandle};
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
 use tracing::{debug, info};
 
 /// Possible types of events that can happen.
@@ -84,6 +88,11 @@ pub enum ProtocolCommand {
     PropagateOperations(Storage),
     /// Propagate endorsements
     PropagateEndorsements(Storage),
+    /// Get the stats of the network event queues
+    GetStats {
+        /// response channel
+        response_tx: oneshot::Sender<ProtocolStats>,
+    },
 }
 
 /// protocol management commands
@@ -157,6 +166,22 @@ impl ProtocolCommandSender {
             })
     }
 
+    /// Propagate a batch of operations from a synchronous context, such as the factory threads.
+    /// See `propagate_operations`.
+    pub fn propagate_operations_blocking(
+        &mut self,
+        operations: Storage,
+    ) -> Result<(), ProtocolError> {
+        massa_trace!("protocol.command_sender.propagate_operations", {
+            "operations": operations.get_op_refs()
+        });
+        self.0
+            .blocking_send(ProtocolCommand::PropagateOperations(operations))
+            .map_err(|_| {
+                ProtocolError::ChannelError("propagate_operation command send error".into())
+            })
+    }
+
     /// propagate endorsements to connected node
     pub fn propagate_endorsements(&mut self, endorsements: Storage) -> Result<(), ProtocolError> {
         massa_trace!("protocol.command_sender.propagate_endorsements", {
@@ -168,6 +193,19 @@ impl ProtocolCommandSender {
                 ProtocolError::ChannelError("propagate_endorsements command send error".into())
             })
     }
+
+    /// get the stats of the network event queues
+    pub async fn get_stats(&self) -> Result<ProtocolStats, ProtocolError> {
+        massa_trace!("protocol.command_sender.get_stats", {});
+        let (response_tx, response_rx) = oneshot::channel();
+        self.0
+            .send(ProtocolCommand::GetStats { response_tx })
+            .await
+            .map_err(|_| ProtocolError::ChannelError("get_stats command send error".into()))?;
+        response_rx
+            .await
+            .map_err(|_| ProtocolError::ChannelError("get_stats response read error".into()))
+    }
 }
 
 /// Protocol event receiver
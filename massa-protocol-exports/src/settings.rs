@@ -37,10 +37,21 @@ pub struct ProtocolConfig {
     pub operation_batch_proc_period: MassaTime,
     /// All operations asked are prune each `operation_asked_pruning_period` millisecond
     pub asked_operations_pruning_period: MassaTime,
+    /// Operations asked to a node and still missing after `operation_ask_timeout` milliseconds are asked to it again
+    pub operation_ask_timeout: MassaTime,
+    /// Maximum number of times missing operations are asked again to a node,
+    /// which is also the maximum number of times operations are sent again to a node asking for them
+    pub max_operation_ask_retries: u32,
     /// Interval at which operations are announced in batches.
     pub operation_announcement_interval: MassaTime,
     /// Maximum of operations sent in one message.
     pub max_operations_per_message: u64,
+    /// Minimal number of signatures in a received batch for them to be verified in parallel
+    pub parallel_sig_verification_threshold: usize,
+    /// chain id of the network, signed along the operations
+    pub chain_id: u64,
+    /// first period from which the operations are signed along the chain id
+    pub chain_id_activation_period: u64,
     /// Maximum size in bytes of all serialized operations size in a block
     pub max_serialized_operations_size_per_block: usize,
     /// Controller channel size
@@ -55,4 +66,29 @@ pub struct ProtocolConfig {
     pub max_operations_propagation_time: MassaTime,
     /// max time we propagate endorsements
     pub max_endorsements_propagation_time: MassaTime,
+    /// Maximum number of queued block, header and connection network events.
+    /// Network events are not read anymore while it is reached.
+    pub max_queued_block_events: usize,
+    /// Maximum number of queued endorsement network events.
+    /// The oldest ones are dropped if overflow.
+    pub max_queued_endorsement_events: usize,
+    /// Maximum number of queued operation network events.
+    /// The oldest ones are dropped if overflow.
+    pub max_queued_operation_events: usize,
+    /// Light mode: only block headers and endorsements are downloaded and checked,
+    /// operations are neither fetched nor executed.
+    pub light_mode: bool,
+    /// Header fast relay: headers are relayed as soon as they are checked instead of once their
+    /// block is integrated, and block bodies are only asked to the first nodes known to have them
+    pub header_fast_relay: bool,
+    /// In fast relay mode, after `block_body_ask_timeout` milliseconds we try to ask the operation
+    /// list or the operations of a block to another node
+    pub block_body_ask_timeout: MassaTime,
+    /// In fast relay mode, a node that did not send a block body in time is asked for it again
+    /// after `block_body_retry_delay` milliseconds
+    pub block_body_retry_delay: MassaTime,
+    /// In fast relay mode, block bodies are only asked to `max_block_body_sources` nodes
+    pub max_block_body_sources: usize,
+    /// In fast relay mode, max number of block bodies asked simultaneously to a node
+    pub max_simultaneous_ask_block_bodies_per_node: usize,
 }
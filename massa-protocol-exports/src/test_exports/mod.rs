@@ -1,3 +1,5 @@
 mod mock;
+mod network_simulator;
 
 pub use mock::*;
+pub use network_simulator::*;
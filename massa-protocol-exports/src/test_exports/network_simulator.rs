@@ -0,0 +1,416 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Deterministic network simulator for multi-node protocol tests.
+//!
+//! Each simulated node gets the network channels a protocol worker is started with.
+//! The commands that the protocol workers send to their network are routed to the targeted nodes
+//! as network events, after the latency of the link between both nodes has elapsed
+//! on a virtual clock, or dropped according to the drop rate of the link.
+//! The virtual clock only moves when the test advances it, and the drops are drawn
+//! from a seeded generator, so that the same scenario always delivers the same messages
+//! in the same order.
+
+use massa_models::{config::CHANNEL_SIZE, node::NodeId};
+use massa_network_exports::{
+    NetworkCommand, NetworkCommandSender, NetworkEvent, NetworkEventReceiver, PeerFeatures,
+};
+use massa_signature::KeyPair;
+use massa_time::MassaTime;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tokio::{
+    sync::mpsc::{self, error::TryRecvError},
+    time::sleep,
+};
+
+/// Characteristics of a simulated link, in one direction
+#[derive(Debug, Clone, Copy)]
+pub struct SimulatedLink {
+    /// virtual time taken by a message to go through the link
+    pub latency: MassaTime,
+    /// probability for a message to be lost, in parts per thousand
+    pub drop_per_mille: u16,
+}
+
+impl Default for SimulatedLink {
+    fn default() -> Self {
+        SimulatedLink {
+            latency: MassaTime::from_millis(0),
+            drop_per_mille: 0,
+        }
+    }
+}
+
+/// Network side of a simulated node
+struct SimulatedNode {
+    /// id of the node
+    id: NodeId,
+    /// commands sent by the protocol worker of the node
+    network_command_rx: mpsc::Receiver<NetworkCommand>,
+    /// events received by the protocol worker of the node
+    network_event_tx: mpsc::Sender<NetworkEvent>,
+}
+
+/// Simulates the network between several in-process protocol workers
+pub struct NetworkSimulator {
+    /// simulated nodes, by index
+    nodes: Vec<SimulatedNode>,
+    /// index of the simulated nodes, by id
+    node_indices: HashMap<NodeId, usize>,
+    /// link used between two nodes when no specific one is set
+    default_link: SimulatedLink,
+    /// specific links, by (sender index, receiver index)
+    links: HashMap<(usize, usize), SimulatedLink>,
+    /// connected node pairs, lowest index first
+    connections: HashSet<(usize, usize)>,
+    /// messages going through the links, by (delivery time, sequence number), with their receiver index
+    in_flight: BTreeMap<(MassaTime, u64), (usize, NetworkEvent)>,
+    /// sequence number of the next message, ordering the messages delivered at the same time
+    next_sequence: u64,
+    /// current virtual time
+    now: MassaTime,
+    /// state of the generator drawing the message drops
+    rng_state: u64,
+    /// real time given to the protocol workers to react to the delivered messages
+    settle_time: MassaTime,
+    /// number of messages delivered so far
+    delivered_count: u64,
+    /// number of messages dropped so far, by the links or for lack of connection
+    dropped_count: u64,
+}
+
+impl NetworkSimulator {
+    /// Creates a simulator without nodes
+    ///
+    /// # Arguments
+    /// * `seed`: seed of the generator drawing the message drops
+    /// * `default_link`: link used between two nodes when no specific one is set
+    pub fn new(seed: u64, default_link: SimulatedLink) -> Self {
+        NetworkSimulator {
+            nodes: Vec::new(),
+            node_indices: HashMap::new(),
+            default_link,
+            links: HashMap::new(),
+            connections: HashSet::new(),
+            in_flight: BTreeMap::new(),
+            next_sequence: 0,
+            now: MassaTime::from_millis(0),
+            // the xorshift generator must not start from 0
+            rng_state: (seed ^ 0x9E37_79B9_7F4A_7C15).max(1),
+            settle_time: MassaTime::from_millis(20),
+            delivered_count: 0,
+            dropped_count: 0,
+        }
+    }
+
+    /// Sets the real time given to the protocol workers to react to the delivered messages
+    /// before collecting their commands. The order of the messages is only reproducible
+    /// if the workers react within that time.
+    pub fn set_settle_time(&mut self, settle_time: MassaTime) {
+        self.settle_time = settle_time;
+    }
+
+    /// Adds a node to the simulated network
+    ///
+    /// # Returns
+    /// The id of the node, and the network channels to start its protocol worker with
+    pub fn add_node(&mut self) -> (NodeId, NetworkCommandSender, NetworkEventReceiver) {
+        let (network_command_tx, network_command_rx) =
+            mpsc::channel::<NetworkCommand>(CHANNEL_SIZE);
+        let (network_event_tx, network_event_rx) = mpsc::channel::<NetworkEvent>(CHANNEL_SIZE);
+        let id = NodeId(KeyPair::generate().get_public_key());
+        self.node_indices.insert(id, self.nodes.len());
+        self.nodes.push(SimulatedNode {
+            id,
+            network_command_rx,
+            network_event_tx,
+        });
+        (
+            id,
+            NetworkCommandSender(network_command_tx),
+            NetworkEventReceiver(network_event_rx),
+        )
+    }
+
+    /// Sets the link used by the messages going from `from` to `to`
+    pub fn set_link(&mut self, from: NodeId, to: NodeId, link: SimulatedLink) {
+        let (from, to) = (self.index(&from), self.index(&to));
+        self.links.insert((from, to), link);
+    }
+
+    /// Connects two nodes: both of them are notified of the new connection right away
+    pub async fn connect(&mut self, a: NodeId, b: NodeId) {
+        let (a, b) = (self.index(&a), self.index(&b));
+        if self.connections.insert((a.min(b), a.max(b))) {
+            let (a_id, b_id) = (self.nodes[a].id, self.nodes[b].id);
+            self.deliver(
+                a,
+                NetworkEvent::NewConnection(b_id, PeerFeatures::default()),
+            )
+            .await;
+            self.deliver(
+                b,
+                NetworkEvent::NewConnection(a_id, PeerFeatures::default()),
+            )
+            .await;
+        }
+    }
+
+    /// Disconnects two nodes: both of them are notified of the closed connection right away,
+    /// and the messages still going through their links are lost
+    pub async fn disconnect(&mut self, a: NodeId, b: NodeId) {
+        let (a, b) = (self.index(&a), self.index(&b));
+        if self.close_connection(a, b) {
+            let (a_id, b_id) = (self.nodes[a].id, self.nodes[b].id);
+            self.deliver(a, NetworkEvent::ConnectionClosed(b_id)).await;
+            self.deliver(b, NetworkEvent::ConnectionClosed(a_id)).await;
+        }
+    }
+
+    /// Current virtual time, starting at 0
+    pub fn now(&self) -> MassaTime {
+        self.now
+    }
+
+    /// Number of messages delivered so far
+    pub fn delivered_count(&self) -> u64 {
+        self.delivered_count
+    }
+
+    /// Number of messages dropped so far, by the links or for lack of connection
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped_count
+    }
+
+    /// Number of messages still going through the links
+    pub fn in_flight_count(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Moves the virtual clock forward, delivering the messages whose latency elapses meanwhile,
+    /// including the ones the protocol workers send in reaction to the delivered messages.
+    pub async fn advance(&mut self, duration: MassaTime) {
+        let target = self.now.saturating_add(duration);
+        loop {
+            self.collect_commands().await;
+            let delivery_time = match self.in_flight.keys().next() {
+                Some((time, _)) if *time <= target => *time,
+                _ => break,
+            };
+            self.now = delivery_time;
+            // deliver all the messages due at that time, the ones sent in reaction to them
+            // without latency are collected and delivered at the next iteration
+            while let Some(entry) = self.in_flight.first_entry() {
+                if entry.key().0 > self.now {
+                    break;
+                }
+                let (to, event) = entry.remove();
+                self.delivered_count += 1;
+                self.deliver(to, event).await;
+            }
+        }
+        self.now = target;
+    }
+
+    /// Moves the virtual clock forward until no message is going through the links anymore,
+    /// or until `max_duration` has elapsed
+    ///
+    /// # Returns
+    /// Whether the network became idle
+    pub async fn run_until_idle(&mut self, max_duration: MassaTime) -> bool {
+        let deadline = self.now.saturating_add(max_duration);
+        loop {
+            self.collect_commands().await;
+            let next_time = match self.in_flight.keys().next() {
+                Some((time, _)) => *time,
+                None => return true,
+            };
+            if next_time > deadline {
+                self.now = deadline;
+                return false;
+            }
+            self.advance(next_time.saturating_sub(self.now)).await;
+        }
+    }
+
+    /// Gets the index of a simulated node
+    fn index(&self, node_id: &NodeId) -> usize {
+        *self
+            .node_indices
+            .get(node_id)
+            .expect("node absent from the simulated network")
+    }
+
+    /// Removes a connection, along with the messages going through its links
+    ///
+    /// # Returns
+    /// Whether the nodes were connected
+    fn close_connection(&mut self, a: usize, b: usize) -> bool {
+        if !self.connections.remove(&(a.min(b), a.max(b))) {
+            return false;
+        }
+        let (a_id, b_id) = (self.nodes[a].id, self.nodes[b].id);
+        let before = self.in_flight.len();
+        self.in_flight.retain(|_, (to, event)| {
+            let from = match event {
+                NetworkEvent::ReceivedBlockInfo { node, .. }
+                | NetworkEvent::AskedForBlocks { node, .. }
+                | NetworkEvent::ReceivedOperations { node, .. }
+                | NetworkEvent::ReceivedOperationAnnouncements { node, .. }
+                | NetworkEvent::ReceiveAskForOperations { node, .. }
+                | NetworkEvent::ReceivedEndorsements { node, .. } => *node,
+                NetworkEvent::ReceivedBlockHeader { source_node_id, .. } => *source_node_id,
+                NetworkEvent::NewConnection(..) | NetworkEvent::ConnectionClosed(_) => return true,
+            };
+            !((*to == a && from == b_id) || (*to == b && from == a_id))
+        });
+        self.dropped_count += (before - self.in_flight.len()) as u64;
+        true
+    }
+
+    /// Gives the protocol workers time to react, then routes the commands they sent,
+    /// node after node in index order
+    async fn collect_commands(&mut self) {
+        sleep(self.settle_time.to_duration()).await;
+        for from in 0..self.nodes.len() {
+            loop {
+                match self.nodes[from].network_command_rx.try_recv() {
+                    Ok(cmd) => self.route_command(from, cmd),
+                    Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break,
+                }
+            }
+        }
+    }
+
+    /// Turns a command sent by a node into the events received by the targeted nodes
+    fn route_command(&mut self, from: usize, cmd: NetworkCommand) {
+        let from_id = self.nodes[from].id;
+        match cmd {
+            NetworkCommand::AskForBlocks { list } => {
+                let mut list: Vec<_> = list.into_iter().collect();
+                list.sort_unstable_by_key(|(node, _)| self.node_indices.get(node).copied());
+                for (to, list) in list {
+                    self.send(
+                        from,
+                        to,
+                        NetworkEvent::AskedForBlocks {
+                            node: from_id,
+                            list,
+                        },
+                    );
+                }
+            }
+            NetworkCommand::SendBlockInfo { node, info } => self.send(
+                from,
+                node,
+                NetworkEvent::ReceivedBlockInfo {
+                    node: from_id,
+                    info,
+                },
+            ),
+            NetworkCommand::SendBlockHeader { node, header } => self.send(
+                from,
+                node,
+                NetworkEvent::ReceivedBlockHeader {
+                    source_node_id: from_id,
+                    header,
+                },
+            ),
+            NetworkCommand::SendEndorsements { node, endorsements } => self.send(
+                from,
+                node,
+                NetworkEvent::ReceivedEndorsements {
+                    node: from_id,
+                    endorsements,
+                },
+            ),
+            NetworkCommand::SendOperations { node, operations } => self.send(
+                from,
+                node,
+                NetworkEvent::ReceivedOperations {
+                    node: from_id,
+                    operations,
+                },
+            ),
+            NetworkCommand::SendOperationAnnouncements { to_node, batch } => self.send(
+                from,
+                to_node,
+                NetworkEvent::ReceivedOperationAnnouncements {
+                    node: from_id,
+                    operation_prefix_ids: batch.into_iter().map(|prefix| (prefix, 0)).collect(),
+                },
+            ),
+            NetworkCommand::SendOperationFeeAnnouncements { to_node, batch } => self.send(
+                from,
+                to_node,
+                NetworkEvent::ReceivedOperationAnnouncements {
+                    node: from_id,
+                    operation_prefix_ids: batch,
+                },
+            ),
+            NetworkCommand::AskForOperations { to_node, wishlist } => self.send(
+                from,
+                to_node,
+                NetworkEvent::ReceiveAskForOperations {
+                    node: from_id,
+                    operation_prefix_ids: wishlist,
+                },
+            ),
+            NetworkCommand::NodeBanByIds(ids) => {
+                // a ban closes the connection, notified to both nodes without latency
+                for id in ids {
+                    if let Some(to) = self.node_indices.get(&id).copied() {
+                        if self.close_connection(from, to) {
+                            self.schedule(from, NetworkEvent::ConnectionClosed(id), self.now);
+                            self.schedule(to, NetworkEvent::ConnectionClosed(from_id), self.now);
+                        }
+                    }
+                }
+            }
+            // the other commands query or configure the local network worker,
+            // which is not simulated: their response channels are dropped
+            _ => {}
+        }
+    }
+
+    /// Sends a message through the link from `from` to `to`, unless the nodes are not connected
+    /// or the link drops it
+    fn send(&mut self, from: usize, to: NodeId, event: NetworkEvent) {
+        let to = match self.node_indices.get(&to) {
+            Some(to) if self.connections.contains(&(from.min(*to), from.max(*to))) => *to,
+            _ => {
+                self.dropped_count += 1;
+                return;
+            }
+        };
+        let link = self
+            .links
+            .get(&(from, to))
+            .copied()
+            .unwrap_or(self.default_link);
+        if self.next_random() % 1000 < link.drop_per_mille as u64 {
+            self.dropped_count += 1;
+            return;
+        }
+        self.schedule(to, event, self.now.saturating_add(link.latency));
+    }
+
+    /// Schedules the delivery of an event to a node
+    fn schedule(&mut self, to: usize, event: NetworkEvent, delivery_time: MassaTime) {
+        self.in_flight
+            .insert((delivery_time, self.next_sequence), (to, event));
+        self.next_sequence += 1;
+    }
+
+    /// Delivers an event to a node. Events sent to a stopped node are lost.
+    async fn deliver(&mut self, to: usize, event: NetworkEvent) {
+        let _ = self.nodes[to].network_event_tx.send(event).await;
+    }
+
+    /// Draws the next number of the xorshift64* generator
+    fn next_random(&mut self) -> u64 {
+        self.rng_state ^= self.rng_state >> 12;
+        self.rng_state ^= self.rng_state << 25;
+        self.rng_state ^= self.rng_state >> 27;
+        self.rng_state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
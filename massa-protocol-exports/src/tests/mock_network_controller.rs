@@ -11,7 +11,7 @@ use massa_models::{
 };
 use massa_network_exports::{
     AskForBlocksInfo, BlockInfoReply, NetworkCommand, NetworkCommandSender, NetworkEvent,
-    NetworkEventReceiver,
+    NetworkEventReceiver, PeerFeatures,
 };
 use massa_time::MassaTime;
 use tokio::{sync::mpsc, time::sleep};
@@ -58,8 +58,18 @@ impl MockNetworkController {
 
     /// new connection
     pub async fn new_connection(&mut self, new_node_id: NodeId) {
+        self.new_connection_with_features(new_node_id, PeerFeatures::default())
+            .await;
+    }
+
+    /// new connection, with the features negotiated with the node
+    pub async fn new_connection_with_features(
+        &mut self,
+        new_node_id: NodeId,
+        features: PeerFeatures,
+    ) {
         self.network_event_tx
-            .send(NetworkEvent::NewConnection(new_node_id))
+            .send(NetworkEvent::NewConnection(new_node_id, features))
             .await
             .expect("Couldn't connect node to protocol.");
     }
@@ -110,7 +120,28 @@ impl MockNetworkController {
         self.network_event_tx
             .send(NetworkEvent::ReceivedOperationAnnouncements {
                 node: source_node_id,
-                operation_prefix_ids: operation_ids.iter().map(|id| id.into_prefix()).collect(),
+                operation_prefix_ids: operation_ids
+                    .iter()
+                    .map(|id| (id.into_prefix(), 0))
+                    .collect(),
+            })
+            .await
+            .expect("Couldn't send operations to protocol.");
+    }
+
+    /// send operation ids along their fee density
+    pub async fn send_operation_announcements(
+        &mut self,
+        source_node_id: NodeId,
+        operations: Vec<(OperationId, u64)>,
+    ) {
+        self.network_event_tx
+            .send(NetworkEvent::ReceivedOperationAnnouncements {
+                node: source_node_id,
+                operation_prefix_ids: operations
+                    .iter()
+                    .map(|(id, fee_density)| (id.into_prefix(), *fee_density))
+                    .collect(),
             })
             .await
             .expect("Couldn't send operations to protocol.");
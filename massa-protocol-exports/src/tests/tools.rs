@@ -5,13 +5,13 @@ use crate::protocol_controller::{ProtocolCommandSender, ProtocolEventReceiver};
 use crate::{ProtocolConfig, ProtocolEvent};
 use massa_hash::Hash;
 use massa_models::node::NodeId;
-use massa_models::operation::OperationSerializer;
 use massa_models::prehash::PreHashSet;
 use massa_models::wrapped::WrappedContent;
 use massa_models::{
     address::Address,
     amount::Amount,
     block::{Block, BlockHeader, BlockHeaderSerializer, BlockId, BlockSerializer, WrappedBlock},
+    config::TESTNET_CHAIN_ID,
     endorsement::{Endorsement, EndorsementSerializerLW, WrappedEndorsement},
     operation::{Operation, OperationType, WrappedOperation},
     slot::Slot,
@@ -250,7 +250,7 @@ pub fn create_operation_with_expire_period(
         op,
         expire_period,
     };
-    Operation::new_wrapped(content, OperationSerializer::new(), keypair).unwrap()
+    Operation::new_signed(content, keypair, TESTNET_CHAIN_ID, 0).unwrap()
 }
 
 lazy_static::lazy_static! {
@@ -275,8 +275,13 @@ pub fn create_protocol_config() -> ProtocolConfig {
         operation_announcement_buffer_capacity: 1000,
         operation_batch_proc_period: 200.into(),
         asked_operations_pruning_period: 500.into(),
+        operation_ask_timeout: 1000.into(),
+        max_operation_ask_retries: 2,
         operation_announcement_interval: 150.into(),
         max_operations_per_message: 1024,
+        parallel_sig_verification_threshold: 64,
+        chain_id: TESTNET_CHAIN_ID,
+        chain_id_activation_period: 0,
         thread_count: 32,
         max_serialized_operations_size_per_block: 1024,
         controller_channel_size: 1024,
@@ -285,6 +290,15 @@ pub fn create_protocol_config() -> ProtocolConfig {
         t0: MassaTime::from_millis(16000),
         max_operations_propagation_time: MassaTime::from_millis(30000),
         max_endorsements_propagation_time: MassaTime::from_millis(60000),
+        max_queued_block_events: 1024,
+        max_queued_endorsement_events: 1024,
+        max_queued_operation_events: 1024,
+        light_mode: false,
+        header_fast_relay: false,
+        block_body_ask_timeout: 500.into(),
+        block_body_retry_delay: 500.into(),
+        max_block_body_sources: 2,
+        max_simultaneous_ask_block_bodies_per_node: 10,
     }
 }
 
@@ -2,8 +2,11 @@
 
 //! Simple unreliable, but fast cache implementations
 
+use massa_models::endorsement::EndorsementId;
+use massa_models::node::NodeId;
 use massa_models::prehash::{CapacityAllocator, PreHashMap, PreHashSet, PreHashed};
-use std::collections::{hash_map, VecDeque};
+use massa_models::stats::EndorsementCacheStats;
+use std::collections::{hash_map, HashMap, VecDeque};
 
 /// Structure holding a finite capacity cache set that is entirely cleared when full.
 /// Supports efficient deletion.
@@ -293,3 +296,115 @@ impl<K: PreHashed + std::hash::Hash + Clone + Copy + PartialEq + Eq, V> LinearHa
         });
     }
 }
+
+/// Endorsement propagation cache.
+/// Remembers the endorsements whose signature was already checked, and which endorsements each peer knows,
+/// either because it sent them to us, included them in a block header, or because we sent them to it.
+/// Known endorsements are not sent again to a peer.
+#[derive(Debug, Clone)]
+pub struct EndorsementCache {
+    /// Linear cache of checked endorsement IDs
+    checked: LinearHashCacheSet<EndorsementId>,
+    /// Linear cache of the endorsement IDs known by each peer
+    known_by_node: HashMap<NodeId, LinearHashCacheSet<EndorsementId>>,
+    /// Capacity of the cache of each peer
+    node_capacity: usize,
+    /// Counters exported in the protocol stats
+    stats: EndorsementCacheStats,
+}
+
+impl EndorsementCache {
+    /// Create a new endorsement cache
+    ///
+    /// # Arguments
+    /// * `capacity`: maximum number of checked endorsements remembered
+    /// * `node_capacity`: maximum number of endorsements remembered as known by each peer
+    pub fn new(capacity: usize, node_capacity: usize) -> Self {
+        EndorsementCache {
+            checked: LinearHashCacheSet::new(capacity),
+            known_by_node: Default::default(),
+            node_capacity,
+            stats: Default::default(),
+        }
+    }
+
+    /// Counts endorsements received from a peer, and returns whether the signature of each of them
+    /// still needs to be checked
+    pub fn note_received<'a, I: IntoIterator<Item = &'a EndorsementId>>(
+        &mut self,
+        endorsement_ids: I,
+    ) -> Vec<bool> {
+        endorsement_ids
+            .into_iter()
+            .map(|endorsement_id| {
+                self.stats.received_endorsements =
+                    self.stats.received_endorsements.saturating_add(1);
+                let unchecked = !self.checked.contains(endorsement_id);
+                if !unchecked {
+                    self.stats.duplicate_endorsements =
+                        self.stats.duplicate_endorsements.saturating_add(1);
+                }
+                unchecked
+            })
+            .collect()
+    }
+
+    /// Note endorsements as checked
+    pub fn insert_checked<I: IntoIterator<Item = EndorsementId>>(&mut self, endorsement_ids: I) {
+        self.checked.try_extend(endorsement_ids);
+    }
+
+    /// Note endorsements as known by a peer
+    pub fn insert_known<I: IntoIterator<Item = EndorsementId>>(
+        &mut self,
+        node_id: NodeId,
+        endorsement_ids: I,
+    ) {
+        let node_capacity = self.node_capacity;
+        self.known_by_node
+            .entry(node_id)
+            .or_insert_with(|| LinearHashCacheSet::new(node_capacity))
+            .try_extend(endorsement_ids);
+    }
+
+    /// Selects the endorsements to send to a peer, skipping the ones it already knows,
+    /// and notes the selected ones as known by the peer
+    pub fn select_for_sending<'a, I: IntoIterator<Item = &'a EndorsementId>>(
+        &mut self,
+        node_id: NodeId,
+        endorsement_ids: I,
+    ) -> Vec<EndorsementId> {
+        let node_capacity = self.node_capacity;
+        let known = self
+            .known_by_node
+            .entry(node_id)
+            .or_insert_with(|| LinearHashCacheSet::new(node_capacity));
+        let mut to_send = Vec::new();
+        for endorsement_id in endorsement_ids {
+            if known.contains(endorsement_id) {
+                self.stats.skipped_sends = self.stats.skipped_sends.saturating_add(1);
+            } else {
+                to_send.push(*endorsement_id);
+            }
+        }
+        known.try_extend(to_send.iter().copied());
+        self.stats.sent_endorsements = self
+            .stats
+            .sent_endorsements
+            .saturating_add(to_send.len() as u64);
+        to_send
+    }
+
+    /// Forget the endorsements known by a peer, once disconnected
+    pub fn remove_node(&mut self, node_id: &NodeId) {
+        self.known_by_node.remove(node_id);
+    }
+
+    /// Get the cache counters
+    pub fn get_stats(&self) -> EndorsementCacheStats {
+        EndorsementCacheStats {
+            tracked_nodes: self.known_by_node.len(),
+            ..self.stats
+        }
+    }
+}
@@ -0,0 +1,194 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Prioritized queues of the network events waiting to be processed.
+//!
+//! Network events are split in three priority classes: blocks (with headers and connections),
+//! endorsements and operations. The oldest event of the highest priority non-empty class is always
+//! processed first, so that operation floods do not delay block propagation.
+//!
+//! The endorsement and operation queues drop their oldest event when full.
+//! The block queue never drops events: the worker stops reading the network while it is full.
+
+use massa_models::stats::ProtocolStats;
+use massa_network_exports::NetworkEvent;
+use massa_protocol_exports::ProtocolConfig;
+use std::collections::VecDeque;
+
+/// Priority class of a network event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EventPriority {
+    /// blocks, headers and connections
+    Blocks,
+    /// endorsements
+    Endorsements,
+    /// operations and operation announcements
+    Operations,
+}
+
+impl EventPriority {
+    fn of(event: &NetworkEvent) -> Self {
+        match event {
+            NetworkEvent::NewConnection(..)
+            | NetworkEvent::ConnectionClosed(_)
+            | NetworkEvent::ReceivedBlockInfo { .. }
+            | NetworkEvent::AskedForBlocks { .. }
+            | NetworkEvent::ReceivedBlockHeader { .. } => EventPriority::Blocks,
+            NetworkEvent::ReceivedEndorsements { .. } => EventPriority::Endorsements,
+            NetworkEvent::ReceivedOperations { .. }
+            | NetworkEvent::ReceivedOperationAnnouncements { .. }
+            | NetworkEvent::ReceiveAskForOperations { .. } => EventPriority::Operations,
+        }
+    }
+}
+
+/// Network events waiting to be processed, by priority class
+pub(crate) struct NetworkEventQueues {
+    /// block, header and connection events
+    blocks: VecDeque<NetworkEvent>,
+    /// endorsement events
+    endorsements: VecDeque<NetworkEvent>,
+    /// operation events
+    operations: VecDeque<NetworkEvent>,
+    /// maximum number of queued block events
+    max_block_events: usize,
+    /// maximum number of queued endorsement events
+    max_endorsement_events: usize,
+    /// maximum number of queued operation events
+    max_operation_events: usize,
+    /// number of endorsement events dropped because their queue was full
+    shed_endorsement_events: u64,
+    /// number of operation events dropped because their queue was full
+    shed_operation_events: u64,
+}
+
+impl NetworkEventQueues {
+    /// Creates empty queues sized from the protocol configuration
+    pub fn new(config: &ProtocolConfig) -> Self {
+        NetworkEventQueues {
+            blocks: VecDeque::new(),
+            endorsements: VecDeque::new(),
+            operations: VecDeque::new(),
+            max_block_events: config.max_queued_block_events,
+            max_endorsement_events: config.max_queued_endorsement_events,
+            max_operation_events: config.max_queued_operation_events,
+            shed_endorsement_events: 0,
+            shed_operation_events: 0,
+        }
+    }
+
+    /// Returns true if new network events can be queued: the block queue must not be full
+    pub fn can_receive(&self) -> bool {
+        self.blocks.len() < self.max_block_events
+    }
+
+    /// Returns true if no event is waiting to be processed
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty() && self.endorsements.is_empty() && self.operations.is_empty()
+    }
+
+    /// Queues an event, dropping the oldest event of its class if it is a full endorsement or operation class
+    pub fn push(&mut self, event: NetworkEvent) {
+        let (queue, max_events, shed_count) = match EventPriority::of(&event) {
+            EventPriority::Blocks => {
+                self.blocks.push_back(event);
+                return;
+            }
+            EventPriority::Endorsements => (
+                &mut self.endorsements,
+                self.max_endorsement_events,
+                &mut self.shed_endorsement_events,
+            ),
+            EventPriority::Operations => (
+                &mut self.operations,
+                self.max_operation_events,
+                &mut self.shed_operation_events,
+            ),
+        };
+        queue.push_back(event);
+        while queue.len() > max_events {
+            queue.pop_front();
+            *shed_count = shed_count.saturating_add(1);
+        }
+    }
+
+    /// Takes the oldest event of the highest priority non-empty class
+    pub fn pop(&mut self) -> Option<NetworkEvent> {
+        self.blocks
+            .pop_front()
+            .or_else(|| self.endorsements.pop_front())
+            .or_else(|| self.operations.pop_front())
+    }
+
+    /// Returns the current depths of the queues and the number of dropped events.
+    /// The endorsement cache counters are left to the protocol worker.
+    pub fn get_stats(&self) -> ProtocolStats {
+        ProtocolStats {
+            block_queue_depth: self.blocks.len(),
+            endorsement_queue_depth: self.endorsements.len(),
+            operation_queue_depth: self.operations.len(),
+            shed_endorsement_events: self.shed_endorsement_events,
+            shed_operation_events: self.shed_operation_events,
+            endorsement_cache: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_models::node::NodeId;
+    use massa_protocol_exports::tests::tools::create_protocol_config;
+    use massa_signature::KeyPair;
+
+    #[test]
+    fn test_network_event_queues() {
+        let mut config = create_protocol_config();
+        config.max_queued_block_events = 2;
+        config.max_queued_operation_events = 1;
+        let mut queues = NetworkEventQueues::new(&config);
+        let node = NodeId(KeyPair::generate().get_public_key());
+        let other_node = NodeId(KeyPair::generate().get_public_key());
+
+        // operations are queued first but processed last, and only the newest one is kept
+        queues.push(NetworkEvent::ReceivedOperations {
+            node,
+            operations: Vec::new(),
+        });
+        queues.push(NetworkEvent::ReceivedOperations {
+            node: other_node,
+            operations: Vec::new(),
+        });
+        queues.push(NetworkEvent::ReceivedEndorsements {
+            node,
+            endorsements: Vec::new(),
+        });
+        queues.push(NetworkEvent::NewConnection(node, Default::default()));
+        assert!(queues.can_receive());
+        queues.push(NetworkEvent::ConnectionClosed(node));
+        assert!(!queues.can_receive());
+        let stats = queues.get_stats();
+        assert_eq!(stats.block_queue_depth, 2);
+        assert_eq!(stats.endorsement_queue_depth, 1);
+        assert_eq!(stats.operation_queue_depth, 1);
+        assert_eq!(stats.shed_operation_events, 1);
+
+        assert!(matches!(
+            queues.pop(),
+            Some(NetworkEvent::NewConnection(..))
+        ));
+        assert!(matches!(
+            queues.pop(),
+            Some(NetworkEvent::ConnectionClosed(_))
+        ));
+        assert!(matches!(
+            queues.pop(),
+            Some(NetworkEvent::ReceivedEndorsements { .. })
+        ));
+        assert!(matches!(
+            queues.pop(),
+            Some(NetworkEvent::ReceivedOperations { node, .. }) if node == other_node
+        ));
+        assert!(queues.pop().is_none());
+        assert!(queues.is_empty());
+    }
+}
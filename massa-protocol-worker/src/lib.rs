@@ -16,6 +16,7 @@ pub mod worker_operations_impl;
 pub use protocol_worker::start_protocol_controller;
 mod cache;
 mod checked_operations;
+mod event_queues;
 mod node_info;
 mod protocol_network;
 mod sig_verifier;
@@ -5,14 +5,17 @@
 //! # Operations
 //! Same as for wanted/known blocks, we remember here in cache which node asked
 //! for operations and which operations he seem to already know.
+//! We also count how many times each operation was sent to a node on request,
+//! to bound the retransmissions to a node asking again for missing operations.
 
+use massa_models::block::BlockId;
 use massa_models::operation::OperationPrefixId;
 use massa_models::prehash::{CapacityAllocator, PreHashMap};
-use massa_models::{block::BlockId, endorsement::EndorsementId};
+use massa_network_exports::PeerFeatures;
 use massa_protocol_exports::ProtocolConfig;
 use tokio::time::Instant;
 
-use crate::cache::LinearHashCacheSet;
+use crate::cache::{LinearHashCacheMap, LinearHashCacheSet};
 
 /// Information about a node we are connected to,
 /// essentially our view of its state.
@@ -21,30 +24,40 @@ pub(crate) struct NodeInfo {
     /// The blocks the node "knows about",
     /// defined as the one the node propagated headers to us for.
     pub(crate) known_blocks: PreHashMap<BlockId, (bool, Instant)>,
+    /// Blocks whose header we sent to that node
+    sent_headers: LinearHashCacheSet<BlockId>,
     /// Blocks we asked that node for
     pub asked_blocks: PreHashMap<BlockId, Instant>,
     /// Instant when the node was added
     pub connection_instant: Instant,
     /// all known operations (prefix-based)
     known_operations: LinearHashCacheSet<OperationPrefixId>,
-    /// all known endorsements
-    known_endorsements: LinearHashCacheSet<EndorsementId>,
+    /// number of times operations were sent to the node on request (prefix-based)
+    sent_operations: LinearHashCacheMap<OperationPrefixId, u32>,
+    /// features supported by both the node and us, negotiated during the handshake
+    features: PeerFeatures,
 }
 
 impl NodeInfo {
     /// Creates empty node info
-    pub fn new(pool_settings: &ProtocolConfig) -> NodeInfo {
+    pub fn new(pool_settings: &ProtocolConfig, features: PeerFeatures) -> NodeInfo {
         NodeInfo {
             known_blocks: PreHashMap::with_capacity(pool_settings.max_node_known_blocks_size),
+            sent_headers: LinearHashCacheSet::new(pool_settings.max_node_known_blocks_size),
             asked_blocks: Default::default(),
             connection_instant: Instant::now(),
             known_operations: LinearHashCacheSet::new(pool_settings.max_node_known_ops_size),
-            known_endorsements: LinearHashCacheSet::new(
-                pool_settings.max_node_known_endorsements_size,
-            ),
+            sent_operations: LinearHashCacheMap::new(pool_settings.max_node_known_ops_size),
+            features,
         }
     }
 
+    /// Returns true if `feature` can be used when sending messages to the node.
+    /// To be checked before sending a message whose encoding depends on a feature.
+    pub fn supports(&self, feature: PeerFeatures) -> bool {
+        self.features.contains(feature)
+    }
+
     /// Get boolean if block knows about the block and when this information was got
     /// in a option if we don't know if that node knows that block or not
     pub fn get_known_block(&self, block_id: &BlockId) -> Option<&(bool, Instant)> {
@@ -87,15 +100,10 @@ impl NodeInfo {
         self.remove_excess_known_blocks(max_node_known_blocks_size);
     }
 
-    pub fn insert_known_endorsements<I: IntoIterator<Item = EndorsementId>>(
-        &mut self,
-        endorsements: I,
-    ) {
-        self.known_endorsements.try_extend(endorsements);
-    }
-
-    pub fn knows_endorsement(&self, endorsement_id: &EndorsementId) -> bool {
-        self.known_endorsements.contains(endorsement_id)
+    /// Notes that the header of a block is sent to the node.
+    /// Returns `false` if it was already sent.
+    pub fn try_note_sent_header(&mut self, block_id: BlockId) -> bool {
+        self.sent_headers.try_insert(block_id)
     }
 
     pub fn insert_known_ops<I: IntoIterator<Item = OperationPrefixId>>(&mut self, ops: I) {
@@ -105,4 +113,20 @@ impl NodeInfo {
     pub fn knows_op(&self, op: &OperationPrefixId) -> bool {
         self.known_operations.contains(op)
     }
+
+    /// Notes that an operation asked by the node is sent to it.
+    /// Returns `false`, without noting anything, if it was already sent `max_sends` times.
+    pub fn try_note_sent_op(&mut self, op: OperationPrefixId, max_sends: u32) -> bool {
+        match self.sent_operations.get_mut(&op) {
+            Some(sends) if *sends >= max_sends => false,
+            Some(sends) => {
+                *sends += 1;
+                true
+            }
+            None => {
+                self.sent_operations.insert(op, 1);
+                true
+            }
+        }
+    }
 }
@@ -50,15 +50,16 @@ impl ProtocolWorker {
         op_timer: &mut Pin<&mut Sleep>,
     ) -> Result<(), ProtocolError> {
         match evt {
-            NetworkEvent::NewConnection(node_id) => {
-                info!("Connected to node {}", node_id);
+            NetworkEvent::NewConnection(node_id, features) => {
+                info!("Connected to node {} (features {})", node_id, features);
                 massa_trace!(NEW_CONN, { "node": node_id });
                 self.active_nodes
-                    .insert(node_id, NodeInfo::new(&self.config));
+                    .insert(node_id, NodeInfo::new(&self.config, features));
                 self.update_ask_block(block_ask_timer).await?;
             }
             NetworkEvent::ConnectionClosed(node_id) => {
                 massa_trace!(CONN_CLOSED, { "node": node_id });
+                self.endorsement_cache.remove_node(&node_id);
                 if self.active_nodes.remove(&node_id).is_some() {
                     // deletes all node info
                     info!("Connection closed with {}", node_id);
@@ -98,6 +99,10 @@ impl ProtocolWorker {
                     self.note_header_from_node(&header, &source_node_id).await?
                 {
                     if is_new {
+                        if self.config.header_fast_relay {
+                            // relay the checked header without waiting for its block
+                            self.propagate_header(block_id, &header).await?;
+                        }
                         self.send_protocol_event(ProtocolEvent::ReceivedBlockHeader {
                             block_id,
                             header,
@@ -139,7 +144,7 @@ impl ProtocolWorker {
                 node,
                 operation_prefix_ids,
             } => {
-                massa_trace!(OPS_BATCH, { "node": node, "operation_ids": operation_prefix_ids});
+                massa_trace!(OPS_BATCH, { "node": node, "operation_ids": operation_prefix_ids.keys().collect::<Vec<_>>()});
                 self.on_operations_announcements_received(operation_prefix_ids, node)
                     .await?;
             }
@@ -355,6 +360,19 @@ impl ProtocolWorker {
 
         // Check operation_list against expected operations hash from header.
         if header.content.operation_merkle_root == Hash::compute_from(&total_hash) {
+            // In light mode, the block is complete once its operation list is checked:
+            // its operations are never fetched.
+            if self.config.light_mode {
+                let header = header.clone();
+                if let Some(info) = self.block_wishlist.remove(&block_id) {
+                    let protocol_event_block =
+                        Self::reconstitute_block(block_id, header, operation_ids, info.storage);
+                    self.send_protocol_event(protocol_event_block).await;
+                }
+                let remove_hashes = vec![block_id].into_iter().collect();
+                return self.remove_asked_blocks_of_node(&remove_hashes);
+            }
+
             // Add the ops of info.
             info.operation_ids = Some(operation_ids.clone());
             let known_operations = info.storage.claim_operation_refs(&operation_ids_set);
@@ -485,42 +503,10 @@ impl ProtocolWorker {
                         return Ok(());
                     }
 
-                    // Re-constitute block.
-                    let block = Block {
-                        header: header.clone(),
-                        operations: block_operation_ids.clone(),
-                    };
-
-                    let mut content_serialized = Vec::new();
-                    BlockSerializer::new() // todo : keep the serializer in the struct to avoid recreating it
-                        .serialize(&block, &mut content_serialized)
-                        .unwrap();
-
-                    // wrap block
-                    let wrapped_block = Wrapped {
-                        signature: header.signature,
-                        creator_public_key: header.creator_public_key,
-                        creator_address: header.creator_address,
-                        id: block_id,
-                        content: block,
-                        serialized_data: content_serialized,
-                    };
-
+                    let block_operation_ids = block_operation_ids.clone();
                     // create block storage (without parents)
-                    let mut block_storage = entry.remove().storage;
-                    // add endorsements to local storage and claim ref
-                    // TODO change this if we make endorsements separate from block header
-                    block_storage.store_endorsements(
-                        wrapped_block.content.header.content.endorsements.clone(),
-                    );
-                    let slot = wrapped_block.content.header.content.slot;
-                    // add block to local storage and claim ref
-                    block_storage.store_block(wrapped_block);
-                    ProtocolEvent::ReceivedBlock {
-                        slot,
-                        block_id,
-                        storage: block_storage,
-                    }
+                    let block_storage = entry.remove().storage;
+                    Self::reconstitute_block(block_id, header, block_operation_ids, block_storage)
                 }
             }
             Entry::Vacant(_) => {
@@ -540,6 +526,50 @@ impl ProtocolWorker {
         self.remove_asked_blocks_of_node(&remove_hashes)
     }
 
+    /// Re-constitutes a block from its header and operation list,
+    /// and stores it with its endorsements in the given block storage.
+    ///
+    /// # Returns
+    /// The `ProtocolEvent::ReceivedBlock` to send to the graph
+    fn reconstitute_block(
+        block_id: BlockId,
+        header: WrappedHeader,
+        operation_ids: Vec<OperationId>,
+        mut block_storage: Storage,
+    ) -> ProtocolEvent {
+        let block = Block {
+            header,
+            operations: operation_ids,
+        };
+
+        let mut content_serialized = Vec::new();
+        BlockSerializer::new() // todo : keep the serializer in the struct to avoid recreating it
+            .serialize(&block, &mut content_serialized)
+            .unwrap();
+
+        // wrap block
+        let wrapped_block = Wrapped {
+            signature: block.header.signature,
+            creator_public_key: block.header.creator_public_key,
+            creator_address: block.header.creator_address,
+            id: block_id,
+            content: block,
+            serialized_data: content_serialized,
+        };
+
+        // add endorsements to local storage and claim ref
+        // TODO change this if we make endorsements separate from block header
+        block_storage.store_endorsements(wrapped_block.content.header.content.endorsements.clone());
+        let slot = wrapped_block.content.header.content.slot;
+        // add block to local storage and claim ref
+        block_storage.store_block(wrapped_block);
+        ProtocolEvent::ReceivedBlock {
+            slot,
+            block_id,
+            storage: block_storage,
+        }
+    }
+
     async fn on_block_info_received(
         &mut self,
         from_node_id: NodeId,
@@ -1,23 +1,29 @@
 //! Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::cache::{LinearHashCacheMap, LinearHashCacheSet};
+use crate::cache::{EndorsementCache, LinearHashCacheMap};
 use crate::checked_operations::CheckedOperations;
+use crate::event_queues::NetworkEventQueues;
+use crate::node_info::NodeInfo;
 use crate::sig_verifier::verify_sigs_batch;
-use crate::{node_info::NodeInfo, worker_operations_impl::OperationBatchBuffer};
+use crate::worker_operations_impl::{OperationAskBuffer, OperationBatchBuffer};
 
 use massa_logging::massa_trace;
 
 use massa_models::slot::Slot;
 use massa_models::timeslots::get_block_slot_timestamp;
 use massa_models::{
+    address::Address,
     block::{BlockId, WrappedHeader},
     endorsement::{EndorsementId, WrappedEndorsement},
     node::NodeId,
     operation::OperationPrefixId,
-    operation::{OperationId, WrappedOperation},
+    operation::{OperationId, OperationOrigin, WrappedOperation},
     prehash::{CapacityAllocator, PreHashMap, PreHashSet},
+    stats::ProtocolStats,
+};
+use massa_network_exports::{
+    AskForBlocksInfo, NetworkCommandSender, NetworkEventReceiver, PeerFeatures,
 };
-use massa_network_exports::{AskForBlocksInfo, NetworkCommandSender, NetworkEventReceiver};
 use massa_pool_exports::PoolController;
 use massa_protocol_exports::{
     ProtocolCommand, ProtocolCommandSender, ProtocolConfig, ProtocolError, ProtocolEvent,
@@ -115,6 +121,8 @@ pub(crate) struct BlockInfo {
     pub(crate) storage: Storage,
     /// Full operations size in bytes
     pub(crate) operations_size: usize,
+    /// Nodes the body of the block is asked to, in header fast relay mode
+    pub(crate) body_sources: Vec<NodeId>,
 }
 
 impl BlockInfo {
@@ -124,6 +132,30 @@ impl BlockInfo {
             operation_ids: None,
             storage,
             operations_size: 0,
+            body_sources: Vec::new(),
+        }
+    }
+
+    /// Whether the body of the block is fetched lazily, as the header fast relay mode requires
+    fn fetches_body_lazily(&self, config: &ProtocolConfig) -> bool {
+        config.header_fast_relay && self.header.is_some()
+    }
+
+    /// Time after which a node that did not answer a request for the block is asked for it again
+    fn ask_timeout(&self, config: &ProtocolConfig) -> MassaTime {
+        if self.fetches_body_lazily(config) {
+            config.block_body_ask_timeout
+        } else {
+            config.ask_block_timeout
+        }
+    }
+
+    /// Time during which a node that does not have the block is not asked for it
+    fn retry_delay(&self, config: &ProtocolConfig) -> MassaTime {
+        if self.fetches_body_lazily(config) {
+            config.block_body_retry_delay
+        } else {
+            config.ask_block_timeout
         }
     }
 }
@@ -136,6 +168,8 @@ pub struct ProtocolWorker {
     pub(crate) network_command_sender: NetworkCommandSender,
     /// Associated network event receiver.
     network_event_receiver: NetworkEventReceiver,
+    /// Network events waiting to be processed, by priority.
+    network_event_queues: NetworkEventQueues,
     /// Channel to send protocol events to the controller.
     controller_event_tx: mpsc::Sender<ProtocolEvent>,
     /// Channel to send protocol pool events to the controller.
@@ -149,8 +183,11 @@ pub struct ProtocolWorker {
     /// List of wanted blocks,
     /// with the info representing their state with in the `as_block` workflow.
     pub(crate) block_wishlist: PreHashMap<BlockId, BlockInfo>,
-    /// List of processed endorsements
-    checked_endorsements: LinearHashCacheSet<EndorsementId>,
+    /// Checked endorsements and endorsements known by each node
+    pub(crate) endorsement_cache: EndorsementCache,
+    /// Block endorsed by the first processed endorsement of each (slot, index, creator),
+    /// used to avoid relaying conflicting endorsements. Cleared when full.
+    endorsement_creators: HashMap<(Slot, u32, Address), BlockId>,
     /// Cache of processed operations
     pub(crate) checked_operations: CheckedOperations,
     /// List of processed headers
@@ -159,10 +196,13 @@ pub struct ProtocolWorker {
     pub(crate) asked_operations: PreHashMap<OperationPrefixId, (Instant, Vec<NodeId>)>,
     /// Buffer for operations that we want later
     pub(crate) op_batch_buffer: OperationBatchBuffer,
+    /// Operations asked to the nodes, asked again if still missing at their deadline
+    pub(crate) pending_operation_asks: OperationAskBuffer,
     /// Shared storage.
     pub(crate) storage: Storage,
     /// Operations to announce at the next interval.
-    operations_to_announce: Vec<OperationId>,
+    /// Operations to announce at the next interval, along their fee density.
+    operations_to_announce: Vec<(OperationId, u64)>,
 }
 
 /// channels used by the protocol worker
@@ -204,19 +244,25 @@ impl ProtocolWorker {
             config,
             network_command_sender,
             network_event_receiver,
+            network_event_queues: NetworkEventQueues::new(&config),
             controller_event_tx,
             pool_controller,
             controller_command_rx,
             controller_manager_rx,
             active_nodes: Default::default(),
             block_wishlist: Default::default(),
-            checked_endorsements: LinearHashCacheSet::new(config.max_known_endorsements_size),
+            endorsement_cache: EndorsementCache::new(
+                config.max_known_endorsements_size,
+                config.max_node_known_endorsements_size,
+            ),
+            endorsement_creators: Default::default(),
             checked_operations: CheckedOperations::new(config.max_known_ops_size),
             checked_headers: LinearHashCacheMap::new(config.max_node_known_blocks_size),
             asked_operations: Default::default(),
             op_batch_buffer: OperationBatchBuffer::with_capacity(
                 config.operation_batch_buffer_capacity,
             ),
+            pending_operation_asks: Default::default(),
             storage,
             operations_to_announce: Vec::with_capacity(
                 config.operation_announcement_buffer_capacity,
@@ -273,7 +319,8 @@ impl ProtocolWorker {
                 We choose this order:
                     * manager commands: low freq, avoid having to wait to stop
                     * incoming commands (high frequency): process commands in priority (this is a high-level crate so we prioritize this side to avoid slowing down consensus)
-                    * network events (high frequency): process incoming events
+                    * network events (high frequency): queue incoming events by priority, unless the block queue is full
+                    * queued network events: process the event with the highest priority
                     * ask for blocks (timing not important)
             */
             tokio::select! {
@@ -294,9 +341,23 @@ impl ProtocolWorker {
                 }
 
                 // listen to network controller events
-                evt = self.network_event_receiver.wait_event() => {
+                evt = self.network_event_receiver.wait_event(), if self.network_event_queues.can_receive() => {
                     massa_trace!("protocol.protocol_worker.run_loop.network_event_rx", {});
-                    self.on_network_event(evt?, &mut block_ask_timer, &mut operation_announcement_interval).await?;
+                    self.network_event_queues.push(evt?);
+                    // queue the events already received so that the most important ones are processed first
+                    while self.network_event_queues.can_receive() {
+                        match self.network_event_receiver.0.try_recv() {
+                            Ok(evt) => self.network_event_queues.push(evt),
+                            Err(_) => break,
+                        }
+                    }
+                }
+
+                // process queued network events
+                _ = std::future::ready(()), if !self.network_event_queues.is_empty() => {
+                    if let Some(evt) = self.network_event_queues.pop() {
+                        self.on_network_event(evt, &mut block_ask_timer, &mut operation_announcement_interval).await?;
+                    }
                 }
 
                 // block ask timer
@@ -350,18 +411,33 @@ impl ProtocolWorker {
             "operation_ids": operation_ids
         });
         for (node, node_info) in self.active_nodes.iter_mut() {
-            let new_ops: Vec<OperationId> = operation_ids
+            let new_ops: Vec<(OperationId, u64)> = operation_ids
                 .iter()
-                .filter(|id| !node_info.knows_op(&id.prefix()))
+                .filter(|(id, _)| !node_info.knows_op(&id.prefix()))
                 .copied()
                 .collect();
             if !new_ops.is_empty() {
-                node_info.insert_known_ops(new_ops.iter().map(|id| id.prefix()));
-
-                let res = self
-                    .network_command_sender
-                    .announce_operations(*node, new_ops.iter().map(|id| id.into_prefix()).collect())
-                    .await;
+                node_info.insert_known_ops(new_ops.iter().map(|(id, _)| id.prefix()));
+
+                // only the nodes that negotiated it can read the fee densities
+                let res = if node_info.supports(PeerFeatures::OPERATION_FEE_DENSITY) {
+                    self.network_command_sender
+                        .announce_operation_fees(
+                            *node,
+                            new_ops
+                                .iter()
+                                .map(|(id, fee_density)| (id.into_prefix(), *fee_density))
+                                .collect(),
+                        )
+                        .await
+                } else {
+                    self.network_command_sender
+                        .announce_operations(
+                            *node,
+                            new_ops.iter().map(|(id, _)| id.into_prefix()).collect(),
+                        )
+                        .await
+                };
                 if let Err(err) = res {
                     debug!("could not send operation batch to node {}: {}", node, err);
                 }
@@ -376,19 +452,28 @@ impl ProtocolWorker {
         timer.set(sleep_until(next_tick));
     }
 
-    /// Add an list of operations to a buffer for announcement at the next interval,
+    /// Add the operations of a storage to a buffer for announcement at the next interval,
     /// or immediately if the buffer is full.
     async fn note_operations_to_announce(
         &mut self,
-        operations: &[OperationId],
+        operations: &Storage,
         timer: &mut Pin<&mut Sleep>,
     ) {
         massa_trace!(
             "protocol.protocol_worker.note_operations_to_announce.begin",
-            { "operations": operations }
+            { "operations": operations.get_op_refs() }
         );
-        // Add the operations to a list for announcement at the next interval.
-        self.operations_to_announce.extend_from_slice(operations);
+        // Add the operations to a list for announcement at the next interval,
+        // along their fee density for the receivers to fetch the most profitable ones first.
+        {
+            let read_operations = operations.read_operations();
+            self.operations_to_announce
+                .extend(operations.get_op_refs().iter().filter_map(|id| {
+                    read_operations
+                        .get(id)
+                        .map(|op| (*id, op.get_fee_density()))
+                }));
+        }
 
         // If the buffer is full,
         // announce operations immediately,
@@ -403,22 +488,17 @@ impl ProtocolWorker {
             "protocol.protocol_worker.process_command.propagate_endorsements.begin",
             { "endorsements": storage.get_endorsement_refs() }
         );
-        for (node, node_info) in self.active_nodes.iter_mut() {
-            let new_endorsements: PreHashMap<EndorsementId, WrappedEndorsement> = {
+        for node in self.active_nodes.keys() {
+            let to_send: Vec<WrappedEndorsement> = {
+                let endorsement_ids = self
+                    .endorsement_cache
+                    .select_for_sending(*node, storage.get_endorsement_refs());
                 let endorsements_reader = storage.read_endorsements();
-                storage
-                    .get_endorsement_refs()
+                endorsement_ids
                     .iter()
-                    .filter_map(|id| {
-                        if node_info.knows_endorsement(id) {
-                            return None;
-                        }
-                        Some((*id, endorsements_reader.get(id).cloned().unwrap()))
-                    })
+                    .map(|id| endorsements_reader.get(id).cloned().unwrap())
                     .collect()
             };
-            node_info.insert_known_endorsements(new_endorsements.keys().copied());
-            let to_send = new_endorsements.into_values().collect::<Vec<_>>();
             if !to_send.is_empty() {
                 let res = self
                     .network_command_sender
@@ -458,24 +538,7 @@ impl ProtocolWorker {
                             ))
                         })?
                 };
-                for (node_id, node_info) in self.active_nodes.iter_mut() {
-                    // node that isn't asking for that block
-                    let cond = node_info.get_known_block(&block_id);
-                    // if we don't know if that node knows that hash or if we know it doesn't
-                    if !cond.map_or_else(|| false, |v| v.0) {
-                        massa_trace!("protocol.protocol_worker.process_command.integrated_block.send_header", { "node": node_id, "block_id": block_id});
-                        self.network_command_sender
-                            .send_block_header(*node_id, header.clone())
-                            .await
-                            .map_err(|_| {
-                                ProtocolError::ChannelError(
-                                    "send block header network command send failed".into(),
-                                )
-                            })?;
-                    } else {
-                        massa_trace!("protocol.protocol_worker.process_command.integrated_block.do_not_send", { "node": node_id, "block_id": block_id });
-                    }
-                }
+                self.propagate_header(block_id, &header).await?;
                 massa_trace!(
                     "protocol.protocol_worker.process_command.integrated_block.end",
                     {}
@@ -538,18 +601,54 @@ impl ProtocolWorker {
                     .extend(operation_ids.iter().copied());
 
                 // Announce operations to active nodes not knowing about it.
-                let to_announce: Vec<OperationId> = operation_ids.iter().copied().collect();
-                self.note_operations_to_announce(&to_announce, op_timer)
-                    .await;
+                self.note_operations_to_announce(&storage, op_timer).await;
             }
             ProtocolCommand::PropagateEndorsements(endorsements) => {
                 self.propagate_endorsements(&endorsements).await;
             }
+            ProtocolCommand::GetStats { response_tx } => {
+                massa_trace!("protocol.protocol_worker.process_command.get_stats", {});
+                let stats = ProtocolStats {
+                    endorsement_cache: self.endorsement_cache.get_stats(),
+                    ..self.network_event_queues.get_stats()
+                };
+                if response_tx.send(stats).is_err() {
+                    warn!("protocol: could not send get_stats response");
+                }
+            }
         }
         massa_trace!("protocol.protocol_worker.process_command.end", {});
         Ok(())
     }
 
+    /// Sends a block header to the active nodes that may not know the block,
+    /// and to which it was not already sent
+    pub(crate) async fn propagate_header(
+        &mut self,
+        block_id: BlockId,
+        header: &WrappedHeader,
+    ) -> Result<(), ProtocolError> {
+        for (node_id, node_info) in self.active_nodes.iter_mut() {
+            // node that isn't asking for that block
+            let cond = node_info.get_known_block(&block_id);
+            // if we don't know if that node knows that hash or if we know it doesn't
+            if !cond.map_or_else(|| false, |v| v.0) && node_info.try_note_sent_header(block_id) {
+                massa_trace!("protocol.protocol_worker.propagate_header.send_header", { "node": node_id, "block_id": block_id});
+                self.network_command_sender
+                    .send_block_header(*node_id, header.clone())
+                    .await
+                    .map_err(|_| {
+                        ProtocolError::ChannelError(
+                            "send block header network command send failed".into(),
+                        )
+                    })?;
+            } else {
+                massa_trace!("protocol.protocol_worker.propagate_header.do_not_send", { "node": node_id, "block_id": block_id });
+            }
+        }
+        Ok(())
+    }
+
     /// Remove the given blocks from the local wishlist
     pub(crate) fn remove_asked_blocks_of_node(
         &mut self,
@@ -584,7 +683,27 @@ impl ProtocolWorker {
             Default::default();
 
         // list blocks to re-ask and from whom
-        for (hash, block_info) in self.block_wishlist.iter() {
+        for (hash, block_info) in self.block_wishlist.iter_mut() {
+            let lazy_body = block_info.fetches_body_lazily(&self.config);
+            let ask_timeout = block_info.ask_timeout(&self.config);
+            let retry_delay = block_info.retry_delay(&self.config);
+            if lazy_body {
+                // the body is only asked to the first nodes known to have the block
+                let active_nodes = &self.active_nodes;
+                block_info
+                    .body_sources
+                    .retain(|node_id| active_nodes.contains_key(node_id));
+                for (node_id, node_info) in active_nodes.iter() {
+                    if block_info.body_sources.len() >= self.config.max_block_body_sources {
+                        break;
+                    }
+                    if matches!(node_info.get_known_block(hash), Some((true, _)))
+                        && !block_info.body_sources.contains(node_id)
+                    {
+                        block_info.body_sources.push(*node_id);
+                    }
+                }
+            }
             let required_info = if block_info.header.is_none() {
                 AskForBlocksInfo::Header
             } else if block_info.operation_ids.is_none() {
@@ -606,11 +725,14 @@ impl ProtocolWorker {
             let mut needs_ask = true;
 
             for (node_id, node_info) in self.active_nodes.iter_mut() {
+                if lazy_body && !block_info.body_sources.contains(node_id) {
+                    continue; // not a body source
+                }
                 // map to remove the borrow on asked_blocks. Otherwise can't call insert_known_blocks
                 let ask_time_opt = node_info.asked_blocks.get(hash).copied();
                 let (timeout_at_opt, timed_out) = if let Some(ask_time) = ask_time_opt {
                     let t = ask_time
-                        .checked_add(self.config.ask_block_timeout.into())
+                        .checked_add(ask_timeout.into())
                         .ok_or(TimeError::TimeOverflowError)?;
                     (Some(t), t <= now)
                 } else {
@@ -621,7 +743,7 @@ impl ProtocolWorker {
                 // check if the node recently told us it doesn't have the block
                 if let Some((false, info_time)) = knows_block {
                     let info_expires = info_time
-                        .checked_add(self.config.ask_block_timeout.into())
+                        .checked_add(retry_delay.into())
                         .ok_or(TimeError::TimeOverflowError)?;
                     if info_expires > now {
                         next_tick = std::cmp::min(next_tick, info_expires);
@@ -697,39 +819,59 @@ impl ProtocolWorker {
             }
         }
 
-        // count active block requests per node
-        let mut active_block_req_count: HashMap<NodeId, usize> = self
-            .active_nodes
-            .iter()
-            .map(|(node_id, node_info)| {
-                (
-                    *node_id,
-                    node_info
-                        .asked_blocks
-                        .iter()
-                        .filter(|(_h, ask_t)| {
-                            ask_t
-                                .checked_add(self.config.ask_block_timeout.into())
-                                .map_or(false, |timeout_t| timeout_t > now)
-                        })
-                        .count(),
-                )
-            })
-            .collect();
+        // count active block requests per node, the lazily fetched block bodies apart
+        let mut active_block_req_count: HashMap<NodeId, usize> = Default::default();
+        let mut active_body_req_count: HashMap<NodeId, usize> = Default::default();
+        for (node_id, node_info) in self.active_nodes.iter() {
+            for (hash, ask_t) in node_info.asked_blocks.iter() {
+                let block_info = self.block_wishlist.get(hash);
+                let ask_timeout = block_info.map_or(self.config.ask_block_timeout, |info| {
+                    info.ask_timeout(&self.config)
+                });
+                if ask_t
+                    .checked_add(ask_timeout.into())
+                    .map_or(true, |timeout_t| timeout_t <= now)
+                {
+                    continue;
+                }
+                let req_count =
+                    if block_info.map_or(false, |info| info.fetches_body_lazily(&self.config)) {
+                        &mut active_body_req_count
+                    } else {
+                        &mut active_block_req_count
+                    };
+                *req_count.entry(*node_id).or_default() += 1;
+            }
+        }
 
         for (hash, criteria) in candidate_nodes.into_iter() {
+            let block_info = self.block_wishlist.get(&hash);
+            let ask_timeout = block_info.map_or(self.config.ask_block_timeout, |info| {
+                info.ask_timeout(&self.config)
+            });
+            let (req_count, max_req_count) =
+                if block_info.map_or(false, |info| info.fetches_body_lazily(&self.config)) {
+                    (
+                        &mut active_body_req_count,
+                        self.config.max_simultaneous_ask_block_bodies_per_node,
+                    )
+                } else {
+                    (
+                        &mut active_block_req_count,
+                        self.config.max_simultaneous_ask_blocks_per_node,
+                    )
+                };
             // find the best node
             if let Some((_knowledge, best_node, required_info)) = criteria
                 .into_iter()
                 .filter(|(_knowledge, node_id, _)| {
                     // filter out nodes with too many active block requests
-                    *active_block_req_count.get(node_id).unwrap_or(&0)
-                        <= self.config.max_simultaneous_ask_blocks_per_node
+                    *req_count.get(node_id).unwrap_or(&0) <= max_req_count
                 })
                 .min_by_key(|(knowledge, node_id, _)| {
                     (
                         *knowledge,                                                 // block knowledge
-                        *active_block_req_count.get(node_id).unwrap_or(&0), // active requests
+                        *req_count.get(node_id).unwrap_or(&0), // active requests
                         self.active_nodes.get(node_id).unwrap().connection_instant, // node age (will not panic, already checked)
                         *node_id,                                                   // node ID
                     )
@@ -737,9 +879,8 @@ impl ProtocolWorker {
             {
                 let info = self.active_nodes.get_mut(&best_node).unwrap(); // will not panic, already checked
                 info.asked_blocks.insert(hash, now);
-                if let Some(cnt) = active_block_req_count.get_mut(&best_node) {
-                    *cnt += 1; // increase the number of actively asked blocks
-                }
+                // increase the number of actively asked blocks
+                *req_count.entry(best_node).or_default() += 1;
 
                 ask_block_list
                     .entry(best_node)
@@ -747,7 +888,7 @@ impl ProtocolWorker {
                     .push((hash, required_info.clone()));
 
                 let timeout_at = now
-                    .checked_add(self.config.ask_block_timeout.into())
+                    .checked_add(ask_timeout.into())
                     .ok_or(TimeError::TimeOverflowError)?;
                 next_tick = std::cmp::min(next_tick, timeout_at);
             }
@@ -776,6 +917,7 @@ impl ProtocolWorker {
     pub(crate) async fn ban_node(&mut self, node_id: &NodeId) -> Result<(), ProtocolError> {
         massa_trace!("protocol.protocol_worker.ban_node", { "node": node_id });
         self.active_nodes.remove(node_id);
+        self.endorsement_cache.remove_node(node_id);
         if self.active_nodes.is_empty() {
             info!("Not connected to any peers.");
         }
@@ -843,7 +985,8 @@ impl ProtocolWorker {
                     now,
                     self.config.max_node_known_blocks_size,
                 );
-                node_info.insert_known_endorsements(
+                self.endorsement_cache.insert_known(
+                    *source_node_id,
                     block_header.content.endorsements.iter().map(|e| e.id),
                 );
             }
@@ -905,7 +1048,10 @@ impl ProtocolWorker {
                 now,
                 self.config.max_node_known_blocks_size,
             );
-            node_info.insert_known_endorsements(header.content.endorsements.iter().map(|e| e.id));
+            self.endorsement_cache.insert_known(
+                *source_node_id,
+                header.content.endorsements.iter().map(|e| e.id),
+            );
             massa_trace!("protocol.protocol_worker.note_header_from_node.ok", { "node": source_node_id,"block_id":block_id, "header": header});
             return Ok(Some((block_id, true)));
         }
@@ -943,8 +1089,18 @@ impl ProtocolWorker {
         verify_sigs_batch(
             &new_operations
                 .iter()
-                .map(|(op_id, op)| (*op_id.get_hash(), op.signature, op.creator_public_key))
+                .map(|(_op_id, op)| {
+                    (
+                        op.compute_operation_signed_hash(
+                            self.config.chain_id,
+                            self.config.chain_id_activation_period,
+                        ),
+                        op.signature,
+                        op.creator_public_key,
+                    )
+                })
                 .collect::<Vec<_>>(),
+            self.config.parallel_sig_verification_threshold,
         )?;
 
         // add to checked operations
@@ -991,13 +1147,12 @@ impl ProtocolWorker {
                     .collect()
             };
             ops_to_propagate.drop_operation_refs(&operations_to_not_propagate);
-            let to_announce: Vec<OperationId> =
-                ops_to_propagate.get_op_refs().iter().copied().collect();
-            self.note_operations_to_announce(&to_announce, op_timer)
+            self.note_operations_to_announce(&ops_to_propagate, op_timer)
                 .await;
 
             // Add to pool
-            self.pool_controller.add_operations(ops);
+            self.pool_controller
+                .add_operations(ops, OperationOrigin::Network);
         }
 
         Ok(())
@@ -1022,12 +1177,15 @@ impl ProtocolWorker {
         let length = endorsements.len();
         let mut new_endorsements = PreHashMap::with_capacity(length);
         let mut endorsement_ids = PreHashSet::with_capacity(length);
-        for endorsement in endorsements.into_iter() {
+        let unchecked = self
+            .endorsement_cache
+            .note_received(endorsements.iter().map(|endorsement| &endorsement.id));
+        for (endorsement, unchecked) in endorsements.into_iter().zip(unchecked) {
             let endorsement_id = endorsement.id;
             endorsement_ids.insert(endorsement_id);
 
             // check endorsement signature if not already checked
-            if !self.checked_endorsements.contains(&endorsement_id) {
+            if unchecked {
                 new_endorsements.insert(endorsement_id, endorsement);
             }
         }
@@ -1045,15 +1203,42 @@ impl ProtocolWorker {
                     )
                 })
                 .collect::<Vec<_>>(),
+            self.config.parallel_sig_verification_threshold,
         )?;
 
         // add to verified signature cache
-        self.checked_endorsements
-            .try_extend(endorsement_ids.iter().copied());
+        self.endorsement_cache
+            .insert_checked(endorsement_ids.iter().copied());
 
         // add to known endorsements for source node.
-        if let Some(node_info) = self.active_nodes.get_mut(source_node_id) {
-            node_info.insert_known_endorsements(endorsement_ids);
+        if self.active_nodes.contains_key(source_node_id) {
+            self.endorsement_cache
+                .insert_known(*source_node_id, endorsement_ids);
+        }
+
+        // detect endorsements conflicting with an already processed one of the same creator
+        // for the same slot and index: they are still given to the pool, which records the equivocation,
+        // but they are not relayed
+        let mut conflicting_endorsements = PreHashSet::default();
+        for (endorsement_id, endorsement) in new_endorsements.iter() {
+            if self.endorsement_creators.len() >= self.config.max_known_endorsements_size {
+                self.endorsement_creators.clear();
+            }
+            let endorsed_block = self
+                .endorsement_creators
+                .entry((
+                    endorsement.content.slot,
+                    endorsement.content.index,
+                    endorsement.creator_address,
+                ))
+                .or_insert(endorsement.content.endorsed_block);
+            if *endorsed_block != endorsement.content.endorsed_block {
+                debug!(
+                    "not relaying endorsement {} from node {}: its creator {} equivocated",
+                    endorsement_id, source_node_id, endorsement.creator_address
+                );
+                conflicting_endorsements.insert(*endorsement_id);
+            }
         }
 
         if !new_endorsements.is_empty() {
@@ -1064,7 +1249,7 @@ impl ProtocolWorker {
             if propagate {
                 // Propagate endorsements when the slot of the block they endorse isn't `max_endorsements_propagation_time` old.
                 let mut endorsements_to_propagate = endorsements.clone();
-                let endorsements_to_not_propagate = {
+                let mut endorsements_to_not_propagate: PreHashSet<EndorsementId> = {
                     let now = MassaTime::now(0)?;
                     let read_endorsements = endorsements_to_propagate.read_endorsements();
                     endorsements_to_propagate
@@ -1095,6 +1280,7 @@ impl ProtocolWorker {
                         })
                         .collect()
                 };
+                endorsements_to_not_propagate.extend(conflicting_endorsements);
                 endorsements_to_propagate.drop_endorsement_refs(&endorsements_to_not_propagate);
                 self.propagate_endorsements(&endorsements_to_propagate)
                     .await;
@@ -1114,6 +1300,7 @@ mod tests {
     use crate::node_info::NodeInfo;
     use massa_hash::Hash;
     use massa_protocol_exports::{tests::tools::create_protocol_config, ProtocolConfig};
+    use massa_signature::KeyPair;
     use serial_test::serial;
 
     lazy_static::lazy_static! {
@@ -1129,7 +1316,7 @@ mod tests {
     fn test_node_info_know_block() {
         let max_node_known_blocks_size = 10;
         let config = &PROTOCOL_CONFIG;
-        let mut nodeinfo = NodeInfo::new(config);
+        let mut nodeinfo = NodeInfo::new(config, Default::default());
         let instant = Instant::now();
 
         let hash_test = get_dummy_block_id("test");
@@ -1178,4 +1365,40 @@ mod tests {
             assert!(nodeinfo.get_known_block(&hash).is_some());
         }
     }
+
+    #[test]
+    fn test_endorsement_cache() {
+        let mut cache = EndorsementCache::new(10, 2);
+        let node = NodeId(KeyPair::generate().get_public_key());
+        let other_node = NodeId(KeyPair::generate().get_public_key());
+        let ids: Vec<EndorsementId> = (0..3)
+            .map(|index| EndorsementId::new(Hash::compute_from(&[index])))
+            .collect();
+
+        // only the endorsements that were not checked yet need their signature verified
+        cache.insert_checked([ids[0]]);
+        assert_eq!(cache.note_received(&ids), vec![false, true, true]);
+        cache.insert_checked(ids.iter().copied());
+        cache.insert_known(node, ids[..2].iter().copied());
+
+        // endorsements are sent once to each node, and not sent back to the node they come from
+        assert_eq!(cache.select_for_sending(node, &ids), vec![ids[2]]);
+        assert_eq!(
+            cache.select_for_sending(other_node, &ids[..1]),
+            vec![ids[0]]
+        );
+        assert!(cache.select_for_sending(node, &ids[2..]).is_empty());
+
+        // the oldest known endorsements are forgotten past the capacity of the node cache
+        assert_eq!(cache.select_for_sending(node, &ids[..1]), vec![ids[0]]);
+
+        let stats = cache.get_stats();
+        assert_eq!(stats.received_endorsements, 3);
+        assert_eq!(stats.duplicate_endorsements, 1);
+        assert_eq!(stats.sent_endorsements, 3);
+        assert_eq!(stats.skipped_sends, 3);
+        assert_eq!(stats.tracked_nodes, 2);
+        cache.remove_node(&other_node);
+        assert_eq!(cache.get_stats().tracked_nodes, 1);
+    }
 }
@@ -6,7 +6,7 @@ use massa_models::{block::BlockId, slot::Slot};
 use massa_network_exports::{AskForBlocksInfo, BlockInfoReply, NetworkCommand};
 use massa_protocol_exports::tests::tools;
 use massa_protocol_exports::tests::tools::{asked_list, assert_hash_asked_to_node};
-use massa_protocol_exports::ProtocolEvent;
+use massa_protocol_exports::{ProtocolConfig, ProtocolEvent};
 use serial_test::serial;
 
 #[tokio::test]
@@ -570,3 +570,96 @@ async fn test_multiple_blocks_without_a_priori() {
     )
     .await;
 }
+
+#[tokio::test]
+#[serial]
+async fn test_header_fast_relay() {
+    // start
+    let protocol_config = ProtocolConfig {
+        header_fast_relay: true,
+        block_body_ask_timeout: 200.into(),
+        block_body_retry_delay: 400.into(),
+        max_block_body_sources: 1,
+        ..tools::create_protocol_config()
+    };
+
+    protocol_test(
+        &protocol_config,
+        async move |mut network_controller,
+                    protocol_event_receiver,
+                    mut protocol_command_sender,
+                    protocol_manager,
+                    protocol_pool_event_receiver| {
+            let node_a = tools::create_and_connect_nodes(1, &mut network_controller)
+                .await
+                .pop()
+                .unwrap();
+            let node_b = tools::create_and_connect_nodes(1, &mut network_controller)
+                .await
+                .pop()
+                .unwrap();
+            let node_c = tools::create_and_connect_nodes(1, &mut network_controller)
+                .await
+                .pop()
+                .unwrap();
+
+            let block = tools::create_block(&node_a.keypair);
+            // end set up
+
+            // Send header via node_a
+            network_controller
+                .send_header(node_a.id, block.content.header.clone())
+                .await;
+
+            // the header is relayed to the other nodes before the block is integrated
+            let send_header_filter = |cmd| match cmd {
+                NetworkCommand::SendBlockHeader { node, header } => Some((node, header.id)),
+                _ => None,
+            };
+            let mut relayed_to = Vec::new();
+            for _ in 0..2 {
+                let (node, block_id) = network_controller
+                    .wait_command(1000.into(), send_header_filter)
+                    .await
+                    .expect("header not relayed");
+                assert_eq!(block_id, block.id);
+                relayed_to.push(node);
+            }
+            relayed_to.sort();
+            let mut expected = vec![node_b.id, node_c.id];
+            expected.sort();
+            assert_eq!(relayed_to, expected);
+
+            // send wishlist
+            protocol_command_sender
+                .send_wishlist_delta(
+                    vec![(block.id, Some(block.content.header.clone()))]
+                        .into_iter()
+                        .collect(),
+                    PreHashSet::<BlockId>::default(),
+                )
+                .await
+                .unwrap();
+
+            // the body is only asked to node A, the only node known to have the block,
+            // again after the retry delay when it does not answer
+            for _ in 0..2 {
+                let list = asked_list(&mut network_controller).await;
+                assert_eq!(list.keys().collect::<Vec<_>>(), vec![&node_a.id]);
+                assert!(matches!(
+                    list[&node_a.id][..],
+                    [(block_id, AskForBlocksInfo::Info)] if block_id == block.id
+                ));
+            }
+
+            (
+                network_controller,
+                protocol_event_receiver,
+                protocol_command_sender,
+                protocol_manager,
+                protocol_pool_event_receiver,
+            )
+        },
+    )
+    .await;
+}
@@ -5,6 +5,7 @@ mod ban_nodes_scenarios;
 mod cache_scenarios;
 mod endorsements_scenarios;
 mod in_block_operations_scenarios;
+mod network_simulation_scenarios;
 mod operations_scenarios;
 mod scenarios;
 mod tools;
@@ -0,0 +1,200 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::start_protocol_controller;
+use massa_models::block::{BlockId, WrappedHeader};
+use massa_models::node::NodeId;
+use massa_network_exports::NetworkEvent;
+use massa_pool_exports::test_exports::{MockPoolController, PoolEventReceiver};
+use massa_protocol_exports::test_exports::{NetworkSimulator, SimulatedLink};
+use massa_protocol_exports::tests::tools::{self, create_block, wait_protocol_event};
+use massa_protocol_exports::{
+    ProtocolCommandSender, ProtocolEvent, ProtocolEventReceiver, ProtocolManager,
+};
+use massa_signature::KeyPair;
+use massa_storage::Storage;
+use massa_time::MassaTime;
+use serial_test::serial;
+
+/// Protocol worker of a simulated node
+struct SimulatedProtocol {
+    id: NodeId,
+    command_sender: ProtocolCommandSender,
+    event_receiver: ProtocolEventReceiver,
+    manager: ProtocolManager,
+    storage: Storage,
+    _pool_event_receiver: PoolEventReceiver,
+}
+
+/// Adds `count` nodes to the simulated network and starts their protocol workers
+async fn start_simulated_nodes(
+    simulator: &mut NetworkSimulator,
+    count: usize,
+) -> Vec<SimulatedProtocol> {
+    let mut nodes = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (id, network_command_sender, network_event_receiver) = simulator.add_node();
+        let (pool_controller, pool_event_receiver) = MockPoolController::new_with_receiver();
+        let storage = Storage::create_root();
+        let (command_sender, event_receiver, manager) = start_protocol_controller(
+            *tools::PROTOCOL_CONFIG,
+            network_command_sender,
+            network_event_receiver,
+            pool_controller,
+            storage.clone(),
+        )
+        .await
+        .expect("could not start protocol controller");
+        nodes.push(SimulatedProtocol {
+            id,
+            command_sender,
+            event_receiver,
+            manager,
+            storage,
+            _pool_event_receiver: pool_event_receiver,
+        });
+    }
+    nodes
+}
+
+/// Stops the protocol workers of the simulated nodes
+async fn stop_simulated_nodes(nodes: Vec<SimulatedProtocol>) {
+    for node in nodes {
+        node.manager
+            .stop(node.event_receiver)
+            .await
+            .expect("Failed to shutdown protocol.");
+    }
+}
+
+/// Waits for the protocol worker of a node to notify consensus of a received header
+async fn wait_header(node: &mut SimulatedProtocol, timeout_millis: u64) -> Option<BlockId> {
+    match wait_protocol_event(
+        &mut node.event_receiver,
+        timeout_millis.into(),
+        |evt| match evt {
+            evt @ ProtocolEvent::ReceivedBlockHeader { .. } => Some(evt),
+            _ => None,
+        },
+    )
+    .await
+    {
+        Some(ProtocolEvent::ReceivedBlockHeader { block_id, .. }) => Some(block_id),
+        _ => None,
+    }
+}
+
+/// Integrates a block in a node, which then announces its header to its peers
+async fn integrate_block(node: &mut SimulatedProtocol, block_id: BlockId) {
+    let storage = node.storage.clone();
+    node.command_sender
+        .integrated_block(block_id, storage)
+        .await
+        .expect("Failed to integrate block.");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_simulated_header_propagation_follows_link_latency() {
+    let mut simulator = NetworkSimulator::new(
+        0,
+        SimulatedLink {
+            latency: MassaTime::from_millis(100),
+            drop_per_mille: 0,
+        },
+    );
+    let mut nodes = start_simulated_nodes(&mut simulator, 3).await;
+    // line topology: 0 - 1 - 2
+    simulator.connect(nodes[0].id, nodes[1].id).await;
+    simulator.connect(nodes[1].id, nodes[2].id).await;
+
+    let block = create_block(&KeyPair::generate());
+    nodes[0].storage.store_block(block.clone());
+    integrate_block(&mut nodes[0], block.id).await;
+
+    // the header is still going through the link
+    simulator.advance(MassaTime::from_millis(50)).await;
+    assert!(wait_header(&mut nodes[1], 100).await.is_none());
+
+    // the latency elapsed: node 1 received the header
+    simulator.advance(MassaTime::from_millis(50)).await;
+    assert_eq!(wait_header(&mut nodes[1], 1000).await, Some(block.id));
+
+    // node 2 only gets the header once node 1 integrated the block
+    simulator.advance(MassaTime::from_millis(200)).await;
+    assert!(wait_header(&mut nodes[2], 100).await.is_none());
+    nodes[1].storage.store_block(block.clone());
+    integrate_block(&mut nodes[1], block.id).await;
+    simulator.advance(MassaTime::from_millis(100)).await;
+    assert_eq!(wait_header(&mut nodes[2], 1000).await, Some(block.id));
+    assert_eq!(simulator.now(), MassaTime::from_millis(400));
+
+    stop_simulated_nodes(nodes).await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_simulated_header_propagation_through_lossy_link() {
+    let mut simulator = NetworkSimulator::new(0, SimulatedLink::default());
+    let mut nodes = start_simulated_nodes(&mut simulator, 3).await;
+    simulator.connect(nodes[0].id, nodes[1].id).await;
+    simulator.connect(nodes[0].id, nodes[2].id).await;
+    simulator.set_link(
+        nodes[0].id,
+        nodes[1].id,
+        SimulatedLink {
+            latency: MassaTime::from_millis(10),
+            drop_per_mille: 1000,
+        },
+    );
+
+    let block = create_block(&KeyPair::generate());
+    nodes[0].storage.store_block(block.clone());
+    integrate_block(&mut nodes[0], block.id).await;
+    assert!(simulator.run_until_idle(MassaTime::from_millis(1000)).await);
+
+    // the header is lost on the way to node 1, but reaches node 2
+    assert!(wait_header(&mut nodes[1], 100).await.is_none());
+    assert_eq!(wait_header(&mut nodes[2], 1000).await, Some(block.id));
+    assert!(simulator.dropped_count() >= 1);
+
+    stop_simulated_nodes(nodes).await;
+}
+
+/// Sends headers through a link losing half of the messages, and returns the ones received
+async fn simulate_lossy_header_transfer(seed: u64, headers: &[WrappedHeader]) -> Vec<BlockId> {
+    let mut simulator = NetworkSimulator::new(
+        seed,
+        SimulatedLink {
+            latency: MassaTime::from_millis(10),
+            drop_per_mille: 500,
+        },
+    );
+    let (sender_id, sender_commands, _sender_events) = simulator.add_node();
+    let (receiver_id, _receiver_commands, mut receiver_events) = simulator.add_node();
+    simulator.connect(sender_id, receiver_id).await;
+    for header in headers {
+        sender_commands
+            .send_block_header(receiver_id, header.clone())
+            .await
+            .expect("could not send header");
+    }
+    assert!(simulator.run_until_idle(MassaTime::from_millis(1000)).await);
+    let mut received = Vec::new();
+    while let Ok(event) = receiver_events.0.try_recv() {
+        if let NetworkEvent::ReceivedBlockHeader { header, .. } = event {
+            received.push(header.id);
+        }
+    }
+    received
+}
+
+#[tokio::test]
+#[serial]
+async fn test_simulated_drops_are_reproducible() {
+    let headers: Vec<WrappedHeader> = (0..32)
+        .map(|_| create_block(&KeyPair::generate()).content.header)
+        .collect();
+    let received = simulate_lossy_header_transfer(42, &headers).await;
+    assert!(!received.is_empty() && received.len() < headers.len());
+    assert_eq!(simulate_lossy_header_transfer(42, &headers).await, received);
+}
@@ -5,10 +5,10 @@
 use super::tools::{protocol_test, protocol_test_with_storage};
 use massa_models::prehash::PreHashSet;
 use massa_models::{self, address::Address, amount::Amount, block::BlockId, slot::Slot};
-use massa_network_exports::{BlockInfoReply, NetworkCommand};
+use massa_network_exports::{BlockInfoReply, NetworkCommand, PeerFeatures};
 use massa_pool_exports::test_exports::MockPoolControllerMessage;
 use massa_protocol_exports::tests::tools::{self, assert_hash_asked_to_node};
-use massa_protocol_exports::ProtocolEvent;
+use massa_protocol_exports::{ProtocolConfig, ProtocolEvent};
 use serial_test::serial;
 use std::str::FromStr;
 use std::time::Duration;
@@ -197,6 +197,82 @@ async fn test_protocol_propagates_operations_to_active_nodes() {
     .await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_protocol_announces_fee_densities_to_supporting_nodes() {
+    let protocol_config = &tools::PROTOCOL_CONFIG;
+    protocol_test(
+        protocol_config,
+        async move |mut network_controller,
+                    protocol_event_receiver,
+                    protocol_command_sender,
+                    protocol_manager,
+                    mut pool_event_receiver| {
+            // Create 2 nodes not supporting fee densities, and 1 node supporting them.
+            let nodes = tools::create_and_connect_nodes(2, &mut network_controller).await;
+            let supporting_node = tools::create_node();
+            network_controller
+                .new_connection_with_features(
+                    supporting_node.id,
+                    PeerFeatures::OPERATION_FEE_DENSITY,
+                )
+                .await;
+
+            // 1. Send an operation from the first node.
+            let operation = tools::create_operation_with_expire_period(&nodes[0].keypair, 1);
+            network_controller
+                .send_operations(nodes[0].id, vec![operation.clone()])
+                .await;
+
+            pool_event_receiver.wait_command(1000.into(), |evt| match evt {
+                MockPoolControllerMessage::AddOperations { .. } => {
+                    Some(MockPoolControllerMessage::Any)
+                }
+                _ => panic!("Unexpected or no protocol pool event."),
+            });
+
+            // 2. The operation is announced along its fee density only to the supporting node.
+            let mut legacy_announced = false;
+            let mut fee_announced = false;
+            while !(legacy_announced && fee_announced) {
+                match network_controller
+                    .wait_command(1000.into(), |cmd| match cmd {
+                        cmd @ NetworkCommand::SendOperationAnnouncements { .. } => Some(cmd),
+                        cmd @ NetworkCommand::SendOperationFeeAnnouncements { .. } => Some(cmd),
+                        _ => None,
+                    })
+                    .await
+                {
+                    Some(NetworkCommand::SendOperationAnnouncements { to_node, batch }) => {
+                        assert_eq!(batch.len(), 1);
+                        assert!(batch.contains(&operation.id.prefix()));
+                        assert_eq!(nodes[1].id, to_node);
+                        legacy_announced = true;
+                    }
+                    Some(NetworkCommand::SendOperationFeeAnnouncements { to_node, batch }) => {
+                        assert_eq!(batch.len(), 1);
+                        assert_eq!(
+                            batch.get(&operation.id.prefix()),
+                            Some(&operation.get_fee_density())
+                        );
+                        assert_eq!(supporting_node.id, to_node);
+                        fee_announced = true;
+                    }
+                    _ => panic!("Unexpected or no network command."),
+                };
+            }
+            (
+                network_controller,
+                protocol_event_receiver,
+                protocol_command_sender,
+                protocol_manager,
+                pool_event_receiver,
+            )
+        },
+    )
+    .await;
+}
+
 #[tokio::test]
 #[serial]
 async fn test_protocol_propagates_operations_only_to_nodes_that_dont_know_about_it() {
@@ -681,7 +757,7 @@ async fn test_protocol_does_not_propagates_operations_when_receiving_those_insid
                 _ => None,
             }) {
                 None => panic!("Protocol did not send operations to pool."),
-                Some(MockPoolControllerMessage::AddOperations { operations }) => {
+                Some(MockPoolControllerMessage::AddOperations { operations, .. }) => {
                     let expected_id = operation.id;
                     let op_refs = operations.get_op_refs();
                     assert!(op_refs.contains(&expected_id));
@@ -755,6 +831,68 @@ async fn test_protocol_ask_operations_on_batch_received() {
     .await;
 }
 
+#[tokio::test]
+#[serial]
+async fn test_protocol_asks_operations_by_fee_density() {
+    let protocol_config = ProtocolConfig {
+        max_operations_per_message: 1,
+        ..tools::create_protocol_config()
+    };
+    protocol_test(
+        &protocol_config,
+        async move |mut network_controller,
+                    protocol_event_receiver,
+                    protocol_command_sender,
+                    protocol_manager,
+                    protocol_pool_event_receiver| {
+            // Create 1 node.
+            let mut nodes = tools::create_and_connect_nodes(1, &mut network_controller).await;
+
+            let creator_node = nodes.pop().expect("Failed to get node info.");
+
+            // 1. Create operations, announced with different fee densities
+            let op_1 = tools::create_operation_with_expire_period(&creator_node.keypair, 1);
+            let op_2 = tools::create_operation_with_expire_period(&creator_node.keypair, 2);
+            let op_3 = tools::create_operation_with_expire_period(&creator_node.keypair, 3);
+
+            // 2. Send the announcements to protocol.
+            network_controller
+                .send_operation_announcements(
+                    creator_node.id,
+                    vec![(op_1.id, 10), (op_2.id, 1000), (op_3.id, 100)],
+                )
+                .await;
+
+            // 3. The operations are asked one by one, by decreasing fee density.
+            for expected_operation_id in [op_2.id, op_3.id, op_1.id] {
+                match network_controller
+                    .wait_command(1000.into(), |cmd| match cmd {
+                        cmd @ NetworkCommand::AskForOperations { .. } => Some(cmd),
+                        _ => None,
+                    })
+                    .await
+                {
+                    Some(NetworkCommand::AskForOperations { to_node, wishlist }) => {
+                        assert_eq!(wishlist.len(), 1);
+                        assert!(wishlist.contains(&expected_operation_id.prefix()));
+                        assert_eq!(to_node, creator_node.id);
+                    }
+                    _ => panic!("Unexpected or no network command."),
+                };
+            }
+
+            (
+                network_controller,
+                protocol_event_receiver,
+                protocol_command_sender,
+                protocol_manager,
+                protocol_pool_event_receiver,
+            )
+        },
+    )
+    .await;
+}
+
 #[tokio::test]
 #[serial]
 async fn test_protocol_re_ask_operations_to_another_node_on_batch_received_after_delay() {
@@ -966,3 +1104,140 @@ async fn test_protocol_on_ask_operations() {
     )
     .await;
 }
+
+#[tokio::test]
+#[serial]
+async fn test_protocol_asks_missing_operations_again_a_bounded_number_of_times() {
+    let protocol_config = &tools::PROTOCOL_CONFIG;
+    protocol_test(
+        protocol_config,
+        async move |mut network_controller,
+                    protocol_event_receiver,
+                    protocol_command_sender,
+                    protocol_manager,
+                    protocol_pool_event_receiver| {
+            // Create 1 node.
+            let mut nodes = tools::create_and_connect_nodes(1, &mut network_controller).await;
+
+            let creator_node = nodes.pop().expect("Failed to get node info.");
+
+            // 1. Create an operation
+            let operation = tools::create_operation_with_expire_period(&creator_node.keypair, 1);
+
+            let expected_operation_id = operation.id;
+            // 2. Send operation batch to protocol.
+            network_controller
+                .send_operation_batch(creator_node.id, vec![expected_operation_id])
+                .await;
+
+            // 3. The operation is never sent: it is asked once, then asked again
+            // `max_operation_ask_retries` times to the same node.
+            for _ in 0..=protocol_config.max_operation_ask_retries {
+                match network_controller
+                    .wait_command(2000.into(), |cmd| match cmd {
+                        cmd @ NetworkCommand::AskForOperations { .. } => Some(cmd),
+                        _ => None,
+                    })
+                    .await
+                {
+                    Some(NetworkCommand::AskForOperations { to_node, wishlist }) => {
+                        assert_eq!(wishlist.len(), 1);
+                        assert!(wishlist.contains(&expected_operation_id.prefix()));
+                        assert_eq!(to_node, creator_node.id);
+                    }
+                    _ => panic!("Unexpected or no network command."),
+                };
+            }
+
+            // 4. Then protocol gives up.
+            if let Some(NetworkCommand::AskForOperations { .. }) = network_controller
+                .wait_command(2000.into(), |cmd| match cmd {
+                    cmd @ NetworkCommand::AskForOperations { .. } => Some(cmd),
+                    _ => None,
+                })
+                .await
+            {
+                panic!("Unexpected ask for operations");
+            }
+
+            (
+                network_controller,
+                protocol_event_receiver,
+                protocol_command_sender,
+                protocol_manager,
+                protocol_pool_event_receiver,
+            )
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_protocol_on_ask_operations_bounds_retransmissions() {
+    let protocol_config = &tools::PROTOCOL_CONFIG;
+    protocol_test_with_storage(
+        protocol_config,
+        async move |mut network_controller,
+                    protocol_event_receiver,
+                    protocol_command_sender,
+                    protocol_manager,
+                    protocol_pool_event_receiver,
+                    mut storage| {
+            // Create 2 nodes.
+            let mut nodes = tools::create_and_connect_nodes(2, &mut network_controller).await;
+
+            let creator_node = nodes.pop().expect("Failed to get node info.");
+
+            // 1. Create an operation and store it in shared storage.
+            let operation = tools::create_operation_with_expire_period(&creator_node.keypair, 1);
+            let expected_operation_id = operation.id;
+            storage.store_operations(vec![operation]);
+
+            // 2. A node keeps asking for the operation: it is sent again
+            // `max_operation_ask_retries` times after the first time.
+            let asker_node = nodes.pop().expect("Failed to get the second node info.");
+            for _ in 0..=protocol_config.max_operation_ask_retries {
+                network_controller
+                    .send_ask_for_operation(asker_node.id, vec![expected_operation_id])
+                    .await;
+                match network_controller
+                    .wait_command(1000.into(), |cmd| match cmd {
+                        cmd @ NetworkCommand::SendOperations { .. } => Some(cmd),
+                        _ => None,
+                    })
+                    .await
+                {
+                    Some(NetworkCommand::SendOperations { node, operations }) => {
+                        assert_eq!(asker_node.id, node);
+                        assert_eq!(operations.len(), 1);
+                    }
+                    _ => panic!("Unexpected or no network command."),
+                };
+            }
+
+            // 3. Further asks are not answered anymore.
+            network_controller
+                .send_ask_for_operation(asker_node.id, vec![expected_operation_id])
+                .await;
+            if let Some(NetworkCommand::SendOperations { .. }) = network_controller
+                .wait_command(1000.into(), |cmd| match cmd {
+                    cmd @ NetworkCommand::SendOperations { .. } => Some(cmd),
+                    _ => None,
+                })
+                .await
+            {
+                panic!("Unexpected operations sent");
+            }
+
+            (
+                network_controller,
+                protocol_event_receiver,
+                protocol_command_sender,
+                protocol_manager,
+                protocol_pool_event_receiver,
+            )
+        },
+    )
+    .await;
+}
@@ -7,21 +7,30 @@
 //! 2) ask for operations
 //! 3) send batches
 //! 4) answer operations
+//! 5) ask again for the operations still missing, a bounded number of times
+//!
+//! Asking for operations acknowledges an announcement: it tells the announcing node which of the
+//! announced operations we actually want. The announcing node only sends back asked operations,
+//! and sends the same operation again to a node at most `max_operation_ask_retries` times.
+//!
+//! Announcements give the fee density of each operation: the operations with the highest
+//! fee density are asked first, in messages of at most `max_operations_per_message` operations.
 
+use std::cmp::Reverse;
 use std::collections::VecDeque;
 
 use crate::protocol_worker::ProtocolWorker;
 use massa_logging::massa_trace;
 use massa_models::{
     node::NodeId,
-    operation::{OperationPrefixIds, WrappedOperation},
+    operation::{OperationAnnouncements, OperationPrefixIds, WrappedOperation},
     prehash::CapacityAllocator,
 };
 use massa_protocol_exports::ProtocolError;
 use massa_time::TimeError;
 use std::pin::Pin;
 use tokio::time::{sleep_until, Instant, Sleep};
-use tracing::warn;
+use tracing::{debug, warn};
 
 /// Structure containing a Batch of `operation_ids` we would like to ask
 /// to a `node_id` now or later. Mainly used in protocol and translated into
@@ -31,13 +40,29 @@ pub struct OperationBatchItem {
     pub instant: Instant,
     /// node id
     pub node_id: NodeId,
-    /// operation prefix ids
-    pub operations_prefix_ids: OperationPrefixIds,
+    /// operation prefix ids along their fee density
+    pub operations_prefix_ids: OperationAnnouncements,
 }
 
 /// Queue containing every `[OperationsBatchItem]` we want to ask now or later.
 pub type OperationBatchBuffer = VecDeque<OperationBatchItem>;
 
+/// Structure containing a batch of operations asked to a `node_id`,
+/// asked again to it if some of them are still missing at `deadline`.
+pub struct OperationAskItem {
+    /// instant at which the missing operations are asked again
+    pub deadline: Instant,
+    /// node id
+    pub node_id: NodeId,
+    /// asked operation prefix ids
+    pub operations_prefix_ids: OperationPrefixIds,
+    /// number of times the operations were already asked again
+    pub retries: u32,
+}
+
+/// Queue containing every `[OperationAskItem]` waiting for its operations, by deadline.
+pub type OperationAskBuffer = VecDeque<OperationAskItem>;
+
 impl ProtocolWorker {
     /// On receive a batch of operation ids `op_batch` from another `node_id`
     /// Execute the following algorithm: [redirect to GitHub](https://github.com/massalabs/massa/issues/2283#issuecomment-1040872779)
@@ -57,27 +82,32 @@ impl ProtocolWorker {
     ///                    future_set.add(op_id)
     ///    if op_batch_buf is not full:
     ///        op_batch_buf.push(now+op_batch_proc_period, node_id, future_set)
-    ///    ask ask_set to node_id
+    ///    ask ask_set to node_id, by decreasing fee density
     ///```
     pub(crate) async fn on_operations_announcements_received(
         &mut self,
-        mut op_batch: OperationPrefixIds,
+        mut op_batch: OperationAnnouncements,
         node_id: NodeId,
     ) -> Result<(), ProtocolError> {
         // mark sender as knowing the ops
         if let Some(node_info) = self.active_nodes.get_mut(&node_id) {
-            node_info.insert_known_ops(op_batch.iter().copied());
+            node_info.insert_known_ops(op_batch.keys().copied());
+        }
+
+        // a light node does not fetch operations
+        if self.config.light_mode {
+            return Ok(());
         }
 
         // filter out the operations that we already know about
-        op_batch.retain(|prefix| !self.checked_operations.contains_prefix(prefix));
+        op_batch.retain(|prefix, _| !self.checked_operations.contains_prefix(prefix));
 
-        let mut ask_set = OperationPrefixIds::with_capacity(op_batch.len());
-        let mut future_set = OperationPrefixIds::with_capacity(op_batch.len());
+        let mut ask_set = OperationAnnouncements::with_capacity(op_batch.len());
+        let mut future_set = OperationAnnouncements::with_capacity(op_batch.len());
         // exactitude isn't important, we want to have a now for that function call
         let now = Instant::now();
         let mut count_reask = 0;
-        for op_id in op_batch {
+        for (op_id, fee_density) in op_batch {
             let wish = match self.asked_operations.get_mut(&op_id) {
                 Some(wish) => {
                     if wish.1.contains(&node_id) {
@@ -97,14 +127,14 @@ impl ProtocolWorker {
                         .ok_or(TimeError::TimeOverflowError)?
                 {
                     count_reask += 1;
-                    ask_set.insert(op_id);
+                    ask_set.insert(op_id, fee_density);
                     wish.0 = now;
                     wish.1.push(node_id);
                 } else {
-                    future_set.insert(op_id);
+                    future_set.insert(op_id, fee_density);
                 }
             } else {
-                ask_set.insert(op_id);
+                ask_set.insert(op_id, fee_density);
                 self.asked_operations.insert(op_id, (now, vec![node_id]));
             }
         } // EndOf for op_id in op_batch:
@@ -124,14 +154,74 @@ impl ProtocolWorker {
             });
         }
 
-        if !ask_set.is_empty() {
+        // ask the operations with the highest fee density first
+        let mut ask_list: Vec<_> = ask_set.into_iter().collect();
+        ask_list.sort_unstable_by_key(|(prefix, fee_density)| (Reverse(*fee_density), *prefix));
+        let deadline = now
+            .checked_add(self.config.operation_ask_timeout.into())
+            .ok_or(TimeError::TimeOverflowError)?;
+        for chunk in ask_list.chunks(self.config.max_operations_per_message.max(1) as usize) {
+            let ask_set: OperationPrefixIds = chunk.iter().map(|(prefix, _)| *prefix).collect();
             self.network_command_sender
-                .send_ask_for_operations(node_id, ask_set)
+                .send_ask_for_operations(node_id, ask_set.clone())
                 .await
-                .map_err(|_| ProtocolError::ChannelError("send ask for operations failed".into()))
-        } else {
-            Ok(())
+                .map_err(|_| {
+                    ProtocolError::ChannelError("send ask for operations failed".into())
+                })?;
+            self.pending_operation_asks.push_back(OperationAskItem {
+                deadline,
+                node_id,
+                operations_prefix_ids: ask_set,
+                retries: 0,
+            });
         }
+        Ok(())
+    }
+
+    /// Ask again to the nodes the operations they did not send us in time,
+    /// giving up after `max_operation_ask_retries` retries.
+    pub(crate) async fn retry_operation_asks(&mut self) -> Result<(), ProtocolError> {
+        let now = Instant::now();
+        let mut retried_items = Vec::new();
+        while !self.pending_operation_asks.is_empty()
+        // This unwrap is ok because we checked that it's not empty just before.
+            && now >= self.pending_operation_asks.front().unwrap().deadline
+        {
+            let mut item = self.pending_operation_asks.pop_front().unwrap();
+
+            // forget the operations received in the meantime
+            item.operations_prefix_ids
+                .retain(|prefix| !self.checked_operations.contains_prefix(prefix));
+            if item.operations_prefix_ids.is_empty()
+                || !self.active_nodes.contains_key(&item.node_id)
+            {
+                continue;
+            }
+            if item.retries >= self.config.max_operation_ask_retries {
+                debug!(
+                    "node {} did not send {} asked operations after {} retries",
+                    item.node_id,
+                    item.operations_prefix_ids.len(),
+                    item.retries
+                );
+                continue;
+            }
+
+            massa_trace!("protocol.protocol_worker.retry_operation_asks", { "node": item.node_id, "count": item.operations_prefix_ids.len(), "retries": item.retries });
+            self.network_command_sender
+                .send_ask_for_operations(item.node_id, item.operations_prefix_ids.clone())
+                .await
+                .map_err(|_| {
+                    ProtocolError::ChannelError("send ask for operations failed".into())
+                })?;
+            item.retries += 1;
+            item.deadline = now
+                .checked_add(self.config.operation_ask_timeout.into())
+                .ok_or(TimeError::TimeOverflowError)?;
+            retried_items.push(item);
+        }
+        self.pending_operation_asks.extend(retried_items);
+        Ok(())
     }
 
     /// On full operations are received from the network,
@@ -184,15 +274,20 @@ impl ProtocolWorker {
             )
             .await?;
         }
+        self.retry_operation_asks().await?;
+
         // reset timer
-        if let Some(item) = self.op_batch_buffer.front() {
-            operation_batch_proc_period_timer.set(sleep_until(item.instant));
-        } else {
-            let next_tick = now
+        let next_tick = match self.op_batch_buffer.front() {
+            Some(item) => item.instant,
+            None => now
                 .checked_add(self.config.operation_batch_proc_period.into())
-                .ok_or(TimeError::TimeOverflowError)?;
-            operation_batch_proc_period_timer.set(sleep_until(next_tick));
-        }
+                .ok_or(TimeError::TimeOverflowError)?,
+        };
+        let next_tick = match self.pending_operation_asks.front() {
+            Some(item) => next_tick.min(item.deadline),
+            None => next_tick,
+        };
+        operation_batch_proc_period_timer.set(sleep_until(next_tick));
 
         Ok(())
     }
@@ -200,11 +295,16 @@ impl ProtocolWorker {
     /// Process the reception of a batch of asked operations, that means that
     /// we have already sent a batch of ids in the network, notifying that we already
     /// have those operations.
+    /// The operations already sent `max_operation_ask_retries` times again to the node are not sent anymore.
     pub(crate) async fn on_asked_operations_received(
         &mut self,
         node_id: NodeId,
-        op_pre_ids: OperationPrefixIds,
+        mut op_pre_ids: OperationPrefixIds,
     ) -> Result<(), ProtocolError> {
+        if let Some(node_info) = self.active_nodes.get_mut(&node_id) {
+            let max_sends = self.config.max_operation_ask_retries.saturating_add(1);
+            op_pre_ids.retain(|prefix| node_info.try_note_sent_op(*prefix, max_sends));
+        }
         if op_pre_ids.is_empty() {
             return Ok(());
         }
@@ -0,0 +1,83 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Configuration of the calls made by a client: timeout, retries and backoff between them.
+
+use std::time::Duration;
+
+/// Configuration of the calls made by a client
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// maximum duration of a call to an endpoint
+    pub timeout: Duration,
+    /// retries of the calls that failed without an answer of the node
+    pub retry: RetryPolicy,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        ClientConfig {
+            timeout: Duration::from_millis(10000),
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+/// Retries of a call on an endpoint before failing over to the next one.
+///
+/// Only the calls that failed without an answer of the node (connection error, timeout) are retried:
+/// an error returned by the node would be returned again.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// number of retries of a call on each endpoint, after the first attempt
+    pub max_retries: u32,
+    /// delay before the first retry, doubled before each of the next ones
+    pub initial_backoff: Duration,
+    /// maximum delay between two attempts
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_millis(5000),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy calling each endpoint only once
+    pub fn no_retry() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Delay before the retry of index `retry`, starting from 0
+    pub fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_backoff() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(1000),
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(800));
+        assert_eq!(policy.backoff(4), Duration::from_millis(1000));
+        assert_eq!(policy.backoff(40), Duration::from_millis(1000));
+    }
+}
@@ -5,25 +5,46 @@
 #![warn(unused_crate_dependencies)]
 
 use jsonrpc_core_client::transports::http;
-use jsonrpc_core_client::{RpcChannel, RpcError, RpcResult, TypedClient};
+use jsonrpc_core_client::{RpcError, RpcResult, TypedClient};
+use massa_ledger_exports::LedgerEntryProof;
 use massa_models::api::{
-    AddressInfo, BlockInfo, BlockSummary, DatastoreEntryInput, DatastoreEntryOutput,
-    EndorsementInfo, EventFilter, NodeStatus, OperationInfo, OperationInput,
-    ReadOnlyBytecodeExecution, ReadOnlyCall, TimeInterval,
+    AddressActivity, AddressDraws, AddressDrawsQuery, AddressHistoryQuery, AddressInfo,
+    AddressesPageInput, BlockInfo, BlockSummary, BlockTemplate, ContractsInput, ContractsOutput,
+    CycleStatsQuery, DatastoreEntryInput, DatastoreEntryOutput, DatastoreKeysInput,
+    DatastoreKeysOutput, DeferredCreditSchedule, DeferredCreditsInput, DeferredCreditsOutput,
+    EndorsementDiagnostic, EndorsementInfo, EventFilter, GraphDotQuery, LedgerProofInput,
+    NodeStatus, NodeSubsystem, OperationInfo, OperationInput, OperationPoolStatusInfo,
+    PeerScoreInfo, ReadOnlyBytecodeExecution, ReadOnlyCall, ReorgInfo, SlotExecutionTrace,
+    StakingKeyRotation, StakingKeyRotationInput, StakingReport, SubsystemAction, TimeInterval,
 };
+use massa_models::block::Block;
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
 use massa_models::execution::ExecuteReadOnlyResponse;
 use massa_models::node::NodeId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::{PreHashMap, PreHashSet};
+use massa_models::stats::{
+    CycleStats, ExecutionPolicyStats, FeeRecommendation, LedgerIntegrityReport,
+    NetworkActivityStats,
+};
 use massa_models::{
-    address::Address, block::BlockId, endorsement::EndorsementId, operation::OperationId,
+    address::Address,
+    block::BlockId,
+    endorsement::{EndorsementEquivocation, EndorsementId},
+    operation::OperationId,
+    slot::Slot,
 };
 
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use serde_json::Value;
 use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+mod config;
+
+pub use config::{ClientConfig, RetryPolicy};
 
 /// Client
 pub struct Client {
@@ -45,53 +66,142 @@ impl Client {
             private: RpcClient::from_url(&private_url).await,
         }
     }
+
+    /// Creates a client whose public calls fail over from an endpoint to the next one.
+    /// The private calls only target the node of `private_url`, as they act on its own state.
+    pub async fn from_endpoints(
+        public_urls: &[String],
+        private_url: &str,
+        config: ClientConfig,
+    ) -> RpcResult<Client> {
+        Ok(Client {
+            public: RpcClient::from_urls(public_urls, config.clone()).await?,
+            private: RpcClient::from_urls(&[private_url.to_string()], config).await?,
+        })
+    }
 }
 
-/// TODO ask @yvan-sraka
-pub struct RpcClient {
+/// Endpoint of a client
+struct Endpoint {
+    url: String,
     client: TypedClient,
-    timeout: u64,
 }
 
-/// This is required by `jsonrpc_core_client::transports::http::connect`
-impl From<RpcChannel> for RpcClient {
-    fn from(channel: RpcChannel) -> Self {
-        RpcClient {
-            client: channel.into(),
-            timeout: 10000,
-        }
-    }
+/// Typed client of the API of a node, failing over between several endpoints
+pub struct RpcClient {
+    endpoints: Vec<Endpoint>,
+    /// index of the endpoint that answered last, tried first
+    current: AtomicUsize,
+    config: ClientConfig,
 }
 
 impl RpcClient {
     /// Default constructor
     pub async fn from_url(url: &str) -> RpcClient {
-        match http::connect::<RpcClient>(url).await {
+        match RpcClient::from_urls(&[url.to_string()], ClientConfig::default()).await {
             Ok(client) => client,
             Err(_) => panic!("unable to connect to Node."),
         }
     }
 
-    /// Typed wrapper to API calls based on the method given by `jsonrpc_core_client`
+    /// Creates a client calling the endpoints of `urls` in turn, starting from the first one,
+    /// and moving to the next one when the current one does not answer
+    pub async fn from_urls(urls: &[String], config: ClientConfig) -> RpcResult<RpcClient> {
+        if urls.is_empty() {
+            return Err(RpcError::Client("no endpoint given".into()));
+        }
+        let mut endpoints = Vec::with_capacity(urls.len());
+        for url in urls {
+            endpoints.push(Endpoint {
+                url: url.clone(),
+                client: http::connect::<TypedClient>(url).await?,
+            });
+        }
+        Ok(RpcClient {
+            endpoints,
+            current: AtomicUsize::new(0),
+            config,
+        })
+    }
+
+    /// URL of the endpoint tried first by the next call
+    pub fn current_url(&self) -> &str {
+        &self.endpoints[self.current.load(Ordering::Relaxed)].url
+    }
+
+    /// Typed wrapper to API calls based on the method given by `jsonrpc_core_client`.
+    ///
+    /// A call failing without an answer of the node is retried on the same endpoint
+    /// according to the retry policy, then on the next endpoints in turn.
     async fn call_method<T: Serialize, R: DeserializeOwned>(
         &self,
         method: &str,
         returns: &str,
         args: T,
+    ) -> RpcResult<R> {
+        // serialized once for all the attempts
+        let args = serde_json::to_value(args)
+            .map_err(|e| RpcError::Client(format!("invalid arguments for {}: {}", method, e)))?;
+        let first = self.current.load(Ordering::Relaxed);
+        let mut last_error = None;
+        for offset in 0..self.endpoints.len() {
+            let index = (first + offset) % self.endpoints.len();
+            let endpoint = &self.endpoints[index];
+            for retry in 0..=self.config.retry.max_retries {
+                if retry > 0 {
+                    tokio::time::sleep(self.config.retry.backoff(retry - 1)).await;
+                }
+                match self
+                    .call_endpoint(endpoint, method, returns, args.clone())
+                    .await
+                {
+                    Ok(result) => {
+                        self.current.store(index, Ordering::Relaxed);
+                        return Ok(result);
+                    }
+                    // the node answered: another attempt would get the same answer
+                    Err(err @ (RpcError::JsonRpcError(_) | RpcError::ParseError(..))) => {
+                        return Err(err)
+                    }
+                    Err(err) => last_error = Some(err),
+                }
+            }
+        }
+        Err(last_error.expect("a client has at least one endpoint"))
+    }
+
+    /// Calls a method on an endpoint, within the timeout of the client
+    async fn call_endpoint<R: DeserializeOwned>(
+        &self,
+        endpoint: &Endpoint,
+        method: &str,
+        returns: &str,
+        args: Value,
     ) -> RpcResult<R> {
         tokio::time::timeout(
-            tokio::time::Duration::from_millis(self.timeout),
-            self.client.call_method(method, returns, args),
+            self.config.timeout,
+            endpoint.client.call_method(method, returns, args),
         )
         .await
-        .map_err(|e| RpcError::Client(format!("timeout during {}: {}", method, e)))?
+        .map_err(|e| {
+            RpcError::Client(format!(
+                "timeout during {} on {}: {}",
+                method, endpoint.url, e
+            ))
+        })?
     }
 
-    /// Gracefully stop the node.
+    /// Stop the node.
     pub async fn stop_node(&self) -> RpcResult<()> {
         self.call_method("stop_node", "()", ()).await
     }
 
+    /// Stop the node after saving the pool, executing the pending final slots
+    /// and flushing the final ledger.
+    pub async fn stop_node_gracefully(&self) -> RpcResult<()> {
+        self.call_method("stop_node_gracefully", "()", ()).await
+    }
+
     /// Sign message with node's key.
     /// Returns the public key that signed the message and the signature.
     pub async fn node_sign_message(&self, message: Vec<u8>) -> RpcResult<PubkeySig> {
@@ -119,6 +229,63 @@ impl RpcClient {
             .await
     }
 
+    /// Sell rolls of a staking address of the node.
+    /// Returns the id of the sent roll sell operation.
+    pub async fn node_sell_staking_rolls(
+        &self,
+        address: Address,
+        roll_count: u64,
+    ) -> RpcResult<OperationId> {
+        self.call_method(
+            "node_sell_staking_rolls",
+            "OperationId",
+            (address, roll_count),
+        )
+        .await
+    }
+
+    /// Stop, restart, pause or resume an individual subsystem of the node.
+    pub async fn node_manage_subsystem(
+        &self,
+        subsystem: NodeSubsystem,
+        action: SubsystemAction,
+    ) -> RpcResult<()> {
+        self.call_method("node_manage_subsystem", "()", (subsystem, action))
+            .await
+    }
+
+    /// Return the staking performance report of each staking address of the node.
+    pub async fn get_staking_report(&self) -> RpcResult<Vec<StakingReport>> {
+        self.call_method("get_staking_report", "Vec<StakingReport>", ())
+            .await
+    }
+
+    /// Replace staking addresses of the node: the new keys stake right away
+    /// and the old addresses are retired once the rolls of the new ones are active.
+    pub async fn node_rotate_staking_keys(
+        &self,
+        input: StakingKeyRotationInput,
+    ) -> RpcResult<StakingKeyRotation> {
+        self.call_method(
+            "node_rotate_staking_keys",
+            "StakingKeyRotation",
+            vec![input],
+        )
+        .await
+    }
+
+    /// Return the staking key rotations started since the node started.
+    pub async fn get_staking_key_rotations(&self) -> RpcResult<Vec<StakingKeyRotation>> {
+        self.call_method("get_staking_key_rotations", "Vec<StakingKeyRotation>", ())
+            .await
+    }
+
+    /// Return the operations left out of the blocks produced by the node because of its execution policy.
+    pub async fn get_execution_policy_stats(&self) -> RpcResult<ExecutionPolicyStats> {
+        self.call_method("get_execution_policy_stats", "ExecutionPolicyStats", ())
+            .await
+    }
+
     /// Bans given ip address(es)
     /// No confirmation to expect.
     pub async fn node_ban_by_ip(&self, ips: Vec<IpAddr>) -> RpcResult<()> {
@@ -155,6 +322,41 @@ impl RpcClient {
             .await
     }
 
+    /// known peers with their quality metrics and score, best scored first
+    pub async fn node_get_peer_scores(&self) -> RpcResult<Vec<PeerScoreInfo>> {
+        self.call_method("node_get_peer_scores", "Vec<PeerScoreInfo>", ())
+            .await
+    }
+
+    /// export a snapshot of the disk ledger to a file of the node host
+    pub async fn node_export_ledger_snapshot(&self, path: String) -> RpcResult<Slot> {
+        self.call_method("node_export_ledger_snapshot", "Slot", vec![path])
+            .await
+    }
+
+    /// check the whole final ledger against its hash and Merkle tree, rebuilding them if `repair` is true
+    pub async fn node_verify_ledger_integrity(
+        &self,
+        repair: bool,
+    ) -> RpcResult<LedgerIntegrityReport> {
+        self.call_method(
+            "node_verify_ledger_integrity",
+            "LedgerIntegrityReport",
+            vec![repair],
+        )
+        .await
+    }
+
+    /// replay a non-final slot in trace mode
+    pub async fn node_replay_slot_with_trace(&self, slot: Slot) -> RpcResult<SlotExecutionTrace> {
+        self.call_method(
+            "node_replay_slot_with_trace",
+            "SlotExecutionTrace",
+            vec![slot],
+        )
+        .await
+    }
+
     ////////////////
     // public-api //
     ////////////////
@@ -166,14 +368,39 @@ impl RpcClient {
         self.call_method("get_status", "NodeStatus", ()).await
     }
 
-    pub(crate) async fn _get_cliques(&self) -> RpcResult<Vec<Clique>> {
+    /// Get network-wide activity statistics over the `window` latest final slots
+    pub async fn get_network_stats(&self, window: u64) -> RpcResult<NetworkActivityStats> {
+        self.call_method("get_network_stats", "NetworkActivityStats", vec![window])
+            .await
+    }
+
+    /// Get the roll distribution and the production statistics of the stakers for a final cycle
+    pub async fn get_cycle_stats(&self, query: CycleStatsQuery) -> RpcResult<CycleStats> {
+        self.call_method("get_cycle_stats", "CycleStats", vec![query])
+            .await
+    }
+
+    /// Get low, medium and high fee suggestions based on the latest final blocks and the pool pressure
+    pub async fn get_fee_recommendation(&self) -> RpcResult<FeeRecommendation> {
+        self.call_method("get_fee_recommendation", "FeeRecommendation", ())
+            .await
+    }
+
+    /// Get the maximal cliques of the block graph
+    pub async fn get_cliques(&self) -> RpcResult<Vec<Clique>> {
         self.call_method("get_cliques", "Vec<Clique>", ()).await
     }
 
+    /// Get the latest blockclique re-organizations, oldest first
+    pub async fn get_reorg_history(&self) -> RpcResult<Vec<ReorgInfo>> {
+        self.call_method("get_reorg_history", "Vec<ReorgInfo>", ())
+            .await
+    }
+
     // Debug (specific information)
 
     /// Returns the active stakers and their roll counts for the current cycle.
-    pub(crate) async fn _get_stakers(&self) -> RpcResult<PreHashMap<Address, u64>> {
+    pub async fn get_stakers(&self) -> RpcResult<PreHashMap<Address, u64>> {
         self.call_method("get_stakers", "Map<Address, u64>", ())
             .await
     }
@@ -206,6 +433,18 @@ impl RpcClient {
             .await
     }
 
+    /// Get the block at a slot in the blockclique, `None` if there is no block at this slot
+    pub async fn get_blockclique_block_by_slot(&self, slot: Slot) -> RpcResult<Option<Block>> {
+        self.call_method("get_blockclique_block_by_slot", "Option<Block>", vec![slot])
+            .await
+    }
+
+    /// Get the candidate block the node would produce at a given slot
+    pub async fn get_block_template(&self, slot: Slot) -> RpcResult<BlockTemplate> {
+        self.call_method("get_block_template", "BlockTemplate", vec![slot])
+            .await
+    }
+
     /// Get events emitted by smart contracts with various filters
     pub async fn get_filtered_sc_output_event(
         &self,
@@ -221,7 +460,7 @@ impl RpcClient {
 
     /// Get the block graph within the specified time interval.
     /// Optional parameters: from `<time_start>` (included) and to `<time_end>` (excluded) millisecond timestamp
-    pub(crate) async fn _get_graph_interval(
+    pub async fn get_graph_interval(
         &self,
         time_interval: TimeInterval,
     ) -> RpcResult<Vec<BlockSummary>> {
@@ -229,12 +468,90 @@ impl RpcClient {
             .await
     }
 
+    /// Get a rendering of the block graph in the DOT language of graphviz
+    pub async fn get_graph_dot(&self, query: GraphDotQuery) -> RpcResult<String> {
+        self.call_method("get_graph_dot", "String", vec![query])
+            .await
+    }
+
     /// Get info by addresses
     pub async fn get_addresses(&self, addresses: Vec<Address>) -> RpcResult<Vec<AddressInfo>> {
         self.call_method("get_addresses", "Vec<AddressInfo>", vec![addresses])
             .await
     }
 
+    /// Get info by addresses, with bounded datastore keys and cycle infos and only the selected fields
+    pub async fn get_addresses_page(
+        &self,
+        input: AddressesPageInput,
+    ) -> RpcResult<Vec<AddressInfo>> {
+        self.call_method("get_addresses_page", "Vec<AddressInfo>", vec![input])
+            .await
+    }
+
+    /// Resolve names (`name.massa`) to addresses, `None` for unknown names
+    pub async fn resolve_names(&self, names: Vec<String>) -> RpcResult<Vec<Option<Address>>> {
+        self.call_method("resolve_names", "Vec<Option<Address>>", vec![names])
+            .await
+    }
+
+    /// Get the recent endorsement draws of addresses and what became of them
+    pub async fn get_endorsement_diagnostics(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<EndorsementDiagnostic>> {
+        self.call_method(
+            "get_endorsement_diagnostics",
+            "Vec<EndorsementDiagnostic>",
+            vec![addresses],
+        )
+        .await
+    }
+
+    /// Get the block and endorsement draws of addresses over a slot range, as far as they are known
+    pub async fn get_address_draws(
+        &self,
+        query: AddressDrawsQuery,
+    ) -> RpcResult<Vec<AddressDraws>> {
+        self.call_method("get_address_draws", "Vec<AddressDraws>", vec![query])
+            .await
+    }
+
+    /// Get the activity history of an address over a slot range, in chronological order
+    pub async fn get_address_history(
+        &self,
+        query: AddressHistoryQuery,
+    ) -> RpcResult<Vec<AddressActivity>> {
+        self.call_method("get_address_history", "Vec<AddressActivity>", vec![query])
+            .await
+    }
+
+    /// Get the conflicting endorsements recently created by addresses (all of them if `addresses` is empty)
+    pub async fn get_endorsement_equivocations(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<EndorsementEquivocation>> {
+        self.call_method(
+            "get_endorsement_equivocations",
+            "Vec<EndorsementEquivocation>",
+            vec![addresses],
+        )
+        .await
+    }
+
+    /// Get the status of operations in the pool, with the reason of their eviction if they were evicted
+    pub async fn get_operation_pool_status(
+        &self,
+        operation_ids: Vec<OperationId>,
+    ) -> RpcResult<Vec<OperationPoolStatusInfo>> {
+        self.call_method(
+            "get_operation_pool_status",
+            "Vec<OperationPoolStatusInfo>",
+            vec![operation_ids],
+        )
+        .await
+    }
+
     /// Get datastore entries
     pub async fn get_datastore_entries(
         &self,
@@ -248,6 +565,56 @@ impl RpcClient {
         .await
     }
 
+    /// Get a page of the datastore keys of an address
+    pub async fn get_datastore_keys(
+        &self,
+        input: DatastoreKeysInput,
+    ) -> RpcResult<DatastoreKeysOutput> {
+        self.call_method("get_datastore_keys", "DatastoreKeysOutput", vec![input])
+            .await
+    }
+
+    /// Get a page of the final contracts
+    pub async fn get_contracts(&self, input: ContractsInput) -> RpcResult<ContractsOutput> {
+        self.call_method("get_contracts", "ContractsOutput", vec![input])
+            .await
+    }
+
+    /// Get a page of the deferred credits scheduled in a slot range
+    pub async fn get_deferred_credits(
+        &self,
+        input: DeferredCreditsInput,
+    ) -> RpcResult<DeferredCreditsOutput> {
+        self.call_method("get_deferred_credits", "DeferredCreditsOutput", vec![input])
+            .await
+    }
+
+    /// Get the deferred credits of addresses, upcoming and already paid, with the roll sells they reimburse
+    pub async fn get_deferred_credit_schedule(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<DeferredCreditSchedule>> {
+        self.call_method(
+            "get_deferred_credit_schedule",
+            "Vec<DeferredCreditSchedule>",
+            vec![addresses],
+        )
+        .await
+    }
+
+    /// Get a proof of inclusion of a final balance or datastore entry
+    pub async fn get_ledger_entry_proof(
+        &self,
+        input: LedgerProofInput,
+    ) -> RpcResult<Option<LedgerEntryProof>> {
+        self.call_method(
+            "get_ledger_entry_proof",
+            "Option<LedgerEntryProof>",
+            vec![input],
+        )
+        .await
+    }
+
     // User (interaction with the node)
 
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
@@ -290,4 +657,9 @@ impl RpcClient {
         .pop()
         .ok_or_else(|| RpcError::Client("missing return value on execute_read_only_call".into()))
     }
+
+    /// Get the OpenRPC specification of the API
+    pub async fn get_openrpc_spec(&self) -> RpcResult<Value> {
+        self.call_method("rpc.discover", "Value", ()).await
+    }
 }
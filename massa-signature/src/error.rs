@@ -15,4 +15,10 @@ pub enum MassaSignatureError {
 
     /// Wrong prefix for hash: expected {0}, got {1}
     WrongPrefix(String, String),
+
+    /// Unsupported key version: {0}
+    UnsupportedVersion(u64),
+
+    /// Key version {0} can not sign network objects, whose wire formats only carry version 0 keys
+    WireUnsupportedVersion(u64),
 }
@@ -8,6 +8,8 @@ mod signature_impl;
 
 pub use error::MassaSignatureError;
 pub use signature_impl::{
-    verify_signature_batch, KeyPair, PublicKey, PublicKeyDeserializer, Signature,
-    SignatureDeserializer, PUBLIC_KEY_SIZE_BYTES, SECRET_KEY_BYTES_SIZE, SIGNATURE_SIZE_BYTES,
+    verify_signature_batch, verify_signature_batch_parallel, KeyPair, PublicKey,
+    PublicKeyDeserializer, Signature, SignatureDeserializer, VersionedPublicKeyDeserializer,
+    KEYPAIR_VERSION_ED25519, KEYPAIR_VERSION_ED25519PH, PUBLIC_KEY_SIZE_BYTES,
+    SECRET_KEY_BYTES_SIZE, SIGNATURE_SIZE_BYTES, SUPPORTED_KEYPAIR_VERSIONS,
 };
@@ -11,11 +11,13 @@ use nom::{
     IResult,
 };
 use rand::rngs::OsRng;
+use rayon::{prelude::ParallelIterator, slice::ParallelSlice};
 use serde::{
     de::{MapAccess, SeqAccess, Visitor},
     ser::SerializeStruct,
     Deserialize,
 };
+use sha2::{Digest, Sha512};
 use std::{borrow::Cow, cmp::Ordering, hash::Hasher, ops::Bound::Included};
 use std::{convert::TryInto, str::FromStr};
 
@@ -25,28 +27,61 @@ pub const PUBLIC_KEY_SIZE_BYTES: usize = ed25519_dalek::PUBLIC_KEY_LENGTH;
 pub const SECRET_KEY_BYTES_SIZE: usize = ed25519_dalek::SECRET_KEY_LENGTH;
 /// Size of a signature
 pub const SIGNATURE_SIZE_BYTES: usize = ed25519_dalek::SIGNATURE_LENGTH;
+
+/// Version of the keys signing the hashes with Ed25519
+pub const KEYPAIR_VERSION_ED25519: u64 = 0;
+/// Version of the keys signing the hashes with Ed25519ph:
+/// a SHA-512 prehash of the hash is signed, with the `ED25519PH_CONTEXT` domain separation
+pub const KEYPAIR_VERSION_ED25519PH: u64 = 1;
+/// Key versions this node can sign and verify with
+pub const SUPPORTED_KEYPAIR_VERSIONS: [u64; 2] =
+    [KEYPAIR_VERSION_ED25519, KEYPAIR_VERSION_ED25519PH];
+/// Domain separation context of the Ed25519ph signatures
+const ED25519PH_CONTEXT: &[u8] = b"massa-signature";
+
+/// Checks that a key version is known, before a key of that version is built
+fn check_keypair_version(version: u64) -> Result<(), MassaSignatureError> {
+    if SUPPORTED_KEYPAIR_VERSIONS.contains(&version) {
+        Ok(())
+    } else {
+        Err(MassaSignatureError::UnsupportedVersion(version))
+    }
+}
+
+/// Prehash of a hash signed by the Ed25519ph keys
+fn ed25519ph_prehash(hash: &Hash) -> Sha512 {
+    Sha512::new().chain(hash.to_bytes())
+}
+
 /// `KeyPair` is used for signature and decryption
-pub struct KeyPair(ed25519_dalek::Keypair);
+pub struct KeyPair {
+    /// signature algorithm version
+    version: u64,
+    keypair: ed25519_dalek::Keypair,
+}
 
 impl Clone for KeyPair {
     fn clone(&self) -> Self {
-        KeyPair(ed25519_dalek::Keypair {
-            // This will never error since self is a valid keypair
-            secret: ed25519_dalek::SecretKey::from_bytes(self.0.secret.as_bytes()).unwrap(),
-            public: self.0.public,
-        })
+        KeyPair {
+            version: self.version,
+            keypair: ed25519_dalek::Keypair {
+                // This will never error since self is a valid keypair
+                secret: ed25519_dalek::SecretKey::from_bytes(self.keypair.secret.as_bytes())
+                    .unwrap(),
+                public: self.keypair.public,
+            },
+        }
     }
 }
 
 const SECRET_PREFIX: char = 'S';
-const KEYPAIR_VERSION: u64 = 0;
 
 impl std::fmt::Display for KeyPair {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let u64_serializer = U64VarIntSerializer::new();
         let mut bytes = Vec::new();
         u64_serializer
-            .serialize(&KEYPAIR_VERSION, &mut bytes)
+            .serialize(&self.version, &mut bytes)
             .map_err(|_| std::fmt::Error)?;
         bytes.extend(self.to_bytes());
         write!(
@@ -79,15 +114,18 @@ impl FromStr for KeyPair {
                             MassaSignatureError::ParsingError(format!("bad secret key bs58: {}", s))
                         })?;
                 let u64_deserializer = U64VarIntDeserializer::new(Included(0), Included(u64::MAX));
-                let (rest, _version) = u64_deserializer
+                let (rest, version) = u64_deserializer
                     .deserialize::<DeserializeError>(&decoded_bs58_check[..])
                     .map_err(|err| MassaSignatureError::ParsingError(err.to_string()))?;
-                KeyPair::from_bytes(&rest.try_into().map_err(|_| {
-                    MassaSignatureError::ParsingError(format!(
-                        "secret key not long enough for: {}",
-                        s
-                    ))
-                })?)
+                KeyPair::from_versioned_bytes(
+                    version,
+                    &rest.try_into().map_err(|_| {
+                        MassaSignatureError::ParsingError(format!(
+                            "secret key not long enough for: {}",
+                            s
+                        ))
+                    })?,
+                )
             }
             _ => Err(MassaSignatureError::ParsingError(format!(
                 "bad secret prefix for: {}",
@@ -112,7 +150,55 @@ impl KeyPair {
     /// ```
     pub fn generate() -> Self {
         let mut rng = OsRng::default();
-        KeyPair(ed25519_dalek::Keypair::generate(&mut rng))
+        KeyPair {
+            version: KEYPAIR_VERSION_ED25519,
+            keypair: ed25519_dalek::Keypair::generate(&mut rng),
+        }
+    }
+
+    /// Generate a new `KeyPair` signing with the algorithm of a given key version
+    ///
+    /// # Example
+    ///  ```
+    /// # use massa_signature::{KeyPair, KEYPAIR_VERSION_ED25519PH};
+    /// # use massa_hash::Hash;
+    /// let keypair = KeyPair::generate_with_version(KEYPAIR_VERSION_ED25519PH).unwrap();
+    /// let data = Hash::compute_from("Hello World!".as_bytes());
+    /// let signature = keypair.sign(&data).unwrap();
+    /// assert!(keypair.get_public_key().verify_signature(&data, &signature).is_ok());
+    /// ```
+    pub fn generate_with_version(version: u64) -> Result<Self, MassaSignatureError> {
+        check_keypair_version(version)?;
+        let mut rng = OsRng::default();
+        Ok(KeyPair {
+            version,
+            keypair: ed25519_dalek::Keypair::generate(&mut rng),
+        })
+    }
+
+    /// Get the version of the keypair, giving its signature algorithm
+    pub fn get_version(&self) -> u64 {
+        self.version
+    }
+
+    /// Checks that the keypair can sign blocks, endorsements, operations and handshakes.
+    /// Their wire formats carry public keys without their version and read them as version 0 keys,
+    /// so the other nodes would derive another address from a key of another version
+    /// and reject its signatures.
+    ///
+    /// # Example
+    ///  ```
+    /// # use massa_signature::{KeyPair, KEYPAIR_VERSION_ED25519PH};
+    /// assert!(KeyPair::generate().check_wire_compatible().is_ok());
+    /// let keypair = KeyPair::generate_with_version(KEYPAIR_VERSION_ED25519PH).unwrap();
+    /// assert!(keypair.check_wire_compatible().is_err());
+    /// ```
+    pub fn check_wire_compatible(&self) -> Result<(), MassaSignatureError> {
+        if self.version == KEYPAIR_VERSION_ED25519 {
+            Ok(())
+        } else {
+            Err(MassaSignatureError::WireUnsupportedVersion(self.version))
+        }
     }
 
     /// Returns the Signature produced by signing
@@ -127,7 +213,19 @@ impl KeyPair {
     /// let signature = keypair.sign(&data).unwrap();
     /// ```
     pub fn sign(&self, hash: &Hash) -> Result<Signature, MassaSignatureError> {
-        Ok(Signature(self.0.sign(hash.to_bytes())))
+        match self.version {
+            KEYPAIR_VERSION_ED25519PH => self
+                .keypair
+                .sign_prehashed(ed25519ph_prehash(hash), Some(ED25519PH_CONTEXT))
+                .map(Signature)
+                .map_err(|err| {
+                    MassaSignatureError::SignatureError(format!(
+                        "Ed25519ph signing failed: {}",
+                        err
+                    ))
+                }),
+            _ => Ok(Signature(self.keypair.sign(hash.to_bytes()))),
+        }
     }
 
     /// Return the bytes representing the keypair (should be a reference in the future)
@@ -139,7 +237,7 @@ impl KeyPair {
     /// let bytes = keypair.to_bytes();
     /// ```
     pub fn to_bytes(&self) -> &[u8; SECRET_KEY_BYTES_SIZE] {
-        self.0.secret.as_bytes()
+        self.keypair.secret.as_bytes()
     }
 
     /// Return the bytes representing the keypair
@@ -151,10 +249,10 @@ impl KeyPair {
     /// let bytes = keypair.into_bytes();
     /// ```
     pub fn into_bytes(&self) -> [u8; SECRET_KEY_BYTES_SIZE] {
-        self.0.secret.to_bytes()
+        self.keypair.secret.to_bytes()
     }
 
-    /// Convert a byte array of size `SECRET_KEY_BYTES_SIZE` to a `KeyPair`
+    /// Convert a byte array of size `SECRET_KEY_BYTES_SIZE` to a `KeyPair` of version `KEYPAIR_VERSION_ED25519`
     ///
     /// # Example
     /// ```
@@ -164,13 +262,34 @@ impl KeyPair {
     /// let keypair2 = KeyPair::from_bytes(&bytes).unwrap();
     /// ```
     pub fn from_bytes(data: &[u8; SECRET_KEY_BYTES_SIZE]) -> Result<Self, MassaSignatureError> {
+        KeyPair::from_versioned_bytes(KEYPAIR_VERSION_ED25519, data)
+    }
+
+    /// Convert a byte array of size `SECRET_KEY_BYTES_SIZE` to a `KeyPair` of a given version
+    ///
+    /// # Example
+    /// ```
+    /// # use massa_signature::{KeyPair, KEYPAIR_VERSION_ED25519PH};
+    /// let keypair = KeyPair::generate_with_version(KEYPAIR_VERSION_ED25519PH).unwrap();
+    /// let bytes = keypair.into_bytes();
+    /// let keypair2 = KeyPair::from_versioned_bytes(KEYPAIR_VERSION_ED25519PH, &bytes).unwrap();
+    /// assert_eq!(keypair.get_public_key(), keypair2.get_public_key());
+    /// ```
+    pub fn from_versioned_bytes(
+        version: u64,
+        data: &[u8; SECRET_KEY_BYTES_SIZE],
+    ) -> Result<Self, MassaSignatureError> {
+        check_keypair_version(version)?;
         let secret = ed25519_dalek::SecretKey::from_bytes(&data[..]).map_err(|err| {
             MassaSignatureError::ParsingError(format!("keypair bytes parsing error: {}", err))
         })?;
-        Ok(KeyPair(ed25519_dalek::Keypair {
-            public: ed25519_dalek::PublicKey::from(&secret),
-            secret,
-        }))
+        Ok(KeyPair {
+            version,
+            keypair: ed25519_dalek::Keypair {
+                public: ed25519_dalek::PublicKey::from(&secret),
+                secret,
+            },
+        })
     }
 
     /// Get the public key of the keypair
@@ -182,10 +301,13 @@ impl KeyPair {
     /// let public_key = keypair.get_public_key();
     /// ```
     pub fn get_public_key(&self) -> PublicKey {
-        PublicKey(self.0.public)
+        PublicKey {
+            version: self.version,
+            public_key: self.keypair.public,
+        }
     }
 
-    /// Encode a keypair into his `base58` form
+    /// Encode a keypair into his `base58` form, without its version
     ///
     /// # Example
     /// ```
@@ -197,7 +319,7 @@ impl KeyPair {
         bs58::encode(self.to_bytes()).with_check().into_string()
     }
 
-    /// Decode a `base58` encoded keypair
+    /// Decode a `base58` encoded keypair, of version `KEYPAIR_VERSION_ED25519`
     ///
     /// # Example
     /// ```
@@ -363,42 +485,44 @@ impl<'de> ::serde::Deserialize<'de> for KeyPair {
 /// by the corresponding `PublicKey`.
 /// Generated from the `KeyPair` using `SignatureEngine`
 #[derive(Clone, Copy, PartialEq, Eq)]
-pub struct PublicKey(ed25519_dalek::PublicKey);
+pub struct PublicKey {
+    /// signature algorithm version
+    version: u64,
+    public_key: ed25519_dalek::PublicKey,
+}
 
 const PUBLIC_PREFIX: char = 'P';
 
 #[allow(clippy::derive_hash_xor_eq)]
 impl std::hash::Hash for PublicKey {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.0.as_bytes().hash(state);
+        self.version.hash(state);
+        self.public_key.as_bytes().hash(state);
     }
 }
 
 impl PartialOrd for PublicKey {
     fn partial_cmp(&self, other: &PublicKey) -> Option<Ordering> {
-        self.0.as_bytes().partial_cmp(other.0.as_bytes())
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for PublicKey {
     fn cmp(&self, other: &PublicKey) -> Ordering {
-        self.0.as_bytes().cmp(other.0.as_bytes())
+        (self.version, self.public_key.as_bytes())
+            .cmp(&(other.version, other.public_key.as_bytes()))
     }
 }
 
 impl std::fmt::Display for PublicKey {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let u64_serializer = U64VarIntSerializer::new();
-        let mut bytes = Vec::new();
-        u64_serializer
-            .serialize(&KEYPAIR_VERSION, &mut bytes)
-            .map_err(|_| std::fmt::Error)?;
-        bytes.extend(self.to_bytes());
         write!(
             f,
             "{}{}",
             PUBLIC_PREFIX,
-            bs58::encode(bytes).with_check().into_string()
+            bs58::encode(self.to_versioned_bytes())
+                .with_check()
+                .into_string()
         )
     }
 }
@@ -423,13 +547,15 @@ impl FromStr for PublicKey {
                         .map_err(|_| {
                             MassaSignatureError::ParsingError("Bad public key bs58".to_owned())
                         })?;
-                let u64_deserializer = U64VarIntDeserializer::new(Included(0), Included(u64::MAX));
-                let (rest, _version) = u64_deserializer
+                let (rest, public_key) = VersionedPublicKeyDeserializer::new()
                     .deserialize::<DeserializeError>(&decoded_bs58_check[..])
                     .map_err(|err| MassaSignatureError::ParsingError(err.to_string()))?;
-                PublicKey::from_bytes(&rest.try_into().map_err(|_| {
-                    MassaSignatureError::ParsingError("Public key not long enough".to_string())
-                })?)
+                if !rest.is_empty() {
+                    return Err(MassaSignatureError::ParsingError(
+                        "Public key too long".to_string(),
+                    ));
+                }
+                Ok(public_key)
             }
             _ => Err(MassaSignatureError::ParsingError(
                 "Bad public key prefix".to_owned(),
@@ -441,16 +567,54 @@ impl FromStr for PublicKey {
 impl PublicKey {
     /// Checks if the `Signature` associated with data bytes
     /// was produced with the `KeyPair` associated to given `PublicKey`
+    /// with the algorithm of its version
     pub fn verify_signature(
         &self,
         hash: &Hash,
         signature: &Signature,
     ) -> Result<(), MassaSignatureError> {
-        self.0.verify(hash.to_bytes(), &signature.0).map_err(|err| {
+        match self.version {
+            KEYPAIR_VERSION_ED25519PH => self.public_key.verify_prehashed(
+                ed25519ph_prehash(hash),
+                Some(ED25519PH_CONTEXT),
+                &signature.0,
+            ),
+            _ => self.public_key.verify(hash.to_bytes(), &signature.0),
+        }
+        .map_err(|err| {
             MassaSignatureError::SignatureError(format!("Signature verification failed: {}", err))
         })
     }
 
+    /// Get the version of the public key, giving its signature algorithm
+    pub fn get_version(&self) -> u64 {
+        self.version
+    }
+
+    /// Serialize a `PublicKey` as its version followed by its bytes,
+    /// as read by `VersionedPublicKeyDeserializer`.
+    ///
+    /// # Example
+    ///  ```
+    /// # use massa_signature::{KeyPair, VersionedPublicKeyDeserializer, KEYPAIR_VERSION_ED25519PH};
+    /// # use massa_serialization::{DeserializeError, Deserializer};
+    /// let public_key = KeyPair::generate_with_version(KEYPAIR_VERSION_ED25519PH).unwrap().get_public_key();
+    ///
+    /// let serialized = public_key.to_versioned_bytes();
+    /// let (rest, deserialized) = VersionedPublicKeyDeserializer::new().deserialize::<DeserializeError>(&serialized).unwrap();
+    /// assert!(rest.is_empty());
+    /// assert_eq!(public_key, deserialized);
+    /// ```
+    pub fn to_versioned_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(PUBLIC_KEY_SIZE_BYTES + 1);
+        // serializing a u64 varint can not fail
+        U64VarIntSerializer::new()
+            .serialize(&self.version, &mut bytes)
+            .expect("critical: public key version serialization failed");
+        bytes.extend(self.to_bytes());
+        bytes
+    }
+
     /// Serialize a `PublicKey` using `bs58` encoding with checksum.
     ///
     /// # Example
@@ -476,7 +640,7 @@ impl PublicKey {
     /// let serialize = keypair.get_public_key().to_bytes();
     /// ```
     pub fn to_bytes(&self) -> &[u8; PUBLIC_KEY_SIZE_BYTES] {
-        self.0.as_bytes()
+        self.public_key.as_bytes()
     }
 
     /// Serialize into bytes.
@@ -490,7 +654,7 @@ impl PublicKey {
     /// let serialize = keypair.get_public_key().to_bytes();
     /// ```
     pub fn into_bytes(self) -> [u8; PUBLIC_KEY_SIZE_BYTES] {
-        self.0.to_bytes()
+        self.public_key.to_bytes()
     }
 
     /// Deserialize a `PublicKey` using `bs58` encoding with checksum.
@@ -524,7 +688,7 @@ impl PublicKey {
             })
     }
 
-    /// Deserialize a `PublicKey` from bytes.
+    /// Deserialize a `PublicKey` of version `KEYPAIR_VERSION_ED25519` from bytes.
     ///
     /// # Example
     ///  ```
@@ -538,13 +702,25 @@ impl PublicKey {
     pub fn from_bytes(
         data: &[u8; PUBLIC_KEY_SIZE_BYTES],
     ) -> Result<PublicKey, MassaSignatureError> {
+        PublicKey::from_versioned_bytes(KEYPAIR_VERSION_ED25519, data)
+    }
+
+    /// Deserialize a `PublicKey` of a given version from bytes.
+    pub fn from_versioned_bytes(
+        version: u64,
+        data: &[u8; PUBLIC_KEY_SIZE_BYTES],
+    ) -> Result<PublicKey, MassaSignatureError> {
+        check_keypair_version(version)?;
         ed25519_dalek::PublicKey::from_bytes(data)
-            .map(Self)
+            .map(|public_key| PublicKey {
+                version,
+                public_key,
+            })
             .map_err(|err| MassaSignatureError::ParsingError(err.to_string()))
     }
 }
 
-/// Serializer for `Signature`
+/// Deserializer for the bytes of a `PublicKey` of version `KEYPAIR_VERSION_ED25519`
 #[derive(Default)]
 pub struct PublicKeyDeserializer;
 
@@ -597,6 +773,58 @@ impl Deserializer<PublicKey> for PublicKeyDeserializer {
     }
 }
 
+/// Deserializer for a `PublicKey` serialized with its version by `PublicKey::to_versioned_bytes`.
+/// Unknown versions are rejected.
+pub struct VersionedPublicKeyDeserializer {
+    version_deserializer: U64VarIntDeserializer,
+}
+
+impl VersionedPublicKeyDeserializer {
+    /// Creates a `VersionedPublicKeyDeserializer`
+    pub const fn new() -> Self {
+        Self {
+            version_deserializer: U64VarIntDeserializer::new(Included(0), Included(u64::MAX)),
+        }
+    }
+}
+
+impl Default for VersionedPublicKeyDeserializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deserializer<PublicKey> for VersionedPublicKeyDeserializer {
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], PublicKey, E> {
+        let (rest, version) = self.version_deserializer.deserialize(buffer)?;
+        if rest.len() < PUBLIC_KEY_SIZE_BYTES {
+            return Err(nom::Err::Error(ParseError::from_error_kind(
+                rest,
+                nom::error::ErrorKind::LengthValue,
+            )));
+        }
+        let key = PublicKey::from_versioned_bytes(
+            version,
+            rest[..PUBLIC_KEY_SIZE_BYTES].try_into().map_err(|_| {
+                nom::Err::Error(ParseError::from_error_kind(
+                    rest,
+                    nom::error::ErrorKind::LengthValue,
+                ))
+            })?,
+        )
+        .map_err(|_| {
+            nom::Err::Error(ParseError::from_error_kind(
+                buffer,
+                nom::error::ErrorKind::Fail,
+            ))
+        })?;
+        Ok((&rest[PUBLIC_KEY_SIZE_BYTES..], key))
+    }
+}
+
 impl ::serde::Serialize for PublicKey {
     /// `::serde::Serialize` trait for `PublicKey`
     /// if the serializer is human readable,
@@ -969,21 +1197,55 @@ pub fn verify_signature_batch(
         return public_key.verify_signature(&hash, &signature);
     }
 
-    // otherwise, use batch verif
-
+    // otherwise, use batch verif for the Ed25519 signatures,
+    // the other algorithms being verified one by one
     let mut hashes = Vec::with_capacity(batch.len());
     let mut signatures = Vec::with_capacity(batch.len());
     let mut public_keys = Vec::with_capacity(batch.len());
-    batch.iter().for_each(|(hash, signature, public_key)| {
-        hashes.push(hash.to_bytes().as_slice());
-        signatures.push(signature.0);
-        public_keys.push(public_key.0);
-    });
+    for (hash, signature, public_key) in batch.iter() {
+        if public_key.version == KEYPAIR_VERSION_ED25519 {
+            hashes.push(hash.to_bytes().as_slice());
+            signatures.push(signature.0);
+            public_keys.push(public_key.public_key);
+        } else {
+            public_key.verify_signature(hash, signature)?;
+        }
+    }
+    if hashes.is_empty() {
+        return Ok(());
+    }
     verify_batch(&hashes, signatures.as_slice(), public_keys.as_slice()).map_err(|err| {
         MassaSignatureError::SignatureError(format!("Batch signature verification failed: {}", err))
     })
 }
 
+/// Verify a batch of signatures, splitting it in chunks verified in parallel on all the cores
+/// when it has at least `parallel_threshold` signatures.
+/// Smaller batches are verified on a single core with `verify_signature_batch`,
+/// as the parallelization overhead would exceed its gain.
+///
+/// # Arguments
+/// * `batch`: a slice of triplets `(hash, signature, public_key)`
+/// * `parallel_threshold`: minimal size of a batch for it to be verified in parallel
+///
+/// # Return value
+/// Returns `Ok(())` if all signatures were successfully verified,
+/// and `Err(MassaSignatureError::SignatureError(_))` if at least one of them failed.
+pub fn verify_signature_batch_parallel(
+    batch: &[(Hash, Signature, PublicKey)],
+    parallel_threshold: usize,
+) -> Result<(), MassaSignatureError> {
+    if batch.len() < parallel_threshold.max(2) {
+        return verify_signature_batch(batch);
+    }
+
+    // one chunk per thread, each of them being batch verified
+    let chunk_size = std::cmp::max(1, batch.len() / rayon::current_num_threads());
+    batch
+        .par_chunks(chunk_size)
+        .try_for_each(verify_signature_batch)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1003,6 +1265,25 @@ mod tests {
             .is_ok())
     }
 
+    #[test]
+    #[serial]
+    fn test_verify_signature_batch_parallel() {
+        let mut batch: Vec<(Hash, Signature, PublicKey)> = (0u32..64)
+            .map(|i| {
+                let keypair = KeyPair::generate();
+                let hash = Hash::compute_from(&i.to_be_bytes());
+                (hash, keypair.sign(&hash).unwrap(), keypair.get_public_key())
+            })
+            .collect();
+        assert!(verify_signature_batch_parallel(&batch, 16).is_ok());
+        assert!(verify_signature_batch_parallel(&batch, 1000).is_ok());
+
+        // a single wrong signature fails the whole batch
+        batch[42].1 = batch[41].1;
+        assert!(verify_signature_batch_parallel(&batch, 16).is_err());
+        assert!(verify_signature_batch_parallel(&batch, 1000).is_err());
+    }
+
     #[test]
     #[serial]
     fn test_serde_keypair() {
@@ -1010,7 +1291,46 @@ mod tests {
         let serialized = serde_json::to_string(&keypair).expect("could not serialize keypair");
         let deserialized: KeyPair =
             serde_json::from_str(&serialized).expect("could not deserialize keypair");
-        assert_eq!(keypair.0.public, deserialized.0.public);
+        assert_eq!(keypair.keypair.public, deserialized.keypair.public);
+    }
+
+    #[test]
+    #[serial]
+    fn test_keypair_versions() {
+        let hash = Hash::compute_from("Hello World!".as_bytes());
+        let keypair = KeyPair::generate_with_version(KEYPAIR_VERSION_ED25519PH).unwrap();
+        let signature = keypair.sign(&hash).unwrap();
+        let public_key = keypair.get_public_key();
+        assert!(public_key.verify_signature(&hash, &signature).is_ok());
+
+        // the version is kept by the text forms
+        let parsed_keypair = KeyPair::from_str(&keypair.to_string()).unwrap();
+        assert_eq!(parsed_keypair.get_version(), KEYPAIR_VERSION_ED25519PH);
+        let parsed_public_key = PublicKey::from_str(&public_key.to_string()).unwrap();
+        assert_eq!(parsed_public_key, public_key);
+
+        // the same key bytes with another algorithm do not verify the signature
+        let ed25519_public_key = PublicKey::from_bytes(public_key.to_bytes()).unwrap();
+        assert_ne!(ed25519_public_key, public_key);
+        assert!(ed25519_public_key
+            .verify_signature(&hash, &signature)
+            .is_err());
+
+        // mixed batches verify each signature with the algorithm of its key
+        let ed25519_keypair = KeyPair::generate();
+        let batch = [
+            (hash, signature, public_key),
+            (
+                hash,
+                ed25519_keypair.sign(&hash).unwrap(),
+                ed25519_keypair.get_public_key(),
+            ),
+        ];
+        assert!(verify_signature_batch(&batch).is_ok());
+
+        // unknown versions are rejected
+        assert!(KeyPair::generate_with_version(42).is_err());
+        assert!(PublicKey::from_versioned_bytes(42, public_key.to_bytes()).is_err());
     }
 
     #[test]
@@ -107,6 +107,16 @@ impl BlockIndexes {
         None
     }
 
+    /// Returns the number of stored blocks
+    pub(crate) fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Iterates over the stored blocks
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &WrappedBlock> {
+        self.blocks.values()
+    }
+
     /// Get a block reference by its ID
     /// Arguments:
     /// - id: ID of the block to retrieve
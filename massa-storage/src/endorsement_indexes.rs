@@ -49,6 +49,16 @@ impl EndorsementIndexes {
         None
     }
 
+    /// Returns the number of stored endorsements
+    pub(crate) fn len(&self) -> usize {
+        self.endorsements.len()
+    }
+
+    /// Iterates over the stored endorsements
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &WrappedEndorsement> {
+        self.endorsements.values()
+    }
+
     /// Gets a reference to a stored endorsement, if any.
     pub fn get(&self, id: &EndorsementId) -> Option<&WrappedEndorsement> {
         self.endorsements.get(id)
@@ -0,0 +1,208 @@
+//! Copyright (c) 2022 MASSA LABS <info@massa.net>
+//!
+//! Introspection of the storage, to diagnose memory growth in long-running nodes:
+//! counts of the stored objects, of the references held by each owner,
+//! stale objects that are still referenced and objects released long after being stored.
+
+use massa_models::{
+    block::BlockId,
+    endorsement::EndorsementId,
+    operation::OperationId,
+    prehash::{PreHashMap, PreHashSet, PreHashed},
+};
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Owner tag of the `Storage` instances that were not tagged
+pub const UNTAGGED_OWNER: &str = "untagged";
+
+/// Numbers of objects of each kind
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StorageObjectCounts {
+    /// number of blocks
+    pub blocks: usize,
+    /// number of operations
+    pub operations: usize,
+    /// number of endorsements
+    pub endorsements: usize,
+}
+
+/// Snapshot of the content of the storage
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StorageStats {
+    /// number of stored objects
+    pub stored: StorageObjectCounts,
+    /// number of references held by the `Storage` instances of each owner tag
+    pub refs_by_owner: BTreeMap<&'static str, StorageObjectCounts>,
+}
+
+/// Objects that are still stored although they are older than the queried age
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StaleObjects {
+    /// blocks whose slot is too old
+    pub blocks: PreHashSet<BlockId>,
+    /// operations whose validity period ended too long ago
+    pub operations: PreHashSet<OperationId>,
+    /// endorsements whose slot is too old
+    pub endorsements: PreHashSet<EndorsementId>,
+}
+
+/// Kind of a stored object
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum ObjectKind {
+    Block,
+    Operation,
+    Endorsement,
+}
+
+/// Reference tracking shared by all the `Storage` instances cloned from the same root
+#[derive(Default)]
+pub(crate) struct RefTracking {
+    /// number of references held by each owner tag
+    refs_by_owner: HashMap<&'static str, StorageObjectCounts>,
+    /// objects released more than this duration after being stored are logged, disabled if None
+    late_drop_threshold: Option<Duration>,
+    /// instants at which the blocks were stored, when late drops are logged
+    block_store_instants: PreHashMap<BlockId, Instant>,
+    /// instants at which the operations were stored, when late drops are logged
+    operation_store_instants: PreHashMap<OperationId, Instant>,
+    /// instants at which the endorsements were stored, when late drops are logged
+    endorsement_store_instants: PreHashMap<EndorsementId, Instant>,
+}
+
+impl RefTracking {
+    /// Adds `delta` references of kind `kind` to the counts of `owner`
+    pub(crate) fn add_refs(&mut self, owner: &'static str, kind: ObjectKind, delta: isize) {
+        if delta == 0 {
+            return;
+        }
+        let counts = self.refs_by_owner.entry(owner).or_default();
+        let count = match kind {
+            ObjectKind::Block => &mut counts.blocks,
+            ObjectKind::Operation => &mut counts.operations,
+            ObjectKind::Endorsement => &mut counts.endorsements,
+        };
+        *count = if delta > 0 {
+            *count + delta as usize
+        } else {
+            count
+                .checked_sub(delta.unsigned_abs())
+                .expect("negative storage reference count for owner")
+        };
+        if *counts == StorageObjectCounts::default() {
+            self.refs_by_owner.remove(owner);
+        }
+    }
+
+    /// Gets the number of references held by each owner tag
+    pub(crate) fn get_refs_by_owner(&self) -> BTreeMap<&'static str, StorageObjectCounts> {
+        self.refs_by_owner
+            .iter()
+            .map(|(owner, counts)| (*owner, *counts))
+            .collect()
+    }
+
+    /// Enables or disables the logging of the objects released late
+    pub(crate) fn set_late_drop_threshold(&mut self, threshold: Option<Duration>) {
+        self.late_drop_threshold = threshold;
+        if threshold.is_none() {
+            self.block_store_instants.clear();
+            self.operation_store_instants.clear();
+            self.endorsement_store_instants.clear();
+        }
+    }
+
+    /// Records the instant at which blocks were stored, if late drops are logged
+    pub(crate) fn on_blocks_stored(&mut self, ids: impl Iterator<Item = BlockId>) {
+        if self.late_drop_threshold.is_some() {
+            let now = Instant::now();
+            for id in ids {
+                self.block_store_instants.entry(id).or_insert(now);
+            }
+        }
+    }
+
+    /// Records the instant at which operations were stored, if late drops are logged
+    pub(crate) fn on_operations_stored(&mut self, ids: impl Iterator<Item = OperationId>) {
+        if self.late_drop_threshold.is_some() {
+            let now = Instant::now();
+            for id in ids {
+                self.operation_store_instants.entry(id).or_insert(now);
+            }
+        }
+    }
+
+    /// Records the instant at which endorsements were stored, if late drops are logged
+    pub(crate) fn on_endorsements_stored(&mut self, ids: impl Iterator<Item = EndorsementId>) {
+        if self.late_drop_threshold.is_some() {
+            let now = Instant::now();
+            for id in ids {
+                self.endorsement_store_instants.entry(id).or_insert(now);
+            }
+        }
+    }
+
+    /// Logs the blocks released by `owner` too long after being stored
+    pub(crate) fn on_blocks_released(&mut self, owner: &'static str, ids: &[BlockId]) {
+        log_late_drops(
+            &mut self.block_store_instants,
+            self.late_drop_threshold,
+            owner,
+            "block",
+            ids,
+        );
+    }
+
+    /// Logs the operations released by `owner` too long after being stored
+    pub(crate) fn on_operations_released(&mut self, owner: &'static str, ids: &[OperationId]) {
+        log_late_drops(
+            &mut self.operation_store_instants,
+            self.late_drop_threshold,
+            owner,
+            "operation",
+            ids,
+        );
+    }
+
+    /// Logs the endorsements released by `owner` too long after being stored
+    pub(crate) fn on_endorsements_released(&mut self, owner: &'static str, ids: &[EndorsementId]) {
+        log_late_drops(
+            &mut self.endorsement_store_instants,
+            self.late_drop_threshold,
+            owner,
+            "endorsement",
+            ids,
+        );
+    }
+}
+
+/// Logs the objects released by `owner`, the last one referencing them,
+/// if they were stored more than `threshold` ago
+fn log_late_drops<IdT: PreHashed + Eq + std::hash::Hash + std::fmt::Display>(
+    store_instants: &mut PreHashMap<IdT, Instant>,
+    threshold: Option<Duration>,
+    owner: &'static str,
+    kind: &str,
+    ids: &[IdT],
+) {
+    let threshold = match threshold {
+        Some(threshold) => threshold,
+        None => return,
+    };
+    for id in ids {
+        let lifetime = match store_instants.remove(id) {
+            Some(stored) => stored.elapsed(),
+            None => continue,
+        };
+        if lifetime > threshold {
+            warn!(
+                "storage: {} {} released by {} {:.1}s after being stored",
+                kind,
+                id,
+                owner,
+                lifetime.as_secs_f64()
+            );
+        }
+    }
+}
@@ -12,6 +12,7 @@
 
 mod block_indexes;
 mod endorsement_indexes;
+mod introspection;
 mod operation_indexes;
 
 #[cfg(test)]
@@ -19,17 +20,21 @@ mod tests;
 
 use block_indexes::BlockIndexes;
 use endorsement_indexes::EndorsementIndexes;
+use introspection::{ObjectKind, RefTracking};
+pub use introspection::{StaleObjects, StorageObjectCounts, StorageStats, UNTAGGED_OWNER};
 use massa_models::prehash::{CapacityAllocator, PreHashMap, PreHashSet, PreHashed};
 use massa_models::wrapped::Id;
 use massa_models::{
     block::{BlockId, WrappedBlock},
     endorsement::{EndorsementId, WrappedEndorsement},
     operation::{OperationId, WrappedOperation},
+    slot::Slot,
 };
 use operation_indexes::OperationIndexes;
 use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::time::Duration;
 use std::{collections::hash_map, sync::Arc};
 
 /// A storage system for objects (blocks, operations...), shared by various components.
@@ -47,6 +52,11 @@ pub struct Storage {
     operation_owners: Arc<RwLock<PreHashMap<OperationId, usize>>>,
     /// global endorsement reference counter
     endorsement_owners: Arc<RwLock<PreHashMap<EndorsementId, usize>>>,
+    /// global reference tracking, for introspection
+    ref_tracking: Arc<RwLock<RefTracking>>,
+
+    /// tag of the module owning this instance, for introspection
+    owner: &'static str,
 
     /// locally used block references
     local_used_blocks: PreHashSet<BlockId>,
@@ -68,25 +78,28 @@ impl Clone for Storage {
         let mut res = Self::clone_without_refs(self);
 
         // claim one more user of the op refs
-        Storage::internal_claim_refs(
+        let claimed_ops = Storage::internal_claim_refs(
             &self.local_used_ops.clone(),
             &mut res.operation_owners.write(),
             &mut res.local_used_ops,
         );
+        res.track_claimed_refs(ObjectKind::Operation, claimed_ops);
 
         // claim one more user of the block refs
-        Storage::internal_claim_refs(
+        let claimed_blocks = Storage::internal_claim_refs(
             &self.local_used_blocks.clone(),
             &mut res.block_owners.write(),
             &mut res.local_used_blocks,
         );
+        res.track_claimed_refs(ObjectKind::Block, claimed_blocks);
 
         // claim one more user of the endorsement refs
-        Storage::internal_claim_refs(
+        let claimed_endorsements = Storage::internal_claim_refs(
             &self.local_used_endorsements.clone(),
             &mut res.endorsement_owners.write(),
             &mut res.local_used_endorsements,
         );
+        res.track_claimed_refs(ObjectKind::Endorsement, claimed_endorsements);
 
         res
     }
@@ -105,6 +118,8 @@ impl Storage {
             block_owners: Default::default(),
             operation_owners: Default::default(),
             endorsement_owners: Default::default(),
+            ref_tracking: Default::default(),
+            owner: UNTAGGED_OWNER,
             local_used_blocks: Default::default(),
             local_used_ops: Default::default(),
             local_used_endorsements: Default::default(),
@@ -120,6 +135,8 @@ impl Storage {
             operation_owners: self.operation_owners.clone(),
             block_owners: self.block_owners.clone(),
             endorsement_owners: self.endorsement_owners.clone(),
+            ref_tracking: self.ref_tracking.clone(),
+            owner: self.owner,
 
             // do not clone local ref lists
             local_used_ops: Default::default(),
@@ -132,26 +149,139 @@ impl Storage {
     pub fn extend(&mut self, mut other: Storage) {
         // Take ownership ot `other`'s references.
         // Objects owned by both require a counter decrement and are handled when `other` is dropped.
-        self.local_used_ops.extend(
-            &other
-                .local_used_ops
-                .drain_filter(|id| !self.local_used_ops.contains(id))
-                .collect::<Vec<_>>(),
-        );
+        let taken_ops = other
+            .local_used_ops
+            .drain_filter(|id| !self.local_used_ops.contains(id))
+            .collect::<Vec<_>>();
+        self.local_used_ops.extend(&taken_ops);
+
+        let taken_blocks = other
+            .local_used_blocks
+            .drain_filter(|id| !self.local_used_blocks.contains(id))
+            .collect::<Vec<_>>();
+        self.local_used_blocks.extend(&taken_blocks);
+
+        let taken_endorsements = other
+            .local_used_endorsements
+            .drain_filter(|id| !self.local_used_endorsements.contains(id))
+            .collect::<Vec<_>>();
+        self.local_used_endorsements.extend(&taken_endorsements);
+
+        // the taken references change owner
+        if self.owner != other.owner {
+            let mut tracking = self.ref_tracking.write();
+            for (kind, count) in [
+                (ObjectKind::Operation, taken_ops.len()),
+                (ObjectKind::Block, taken_blocks.len()),
+                (ObjectKind::Endorsement, taken_endorsements.len()),
+            ] {
+                tracking.add_refs(other.owner, kind, -(count as isize));
+                tracking.add_refs(self.owner, kind, count as isize);
+            }
+        }
+    }
 
-        self.local_used_blocks.extend(
-            &other
-                .local_used_blocks
-                .drain_filter(|id| !self.local_used_blocks.contains(id))
-                .collect::<Vec<_>>(),
-        );
+    /// Tags this instance, and the ones later cloned from it, as owned by `owner`,
+    /// so that their references are reported under that tag by `get_stats`
+    pub fn set_owner(&mut self, owner: &'static str) {
+        if owner == self.owner {
+            return;
+        }
+        let mut tracking = self.ref_tracking.write();
+        for (kind, count) in [
+            (ObjectKind::Operation, self.local_used_ops.len()),
+            (ObjectKind::Block, self.local_used_blocks.len()),
+            (ObjectKind::Endorsement, self.local_used_endorsements.len()),
+        ] {
+            tracking.add_refs(self.owner, kind, -(count as isize));
+            tracking.add_refs(owner, kind, count as isize);
+        }
+        self.owner = owner;
+    }
 
-        self.local_used_endorsements.extend(
-            &other
-                .local_used_endorsements
-                .drain_filter(|id| !self.local_used_endorsements.contains(id))
-                .collect::<Vec<_>>(),
-        );
+    /// Clones the object, with its references, for the module tagged `owner`
+    pub fn clone_for_owner(&self, owner: &'static str) -> Self {
+        let mut res = self.clone();
+        res.set_owner(owner);
+        res
+    }
+
+    /// Gets the owner tag of this instance
+    pub fn get_owner(&self) -> &'static str {
+        self.owner
+    }
+
+    /// Gets the numbers of stored objects and of references held by each owner tag
+    pub fn get_stats(&self) -> StorageStats {
+        let stored = StorageObjectCounts {
+            blocks: self.blocks.read().len(),
+            operations: self.operations.read().len(),
+            endorsements: self.endorsements.read().len(),
+        };
+        StorageStats {
+            stored,
+            refs_by_owner: self.ref_tracking.read().get_refs_by_owner(),
+        }
+    }
+
+    /// Gets the objects that are still stored although they are more than `max_age` slots older than `current_slot`:
+    /// blocks and endorsements by their slot, operations by the last slot of their validity period.
+    /// Such objects are usually references that some module forgot to drop.
+    pub fn get_stale_objects(
+        &self,
+        current_slot: Slot,
+        max_age: u64,
+        thread_count: u8,
+    ) -> StaleObjects {
+        let is_stale = |slot: Slot| {
+            current_slot
+                .slots_since(&slot, thread_count)
+                .map_or(false, |age| age > max_age)
+        };
+        StaleObjects {
+            blocks: self
+                .blocks
+                .read()
+                .iter()
+                .filter(|block| is_stale(block.content.header.content.slot))
+                .map(|block| block.id)
+                .collect(),
+            operations: self
+                .operations
+                .read()
+                .iter()
+                .filter(|op| {
+                    is_stale(Slot::new(
+                        op.content.expire_period,
+                        op.creator_address.get_thread(thread_count),
+                    ))
+                })
+                .map(|op| op.id)
+                .collect(),
+            endorsements: self
+                .endorsements
+                .read()
+                .iter()
+                .filter(|endorsement| is_stale(endorsement.content.slot))
+                .map(|endorsement| endorsement.id)
+                .collect(),
+        }
+    }
+
+    /// Logs the objects removed from the storage more than `threshold` after being stored, disabled if None.
+    /// Meant for debugging: it records the instant at which every object is stored.
+    /// Applies to all the instances cloned from the same root.
+    pub fn set_late_drop_threshold(&self, threshold: Option<Duration>) {
+        self.ref_tracking.write().set_late_drop_threshold(threshold);
+    }
+
+    /// internal helper to count newly claimed local references in the owner stats
+    fn track_claimed_refs(&self, kind: ObjectKind, count: usize) {
+        if count > 0 {
+            self.ref_tracking
+                .write()
+                .add_refs(self.owner, kind, count as isize);
+        }
     }
 
     /// Efficiently splits off a subset of the reference ownership into a new Storage object.
@@ -199,16 +329,20 @@ impl Storage {
     }
 
     /// internal helper to locally claim a reference to an object
+    /// Returns the number of references that were not already claimed locally.
     fn internal_claim_refs<IdT: Id + PartialEq + Eq + Hash + PreHashed + Copy>(
         ids: &PreHashSet<IdT>,
         owners: &mut RwLockWriteGuard<PreHashMap<IdT, usize>>,
         local_used_ids: &mut PreHashSet<IdT>,
-    ) {
+    ) -> usize {
+        let mut claimed_count = 0;
         for &id in ids {
             if local_used_ids.insert(id) {
                 owners.entry(id).and_modify(|v| *v += 1).or_insert(1);
+                claimed_count += 1;
             }
         }
+        claimed_count
     }
 
     /// get the block reference ownership
@@ -231,7 +365,9 @@ impl Storage {
         claimed.extend(ids.iter().filter(|id| owners.contains_key(id)));
 
         // effectively add local ownership on the refs
-        Storage::internal_claim_refs(&claimed, owners, &mut self.local_used_blocks);
+        let claimed_count =
+            Storage::internal_claim_refs(&claimed, owners, &mut self.local_used_blocks);
+        self.track_claimed_refs(ObjectKind::Block, claimed_count);
 
         claimed
     }
@@ -243,11 +379,13 @@ impl Storage {
         }
         let mut owners = self.block_owners.write();
         let mut orphaned_ids = Vec::new();
+        let mut released_count = 0;
         for id in ids {
             if !self.local_used_blocks.remove(id) {
                 // the object was already not referenced locally
                 continue;
             }
+            released_count += 1;
             match owners.entry(*id) {
                 hash_map::Entry::Occupied(mut occ) => {
                     let res_count = {
@@ -267,6 +405,11 @@ impl Storage {
                 }
             }
         }
+        {
+            let mut tracking = self.ref_tracking.write();
+            tracking.add_refs(self.owner, ObjectKind::Block, -released_count);
+            tracking.on_blocks_released(self.owner, &orphaned_ids);
+        }
         // if there are orphaned objects, remove them from storage
         if !orphaned_ids.is_empty() {
             let mut blocks = self.blocks.write();
@@ -284,11 +427,14 @@ impl Storage {
         let mut blocks = self.blocks.write();
         blocks.insert(block);
         // update local reference counters
-        Storage::internal_claim_refs(
+        let claimed_count = Storage::internal_claim_refs(
             &vec![id].into_iter().collect(),
             &mut owners,
             &mut self.local_used_blocks,
         );
+        let mut tracking = self.ref_tracking.write();
+        tracking.on_blocks_stored(std::iter::once(id));
+        tracking.add_refs(self.owner, ObjectKind::Block, claimed_count as isize);
     }
 
     /// Claim operation references.
@@ -309,7 +455,9 @@ impl Storage {
         claimed.extend(ids.iter().filter(|id| owners.contains_key(id)));
 
         // effectively add local ownership on the refs
-        Storage::internal_claim_refs(&claimed, owners, &mut self.local_used_ops);
+        let claimed_count =
+            Storage::internal_claim_refs(&claimed, owners, &mut self.local_used_ops);
+        self.track_claimed_refs(ObjectKind::Operation, claimed_count);
 
         claimed
     }
@@ -327,11 +475,13 @@ impl Storage {
         }
         let mut owners = self.operation_owners.write();
         let mut orphaned_ids = Vec::new();
+        let mut released_count = 0;
         for id in ids {
             if !self.local_used_ops.remove(id) {
                 // the object was already not referenced locally
                 continue;
             }
+            released_count += 1;
             match owners.entry(*id) {
                 hash_map::Entry::Occupied(mut occ) => {
                     let res_count = {
@@ -351,6 +501,11 @@ impl Storage {
                 }
             }
         }
+        {
+            let mut tracking = self.ref_tracking.write();
+            tracking.add_refs(self.owner, ObjectKind::Operation, -released_count);
+            tracking.on_operations_released(self.owner, &orphaned_ids);
+        }
         // if there are orphaned objects, remove them from storage
         if !orphaned_ids.is_empty() {
             let mut ops = self.operations.write();
@@ -372,7 +527,11 @@ impl Storage {
         for op in operations {
             op_store.insert(op);
         }
-        Storage::internal_claim_refs(&ids, &mut owners, &mut self.local_used_ops);
+        let claimed_count =
+            Storage::internal_claim_refs(&ids, &mut owners, &mut self.local_used_ops);
+        let mut tracking = self.ref_tracking.write();
+        tracking.on_operations_stored(ids.into_iter());
+        tracking.add_refs(self.owner, ObjectKind::Operation, claimed_count as isize);
     }
 
     /// Gets a read reference to the operations index
@@ -408,7 +567,9 @@ impl Storage {
         claimed.extend(ids.iter().filter(|id| owners.contains_key(id)));
 
         // effectively add local ownership on the refs
-        Storage::internal_claim_refs(&claimed, owners, &mut self.local_used_endorsements);
+        let claimed_count =
+            Storage::internal_claim_refs(&claimed, owners, &mut self.local_used_endorsements);
+        self.track_claimed_refs(ObjectKind::Endorsement, claimed_count);
         claimed
     }
 
@@ -425,11 +586,13 @@ impl Storage {
         }
         let mut owners = self.endorsement_owners.write();
         let mut orphaned_ids = Vec::new();
+        let mut released_count = 0;
         for id in ids {
             if !self.local_used_endorsements.remove(id) {
                 // the object was already not referenced locally
                 continue;
             }
+            released_count += 1;
             match owners.entry(*id) {
                 hash_map::Entry::Occupied(mut occ) => {
                     let res_count = {
@@ -449,6 +612,11 @@ impl Storage {
                 }
             }
         }
+        {
+            let mut tracking = self.ref_tracking.write();
+            tracking.add_refs(self.owner, ObjectKind::Endorsement, -released_count);
+            tracking.on_endorsements_released(self.owner, &orphaned_ids);
+        }
         // if there are orphaned objects, remove them from storage
         if !orphaned_ids.is_empty() {
             let mut endos = self.endorsements.write();
@@ -470,7 +638,11 @@ impl Storage {
         for endorsement in endorsements {
             endo_store.insert(endorsement);
         }
-        Storage::internal_claim_refs(&ids, &mut owners, &mut self.local_used_endorsements);
+        let claimed_count =
+            Storage::internal_claim_refs(&ids, &mut owners, &mut self.local_used_endorsements);
+        let mut tracking = self.ref_tracking.write();
+        tracking.on_endorsements_stored(ids.into_iter());
+        tracking.add_refs(self.owner, ObjectKind::Endorsement, claimed_count as isize);
     }
 }
 
@@ -63,6 +63,16 @@ impl OperationIndexes {
         None
     }
 
+    /// Returns the number of stored operations
+    pub(crate) fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Iterates over the stored operations
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &WrappedOperation> {
+        self.operations.values()
+    }
+
     /// Gets a reference to a stored operation, if any.
     pub fn get(&self, id: &OperationId) -> Option<&WrappedOperation> {
         self.operations.get(id)
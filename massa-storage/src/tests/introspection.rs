@@ -0,0 +1,56 @@
+use crate::{Storage, StorageObjectCounts, UNTAGGED_OWNER};
+use massa_factory_exports::test_exports::create_empty_block;
+use massa_models::{config::THREAD_COUNT, prehash::PreHashSet, slot::Slot};
+use massa_signature::KeyPair;
+
+#[test]
+/// Count the references held by each owner tag
+fn test_refs_by_owner() {
+    let mut storage = Storage::create_root();
+    let block = create_empty_block(&KeyPair::generate(), &Slot::new(0, 0));
+    storage.store_block(block.clone());
+
+    let mut pool_storage = storage.clone();
+    pool_storage.set_owner("pool");
+    let mut consensus_storage = storage.clone_without_refs();
+    consensus_storage.set_owner("consensus");
+    consensus_storage.extend(pool_storage.clone());
+
+    let stats = storage.get_stats();
+    assert_eq!(stats.stored.blocks, 1);
+    let one_block = StorageObjectCounts {
+        blocks: 1,
+        ..Default::default()
+    };
+    assert_eq!(stats.refs_by_owner.get(UNTAGGED_OWNER), Some(&one_block));
+    assert_eq!(stats.refs_by_owner.get("pool"), Some(&one_block));
+    assert_eq!(stats.refs_by_owner.get("consensus"), Some(&one_block));
+
+    drop(pool_storage);
+    let ids: PreHashSet<_> = std::iter::once(block.id).collect();
+    storage.drop_block_refs(&ids);
+    let stats = storage.get_stats();
+    assert_eq!(stats.stored.blocks, 1);
+    assert_eq!(stats.refs_by_owner.len(), 1);
+    assert_eq!(stats.refs_by_owner.get("consensus"), Some(&one_block));
+
+    drop(consensus_storage);
+    let stats = storage.get_stats();
+    assert_eq!(stats.stored.blocks, 0);
+    assert!(stats.refs_by_owner.is_empty());
+}
+
+#[test]
+/// Find the blocks kept long after their slot
+fn test_stale_objects() {
+    let mut storage = Storage::create_root();
+    let old_block = create_empty_block(&KeyPair::generate(), &Slot::new(1, 0));
+    let recent_block = create_empty_block(&KeyPair::generate(), &Slot::new(9, 0));
+    storage.store_block(old_block.clone());
+    storage.store_block(recent_block);
+
+    let stale = storage.get_stale_objects(Slot::new(10, 0), 2 * THREAD_COUNT as u64, THREAD_COUNT);
+    assert_eq!(stale.blocks, std::iter::once(old_block.id).collect());
+    assert!(stale.operations.is_empty());
+    assert!(stale.endorsements.is_empty());
+}
@@ -0,0 +1,96 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Clock compensation shared between the modules, and monotonic clock
+
+use crate::{MassaTime, TimeError};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Clock compensation in relative milliseconds, shared between the modules.
+///
+/// The compensation is set at bootstrap and may be adjusted while the node runs:
+/// all the clones of a `ClockCompensation` observe the adjustments.
+#[derive(Debug, Clone, Default)]
+pub struct ClockCompensation(Arc<AtomicI64>);
+
+impl ClockCompensation {
+    /// Creates a clock compensation of `millis` milliseconds
+    pub fn new(millis: i64) -> Self {
+        ClockCompensation(Arc::new(AtomicI64::new(millis)))
+    }
+
+    /// Gets the current compensation in milliseconds
+    pub fn get(&self) -> i64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Replaces the compensation by `millis` milliseconds
+    pub fn set(&self, millis: i64) {
+        self.0.store(millis, Ordering::Relaxed)
+    }
+
+    /// Current time, compensated
+    /// ```
+    /// # use massa_time::*;
+    /// let compensation = ClockCompensation::new(0);
+    /// let other = compensation.clone();
+    /// other.set(1000);
+    /// assert_eq!(compensation.get(), 1000);
+    /// assert!(compensation.now().unwrap() > MassaTime::now(0).unwrap());
+    /// ```
+    pub fn now(&self) -> Result<MassaTime, TimeError> {
+        MassaTime::now(self.get())
+    }
+}
+
+/// Clock that cannot go backwards nor jump, unlike the system time.
+///
+/// It is anchored on the system time at creation and then follows the monotonic clock of the
+/// operating system: comparing both reveals the adjustments of the system time.
+#[derive(Debug, Clone, Copy)]
+pub struct MonotonicClock {
+    anchor_instant: Instant,
+    anchor_time: MassaTime,
+}
+
+impl MonotonicClock {
+    /// Creates a monotonic clock anchored on the current system time
+    pub fn new() -> Result<Self, TimeError> {
+        Ok(MonotonicClock {
+            anchor_instant: Instant::now(),
+            anchor_time: MassaTime::now(0)?,
+        })
+    }
+
+    /// Current time according to the monotonic clock, not compensated
+    pub fn now(&self) -> Result<MassaTime, TimeError> {
+        self.anchor_time.checked_add(MassaTime::from_millis(
+            self.anchor_instant
+                .elapsed()
+                .as_millis()
+                .try_into()
+                .map_err(|_| TimeError::TimeOverflowError)?,
+        ))
+    }
+
+    /// Milliseconds by which the system time moved relatively to the monotonic clock since the anchor.
+    /// Positive if the system time jumped forward.
+    /// ```
+    /// # use massa_time::*;
+    /// let clock = MonotonicClock::new().unwrap();
+    /// assert!(clock.system_time_shift().unwrap().abs() < 1000);
+    /// ```
+    pub fn system_time_shift(&self) -> Result<i64, TimeError> {
+        let system: i64 = MassaTime::now(0)?
+            .to_millis()
+            .try_into()
+            .map_err(|_| TimeError::TimeOverflowError)?;
+        let monotonic: i64 = self
+            .now()?
+            .to_millis()
+            .try_into()
+            .map_err(|_| TimeError::TimeOverflowError)?;
+        Ok(system - monotonic)
+    }
+}
@@ -4,7 +4,9 @@
 #![warn(unused_crate_dependencies)]
 #![feature(bound_map)]
 
+mod clock;
 mod error;
+pub use clock::{ClockCompensation, MonotonicClock};
 pub use error::TimeError;
 use massa_serialization::{Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer};
 use nom::error::{context, ContextError, ParseError};
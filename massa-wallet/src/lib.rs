@@ -11,9 +11,8 @@ use massa_cipher::{decrypt, encrypt};
 use massa_hash::Hash;
 use massa_models::address::Address;
 use massa_models::composite::PubkeySig;
-use massa_models::operation::{Operation, OperationSerializer, WrappedOperation};
+use massa_models::operation::{Operation, WrappedOperation};
 use massa_models::prehash::{PreHashMap, PreHashSet};
-use massa_models::wrapped::WrappedContent;
 use massa_signature::{KeyPair, PublicKey};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -39,6 +38,9 @@ impl Wallet {
             let (_version, decrypted_content) = decrypt(&password, content)?;
             let keys =
                 serde_json::from_slice::<PreHashMap<Address, KeyPair>>(&decrypted_content[..])?;
+            for keypair in keys.values() {
+                keypair.check_wire_compatible()?;
+            }
             Ok(Wallet {
                 keys,
                 wallet_path: path,
@@ -59,8 +61,15 @@ impl Wallet {
     /// returns none if the address isn't in the wallet or if an error occurred during the signature
     /// else returns the public key that signed the message and the signature
     pub fn sign_message(&self, address: &Address, msg: Vec<u8>) -> Option<PubkeySig> {
+        self.sign_hash(address, &Hash::compute_from(&msg))
+    }
+
+    /// Sign the hash of arbitrary data with the associated keypair, for data too large to be hashed in memory.
+    /// Signing the hash of a message gives the same signature as `sign_message` on the message.
+    /// returns none if the address isn't in the wallet or if an error occurred during the signature
+    pub fn sign_hash(&self, address: &Address, hash: &Hash) -> Option<PubkeySig> {
         if let Some(key) = self.keys.get(address) {
-            if let Ok(signature) = key.sign(&Hash::compute_from(&msg)) {
+            if let Ok(signature) = key.sign(hash) {
                 Some(PubkeySig {
                     public_key: key.get_public_key(),
                     signature,
@@ -75,7 +84,11 @@ impl Wallet {
 
     /// Adds a list of keypairs to the wallet, returns their addresses.
     /// The wallet file is updated.
+    /// Fails without adding any keypair if one of them can not sign operations and blocks.
     pub fn add_keypairs(&mut self, keys: Vec<KeyPair>) -> Result<Vec<Address>, WalletError> {
+        for key in &keys {
+            key.check_wire_compatible()?;
+        }
         let mut changed = false;
         let mut addrs = Vec::with_capacity(keys.len());
         for key in keys {
@@ -137,16 +150,25 @@ impl Wallet {
         &self.keys
     }
 
-    /// Signs an operation with the keypair corresponding to the given address
+    /// Signs an operation with the keypair corresponding to the given address,
+    /// for the network of `chain_id`, see `compute_operation_signed_hash`
     pub fn create_operation(
         &self,
         content: Operation,
         address: Address,
+        chain_id: u64,
+        chain_id_activation_period: u64,
     ) -> Result<WrappedOperation, WalletError> {
         let sender_keypair = self
             .find_associated_keypair(&address)
             .ok_or_else(|| WalletError::MissingKeyError(address))?;
-        Ok(Operation::new_wrapped(content, OperationSerializer::new(), sender_keypair).unwrap())
+        Ok(Operation::new_signed(
+            content,
+            sender_keypair,
+            chain_id,
+            chain_id_activation_period,
+        )
+        .unwrap())
     }
 }
 